@@ -1,10 +1,17 @@
 use crate::directories::*;
 use crate::EditorConfig;
 use crate::ScrollConfig;
+use crate::{default_formatters, parse_formatters, Formatter, FormattersMapping};
+use crate::{
+    default_language_servers, parse_language_servers, LanguageServer, LanguageServersMapping,
+};
+use crate::{parse_snippets, Snippet, SnippetsMapping};
+use rider_lexers::grammar::{self, Grammar};
 use rider_lexers::Language;
 use rider_themes::Theme;
 use std::collections::HashMap;
 use std::fs;
+use std::path::Path;
 
 pub type LanguageMapping = HashMap<String, Language>;
 
@@ -18,6 +25,9 @@ pub struct Config {
     extensions_mapping: LanguageMapping,
     scroll: ScrollConfig,
     directories: Directories,
+    snippets: SnippetsMapping,
+    language_servers: LanguageServersMapping,
+    formatters: FormattersMapping,
 }
 
 impl Config {
@@ -29,6 +39,39 @@ impl Config {
         extensions_mapping.insert("txt".to_string(), Language::PlainText);
         extensions_mapping.insert("rs".to_string(), Language::Rust);
         extensions_mapping.insert("toml".to_string(), Language::Toml);
+        extensions_mapping.insert("md".to_string(), Language::Markdown);
+        extensions_mapping.insert("markdown".to_string(), Language::Markdown);
+        extensions_mapping.insert("json".to_string(), Language::Json);
+        extensions_mapping.insert("yaml".to_string(), Language::Yaml);
+        extensions_mapping.insert("yml".to_string(), Language::Yaml);
+        extensions_mapping.insert("py".to_string(), Language::Python);
+        extensions_mapping.insert("pyi".to_string(), Language::Python);
+        extensions_mapping.insert("js".to_string(), Language::JavaScript);
+        extensions_mapping.insert("mjs".to_string(), Language::JavaScript);
+        extensions_mapping.insert("cjs".to_string(), Language::JavaScript);
+        extensions_mapping.insert("jsx".to_string(), Language::JavaScript);
+        extensions_mapping.insert("ts".to_string(), Language::TypeScript);
+        extensions_mapping.insert("tsx".to_string(), Language::TypeScript);
+        extensions_mapping.insert("c".to_string(), Language::C);
+        extensions_mapping.insert("h".to_string(), Language::C);
+        extensions_mapping.insert("cpp".to_string(), Language::Cpp);
+        extensions_mapping.insert("cc".to_string(), Language::Cpp);
+        extensions_mapping.insert("cxx".to_string(), Language::Cpp);
+        extensions_mapping.insert("hpp".to_string(), Language::Cpp);
+        extensions_mapping.insert("hh".to_string(), Language::Cpp);
+        extensions_mapping.insert("hxx".to_string(), Language::Cpp);
+        extensions_mapping.insert("sh".to_string(), Language::Shell);
+        extensions_mapping.insert("bash".to_string(), Language::Shell);
+        extensions_mapping.insert("zsh".to_string(), Language::Shell);
+        extensions_mapping.insert("mk".to_string(), Language::Makefile);
+        extensions_mapping.insert("mak".to_string(), Language::Makefile);
+        extensions_mapping.insert("Makefile".to_string(), Language::Makefile);
+        extensions_mapping.insert("makefile".to_string(), Language::Makefile);
+        extensions_mapping.insert("GNUmakefile".to_string(), Language::Makefile);
+        extensions_mapping.insert("Dockerfile".to_string(), Language::Dockerfile);
+        extensions_mapping.insert("dockerfile".to_string(), Language::Dockerfile);
+        extensions_mapping.insert("diff".to_string(), Language::Diff);
+        extensions_mapping.insert("patch".to_string(), Language::Diff);
 
         Self {
             width: 1024,
@@ -39,6 +82,9 @@ impl Config {
             extensions_mapping,
             scroll: ScrollConfig::new(),
             directories,
+            snippets: SnippetsMapping::new(),
+            language_servers: default_language_servers(),
+            formatters: default_formatters(),
         }
     }
 
@@ -94,6 +140,37 @@ impl Config {
         &self.directories
     }
 
+    pub fn snippets(&self, language: Language) -> &[Snippet] {
+        self.snippets
+            .get(&language)
+            .map(|snippets| snippets.as_slice())
+            .unwrap_or(&[])
+    }
+
+    pub fn language_server(&self, language: Language) -> Option<&LanguageServer> {
+        self.language_servers.get(&language)
+    }
+
+    pub fn language_servers_mut(&mut self) -> &mut LanguageServersMapping {
+        &mut self.language_servers
+    }
+
+    pub fn set_language_server(&mut self, language: Language, server: LanguageServer) {
+        self.language_servers.insert(language, server);
+    }
+
+    pub fn formatter(&self, language: Language) -> Option<&Formatter> {
+        self.formatters.get(&language)
+    }
+
+    pub fn formatters_mut(&mut self) -> &mut FormattersMapping {
+        &mut self.formatters
+    }
+
+    pub fn set_formatter(&mut self, language: Language, formatter: Formatter) {
+        self.formatters.insert(language, formatter);
+    }
+
     pub fn set_theme(&mut self, theme: String) {
         self.theme = self.load_theme(theme);
     }
@@ -113,6 +190,120 @@ impl Config {
         let contents = fs::read_to_string(&config_file.join(file_name)).unwrap_or_default();
         serde_json::from_str(&contents).unwrap_or_default()
     }
+
+    /// Registers grammars from syntaxes directory, returns errors for files which failed to load
+    pub fn load_syntaxes(&mut self) -> Vec<String> {
+        let syntaxes_dir = self.directories.syntaxes_dir.clone();
+        self.load_syntaxes_from(&syntaxes_dir)
+    }
+
+    fn load_syntaxes_from(&mut self, dir: &Path) -> Vec<String> {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            _ => return vec![],
+        };
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().map_or(false, |ext| ext == "json"))
+            .collect();
+        paths.sort();
+
+        let mut errors = vec![];
+        for path in paths {
+            let grammar = fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|content| Grammar::from_json(&content).map_err(|e| e.to_string()));
+            match grammar {
+                Ok(grammar) => {
+                    let extensions = grammar.extensions().clone();
+                    let language = grammar::register(grammar);
+                    for ext in extensions {
+                        self.extensions_mapping.insert(ext, language);
+                    }
+                }
+                Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+            }
+        }
+        errors
+    }
+
+    /// Loads `<language>.json` files from snippets directory, returns errors for files which failed to load
+    pub fn load_snippets(&mut self) -> Vec<String> {
+        let snippets_dir = self.directories.snippets_dir.clone();
+        self.load_snippets_from(&snippets_dir)
+    }
+
+    /// Overrides default language servers with `language_servers.json` from config directory
+    pub fn load_language_servers(&mut self) -> Result<(), String> {
+        let path = self.directories.config_dir.join("language_servers.json");
+        self.load_language_servers_from(&path)
+    }
+
+    fn load_language_servers_from(&mut self, path: &Path) -> Result<(), String> {
+        if !path.exists() {
+            return Ok(());
+        }
+        let servers = fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| parse_language_servers(&content))
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        self.language_servers.extend(servers);
+        Ok(())
+    }
+
+    /// Overrides default formatters with `formatters.json` from config directory
+    pub fn load_formatters(&mut self) -> Result<(), String> {
+        let path = self.directories.config_dir.join("formatters.json");
+        self.load_formatters_from(&path)
+    }
+
+    fn load_formatters_from(&mut self, path: &Path) -> Result<(), String> {
+        if !path.exists() {
+            return Ok(());
+        }
+        let formatters = fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| parse_formatters(&content))
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        self.formatters.extend(formatters);
+        Ok(())
+    }
+
+    fn load_snippets_from(&mut self, dir: &Path) -> Vec<String> {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            _ => return vec![],
+        };
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().map_or(false, |ext| ext == "json"))
+            .collect();
+        paths.sort();
+
+        let mut errors = vec![];
+        for path in paths {
+            let language = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(Language::from_name)
+                .ok_or_else(|| "unknown language".to_string());
+            let snippets = language.and_then(|language| {
+                fs::read_to_string(&path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|content| parse_snippets(&content))
+                    .map(|snippets| (language, snippets))
+            });
+            match snippets {
+                Ok((language, snippets)) => self
+                    .snippets
+                    .entry(language)
+                    .or_insert_with(Vec::new)
+                    .extend(snippets),
+                Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+            }
+        }
+        errors
+    }
 }
 
 #[cfg(test)]
@@ -131,6 +322,39 @@ mod tests {
                 "txt".to_string(),
                 "rs".to_string(),
                 "toml".to_string(),
+                "md".to_string(),
+                "markdown".to_string(),
+                "json".to_string(),
+                "yaml".to_string(),
+                "yml".to_string(),
+                "py".to_string(),
+                "pyi".to_string(),
+                "js".to_string(),
+                "mjs".to_string(),
+                "cjs".to_string(),
+                "jsx".to_string(),
+                "ts".to_string(),
+                "tsx".to_string(),
+                "c".to_string(),
+                "h".to_string(),
+                "cpp".to_string(),
+                "cc".to_string(),
+                "cxx".to_string(),
+                "hpp".to_string(),
+                "hh".to_string(),
+                "hxx".to_string(),
+                "sh".to_string(),
+                "bash".to_string(),
+                "zsh".to_string(),
+                "mk".to_string(),
+                "mak".to_string(),
+                "Makefile".to_string(),
+                "makefile".to_string(),
+                "GNUmakefile".to_string(),
+                "Dockerfile".to_string(),
+                "dockerfile".to_string(),
+                "diff".to_string(),
+                "patch".to_string(),
             ];
             keys.sort();
             expected.sort();
@@ -143,6 +367,39 @@ mod tests {
                 Language::PlainText,
                 Language::Rust,
                 Language::Toml,
+                Language::Markdown,
+                Language::Markdown,
+                Language::Json,
+                Language::Yaml,
+                Language::Yaml,
+                Language::Python,
+                Language::Python,
+                Language::JavaScript,
+                Language::JavaScript,
+                Language::JavaScript,
+                Language::JavaScript,
+                Language::TypeScript,
+                Language::TypeScript,
+                Language::C,
+                Language::C,
+                Language::Cpp,
+                Language::Cpp,
+                Language::Cpp,
+                Language::Cpp,
+                Language::Cpp,
+                Language::Cpp,
+                Language::Shell,
+                Language::Shell,
+                Language::Shell,
+                Language::Makefile,
+                Language::Makefile,
+                Language::Makefile,
+                Language::Makefile,
+                Language::Makefile,
+                Language::Dockerfile,
+                Language::Dockerfile,
+                Language::Diff,
+                Language::Diff,
             ];
             keys.sort();
             expected.sort();
@@ -243,8 +500,157 @@ mod test_getters {
             "txt".to_string(),
             ".".to_string(),
             "toml".to_string(),
+            "md".to_string(),
+            "markdown".to_string(),
+            "json".to_string(),
+            "yaml".to_string(),
+            "yml".to_string(),
+            "py".to_string(),
+            "pyi".to_string(),
+            "js".to_string(),
+            "mjs".to_string(),
+            "cjs".to_string(),
+            "jsx".to_string(),
+            "ts".to_string(),
+            "tsx".to_string(),
+            "c".to_string(),
+            "h".to_string(),
+            "cpp".to_string(),
+            "cc".to_string(),
+            "cxx".to_string(),
+            "hpp".to_string(),
+            "hh".to_string(),
+            "hxx".to_string(),
+            "sh".to_string(),
+            "bash".to_string(),
+            "zsh".to_string(),
+            "mk".to_string(),
+            "mak".to_string(),
+            "Makefile".to_string(),
+            "makefile".to_string(),
+            "GNUmakefile".to_string(),
+            "Dockerfile".to_string(),
+            "dockerfile".to_string(),
+            "diff".to_string(),
+            "patch".to_string(),
         ];
         expected.sort();
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn must_load_syntaxes_and_report_errors() {
+        let dir = std::env::temp_dir().join(format!("rider-syntaxes-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("ini.json"),
+            r#"{ "name": "config-ini", "extensions": ["ini"], "states": {
+                "main": [{ "match": ";.*", "token": "comment" }]
+            } }"#,
+        )
+        .unwrap();
+        fs::write(dir.join("broken.json"), r#"{ "name": "broken" }"#).unwrap();
+
+        let mut config = Config::new();
+        let errors = config.load_syntaxes_from(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].contains("broken.json"), true);
+        assert_eq!(
+            config.extensions_mapping().get("ini").cloned(),
+            Language::from_name("config-ini")
+        );
+    }
+
+    #[test]
+    fn must_load_snippets_and_report_errors() {
+        let dir = std::env::temp_dir().join(format!("rider-snippets-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("rust.json"),
+            r#"{ "fn": { "body": "fn $1() {}", "description": "function" } }"#,
+        )
+        .unwrap();
+        fs::write(dir.join("unknown-language.json"), "{}").unwrap();
+        fs::write(dir.join("python.json"), r#"{ "def": {} }"#).unwrap();
+
+        let mut config = Config::new();
+        let errors = config.load_snippets_from(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].contains("python.json"), true);
+        assert_eq!(errors[1].contains("unknown-language.json"), true);
+        let prefixes: Vec<&str> = config
+            .snippets(Language::Rust)
+            .iter()
+            .map(|s| s.prefix.as_str())
+            .collect();
+        assert_eq!(prefixes, vec!["fn"]);
+        assert_eq!(config.snippets(Language::Python).is_empty(), true);
+    }
+
+    #[test]
+    fn must_load_language_servers() {
+        let path = std::env::temp_dir().join(format!("rider-servers-{}.json", std::process::id()));
+        let mut config = Config::new();
+        assert_eq!(config.load_language_servers_from(&path), Ok(()));
+        assert_eq!(
+            config.language_server(Language::Rust),
+            Some(&LanguageServer::new("rust-analyzer", "rust"))
+        );
+
+        fs::write(&path, r#"{ "python": { "command": "pyls" } }"#).unwrap();
+        let result = config.load_language_servers_from(&path);
+        fs::write(&path, "[]").unwrap();
+        let error = config.load_language_servers_from(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(error.is_err(), true);
+        assert_eq!(
+            config.language_server(Language::Python),
+            Some(&LanguageServer::new("pyls", "python"))
+        );
+        assert_eq!(config.language_server(Language::Toml), None);
+    }
+
+    #[test]
+    fn must_load_formatters() {
+        let path =
+            std::env::temp_dir().join(format!("rider-formatters-{}.json", std::process::id()));
+        let mut config = Config::new();
+        assert_eq!(config.load_formatters_from(&path), Ok(()));
+        assert_eq!(
+            config.formatter(Language::Rust),
+            Some(&Formatter::new("rustfmt", &["--emit", "stdout"]))
+        );
+
+        fs::write(
+            &path,
+            r#"{ "rust": { "command": "fmt", "onSave": false } }"#,
+        )
+        .unwrap();
+        let result = config.load_formatters_from(&path);
+        fs::write(&path, "{ \"rust\": 1 }").unwrap();
+        let error = config.load_formatters_from(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(error.is_err(), true);
+        assert_eq!(
+            config.formatter(Language::Rust),
+            Some(&Formatter {
+                command: "fmt".to_owned(),
+                args: vec![],
+                on_save: false,
+            })
+        );
+        assert_eq!(
+            config.formatter(Language::Toml),
+            Some(&Formatter::new("taplo", &["fmt", "-"]))
+        );
+        assert_eq!(config.formatter(Language::Python), None);
+    }
 }
@@ -7,6 +7,8 @@ pub struct Directories {
     pub log_dir: PathBuf,
     pub themes_dir: PathBuf,
     pub fonts_dir: PathBuf,
+    pub syntaxes_dir: PathBuf,
+    pub snippets_dir: PathBuf,
     pub config_dir: PathBuf,
     pub project_dir: PathBuf,
 }
@@ -31,6 +33,8 @@ impl Directories {
             log_dir: log_dir(&config_dir),
             themes_dir: themes_dir(&config_dir),
             fonts_dir: fonts_dir(&config_dir),
+            syntaxes_dir: syntaxes_dir(&config_dir),
+            snippets_dir: snippets_dir(&config_dir),
             config_dir,
             project_dir,
         }
@@ -57,6 +61,14 @@ pub fn fonts_dir(config_dir: &PathBuf) -> PathBuf {
     PathBuf::from(config_dir.to_str().unwrap().to_owned()).join("fonts")
 }
 
+pub fn syntaxes_dir(config_dir: &PathBuf) -> PathBuf {
+    PathBuf::from(config_dir.to_str().unwrap().to_owned()).join("syntaxes")
+}
+
+pub fn snippets_dir(config_dir: &PathBuf) -> PathBuf {
+    PathBuf::from(config_dir.to_str().unwrap().to_owned()).join("snippets")
+}
+
 #[cfg_attr(tarpaulin, skip)]
 pub fn project_dir() -> PathBuf {
     PathBuf::from(dirs::runtime_dir().unwrap().to_str().unwrap().to_owned()).join(".rider")
@@ -162,6 +174,22 @@ mod tests {
         assert_eq!(path, expected);
     }
 
+    #[test]
+    fn assert_syntaxes_dir() {
+        let directories = Directories::new(Some("/tmp".to_owned()), None);
+        let path = directories.syntaxes_dir.clone();
+        let expected: PathBuf = Path::new("/tmp/rider/syntaxes").into();
+        assert_eq!(path, expected);
+    }
+
+    #[test]
+    fn assert_snippets_dir() {
+        let directories = Directories::new(Some("/tmp".to_owned()), None);
+        let path = directories.snippets_dir.clone();
+        let expected: PathBuf = Path::new("/tmp/rider/snippets").into();
+        assert_eq!(path, expected);
+    }
+
     #[test]
     fn assert_config_dir() {
         let directories = Directories::new(Some("/tmp".to_owned()), None);
@@ -0,0 +1,128 @@
+use rider_lexers::Language;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+pub type FormattersMapping = BTreeMap<Language, Formatter>;
+
+/// Command which reads source from stdin and writes formatted source to stdout
+#[derive(Debug, Clone, PartialEq)]
+pub struct Formatter {
+    pub command: String,
+    pub args: Vec<String>,
+    pub on_save: bool,
+}
+
+impl Formatter {
+    pub fn new(command: &str, args: &[&str]) -> Self {
+        Self {
+            command: command.to_owned(),
+            args: args.iter().map(|arg| (*arg).to_owned()).collect(),
+            on_save: true,
+        }
+    }
+}
+
+pub fn default_formatters() -> FormattersMapping {
+    let mut formatters = FormattersMapping::new();
+    formatters.insert(
+        Language::Rust,
+        Formatter::new("rustfmt", &["--emit", "stdout"]),
+    );
+    formatters.insert(Language::Toml, Formatter::new("taplo", &["fmt", "-"]));
+    formatters
+}
+
+/// Formatters file content, key is language name, for example:
+///
+/// ```json
+/// {
+///   "rust": { "command": "rustfmt", "args": ["--emit", "stdout", "--edition", "2018"] },
+///   "python": { "command": "black", "args": ["-q", "-"], "onSave": false }
+/// }
+/// ```
+pub fn parse_formatters(content: &str) -> Result<Vec<(Language, Formatter)>, String> {
+    let value: Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let definitions = value
+        .as_object()
+        .ok_or_else(|| "Formatters must be an object".to_string())?;
+    let mut formatters = vec![];
+    for (name, definition) in definitions.iter() {
+        let language =
+            Language::from_name(name).ok_or_else(|| format!("Unknown language {}", name))?;
+        let command = definition
+            .get("command")
+            .and_then(|command| command.as_str())
+            .ok_or_else(|| format!("Formatter for {} has no command", name))?
+            .to_owned();
+        let args = match definition.get("args") {
+            None => vec![],
+            Some(Value::Array(args)) => args
+                .iter()
+                .map(|arg| arg.as_str().map(|s| s.to_owned()))
+                .collect::<Option<Vec<String>>>()
+                .ok_or_else(|| format!("Formatter for {} has invalid args", name))?,
+            _ => return Err(format!("Formatter for {} has invalid args", name)),
+        };
+        let on_save = match definition.get("onSave") {
+            None => true,
+            Some(Value::Bool(on_save)) => *on_save,
+            _ => return Err(format!("Formatter for {} has invalid onSave", name)),
+        };
+        formatters.push((
+            language,
+            Formatter {
+                command,
+                args,
+                on_save,
+            },
+        ));
+    }
+    Ok(formatters)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn must_parse_formatters() {
+        let result = parse_formatters(
+            r#"{
+                "python": { "command": "black", "args": ["-q", "-"], "onSave": false },
+                "toml": { "command": "taplo", "args": ["fmt", "-"] }
+            }"#,
+        );
+        assert_eq!(
+            result,
+            Ok(vec![
+                (
+                    Language::Python,
+                    Formatter {
+                        command: "black".to_owned(),
+                        args: vec!["-q".to_owned(), "-".to_owned()],
+                        on_save: false,
+                    }
+                ),
+                (Language::Toml, Formatter::new("taplo", &["fmt", "-"])),
+            ])
+        );
+    }
+
+    #[test]
+    fn must_fail_for_invalid_formatters() {
+        assert_eq!(parse_formatters("[]").is_err(), true);
+        assert_eq!(parse_formatters(r#"{ "rust": {} }"#).is_err(), true);
+        assert_eq!(
+            parse_formatters(r#"{ "nope": { "command": "a" } }"#).is_err(),
+            true
+        );
+        assert_eq!(
+            parse_formatters(r#"{ "rust": { "command": "a", "args": "b" } }"#).is_err(),
+            true
+        );
+        assert_eq!(
+            parse_formatters(r#"{ "rust": { "command": "a", "onSave": "yes" } }"#).is_err(),
+            true
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use rider_lexers::Language;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+pub type LanguageServersMapping = BTreeMap<Language, LanguageServer>;
+
+/// Command launching language server which speaks LSP over stdio
+#[derive(Debug, Clone, PartialEq)]
+pub struct LanguageServer {
+    pub command: String,
+    pub args: Vec<String>,
+    pub language_id: String,
+}
+
+impl LanguageServer {
+    pub fn new(command: &str, language_id: &str) -> Self {
+        Self {
+            command: command.to_owned(),
+            args: vec![],
+            language_id: language_id.to_owned(),
+        }
+    }
+}
+
+pub fn default_language_servers() -> LanguageServersMapping {
+    let mut servers = LanguageServersMapping::new();
+    servers.insert(Language::Rust, LanguageServer::new("rust-analyzer", "rust"));
+    servers
+}
+
+/// Language servers file content, key is language name, for example:
+///
+/// ```json
+/// {
+///   "rust": { "command": "rust-analyzer" },
+///   "python": { "command": "pyls", "args": ["-v"], "languageId": "python" }
+/// }
+/// ```
+pub fn parse_language_servers(content: &str) -> Result<Vec<(Language, LanguageServer)>, String> {
+    let value: Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let definitions = value
+        .as_object()
+        .ok_or_else(|| "Language servers must be an object".to_string())?;
+    let mut servers = vec![];
+    for (name, definition) in definitions.iter() {
+        let language =
+            Language::from_name(name).ok_or_else(|| format!("Unknown language {}", name))?;
+        let command = definition
+            .get("command")
+            .and_then(|command| command.as_str())
+            .ok_or_else(|| format!("Language server for {} has no command", name))?
+            .to_owned();
+        let args = match definition.get("args") {
+            None => vec![],
+            Some(Value::Array(args)) => args
+                .iter()
+                .map(|arg| arg.as_str().map(|s| s.to_owned()))
+                .collect::<Option<Vec<String>>>()
+                .ok_or_else(|| format!("Language server for {} has invalid args", name))?,
+            _ => return Err(format!("Language server for {} has invalid args", name)),
+        };
+        let language_id = definition
+            .get("languageId")
+            .and_then(|id| id.as_str())
+            .unwrap_or(name.as_str())
+            .to_owned();
+        servers.push((
+            language,
+            LanguageServer {
+                command,
+                args,
+                language_id,
+            },
+        ));
+    }
+    Ok(servers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn must_parse_language_servers() {
+        let result = parse_language_servers(
+            r#"{
+                "python": { "command": "pyls", "args": ["-v"] },
+                "rs": { "command": "ra", "languageId": "rust" }
+            }"#,
+        );
+        assert_eq!(
+            result,
+            Ok(vec![
+                (
+                    Language::Python,
+                    LanguageServer {
+                        command: "pyls".to_owned(),
+                        args: vec!["-v".to_owned()],
+                        language_id: "python".to_owned(),
+                    }
+                ),
+                (Language::Rust, LanguageServer::new("ra", "rust")),
+            ])
+        );
+    }
+
+    #[test]
+    fn must_fail_for_invalid_language_servers() {
+        assert_eq!(parse_language_servers("[]").is_err(), true);
+        assert_eq!(parse_language_servers(r#"{ "rust": {} }"#).is_err(), true);
+        assert_eq!(
+            parse_language_servers(r#"{ "nope": { "command": "a" } }"#).is_err(),
+            true
+        );
+        assert_eq!(
+            parse_language_servers(r#"{ "rust": { "command": "a", "args": "b" } }"#).is_err(),
+            true
+        );
+    }
+}
@@ -6,12 +6,18 @@ use std::sync::{Arc, RwLock};
 pub mod config;
 pub mod directories;
 pub mod editor_config;
+pub mod formatters;
+pub mod language_servers;
 pub mod scroll_config;
+pub mod snippets;
 
 pub use crate::config::*;
 pub use crate::directories::*;
 pub use crate::editor_config::*;
+pub use crate::formatters::*;
+pub use crate::language_servers::*;
 pub use crate::scroll_config::*;
+pub use crate::snippets::*;
 
 pub type ConfigAccess = Arc<RwLock<Config>>;
 
@@ -0,0 +1,90 @@
+use rider_lexers::Language;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+pub type SnippetsMapping = BTreeMap<Language, Vec<Snippet>>;
+
+/// Snippet expanded when its prefix is typed before Tab or picked from completion
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snippet {
+    pub prefix: String,
+    pub body: String,
+    pub description: String,
+}
+
+/// Snippets file content, body is a string or a list of lines, for example:
+///
+/// ```json
+/// {
+///   "fn": { "body": ["fn ${1:name}($2) {", "    $0", "}"], "description": "function" },
+///   "todo": { "body": "// TODO($CURRENT_DATE): $0" }
+/// }
+/// ```
+pub fn parse_snippets(content: &str) -> Result<Vec<Snippet>, String> {
+    let value: Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let definitions = value
+        .as_object()
+        .ok_or_else(|| "Snippets must be an object".to_string())?;
+    let mut snippets = vec![];
+    for (prefix, definition) in definitions.iter() {
+        let body = match definition.get("body") {
+            Some(Value::String(body)) => body.clone(),
+            Some(Value::Array(lines)) => lines
+                .iter()
+                .map(|line| line.as_str().map(|s| s.to_owned()))
+                .collect::<Option<Vec<String>>>()
+                .ok_or_else(|| format!("Snippet {} has invalid body line", prefix))?
+                .join("\n"),
+            _ => return Err(format!("Snippet {} has no body", prefix)),
+        };
+        let description = definition
+            .get("description")
+            .and_then(|d| d.as_str())
+            .unwrap_or_default()
+            .to_owned();
+        snippets.push(Snippet {
+            prefix: prefix.clone(),
+            body,
+            description,
+        });
+    }
+    Ok(snippets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn must_parse_string_and_lines_body() {
+        let result = parse_snippets(
+            r#"{
+                "fn": { "body": ["fn $1() {", "    $0", "}"], "description": "function" },
+                "todo": { "body": "// TODO: $0" }
+            }"#,
+        );
+        assert_eq!(
+            result,
+            Ok(vec![
+                Snippet {
+                    prefix: "fn".to_owned(),
+                    body: "fn $1() {\n    $0\n}".to_owned(),
+                    description: "function".to_owned(),
+                },
+                Snippet {
+                    prefix: "todo".to_owned(),
+                    body: "// TODO: $0".to_owned(),
+                    description: "".to_owned(),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn must_fail_for_invalid_snippets() {
+        assert_eq!(parse_snippets("[]").is_err(), true);
+        assert_eq!(parse_snippets(r#"{ "a": {} }"#).is_err(), true);
+        assert_eq!(parse_snippets(r#"{ "a": { "body": [1] } }"#).is_err(), true);
+        assert_eq!(parse_snippets("{").is_err(), true);
+    }
+}
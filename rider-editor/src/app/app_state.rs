@@ -1,13 +1,37 @@
 use crate::app::application::Application;
+use crate::app::cargo_runner::*;
+use crate::app::completion::*;
+use crate::app::diagnostics::*;
+use crate::app::external_change::*;
+use crate::app::file_content_manager::{self, Replacement, UndoStep};
+use crate::app::formatter::*;
+use crate::app::fs_watcher::*;
+use crate::app::git::*;
+use crate::app::jump_list::JumpList;
+use crate::app::snippets::*;
+use crate::app::symbol_index::*;
+use crate::app::terminal::TerminalAction;
 use crate::app::UpdateResult;
+use crate::lsp::*;
 use crate::renderer::renderer::Renderer;
 use crate::ui::*;
 use rider_config::*;
-use sdl2::rect::Point;
+use sdl2::rect::{Point, Rect};
 use sdl2::VideoSubsystem as VS;
+use std::collections::BTreeSet;
 use std::fs::{read_to_string, File};
 use std::io::Write;
+use std::path::Path;
+use std::sync::mpsc::{channel, Receiver};
 use std::sync::*;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+const TAB_SIZE: usize = 4;
+const LSP_DIAGNOSTICS: &str = "lsp";
+const CARGO_DIAGNOSTICS: &str = "cargo";
+const GIT_STATUS_DELAY: Duration = Duration::from_millis(500);
+const GIT_HUNKS_DELAY: Duration = Duration::from_millis(300);
 
 pub struct AppState {
     menu_bar: MenuBar,
@@ -16,6 +40,33 @@ pub struct AppState {
     config: Arc<RwLock<Config>>,
     file_editor: FileEditor,
     modal: Option<ModalType>,
+    root_path: String,
+    symbol_index: SymbolIndex,
+    index_receiver: Option<Receiver<SymbolIndex>>,
+    jump_list: JumpList,
+    completion: CompletionPopup,
+    snippet: Option<SnippetSession>,
+    lsp: LspManager,
+    diagnostics: DiagnosticsStore,
+    hover: HoverPopup,
+    hovered_diagnostic: Option<Diagnostic>,
+    blame: BlameColumn,
+    hovered_commit: Option<String>,
+    server_words: BTreeSet<String>,
+    completion_requested: bool,
+    cargo_program: String,
+    cargo: Option<CargoRunner>,
+    output: OutputPanel,
+    terminal: TerminalPanel,
+    commit_panel: CommitPanel,
+    diff_view: Option<DiffView>,
+    fs_watcher: Option<FsWatcher>,
+    git_statuses: Arc<GitStatuses>,
+    git_status_due: Option<Instant>,
+    overwrite_confirmed: Option<String>,
+    formatting: Option<FormatterRun>,
+    asked_about_change: Option<(String, Option<SystemTime>)>,
+    shell: String,
 }
 
 impl AppState {
@@ -29,6 +80,33 @@ impl AppState {
             files: vec![],
             file_editor: FileEditor::new(config.clone()),
             modal: None,
+            root_path: Application::current_working_directory(),
+            symbol_index: SymbolIndex::new(),
+            index_receiver: None,
+            jump_list: JumpList::new(),
+            completion: CompletionPopup::new(config.clone()),
+            snippet: None,
+            lsp: LspManager::new(Application::current_working_directory()),
+            diagnostics: DiagnosticsStore::new(),
+            hover: HoverPopup::new(config.clone()),
+            hovered_diagnostic: None,
+            blame: BlameColumn::new(config.clone()),
+            hovered_commit: None,
+            server_words: BTreeSet::new(),
+            completion_requested: false,
+            cargo_program: "cargo".to_owned(),
+            cargo: None,
+            output: OutputPanel::new(config.clone()),
+            terminal: TerminalPanel::new(config.clone()),
+            commit_panel: CommitPanel::new(config.clone()),
+            diff_view: None,
+            fs_watcher: None,
+            git_statuses: Arc::new(GitStatuses::default()),
+            git_status_due: None,
+            overwrite_confirmed: None,
+            formatting: None,
+            asked_about_change: None,
+            shell: std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_owned()),
             config,
         }
     }
@@ -38,22 +116,101 @@ impl AppState {
     where
         R: Renderer + CharacterSizeManager + ConfigHolder,
     {
-        let buffer = read_to_string(&file_path)
-            .map_err(|file_path| format!("Failed to open file: {}", file_path))?;
+        let version = DiskVersion::read(file_path.as_str())
+            .map_err(|e| format!("Failed to open file: {}", e))?;
+        self.sync_language_server();
+        let buffer = version.content.clone();
         let mut file = EditorFile::new(file_path.clone(), buffer, self.config.clone());
+        file.set_disk_version(Some(Arc::new(version)));
+        file.set_diagnostics(self.diagnostics.file(file_path.as_str()));
+        file.set_git_base(head_content(file_path.as_str()).map(Arc::new));
         file.prepare_ui(renderer);
+        let conflicts = file.conflicts().len();
         match self.file_editor.open_file(file) {
             Some(old) => self.files.push(old),
             _ => (),
         }
+        if conflicts > 0 {
+            let message = format!(
+                "{} merge conflicts, CTRL+ALT+O / T / B accepts ours / theirs / both",
+                conflicts
+            );
+            self.menu_bar.set_status(message, renderer);
+        }
+        self.refresh_blame(renderer);
         Ok(())
     }
 
-    pub fn save_file(&self) -> Result<(), String> {
+    /// Switches to already opened file or reads it from disk
+    pub fn show_file<R>(&mut self, file_path: String, renderer: &mut R) -> Result<(), String>
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if self
+            .file_editor
+            .file()
+            .map_or(false, |f| f.path() == file_path)
+        {
+            return Ok(());
+        }
+        match self.files.iter().position(|f| f.path() == file_path) {
+            Some(index) => {
+                self.sync_language_server();
+                let file = self.files.remove(index);
+                if let Some(old) = self.file_editor.open_file(file) {
+                    self.files.push(old);
+                }
+                self.refresh_blame(renderer);
+                self.check_files_on_disk(&[file_path], renderer);
+                Ok(())
+            }
+            None => self.open_file(file_path, renderer),
+        }
+    }
+
+    /// Writes current file, its formatter runs first when it should format on save.
+    ///
+    /// File is saved unformatted when formatter fails. File changed on disk since it was
+    /// read is not overwritten until it's saved again.
+    pub fn save_file<R>(&mut self, renderer: &mut R) -> Result<(), String>
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let (path, changed_on_disk) = match self.file_editor.file() {
+            Some(f) => {
+                let path = f.path();
+                let changed = f.disk_version().map_or(false, |known| {
+                    read_if_modified(path.as_str(), known)
+                        .map_or(false, |v| v.content != known.content)
+                });
+                (path, changed)
+            }
+            _ => Err("No buffer found".to_string())?,
+        };
+        if changed_on_disk && self.overwrite_confirmed.as_ref() != Some(&path) {
+            self.overwrite_confirmed = Some(path.clone());
+            let message = format!("{} changed on disk, save again to overwrite it", path);
+            self.menu_bar.set_status(message, renderer);
+            return Ok(());
+        }
+        self.overwrite_confirmed = None;
+        if let Some(formatter) = self.current_formatter().filter(|f| f.on_save) {
+            self.format_with(&formatter, true);
+            return Ok(());
+        }
+        self.write_file(renderer)
+    }
+
+    /// Writes buffer of current file, saved file is indexed and its git state is read again
+    fn write_file<R>(&mut self, renderer: &mut R) -> Result<(), String>
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
         let editor_file = match self.file_editor.file() {
             Some(f) => f,
             _ => Err("No buffer found".to_string())?,
         };
+        let path = editor_file.path();
         let mut f = File::create(editor_file.path())
             .or_else(|_| Err("File can't be opened".to_string()))?;
 
@@ -62,9 +219,192 @@ impl AppState {
 
         f.flush()
             .or_else(|_| Err("Failed to write to file".to_string()))?;
+        let version = DiskVersion::written(path.as_str(), editor_file.buffer());
+        if let Some(file) = self.file_editor.file_mut() {
+            file.set_disk_version(Some(Arc::new(version)));
+        }
+        self.reindex_current_file();
+        self.notify_file_saved();
+        self.refresh_git_status(renderer);
+        self.refresh_blame(renderer);
         Ok(())
     }
 
+    /// Checks every open file, used when watcher lost events
+    fn check_open_files<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let paths: Vec<String> = self
+            .file_editor
+            .file()
+            .into_iter()
+            .chain(self.files.iter())
+            .map(|file| file.path())
+            .collect();
+        self.check_files_on_disk(&paths, renderer);
+    }
+
+    /// Reloads unchanged buffers of given files when they were modified on disk.
+    ///
+    /// Edited current file asks what to do, edited background file asks once it's shown.
+    fn check_files_on_disk<R>(&mut self, paths: &[String], renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let current = self.file_editor.file().map(|file| file.path());
+        let open: Vec<(String, Arc<DiskVersion>)> = self
+            .file_editor
+            .file()
+            .into_iter()
+            .chain(self.files.iter())
+            .filter(|file| paths.contains(&file.path()))
+            .filter_map(|file| file.disk_version().map(|v| (file.path(), Arc::clone(v))))
+            .collect();
+        for (path, known) in open {
+            let version = match read_if_modified(path.as_str(), &known) {
+                Some(version) => version,
+                _ => continue,
+            };
+            let is_modified = self
+                .open_file_ref(path.as_str())
+                .map_or(false, |f| f.is_modified());
+            if version.content == known.content {
+                self.set_disk_version(path.as_str(), version);
+            } else if !is_modified {
+                self.reload_file(path.as_str(), version, renderer);
+            } else if current.as_ref() == Some(&path) && self.modal.is_none() {
+                // dismissed question is not asked again until file changes again
+                let change = (path.clone(), version.modified);
+                if self.asked_about_change.as_ref() == Some(&change) {
+                    continue;
+                }
+                self.asked_about_change = Some(change);
+                let entries = vec![
+                    ExternalChangeAction::Reload,
+                    ExternalChangeAction::KeepMine,
+                    ExternalChangeAction::Diff,
+                ]
+                .into_iter()
+                .map(|action| PickerEntry::from_external_change(path.as_str(), action))
+                .collect();
+                let name = Path::new(&path)
+                    .file_name()
+                    .map_or(path.clone(), |name| name.to_string_lossy().to_string());
+                let title = format!("{} changed on disk", name);
+                self.open_picker(title, entries, renderer);
+            }
+        }
+    }
+
+    /// Applies answer to file changed on disk while its buffer was edited
+    pub fn resolve_external_change<R>(
+        &mut self,
+        path: String,
+        action: ExternalChangeAction,
+        renderer: &mut R,
+    ) where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        self.modal = None;
+        let version = match DiskVersion::read(path.as_str()) {
+            Ok(version) => version,
+            Err(e) => {
+                let message = format!("Failed to read {}: {}", path, e);
+                self.menu_bar.set_status(message, renderer);
+                return;
+            }
+        };
+        match action {
+            ExternalChangeAction::Reload => self.reload_file(path.as_str(), version, renderer),
+            ExternalChangeAction::KeepMine => self.set_disk_version(path.as_str(), version),
+            ExternalChangeAction::Diff => {
+                self.set_disk_version(path.as_str(), version);
+                if self.file_editor.file().map(|f| f.path()) == Some(path) {
+                    self.compare_with_disk(renderer);
+                }
+            }
+        }
+    }
+
+    fn open_file_ref(&self, path: &str) -> Option<&EditorFile> {
+        self.file_editor
+            .file()
+            .into_iter()
+            .chain(self.files.iter())
+            .find(|file| file.path() == path)
+    }
+
+    fn set_disk_version(&mut self, path: &str, version: DiskVersion) {
+        let file = match self.file_editor.file_mut() {
+            Some(file) if file.path() == path => Some(file),
+            _ => self.files.iter_mut().find(|file| file.path() == path),
+        };
+        if let Some(file) = file {
+            file.set_disk_version(Some(Arc::new(version)));
+        }
+    }
+
+    /// Replaces buffer of open file with its content on disk, caret of current file stays in place.
+    ///
+    /// Reload is one more step of undo stack, folds outside of changed lines are kept.
+    fn reload_file<R>(&mut self, path: &str, version: DiskVersion, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let content = version.content.clone();
+        let is_current = self.file_editor.file().map_or(false, |f| f.path() == path);
+        if is_current {
+            let buffer = self
+                .file_editor
+                .file()
+                .map_or(String::new(), |f| f.buffer());
+            let caret = self.file_editor.caret().text_position();
+            let replacement = Replacement {
+                start: 0,
+                end: buffer.chars().count(),
+                text: content.clone(),
+            };
+            self.snippet = None;
+            let caret = caret.min(content.chars().count());
+            if let Err(e) = file_content_manager::replace_ranges(
+                &mut self.file_editor,
+                vec![replacement],
+                caret,
+                renderer,
+            ) {
+                self.menu_bar.set_status(e, renderer);
+                return;
+            }
+            if let Some(file) = self.file_editor.file_mut() {
+                file.set_git_base(head_content(path).map(Arc::new));
+            }
+        } else if let Some(index) = self.files.iter().position(|f| f.path() == path) {
+            let previous = &mut self.files[index];
+            let step = UndoStep::between(previous.buffer_ref(), content.as_str(), 0);
+            let edited_line = content
+                .chars()
+                .take(step.start)
+                .filter(|c| *c == '\n')
+                .count();
+            let mut undo_stack = previous.take_undo_stack();
+            undo_stack.push(step);
+            let mut file = EditorFile::new(path.to_owned(), content, self.config.clone());
+            file.set_diagnostics(self.diagnostics.file(path));
+            file.set_git_base(head_content(path).map(Arc::new));
+            file.set_undo_stack(undo_stack);
+            file.restore_folds(previous, edited_line);
+            file.prepare_ui(renderer);
+            self.files[index] = file;
+        }
+        self.set_disk_version(path, version);
+        if is_current {
+            self.refresh_blame(renderer);
+        }
+        let message = format!("Reloaded {} changed on disk", path);
+        self.menu_bar.set_status(message, renderer);
+    }
+
     pub fn open_settings<R>(&mut self, renderer: &mut R) -> Result<(), String>
     where
         R: Renderer + CharacterSizeManager + ConfigHolder,
@@ -80,305 +420,3033 @@ impl AppState {
         Ok(())
     }
 
-    pub fn close_modal(&mut self) -> Result<(), String> {
-        self.modal = None;
-        Ok(())
+    /// Opens symbol picker for current file, does nothing when other modal is open
+    pub fn open_outline<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let entries = match self.file_editor.file() {
+            Some(file) => file
+                .symbols()
+                .iter()
+                .map(PickerEntry::from_symbol)
+                .collect(),
+            _ => return,
+        };
+        self.open_picker("Symbols".to_owned(), entries, renderer);
     }
 
-    pub fn open_directory<R>(&mut self, dir_path: String, renderer: &mut R)
+    fn open_picker<R>(&mut self, title: String, entries: Vec<PickerEntry>, renderer: &mut R)
     where
         R: Renderer + CharacterSizeManager + ConfigHolder,
     {
-        match self.modal.as_mut() {
-            Some(ModalType::OpenFile(modal)) => modal.open_directory(dir_path, renderer),
-            None => self.project_tree.open_directory(dir_path, renderer),
-            _ => (),
-        };
+        if self.modal.is_some() {
+            return;
+        }
+        let mut picker = Picker::new(title, entries, 600, 600, self.config.clone());
+        picker.prepare_ui(renderer);
+        self.modal = Some(ModalType::Picker(picker));
     }
 
-    #[cfg_attr(tarpaulin, skip)]
-    pub fn file_editor(&self) -> &FileEditor {
-        &self.file_editor
+    /// Closes modal and moves caret to given char position of current file
+    pub fn jump_to(&mut self, position: usize) {
+        self.modal = None;
+        if let Some(location) = self.caret_location() {
+            self.jump_list.push(location);
+        }
+        self.file_editor.jump_to(position);
     }
 
-    #[cfg_attr(tarpaulin, skip)]
-    pub fn file_editor_mut(&mut self) -> &mut FileEditor {
-        &mut self.file_editor
+    /// Starts indexing project in background, index is replaced once scan is done
+    pub fn start_indexing(&mut self, root_path: String) {
+        let mapping = self.config.read().unwrap().extensions_mapping().clone();
+        let (sender, receiver) = channel();
+        let root = root_path.clone();
+        thread::spawn(move || sender.send(SymbolIndex::scan(root.as_str(), &mapping)));
+        self.lsp.set_root_path(root_path.clone());
+        self.root_path = root_path;
+        self.index_receiver = Some(receiver);
     }
 
-    pub fn set_open_file_modal(&mut self, modal: Option<OpenFile>) {
-        self.modal = if let Some(modal) = modal {
-            Some(ModalType::OpenFile(modal))
-        } else {
-            None
-        };
+    /// Starts watching project for changes made outside of editor
+    pub fn watch_project<R>(&mut self, root_path: String, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        match FsWatcher::new(root_path.as_str()) {
+            Ok(watcher) => self.fs_watcher = Some(watcher),
+            Err(e) => {
+                let message = format!("Failed to watch {}: {}", root_path, e);
+                self.menu_bar.set_status(message, renderer);
+            }
+        }
     }
 
-    pub fn scroll_by(&mut self, x: i32, y: i32) {
-        match self.modal.as_mut() {
-            Some(ModalType::OpenFile(modal)) => modal.scroll_by(x, y),
-            Some(ModalType::Settings(modal)) => modal.scroll_by(x, y),
-            _ => self.file_editor_mut().scroll_by(x, y),
+    /// Applies changes of project files to tree and open files.
+    ///
+    /// Git status is read again a moment after entries which are not ignored were created,
+    /// removed or renamed, so burst of changes runs git once.
+    pub fn poll_file_system<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let events = match self.fs_watcher.as_mut() {
+            Some(watcher) => watcher.poll(),
+            _ => return,
         };
+        if events.iter().any(|event| self.changes_git_status(event)) {
+            self.git_status_due
+                .get_or_insert_with(|| Instant::now() + GIT_STATUS_DELAY);
+        }
+        if self
+            .git_status_due
+            .map_or(false, |due| Instant::now() >= due)
+        {
+            self.refresh_git_status(renderer);
+        }
+        if events.is_empty() {
+            return;
+        }
+        let mut changed = vec![];
+        let mut rescan = false;
+        for event in events.iter() {
+            match event {
+                FsEvent::Created(path) => {
+                    self.project_tree.refresh_entry(path.as_str(), renderer);
+                    changed.push(path.clone());
+                }
+                FsEvent::Removed(path) => self.project_tree.refresh_entry(path.as_str(), renderer),
+                FsEvent::Renamed { from, to } => {
+                    self.project_tree
+                        .rename_entry(from.as_str(), to.as_str(), renderer);
+                    // programs often save by renaming new file over old one
+                    changed.push(to.clone());
+                }
+                FsEvent::Modified(path) => changed.push(path.clone()),
+                FsEvent::Rescan => {
+                    self.project_tree.refresh(renderer);
+                    rescan = true;
+                }
+            }
+        }
+        if rescan {
+            self.check_open_files(renderer);
+        } else {
+            self.check_files_on_disk(&changed, renderer);
+        }
     }
 
-    pub fn open_file_modal(&self) -> Option<&OpenFile> {
-        match self.modal {
-            Some(ModalType::OpenFile(ref m)) => Some(m),
-            _ => None,
+    /// Content changes are left to saves, entries of ignored paths never change status.
+    ///
+    /// Only ignored files git already reported are known, new ones refresh status once.
+    fn changes_git_status(&self, event: &FsEvent) -> bool {
+        match event {
+            FsEvent::Created(path) | FsEvent::Removed(path) => !self.is_git_ignored(path),
+            FsEvent::Renamed { from, to } => !self.is_git_ignored(from) || !self.is_git_ignored(to),
+            FsEvent::Modified(_) => false,
+            FsEvent::Rescan => true,
         }
     }
 
-    pub fn settings_modal(&self) -> Option<&Settings> {
-        match self.modal {
-            Some(ModalType::Settings(ref m)) => Some(m),
-            _ => None,
-        }
+    fn is_git_ignored(&self, path: &str) -> bool {
+        let relative = Path::new(path)
+            .strip_prefix(self.root_path.as_str())
+            .unwrap_or_else(|_| Path::new(path));
+        relative.components().any(|component| {
+            let name = component.as_os_str().to_string_lossy();
+            name == ".git" || IGNORED_DIRECTORIES.contains(&name.as_ref())
+        }) || self.git_statuses.file_status(path) == Some(GitStatus::Ignored)
     }
-}
 
-#[cfg_attr(tarpaulin, skip)]
-impl AppState {
-    pub fn render<C, R>(&self, canvas: &mut C, renderer: &mut R, _context: &RenderContext)
+    /// Reads every expanded directory of project tree again
+    pub fn refresh_fs_tree<R>(&mut self, renderer: &mut R)
     where
-        C: CanvasAccess,
-        R: Renderer + ConfigHolder + CharacterSizeManager,
+        R: Renderer + CharacterSizeManager + ConfigHolder,
     {
-        // file editor
-        self.file_editor
-            .render(canvas, renderer, &RenderContext::Nothing);
-
-        // menu bar
-        self.menu_bar
-            .render(canvas, renderer, &RenderContext::Nothing);
-
-        // project tree
-        self.project_tree
-            .render(canvas, renderer, &RenderContext::Nothing);
+        self.project_tree.refresh(renderer);
+        self.refresh_git_status(renderer);
+    }
 
-        // settings modal
-        match self.modal.as_ref() {
-            Some(ModalType::OpenFile(modal)) => {
-                return modal.render(canvas, renderer, &RenderContext::Nothing)
-            }
-            Some(ModalType::Settings(modal)) => {
-                return modal.render(canvas, renderer, &RenderContext::Nothing)
-            }
-            _ => (),
+    /// Updates index with current buffer, used after file was saved
+    pub fn reindex_current_file(&mut self) {
+        let file = match self.file_editor.file() {
+            Some(file) => file,
+            _ => return,
         };
+        let mapping = self.config.read().unwrap().extensions_mapping().clone();
+        if let Some(language) = language_of(file.path().as_str(), &mapping) {
+            self.symbol_index
+                .index_file(file.path().as_str(), file.buffer_ref(), language);
+        }
     }
 
-    pub fn prepare_ui<R>(&mut self, renderer: &mut R)
+    pub fn symbol_index(&self) -> &SymbolIndex {
+        &self.symbol_index
+    }
+
+    /// Definition of identifier at caret, picker is shown when there are many.
+    ///
+    /// Language server is asked when file has one, project index is used otherwise.
+    pub fn go_to_definition<R>(&mut self, renderer: &mut R) -> Result<(), String>
     where
         R: Renderer + CharacterSizeManager + ConfigHolder,
     {
-        self.menu_bar.prepare_ui();
-        self.project_tree.prepare_ui(renderer);
-        self.file_editor.prepare_ui(renderer);
+        if self.request_at_caret(|client, path, position| client.definition(path, position)) {
+            return Ok(());
+        }
+        self.go_to_indexed_definition(renderer)
     }
 
-    pub fn update(&mut self, ticks: i32, context: &UpdateContext) -> UpdateResult {
-        let res = match self.modal.as_mut() {
-            Some(ModalType::OpenFile(modal)) => modal.update(ticks, context.clone()),
-            Some(ModalType::Settings(modal)) => modal.update(ticks, context.clone()),
-            None => UpdateResult::NoOp,
+    fn go_to_indexed_definition<R>(&mut self, renderer: &mut R) -> Result<(), String>
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let (name, current_path) = match (self.word_at_caret(), self.file_editor.file()) {
+            (Some(name), Some(file)) => (name, file.path()),
+            _ => return Ok(()),
         };
-        if res != UpdateResult::NoOp {
-            return res;
+        let mut locations = self.symbol_index.definitions(name.as_str());
+        locations.sort_by_key(|location| location.path != current_path);
+        match locations.len() {
+            0 => Ok(()),
+            1 => self.open_location(locations.remove(0), renderer),
+            _ => {
+                self.open_locations_picker(format!("Definitions of {}", name), locations, renderer);
+                Ok(())
+            }
         }
+    }
 
-        // menu bar
-        self.menu_bar.update(ticks, context);
+    /// Every occurrence of identifier at caret in project
+    pub fn find_references<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let name = match self.word_at_caret() {
+            Some(name) => name,
+            _ => return,
+        };
+        let locations = self.symbol_index.references(name.as_str());
+        self.open_locations_picker(format!("References of {}", name), locations, renderer);
+    }
 
-        // sidebar
-        self.project_tree.update(ticks, context);
+    /// Lists diagnostics of all files, selected one is opened
+    pub fn open_problems<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let entries = self
+            .diagnostics
+            .all()
+            .iter()
+            .map(|diagnostic| PickerEntry::from_diagnostic(diagnostic, self.root_path.as_str()))
+            .collect();
+        self.open_picker("Problems".to_owned(), entries, renderer);
+    }
 
-        // file editor
-        let context = UpdateContext::ParentPosition(
-            self.project_tree.full_rect().top_right() + Point::new(10, 0),
-        );
-        self.file_editor.update(ticks, &context);
-        UpdateResult::NoOp
+    fn open_locations_picker<R>(
+        &mut self,
+        title: String,
+        locations: Vec<Location>,
+        renderer: &mut R,
+    ) where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let entries = locations
+            .iter()
+            .map(|location| PickerEntry::from_location(location, self.root_path.as_str()))
+            .collect();
+        self.open_picker(title, entries, renderer);
     }
-}
 
-impl AppState {
-    #[cfg_attr(tarpaulin, skip)]
-    pub fn on_left_click(&mut self, point: &Point, video_subsystem: &mut VS) -> UpdateResult {
-        if self
-            .project_tree
-            .is_left_click_target(point, &UpdateContext::Nothing)
-        {
-            return self
-                .project_tree
-                .on_left_click(point, &UpdateContext::Nothing);
-        }
-        match self.modal.as_mut() {
-            Some(ModalType::OpenFile(modal)) => {
-                return modal.on_left_click(point, &UpdateContext::Nothing)
-            }
-            Some(ModalType::Settings(modal)) => {
-                return modal.on_left_click(point, &UpdateContext::Nothing)
+    /// Closes modal and shows location, current one is remembered in jump list
+    pub fn open_location<R>(&mut self, location: Location, renderer: &mut R) -> Result<(), String>
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        self.modal = None;
+        let current = self.caret_location();
+        if self.show_location(location, renderer) {
+            if let Some(current) = current {
+                self.jump_list.push(current);
             }
-            _ => (),
+        }
+        Ok(())
+    }
+
+    pub fn jump_back<R>(&mut self, renderer: &mut R) -> Result<(), String>
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let current = match self.caret_location() {
+            Some(current) => current,
+            _ => return Ok(()),
         };
-        if self
-            .menu_bar
-            .is_left_click_target(point, &UpdateContext::Nothing)
-        {
-            video_subsystem.text_input().stop();
-            return self.menu_bar.on_left_click(point, &UpdateContext::Nothing);
-        } else if !self
-            .file_editor
-            .is_left_click_target(point, &UpdateContext::Nothing)
-        {
-            return UpdateResult::NoOp;
-        } else {
-            video_subsystem.text_input().start();
-            return self
-                .file_editor
-                .on_left_click(point, &UpdateContext::Nothing);
+        if let Some(location) = self.jump_list.back(current) {
+            if !self.show_location(location, renderer) {
+                self.jump_list.drop_back();
+            }
         }
+        Ok(())
     }
 
-    pub fn is_left_click_target(&self, _point: &Point) -> bool {
-        true
+    pub fn jump_forward<R>(&mut self, renderer: &mut R) -> Result<(), String>
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let current = match self.caret_location() {
+            Some(current) => current,
+            _ => return Ok(()),
+        };
+        if let Some(location) = self.jump_list.forward(current) {
+            if !self.show_location(location, renderer) {
+                self.jump_list.drop_forward();
+            }
+        }
+        Ok(())
     }
-}
 
-impl ConfigHolder for AppState {
-    fn config(&self) -> &ConfigAccess {
+    /// Moves caret to location, file which can't be read is reported in status bar and
+    /// false is returned
+    fn show_location<R>(&mut self, location: Location, renderer: &mut R) -> bool
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if let Err(e) = self.show_file(location.path.clone(), renderer) {
+            let message = format!("{}: {}", location.path, e);
+            self.menu_bar.set_status(message, renderer);
+            return false;
+        }
+        let position = self
+            .file_editor
+            .file()
+            .and_then(|file| file.position_at(location.line, location.column));
+        if let Some(position) = position {
+            self.file_editor.jump_to(position);
+        }
+        true
+    }
+
+    fn caret_location(&self) -> Option<Location> {
+        let file = self.file_editor.file()?;
+        let caret = self.file_editor.caret().position();
+        Some(Location {
+            path: file.path(),
+            line: caret.line_number(),
+            column: caret.line_position(),
+        })
+    }
+
+    fn word_at_caret(&self) -> Option<String> {
+        let file = self.file_editor.file()?;
+        word_at(
+            file.buffer_ref().as_str(),
+            self.file_editor.caret().text_position(),
+        )
+    }
+
+    pub fn close_modal(&mut self) -> Result<(), String> {
+        self.modal = None;
+        self.diff_view = None;
+        self.hide_popups();
+        self.snippet = None;
+        Ok(())
+    }
+
+    pub fn hide_popups(&mut self) {
+        self.completion.hide();
+        self.completion_requested = false;
+        self.hover.hide();
+        self.hovered_diagnostic = None;
+        self.hovered_commit = None;
+    }
+
+    /// Shows completion for identifier before caret and asks language server for more
+    pub fn update_completion<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        self.hover.hide();
+        self.refresh_completion(renderer);
+        let has_prefix = self.file_editor.file().map_or(false, |file| {
+            !prefix_before(
+                file.buffer_ref().as_str(),
+                self.file_editor.caret().text_position(),
+            )
+            .is_empty()
+        });
+        self.completion_requested = has_prefix
+            && self.modal.is_none()
+            && self.request_at_caret(|client, path, position| client.completion(path, position));
+    }
+
+    /// Shows snippets, words of open files and last words from language server
+    /// matching identifier before caret, hides popup when nothing matches
+    fn refresh_completion<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let file = match self.file_editor.file() {
+            Some(file) if self.modal.is_none() => file,
+            _ => {
+                self.completion.hide();
+                return;
+            }
+        };
+        let prefix = prefix_before(
+            file.buffer_ref().as_str(),
+            self.file_editor.caret().text_position(),
+        );
+        let mut words = file.words();
+        for other in self.files.iter() {
+            words.extend(other.words());
+        }
+        words.extend(self.server_words.iter().cloned());
+        let items = completion_items(&words, &self.current_snippets(), prefix.as_str());
+        if items.is_empty() {
+            self.completion.hide();
+            return;
+        }
+        let point = self.file_editor.caret_window_rect().bottom_left();
+        self.completion.show(prefix, items, point, renderer);
+    }
+
+    /// Replaces typed prefix with selected item, returns false when popup is hidden
+    pub fn accept_completion<R>(&mut self, renderer: &mut R) -> bool
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let item = match self.completion.selected_item() {
+            Some(item) => item.clone(),
+            _ => return false,
+        };
+        let prefix_len = self.completion.prefix().chars().count();
+        self.hide_popups();
+        match item {
+            CompletionItem::Word(word) => {
+                let caret = self.file_editor.caret().text_position();
+                let new_caret = caret - prefix_len + word.chars().count();
+                let replacement = Replacement {
+                    start: caret - prefix_len,
+                    end: caret,
+                    text: word,
+                };
+                self.snippet = None;
+                file_content_manager::replace_ranges(
+                    &mut self.file_editor,
+                    vec![replacement],
+                    new_caret,
+                    renderer,
+                )
+                .unwrap_or_else(|e| error!("{}", e));
+            }
+            CompletionItem::Snippet(snippet) => self.expand_snippet(&snippet, prefix_len, renderer),
+        }
+        true
+    }
+
+    /// Tab accepts completion, moves to next tab stop of snippet or expands snippet before caret
+    pub fn on_tab<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if self.accept_completion(renderer) || self.next_snippet_stop() {
+            return;
+        }
+        let prefix = match self.file_editor.file() {
+            Some(file) => prefix_before(
+                file.buffer_ref().as_str(),
+                self.file_editor.caret().text_position(),
+            ),
+            _ => return,
+        };
+        let snippet = self
+            .current_snippets()
+            .into_iter()
+            .find(|snippet| !prefix.is_empty() && snippet.prefix == prefix);
+        if let Some(snippet) = snippet {
+            self.expand_snippet(&snippet, prefix.chars().count(), renderer);
+        }
+    }
+
+    /// Replaces prefix before caret with snippet in one edit and starts its tab stops
+    pub fn expand_snippet<R>(&mut self, snippet: &Snippet, prefix_len: usize, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let (path, indent) = match self.file_editor.file() {
+            Some(file) => (
+                file.path(),
+                line_indent(
+                    file.buffer_ref().as_str(),
+                    self.file_editor.caret().text_position(),
+                ),
+            ),
+            _ => return,
+        };
+        let caret = self.file_editor.caret().text_position();
+        let start = caret - prefix_len;
+        let now = SystemTime::now();
+        let expansion = expand(
+            snippet.body.as_str(),
+            &variables(path.as_str(), now, local_utc_offset(now)),
+            indent.as_str(),
+        );
+        let session = SnippetSession::new(&expansion, start);
+        let replacement = Replacement {
+            start,
+            end: caret,
+            text: expansion.text,
+        };
+        let new_caret = session.caret().unwrap_or(start);
+        match file_content_manager::replace_ranges(
+            &mut self.file_editor,
+            vec![replacement],
+            new_caret,
+            renderer,
+        ) {
+            Ok(()) if !session.is_finished() => self.snippet = Some(session),
+            Ok(()) => self.snippet = None,
+            Err(e) => error!("{}", e),
+        }
+    }
+
+    /// Moves caret to next tab stop, returns false when no snippet is active
+    pub fn next_snippet_stop(&mut self) -> bool {
+        let session = match self.snippet.as_mut() {
+            Some(session) => session,
+            _ => return false,
+        };
+        let caret = session.next_stop();
+        if session.is_finished() {
+            self.snippet = None;
+        }
+        if let Some(caret) = caret {
+            self.file_editor.jump_to(caret);
+        }
+        true
+    }
+
+    pub fn end_snippet(&mut self) {
+        self.snippet = None;
+    }
+
+    pub fn snippet(&self) -> Option<&SnippetSession> {
+        self.snippet.as_ref()
+    }
+
+    /// Inserts text at caret, inside of snippet tab stop its mirrors are updated too
+    pub fn insert_text<R>(&mut self, text: String, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if !self.edit_snippet(SnippetEdit::Insert(text.clone()), renderer) {
+            self.file_editor.insert_text(text, renderer);
+        }
+        self.update_completion(renderer);
+    }
+
+    /// Reverts last edit of current file, snippet being filled ends
+    pub fn undo<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        self.snippet = None;
+        self.hide_popups();
+        if !self.file_editor.undo(renderer) {
+            self.menu_bar
+                .set_status("Nothing to undo".to_owned(), renderer);
+        }
+    }
+
+    /// Deletes character before caret, inside of snippet tab stop its mirrors are updated too
+    pub fn delete_front<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        self.hover.hide();
+        if !self.edit_snippet(SnippetEdit::DeleteFront, renderer) {
+            self.file_editor.delete_front(renderer);
+        }
+        if self.completion.is_visible() {
+            self.update_completion(renderer);
+        }
+    }
+
+    /// Applies edit to current tab stop, snippet ends when caret is outside of it
+    fn edit_snippet<R>(&mut self, edit: SnippetEdit, renderer: &mut R) -> bool
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let caret = self.file_editor.caret().text_position();
+        let result = match (self.snippet.as_mut(), self.file_editor.file()) {
+            (Some(session), Some(file)) => session.edit(caret, edit, file.buffer_ref().as_str()),
+            _ => return false,
+        };
+        match result {
+            Some((replacements, new_caret)) => {
+                file_content_manager::replace_ranges(
+                    &mut self.file_editor,
+                    replacements,
+                    new_caret,
+                    renderer,
+                )
+                .unwrap_or_else(|e| error!("{}", e));
+                true
+            }
+            None => {
+                self.snippet = None;
+                false
+            }
+        }
+    }
+
+    fn current_snippets(&self) -> Vec<Snippet> {
+        let config = self.config.read().unwrap();
+        self.file_editor
+            .file()
+            .and_then(|file| language_of(file.path().as_str(), config.extensions_mapping()))
+            .map_or_else(Vec::new, |language| config.snippets(language).to_vec())
+    }
+
+    /// Language server of current file together with the file
+    fn language_client(&mut self) -> Option<(&mut LspClient, &EditorFile)> {
+        let file = self.file_editor.file()?;
+        let config = self.config.read().unwrap();
+        let language = language_of(file.path().as_str(), config.extensions_mapping())?;
+        let client = self.lsp.client(language, &config)?;
+        Some((client, file))
+    }
+
+    /// Sends edits of current file to its language server, file is opened there when needed
+    pub fn sync_language_server(&mut self) {
+        let changes = self.file_editor.take_changes();
+        if let Some((client, file)) = self.language_client() {
+            let path = file.path();
+            if !client.is_open(path.as_str()) {
+                client.did_open(path.as_str(), file.buffer_ref());
+            } else if !changes.is_empty() {
+                client.did_change(path.as_str(), &changes);
+            }
+        }
+    }
+
+    pub fn notify_file_saved(&mut self) {
+        self.sync_language_server();
+        if let Some((client, file)) = self.language_client() {
+            client.did_save(file.path().as_str());
+        }
+    }
+
+    /// Sends request about caret position, returns false when file has no language server
+    fn request_at_caret<F>(&mut self, request: F) -> bool
+    where
+        F: FnOnce(&mut LspClient, &str, Position),
+    {
+        self.sync_language_server();
+        let caret = self.file_editor.caret().text_position();
+        match self.language_client() {
+            Some((client, file)) => {
+                let position = position_at(file.buffer_ref(), caret);
+                request(client, file.path().as_str(), position);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Asks language server about symbol at caret, answer is shown below caret.
+    ///
+    /// Without language server message of diagnostic at caret is shown.
+    pub fn show_hover<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if self.request_at_caret(|client, path, position| client.hover(path, position)) {
+            return;
+        }
+        let caret = self.file_editor.caret().text_position();
+        let text = match self.file_editor.file().and_then(|f| f.diagnostic_at(caret)) {
+            Some(diagnostic) => diagnostic.text(),
+            _ => return,
+        };
+        let point = self.file_editor.caret_window_rect().bottom_left();
+        self.completion.hide();
+        self.hover.show(text, point, renderer);
+    }
+
+    /// Shows message of diagnostic under mouse, it is hidden once mouse leaves diagnostic
+    pub fn on_mouse_move<R>(&mut self, point: &Point, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if self.modal.is_some() {
+            return;
+        }
+        if self.blame.dest().contains_point(*point) && self.blame.is_visible() {
+            return self.on_blame_hover(point, renderer);
+        }
+        if self.hovered_commit.take().is_some() {
+            self.hover.hide();
+        }
+        let (diagnostic, line_height) = match self.file_editor.file() {
+            Some(file) => (
+                self.file_editor
+                    .position_at_point(point)
+                    .and_then(|position| file.diagnostic_at(position))
+                    .cloned(),
+                file.line_height() as i32,
+            ),
+            _ => return,
+        };
+        if diagnostic == self.hovered_diagnostic {
+            return;
+        }
+        match diagnostic.as_ref() {
+            Some(diagnostic) => {
+                let point = point.offset(0, line_height);
+                self.completion.hide();
+                self.hover.show(diagnostic.text(), point, renderer);
+            }
+            None if self.hover.is_visible() => self.hover.hide(),
+            None => (),
+        }
+        self.hovered_diagnostic = diagnostic;
+    }
+
+    /// Formats current file with its formatter, language server is asked when there is none
+    pub fn format_document<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if let Some(formatter) = self.current_formatter() {
+            self.format_with(&formatter, false);
+            return;
+        }
+        self.sync_language_server();
+        if let Some((client, file)) = self.language_client() {
+            client.formatting(file.path().as_str(), TAB_SIZE);
+        }
+    }
+
+    fn current_formatter(&self) -> Option<Formatter> {
+        let file = self.file_editor.file()?;
+        let config = self.config.read().unwrap();
+        let language = language_of(file.path().as_str(), config.extensions_mapping())?;
+        config.formatter(language).cloned()
+    }
+
+    /// Starts formatter for current file, buffer is replaced when its output is polled.
+    ///
+    /// Save asked for while file is formatted is done once formatter is done.
+    fn format_with(&mut self, formatter: &Formatter, save: bool) {
+        let (path, buffer, dir) = match self.file_editor.file() {
+            Some(file) => {
+                let path = file.path();
+                let dir = Path::new(path.as_str())
+                    .parent()
+                    .filter(|dir| dir.is_dir())
+                    .map_or_else(
+                        || self.root_path.clone(),
+                        |dir| dir.to_string_lossy().to_string(),
+                    );
+                (path, file.buffer(), dir)
+            }
+            _ => return,
+        };
+        if let Some(run) = self.formatting.as_mut() {
+            if run.path() == &path && run.buffer() == &buffer {
+                run.set_save(save || run.save());
+                return;
+            }
+        }
+        self.formatting = Some(FormatterRun::start(formatter, path, buffer, dir, save));
+    }
+
+    /// Applies output of finished formatter, failure is shown in status and leaves buffer untouched.
+    ///
+    /// Output is dropped when buffer was edited or hidden while it was formatted.
+    pub fn poll_formatter<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let result = match self.formatting.as_ref().and_then(|run| run.poll()) {
+            Some(result) => result,
+            _ => return,
+        };
+        let run = match self.formatting.take() {
+            Some(run) => run,
+            _ => return,
+        };
+        let (current, edited) = match self.file_editor.file() {
+            Some(file) if &file.path() == run.path() => (true, file.buffer_ref() != run.buffer()),
+            _ => (false, false),
+        };
+        let applied = match result {
+            Ok(_) if !current => Err(format!("{} was hidden while it was formatted", run.path())),
+            Ok(_) if edited => Err(format!("{} was edited while it was formatted", run.path())),
+            Ok(formatted) => self.replace_with_formatted(run.buffer(), formatted, renderer),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = applied {
+            self.menu_bar.set_status(e, renderer);
+        }
+        if run.save() && current {
+            if let Err(e) = self.write_file(renderer) {
+                self.menu_bar.set_status(e, renderer);
+            }
+        }
+    }
+
+    /// Replaces buffer with formatter output as one edit, caret follows its line
+    fn replace_with_formatted<R>(
+        &mut self,
+        buffer: &String,
+        formatted: String,
+        renderer: &mut R,
+    ) -> Result<(), String>
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if &formatted == buffer {
+            return Ok(());
+        }
+        let hunks = diff_lines(buffer.as_str(), formatted.as_str());
+        let caret = self.caret_location().map_or(0, |location| {
+            let line = new_line(&hunks, location.line);
+            position_in_text(formatted.as_str(), line, location.column)
+        });
+        let replacement = Replacement {
+            start: 0,
+            end: buffer.chars().count(),
+            text: formatted,
+        };
+        self.snippet = None;
+        file_content_manager::replace_ranges(
+            &mut self.file_editor,
+            vec![replacement],
+            caret,
+            renderer,
+        )
+    }
+
+    /// Restores lines of change at caret to their HEAD version, caret moves to first of them
+    pub fn revert_hunk<R>(&mut self, renderer: &mut R) -> Result<(), String>
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let (buffer, reverted, line) = match self.hunk_at_caret() {
+            Some((file, base, hunk)) => (
+                file.buffer(),
+                revert_hunk(base.as_str(), file.buffer_ref(), &hunk),
+                hunk.new_start,
+            ),
+            _ => return Ok(()),
+        };
+        let caret = position_in_text(reverted.as_str(), line, 0);
+        let replacement = Replacement {
+            start: 0,
+            end: buffer.chars().count(),
+            text: reverted,
+        };
+        self.snippet = None;
+        file_content_manager::replace_ranges(
+            &mut self.file_editor,
+            vec![replacement],
+            caret,
+            renderer,
+        )
+    }
+
+    /// Shows change at caret in unified diff format
+    pub fn show_hunk_diff<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let text = match self.hunk_at_caret() {
+            Some((file, base, hunk)) => hunk_text(base.as_str(), file.buffer_ref(), &hunk),
+            _ => return,
+        };
+        let point = self.file_editor.caret_window_rect().bottom_left();
+        self.completion.hide();
+        self.hover.show(text, point, renderer);
+    }
+
+    /// Compares current file with HEAD a moment after edits, so typing does not wait for diff
+    pub fn poll_git_hunks(&mut self) {
+        if let Some(file) = self.file_editor.file_mut() {
+            if file
+                .hunks_outdated_since()
+                .map_or(false, |since| since.elapsed() >= GIT_HUNKS_DELAY)
+            {
+                file.refresh_hunks();
+            }
+        }
+    }
+
+    fn hunk_at_caret(&mut self) -> Option<(&EditorFile, Arc<String>, Hunk)> {
+        if let Some(file) = self.file_editor.file_mut() {
+            file.refresh_hunks();
+        }
+        let file = self.file_editor.file()?;
+        let base = file.git_base()?.clone();
+        let hunk = file
+            .hunk_at_line(self.file_editor.caret().line_number())?
+            .clone();
+        Some((file, base, hunk))
+    }
+
+    /// Replaces merge conflict at caret with chosen side, caret moves to first line of it
+    pub fn resolve_conflict<R>(
+        &mut self,
+        resolution: ConflictResolution,
+        renderer: &mut R,
+    ) -> Result<(), String>
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let line = self.file_editor.caret().line_number();
+        let (buffer, resolved, line) = match self.file_editor.file() {
+            Some(file) => match file.conflict_at_line(line) {
+                Some(conflict) => (
+                    file.buffer(),
+                    resolve_conflict(file.buffer_ref(), conflict, resolution),
+                    conflict.start,
+                ),
+                _ => {
+                    return Ok(self
+                        .menu_bar
+                        .set_status("No conflict at caret".to_owned(), renderer))
+                }
+            },
+            _ => return Ok(()),
+        };
+        let caret = position_in_text(resolved.as_str(), line, 0);
+        let replacement = Replacement {
+            start: 0,
+            end: buffer.chars().count(),
+            text: resolved,
+        };
+        self.snippet = None;
+        file_content_manager::replace_ranges(
+            &mut self.file_editor,
+            vec![replacement],
+            caret,
+            renderer,
+        )
+    }
+
+    /// Moves caret to start of next or previous merge conflict, search wraps around file
+    pub fn jump_to_conflict(&mut self, forward: bool) {
+        let line = self.file_editor.caret().line_number();
+        let position = match self.file_editor.file() {
+            Some(file) => {
+                let starts: Vec<usize> = file.conflicts().iter().map(|c| c.start).collect();
+                let target = if forward {
+                    starts
+                        .iter()
+                        .find(|start| **start > line)
+                        .or_else(|| starts.first())
+                } else {
+                    starts
+                        .iter()
+                        .rev()
+                        .find(|start| **start < line)
+                        .or_else(|| starts.last())
+                };
+                target.and_then(|start| file.position_at(*start, 0))
+            }
+            _ => None,
+        };
+        if let Some(position) = position {
+            self.file_editor.jump_to(position);
+        }
+    }
+
+    /// Shows or hides commit, author and age of every line of current file
+    pub fn toggle_blame<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if self.blame.is_visible() {
+            self.blame.hide();
+            return;
+        }
+        self.blame.show();
+        self.refresh_blame(renderer);
+    }
+
+    pub fn blame(&self) -> &BlameColumn {
+        &self.blame
+    }
+
+    /// Blames current buffer when blame column is visible
+    pub fn refresh_blame<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if !self.blame.is_visible() {
+            return;
+        }
+        let path = match self.file_editor.file() {
+            Some(file) => file.path(),
+            _ => return,
+        };
+        match self.blame_current_file() {
+            Ok(blame) => self.blame.set_blame(path, blame, unix_now(), renderer),
+            Err(e) => {
+                self.blame.clear(path);
+                self.menu_bar
+                    .set_status(format!("Blame is not available: {}", e), renderer);
+            }
+        }
+    }
+
+    fn blame_current_file(&self) -> Result<Blame, String> {
+        match self.file_editor.file() {
+            Some(file) => blame_buffer(file.path().as_str(), file.buffer_ref()),
+            _ => Err("No buffer found".to_owned()),
+        }
+    }
+
+    /// Y of first line of current file in window
+    fn editor_text_top(&self) -> i32 {
+        self.file_editor.render_start_point().y() + self.file_editor.scroll().y()
+    }
+
+    /// Full message of commit under mouse, it is hidden once mouse leaves commit
+    fn on_blame_hover<R>(&mut self, point: &Point, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let top = self.editor_text_top();
+        let (commit, path) = match self.file_editor.file() {
+            Some(file) => (
+                self.blame
+                    .commit_at_point(file, top, point)
+                    .filter(|commit| commit.is_committed())
+                    .cloned(),
+                file.path(),
+            ),
+            _ => return,
+        };
+        let id = commit.as_ref().map(|commit| commit.id.clone());
+        if id == self.hovered_commit {
+            return;
+        }
+        self.hovered_diagnostic = None;
+        let message = commit.and_then(|commit| {
+            let dir = Path::new(path.as_str()).parent()?.to_str()?.to_owned();
+            commit_message(dir.as_str(), commit.id.as_str()).ok()
+        });
+        match message {
+            Some(message) => {
+                let line_height = renderer.load_character_size('W').height() as i32;
+                self.completion.hide();
+                self.hover
+                    .show(message, point.offset(0, line_height), renderer);
+            }
+            None if self.hover.is_visible() => self.hover.hide(),
+            None => (),
+        }
+        self.hovered_commit = id;
+    }
+
+    /// Opens changes of commit which last changed line at caret
+    pub fn show_blame_commit<R>(&mut self, renderer: &mut R) -> Result<(), String>
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let line = self.file_editor.caret().line_number();
+        let path = match self.file_editor.file() {
+            Some(file) => file.path(),
+            _ => return Ok(()),
+        };
+        let commit = match self.blame_current_file() {
+            Ok(blame) => blame.commit_at(line).cloned(),
+            Err(e) => {
+                let message = format!("Blame is not available: {}", e);
+                self.menu_bar.set_status(message, renderer);
+                return Ok(());
+            }
+        };
+        let commit = match commit {
+            Some(commit) if commit.is_committed() => commit,
+            _ => {
+                let message = "Line is not committed yet".to_owned();
+                self.menu_bar.set_status(message, renderer);
+                return Ok(());
+            }
+        };
+        let dir = Path::new(path.as_str())
+            .parent()
+            .and_then(|dir| dir.to_str())
+            .unwrap_or(".")
+            .to_owned();
+        let diff = match commit_diff(dir.as_str(), commit.id.as_str()) {
+            Ok(diff) => diff,
+            Err(e) => {
+                self.menu_bar.set_status(e, renderer);
+                return Ok(());
+            }
+        };
+        let commits_dir = std::env::temp_dir().join("rider").join("commits");
+        let diff_path = commits_dir.join(format!("{}.diff", commit.short_id()));
+        let written =
+            std::fs::create_dir_all(&commits_dir).and_then(|_| std::fs::write(&diff_path, diff));
+        if let Err(e) = written {
+            let message = format!("Failed to write commit diff: {}", e);
+            self.menu_bar.set_status(message, renderer);
+            return Ok(());
+        }
+        self.open_file(diff_path.to_string_lossy().to_string(), renderer)
+    }
+
+    /// Reads status of repository of project, tree and commit panel show it
+    pub fn refresh_git_status<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let statuses = Arc::new(repository_status(self.root_path.as_str()).unwrap_or_default());
+        self.git_status_due = None;
+        self.commit_panel.set_statuses(&statuses, renderer);
+        self.project_tree
+            .set_git_statuses(Arc::clone(&statuses), renderer);
+        self.git_statuses = statuses;
+    }
+
+    /// Shows and focuses commit panel with current changes, focused panel is hidden
+    pub fn toggle_commit_panel<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if self.commit_panel.is_focused() {
+            return self.commit_panel.hide();
+        }
+        self.output.hide();
+        self.terminal.hide();
+        self.refresh_git_status(renderer);
+        self.commit_panel.show();
+    }
+
+    pub fn commit_panel(&self) -> &CommitPanel {
+        &self.commit_panel
+    }
+
+    /// Commit panel when it receives typed text
+    pub fn focused_commit_panel_mut(&mut self) -> Option<&mut CommitPanel> {
+        if self.commit_panel.is_focused() {
+            Some(&mut self.commit_panel)
+        } else {
+            None
+        }
+    }
+
+    /// Stages file with unstaged changes, fully staged file is taken back from index
+    pub fn toggle_staged<R>(&mut self, path: String, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let root = self.root_path.as_str();
+        let result = match self.commit_panel.entry(path.as_str()) {
+            Some(entry) if !entry.has_unstaged() => unstage_file(root, path.as_str()),
+            _ => stage_file(root, path.as_str()),
+        };
+        self.finish_git_action(result, renderer);
+    }
+
+    /// Stages change at caret, buffer is compared with index
+    pub fn stage_hunk<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let line = self.file_editor.caret().line_number();
+        let result = match self.file_editor.file() {
+            Some(file) => {
+                let path = file.path();
+                match index_content(path.as_str()) {
+                    Some(index) => {
+                        let buffer = file.buffer_ref().as_str();
+                        match diff_lines(index.as_str(), buffer)
+                            .into_iter()
+                            .find(|hunk| hunk.contains_line(line))
+                        {
+                            Some(hunk) => apply_hunk_to_index(
+                                path.as_str(),
+                                index.as_str(),
+                                buffer,
+                                &hunk,
+                                false,
+                            ),
+                            _ => return,
+                        }
+                    }
+                    _ => Err(format!("{} is not tracked", path)),
+                }
+            }
+            _ => return,
+        };
+        self.finish_git_action(result, renderer);
+    }
+
+    /// Takes staged change at caret back from index.
+    ///
+    /// Caret line is moved to index by changes of buffer which are not staged.
+    pub fn unstage_hunk<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let line = self.file_editor.caret().line_number();
+        let result = match self.file_editor.file() {
+            Some(file) => {
+                let path = file.path();
+                match (head_content(path.as_str()), index_content(path.as_str())) {
+                    (Some(head), Some(index)) => {
+                        let unstaged = diff_lines(index.as_str(), file.buffer_ref());
+                        let line = old_line(&unstaged, line);
+                        match diff_lines(head.as_str(), index.as_str())
+                            .into_iter()
+                            .find(|hunk| hunk.contains_line(line))
+                        {
+                            Some(hunk) => apply_hunk_to_index(
+                                path.as_str(),
+                                head.as_str(),
+                                index.as_str(),
+                                &hunk,
+                                true,
+                            ),
+                            _ => return,
+                        }
+                    }
+                    _ => Err(format!("{} is not tracked", path)),
+                }
+            }
+            _ => return,
+        };
+        self.finish_git_action(result, renderer);
+    }
+
+    /// Commits staged changes with message from commit panel, open files are compared with new HEAD.
+    ///
+    /// Nothing is committed while panel is hidden or not focused.
+    pub fn commit<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if !self.commit_panel.is_focused() {
+            return;
+        }
+        let result = commit(
+            self.root_path.as_str(),
+            self.commit_panel.message().as_str(),
+        );
+        if result.is_ok() {
+            self.commit_panel.clear_message(renderer);
+            self.menu_bar.set_status("Committed".to_owned(), renderer);
+            let files = self
+                .file_editor
+                .file_mut()
+                .into_iter()
+                .chain(self.files.iter_mut());
+            for file in files {
+                file.set_git_base(head_content(file.path().as_str()).map(Arc::new));
+            }
+        }
+        self.finish_git_action(result, renderer);
+    }
+
+    fn finish_git_action<R>(&mut self, result: Result<(), String>, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if let Err(e) = result {
+            self.menu_bar.set_status(e, renderer);
+        }
+        self.refresh_git_status(renderer);
+    }
+
+    /// Shows current buffer next to its version from `HEAD`
+    pub fn compare_with_head<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let path = match self.file_editor.file() {
+            Some(file) => file.path(),
+            _ => return,
+        };
+        match head_content(path.as_str()) {
+            Some(head) => self.open_diff_view((DiffSource::Head(path), head), renderer),
+            _ => self
+                .menu_bar
+                .set_status(format!("{} is not in git repository", path), renderer),
+        }
+    }
+
+    /// Shows current buffer next to its saved version
+    pub fn compare_with_disk<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let path = match self.file_editor.file() {
+            Some(file) => file.path(),
+            _ => return,
+        };
+        match read_to_string(&path) {
+            Ok(text) => self.open_diff_view((DiffSource::Disk(path), text), renderer),
+            Err(e) => self
+                .menu_bar
+                .set_status(format!("Failed to read {}: {}", path, e), renderer),
+        }
+    }
+
+    /// Lists project files, selected one is compared with current buffer
+    pub fn open_compare_picker<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let current = match self.file_editor.file() {
+            Some(file) => file.path(),
+            _ => return,
+        };
+        let entries = project_files(self.root_path.as_str())
+            .iter()
+            .filter(|path| **path != current)
+            .map(|path| PickerEntry::from_compared_file(path, self.root_path.as_str()))
+            .collect();
+        self.open_picker("Compare with".to_owned(), entries, renderer);
+    }
+
+    /// Closes modal and shows other file next to current buffer
+    pub fn compare_with_file<R>(&mut self, path: String, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        self.modal = None;
+        match read_to_string(&path) {
+            Ok(text) => self.open_diff_view((DiffSource::File(path), text), renderer),
+            Err(e) => self
+                .menu_bar
+                .set_status(format!("Failed to read {}: {}", path, e), renderer),
+        }
+    }
+
+    /// Diff view replaces editor and panels until it is closed
+    fn open_diff_view<R>(&mut self, left: (DiffSource, String), renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let right = match self.file_editor.file() {
+            Some(file) => (DiffSource::Buffer(file.path()), file.buffer()),
+            _ => return,
+        };
+        let mut view = DiffView::new(left, right, self.config.clone());
+        view.prepare_ui(renderer);
+        self.hide_popups();
+        self.output.hide();
+        self.terminal.hide();
+        self.commit_panel.hide();
+        self.diff_view = Some(view);
+    }
+
+    pub fn diff_view(&self) -> Option<&DiffView> {
+        self.diff_view.as_ref()
+    }
+
+    pub fn diff_view_mut(&mut self) -> Option<&mut DiffView> {
+        self.diff_view.as_mut()
+    }
+
+    /// Copies change of diff view into given side.
+    ///
+    /// Buffer of current file is edited in place, other file is written to disk.
+    pub fn copy_hunk<R>(
+        &mut self,
+        hunk: usize,
+        target: DiffSide,
+        renderer: &mut R,
+    ) -> Result<(), String>
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let (source, text, line) = match self.diff_view.as_ref() {
+            Some(view) => match view.copied_text(hunk, target) {
+                Ok(text) => {
+                    let hunk = &view.hunks()[hunk];
+                    let line = match target {
+                        DiffSide::Left => hunk.old_start,
+                        DiffSide::Right => hunk.new_start,
+                    };
+                    (view.side_source(target).clone(), text, line)
+                }
+                Err(e) => {
+                    self.menu_bar.set_status(e, renderer);
+                    return Ok(());
+                }
+            },
+            _ => return Ok(()),
+        };
+        match source {
+            DiffSource::Buffer(ref path) => {
+                let buffer = match self.file_editor.file() {
+                    Some(file) if file.path() == *path => file.buffer(),
+                    _ => {
+                        let message = format!("{} is not current file", path);
+                        self.menu_bar.set_status(message, renderer);
+                        return Ok(());
+                    }
+                };
+                let replacement = Replacement {
+                    start: 0,
+                    end: buffer.chars().count(),
+                    text: text.clone(),
+                };
+                let caret = position_in_text(text.as_str(), line, 0);
+                self.snippet = None;
+                file_content_manager::replace_ranges(
+                    &mut self.file_editor,
+                    vec![replacement],
+                    caret,
+                    renderer,
+                )?;
+            }
+            ref source => {
+                if let Err(e) = std::fs::write(source.path(), text.as_bytes()) {
+                    let message = format!("Failed to write {}: {}", source.path(), e);
+                    self.menu_bar.set_status(message, renderer);
+                    return Ok(());
+                }
+            }
+        }
+        if let Some(view) = self.diff_view.as_mut() {
+            view.set_text(target, text, renderer);
+        }
+        Ok(())
+    }
+
+    /// Message in status area of menu bar
+    pub fn status(&self) -> String {
+        self.menu_bar.status()
+    }
+
+    /// Handles answers and notifications of language servers
+    pub fn poll_language_servers<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        for event in self.lsp.poll() {
+            match event {
+                LspEvent::Diagnostics { path, diagnostics } => {
+                    let buffer = self.file_buffer(path.as_str());
+                    let diagnostics = diagnostics
+                        .iter()
+                        .map(|d| Diagnostic::from_lsp(path.as_str(), d, buffer.as_str()))
+                        .collect();
+                    self.diagnostics
+                        .set_file(LSP_DIAGNOSTICS, path.as_str(), diagnostics);
+                    self.refresh_file_diagnostics();
+                }
+                LspEvent::Hover(text) if self.modal.is_none() => {
+                    let point = self.file_editor.caret_window_rect().bottom_left();
+                    self.completion.hide();
+                    self.hover.show(text, point, renderer);
+                }
+                LspEvent::Completion(labels) if self.completion_requested => {
+                    self.completion_requested = false;
+                    self.server_words = labels.into_iter().collect();
+                    self.refresh_completion(renderer);
+                }
+                LspEvent::Definition(locations) => {
+                    let mut locations: Vec<Location> = locations
+                        .into_iter()
+                        .map(|location| self.location_from_lsp(location))
+                        .collect();
+                    let result = match locations.len() {
+                        0 => self.go_to_indexed_definition(renderer),
+                        1 => self.open_location(locations.remove(0), renderer),
+                        _ => {
+                            let title = "Definitions".to_owned();
+                            self.open_locations_picker(title, locations, renderer);
+                            Ok(())
+                        }
+                    };
+                    result.unwrap_or_else(|e| error!("{}", e));
+                }
+                LspEvent::Formatting { path, edits } => {
+                    self.apply_text_edits(path, edits, renderer)
+                }
+                LspEvent::Error(e) => error!("Language server: {}", e),
+                _ => (),
+            }
+        }
+    }
+
+    pub fn diagnostics(&self, path: &str) -> Vec<Diagnostic> {
+        self.diagnostics.file(path)
+    }
+
+    /// Replaces every diagnostic reported by provider, for example build tool
+    pub fn set_diagnostics(&mut self, provider: &str, diagnostics: Vec<Diagnostic>) {
+        self.diagnostics.set_all(provider, diagnostics);
+        self.refresh_file_diagnostics();
+    }
+
+    /// Attaches stored diagnostics to current and other opened files
+    fn refresh_file_diagnostics(&mut self) {
+        let store = &self.diagnostics;
+        let files = self
+            .file_editor
+            .file_mut()
+            .into_iter()
+            .chain(self.files.iter_mut());
+        for file in files {
+            file.set_diagnostics(store.file(file.path().as_str()));
+        }
+        self.hovered_diagnostic = None;
+    }
+
+    pub fn hover(&self) -> &HoverPopup {
+        &self.hover
+    }
+
+    /// Text of opened file or file on disk
+    fn file_buffer(&self, path: &str) -> String {
+        self.file_editor
+            .file()
+            .into_iter()
+            .chain(self.files.iter())
+            .find(|file| file.path() == path)
+            .map(|file| file.buffer())
+            .or_else(|| read_to_string(path).ok())
+            .unwrap_or_default()
+    }
+
+    /// Location with char column, text is taken from open file or disk
+    fn location_from_lsp(&self, location: LspLocation) -> Location {
+        let buffer = self.file_buffer(location.path.as_str());
+        let start = location.range.start;
+        let column = offset_at(buffer.as_str(), start)
+            - offset_at(buffer.as_str(), Position::new(start.line, 0));
+        Location {
+            path: location.path,
+            line: start.line,
+            column,
+        }
+    }
+
+    /// Applies edits of language server to current file as one edit
+    fn apply_text_edits<R>(&mut self, path: String, edits: Vec<TextEdit>, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let replacements: Vec<Replacement> = match self.file_editor.file() {
+            Some(file) if file.path() == path => edits
+                .into_iter()
+                .map(|edit| Replacement {
+                    start: offset_at(file.buffer_ref(), edit.range.start),
+                    end: offset_at(file.buffer_ref(), edit.range.end),
+                    text: edit.new_text,
+                })
+                .collect(),
+            _ => return,
+        };
+        if replacements.is_empty() {
+            return;
+        }
+        let caret = self.file_editor.caret().text_position();
+        self.snippet = None;
+        file_content_manager::replace_ranges(&mut self.file_editor, replacements, caret, renderer)
+            .unwrap_or_else(|e| error!("{}", e));
+    }
+
+    /// Runs cargo in project root and shows its output, running task is killed
+    pub fn run_cargo<R>(&mut self, command: CargoCommand, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        self.cargo = None;
+        self.output
+            .start(format!("cargo {}", command.name()), renderer);
+        match CargoRunner::start(
+            self.cargo_program.as_str(),
+            command,
+            self.root_path.as_str(),
+        ) {
+            Ok(runner) => {
+                self.set_diagnostics(CARGO_DIAGNOSTICS, vec![]);
+                self.cargo = Some(runner);
+            }
+            Err(e) => {
+                self.output.push_lines(vec![OutputLine::new(e)], renderer);
+            }
+        }
+    }
+
+    pub fn cancel_cargo(&mut self) {
+        if let Some(runner) = self.cargo.as_mut() {
+            runner.cancel();
+        }
+    }
+
+    /// Streams output of running cargo into output panel and its diagnostics into files
+    pub fn poll_cargo<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let runner = match self.cargo.as_mut() {
+            Some(runner) => runner,
+            _ => return,
+        };
+        let mut lines = vec![];
+        let mut diagnostics = None;
+        let mut status = None;
+        for event in runner.poll() {
+            match event {
+                CargoEvent::Output(line) => lines.push(line),
+                CargoEvent::Diagnostic(_) => diagnostics = Some(runner.diagnostics().clone()),
+                CargoEvent::Finished(true) => status = Some("finished"),
+                CargoEvent::Finished(false) if runner.is_cancelled() => status = Some("cancelled"),
+                CargoEvent::Finished(false) => status = Some("failed"),
+            }
+        }
+        let command = runner.command();
+        self.output.push_lines(lines, renderer);
+        if let Some(diagnostics) = diagnostics {
+            self.set_diagnostics(CARGO_DIAGNOSTICS, diagnostics);
+        }
+        if let Some(status) = status {
+            self.cargo = None;
+            self.output
+                .set_title(format!("cargo {} {}", command.name(), status), renderer);
+        }
+    }
+
+    pub fn is_cargo_running(&self) -> bool {
+        self.cargo.is_some()
+    }
+
+    pub fn toggle_output(&mut self) {
+        self.output.toggle();
+        if self.output.is_visible() {
+            self.terminal.hide();
+            self.commit_panel.hide();
+        }
+    }
+
+    pub fn output(&self) -> &OutputPanel {
+        &self.output
+    }
+
+    /// Shows and focuses terminal, shell is started when there is no tab.
+    ///
+    /// Focused terminal is hidden.
+    pub fn toggle_terminal<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if self.terminal.is_focused() {
+            return self.terminal.hide();
+        }
+        if self.terminal.terminals().is_empty() {
+            return self.open_terminal(renderer);
+        }
+        self.output.hide();
+        self.commit_panel.hide();
+        self.terminal.show();
+    }
+
+    fn open_terminal<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        self.output.hide();
+        self.commit_panel.hide();
+        let shell = self.shell.clone();
+        if let Err(e) = self
+            .terminal
+            .open(shell.as_str(), self.root_path.as_str(), renderer)
+        {
+            self.menu_bar.set_status(e, renderer);
+        }
+    }
+
+    pub fn terminal_action<R>(&mut self, action: TerminalAction, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let page = self.terminal.grid_size().1 as isize;
+        match action {
+            TerminalAction::NewTab => self.open_terminal(renderer),
+            TerminalAction::CloseTab => self.terminal.close_current(renderer),
+            TerminalAction::NextTab => self.terminal.next_tab(renderer),
+            TerminalAction::PreviousTab => self.terminal.previous_tab(renderer),
+            TerminalAction::ScrollUp => self.terminal.scroll(page),
+            TerminalAction::ScrollDown => self.terminal.scroll(-page),
+            TerminalAction::Search => self.terminal.start_search(renderer),
+            TerminalAction::Input(bytes) => self.terminal.input(&bytes, renderer),
+        }
+    }
+
+    /// Reads output of shells, tabs of exited ones are closed
+    pub fn poll_terminals<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        self.terminal.poll(renderer);
+    }
+
+    pub fn is_terminal_focused(&self) -> bool {
+        self.terminal.is_focused()
+    }
+
+    pub fn terminal(&self) -> &TerminalPanel {
+        &self.terminal
+    }
+
+    pub fn completion(&self) -> &CompletionPopup {
+        &self.completion
+    }
+
+    pub fn completion_mut(&mut self) -> &mut CompletionPopup {
+        &mut self.completion
+    }
+
+    pub fn open_directory<R>(&mut self, dir_path: String, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        match self.modal.as_mut() {
+            Some(ModalType::OpenFile(modal)) => modal.open_directory(dir_path, renderer),
+            None => self.project_tree.open_directory(dir_path, renderer),
+            _ => (),
+        };
+    }
+
+    #[cfg_attr(tarpaulin, skip)]
+    pub fn file_editor(&self) -> &FileEditor {
+        &self.file_editor
+    }
+
+    #[cfg_attr(tarpaulin, skip)]
+    pub fn file_editor_mut(&mut self) -> &mut FileEditor {
+        &mut self.file_editor
+    }
+
+    pub fn set_open_file_modal(&mut self, modal: Option<OpenFile>) {
+        self.modal = if let Some(modal) = modal {
+            Some(ModalType::OpenFile(modal))
+        } else {
+            None
+        };
+    }
+
+    pub fn scroll_by(&mut self, x: i32, y: i32) {
+        match self.modal.as_mut() {
+            Some(ModalType::OpenFile(modal)) => modal.scroll_by(x, y),
+            Some(ModalType::Picker(modal)) => modal.scroll_by(x, y),
+            Some(ModalType::Settings(modal)) => modal.scroll_by(x, y),
+            _ => self.file_editor_mut().scroll_by(x, y),
+        };
+    }
+
+    pub fn open_file_modal(&self) -> Option<&OpenFile> {
+        match self.modal {
+            Some(ModalType::OpenFile(ref m)) => Some(m),
+            _ => None,
+        }
+    }
+
+    pub fn picker_modal(&self) -> Option<&Picker> {
+        match self.modal {
+            Some(ModalType::Picker(ref m)) => Some(m),
+            _ => None,
+        }
+    }
+
+    pub fn picker_modal_mut(&mut self) -> Option<&mut Picker> {
+        match self.modal {
+            Some(ModalType::Picker(ref mut m)) => Some(m),
+            _ => None,
+        }
+    }
+
+    pub fn settings_modal(&self) -> Option<&Settings> {
+        match self.modal {
+            Some(ModalType::Settings(ref m)) => Some(m),
+            _ => None,
+        }
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+impl AppState {
+    pub fn render<C, R>(&self, canvas: &mut C, renderer: &mut R, _context: &RenderContext)
+    where
+        C: CanvasAccess,
+        R: Renderer + ConfigHolder + CharacterSizeManager,
+    {
+        // file editor
+        self.file_editor
+            .render(canvas, renderer, &RenderContext::Nothing);
+
+        // blame column
+        if let Some(file) = self.file_editor.file() {
+            self.blame
+                .render(canvas, renderer, file, self.editor_text_top());
+        }
+
+        // menu bar
+        self.menu_bar
+            .render(canvas, renderer, &RenderContext::Nothing);
+
+        // project tree
+        self.project_tree
+            .render(canvas, renderer, &RenderContext::Nothing);
+
+        // cargo output
+        self.output
+            .render(canvas, renderer, &RenderContext::Nothing);
+
+        // terminal
+        self.terminal
+            .render(canvas, renderer, &RenderContext::Nothing);
+
+        // commit panel
+        self.commit_panel
+            .render(canvas, renderer, &RenderContext::Nothing);
+
+        // diff view
+        if let Some(view) = self.diff_view.as_ref() {
+            view.render(canvas, renderer, &RenderContext::Nothing);
+        }
+
+        // completion and hover
+        if self.modal.is_none() {
+            self.completion
+                .render(canvas, renderer, &RenderContext::Nothing);
+            self.hover.render(canvas, renderer, &RenderContext::Nothing);
+        }
+
+        // settings modal
+        match self.modal.as_ref() {
+            Some(ModalType::OpenFile(modal)) => {
+                return modal.render(canvas, renderer, &RenderContext::Nothing)
+            }
+            Some(ModalType::Picker(modal)) => {
+                return modal.render(canvas, renderer, &RenderContext::Nothing)
+            }
+            Some(ModalType::Settings(modal)) => {
+                return modal.render(canvas, renderer, &RenderContext::Nothing)
+            }
+            _ => (),
+        };
+    }
+
+    pub fn prepare_ui<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        self.menu_bar.prepare_ui();
+        self.project_tree.prepare_ui(renderer);
+        self.file_editor.prepare_ui(renderer);
+        self.refresh_git_status(renderer);
+    }
+
+    pub fn update(&mut self, ticks: i32, context: &UpdateContext) -> UpdateResult {
+        if let Some(Ok(index)) = self.index_receiver.as_ref().map(|r| r.try_recv()) {
+            self.symbol_index = index;
+            self.index_receiver = None;
+        }
+
+        let res = match self.modal.as_mut() {
+            Some(ModalType::OpenFile(modal)) => modal.update(ticks, context.clone()),
+            Some(ModalType::Picker(modal)) => modal.update(ticks, context.clone()),
+            Some(ModalType::Settings(modal)) => modal.update(ticks, context.clone()),
+            None => UpdateResult::NoOp,
+        };
+        if res != UpdateResult::NoOp {
+            return res;
+        }
+
+        // menu bar
+        self.menu_bar.update(ticks, context);
+
+        // sidebar
+        self.project_tree.update(ticks, context);
+
+        // cargo output
+        self.output.update(ticks, context);
+
+        // terminal
+        self.terminal.update(ticks, context);
+
+        // commit panel
+        self.commit_panel.update(ticks, context);
+
+        // diff view
+        if let Some(view) = self.diff_view.as_mut() {
+            view.update(ticks, context);
+        }
+
+        // file editor and blame column left of it
+        let editor_start = self.project_tree.full_rect().top_right() + Point::new(10, 0);
+        let context =
+            UpdateContext::ParentPosition(editor_start + Point::new(self.blame.width() as i32, 0));
+        self.file_editor.update(ticks, &context);
+        let editor = self.file_editor.dest().clone();
+        self.blame.set_dest(&Rect::new(
+            editor_start.x(),
+            editor.y(),
+            self.blame.width().max(1),
+            editor.height(),
+        ));
+        UpdateResult::NoOp
+    }
+}
+
+impl AppState {
+    #[cfg_attr(tarpaulin, skip)]
+    pub fn on_left_click(&mut self, point: &Point, video_subsystem: &mut VS) -> UpdateResult {
+        if let Some(view) = self.diff_view.as_mut() {
+            if view.is_left_click_target(point, &UpdateContext::Nothing) {
+                return view.on_left_click(point, &UpdateContext::Nothing);
+            }
+        }
+        if self
+            .project_tree
+            .is_left_click_target(point, &UpdateContext::Nothing)
+        {
+            return self
+                .project_tree
+                .on_left_click(point, &UpdateContext::Nothing);
+        }
+        match self.modal.as_mut() {
+            Some(ModalType::OpenFile(modal)) => {
+                return modal.on_left_click(point, &UpdateContext::Nothing)
+            }
+            Some(ModalType::Picker(modal)) => {
+                return modal.on_left_click(point, &UpdateContext::Nothing)
+            }
+            Some(ModalType::Settings(modal)) => {
+                return modal.on_left_click(point, &UpdateContext::Nothing)
+            }
+            _ => (),
+        };
+        if self
+            .completion
+            .is_left_click_target(point, &UpdateContext::Nothing)
+        {
+            return self
+                .completion
+                .on_left_click(point, &UpdateContext::Nothing);
+        }
+        self.completion.hide();
+        if self
+            .hover
+            .is_left_click_target(point, &UpdateContext::Nothing)
+        {
+            return self.hover.on_left_click(point, &UpdateContext::Nothing);
+        }
+        self.hover.hide();
+        if self
+            .terminal
+            .is_left_click_target(point, &UpdateContext::Nothing)
+        {
+            video_subsystem.text_input().start();
+            return self.terminal.on_left_click(point, &UpdateContext::Nothing);
+        }
+        self.terminal.set_focused(false);
+        if self
+            .commit_panel
+            .is_left_click_target(point, &UpdateContext::Nothing)
+        {
+            video_subsystem.text_input().start();
+            return self
+                .commit_panel
+                .on_left_click(point, &UpdateContext::Nothing);
+        }
+        self.commit_panel.set_focused(false);
+        if self
+            .output
+            .is_left_click_target(point, &UpdateContext::Nothing)
+        {
+            return self.output.on_left_click(point, &UpdateContext::Nothing);
+        }
+        if self
+            .menu_bar
+            .is_left_click_target(point, &UpdateContext::Nothing)
+        {
+            video_subsystem.text_input().stop();
+            return self.menu_bar.on_left_click(point, &UpdateContext::Nothing);
+        } else if !self
+            .file_editor
+            .is_left_click_target(point, &UpdateContext::Nothing)
+        {
+            return UpdateResult::NoOp;
+        } else {
+            video_subsystem.text_input().start();
+            return self
+                .file_editor
+                .on_left_click(point, &UpdateContext::Nothing);
+        }
+    }
+
+    pub fn is_left_click_target(&self, _point: &Point) -> bool {
+        true
+    }
+}
+
+impl ConfigHolder for AppState {
+    fn config(&self) -> &ConfigAccess {
         &self.config
     }
-}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use rider_derive::*;
+
+    #[test]
+    fn must_return_none_for_default_file() {
+        let config = build_config();
+        let state = AppState::new(config.clone());
+        let file = state.file_editor().file();
+        assert_eq!(file.is_none(), true);
+    }
+
+    #[test]
+    fn must_scroll_file_when_no_modal() {
+        let config = build_config();
+        let mut state = AppState::new(config.clone());
+        let old_scroll = state.file_editor().scroll();
+        state.set_open_file_modal(None);
+        state.scroll_by(10, 10);
+        assert_ne!(state.file_editor().scroll(), old_scroll);
+    }
+
+    #[test]
+    fn must_scroll_modal_when_modal_was_set() {
+        let config = build_config();
+        let mut state = AppState::new(config.clone());
+        let modal = OpenFile::new("/".to_owned(), 100, 100, config.clone());
+        let file_scroll = state.file_editor().scroll();
+        let old_scroll = state.file_editor().scroll();
+        state.set_open_file_modal(Some(modal));
+        state.scroll_by(10, 10);
+        assert_eq!(state.file_editor().scroll(), file_scroll);
+        assert_ne!(
+            state
+                .open_file_modal()
+                .unwrap_or_else(|| panic!("Failed to open file modal"))
+                .scroll(),
+            old_scroll
+        );
+    }
+
+    #[test]
+    fn must_fail_save_file_when_none_is_open() {
+        build_test_renderer!(renderer);
+        let mut state = AppState::new(config.clone());
+        let result = state.save_file(&mut renderer);
+        assert_eq!(result, Err(format!("No buffer found")));
+    }
+
+    #[test]
+    fn must_succeed_save_file_when_file_is_open() {
+        assert_eq!(std::fs::create_dir_all("/tmp").is_ok(), true);
+        assert_eq!(
+            std::fs::write(
+                "/tmp/must_succeed_save_file_when_file_is_open.md",
+                "Foo bar"
+            )
+            .is_ok(),
+            true
+        );
+
+        build_test_renderer!(renderer);
+        let mut state = AppState::new(config.clone());
+        let result = state.open_file(
+            format!("/tmp/must_succeed_save_file_when_file_is_open.md"),
+            &mut renderer,
+        );
+        assert_eq!(result, Ok(()));
+        let result = state.save_file(&mut renderer);
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn must_succeed_save_file_when_file_does_not_exists() {
+        assert_eq!(std::fs::create_dir_all("/tmp").is_ok(), true);
+        assert_eq!(
+            std::fs::write(
+                "/tmp/must_succeed_save_file_when_file_does_not_exists.md",
+                "Foo bar"
+            )
+            .is_ok(),
+            true
+        );
+
+        build_test_renderer!(renderer);
+        let mut state = AppState::new(config.clone());
+        let result = state.open_file(
+            format!("/tmp/must_succeed_save_file_when_file_does_not_exists.md"),
+            &mut renderer,
+        );
+        assert_eq!(result, Ok(()));
+        let result = state.save_file(&mut renderer);
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn must_close_modal_when_no_modal_is_open() {
+        let config = build_config();
+        let mut state = AppState::new(config.clone());
+        assert_eq!(state.close_modal(), Ok(()));
+    }
+
+    #[test]
+    fn must_close_modal_when_some_modal_is_open() {
+        let config = build_config();
+        let mut state = AppState::new(config.clone());
+        let modal = OpenFile::new("/".to_owned(), 100, 100, config.clone());
+        state.set_open_file_modal(Some(modal));
+        assert_eq!(state.close_modal(), Ok(()));
+    }
+
+    #[test]
+    fn open_settings_when_there_is_no_other_modal() {
+        build_test_renderer!(renderer);
+        let mut state = AppState::new(config.clone());
+        assert_eq!(state.open_settings(&mut renderer), Ok(()));
+    }
+
+    #[test]
+    fn open_settings_when_other_modal_is_open() {
+        build_test_renderer!(renderer);
+        let mut state = AppState::new(config.clone());
+        let modal = OpenFile::new("/".to_owned(), 100, 100, config.clone());
+        state.set_open_file_modal(Some(modal));
+        assert_eq!(state.open_settings(&mut renderer), Ok(()));
+    }
+
+    #[test]
+    fn must_not_open_outline_without_file() {
+        build_test_renderer!(renderer);
+        let mut state = AppState::new(config.clone());
+        state.open_outline(&mut renderer);
+        assert_eq!(state.picker_modal().is_some(), false);
+    }
+
+    #[test]
+    fn must_jump_to_symbol_from_outline() {
+        build_test_renderer!(renderer);
+        let mut state = AppState::new(config.clone());
+        let mut file = EditorFile::new(
+            "./foo.rs".to_owned(),
+            "fn a() {}\nfn b() {}".to_owned(),
+            config.clone(),
+        );
+        file.prepare_ui(&mut renderer);
+        state.file_editor_mut().open_file(file);
+        state.open_outline(&mut renderer);
+        let result = state
+            .picker_modal_mut()
+            .map(|modal| {
+                modal.select_next();
+                modal.confirm()
+            })
+            .unwrap_or_else(|| panic!("Failed to open outline modal"));
+        assert_eq!(result, UpdateResult::JumpTo(13));
+        state.jump_to(13);
+        assert_eq!(state.picker_modal().is_some(), false);
+        assert_eq!(state.file_editor().caret().text_position(), 13);
+        assert_eq!(state.file_editor().caret().line_number(), 1);
+    }
+
+    #[test]
+    fn must_complete_word_before_caret() {
+        let path = "/tmp/rider/test-app-state/completion.rs";
+        std::fs::create_dir_all("/tmp/rider/test-app-state").unwrap();
+        std::fs::write(path, "let value = 1;\nva\n").unwrap();
+        build_test_renderer!(renderer);
+        let mut state = AppState::new(config.clone());
+        state.open_file(path.to_owned(), &mut renderer).unwrap();
+        state.file_editor_mut().jump_to(17);
+
+        state.update_completion(&mut renderer);
+        assert_eq!(state.completion().is_visible(), true);
+        assert_eq!(state.completion().prefix(), &"va".to_owned());
+        assert_eq!(
+            state.completion().selected_item().map(|item| item.label()),
+            Some("value".to_owned())
+        );
+
+        assert_eq!(state.accept_completion(&mut renderer), true);
+        assert_eq!(state.completion().is_visible(), false);
+        assert_eq!(
+            state.file_editor().file().map(|f| f.buffer()),
+            Some("let value = 1;\nvalue\n".to_owned())
+        );
+        assert_eq!(state.accept_completion(&mut renderer), false);
+    }
+
+    fn wait_for_language_server<R, F>(state: &mut AppState, renderer: &mut R, done: F)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+        F: Fn(&AppState) -> bool,
+    {
+        for _ in 0..500 {
+            state.poll_language_servers(renderer);
+            if done(state) {
+                return;
+            }
+            thread::sleep(std::time::Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn must_sync_file_and_use_language_server() {
+        let path = "/tmp/rider/test-app-state/lsp.rs";
+        std::fs::create_dir_all("/tmp/rider/test-app-state").unwrap();
+        std::fs::write(path, "fn main() {}\n").unwrap();
+        build_test_renderer!(renderer);
+        let script = r#"{
+            "echoText": true,
+            "responses": {
+                "textDocument/hover": { "contents": "fn main()" },
+                "textDocument/definition": {
+                    "uri": "file:///tmp/rider/test-app-state/lsp.rs",
+                    "range": { "start": { "line": 0, "character": 3 }, "end": { "line": 0, "character": 7 } }
+                }
+            }
+        }"#;
+        config.write().unwrap().set_language_server(
+            rider_lexers::Language::Rust,
+            lsp_stub_server("app-state", script),
+        );
+        let mut state = AppState::new(config.clone());
+        state.open_file(path.to_owned(), &mut renderer).unwrap();
+        state.file_editor_mut().jump_to(11);
+        state.insert_text("x".to_owned(), &mut renderer);
+        state.sync_language_server();
+
+        let synced = |state: &AppState| {
+            state
+                .diagnostics(path)
+                .last()
+                .map_or(false, |d| d.message == "fn main() {x}\n")
+        };
+        wait_for_language_server(&mut state, &mut renderer, synced);
+        assert_eq!(synced(&state), true);
+
+        state.show_hover(&mut renderer);
+        wait_for_language_server(&mut state, &mut renderer, |s| s.hover().is_visible());
+        assert_eq!(state.hover().text(), &"fn main()".to_owned());
+
+        state.go_to_definition(&mut renderer).unwrap();
+        wait_for_language_server(&mut state, &mut renderer, |s| {
+            s.file_editor().caret().text_position() == 3
+        });
+        assert_eq!(state.file_editor().caret().text_position(), 3);
+    }
+
+    #[test]
+    fn must_expand_snippet_and_move_through_tab_stops() {
+        let path = "/tmp/rider/test-app-state/snippet.rs";
+        std::fs::create_dir_all("/tmp/rider/test-app-state").unwrap();
+        std::fs::write(path, "    fn\n").unwrap();
+        build_test_renderer!(renderer);
+        let mut state = AppState::new(config.clone());
+        state.open_file(path.to_owned(), &mut renderer).unwrap();
+        state.file_editor_mut().jump_to(6);
+        let snippet = Snippet {
+            prefix: "fn".to_owned(),
+            body: "fn ${1:name}() {\n    $0\n} // $1".to_owned(),
+            description: "".to_owned(),
+        };
+        let buffer = |state: &AppState| state.file_editor().file().map(|f| f.buffer());
+
+        state.expand_snippet(&snippet, 2, &mut renderer);
+        assert_eq!(
+            buffer(&state),
+            Some("    fn name() {\n        \n    } // name\n".to_owned())
+        );
+        assert_eq!(state.file_editor().caret().text_position(), 11);
+
+        state.insert_text("r".to_owned(), &mut renderer);
+        state.insert_text("un".to_owned(), &mut renderer);
+        assert_eq!(
+            buffer(&state),
+            Some("    fn run() {\n        \n    } // run\n".to_owned())
+        );
+        assert_eq!(state.file_editor().caret().text_position(), 10);
+
+        assert_eq!(state.next_snippet_stop(), true);
+        assert_eq!(state.file_editor().caret().text_position(), 23);
+        assert_eq!(state.snippet().is_none(), true);
+        assert_eq!(state.next_snippet_stop(), false);
+    }
+
+    #[test]
+    fn must_undo_snippet_expansion_as_one_edit() {
+        let path = "/tmp/rider/test-app-state/snippet-undo.rs";
+        std::fs::create_dir_all("/tmp/rider/test-app-state").unwrap();
+        std::fs::write(path, "    fn\n").unwrap();
+        build_test_renderer!(renderer);
+        let mut state = AppState::new(config.clone());
+        state.open_file(path.to_owned(), &mut renderer).unwrap();
+        state.file_editor_mut().jump_to(6);
+        let snippet = Snippet {
+            prefix: "fn".to_owned(),
+            body: "fn ${1:name}() {\n    $0\n}".to_owned(),
+            description: "".to_owned(),
+        };
+
+        state.expand_snippet(&snippet, 2, &mut renderer);
+        assert_eq!(state.snippet().is_some(), true);
+        state.undo(&mut renderer);
+        assert_eq!(
+            state.file_editor().file().map(|f| f.buffer()),
+            Some("    fn\n".to_owned())
+        );
+        assert_eq!(state.file_editor().caret().text_position(), 6);
+        assert_eq!(state.snippet().is_none(), true);
+        assert_eq!(state.file_editor_mut().undo(&mut renderer), false);
+    }
+
+    fn build_indexed_project<R>(root: &str, state: &mut AppState, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        std::fs::create_dir_all(root).unwrap();
+        std::fs::write(format!("{}/a.rs", root), "fn main() {\n    run();\n}\n").unwrap();
+        std::fs::write(format!("{}/b.rs", root), "pub fn run() {}\n").unwrap();
+        let mapping = state.config.read().unwrap().extensions_mapping().clone();
+        state.symbol_index = SymbolIndex::scan(root, &mapping);
+        state.root_path = root.to_owned();
+        state.open_file(format!("{}/a.rs", root), renderer).unwrap();
+        state.file_editor_mut().jump_to(16);
+    }
+
+    fn wait_for_formatter<R>(state: &mut AppState, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        for _ in 0..500 {
+            state.poll_formatter(renderer);
+            if state.formatting.is_none() {
+                return;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn must_format_on_save_and_keep_buffer_when_formatter_fails() {
+        let root = "/tmp/rider/test-app-state/format";
+        build_test_renderer!(renderer);
+        let mut state = AppState::new(config.clone());
+        build_indexed_project(root, &mut state, &mut renderer);
+        let path = format!("{}/a.rs", root);
+        let formatter =
+            |name: &str, script: &str| Formatter::new(fake_program(name, script).as_str(), &[]);
+        let buffer = |state: &AppState| state.file_editor().file().map(|f| f.buffer());
+
+        config.write().unwrap().set_formatter(
+            rider_lexers::Language::Rust,
+            formatter("app-state-format", "sed 's/^fn/pub fn/'\n"),
+        );
+        state
+            .menu_bar
+            .set_status("Reloaded a.rs".to_owned(), &mut renderer);
+        assert_eq!(state.save_file(&mut renderer), Ok(()));
+        wait_for_formatter(&mut state, &mut renderer);
+        let formatted = "pub fn main() {\n    run();\n}\n".to_owned();
+        assert_eq!(buffer(&state), Some(formatted.clone()));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), formatted);
+        assert_eq!(state.file_editor().caret().text_position(), 20);
+        assert_eq!(state.status(), "Reloaded a.rs".to_owned());
+
+        config.write().unwrap().set_formatter(
+            rider_lexers::Language::Rust,
+            formatter(
+                "app-state-format-failing",
+                "echo 'error: expected item' >&2\nexit 1\n",
+            ),
+        );
+        state.format_document(&mut renderer);
+        wait_for_formatter(&mut state, &mut renderer);
+        assert_eq!(buffer(&state), Some(formatted));
+        assert_eq!(
+            state.status().ends_with("failed: error: expected item"),
+            true
+        );
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::tests::*;
-    use rider_derive::*;
+    #[test]
+    fn must_drop_formatter_output_when_buffer_was_edited() {
+        let root = "/tmp/rider/test-app-state/format-edited";
+        build_test_renderer!(renderer);
+        let mut state = AppState::new(config.clone());
+        build_indexed_project(root, &mut state, &mut renderer);
+        let path = format!("{}/a.rs", root);
+        let formatter = fake_program("app-state-format-edited", "sed 's/^fn/pub fn/'\n");
+        config.write().unwrap().set_formatter(
+            rider_lexers::Language::Rust,
+            Formatter::new(formatter.as_str(), &[]),
+        );
+
+        assert_eq!(state.save_file(&mut renderer), Ok(()));
+        state.insert_text("x".to_owned(), &mut renderer);
+        wait_for_formatter(&mut state, &mut renderer);
+        let edited = "fn main() {\n    xrun();\n}\n".to_owned();
+        assert_eq!(
+            state.file_editor().file().map(|f| f.buffer()),
+            Some(edited.clone())
+        );
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), edited);
+        assert_eq!(
+            state
+                .status()
+                .ends_with("was edited while it was formatted"),
+            true
+        );
+    }
 
     #[test]
-    fn must_return_none_for_default_file() {
-        let config = build_config();
-        let state = AppState::new(config.clone());
-        let file = state.file_editor().file();
-        assert_eq!(file.is_none(), true);
+    fn must_not_format_buffer_when_save_is_refused() {
+        let root = "/tmp/rider/test-app-state/format-refused";
+        build_test_renderer!(renderer);
+        let mut state = AppState::new(config.clone());
+        build_indexed_project(root, &mut state, &mut renderer);
+        let path = format!("{}/a.rs", root);
+        let formatter = fake_program("app-state-format-refused", "sed 's/^fn/pub fn/'\n");
+        config.write().unwrap().set_formatter(
+            rider_lexers::Language::Rust,
+            Formatter::new(formatter.as_str(), &[]),
+        );
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        std::fs::write(&path, "fn other() {}\n").unwrap();
+
+        assert_eq!(state.save_file(&mut renderer), Ok(()));
+        assert_eq!(
+            state.file_editor().file().map(|f| f.buffer()),
+            Some("fn main() {\n    run();\n}\n".to_owned())
+        );
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "fn other() {}\n".to_owned()
+        );
+        assert_eq!(state.status().ends_with("save again to overwrite it"), true);
     }
 
     #[test]
-    fn must_scroll_file_when_no_modal() {
-        let config = build_config();
+    fn must_keep_caret_on_its_line_when_formatter_adds_lines() {
+        let root = "/tmp/rider/test-app-state/format-lines";
+        build_test_renderer!(renderer);
         let mut state = AppState::new(config.clone());
-        let old_scroll = state.file_editor().scroll();
-        state.set_open_file_modal(None);
-        state.scroll_by(10, 10);
-        assert_ne!(state.file_editor().scroll(), old_scroll);
+        build_indexed_project(root, &mut state, &mut renderer);
+        let formatter = fake_program("app-state-format-lines", "sed '1i // formatted'\n");
+        config.write().unwrap().set_formatter(
+            rider_lexers::Language::Rust,
+            Formatter::new(formatter.as_str(), &[]),
+        );
+
+        state.format_document(&mut renderer);
+        wait_for_formatter(&mut state, &mut renderer);
+        assert_eq!(
+            state.file_editor().file().map(|f| f.buffer()),
+            Some("// formatted\nfn main() {\n    run();\n}\n".to_owned())
+        );
+        assert_eq!(state.file_editor().caret().line_number(), 2);
+        assert_eq!(state.file_editor().caret().text_position(), 29);
     }
 
     #[test]
-    fn must_scroll_modal_when_modal_was_set() {
-        let config = build_config();
+    fn must_undo_formatting_as_one_edit() {
+        let root = "/tmp/rider/test-app-state/format-undo";
+        build_test_renderer!(renderer);
         let mut state = AppState::new(config.clone());
-        let modal = OpenFile::new("/".to_owned(), 100, 100, config.clone());
-        let file_scroll = state.file_editor().scroll();
-        let old_scroll = state.file_editor().scroll();
-        state.set_open_file_modal(Some(modal));
-        state.scroll_by(10, 10);
-        assert_eq!(state.file_editor().scroll(), file_scroll);
-        assert_ne!(
+        build_indexed_project(root, &mut state, &mut renderer);
+        let formatter = fake_program("app-state-format-undo", "sed 's/^/  /'\n");
+        config.write().unwrap().set_formatter(
+            rider_lexers::Language::Rust,
+            Formatter::new(formatter.as_str(), &[]),
+        );
+
+        state.format_document(&mut renderer);
+        wait_for_formatter(&mut state, &mut renderer);
+        assert_eq!(
+            state.file_editor().file().map(|f| f.buffer()),
+            Some("  fn main() {\n      run();\n  }\n".to_owned())
+        );
+        state.undo(&mut renderer);
+        assert_eq!(
+            state.file_editor().file().map(|f| f.buffer()),
+            Some("fn main() {\n    run();\n}\n".to_owned())
+        );
+        assert_eq!(state.file_editor().caret().text_position(), 16);
+        state.undo(&mut renderer);
+        assert_eq!(state.status(), "Nothing to undo".to_owned());
+    }
+
+    #[test]
+    fn must_show_and_revert_change_against_head() {
+        let dir = git_repository("app-state");
+        let path = format!("{}/a.txt", dir);
+        std::fs::write(&path, "first\nchanged\nthird\n").unwrap();
+        build_test_renderer!(renderer);
+        let mut state = AppState::new(config.clone());
+        assert_eq!(state.open_file(path, &mut renderer), Ok(()));
+        assert_eq!(state.file_editor().file().map(|f| f.hunks().len()), Some(1));
+
+        state.file_editor_mut().jump_to(6);
+        state.show_hunk_diff(&mut renderer);
+        assert_eq!(
+            state.hover().text(),
+            &"@@ -2,1 +2,2 @@\n-second\n+changed\n+third".to_owned()
+        );
+
+        assert_eq!(state.revert_hunk(&mut renderer), Ok(()));
+        assert_eq!(
+            state.file_editor().file().map(|f| f.buffer()),
+            Some("first\nsecond\n".to_owned())
+        );
+        assert_eq!(state.file_editor().caret().text_position(), 6);
+        state.poll_git_hunks();
+        assert_eq!(state.file_editor().file().map(|f| f.hunks().len()), Some(1));
+        std::thread::sleep(GIT_HUNKS_DELAY);
+        state.poll_git_hunks();
+        assert_eq!(state.file_editor().file().map(|f| f.hunks().len()), Some(0));
+    }
+
+    #[test]
+    fn must_stage_hunks_and_commit_them() {
+        let dir = git_repository("commit-panel");
+        let path = format!("{}/a.txt", dir);
+        std::fs::write(&path, "zero\nfirst\nsecond\nthird\n").unwrap();
+        build_test_renderer!(renderer);
+        let mut state = AppState::new(config.clone());
+        state.root_path = dir.clone();
+        assert_eq!(state.open_file(path.clone(), &mut renderer), Ok(()));
+        state.toggle_commit_panel(&mut renderer);
+        assert_eq!(state.commit_panel().is_focused(), true);
+        assert_eq!(state.commit_panel().entries().len(), 1);
+
+        // caret at "third"
+        state.file_editor_mut().jump_to(18);
+        state.stage_hunk(&mut renderer);
+        let staged = "first\nsecond\nthird\n".to_owned();
+        assert_eq!(index_content(path.as_str()), Some(staged.clone()));
+        state.unstage_hunk(&mut renderer);
+        assert_eq!(
+            index_content(path.as_str()),
+            Some("first\nsecond\n".to_owned())
+        );
+        state.stage_hunk(&mut renderer);
+
+        state.commit(&mut renderer);
+        assert_eq!(state.status(), "Commit message is empty".to_owned());
+        state
+            .focused_commit_panel_mut()
+            .unwrap()
+            .push_message("Add third", &mut renderer);
+        state.commit(&mut renderer);
+        assert_eq!(state.status(), "Committed".to_owned());
+        assert_eq!(state.commit_panel().message(), &String::new());
+        assert_eq!(head_content(path.as_str()), Some(staged));
+        assert_eq!(state.file_editor().file().map(|f| f.hunks().len()), Some(1));
+
+        state.toggle_staged(path.clone(), &mut renderer);
+        assert_eq!(state.commit_panel().entries()[0].is_staged(), true);
+        state.toggle_staged(path, &mut renderer);
+        assert_eq!(state.commit_panel().entries()[0].is_staged(), false);
+        state
+            .focused_commit_panel_mut()
+            .unwrap()
+            .push_message("Hidden", &mut renderer);
+        state.toggle_commit_panel(&mut renderer);
+        assert_eq!(state.commit_panel().is_visible(), false);
+        state.commit(&mut renderer);
+        assert_eq!(state.status(), "Committed".to_owned());
+        assert_eq!(state.commit_panel().message(), &"Hidden".to_owned());
+    }
+
+    #[test]
+    fn must_blame_lines_and_open_commit() {
+        let dir = git_repository("blame-column");
+        let path = format!("{}/a.txt", dir);
+        build_test_renderer!(renderer);
+        let mut state = AppState::new(config.clone());
+        state.root_path = dir.clone();
+        assert_eq!(state.open_file(path.clone(), &mut renderer), Ok(()));
+        state.toggle_blame(&mut renderer);
+        assert_eq!(state.blame().is_visible(), true);
+        assert_eq!(state.blame().path(), Some(&path));
+        assert_eq!(
             state
-                .open_file_modal()
-                .unwrap_or_else(|| panic!("Failed to open file modal"))
-                .scroll(),
-            old_scroll
+                .blame()
+                .blame()
+                .commit_at(0)
+                .map(|c| c.summary.clone()),
+            Some("init".to_owned())
+        );
+        state.update(0, &UpdateContext::Nothing);
+        assert_eq!(state.file_editor().dest().x(), state.blame().dest().right());
+
+        let point = Point::new(state.blame().dest().x() + 5, state.editor_text_top() + 1);
+        state.on_mouse_move(&point, &mut renderer);
+        assert_eq!(state.hover.is_visible(), true);
+        let other = Point::new(point.x(), state.editor_text_top() + 200);
+        state.on_mouse_move(&other, &mut renderer);
+        assert_eq!(state.hover.is_visible(), false);
+
+        assert_eq!(state.show_blame_commit(&mut renderer), Ok(()));
+        let opened = state.file_editor().file().map(|f| f.path()).unwrap();
+        assert_eq!(opened.ends_with(".diff"), true);
+        assert_eq!(
+            state
+                .file_editor()
+                .file()
+                .map(|f| f.buffer().contains("+first")),
+            Some(true)
         );
+
+        state.toggle_blame(&mut renderer);
+        assert_eq!(state.blame().width(), 0);
     }
 
     #[test]
-    fn must_fail_save_file_when_none_is_open() {
-        let config = build_config();
-        let state = AppState::new(config.clone());
-        let result = state.save_file();
-        assert_eq!(result, Err(format!("No buffer found")));
+    fn must_watch_project_and_refresh_tree() {
+        let dir = "/tmp/rider/watched-project".to_owned();
+        std::fs::remove_dir_all(&dir).unwrap_or_default();
+        std::fs::create_dir_all(format!("{}/sub", dir)).unwrap();
+        build_test_renderer!(renderer);
+        let mut state = AppState::new(config.clone());
+        state.project_tree = ProjectTreeSidebar::new(dir.clone(), config.clone());
+        state.project_tree.prepare_ui(&mut renderer);
+        state.watch_project(dir.clone(), &mut renderer);
+        state.open_directory(format!("{}/sub", dir), &mut renderer);
+
+        std::fs::rename(format!("{}/sub", dir), format!("{}/moved", dir)).unwrap();
+        state.poll_file_system(&mut renderer);
+        assert_eq!(
+            state.project_tree.expanded_directories(),
+            vec![dir.clone(), format!("{}/moved", dir)]
+        );
+
+        state.watch_project("/tmp/rider/not-existing".to_owned(), &mut renderer);
+        assert_eq!(state.status().starts_with("Failed to watch"), true);
     }
 
     #[test]
-    fn must_succeed_save_file_when_file_is_open() {
-        assert_eq!(std::fs::create_dir_all("/tmp").is_ok(), true);
+    fn must_refresh_git_status_once_after_tree_changes() {
+        let dir = git_repository("watched-status");
+        build_test_renderer!(renderer);
+        std::fs::write(format!("{}/out.log", dir), "").unwrap();
+        let mut state = AppState::new(config.clone());
+        state.root_path = dir.clone();
+        state.refresh_git_status(&mut renderer);
+        state.watch_project(dir.clone(), &mut renderer);
+        let status = |state: &AppState, name: &str| {
+            state
+                .git_statuses
+                .file_status(format!("{}/{}", dir, name).as_str())
+        };
+
+        std::fs::write(format!("{}/a.txt", dir), "changed\n").unwrap();
+        std::fs::create_dir(format!("{}/target", dir)).unwrap();
+        std::fs::remove_file(format!("{}/out.log", dir)).unwrap();
+        state.poll_file_system(&mut renderer);
+        assert_eq!(state.git_status_due, None);
+
+        std::fs::write(format!("{}/b.txt", dir), "").unwrap();
+        state.poll_file_system(&mut renderer);
+        assert_eq!(state.git_status_due.is_some(), true);
+        assert_eq!(status(&state, "b.txt"), None);
+
+        state.git_status_due = Some(Instant::now());
+        state.poll_file_system(&mut renderer);
+        assert_eq!(state.git_status_due, None);
+        assert_eq!(status(&state, "b.txt"), Some(GitStatus::Untracked));
+        assert_eq!(status(&state, "a.txt"), Some(GitStatus::Modified));
+    }
+
+    #[test]
+    fn must_reload_or_ask_about_files_changed_on_disk() {
+        let dir = "/tmp/rider/changed-on-disk";
+        std::fs::create_dir_all(dir).unwrap();
+        let path = format!("{}/a.txt", dir);
+        std::fs::write(&path, "first\n").unwrap();
+        build_test_renderer!(renderer);
+        let mut state = AppState::new(config.clone());
+        assert_eq!(state.open_file(path.clone(), &mut renderer), Ok(()));
+        state.watch_project(dir.to_owned(), &mut renderer);
+        let buffer = |state: &AppState| state.file_editor().file().map(|f| f.buffer());
+
+        std::fs::write(&path, "second\n").unwrap();
+        state.poll_file_system(&mut renderer);
+        assert_eq!(buffer(&state), Some("second\n".to_owned()));
+        assert_eq!(state.status(), format!("Reloaded {} changed on disk", path));
+
+        state.insert_text("mine ".to_owned(), &mut renderer);
+        std::fs::write(&path, "third\n").unwrap();
+        state.poll_file_system(&mut renderer);
+        assert_eq!(buffer(&state), Some("mine second\n".to_owned()));
         assert_eq!(
-            std::fs::write(
-                "/tmp/must_succeed_save_file_when_file_is_open.md",
-                "Foo bar"
-            )
-            .is_ok(),
-            true
+            state.picker_modal().map(|modal| modal.confirm()),
+            Some(UpdateResult::ResolveExternalChange(
+                path.clone(),
+                ExternalChangeAction::Reload
+            ))
+        );
+        assert_eq!(state.close_modal(), Ok(()));
+        state.poll_file_system(&mut renderer);
+        assert_eq!(state.picker_modal().is_none(), true);
+
+        state.resolve_external_change(path.clone(), ExternalChangeAction::KeepMine, &mut renderer);
+        assert_eq!(state.save_file(&mut renderer), Ok(()));
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "mine second\n".to_owned()
         );
 
+        std::fs::write(&path, "fourth\n").unwrap();
+        assert_eq!(state.save_file(&mut renderer), Ok(()));
+        assert_eq!(
+            state.status(),
+            format!("{} changed on disk, save again to overwrite it", path)
+        );
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "fourth\n".to_owned()
+        );
+        assert_eq!(state.save_file(&mut renderer), Ok(()));
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "mine second\n".to_owned()
+        );
+
+        std::fs::write(&path, "fifth\n").unwrap();
+        state.insert_text("x".to_owned(), &mut renderer);
+        state.resolve_external_change(path.clone(), ExternalChangeAction::Diff, &mut renderer);
+        assert_eq!(state.diff_view().is_some(), true);
+        state.resolve_external_change(path.clone(), ExternalChangeAction::Reload, &mut renderer);
+        assert_eq!(buffer(&state), Some("fifth\n".to_owned()));
+        assert_eq!(
+            state.file_editor().file().map(|f| f.is_modified()),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn must_keep_undo_of_background_file_reloaded_from_disk() {
+        let dir = "/tmp/rider/changed-on-disk-background";
+        std::fs::create_dir_all(dir).unwrap();
+        let path = format!("{}/a.txt", dir);
+        std::fs::write(&path, "first\n").unwrap();
+        std::fs::write(format!("{}/b.txt", dir), "other\n").unwrap();
         build_test_renderer!(renderer);
         let mut state = AppState::new(config.clone());
-        let result = state.open_file(
-            format!("/tmp/must_succeed_save_file_when_file_is_open.md"),
-            &mut renderer,
+        assert_eq!(state.open_file(path.clone(), &mut renderer), Ok(()));
+        state.insert_text("my ".to_owned(), &mut renderer);
+        assert_eq!(state.save_file(&mut renderer), Ok(()));
+        assert_eq!(
+            state.open_file(format!("{}/b.txt", dir), &mut renderer),
+            Ok(())
         );
-        assert_eq!(result, Ok(()));
-        let result = state.save_file();
-        assert_eq!(result, Ok(()));
+        state.watch_project(dir.to_owned(), &mut renderer);
+
+        std::fs::write(&path, "my second\n").unwrap();
+        state.poll_file_system(&mut renderer);
+        assert_eq!(state.status(), format!("Reloaded {} changed on disk", path));
+        assert_eq!(state.show_file(path.clone(), &mut renderer), Ok(()));
+        let buffer = |state: &AppState| state.file_editor().file().map(|f| f.buffer());
+        assert_eq!(buffer(&state), Some("my second\n".to_owned()));
+        state.undo(&mut renderer);
+        assert_eq!(buffer(&state), Some("my first\n".to_owned()));
+        state.undo(&mut renderer);
+        assert_eq!(buffer(&state), Some("first\n".to_owned()));
     }
 
     #[test]
-    fn must_succeed_save_file_when_file_does_not_exists() {
-        assert_eq!(std::fs::create_dir_all("/tmp").is_ok(), true);
+    fn must_navigate_and_resolve_conflicts() {
+        let dir = "/tmp/rider/conflicts";
+        std::fs::create_dir_all(dir).unwrap();
+        let path = format!("{}/a.txt", dir);
+        let text = "a\n<<<<<<< HEAD\nb\n=======\nc\n>>>>>>> x\nd\n<<<<<<< HEAD\ne\n=======\nf\n>>>>>>> y\n";
+        std::fs::write(&path, text).unwrap();
+        build_test_renderer!(renderer);
+        let mut state = AppState::new(config.clone());
+        assert_eq!(state.open_file(path.clone(), &mut renderer), Ok(()));
         assert_eq!(
-            std::fs::write(
-                "/tmp/must_succeed_save_file_when_file_does_not_exists.md",
-                "Foo bar"
-            )
-            .is_ok(),
-            true
+            state.status(),
+            "2 merge conflicts, CTRL+ALT+O / T / B accepts ours / theirs / both".to_owned()
+        );
+
+        assert_eq!(
+            state.resolve_conflict(ConflictResolution::Ours, &mut renderer),
+            Ok(())
         );
+        assert_eq!(state.status(), "No conflict at caret".to_owned());
+        state.jump_to_conflict(false);
+        assert_eq!(state.file_editor().caret().line_number(), 7);
+        state.jump_to_conflict(true);
+        assert_eq!(state.file_editor().caret().line_number(), 1);
 
+        assert_eq!(
+            state.resolve_conflict(ConflictResolution::Theirs, &mut renderer),
+            Ok(())
+        );
+        assert_eq!(state.file_editor().caret().line_number(), 1);
+        state.jump_to_conflict(true);
+        assert_eq!(
+            state.resolve_conflict(ConflictResolution::Both, &mut renderer),
+            Ok(())
+        );
+        assert_eq!(
+            state.file_editor().file().map(|f| f.buffer()),
+            Some("a\nc\nd\ne\nf\n".to_owned())
+        );
+        assert_eq!(
+            state.file_editor().file().map(|f| f.conflicts().len()),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn must_undo_conflict_resolution_as_one_edit() {
+        let dir = "/tmp/rider/conflicts-undo";
+        std::fs::create_dir_all(dir).unwrap();
+        let path = format!("{}/a.txt", dir);
+        let text = "a\n<<<<<<< HEAD\nb\n=======\nc\n>>>>>>> x\nd\n";
+        std::fs::write(&path, text).unwrap();
         build_test_renderer!(renderer);
         let mut state = AppState::new(config.clone());
-        let result = state.open_file(
-            format!("/tmp/must_succeed_save_file_when_file_does_not_exists.md"),
-            &mut renderer,
+        assert_eq!(state.open_file(path.clone(), &mut renderer), Ok(()));
+        state.jump_to_conflict(true);
+
+        assert_eq!(
+            state.resolve_conflict(ConflictResolution::Both, &mut renderer),
+            Ok(())
         );
-        assert_eq!(result, Ok(()));
-        let result = state.save_file();
-        assert_eq!(result, Ok(()));
+        assert_eq!(
+            state.file_editor().file().map(|f| f.buffer()),
+            Some("a\nb\nc\nd\n".to_owned())
+        );
+        state.undo(&mut renderer);
+        assert_eq!(
+            state.file_editor().file().map(|f| f.buffer()),
+            Some(text.to_owned())
+        );
+        assert_eq!(
+            state.file_editor().file().map(|f| f.conflicts().len()),
+            Some(1)
+        );
+        assert_eq!(state.file_editor().caret().line_number(), 1);
+        assert_eq!(state.file_editor_mut().undo(&mut renderer), false);
     }
 
     #[test]
-    fn must_close_modal_when_no_modal_is_open() {
-        let config = build_config();
+    fn must_compare_buffer_and_copy_hunks() {
+        let dir = git_repository("diff-view");
+        let path = format!("{}/a.txt", dir);
+        let other = format!("{}/b.txt", dir);
+        std::fs::write(&path, "zero\nfirst\nsecond\n").unwrap();
+        std::fs::write(&other, "first\nsecond\nthird\n").unwrap();
+        build_test_renderer!(renderer);
         let mut state = AppState::new(config.clone());
+        state.root_path = dir.clone();
+        assert_eq!(state.open_file(path.clone(), &mut renderer), Ok(()));
+
+        state.compare_with_head(&mut renderer);
+        let view = state.diff_view().unwrap();
+        assert_eq!(
+            view.side_source(DiffSide::Left),
+            &DiffSource::Head(path.clone())
+        );
+        assert_eq!(view.hunks().len(), 1);
+        assert_eq!(state.copy_hunk(0, DiffSide::Left, &mut renderer), Ok(()));
+        assert_eq!(state.status(), format!("{} (HEAD) is read only", path));
         assert_eq!(state.close_modal(), Ok(()));
+        assert_eq!(state.diff_view().is_none(), true);
+
+        state.open_compare_picker(&mut renderer);
+        assert_eq!(state.picker_modal().is_some(), true);
+        state.compare_with_file(other.clone(), &mut renderer);
+        assert_eq!(state.picker_modal().is_none(), true);
+        assert_eq!(state.diff_view().unwrap().hunks().len(), 2);
+
+        // removes "zero" from buffer
+        assert_eq!(state.copy_hunk(0, DiffSide::Right, &mut renderer), Ok(()));
+        assert_eq!(
+            state.file_editor().file().map(|f| f.buffer()),
+            Some("first\nsecond\n".to_owned())
+        );
+        assert_eq!(state.diff_view().unwrap().hunks().len(), 1);
+        // removes "third" from other file
+        assert_eq!(state.copy_hunk(0, DiffSide::Left, &mut renderer), Ok(()));
+        assert_eq!(
+            read_to_string(&other).unwrap(),
+            "first\nsecond\n".to_owned()
+        );
+        assert_eq!(state.diff_view().unwrap().hunks().len(), 0);
     }
 
     #[test]
-    fn must_close_modal_when_some_modal_is_open() {
-        let config = build_config();
+    fn must_go_to_definition_in_other_file_and_back() {
+        let root = "/tmp/rider/test-app-state/go-to-definition";
+        build_test_renderer!(renderer);
         let mut state = AppState::new(config.clone());
-        let modal = OpenFile::new("/".to_owned(), 100, 100, config.clone());
-        state.set_open_file_modal(Some(modal));
-        assert_eq!(state.close_modal(), Ok(()));
+        build_indexed_project(root, &mut state, &mut renderer);
+        let path = |state: &AppState| state.file_editor().file().map(|f| f.path());
+
+        assert_eq!(state.go_to_definition(&mut renderer), Ok(()));
+        assert_eq!(path(&state), Some(format!("{}/b.rs", root)));
+        assert_eq!(state.file_editor().caret().text_position(), 7);
+
+        assert_eq!(state.jump_back(&mut renderer), Ok(()));
+        assert_eq!(path(&state), Some(format!("{}/a.rs", root)));
+        assert_eq!(state.file_editor().caret().text_position(), 16);
+
+        assert_eq!(state.jump_forward(&mut renderer), Ok(()));
+        assert_eq!(path(&state), Some(format!("{}/b.rs", root)));
+        assert_eq!(state.file_editor().caret().text_position(), 7);
     }
 
     #[test]
-    fn open_settings_when_there_is_no_other_modal() {
+    fn must_report_locations_which_can_not_be_opened() {
+        let root = "/tmp/rider/test-app-state/missing-location";
         build_test_renderer!(renderer);
         let mut state = AppState::new(config.clone());
-        assert_eq!(state.open_settings(&mut renderer), Ok(()));
+        build_indexed_project(root, &mut state, &mut renderer);
+        let missing = Location {
+            path: format!("{}/missing.rs", root),
+            line: 0,
+            column: 0,
+        };
+        let path = |state: &AppState| state.file_editor().file().map(|f| f.path());
+
+        assert_eq!(state.open_location(missing.clone(), &mut renderer), Ok(()));
+        assert_eq!(path(&state), Some(format!("{}/a.rs", root)));
+        assert_eq!(state.status().starts_with(missing.path.as_str()), true);
+        assert_eq!(state.jump_back(&mut renderer), Ok(()));
+        assert_eq!(state.file_editor().caret().text_position(), 16);
+
+        state.jump_list.push(missing);
+        assert_eq!(state.jump_back(&mut renderer), Ok(()));
+        assert_eq!(path(&state), Some(format!("{}/a.rs", root)));
+        assert_eq!(state.jump_list, JumpList::new());
     }
 
     #[test]
-    fn open_settings_when_other_modal_is_open() {
+    fn must_list_references_in_picker() {
+        let root = "/tmp/rider/test-app-state/find-references";
         build_test_renderer!(renderer);
         let mut state = AppState::new(config.clone());
-        let modal = OpenFile::new("/".to_owned(), 100, 100, config.clone());
-        state.set_open_file_modal(Some(modal));
-        assert_eq!(state.open_settings(&mut renderer), Ok(()));
+        build_indexed_project(root, &mut state, &mut renderer);
+        state.find_references(&mut renderer);
+        let texts: Vec<String> = state
+            .picker_modal()
+            .map(|modal| modal.matches().iter().map(|e| e.text.clone()).collect())
+            .unwrap_or_default();
+        assert_eq!(texts, vec!["a.rs:2".to_owned(), "b.rs:1".to_owned()]);
+    }
+
+    #[test]
+    fn must_attach_diagnostics_and_list_problems() {
+        let root = "/tmp/rider/test-app-state/problems";
+        build_test_renderer!(renderer);
+        let mut state = AppState::new(config.clone());
+        build_indexed_project(root, &mut state, &mut renderer);
+        let location = Location {
+            path: format!("{}/a.rs", root),
+            line: 1,
+            column: 4,
+        };
+        state.set_diagnostics(
+            "cargo",
+            vec![Diagnostic {
+                location: location.clone(),
+                end_line: 1,
+                end_column: 7,
+                severity: DiagnosticSeverity::Error,
+                message: "cannot find function `run`".to_owned(),
+                source: "rustc".to_owned(),
+            }],
+        );
+        assert_eq!(
+            state.file_editor().file().map(|f| f.diagnostics().len()),
+            Some(1)
+        );
+
+        state.show_hover(&mut renderer);
+        assert_eq!(
+            state.hover().text(),
+            &"rustc: cannot find function `run`".to_owned()
+        );
+
+        state.open_problems(&mut renderer);
+        let entries: Vec<(String, UpdateResult)> = state
+            .picker_modal()
+            .map(|modal| {
+                modal
+                    .matches()
+                    .iter()
+                    .map(|e| (e.text.clone(), e.result()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        assert_eq!(
+            entries,
+            vec![(
+                "error a.rs:2  rustc: cannot find function `run`".to_owned(),
+                UpdateResult::OpenLocation(location)
+            )]
+        );
+    }
+
+    #[test]
+    fn must_run_cargo_and_attach_its_diagnostics() {
+        let root = "/tmp/rider/test-app-state/cargo";
+        build_test_renderer!(renderer);
+        let mut state = AppState::new(config.clone());
+        build_indexed_project(root, &mut state, &mut renderer);
+        let message = r#"{"reason":"compiler-message","message":{"rendered":"warning: unused\n","code":null,"level":"warning","message":"unused","spans":[{"column_end":8,"column_start":5,"file_name":"a.rs","is_primary":true,"line_end":2,"line_start":2}]}}"#;
+        state.cargo_program = fake_program(
+            "app-state",
+            format!("printf '%s\\n' '{}'\necho 'Finished'\n", message).as_str(),
+        );
+
+        state.run_cargo(CargoCommand::Clippy, &mut renderer);
+        for _ in 0..500 {
+            state.poll_cargo(&mut renderer);
+            if !state.is_cargo_running() {
+                break;
+            }
+            thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(state.output().title(), &"cargo clippy finished".to_owned());
+        let texts: Vec<String> = state
+            .output()
+            .lines()
+            .iter()
+            .map(|line| line.text.clone())
+            .collect();
+        assert_eq!(
+            texts,
+            vec!["warning: unused".to_owned(), "Finished".to_owned()]
+        );
+        let diagnostics: Vec<(usize, usize)> = state
+            .file_editor()
+            .file()
+            .map(|f| f.diagnostics().clone())
+            .unwrap_or_default()
+            .iter()
+            .map(|d| (d.location.line, d.location.column))
+            .collect();
+        assert_eq!(diagnostics, vec![(1, 4)]);
     }
 
     #[test]
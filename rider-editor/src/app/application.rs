@@ -1,4 +1,9 @@
 pub use crate::app::app_state::AppState;
+use crate::app::cargo_runner::CargoCommand;
+use crate::app::external_change::ExternalChangeAction;
+use crate::app::git::ConflictResolution;
+use crate::app::symbol_index::Location;
+use crate::app::terminal::{key_bytes, TerminalAction};
 pub use crate::renderer::CanvasRenderer;
 use crate::ui::caret::{CaretPosition, MoveDirection};
 use crate::ui::*;
@@ -8,6 +13,7 @@ use sdl2::event::*;
 use sdl2::hint;
 use sdl2::keyboard::Keycode;
 use sdl2::keyboard::Scancode;
+use sdl2::messagebox::{show_simple_message_box, MESSAGEBOX_WARNING};
 use sdl2::mouse::*;
 use sdl2::pixels::Color;
 use sdl2::rect::{Point, Rect};
@@ -33,16 +39,59 @@ pub enum UpdateResult {
     MouseLeftClicked(Point),
     MouseDragStart(Point),
     MouseDragStop(Point),
+    MouseMoved(Point),
     MoveCaret(Rect, CaretPosition),
     DeleteFront,
     DeleteBack,
     DeleteLine,
+    Undo,
     Input(String),
     InsertNewLine,
     MoveCaretLeft,
     MoveCaretRight,
     MoveCaretUp,
     MoveCaretDown,
+    MoveCaretToMatchingBracket,
+    FoldCurrent,
+    UnfoldCurrent,
+    FoldAll,
+    UnfoldAll,
+    FoldLevel(usize),
+    OpenOutline,
+    JumpTo(usize),
+    GoToDefinition,
+    FindReferences,
+    OpenLocation(Location),
+    JumpBack,
+    JumpForward,
+    AcceptCompletion,
+    TabPressed,
+    ShowHover,
+    FormatDocument,
+    OpenProblems,
+    RevertHunk,
+    ShowHunkDiff,
+    ToggleCommitPanel,
+    ToggleStaged(String),
+    StageHunk,
+    UnstageHunk,
+    Commit,
+    CompareWithHead,
+    CompareWithDisk,
+    OpenCompareFile,
+    CompareWithFile(String),
+    CopyHunk(usize, DiffSide),
+    ResolveConflict(ConflictResolution),
+    NextConflict,
+    PreviousConflict,
+    ToggleBlame,
+    ShowBlameCommit,
+    ResolveExternalChange(String, ExternalChangeAction),
+    RunCargo(CargoCommand),
+    CancelCargo,
+    ToggleOutput,
+    ToggleTerminal,
+    Terminal(TerminalAction),
     Scroll { x: i32, y: i32 },
     WindowResize { width: i32, height: i32 },
     RefreshFsTree,
@@ -74,6 +123,14 @@ impl Application {
 
         let mut config = Config::new();
         config.set_theme(config.editor_config().current_theme().clone());
+        let syntax_errors = config.load_syntaxes();
+        let snippet_errors = config.load_snippets();
+        config
+            .load_language_servers()
+            .unwrap_or_else(|e| error!("Failed to load language servers {}", e));
+        config
+            .load_formatters()
+            .unwrap_or_else(|e| error!("Failed to load formatters {}", e));
         let config = Arc::new(RwLock::new(config));
         let sdl_context = sdl2::init().unwrap();
 
@@ -100,6 +157,31 @@ impl Application {
         let mut icon = Surface::load_bmp_rw(&mut rw).unwrap();
         window.set_icon(&mut icon);
 
+        if !syntax_errors.is_empty() {
+            for e in syntax_errors.iter() {
+                error!("Failed to load syntax {}", e);
+            }
+            show_simple_message_box(
+                MESSAGEBOX_WARNING,
+                "Failed to load syntaxes",
+                syntax_errors.join("\n").as_str(),
+                &window,
+            )
+            .unwrap_or_else(|e| error!("{:?}", e));
+        }
+        if !snippet_errors.is_empty() {
+            for e in snippet_errors.iter() {
+                error!("Failed to load snippets {}", e);
+            }
+            show_simple_message_box(
+                MESSAGEBOX_WARNING,
+                "Failed to load snippets",
+                snippet_errors.join("\n").as_str(),
+                &window,
+            )
+            .unwrap_or_else(|e| error!("{:?}", e));
+        }
+
         let canvas = window.into_canvas().accelerated().build().unwrap();
         let clear_color: Color = { config.read().unwrap().theme().background().into() };
 
@@ -127,9 +209,15 @@ impl Application {
         let mut renderer =
             CanvasRenderer::new(Arc::clone(&self.config), &font_context, &texture_creator);
         app_state.prepare_ui(&mut renderer);
+        app_state.start_indexing(Self::current_working_directory());
+        app_state.watch_project(Self::current_working_directory(), &mut renderer);
 
         'running: loop {
-            self.handle_events(&mut event_pump);
+            self.handle_events(
+                &mut event_pump,
+                app_state.is_terminal_focused(),
+                app_state.commit_panel().is_focused(),
+            );
             let mut new_tasks: Vec<UpdateResult> = vec![];
             for task in self.tasks.iter() {
                 match task {
@@ -147,38 +235,150 @@ impl Application {
                                 app_state.set_open_file_modal(None);
                             }
                             UpdateResult::SaveCurrentFile => new_tasks.push(res),
+                            UpdateResult::JumpTo(_) => new_tasks.push(res),
+                            UpdateResult::OpenLocation(_) => new_tasks.push(res),
+                            UpdateResult::AcceptCompletion => new_tasks.push(res),
+                            UpdateResult::ToggleStaged(_) => new_tasks.push(res),
+                            UpdateResult::CompareWithFile(_) => new_tasks.push(res),
+                            UpdateResult::CopyHunk(_, _) => new_tasks.push(res),
+                            UpdateResult::ResolveExternalChange(_, _) => new_tasks.push(res),
                             _ => {}
                         }
                     }
                     UpdateResult::DeleteFront => {
-                        app_state.file_editor_mut().delete_front(&mut renderer);
+                        if let Some(modal) = app_state.picker_modal_mut() {
+                            modal.pop_filter(&mut renderer);
+                        } else if let Some(panel) = app_state.focused_commit_panel_mut() {
+                            panel.pop_message(&mut renderer);
+                        } else {
+                            app_state.delete_front(&mut renderer);
+                        }
                     }
                     UpdateResult::DeleteBack => {
+                        app_state.end_snippet();
                         app_state.file_editor_mut().delete_back(&mut renderer);
                     }
-                    UpdateResult::Input(text) => app_state
-                        .file_editor_mut()
-                        .insert_text(text.clone(), &mut renderer),
+                    UpdateResult::Input(text) => {
+                        if let Some(modal) = app_state.picker_modal_mut() {
+                            modal.push_filter(text.clone(), &mut renderer);
+                        } else if let Some(panel) = app_state.focused_commit_panel_mut() {
+                            panel.push_message(text.as_str(), &mut renderer);
+                        } else {
+                            app_state.insert_text(text.clone(), &mut renderer);
+                        }
+                    }
                     UpdateResult::InsertNewLine => {
-                        app_state.file_editor_mut().insert_new_line(&mut renderer)?
+                        if let Some(modal) = app_state.picker_modal() {
+                            new_tasks.push(modal.confirm());
+                        } else if let Some(panel) = app_state.focused_commit_panel_mut() {
+                            panel.push_message("\n", &mut renderer);
+                        } else if !app_state.accept_completion(&mut renderer) {
+                            app_state.end_snippet();
+                            app_state.file_editor_mut().insert_new_line(&mut renderer)?;
+                        }
+                    }
+                    UpdateResult::AcceptCompletion => {
+                        app_state.accept_completion(&mut renderer);
+                    }
+                    UpdateResult::TabPressed => app_state.on_tab(&mut renderer),
+                    UpdateResult::ShowHover => app_state.show_hover(&mut renderer),
+                    UpdateResult::FormatDocument => app_state.format_document(&mut renderer),
+                    UpdateResult::OpenProblems => app_state.open_problems(&mut renderer),
+                    UpdateResult::RevertHunk => app_state.revert_hunk(&mut renderer)?,
+                    UpdateResult::ShowHunkDiff => app_state.show_hunk_diff(&mut renderer),
+                    UpdateResult::ToggleCommitPanel => app_state.toggle_commit_panel(&mut renderer),
+                    UpdateResult::ToggleStaged(path) => {
+                        app_state.toggle_staged(path.clone(), &mut renderer)
+                    }
+                    UpdateResult::StageHunk => app_state.stage_hunk(&mut renderer),
+                    UpdateResult::UnstageHunk => app_state.unstage_hunk(&mut renderer),
+                    UpdateResult::Commit => app_state.commit(&mut renderer),
+                    UpdateResult::CompareWithHead => app_state.compare_with_head(&mut renderer),
+                    UpdateResult::CompareWithDisk => app_state.compare_with_disk(&mut renderer),
+                    UpdateResult::OpenCompareFile => app_state.open_compare_picker(&mut renderer),
+                    UpdateResult::CompareWithFile(path) => {
+                        app_state.compare_with_file(path.clone(), &mut renderer)
+                    }
+                    UpdateResult::CopyHunk(hunk, side) => {
+                        app_state.copy_hunk(*hunk, *side, &mut renderer)?
+                    }
+                    UpdateResult::ResolveConflict(resolution) => {
+                        app_state.resolve_conflict(*resolution, &mut renderer)?
+                    }
+                    UpdateResult::NextConflict => app_state.jump_to_conflict(true),
+                    UpdateResult::PreviousConflict => app_state.jump_to_conflict(false),
+                    UpdateResult::ToggleBlame => app_state.toggle_blame(&mut renderer),
+                    UpdateResult::ShowBlameCommit => app_state.show_blame_commit(&mut renderer)?,
+                    UpdateResult::ResolveExternalChange(path, action) => {
+                        app_state.resolve_external_change(path.clone(), *action, &mut renderer)
+                    }
+                    UpdateResult::RunCargo(command) => app_state.run_cargo(*command, &mut renderer),
+                    UpdateResult::CancelCargo => app_state.cancel_cargo(),
+                    UpdateResult::ToggleOutput => app_state.toggle_output(),
+                    UpdateResult::ToggleTerminal => app_state.toggle_terminal(&mut renderer),
+                    UpdateResult::Terminal(action) => {
+                        app_state.terminal_action(action.clone(), &mut renderer)
+                    }
+                    UpdateResult::Undo => app_state.undo(&mut renderer),
+                    UpdateResult::DeleteLine => {
+                        app_state.end_snippet();
+                        app_state
+                            .file_editor_mut()
+                            .delete_current_line(&mut renderer)?
                     }
-                    UpdateResult::DeleteLine => app_state
-                        .file_editor_mut()
-                        .delete_current_line(&mut renderer)?,
                     UpdateResult::MoveCaretLeft => {
+                        app_state.hide_popups();
                         app_state.file_editor_mut().move_caret(MoveDirection::Left);
                     }
                     UpdateResult::MoveCaretRight => {
+                        app_state.hide_popups();
                         app_state.file_editor_mut().move_caret(MoveDirection::Right);
                     }
                     UpdateResult::MoveCaretUp => {
-                        app_state.file_editor_mut().move_caret(MoveDirection::Up);
+                        if let Some(modal) = app_state.picker_modal_mut() {
+                            modal.select_previous();
+                        } else if app_state.completion().is_visible() {
+                            app_state.completion_mut().select_previous();
+                        } else {
+                            app_state.hide_popups();
+                            app_state.file_editor_mut().move_caret(MoveDirection::Up);
+                        }
                     }
                     UpdateResult::MoveCaretDown => {
-                        app_state.file_editor_mut().move_caret(MoveDirection::Down);
+                        if let Some(modal) = app_state.picker_modal_mut() {
+                            modal.select_next();
+                        } else if app_state.completion().is_visible() {
+                            app_state.completion_mut().select_next();
+                        } else {
+                            app_state.hide_popups();
+                            app_state.file_editor_mut().move_caret(MoveDirection::Down);
+                        }
+                    }
+                    UpdateResult::MoveCaretToMatchingBracket => {
+                        app_state.file_editor_mut().move_caret_to_matching_bracket();
                     }
+                    UpdateResult::FoldCurrent => app_state.file_editor_mut().fold_current(),
+                    UpdateResult::UnfoldCurrent => app_state.file_editor_mut().unfold_current(),
+                    UpdateResult::FoldAll => app_state.file_editor_mut().fold_all(),
+                    UpdateResult::UnfoldAll => app_state.file_editor_mut().unfold_all(),
+                    UpdateResult::FoldLevel(level) => {
+                        app_state.file_editor_mut().fold_level(*level)
+                    }
+                    UpdateResult::OpenOutline => app_state.open_outline(&mut renderer),
+                    UpdateResult::JumpTo(position) => app_state.jump_to(*position),
+                    UpdateResult::GoToDefinition => app_state.go_to_definition(&mut renderer)?,
+                    UpdateResult::FindReferences => app_state.find_references(&mut renderer),
+                    UpdateResult::OpenLocation(location) => {
+                        app_state.open_location(location.clone(), &mut renderer)?
+                    }
+                    UpdateResult::JumpBack => app_state.jump_back(&mut renderer)?,
+                    UpdateResult::JumpForward => app_state.jump_forward(&mut renderer)?,
                     UpdateResult::Scroll { x, y } => {
-                        app_state.scroll_by(-x.clone(), -y.clone());
+                        if let Some(view) = app_state.diff_view_mut() {
+                            view.scroll(-y.clone(), &mut renderer);
+                        } else {
+                            app_state.scroll_by(-x.clone(), -y.clone());
+                        }
                     }
                     UpdateResult::WindowResize { width, height } => app_state
                         .config()
@@ -192,7 +392,7 @@ impl Application {
                             }
                         })
                         .map_err(|_| format!("Failed to update window size"))?,
-                    UpdateResult::RefreshFsTree => unimplemented!(),
+                    UpdateResult::RefreshFsTree => app_state.refresh_fs_tree(&mut renderer),
                     UpdateResult::OpenFile(file_path) => {
                         app_state.open_file(file_path.clone(), &mut renderer)?;
                     }
@@ -209,8 +409,11 @@ impl Application {
                     }
                     UpdateResult::MouseDragStart(_point) => (),
                     UpdateResult::MouseDragStop(_point) => (),
+                    UpdateResult::MouseMoved(point) => {
+                        app_state.on_mouse_move(point, &mut renderer)
+                    }
                     UpdateResult::FileDropped(_path) => (),
-                    UpdateResult::SaveCurrentFile => app_state.save_file()?,
+                    UpdateResult::SaveCurrentFile => app_state.save_file(&mut renderer)?,
                     UpdateResult::OpenSettings => app_state.open_settings(&mut renderer)?,
                     UpdateResult::CloseModal => app_state.close_modal()?,
                 }
@@ -219,6 +422,13 @@ impl Application {
 
             self.clear();
 
+            app_state.sync_language_server();
+            app_state.poll_language_servers(&mut renderer);
+            app_state.poll_cargo(&mut renderer);
+            app_state.poll_formatter(&mut renderer);
+            app_state.poll_terminals(&mut renderer);
+            app_state.poll_file_system(&mut renderer);
+            app_state.poll_git_hunks();
             app_state.update(timer.ticks() as i32, &UpdateContext::Nothing);
             app_state.render(&mut self.canvas, &mut renderer, &RenderContext::Nothing);
 
@@ -245,7 +455,12 @@ impl Application {
         self.canvas.clear();
     }
 
-    fn handle_events(&mut self, event_pump: &mut EventPump) {
+    fn handle_events(
+        &mut self,
+        event_pump: &mut EventPump,
+        terminal_focused: bool,
+        commit_panel_focused: bool,
+    ) {
         let left_control_pressed = event_pump
             .keyboard_state()
             .is_scancode_pressed(Scancode::LCtrl);
@@ -255,6 +470,12 @@ impl Application {
             || event_pump
                 .keyboard_state()
                 .is_scancode_pressed(Scancode::RShift);
+        let alt_pressed = event_pump
+            .keyboard_state()
+            .is_scancode_pressed(Scancode::LAlt)
+            || event_pump
+                .keyboard_state()
+                .is_scancode_pressed(Scancode::RAlt);
 
         for event in event_pump.poll_iter() {
             match event {
@@ -267,6 +488,9 @@ impl Application {
                     self.tasks
                         .push(UpdateResult::MouseLeftClicked(Point::new(x, y)));
                 }
+                Event::MouseMotion { x, y, .. } => {
+                    self.tasks.push(UpdateResult::MouseMoved(Point::new(x, y)))
+                }
                 Event::DropFile { filename, .. } => {
                     self.tasks.push(UpdateResult::FileDropped(filename))
                 }
@@ -275,6 +499,14 @@ impl Application {
                 } if mouse_btn == MouseButton::Left => self
                     .tasks
                     .push(UpdateResult::MouseDragStart(Point::new(x, y))),
+                Event::KeyDown {
+                    keycode: Some(keycode),
+                    ..
+                } if terminal_focused => {
+                    if let Some(task) = terminal_key(keycode, left_control_pressed, shift_pressed) {
+                        self.tasks.push(task);
+                    }
+                }
                 Event::KeyDown { keycode, .. } if keycode.is_some() => match keycode.unwrap() {
                     Keycode::Escape => {
                         self.tasks.push(UpdateResult::CloseModal);
@@ -288,9 +520,45 @@ impl Application {
                     Keycode::Delete if shift_pressed => {
                         self.tasks.push(UpdateResult::DeleteLine);
                     }
+                    Keycode::KpEnter | Keycode::Return
+                        if left_control_pressed && commit_panel_focused =>
+                    {
+                        self.tasks.push(UpdateResult::Commit);
+                    }
                     Keycode::KpEnter | Keycode::Return => {
                         self.tasks.push(UpdateResult::InsertNewLine);
                     }
+                    Keycode::Tab => {
+                        self.tasks.push(UpdateResult::TabPressed);
+                    }
+                    Keycode::Left if alt_pressed => {
+                        self.tasks.push(UpdateResult::JumpBack);
+                    }
+                    Keycode::Right if alt_pressed => {
+                        self.tasks.push(UpdateResult::JumpForward);
+                    }
+                    Keycode::F5 if shift_pressed => {
+                        self.tasks.push(UpdateResult::CancelCargo);
+                    }
+                    Keycode::F5 => {
+                        self.tasks.push(UpdateResult::RunCargo(CargoCommand::Check));
+                    }
+                    Keycode::F6 => {
+                        self.tasks.push(UpdateResult::RunCargo(CargoCommand::Build));
+                    }
+                    Keycode::F7 => {
+                        self.tasks.push(UpdateResult::RunCargo(CargoCommand::Test));
+                    }
+                    Keycode::F8 => {
+                        self.tasks
+                            .push(UpdateResult::RunCargo(CargoCommand::Clippy));
+                    }
+                    Keycode::F12 if shift_pressed => {
+                        self.tasks.push(UpdateResult::FindReferences);
+                    }
+                    Keycode::F12 => {
+                        self.tasks.push(UpdateResult::GoToDefinition);
+                    }
                     Keycode::Left => {
                         self.tasks.push(UpdateResult::MoveCaretLeft);
                     }
@@ -303,14 +571,111 @@ impl Application {
                     Keycode::Down => {
                         self.tasks.push(UpdateResult::MoveCaretDown);
                     }
+                    Keycode::O if left_control_pressed && alt_pressed => {
+                        let resolution = ConflictResolution::Ours;
+                        self.tasks.push(UpdateResult::ResolveConflict(resolution))
+                    }
+                    Keycode::T if left_control_pressed && alt_pressed => {
+                        let resolution = ConflictResolution::Theirs;
+                        self.tasks.push(UpdateResult::ResolveConflict(resolution))
+                    }
+                    Keycode::B if left_control_pressed && alt_pressed => {
+                        let resolution = ConflictResolution::Both;
+                        self.tasks.push(UpdateResult::ResolveConflict(resolution))
+                    }
+                    Keycode::A if left_control_pressed && alt_pressed => {
+                        self.tasks.push(UpdateResult::ToggleBlame)
+                    }
+                    Keycode::L if left_control_pressed && alt_pressed => {
+                        self.tasks.push(UpdateResult::ShowBlameCommit)
+                    }
+                    Keycode::N if left_control_pressed && alt_pressed => {
+                        self.tasks.push(UpdateResult::NextConflict)
+                    }
+                    Keycode::P if left_control_pressed && alt_pressed => {
+                        self.tasks.push(UpdateResult::PreviousConflict)
+                    }
                     Keycode::O if left_control_pressed && !shift_pressed => {
                         self.tasks.push(UpdateResult::OpenFileModal)
                     }
+                    Keycode::O if left_control_pressed => {
+                        self.tasks.push(UpdateResult::OpenOutline)
+                    }
+                    Keycode::S if left_control_pressed && alt_pressed => {
+                        self.tasks.push(UpdateResult::StageHunk)
+                    }
+                    Keycode::U if left_control_pressed && alt_pressed => {
+                        self.tasks.push(UpdateResult::UnstageHunk)
+                    }
+                    Keycode::G if left_control_pressed && alt_pressed => {
+                        self.tasks.push(UpdateResult::ToggleCommitPanel)
+                    }
+                    Keycode::H if left_control_pressed && alt_pressed => {
+                        self.tasks.push(UpdateResult::CompareWithHead)
+                    }
+                    Keycode::W if left_control_pressed && alt_pressed => {
+                        self.tasks.push(UpdateResult::CompareWithDisk)
+                    }
+                    Keycode::C if left_control_pressed && alt_pressed => {
+                        self.tasks.push(UpdateResult::OpenCompareFile)
+                    }
                     Keycode::S if left_control_pressed => {
                         self.tasks.push(UpdateResult::SaveCurrentFile)
                     }
+                    Keycode::J if left_control_pressed => {
+                        self.tasks.push(UpdateResult::ToggleOutput);
+                    }
+                    Keycode::Backquote if left_control_pressed && shift_pressed => {
+                        self.tasks
+                            .push(UpdateResult::Terminal(TerminalAction::NewTab));
+                    }
+                    Keycode::Backquote if left_control_pressed => {
+                        self.tasks.push(UpdateResult::ToggleTerminal);
+                    }
+                    Keycode::K if left_control_pressed => {
+                        self.tasks.push(UpdateResult::ShowHover);
+                    }
+                    Keycode::F if left_control_pressed && shift_pressed => {
+                        self.tasks.push(UpdateResult::FormatDocument);
+                    }
+                    Keycode::Z if left_control_pressed && alt_pressed => {
+                        self.tasks.push(UpdateResult::RevertHunk)
+                    }
+                    Keycode::D if left_control_pressed && alt_pressed => {
+                        self.tasks.push(UpdateResult::ShowHunkDiff)
+                    }
+                    Keycode::M if left_control_pressed && shift_pressed => {
+                        self.tasks.push(UpdateResult::OpenProblems)
+                    }
+                    Keycode::Z if left_control_pressed => self.tasks.push(UpdateResult::Undo),
+                    Keycode::M if left_control_pressed => {
+                        self.tasks.push(UpdateResult::MoveCaretToMatchingBracket)
+                    }
+                    Keycode::LeftBracket if left_control_pressed && !shift_pressed => {
+                        self.tasks.push(UpdateResult::FoldCurrent)
+                    }
+                    Keycode::RightBracket if left_control_pressed && !shift_pressed => {
+                        self.tasks.push(UpdateResult::UnfoldCurrent)
+                    }
+                    Keycode::LeftBracket if left_control_pressed => {
+                        self.tasks.push(UpdateResult::FoldAll)
+                    }
+                    Keycode::RightBracket if left_control_pressed => {
+                        self.tasks.push(UpdateResult::UnfoldAll)
+                    }
+                    keycode if left_control_pressed => {
+                        if let Some(level) = fold_level(keycode) {
+                            self.tasks.push(UpdateResult::FoldLevel(level))
+                        }
+                    }
                     _ => {}
                 },
+                Event::TextInput { text, .. } if terminal_focused => {
+                    self.tasks
+                        .push(UpdateResult::Terminal(TerminalAction::Input(
+                            text.into_bytes(),
+                        )));
+                }
                 Event::TextInput { text, .. } => {
                     self.tasks.push(UpdateResult::Input(text));
                 }
@@ -348,3 +713,35 @@ impl ConfigHolder for Application {
         &self.config
     }
 }
+
+/// Task of key pressed while terminal is focused, other keys are sent to its shell
+fn terminal_key(keycode: Keycode, ctrl: bool, shift: bool) -> Option<UpdateResult> {
+    let action = match keycode {
+        Keycode::Backquote if ctrl && shift => TerminalAction::NewTab,
+        Keycode::Backquote if ctrl => return Some(UpdateResult::ToggleTerminal),
+        Keycode::W if ctrl && shift => TerminalAction::CloseTab,
+        Keycode::F if ctrl && shift => TerminalAction::Search,
+        Keycode::PageUp if ctrl => TerminalAction::PreviousTab,
+        Keycode::PageDown if ctrl => TerminalAction::NextTab,
+        Keycode::PageUp if shift => TerminalAction::ScrollUp,
+        Keycode::PageDown if shift => TerminalAction::ScrollDown,
+        keycode => TerminalAction::Input(key_bytes(keycode, ctrl)?),
+    };
+    Some(UpdateResult::Terminal(action))
+}
+
+/// Fold level selected with number key
+fn fold_level(keycode: Keycode) -> Option<usize> {
+    match keycode {
+        Keycode::Num1 => Some(1),
+        Keycode::Num2 => Some(2),
+        Keycode::Num3 => Some(3),
+        Keycode::Num4 => Some(4),
+        Keycode::Num5 => Some(5),
+        Keycode::Num6 => Some(6),
+        Keycode::Num7 => Some(7),
+        Keycode::Num8 => Some(8),
+        Keycode::Num9 => Some(9),
+        _ => None,
+    }
+}
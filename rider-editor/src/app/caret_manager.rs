@@ -6,11 +6,15 @@ where
 {
     let text_character: TextCharacter = file_editor
         .file()
-        .map(|file| file.get_character_at(file_editor.caret().text_position() + 1))??;
+        .map(|file| visible_character_after(file, file_editor.caret().text_position() + 1))??;
 
     let pos = file_editor.caret().position();
     let dest = text_character.dest().clone();
-    let new_pos = pos.moved(1, 0, 0);
+    let new_pos = if text_character.position() == pos.text_position() + 1 {
+        pos.moved(1, 0, 0)
+    } else {
+        CaretPosition::new(text_character.position(), text_character.line(), 0)
+    };
     file_editor.caret_mut().move_caret(new_pos, dest.top_left());
     Some(text_character)
 }
@@ -22,12 +26,26 @@ where
     if file_editor.caret().text_position() == 0 {
         return None;
     }
-    let text_character: TextCharacter = file_editor
-        .file()
-        .map(|file| file.get_character_at(file_editor.caret().text_position() - 1))??;
+    let (text_character, line_position) = file_editor.file().map(|file| {
+        let caret = file_editor.caret().text_position();
+        let text_character = visible_character_before(file, caret - 1)?;
+        let line_position = if text_character.position() + 1 == caret {
+            None
+        } else {
+            Some(line_position_of(file, text_character.position()))
+        };
+        Some((text_character, line_position))
+    })??;
     let pos = file_editor.caret().position();
     let character_destination = text_character.dest().clone();
-    let p = pos.moved(-1, 0, 0);
+    let p = match line_position {
+        None => pos.moved(-1, 0, 0),
+        Some(line_position) => CaretPosition::new(
+            text_character.position(),
+            text_character.line(),
+            line_position,
+        ),
+    };
     file_editor
         .caret_mut()
         .move_caret(p, character_destination.top_left());
@@ -43,7 +61,9 @@ where
     }
     let current_line_number = file_editor.caret().line_number();
     let mut next_line_position = 0;
+    let mut next_line = current_line_number + 1;
     let text_character = file_editor.file().map(|file| {
+        next_line = file.folds().next_visible_line(current_line_number);
         let mut desired_line_position = 0;
         let mut text_character: Option<&TextCharacter> = None;
         for c in file.iter_char() {
@@ -53,14 +73,14 @@ where
                 {
                     desired_line_position += 1
                 }
-                line if line == current_line_number + 1 => {
+                line if line == next_line => {
                     text_character = Some(c);
                     if next_line_position == desired_line_position {
                         break;
                     }
                     next_line_position += 1;
                 }
-                line if line == current_line_number + 2 => break,
+                line if line > next_line => break,
                 _ => {}
             }
         }
@@ -71,7 +91,7 @@ where
     let character_destination = text_character.dest().clone();
     let pos = text_character.position().clone();
     file_editor.caret_mut().move_caret(
-        CaretPosition::new(pos, current_line_number + 1, next_line_position),
+        CaretPosition::new(pos, next_line, next_line_position),
         character_destination.top_left(),
     );
     Some(text_character.clone())
@@ -91,6 +111,7 @@ where
 
     let mut desired_line_position = 0;
     let text_character: TextCharacter = file_editor.file().map(|file| {
+        let previous_line = file.folds().previous_visible_line(current_line_number)?;
         let mut prev_line = vec![];
         let mut found = false;
         for c in file.iter_char() {
@@ -106,7 +127,7 @@ where
                 {
                     found = true
                 }
-                line if line == current_line_number - 1 => prev_line.push(c),
+                line if line == previous_line => prev_line.push(c),
                 line if line == current_line_number + 1 => break,
                 _ => {}
             }
@@ -130,6 +151,66 @@ where
     Some(text_character)
 }
 
+pub fn move_caret_to_matching_bracket<C>(file_editor: &mut C) -> Option<TextCharacter>
+where
+    C: CaretAccess + FileAccess + ?Sized,
+{
+    let (_, target) = file_editor
+        .file()?
+        .bracket_pair_near(file_editor.caret().text_position())?;
+    move_caret_to_position(file_editor, target)
+}
+
+pub fn move_caret_to_position<C>(file_editor: &mut C, position: usize) -> Option<TextCharacter>
+where
+    C: CaretAccess + FileAccess + ?Sized,
+{
+    let (text_character, line_position) = file_editor.file().map(|file| {
+        let text_character = file.get_character_at(position)?;
+        Some((text_character, line_position_of(file, position)))
+    })??;
+
+    let character_destination = text_character.dest().clone();
+    file_editor.caret_mut().move_caret(
+        CaretPosition::new(
+            text_character.position(),
+            text_character.line(),
+            line_position,
+        ),
+        character_destination.top_left(),
+    );
+    Some(text_character)
+}
+
+/// Character at position or first one after it which is not hidden by fold
+fn visible_character_after(file: &EditorFile, position: usize) -> Option<TextCharacter> {
+    match file.get_character_at(position) {
+        Some(ref c) if c.is_hidden() => file
+            .iter_char()
+            .find(|c| c.position() > position && !c.is_hidden())
+            .cloned(),
+        c => c,
+    }
+}
+
+/// Character at position or last one before it which is not hidden by fold
+fn visible_character_before(file: &EditorFile, position: usize) -> Option<TextCharacter> {
+    match file.get_character_at(position) {
+        Some(ref c) if c.is_hidden() => file
+            .iter_char()
+            .filter(|c| c.position() < position && !c.is_hidden())
+            .last()
+            .cloned(),
+        c => c,
+    }
+}
+
+/// Number of characters between beginning of line and given char position
+fn line_position_of(file: &EditorFile, position: usize) -> usize {
+    let before: Vec<char> = file.buffer_ref().chars().take(position).collect();
+    before.iter().rev().take_while(|c| **c != '\n').count()
+}
+
 #[cfg(test)]
 mod test_move_right {
     use super::*;
@@ -242,7 +323,7 @@ mod test_move_up {
         editor.prepare_ui(&mut renderer);
         editor.caret_mut().set_line_position(1);
         editor.caret_mut().set_line_number(1);
-        editor.caret_mut().set_text_position(3);
+        editor.caret_mut().set_text_position(4);
 
         assert_eq!(
             move_caret_up(&mut editor),
@@ -285,7 +366,7 @@ mod test_move_down {
         editor.prepare_ui(&mut renderer);
         editor.caret_mut().set_line_position(1);
         editor.caret_mut().set_line_number(1);
-        editor.caret_mut().set_text_position(3);
+        editor.caret_mut().set_text_position(4);
 
         assert_eq!(
             move_caret_down(&mut editor),
@@ -294,3 +375,115 @@ mod test_move_down {
         assert_eq!(editor.caret().position(), &CaretPosition::new(6, 2, 1));
     }
 }
+
+#[cfg(test)]
+mod test_move_to_matching_bracket {
+    use super::*;
+    use crate::tests::*;
+    use rider_derive::*;
+
+    #[test]
+    fn assert_move_caret_without_file() {
+        let config = build_config();
+        let mut editor = FileEditor::new(config);
+
+        assert_eq!(move_caret_to_matching_bracket(&mut editor).is_some(), false);
+    }
+
+    #[test]
+    fn assert_move_caret_without_bracket() {
+        build_test_renderer!(renderer);
+        let mut editor = FileEditor::new(config.clone());
+        let mut file = EditorFile::new("test.rs".to_owned(), "hello".to_owned(), config);
+        file.prepare_ui(&mut renderer);
+        editor.open_file(file);
+        editor.prepare_ui(&mut renderer);
+
+        assert_eq!(move_caret_to_matching_bracket(&mut editor).is_some(), false);
+    }
+
+    #[test]
+    fn assert_move_caret_to_closing_bracket() {
+        build_test_renderer!(renderer);
+        let mut editor = FileEditor::new(config.clone());
+        let mut file = EditorFile::new("test.rs".to_owned(), "{\n  a\n}".to_owned(), config);
+        file.prepare_ui(&mut renderer);
+        editor.open_file(file);
+        editor.prepare_ui(&mut renderer);
+
+        assert_eq!(move_caret_to_matching_bracket(&mut editor).is_some(), true);
+        assert_eq!(editor.caret().text_position(), 6);
+        assert_eq!(editor.caret().line_number(), 2);
+        assert_eq!(editor.caret().line_position(), 0);
+    }
+}
+
+#[cfg(test)]
+mod test_move_over_folds {
+    use super::*;
+    use crate::renderer::renderer::Renderer;
+    use crate::tests::*;
+    use rider_config::{ConfigAccess, ConfigHolder};
+    use rider_derive::*;
+    use sdl2::rect::Point;
+
+    fn build_folded_editor<R>(config: ConfigAccess, renderer: &mut R) -> FileEditor
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let mut editor = FileEditor::new(config.clone());
+        let mut file = EditorFile::new(
+            "test.rs".to_owned(),
+            "fn a() {\n    b();\n}\nc".to_owned(),
+            config,
+        );
+        file.prepare_ui(renderer);
+        assert_eq!(file.fold(0), true);
+        editor.open_file(file);
+        editor.prepare_ui(renderer);
+        editor
+    }
+
+    #[test]
+    fn assert_move_down_skips_folded_lines() {
+        build_test_renderer!(renderer);
+        let mut editor = build_folded_editor(config, &mut renderer);
+        editor
+            .caret_mut()
+            .move_caret(CaretPosition::new(1, 0, 1), Point::new(0, 0));
+
+        assert_eq!(move_caret_down(&mut editor).is_some(), true);
+        assert_eq!(editor.caret().line_number(), 2);
+        assert_eq!(editor.caret().text_position(), 19);
+    }
+
+    #[test]
+    fn assert_move_up_skips_folded_lines() {
+        build_test_renderer!(renderer);
+        let mut editor = build_folded_editor(config, &mut renderer);
+        editor
+            .caret_mut()
+            .move_caret(CaretPosition::new(18, 2, 0), Point::new(0, 0));
+
+        assert_eq!(move_caret_up(&mut editor).is_some(), true);
+        assert_eq!(editor.caret().line_number(), 0);
+        assert_eq!(editor.caret().text_position(), 0);
+    }
+
+    #[test]
+    fn assert_move_right_and_left_skip_folded_lines() {
+        build_test_renderer!(renderer);
+        let mut editor = build_folded_editor(config, &mut renderer);
+        editor
+            .caret_mut()
+            .move_caret(CaretPosition::new(8, 0, 8), Point::new(0, 0));
+
+        assert_eq!(move_caret_right(&mut editor).is_some(), true);
+        assert_eq!(editor.caret().text_position(), 18);
+        assert_eq!(editor.caret().line_number(), 2);
+        assert_eq!(move_caret_left(&mut editor).is_some(), true);
+        assert_eq!(editor.caret().text_position(), 8);
+        assert_eq!(editor.caret().line_number(), 0);
+        assert_eq!(editor.caret().line_position(), 8);
+    }
+}
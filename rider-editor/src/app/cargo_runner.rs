@@ -0,0 +1,375 @@
+use crate::app::diagnostics::*;
+use crate::app::symbol_index::Location;
+use serde_json::Value;
+use std::io::{BufRead, BufReader, Read};
+use std::os::unix::process::CommandExt;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+use std::thread;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CargoCommand {
+    Check,
+    Build,
+    Test,
+    Clippy,
+}
+
+impl CargoCommand {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CargoCommand::Check => "check",
+            CargoCommand::Build => "build",
+            CargoCommand::Test => "test",
+            CargoCommand::Clippy => "clippy",
+        }
+    }
+}
+
+/// Line of task output, lines of compiler message lead to its place
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutputLine {
+    pub text: String,
+    pub location: Option<Location>,
+}
+
+impl OutputLine {
+    pub fn new(text: String) -> Self {
+        Self {
+            text,
+            location: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CargoEvent {
+    Output(OutputLine),
+    Diagnostic(Diagnostic),
+    /// Task ended, `true` when cargo succeeded
+    Finished(bool),
+}
+
+/// Cargo process started in project root with JSON messages.
+///
+/// Both stdout and stderr are read in background, lines are translated when polled.
+pub struct CargoRunner {
+    command: CargoCommand,
+    child: Child,
+    receiver: Receiver<String>,
+    root_path: String,
+    diagnostics: Vec<Diagnostic>,
+    cancelled: bool,
+    finished: bool,
+}
+
+impl CargoRunner {
+    /// Cargo leads its own process group, so cancel stops compilers and tests it started
+    pub fn start(program: &str, command: CargoCommand, root_path: &str) -> Result<Self, String> {
+        let mut cargo = Command::new(program);
+        cargo
+            .arg(command.name())
+            .arg("--message-format=json")
+            .current_dir(root_path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        unsafe {
+            cargo.pre_exec(|| {
+                if libc::setpgid(0, 0) == -1 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        let mut child = cargo
+            .spawn()
+            .map_err(|e| format!("Failed to start {}: {}", program, e))?;
+        let (sender, receiver) = channel();
+        if let Some(stdout) = child.stdout.take() {
+            read_lines(stdout, sender.clone());
+        }
+        if let Some(stderr) = child.stderr.take() {
+            read_lines(stderr, sender);
+        }
+        Ok(Self {
+            command,
+            child,
+            receiver,
+            root_path: root_path.to_owned(),
+            diagnostics: vec![],
+            cancelled: false,
+            finished: false,
+        })
+    }
+
+    pub fn command(&self) -> CargoCommand {
+        self.command
+    }
+
+    pub fn is_running(&self) -> bool {
+        !self.finished
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    /// Diagnostics reported since task started, repeated ones are skipped
+    pub fn diagnostics(&self) -> &Vec<Diagnostic> {
+        &self.diagnostics
+    }
+
+    /// Kills cargo with processes it started, its output which was already read is still
+    /// returned by `poll`
+    pub fn cancel(&mut self) {
+        if self.finished || self.cancelled {
+            return;
+        }
+        self.cancelled = true;
+        if unsafe { libc::killpg(self.child.id() as libc::pid_t, libc::SIGKILL) } == -1 {
+            warn!("{}", std::io::Error::last_os_error());
+            self.child.kill().unwrap_or_else(|e| warn!("{}", e));
+        }
+    }
+
+    /// Events from lines received since last call
+    pub fn poll(&mut self) -> Vec<CargoEvent> {
+        let mut events = vec![];
+        if self.finished {
+            return events;
+        }
+        loop {
+            match self.receiver.try_recv() {
+                Ok(line) => self.handle_line(line, &mut events),
+                // processes started by cancelled cargo may still hold its output open
+                Err(TryRecvError::Empty) if self.cancelled => {
+                    if let Ok(Some(_)) = self.child.try_wait() {
+                        self.finished = true;
+                        events.push(CargoEvent::Finished(false));
+                    }
+                    break;
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    let success = self.child.wait().map_or(false, |status| status.success());
+                    self.finished = true;
+                    events.push(CargoEvent::Finished(success && !self.cancelled));
+                    break;
+                }
+            }
+        }
+        events
+    }
+
+    fn handle_line(&mut self, line: String, events: &mut Vec<CargoEvent>) {
+        let message: Value = match serde_json::from_str(line.as_str()) {
+            Ok(message @ Value::Object(_)) => message,
+            _ => {
+                events.push(CargoEvent::Output(OutputLine::new(line)));
+                return;
+            }
+        };
+        if message["reason"] != "compiler-message" {
+            return;
+        }
+        let diagnostic = parse_compiler_message(&message["message"], self.root_path.as_str());
+        if let Some(ref diagnostic) = diagnostic {
+            if self.diagnostics.contains(diagnostic) {
+                return;
+            }
+            self.diagnostics.push(diagnostic.clone());
+        }
+        let location = diagnostic.as_ref().map(|d| d.location.clone());
+        let rendered = message["message"]["rendered"].as_str().unwrap_or_default();
+        for text in rendered.trim_end().lines() {
+            events.push(CargoEvent::Output(OutputLine {
+                text: text.to_owned(),
+                location: location.clone(),
+            }));
+        }
+        if let Some(diagnostic) = diagnostic {
+            events.push(CargoEvent::Diagnostic(diagnostic));
+        }
+    }
+}
+
+impl Drop for CargoRunner {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.child.kill().ok();
+            self.child.wait().ok();
+        }
+    }
+}
+
+fn read_lines<R>(input: R, sender: Sender<String>)
+where
+    R: Read + Send + 'static,
+{
+    thread::spawn(move || {
+        for line in BufReader::new(input).lines() {
+            let sent = line.ok().map_or(false, |line| sender.send(line).is_ok());
+            if !sent {
+                break;
+            }
+        }
+    });
+}
+
+/// Diagnostic at primary span of rustc message, messages without spans have none.
+///
+/// Span lines and columns are counted from 1, relative paths are resolved against root.
+pub fn parse_compiler_message(message: &Value, root_path: &str) -> Option<Diagnostic> {
+    let span = message["spans"]
+        .as_array()?
+        .iter()
+        .find(|span| span["is_primary"] == true)?;
+    let severity = match message["level"].as_str()? {
+        "error" | "error: internal compiler error" => DiagnosticSeverity::Error,
+        "warning" => DiagnosticSeverity::Warning,
+        "help" => DiagnosticSeverity::Hint,
+        _ => DiagnosticSeverity::Information,
+    };
+    let number = |key: &str| span[key].as_u64().map(|n| n.saturating_sub(1) as usize);
+    let path = Path::new(root_path).join(span["file_name"].as_str()?);
+    Some(Diagnostic {
+        location: Location {
+            path: path.to_string_lossy().to_string(),
+            line: number("line_start")?,
+            column: number("column_start")?,
+        },
+        end_line: number("line_end")?,
+        end_column: number("column_end")?,
+        severity,
+        message: message["message"].as_str()?.to_owned(),
+        source: message["code"]["code"]
+            .as_str()
+            .map_or_else(|| "rustc".to_owned(), |code| format!("rustc {}", code)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use std::time::Duration;
+
+    const CHECK_OUTPUT: &str = r#"{"reason":"compiler-artifact","package_id":"dep 0.1.0","target":{"name":"dep"},"filenames":[]}
+{"reason":"compiler-message","package_id":"app 0.1.0","message":{"rendered":"error[E0425]: cannot find function `run` in this scope\n --> src/main.rs:2:5\n","children":[],"code":{"code":"E0425","explanation":null},"level":"error","message":"cannot find function `run` in this scope","spans":[{"byte_end":20,"byte_start":17,"column_end":8,"column_start":5,"file_name":"src/main.rs","is_primary":true,"label":"not found in this scope","line_end":2,"line_start":2}]}}
+{"reason":"compiler-message","package_id":"app 0.1.0","message":{"rendered":"error: aborting due to previous error\n","children":[],"code":null,"level":"error","message":"aborting due to previous error","spans":[]}}
+{"reason":"build-finished","success":false}
+"#;
+
+    fn wait_for_finish(runner: &mut CargoRunner) -> Vec<CargoEvent> {
+        let mut events = vec![];
+        for _ in 0..500 {
+            events.extend(runner.poll());
+            if !runner.is_running() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        events
+    }
+
+    #[test]
+    fn must_parse_compiler_messages() {
+        let script = format!(
+            "cat <<'EOF'\n{}EOF\necho \"fake cargo $@\"\nexit 101\n",
+            CHECK_OUTPUT
+        );
+        let program = fake_program("runner-check", script.as_str());
+        let mut runner =
+            CargoRunner::start(program.as_str(), CargoCommand::Check, "/tmp/rider").unwrap();
+        let events = wait_for_finish(&mut runner);
+
+        let location = Location {
+            path: "/tmp/rider/src/main.rs".to_owned(),
+            line: 1,
+            column: 4,
+        };
+        let line = |text: &str, location: Option<Location>| {
+            CargoEvent::Output(OutputLine {
+                text: text.to_owned(),
+                location,
+            })
+        };
+        let diagnostic = Diagnostic {
+            location: location.clone(),
+            end_line: 1,
+            end_column: 7,
+            severity: DiagnosticSeverity::Error,
+            message: "cannot find function `run` in this scope".to_owned(),
+            source: "rustc E0425".to_owned(),
+        };
+        assert_eq!(
+            events,
+            vec![
+                line(
+                    "error[E0425]: cannot find function `run` in this scope",
+                    Some(location.clone())
+                ),
+                line(" --> src/main.rs:2:5", Some(location)),
+                CargoEvent::Diagnostic(diagnostic.clone()),
+                line("error: aborting due to previous error", None),
+                line("fake cargo check --message-format=json", None),
+                CargoEvent::Finished(false),
+            ]
+        );
+        assert_eq!(runner.diagnostics(), &vec![diagnostic]);
+    }
+
+    #[test]
+    fn must_cancel_running_task() {
+        let program = fake_program("runner-cancel", "exec sleep 10\n");
+        let mut runner =
+            CargoRunner::start(program.as_str(), CargoCommand::Build, "/tmp/rider").unwrap();
+        runner.cancel();
+        let events = wait_for_finish(&mut runner);
+        assert_eq!(events, vec![CargoEvent::Finished(false)]);
+    }
+
+    #[test]
+    fn must_kill_processes_started_by_cancelled_task() {
+        let program = fake_program("runner-cancel-group", "sleep 10 &\necho $!\nwait\n");
+        let mut runner =
+            CargoRunner::start(program.as_str(), CargoCommand::Test, "/tmp/rider").unwrap();
+        let mut pid = None;
+        for _ in 0..500 {
+            pid = runner.poll().into_iter().find_map(|event| match event {
+                CargoEvent::Output(line) => Some(line.text),
+                _ => None,
+            });
+            if pid.is_some() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        let pid = pid.unwrap();
+        runner.cancel();
+        assert_eq!(
+            wait_for_finish(&mut runner),
+            vec![CargoEvent::Finished(false)]
+        );
+        // killed process stays zombie until its new parent waits for it
+        let is_dead = || {
+            let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).unwrap_or_default();
+            stat.is_empty() || stat.contains(") Z ")
+        };
+        for _ in 0..500 {
+            if is_dead() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(is_dead(), true);
+    }
+
+    #[test]
+    fn must_fail_to_start_missing_cargo() {
+        let result = CargoRunner::start("/tmp/rider/no-such-cargo", CargoCommand::Test, "/tmp");
+        assert_eq!(result.is_err(), true);
+    }
+}
@@ -0,0 +1,122 @@
+use crate::app::{fuzzy_filter, fuzzy_score};
+use rider_config::Snippet;
+use std::collections::BTreeSet;
+
+pub const MAX_COMPLETIONS: usize = 10;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CompletionItem {
+    Word(String),
+    Snippet(Snippet),
+}
+
+impl CompletionItem {
+    pub fn label(&self) -> String {
+        match self {
+            CompletionItem::Word(word) => word.clone(),
+            CompletionItem::Snippet(snippet) if snippet.description.is_empty() => {
+                format!("{} (snippet)", snippet.prefix)
+            }
+            CompletionItem::Snippet(snippet) => {
+                format!("{} (snippet) {}", snippet.prefix, snippet.description)
+            }
+        }
+    }
+}
+
+/// Part of identifier written before given char position
+pub fn prefix_before(buffer: &str, position: usize) -> String {
+    let chars: Vec<char> = buffer.chars().take(position).collect();
+    let start = chars
+        .iter()
+        .rposition(|c| !(c.is_alphanumeric() || *c == '_'))
+        .map_or(0, |index| index + 1);
+    chars[start..].iter().collect()
+}
+
+/// Words matching prefix, best first, prefix itself is never offered
+pub fn completions(words: &BTreeSet<String>, prefix: &str) -> Vec<String> {
+    if prefix.is_empty() {
+        return vec![];
+    }
+    let words: Vec<&String> = words.iter().filter(|word| *word != prefix).collect();
+    fuzzy_filter(&words, prefix, |word| word.as_str())
+        .into_iter()
+        .take(MAX_COMPLETIONS)
+        .map(|word| word.to_string())
+        .collect()
+}
+
+/// Snippets matching prefix followed by matching words, best first
+pub fn completion_items(
+    words: &BTreeSet<String>,
+    snippets: &[Snippet],
+    prefix: &str,
+) -> Vec<CompletionItem> {
+    if prefix.is_empty() {
+        return vec![];
+    }
+    let mut scored: Vec<(i32, &Snippet)> = snippets
+        .iter()
+        .filter_map(|snippet| fuzzy_score(prefix, snippet.prefix.as_str()).map(|s| (s, snippet)))
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0));
+    scored
+        .into_iter()
+        .map(|(_, snippet)| CompletionItem::Snippet(snippet.clone()))
+        .chain(
+            completions(words, prefix)
+                .into_iter()
+                .map(CompletionItem::Word),
+        )
+        .take(MAX_COMPLETIONS)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(list: &[&str]) -> BTreeSet<String> {
+        list.iter().map(|word| word.to_string()).collect()
+    }
+
+    #[test]
+    fn must_find_prefix_before_position() {
+        assert_eq!(prefix_before("let foo_ba", 10), "foo_ba".to_owned());
+        assert_eq!(prefix_before("let foo_bar", 6), "fo".to_owned());
+        assert_eq!(prefix_before("foo(", 4), "".to_owned());
+        assert_eq!(prefix_before("", 0), "".to_owned());
+    }
+
+    #[test]
+    fn must_offer_snippets_before_words() {
+        let snippet = |prefix: &str| Snippet {
+            prefix: prefix.to_owned(),
+            body: "".to_owned(),
+            description: "".to_owned(),
+        };
+        let snippets = vec![snippet("for"), snippet("fn"), snippet("match")];
+        let words = words(&["format", "fn", "fun_name"]);
+        let labels: Vec<String> = completion_items(&words, &snippets, "fn")
+            .iter()
+            .map(|item| item.label())
+            .collect();
+        assert_eq!(
+            labels,
+            vec!["fn (snippet)".to_owned(), "fun_name".to_owned()]
+        );
+        assert_eq!(completion_items(&words, &snippets, ""), vec![]);
+    }
+
+    #[test]
+    fn must_offer_matching_words() {
+        let words = words(&["format", "main", "max", "ma", "other"]);
+        assert_eq!(
+            completions(&words, "ma"),
+            vec!["main".to_owned(), "max".to_owned(), "format".to_owned()]
+        );
+        assert_eq!(completions(&words, ""), Vec::<String>::new());
+        assert_eq!(completions(&words, "xyz"), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,216 @@
+use crate::app::symbol_index::Location;
+use crate::lsp::{offset_at, LspDiagnostic, Position};
+use rider_themes::Theme;
+use sdl2::pixels::Color;
+use std::collections::BTreeMap;
+
+/// Severity of problem, more important ones are sorted first
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+impl DiagnosticSeverity {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DiagnosticSeverity::Error => "error",
+            DiagnosticSeverity::Warning => "warning",
+            DiagnosticSeverity::Information => "info",
+            DiagnosticSeverity::Hint => "hint",
+        }
+    }
+
+    /// Error and warning use code highlighting colors, other ones border color
+    pub fn color(&self, theme: &Theme) -> Color {
+        match self {
+            DiagnosticSeverity::Error => theme.code_highlighting().error().color().into(),
+            DiagnosticSeverity::Warning => theme.code_highlighting().warning().color().into(),
+            _ => theme.border_color().into(),
+        }
+    }
+}
+
+/// Problem reported about part of file by language server, compiler or linter.
+///
+/// Range starts at `location` and ends at `end_line` and `end_column`, both counted in characters.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub location: Location,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub severity: DiagnosticSeverity,
+    pub message: String,
+    pub source: String,
+}
+
+impl Diagnostic {
+    /// Converts UTF-16 range of language server using text of file
+    pub fn from_lsp(path: &str, diagnostic: &LspDiagnostic, buffer: &str) -> Self {
+        let column = |position: Position| {
+            offset_at(buffer, position) - offset_at(buffer, Position::new(position.line, 0))
+        };
+        let range = diagnostic.range;
+        Self {
+            location: Location {
+                path: path.to_owned(),
+                line: range.start.line,
+                column: column(range.start),
+            },
+            end_line: range.end.line,
+            end_column: column(range.end),
+            severity: diagnostic.severity,
+            message: diagnostic.message.clone(),
+            source: diagnostic.source.clone(),
+        }
+    }
+
+    /// Message prefixed with source when it is known
+    pub fn text(&self) -> String {
+        match self.source.as_str() {
+            "" => self.message.clone(),
+            source => format!("{}: {}", source, self.message),
+        }
+    }
+}
+
+/// Diagnostics of every file grouped by provider which reported them.
+///
+/// Each provider replaces only its own diagnostics so language server
+/// and build tools can report about same file.
+#[derive(Clone, Debug, Default)]
+pub struct DiagnosticsStore {
+    providers: BTreeMap<String, BTreeMap<String, Vec<Diagnostic>>>,
+}
+
+impl DiagnosticsStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces diagnostics of provider for single file
+    pub fn set_file(&mut self, provider: &str, path: &str, diagnostics: Vec<Diagnostic>) {
+        let files = self.providers.entry(provider.to_owned()).or_default();
+        if diagnostics.is_empty() {
+            files.remove(path);
+        } else {
+            files.insert(path.to_owned(), diagnostics);
+        }
+    }
+
+    /// Replaces all diagnostics of provider
+    pub fn set_all(&mut self, provider: &str, diagnostics: Vec<Diagnostic>) {
+        let mut files: BTreeMap<String, Vec<Diagnostic>> = BTreeMap::new();
+        for diagnostic in diagnostics {
+            files
+                .entry(diagnostic.location.path.clone())
+                .or_default()
+                .push(diagnostic);
+        }
+        self.providers.insert(provider.to_owned(), files);
+    }
+
+    /// Diagnostics of file from all providers sorted by position
+    pub fn file(&self, path: &str) -> Vec<Diagnostic> {
+        let mut diagnostics: Vec<Diagnostic> = self
+            .providers
+            .values()
+            .filter_map(|files| files.get(path))
+            .flat_map(|diagnostics| diagnostics.iter().cloned())
+            .collect();
+        sort(&mut diagnostics);
+        diagnostics
+    }
+
+    /// Diagnostics of all files sorted by path and position
+    pub fn all(&self) -> Vec<Diagnostic> {
+        let mut diagnostics: Vec<Diagnostic> = self
+            .providers
+            .values()
+            .flat_map(|files| files.values())
+            .flat_map(|diagnostics| diagnostics.iter().cloned())
+            .collect();
+        sort(&mut diagnostics);
+        diagnostics
+    }
+}
+
+fn sort(diagnostics: &mut Vec<Diagnostic>) {
+    diagnostics.sort_by(|a, b| {
+        a.location
+            .cmp(&b.location)
+            .then(a.severity.cmp(&b.severity))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsp::Range;
+
+    fn diagnostic(path: &str, line: usize, severity: DiagnosticSeverity) -> Diagnostic {
+        Diagnostic {
+            location: Location {
+                path: path.to_owned(),
+                line,
+                column: 0,
+            },
+            end_line: line,
+            end_column: 1,
+            severity,
+            message: format!("{}:{}", path, line),
+            source: String::new(),
+        }
+    }
+
+    #[test]
+    fn must_keep_diagnostics_of_each_provider() {
+        let mut store = DiagnosticsStore::new();
+        store.set_file(
+            "lsp",
+            "/a.rs",
+            vec![diagnostic("/a.rs", 3, DiagnosticSeverity::Hint)],
+        );
+        store.set_all(
+            "cargo",
+            vec![
+                diagnostic("/b.rs", 0, DiagnosticSeverity::Error),
+                diagnostic("/a.rs", 1, DiagnosticSeverity::Warning),
+            ],
+        );
+        let lines: Vec<usize> = store
+            .file("/a.rs")
+            .iter()
+            .map(|d| d.location.line)
+            .collect();
+        assert_eq!(lines, vec![1, 3]);
+        assert_eq!(store.all().len(), 3);
+
+        store.set_file("lsp", "/a.rs", vec![]);
+        store.set_all(
+            "cargo",
+            vec![diagnostic("/b.rs", 2, DiagnosticSeverity::Error)],
+        );
+        assert_eq!(
+            store.all(),
+            vec![diagnostic("/b.rs", 2, DiagnosticSeverity::Error)]
+        );
+    }
+
+    #[test]
+    fn must_convert_lsp_diagnostic() {
+        let lsp_diagnostic = LspDiagnostic {
+            range: Range::new(Position::new(1, 2), Position::new(1, 4)),
+            severity: DiagnosticSeverity::Warning,
+            message: "unused".to_owned(),
+            source: "rustc".to_owned(),
+        };
+        let result = Diagnostic::from_lsp("/a.rs", &lsp_diagnostic, "fn\n😀ab\n");
+        assert_eq!(result.location.line, 1);
+        assert_eq!(result.location.column, 1);
+        assert_eq!((result.end_line, result.end_column), (1, 3));
+        assert_eq!(result.text(), "rustc: unused".to_owned());
+    }
+}
@@ -0,0 +1,100 @@
+use std::fs;
+use std::time::SystemTime;
+
+/// Content and modification time of file when editor last read or wrote it
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiskVersion {
+    pub content: String,
+    pub modified: Option<SystemTime>,
+}
+
+impl DiskVersion {
+    pub fn read(path: &str) -> Result<Self, String> {
+        let modified = modified_time(path);
+        let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Ok(Self { content, modified })
+    }
+
+    /// Version of just written content
+    pub fn written(path: &str, content: String) -> Self {
+        Self {
+            content,
+            modified: modified_time(path),
+        }
+    }
+}
+
+/// What to do with edited buffer of file which was changed by other program
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExternalChangeAction {
+    Reload,
+    KeepMine,
+    Diff,
+}
+
+impl ExternalChangeAction {
+    pub fn label(&self) -> &str {
+        match self {
+            ExternalChangeAction::Reload => "Reload from disk, my changes are lost",
+            ExternalChangeAction::KeepMine => "Keep mine, next save overwrites disk",
+            ExternalChangeAction::Diff => "Compare mine with disk",
+        }
+    }
+}
+
+pub fn modified_time(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// Reads file again when its modification time differs from known one.
+///
+/// Missing file gives `None`, content may be the same when file was only touched.
+pub fn read_if_modified(path: &str, known: &DiskVersion) -> Option<DiskVersion> {
+    let modified = modified_time(path)?;
+    if known.modified == Some(modified) {
+        return None;
+    }
+    DiskVersion::read(path).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn must_read_only_modified_file() {
+        let dir = "/tmp/rider/external-change";
+        fs::create_dir_all(dir).unwrap();
+        let path = format!("{}/a.txt", dir);
+        fs::write(&path, "first").unwrap();
+        let known = DiskVersion::read(path.as_str()).unwrap();
+        assert_eq!(known.content, "first".to_owned());
+        assert_eq!(read_if_modified(path.as_str(), &known), None);
+
+        let touched = DiskVersion {
+            modified: known.modified.map(|t| t - Duration::from_secs(5)),
+            ..known.clone()
+        };
+        assert_eq!(
+            read_if_modified(path.as_str(), &touched),
+            Some(known.clone())
+        );
+
+        fs::write(&path, "second").unwrap();
+        let stale = DiskVersion {
+            modified: known.modified.map(|t| t - Duration::from_secs(5)),
+            ..known
+        };
+        assert_eq!(
+            read_if_modified(path.as_str(), &stale).map(|v| v.content),
+            Some("second".to_owned())
+        );
+        let written = DiskVersion::written(path.as_str(), "second".to_owned());
+        assert_eq!(read_if_modified(path.as_str(), &written), None);
+        assert_eq!(
+            read_if_modified("/tmp/rider/external-change/b.txt", &written),
+            None
+        );
+    }
+}
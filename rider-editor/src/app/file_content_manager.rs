@@ -1,9 +1,84 @@
 use crate::app::*;
+use crate::lsp::text_change;
 use crate::renderer::renderer::Renderer;
 use crate::ui::*;
 use sdl2::rect::Point;
+use std::collections::VecDeque;
 use std::sync::*;
 
+/// Older edits are dropped
+const MAX_UNDO_STEPS: usize = 1000;
+
+/// Char range edit filled in buffer with text it replaced and caret char position from before edit
+#[derive(Clone, Debug, PartialEq)]
+pub struct UndoStep {
+    pub start: usize,
+    pub inserted: usize,
+    pub removed: String,
+    pub caret: usize,
+}
+
+impl UndoStep {
+    /// Step covering only characters which differ between buffers
+    pub fn between(old: &str, new: &str, caret: usize) -> Self {
+        let old: Vec<char> = old.chars().collect();
+        let new: Vec<char> = new.chars().collect();
+        let prefix = old
+            .iter()
+            .zip(new.iter())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let suffix = old[prefix..]
+            .iter()
+            .rev()
+            .zip(new[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        Self {
+            start: prefix,
+            inserted: new.len() - suffix - prefix,
+            removed: old[prefix..old.len() - suffix].iter().collect(),
+            caret,
+        }
+    }
+
+    /// Buffer from before edit
+    pub fn revert(&self, buffer: &str) -> String {
+        let mut chars: Vec<char> = buffer.chars().collect();
+        let start = self.start.min(chars.len());
+        let end = (self.start + self.inserted).min(chars.len());
+        chars.splice(start..end, self.removed.chars());
+        chars.into_iter().collect()
+    }
+}
+
+/// Edits of buffer which can be reverted, every edit of file pushes one step
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UndoStack {
+    steps: VecDeque<UndoStep>,
+}
+
+impl UndoStack {
+    pub fn push(&mut self, step: UndoStep) {
+        self.steps.push_back(step);
+        if self.steps.len() > MAX_UNDO_STEPS {
+            self.steps.pop_front();
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<UndoStep> {
+        self.steps.pop_back()
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
 pub fn current_file_path(file_editor: &mut FileEditor) -> String {
     file_editor
         .file()
@@ -20,6 +95,7 @@ where
         _ => return,
     };
     let position: CaretPosition = file_editor.caret().position().clone();
+    let caret = position.text_position();
     if position.text_position() == 0 {
         return;
     }
@@ -40,13 +116,18 @@ where
         Some((position, point)) => file_editor.caret_mut().move_caret(position, point),
         None => file_editor.caret_mut().reset_caret(),
     };
-    let mut new_file = EditorFile::new(
+    let new_file = EditorFile::new(
         current_file_path(file_editor),
         buffer,
         file_editor.config().clone(),
     );
-    new_file.prepare_ui(renderer);
-    file_editor.replace_current_file(new_file);
+    replace_file(
+        file_editor,
+        new_file,
+        position.line_number(),
+        caret,
+        renderer,
+    );
 }
 
 #[cfg_attr(tarpaulin, skip)]
@@ -64,9 +145,9 @@ where
         return;
     }
     buffer.remove(position);
-    let mut new_file = EditorFile::new(file.path(), buffer, file_editor.config().clone());
-    new_file.prepare_ui(renderer);
-    file_editor.replace_current_file(new_file);
+    let new_file = EditorFile::new(file.path(), buffer, file_editor.config().clone());
+    let line = file_editor.caret().line_number();
+    replace_file(file_editor, new_file, line, position, renderer);
 }
 
 pub fn insert_text<R>(file_editor: &mut FileEditor, text: String, renderer: &mut R)
@@ -87,6 +168,8 @@ where
         None => Point::new(0, 0),
     };
     let mut position: CaretPosition = file_editor.caret().position().clone();
+    let line = position.line_number();
+    let caret = position.text_position();
     for c in text.chars() {
         buffer.insert(position.text_position(), c);
         let rect = renderer.load_character_size(c);
@@ -95,13 +178,12 @@ where
         file_editor.caret_mut().move_caret(position, pos.clone());
     }
 
-    let mut new_file = EditorFile::new(
+    let new_file = EditorFile::new(
         file_editor.file().map_or(String::new(), |f| f.path()),
         buffer,
         file_editor.config().clone(),
     );
-    new_file.prepare_ui(renderer);
-    file_editor.replace_current_file(new_file);
+    replace_file(file_editor, new_file, line, caret, renderer);
 }
 
 pub fn insert_new_line<R>(file_editor: &mut FileEditor, renderer: &mut R) -> Result<(), String>
@@ -121,19 +203,20 @@ where
         Some(current) => current.dest().top_left(),
     };
     let mut position: CaretPosition = file_editor.caret().position().clone();
+    let line = position.line_number();
+    let caret = position.text_position();
     buffer.insert(position.text_position(), '\n');
     let rect = renderer.load_character_size('\n');
     pos = Point::new(0, pos.y() + rect.height() as i32);
     position = position.moved(1, 1, -(position.line_position() as i32));
     file_editor.caret_mut().move_caret(position, pos.clone());
 
-    let mut new_file = EditorFile::new(
+    let new_file = EditorFile::new(
         current_file_path(file_editor),
         buffer,
         Arc::clone(file_editor.config()),
     );
-    new_file.prepare_ui(renderer);
-    file_editor.replace_current_file(new_file);
+    replace_file(file_editor, new_file, line, caret, renderer);
     Ok(())
 }
 
@@ -160,10 +243,143 @@ where
             _ => new_buffer.push(c),
         }
     }
-    let mut new_file = EditorFile::new(file.path(), new_buffer, file_editor.config().clone());
+    let new_file = EditorFile::new(file.path(), new_buffer, file_editor.config().clone());
+    let caret = file_editor.caret().text_position();
+    replace_file(file_editor, new_file, target_line, caret, renderer);
+    Ok(())
+}
+
+/// Text which replaces char range of buffer
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replacement {
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+}
+
+/// Applies all replacements as one edit and moves caret to given char position
+pub fn replace_ranges<R>(
+    file_editor: &mut FileEditor,
+    mut replacements: Vec<Replacement>,
+    caret: usize,
+    renderer: &mut R,
+) -> Result<(), String>
+where
+    R: ConfigHolder + CharacterSizeManager + Renderer,
+{
+    let file: &EditorFile = file_editor
+        .file()
+        .ok_or_else(|| "No file is open".to_string())?;
+    let mut chars: Vec<char> = file.buffer_ref().chars().collect();
+    replacements.sort_by(|a, b| b.start.cmp(&a.start));
+    if replacements
+        .iter()
+        .any(|r| r.start > r.end || r.end > chars.len())
+    {
+        return Err("Replaced range is outside of buffer".to_string());
+    }
+    let edited_line = replacements.last().map_or(0, |r| {
+        chars[..r.start].iter().filter(|c| **c == '\n').count()
+    });
+    for replacement in replacements {
+        chars.splice(replacement.start..replacement.end, replacement.text.chars());
+    }
+    let new_file = EditorFile::new(
+        file.path(),
+        chars.into_iter().collect(),
+        file_editor.config().clone(),
+    );
+    let previous_caret = file_editor.caret().text_position();
+    replace_file(file_editor, new_file, edited_line, previous_caret, renderer);
+    place_caret(file_editor, caret);
+    Ok(())
+}
+
+/// Restores buffer and caret from before last edit of current file.
+///
+/// Returns false when there is nothing to undo.
+pub fn undo<R>(file_editor: &mut FileEditor, renderer: &mut R) -> bool
+where
+    R: ConfigHolder + CharacterSizeManager + Renderer,
+{
+    let (step, undo_stack, path, buffer) = match file_editor.file_mut() {
+        Some(file) => {
+            let mut undo_stack = file.take_undo_stack();
+            match undo_stack.pop() {
+                Some(step) => (step, undo_stack, file.path(), file.buffer()),
+                None => return false,
+            }
+        }
+        None => return false,
+    };
+    let edited_line = buffer
+        .chars()
+        .take(step.start)
+        .filter(|c| *c == '\n')
+        .count();
+    let mut new_file = EditorFile::new(
+        path,
+        step.revert(buffer.as_str()),
+        file_editor.config().clone(),
+    );
+    new_file.set_undo_stack(undo_stack);
+    apply_file(file_editor, new_file, edited_line, renderer);
+    place_caret(file_editor, step.caret);
+    true
+}
+
+/// Moves caret to char position, position past last character moves it to end of buffer
+fn place_caret(file_editor: &mut FileEditor, caret: usize) {
+    if move_caret_to_position(file_editor, caret).is_none() {
+        let last_line = file_editor.file().map_or(0, |f| f.lines_count() - 1);
+        file_editor.set_caret_to_end_of_line(last_line as i32);
+    }
+}
+
+/// Replaces file with its edited version, changed text and caret from before edit can be undone
+fn replace_file<R>(
+    file_editor: &mut FileEditor,
+    mut new_file: EditorFile,
+    edited_line: usize,
+    caret: usize,
+    renderer: &mut R,
+) where
+    R: ConfigHolder + CharacterSizeManager + Renderer,
+{
+    if let Some(file) = file_editor.file_mut() {
+        let mut undo_stack = file.take_undo_stack();
+        undo_stack.push(UndoStep::between(
+            file.buffer_ref(),
+            new_file.buffer_ref(),
+            caret,
+        ));
+        new_file.set_undo_stack(undo_stack);
+    }
+    apply_file(file_editor, new_file, edited_line, renderer);
+}
+
+/// Replaces file with its new version keeping folds which were not affected by edit
+fn apply_file<R>(
+    file_editor: &mut FileEditor,
+    mut new_file: EditorFile,
+    edited_line: usize,
+    renderer: &mut R,
+) where
+    R: ConfigHolder + CharacterSizeManager + Renderer,
+{
+    let mut change = None;
+    if let Some(file) = file_editor.file() {
+        new_file.restore_folds(file, edited_line);
+        new_file.set_diagnostics(file.diagnostics().clone());
+        new_file.carry_git_base(file);
+        new_file.set_disk_version(file.disk_version().cloned());
+        change = text_change(file.buffer_ref(), new_file.buffer_ref());
+    }
+    if let Some(change) = change {
+        file_editor.push_change(change);
+    }
     new_file.prepare_ui(renderer);
     file_editor.replace_current_file(new_file);
-    Ok(())
 }
 
 #[cfg(test)]
@@ -401,4 +617,80 @@ mod tests {
         assert_eq!(widget.file().is_some(), true);
         assert_eq!(widget.file().unwrap().buffer(), "ab\ncd".to_owned());
     }
+
+    #[test]
+    fn assert_insert_new_line_keeps_folds_below() {
+        let config = build_config();
+        let mut renderer = RendererMock::new(config.clone());
+        let mut widget = FileEditor::new(config.clone());
+        let mut file = EditorFile::new(
+            "hello.rs".to_owned(),
+            "a\nfn b() {\n    c\n}".to_owned(),
+            config.clone(),
+        );
+        file.prepare_ui(&mut renderer);
+        assert_eq!(file.fold(1), true);
+        widget.open_file(file);
+        widget.prepare_ui(&mut renderer);
+        let res: Result<(), String> = widget.insert_new_line(&mut renderer);
+        assert_eq!(res.is_ok(), true);
+        assert_eq!(widget.file().unwrap().folds().folded(), &vec![2]);
+        assert_eq!(widget.file().unwrap().folds().is_hidden(3), true);
+    }
+
+    #[test]
+    fn assert_undo_edits() {
+        let config = build_config();
+        let mut renderer = RendererMock::new(config.clone());
+        let mut widget = FileEditor::new(config.clone());
+        let file = EditorFile::new("hello.txt".to_owned(), "ab".to_owned(), config.clone());
+        widget.open_file(file);
+        widget.prepare_ui(&mut renderer);
+        assert_eq!(undo(&mut widget, &mut renderer), false);
+
+        insert_text(&mut widget, "xy".to_owned(), &mut renderer);
+        insert_new_line(&mut widget, &mut renderer).unwrap();
+        assert_eq!(widget.file().unwrap().buffer(), "xy\nab".to_owned());
+        assert_eq!(widget.file().unwrap().undo_stack().len(), 2);
+
+        assert_eq!(undo(&mut widget, &mut renderer), true);
+        assert_eq!(widget.file().unwrap().buffer(), "xyab".to_owned());
+        assert_eq!(widget.caret().text_position(), 2);
+        assert_eq!(undo(&mut widget, &mut renderer), true);
+        assert_eq!(widget.file().unwrap().buffer(), "ab".to_owned());
+        assert_eq!(widget.caret().text_position(), 0);
+        assert_eq!(widget.file().unwrap().undo_stack().is_empty(), true);
+    }
+
+    #[test]
+    fn assert_undo_stack_drops_oldest_steps() {
+        let mut stack = UndoStack::default();
+        for caret in 0..MAX_UNDO_STEPS + 5 {
+            stack.push(UndoStep::between("", "", caret));
+        }
+        assert_eq!(stack.len(), MAX_UNDO_STEPS);
+        assert_eq!(stack.pop().map(|step| step.caret), Some(MAX_UNDO_STEPS + 4));
+    }
+
+    #[test]
+    fn assert_undo_step_keeps_only_changed_text() {
+        let step = UndoStep::between("let a = 1;\nfoo();", "let abc = 1;\nfoo();", 5);
+        assert_eq!(
+            step,
+            UndoStep {
+                start: 5,
+                inserted: 2,
+                removed: String::new(),
+                caret: 5,
+            }
+        );
+        assert_eq!(
+            step.revert("let abc = 1;\nfoo();"),
+            "let a = 1;\nfoo();".to_owned()
+        );
+
+        let step = UndoStep::between("aXXb", "aYb", 1);
+        assert_eq!(step.removed, "XX".to_owned());
+        assert_eq!(step.revert("aYb"), "aXXb".to_owned());
+    }
 }
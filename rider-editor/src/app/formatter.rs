@@ -0,0 +1,246 @@
+use crate::app::symbol_index::line_starts;
+use rider_config::Formatter;
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Formatter still running after this time is killed
+const FORMATTER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Pipes buffer through formatter started in given directory and returns its output.
+///
+/// Failed formatter, output which is not UTF-8 or empty output for non-empty buffer are errors
+/// so buffer is never replaced with something formatter did not mean.
+pub fn run_formatter(formatter: &Formatter, buffer: &str, dir: &str) -> Result<String, String> {
+    run_formatter_with_timeout(formatter, buffer, dir, FORMATTER_TIMEOUT)
+}
+
+/// Formatter running on other thread for buffer of file, its output is taken when polled
+pub struct FormatterRun {
+    path: String,
+    buffer: String,
+    save: bool,
+    receiver: Receiver<Result<String, String>>,
+}
+
+impl FormatterRun {
+    /// File is written once formatter is done when `save` is set
+    pub fn start(
+        formatter: &Formatter,
+        path: String,
+        buffer: String,
+        dir: String,
+        save: bool,
+    ) -> Self {
+        let (sender, receiver) = channel();
+        let formatter = formatter.clone();
+        let input = buffer.clone();
+        thread::spawn(move || {
+            sender
+                .send(run_formatter(&formatter, input.as_str(), dir.as_str()))
+                .ok();
+        });
+        Self {
+            path,
+            buffer,
+            save,
+            receiver,
+        }
+    }
+
+    pub fn path(&self) -> &String {
+        &self.path
+    }
+
+    /// Buffer given to formatter
+    pub fn buffer(&self) -> &String {
+        &self.buffer
+    }
+
+    pub fn save(&self) -> bool {
+        self.save
+    }
+
+    pub fn set_save(&mut self, save: bool) {
+        self.save = save;
+    }
+
+    /// Formatter output, `None` while it's running
+    pub fn poll(&self) -> Option<Result<String, String>> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err("Formatter stopped".to_owned())),
+        }
+    }
+}
+
+fn run_formatter_with_timeout(
+    formatter: &Formatter,
+    buffer: &str,
+    dir: &str,
+    timeout: Duration,
+) -> Result<String, String> {
+    let mut child = Command::new(formatter.command.as_str())
+        .args(&formatter.args)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to start {}: {}", formatter.command, e))?;
+    // formatter may write its output before it reads whole input
+    let input = buffer.to_owned();
+    if let Some(mut stdin) = child.stdin.take() {
+        thread::spawn(move || {
+            stdin.write_all(input.as_bytes()).ok();
+        });
+    }
+    let stdout = read_in_background(child.stdout.take());
+    let stderr = read_in_background(child.stderr.take());
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if started.elapsed() < timeout => thread::sleep(Duration::from_millis(10)),
+            Ok(None) => {
+                child.kill().ok();
+                child.wait().ok();
+                return Err(format!(
+                    "{} timed out after {} seconds",
+                    formatter.command,
+                    timeout.as_secs_f32()
+                ));
+            }
+            Err(e) => return Err(format!("{} failed: {}", formatter.command, e)),
+        }
+    };
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+    if !status.success() {
+        let stderr = String::from_utf8_lossy(&stderr);
+        let reason = stderr
+            .lines()
+            .map(|line| line.trim())
+            .find(|line| !line.is_empty())
+            .map_or_else(|| status.to_string(), |line| line.to_owned());
+        return Err(format!("{} failed: {}", formatter.command, reason));
+    }
+    let formatted = String::from_utf8(stdout)
+        .map_err(|_| format!("{} returned invalid UTF-8", formatter.command))?;
+    if formatted.is_empty() && !buffer.is_empty() {
+        return Err(format!("{} returned no output", formatter.command));
+    }
+    Ok(formatted)
+}
+
+/// Reads whole pipe on other thread, so child is never blocked on full pipe while it's waited for
+fn read_in_background<P>(pipe: Option<P>) -> JoinHandle<Vec<u8>>
+where
+    P: Read + Send + 'static,
+{
+    thread::spawn(move || {
+        let mut bytes = vec![];
+        if let Some(mut pipe) = pipe {
+            pipe.read_to_end(&mut bytes).ok();
+        }
+        bytes
+    })
+}
+
+/// Char position of column in line of text, both are limited to text size
+pub fn position_in_text(text: &str, line: usize, column: usize) -> usize {
+    let starts = line_starts(text);
+    let line = line.min(starts.len() - 1);
+    let start = starts[line];
+    let end = starts
+        .get(line + 1)
+        .map_or_else(|| text.chars().count(), |next| next - 1);
+    start + column.min(end - start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+
+    fn formatter(name: &str, script: &str) -> Formatter {
+        Formatter::new(fake_program(name, script).as_str(), &["-"])
+    }
+
+    #[test]
+    fn must_pipe_buffer_through_formatter() {
+        let formatter = formatter("formatter-upcase", "[ \"$1\" = \"-\" ] && tr a-z A-Z\n");
+        let result = run_formatter(&formatter, "fn main() {}\n", "/tmp");
+        assert_eq!(result, Ok("FN MAIN() {}\n".to_owned()));
+    }
+
+    #[test]
+    fn must_run_formatter_in_background() {
+        let formatter = formatter("formatter-background", "sleep 0.1\ntr a-z A-Z\n");
+        let run = FormatterRun::start(
+            &formatter,
+            "/tmp/a.rs".to_owned(),
+            "fn main() {}\n".to_owned(),
+            "/tmp".to_owned(),
+            true,
+        );
+        assert_eq!(run.poll(), None);
+        let mut result = None;
+        for _ in 0..200 {
+            result = run.poll();
+            if result.is_some() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(result, Some(Ok("FN MAIN() {}\n".to_owned())));
+        assert_eq!(run.save(), true);
+    }
+
+    #[test]
+    fn must_report_formatter_errors() {
+        let failing = formatter(
+            "formatter-failing",
+            "cat > /dev/null\necho '' >&2\necho 'error: expected item' >&2\nexit 1\n",
+        );
+        let result = run_formatter(&failing, "fn main( {}\n", "/tmp");
+        assert_eq!(
+            result,
+            Err(format!("{} failed: error: expected item", failing.command))
+        );
+
+        let silent = formatter("formatter-silent", "cat > /dev/null\n");
+        let result = run_formatter(&silent, "fn main() {}\n", "/tmp");
+        assert_eq!(
+            result,
+            Err(format!("{} returned no output", silent.command))
+        );
+
+        let missing = Formatter::new("/tmp/rider/no-such-formatter", &[]);
+        assert_eq!(run_formatter(&missing, "a", "/tmp").is_err(), true);
+    }
+
+    #[test]
+    fn must_kill_formatter_after_timeout() {
+        let hanging = formatter("formatter-hanging", "exec sleep 5\n");
+        let started = Instant::now();
+        let result = run_formatter_with_timeout(&hanging, "a", "/tmp", Duration::from_millis(200));
+        assert_eq!(
+            result,
+            Err(format!("{} timed out after 0.2 seconds", hanging.command))
+        );
+        assert_eq!(started.elapsed() < Duration::from_secs(2), true);
+    }
+
+    #[test]
+    fn must_find_position_in_text() {
+        let text = "fn main() {\n    run();\n}\n";
+        assert_eq!(position_in_text(text, 1, 4), 16);
+        assert_eq!(position_in_text(text, 1, 40), 22);
+        assert_eq!(position_in_text(text, 2, 0), 23);
+        assert_eq!(position_in_text(text, 9, 3), 25);
+    }
+}
@@ -0,0 +1,299 @@
+use crate::app::symbol_index::IGNORED_DIRECTORIES;
+use std::collections::HashMap;
+use std::ffi::{CString, OsStr};
+use std::fs::{self, File};
+use std::io::{ErrorKind, Read};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::path::Path;
+
+const WATCH_MASK: u32 = libc::IN_CREATE
+    | libc::IN_DELETE
+    | libc::IN_MOVED_FROM
+    | libc::IN_MOVED_TO
+    | libc::IN_CLOSE_WRITE;
+const BUFFER_SIZE: usize = 64 * 1024;
+
+/// Change of entry below watched root, paths are absolute
+#[derive(Clone, Debug, PartialEq)]
+pub enum FsEvent {
+    Created(String),
+    Removed(String),
+    Renamed {
+        from: String,
+        to: String,
+    },
+    Modified(String),
+    /// Kernel queue overflowed and events were lost, whole tree must be read again
+    Rescan,
+}
+
+/// Watches root and every directory below it with inotify.
+///
+/// Hidden and build directories are skipped, directories created later are watched too.
+pub struct FsWatcher {
+    inotify: File,
+    watches: HashMap<i32, String>,
+}
+
+impl FsWatcher {
+    pub fn new(root: &str) -> Result<Self, String> {
+        let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+        if fd == -1 {
+            return Err(std::io::Error::last_os_error().to_string());
+        }
+        let mut watcher = Self {
+            inotify: unsafe { File::from_raw_fd(fd) },
+            watches: HashMap::new(),
+        };
+        watcher.watch_tree(root)?;
+        Ok(watcher)
+    }
+
+    /// Directories which are watched
+    pub fn watched(&self) -> Vec<&String> {
+        let mut watched: Vec<&String> = self.watches.values().collect();
+        watched.sort();
+        watched
+    }
+
+    /// Changes since last call, moves inside tree are paired into renames
+    pub fn poll(&mut self) -> Vec<FsEvent> {
+        let mut events = vec![];
+        let mut moved: Vec<(u32, String, bool)> = vec![];
+        let mut buffer = vec![0u8; BUFFER_SIZE];
+        loop {
+            let count = match self.inotify.read(&mut buffer) {
+                Ok(count) if count > 0 => count,
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => continue,
+                _ => break,
+            };
+            let mut offset = 0;
+            while offset + HEADER_SIZE <= count {
+                let header: libc::inotify_event = unsafe {
+                    std::ptr::read_unaligned(buffer[offset..].as_ptr() as *const libc::inotify_event)
+                };
+                let name_start = offset + HEADER_SIZE;
+                let name_end = (name_start + header.len as usize).min(count);
+                let name = buffer[name_start..name_end].split(|b| *b == 0).next();
+                offset = name_end;
+                self.read_event(&header, name.unwrap_or(&[]), &mut moved, &mut events);
+            }
+        }
+        // entries moved out of watched tree are gone for us
+        for (_cookie, path, is_dir) in moved {
+            if is_dir {
+                self.unwatch_tree(path.as_str());
+            }
+            events.push(FsEvent::Removed(path));
+        }
+        events
+    }
+
+    fn read_event(
+        &mut self,
+        header: &libc::inotify_event,
+        name: &[u8],
+        moved: &mut Vec<(u32, String, bool)>,
+        events: &mut Vec<FsEvent>,
+    ) {
+        let mask = header.mask;
+        if mask & libc::IN_Q_OVERFLOW != 0 {
+            return events.push(FsEvent::Rescan);
+        }
+        if mask & libc::IN_IGNORED != 0 {
+            self.watches.remove(&header.wd);
+            return;
+        }
+        let dir = match self.watches.get(&header.wd) {
+            Some(dir) => dir.clone(),
+            _ => return,
+        };
+        let path = Path::new(&dir)
+            .join(OsStr::from_bytes(name))
+            .to_string_lossy()
+            .to_string();
+        let is_dir = mask & libc::IN_ISDIR != 0;
+        if mask & libc::IN_CREATE != 0 {
+            if is_dir {
+                self.watch_new_directory(path.as_str());
+            }
+            events.push(FsEvent::Created(path));
+        } else if mask & libc::IN_DELETE != 0 {
+            events.push(FsEvent::Removed(path));
+        } else if mask & libc::IN_CLOSE_WRITE != 0 {
+            events.push(FsEvent::Modified(path));
+        } else if mask & libc::IN_MOVED_FROM != 0 {
+            moved.push((header.cookie, path, is_dir));
+        } else if mask & libc::IN_MOVED_TO != 0 {
+            match moved
+                .iter()
+                .position(|(cookie, ..)| *cookie == header.cookie)
+            {
+                Some(index) => {
+                    let (_cookie, from, _is_dir) = moved.remove(index);
+                    if is_dir {
+                        self.rename_watches(from.as_str(), path.as_str());
+                    }
+                    events.push(FsEvent::Renamed { from, to: path });
+                }
+                None => {
+                    if is_dir {
+                        self.watch_new_directory(path.as_str());
+                    }
+                    events.push(FsEvent::Created(path));
+                }
+            }
+        }
+    }
+
+    fn watch_new_directory(&mut self, path: &str) {
+        let name = Path::new(path)
+            .file_name()
+            .map_or(String::new(), |name| name.to_string_lossy().to_string());
+        if !is_skipped(name.as_str()) {
+            self.watch_tree(path).unwrap_or_default();
+        }
+    }
+
+    /// Watches directory and its subdirectories
+    fn watch_tree(&mut self, root: &str) -> Result<(), String> {
+        let mut directories = vec![root.to_owned()];
+        while let Some(directory) = directories.pop() {
+            let c_path = CString::new(directory.as_bytes()).map_err(|e| e.to_string())?;
+            let wd = unsafe {
+                libc::inotify_add_watch(self.inotify.as_raw_fd(), c_path.as_ptr(), WATCH_MASK)
+            };
+            if wd == -1 {
+                // root must be watched, nested directory may be already gone
+                if directory == root {
+                    return Err(std::io::Error::last_os_error().to_string());
+                }
+                continue;
+            }
+            self.watches.insert(wd, directory.clone());
+            let entries = match fs::read_dir(&directory) {
+                Ok(entries) => entries,
+                _ => continue,
+            };
+            for entry in entries.filter_map(|entry| entry.ok()) {
+                let name = entry.file_name().to_string_lossy().to_string();
+                let is_dir = entry.file_type().map_or(false, |t| t.is_dir());
+                if is_dir && !is_skipped(name.as_str()) {
+                    directories.push(entry.path().to_string_lossy().to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Stops watching directory moved out of tree, its entries are not ours anymore
+    fn unwatch_tree(&mut self, root: &str) {
+        let prefix = format!("{}/", root);
+        let fd = self.inotify.as_raw_fd();
+        self.watches.retain(|wd, path| {
+            if path.as_str() != root && !path.starts_with(prefix.as_str()) {
+                return true;
+            }
+            unsafe { libc::inotify_rm_watch(fd, *wd) };
+            false
+        });
+    }
+
+    /// Watches of moved directory keep their descriptors, only paths change
+    fn rename_watches(&mut self, from: &str, to: &str) {
+        let prefix = format!("{}/", from);
+        for path in self.watches.values_mut() {
+            if path.as_str() == from || path.starts_with(prefix.as_str()) {
+                *path = format!("{}{}", to, &path[from.len()..]);
+            }
+        }
+    }
+}
+
+const HEADER_SIZE: usize = std::mem::size_of::<libc::inotify_event>();
+
+fn is_skipped(name: &str) -> bool {
+    name.starts_with('.') || IGNORED_DIRECTORIES.contains(&name)
+}
+
+/// Directory containing entry
+pub fn parent_directory(path: &str) -> Option<String> {
+    Path::new(path)
+        .parent()
+        .map(|parent| parent.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn project(name: &str) -> String {
+        let dir = format!("/tmp/rider/fs-watcher/{}", name);
+        fs::remove_dir_all(&dir).unwrap_or_default();
+        fs::create_dir_all(format!("{}/src", dir)).unwrap();
+        fs::create_dir_all(format!("{}/target/debug", dir)).unwrap();
+        fs::create_dir_all(format!("{}/.git", dir)).unwrap();
+        dir
+    }
+
+    #[test]
+    fn must_watch_tree_without_hidden_and_build_directories() {
+        let dir = project("tree");
+        let watcher = FsWatcher::new(dir.as_str()).unwrap();
+        assert_eq!(watcher.watched(), vec![&dir, &format!("{}/src", dir)]);
+        assert_eq!(
+            FsWatcher::new("/tmp/rider/fs-watcher/missing").is_err(),
+            true
+        );
+    }
+
+    #[test]
+    fn must_report_changes() {
+        let dir = project("changes");
+        let mut watcher = FsWatcher::new(dir.as_str()).unwrap();
+        assert_eq!(watcher.poll(), vec![]);
+
+        fs::write(format!("{}/src/a.rs", dir), "fn a() {}").unwrap();
+        fs::create_dir(format!("{}/lib", dir)).unwrap();
+        assert_eq!(
+            watcher.poll(),
+            vec![
+                FsEvent::Created(format!("{}/src/a.rs", dir)),
+                FsEvent::Modified(format!("{}/src/a.rs", dir)),
+                FsEvent::Created(format!("{}/lib", dir)),
+            ]
+        );
+
+        fs::rename(format!("{}/src/a.rs", dir), format!("{}/lib/b.rs", dir)).unwrap();
+        fs::rename(format!("{}/lib", dir), format!("{}/core", dir)).unwrap();
+        fs::remove_file(format!("{}/core/b.rs", dir)).unwrap();
+        assert_eq!(
+            watcher.poll(),
+            vec![
+                FsEvent::Renamed {
+                    from: format!("{}/src/a.rs", dir),
+                    to: format!("{}/lib/b.rs", dir),
+                },
+                FsEvent::Renamed {
+                    from: format!("{}/lib", dir),
+                    to: format!("{}/core", dir),
+                },
+                FsEvent::Removed(format!("{}/core/b.rs", dir)),
+            ]
+        );
+        assert_eq!(watcher.watched().contains(&&format!("{}/core", dir)), true);
+
+        let outside = "/tmp/rider/fs-watcher/moved-out";
+        fs::remove_dir_all(outside).unwrap_or_default();
+        fs::rename(format!("{}/src", dir), outside).unwrap();
+        assert_eq!(
+            watcher.poll(),
+            vec![FsEvent::Removed(format!("{}/src", dir))]
+        );
+        fs::write(format!("{}/c.rs", outside), "").unwrap();
+        assert_eq!(watcher.poll(), vec![]);
+        assert_eq!(watcher.watched(), vec![&dir, &format!("{}/core", dir)]);
+    }
+}
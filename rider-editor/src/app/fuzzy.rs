@@ -0,0 +1,79 @@
+/// Score of text containing all pattern characters in order, ignoring case.
+///
+/// Consecutive characters and characters starting a word score more, `None` when text does not match.
+pub fn fuzzy_score(pattern: &str, text: &str) -> Option<i32> {
+    let mut score = 0;
+    let mut previous: Option<char> = None;
+    let mut last_match: Option<usize> = None;
+    let mut pattern = pattern.chars().flat_map(char::to_lowercase).peekable();
+    for (index, c) in text.chars().enumerate() {
+        let expected = match pattern.peek() {
+            Some(expected) => *expected,
+            _ => break,
+        };
+        if c.to_lowercase().any(|c| c == expected) {
+            score += 1;
+            if last_match.map_or(false, |last| last + 1 == index) {
+                score += 4;
+            }
+            if previous.map_or(true, |p| {
+                !p.is_alphanumeric() || (p.is_lowercase() && c.is_uppercase())
+            }) {
+                score += 2;
+            }
+            last_match = Some(index);
+            pattern.next();
+        }
+        previous = Some(c);
+    }
+    match pattern.peek() {
+        Some(_) => None,
+        _ => Some(score),
+    }
+}
+
+/// Items matching pattern, best first, items with equal score keep their order
+pub fn fuzzy_filter<'a, T, F>(items: &'a [T], pattern: &str, key: F) -> Vec<&'a T>
+where
+    F: Fn(&T) -> &str,
+{
+    let mut scored: Vec<(i32, &T)> = items
+        .iter()
+        .filter_map(|item| fuzzy_score(pattern, key(item)).map(|score| (score, item)))
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0));
+    scored.into_iter().map(|(_, item)| item).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn must_match_subsequence_ignoring_case() {
+        assert_eq!(fuzzy_score("fb", "FooBar").is_some(), true);
+        assert_eq!(fuzzy_score("", "FooBar"), Some(0));
+        assert_eq!(fuzzy_score("bf", "FooBar"), None);
+        assert_eq!(fuzzy_score("foo", "fo"), None);
+    }
+
+    #[test]
+    fn must_prefer_consecutive_and_word_start_characters() {
+        assert!(fuzzy_score("bar", "foo_bar") > fuzzy_score("bar", "b_a_r"));
+        assert!(fuzzy_score("fb", "foo_bar") > fuzzy_score("fb", "fab"));
+        assert!(fuzzy_score("fb", "FooBar") > fuzzy_score("fb", "Foobar"));
+    }
+
+    #[test]
+    fn must_sort_by_score_and_keep_order_of_equal() {
+        let items = vec!["main", "new", "render", "run"];
+        assert_eq!(
+            fuzzy_filter(&items, "n", |s| s),
+            vec![&"new", &"main", &"render", &"run"]
+        );
+        assert_eq!(
+            fuzzy_filter(&items, "", |s| s),
+            vec![&"main", &"new", &"render", &"run"]
+        );
+    }
+}
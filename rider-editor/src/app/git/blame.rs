@@ -0,0 +1,213 @@
+use crate::app::git::{run_git, run_git_with_input, split_path};
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Id git blame gives to lines which are not committed yet
+const NOT_COMMITTED: &str = "0000000000000000000000000000000000000000";
+
+/// Commit which last changed line, `time` is in seconds since epoch
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BlameCommit {
+    pub id: String,
+    pub author: String,
+    pub time: i64,
+    pub summary: String,
+}
+
+impl BlameCommit {
+    pub fn short_id(&self) -> &str {
+        &self.id[..self.id.len().min(7)]
+    }
+
+    pub fn is_committed(&self) -> bool {
+        self.id != NOT_COMMITTED
+    }
+}
+
+/// Commit of every line of file
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Blame {
+    commits: HashMap<String, BlameCommit>,
+    lines: Vec<String>,
+}
+
+impl Blame {
+    /// Reads output of `git blame --porcelain`, commit details are given only for its first line
+    pub fn parse(output: &str) -> Self {
+        let mut blame = Blame::default();
+        let mut current: Option<String> = None;
+        for line in output.lines() {
+            if line.starts_with('\t') {
+                current = None;
+                continue;
+            }
+            let id = match current.as_ref() {
+                Some(id) => id.clone(),
+                None => {
+                    current = blame.push_line(line);
+                    continue;
+                }
+            };
+            let commit = match blame.commits.get_mut(&id) {
+                Some(commit) => commit,
+                _ => continue,
+            };
+            let (key, value) = match line.find(' ') {
+                Some(index) => (&line[..index], &line[index + 1..]),
+                None => (line, ""),
+            };
+            match key {
+                "author" => commit.author = value.to_owned(),
+                "author-time" => commit.time = value.parse().unwrap_or_default(),
+                "summary" => commit.summary = value.to_owned(),
+                _ => (),
+            }
+        }
+        blame
+    }
+
+    /// Reads header of line, `<commit> <original line> <final line> [<lines in group>]`
+    fn push_line(&mut self, header: &str) -> Option<String> {
+        let mut parts = header.split(' ');
+        let id = parts.next()?.to_owned();
+        let final_line = parts.nth(1)?.parse::<usize>().ok()?;
+        if id.len() != 40 || final_line == 0 {
+            return None;
+        }
+        if self.lines.len() < final_line {
+            self.lines.resize(final_line, String::new());
+        }
+        self.lines[final_line - 1] = id.clone();
+        self.commits.entry(id.clone()).or_insert(BlameCommit {
+            id: id.clone(),
+            ..BlameCommit::default()
+        });
+        Some(id)
+    }
+
+    /// Commit of line counted from 0
+    pub fn commit_at(&self, line: usize) -> Option<&BlameCommit> {
+        self.lines.get(line).and_then(|id| self.commits.get(id))
+    }
+
+    pub fn commits(&self) -> Vec<&BlameCommit> {
+        self.commits.values().collect()
+    }
+
+    pub fn lines_count(&self) -> usize {
+        self.lines.len()
+    }
+}
+
+/// Blames given content of file, changed lines belong to not committed one
+pub fn blame_buffer(path: &str, buffer: &str) -> Result<Blame, String> {
+    let (dir, name) = split_path(path).ok_or_else(|| format!("Invalid path {}", path))?;
+    let args = ["blame", "--porcelain", "--contents", "-", "--", name];
+    run_git_with_input(dir, &args, buffer).map(|output| Blame::parse(output.as_str()))
+}
+
+/// Author, date and full message of commit
+pub fn commit_message(dir: &str, id: &str) -> Result<String, String> {
+    let format = "--format=commit %H%nAuthor: %an <%ae>%nDate:   %ad%n%n%B";
+    run_git(dir, &["show", "-s", format, id]).map(|text| text.trim_end().to_owned())
+}
+
+/// Message of commit followed by its changes in unified diff format
+pub fn commit_diff(dir: &str, id: &str) -> Result<String, String> {
+    run_git(dir, &["show", id])
+}
+
+/// Current time in seconds since epoch
+pub fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as i64)
+}
+
+/// Age of time described in words, `now` is in seconds since epoch
+pub fn relative_date(time: i64, now: i64) -> String {
+    let seconds = (now - time).max(0);
+    let (count, unit) = match seconds {
+        s if s < 60 => return "just now".to_owned(),
+        s if s < 3600 => (s / 60, "minute"),
+        s if s < 86400 => (s / 3600, "hour"),
+        s if s < 30 * 86400 => (s / 86400, "day"),
+        s if s < 365 * 86400 => (s / (30 * 86400), "month"),
+        s => (s / (365 * 86400), "year"),
+    };
+    match count {
+        1 => format!("1 {} ago", unit),
+        _ => format!("{} {}s ago", count, unit),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use std::fs;
+
+    const FIRST: &str = "1111111111111111111111111111111111111111";
+    const SECOND: &str = "2222222222222222222222222222222222222222";
+
+    #[test]
+    fn must_parse_porcelain_blame() {
+        let output = format!(
+            "{0} 1 1 2\nauthor Ann\nauthor-time 100\nsummary Init\nfilename a.txt\n\ta\n\
+             {0} 2 2\n\tb\n\
+             {1} 3 3 1\nauthor Bob Smith\nauthor-time 200\nsummary Add c\nprevious {0} a.txt\nfilename a.txt\n\tc\n",
+            FIRST, SECOND
+        );
+        let blame = Blame::parse(output.as_str());
+        assert_eq!(blame.lines_count(), 3);
+        assert_eq!(blame.commits().len(), 2);
+        assert_eq!(
+            blame.commit_at(1),
+            Some(&BlameCommit {
+                id: FIRST.to_owned(),
+                author: "Ann".to_owned(),
+                time: 100,
+                summary: "Init".to_owned(),
+            })
+        );
+        let second = blame.commit_at(2).unwrap();
+        assert_eq!(second.author, "Bob Smith".to_owned());
+        assert_eq!(second.short_id(), "2222222");
+        assert_eq!(second.is_committed(), true);
+        assert_eq!(blame.commit_at(3), None);
+    }
+
+    #[test]
+    fn must_describe_relative_date() {
+        assert_eq!(relative_date(100, 130), "just now".to_owned());
+        assert_eq!(relative_date(0, 60), "1 minute ago".to_owned());
+        assert_eq!(relative_date(0, 3 * 3600), "3 hours ago".to_owned());
+        assert_eq!(relative_date(0, 2 * 86400), "2 days ago".to_owned());
+        assert_eq!(relative_date(0, 90 * 86400), "3 months ago".to_owned());
+        assert_eq!(relative_date(0, 800 * 86400), "2 years ago".to_owned());
+    }
+
+    #[test]
+    fn must_blame_buffer_and_read_commit() {
+        let dir = git_repository("blame");
+        let path = format!("{}/a.txt", dir);
+        let blame = blame_buffer(path.as_str(), "first\nchanged\n").unwrap();
+        let first = blame.commit_at(0).unwrap().clone();
+        assert_eq!(first.author, "rider".to_owned());
+        assert_eq!(first.summary, "init".to_owned());
+        assert_eq!(blame.commit_at(1).map(|c| c.is_committed()), Some(false));
+
+        let message = commit_message(dir.as_str(), first.id.as_str()).unwrap();
+        assert_eq!(
+            message.starts_with(format!("commit {}", first.id).as_str()),
+            true
+        );
+        assert_eq!(message.ends_with("init"), true);
+        let diff = commit_diff(dir.as_str(), first.id.as_str()).unwrap();
+        assert_eq!(diff.contains("+second"), true);
+
+        fs::write(format!("{}/new.txt", dir), "new\n").unwrap();
+        let untracked = format!("{}/new.txt", dir);
+        assert_eq!(blame_buffer(untracked.as_str(), "new\n").is_err(), true);
+    }
+}
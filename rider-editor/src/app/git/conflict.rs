@@ -0,0 +1,151 @@
+use std::ops::Range;
+
+const OURS_MARKER: &str = "<<<<<<<";
+const BASE_MARKER: &str = "|||||||";
+const SEPARATOR: &str = "=======";
+const THEIRS_MARKER: &str = ">>>>>>>";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConflictResolution {
+    Ours,
+    Theirs,
+    Both,
+}
+
+/// Region between conflict markers left by merge, lines are counted from 0.
+///
+/// Ranges hold lines of every side without marker lines, `base` is present only in `diff3` style.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Conflict {
+    pub start: usize,
+    pub ours: Range<usize>,
+    pub base: Option<Range<usize>>,
+    pub theirs: Range<usize>,
+    pub end: usize,
+}
+
+impl Conflict {
+    pub fn contains_line(&self, line: usize) -> bool {
+        line >= self.start && line <= self.end
+    }
+
+    /// Lines with markers, they are not part of any side
+    pub fn marker_lines(&self) -> Vec<usize> {
+        let mut lines = vec![self.start];
+        if let Some(base) = self.base.as_ref() {
+            lines.push(base.start - 1);
+        }
+        lines.push(self.theirs.start - 1);
+        lines.push(self.end);
+        lines
+    }
+}
+
+/// Finds every complete conflict of text, markers must start their lines
+pub fn parse_conflicts(text: &str) -> Vec<Conflict> {
+    let mut conflicts = vec![];
+    let mut start: Option<usize> = None;
+    let mut base: Option<usize> = None;
+    let mut separator: Option<usize> = None;
+    for (line, content) in text.split('\n').enumerate() {
+        if content.starts_with(OURS_MARKER) {
+            start = Some(line);
+            base = None;
+            separator = None;
+        } else if content.starts_with(BASE_MARKER) && start.is_some() && separator.is_none() {
+            base = Some(line);
+        } else if content.starts_with(SEPARATOR) && start.is_some() && separator.is_none() {
+            separator = Some(line);
+        } else if content.starts_with(THEIRS_MARKER) {
+            if let (Some(start), Some(separator)) = (start, separator) {
+                let ours_end = base.unwrap_or(separator);
+                conflicts.push(Conflict {
+                    start,
+                    ours: start + 1..ours_end,
+                    base: base.map(|base| base + 1..separator),
+                    theirs: separator + 1..line,
+                    end: line,
+                });
+            }
+            start = None;
+            base = None;
+            separator = None;
+        }
+    }
+    conflicts
+}
+
+/// Text with conflict replaced by lines of chosen side, both sides keep ours before theirs
+pub fn resolve_conflict(text: &str, conflict: &Conflict, resolution: ConflictResolution) -> String {
+    let mut lines: Vec<&str> = text.split('\n').collect();
+    if conflict.end >= lines.len() {
+        return text.to_owned();
+    }
+    let ours: Vec<&str> = lines[conflict.ours.clone()].to_vec();
+    let theirs: Vec<&str> = lines[conflict.theirs.clone()].to_vec();
+    let resolved = match resolution {
+        ConflictResolution::Ours => ours,
+        ConflictResolution::Theirs => theirs,
+        ConflictResolution::Both => ours.into_iter().chain(theirs.into_iter()).collect(),
+    };
+    lines.splice(conflict.start..=conflict.end, resolved);
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "a\n<<<<<<< HEAD\nours\n=======\ntheirs 1\ntheirs 2\n>>>>>>> topic\nb\n<<<<<<< HEAD\nmine\n||||||| base\nold\n=======\n>>>>>>> other\n";
+
+    #[test]
+    fn must_find_conflicts() {
+        let conflicts = parse_conflicts(TEXT);
+        assert_eq!(
+            conflicts,
+            vec![
+                Conflict {
+                    start: 1,
+                    ours: 2..3,
+                    base: None,
+                    theirs: 4..6,
+                    end: 6,
+                },
+                Conflict {
+                    start: 8,
+                    ours: 9..10,
+                    base: Some(11..12),
+                    theirs: 13..13,
+                    end: 13,
+                },
+            ]
+        );
+        assert_eq!(conflicts[1].marker_lines(), vec![8, 10, 12, 13]);
+        assert_eq!(conflicts[0].contains_line(6), true);
+        assert_eq!(conflicts[0].contains_line(7), false);
+        assert_eq!(parse_conflicts("<<<<<<< HEAD\nours\n=======\n"), vec![]);
+        assert_eq!(parse_conflicts("a\n=======\n>>>>>>> b\n"), vec![]);
+    }
+
+    #[test]
+    fn must_resolve_conflict() {
+        let conflicts = parse_conflicts(TEXT);
+        assert_eq!(
+            resolve_conflict(TEXT, &conflicts[0], ConflictResolution::Ours),
+            "a\nours\nb\n<<<<<<< HEAD\nmine\n||||||| base\nold\n=======\n>>>>>>> other\n"
+                .to_owned()
+        );
+        assert_eq!(
+            resolve_conflict(TEXT, &conflicts[0], ConflictResolution::Theirs),
+            "a\ntheirs 1\ntheirs 2\nb\n<<<<<<< HEAD\nmine\n||||||| base\nold\n=======\n>>>>>>> other\n"
+                .to_owned()
+        );
+        let both = resolve_conflict(TEXT, &conflicts[1], ConflictResolution::Both);
+        assert_eq!(
+            both,
+            "a\n<<<<<<< HEAD\nours\n=======\ntheirs 1\ntheirs 2\n>>>>>>> topic\nb\nmine\n"
+                .to_owned()
+        );
+        assert_eq!(parse_conflicts(both.as_str()).len(), 1);
+    }
+}
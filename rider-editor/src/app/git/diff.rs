@@ -0,0 +1,377 @@
+use std::ops::Range;
+
+/// Diffs with more changed lines are reported as one hunk, finding smallest one would take too long
+const MAX_DIFF_COST: usize = 500;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HunkKind {
+    Added,
+    Modified,
+    Deleted,
+}
+
+/// Lines of old text replaced by lines of new text, lines are counted from 0
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hunk {
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+}
+
+impl Hunk {
+    pub fn kind(&self) -> HunkKind {
+        match (self.old_lines, self.new_lines) {
+            (0, _) => HunkKind::Added,
+            (_, 0) => HunkKind::Deleted,
+            _ => HunkKind::Modified,
+        }
+    }
+
+    /// Deleted lines belong to lines above and below them
+    pub fn contains_line(&self, line: usize) -> bool {
+        match self.kind() {
+            HunkKind::Deleted => line == self.new_start || line + 1 == self.new_start,
+            _ => line >= self.new_start && line < self.new_start + self.new_lines,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Edit {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Changed lines of new text compared with old one
+pub fn diff_lines(old: &str, new: &str) -> Vec<Hunk> {
+    let old: Vec<&str> = old.split('\n').collect();
+    let new: Vec<&str> = new.split('\n').collect();
+    let prefix = old
+        .iter()
+        .zip(new.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    let mut hunks = vec![];
+    let (mut old_line, mut new_line) = (prefix, prefix);
+    let mut current: Option<Hunk> = None;
+    for edit in edit_script(old_middle, new_middle) {
+        match edit {
+            Edit::Equal => {
+                hunks.extend(current.take());
+                old_line += 1;
+                new_line += 1;
+                continue;
+            }
+            Edit::Delete => {
+                current_hunk(&mut current, old_line, new_line).old_lines += 1;
+                old_line += 1;
+            }
+            Edit::Insert => {
+                current_hunk(&mut current, old_line, new_line).new_lines += 1;
+                new_line += 1;
+            }
+        }
+    }
+    hunks.extend(current);
+    hunks
+}
+
+fn current_hunk(current: &mut Option<Hunk>, old_start: usize, new_start: usize) -> &mut Hunk {
+    current.get_or_insert(Hunk {
+        old_start,
+        old_lines: 0,
+        new_start,
+        new_lines: 0,
+    })
+}
+
+/// Myers shortest edit script turning old lines into new ones
+fn edit_script(old: &[&str], new: &[&str]) -> Vec<Edit> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max = n + m;
+    if n == 0 || m == 0 || max as usize > MAX_DIFF_COST * 2 {
+        return replace_all(old.len(), new.len());
+    }
+    let offset = max + 1;
+    let mut v = vec![0isize; 2 * offset as usize + 1];
+    // x of furthest path of every diagonal after each round
+    let mut trace: Vec<Vec<isize>> = vec![];
+    for d in 0..=max {
+        if d as usize > MAX_DIFF_COST {
+            return replace_all(old.len(), new.len());
+        }
+        let mut k = -d;
+        while k <= d {
+            let index = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && v[index - 1] < v[index + 1]) {
+                v[index + 1]
+            } else {
+                v[index - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[index] = x;
+            if x >= n && y >= m {
+                trace.push(v[(offset - d) as usize..=(offset + d) as usize].to_vec());
+                return backtrack(&trace, n, m);
+            }
+            k += 2;
+        }
+        trace.push(v[(offset - d) as usize..=(offset + d) as usize].to_vec());
+    }
+    replace_all(old.len(), new.len())
+}
+
+fn backtrack(trace: &[Vec<isize>], n: isize, m: isize) -> Vec<Edit> {
+    let mut edits = vec![];
+    let (mut x, mut y) = (n, m);
+    for d in (1..trace.len() as isize).rev() {
+        let previous = &trace[d as usize - 1];
+        let at = |k: isize| previous[(k + d - 1) as usize];
+        let k = x - y;
+        let previous_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let previous_x = at(previous_k);
+        let previous_y = previous_x - previous_k;
+        while x > previous_x && y > previous_y {
+            edits.push(Edit::Equal);
+            x -= 1;
+            y -= 1;
+        }
+        edits.push(if x == previous_x {
+            Edit::Insert
+        } else {
+            Edit::Delete
+        });
+        x = previous_x;
+        y = previous_y;
+    }
+    while x > 0 && y > 0 {
+        edits.push(Edit::Equal);
+        x -= 1;
+        y -= 1;
+    }
+    edits.reverse();
+    edits
+}
+
+fn replace_all(old: usize, new: usize) -> Vec<Edit> {
+    let mut edits = vec![Edit::Delete; old];
+    edits.extend(vec![Edit::Insert; new]);
+    edits
+}
+
+/// New text with lines of hunk replaced by their old version
+pub fn revert_hunk(old: &str, new: &str, hunk: &Hunk) -> String {
+    let old: Vec<&str> = old.split('\n').collect();
+    let mut lines: Vec<&str> = new.split('\n').collect();
+    let old_end = (hunk.old_start + hunk.old_lines).min(old.len());
+    let new_end = (hunk.new_start + hunk.new_lines).min(lines.len());
+    lines.splice(
+        hunk.new_start.min(new_end)..new_end,
+        old[hunk.old_start.min(old_end)..old_end].iter().cloned(),
+    );
+    lines.join("\n")
+}
+
+/// Old text with its lines of hunk replaced by new version of them
+pub fn apply_hunk(old: &str, new: &str, hunk: &Hunk) -> String {
+    let new: Vec<&str> = new.split('\n').collect();
+    let mut lines: Vec<&str> = old.split('\n').collect();
+    let old_end = (hunk.old_start + hunk.old_lines).min(lines.len());
+    let new_end = (hunk.new_start + hunk.new_lines).min(new.len());
+    lines.splice(
+        hunk.old_start.min(old_end)..old_end,
+        new[hunk.new_start.min(new_end)..new_end].iter().cloned(),
+    );
+    lines.join("\n")
+}
+
+/// Changed characters of two versions of line, both ranges are empty when lines are equal
+pub fn diff_chars(old: &str, new: &str) -> (Range<usize>, Range<usize>) {
+    let old: Vec<char> = old.chars().collect();
+    let new: Vec<char> = new.chars().collect();
+    let prefix = old
+        .iter()
+        .zip(new.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    (prefix..old.len() - suffix, prefix..new.len() - suffix)
+}
+
+/// Line of old text matching line of new one, lines changed by hunk map to its start
+pub fn old_line(hunks: &[Hunk], line: usize) -> usize {
+    let mut offset: isize = 0;
+    for hunk in hunks {
+        if line < hunk.new_start {
+            break;
+        }
+        if line < hunk.new_start + hunk.new_lines {
+            return hunk.old_start;
+        }
+        offset += hunk.old_lines as isize - hunk.new_lines as isize;
+    }
+    (line as isize + offset).max(0) as usize
+}
+
+/// Line of new text matching line of old one, lines changed by hunk map to line at same offset in it
+pub fn new_line(hunks: &[Hunk], line: usize) -> usize {
+    let mut offset: isize = 0;
+    for hunk in hunks {
+        if line < hunk.old_start {
+            break;
+        }
+        if line < hunk.old_start + hunk.old_lines {
+            let changed = (line - hunk.old_start).min(hunk.new_lines.saturating_sub(1));
+            return hunk.new_start + changed;
+        }
+        offset += hunk.new_lines as isize - hunk.old_lines as isize;
+    }
+    (line as isize + offset).max(0) as usize
+}
+
+/// Hunk in unified diff format, line numbers in header are counted from 1.
+///
+/// Empty range starts at line above it, like in output of git.
+pub fn hunk_text(old: &str, new: &str, hunk: &Hunk) -> String {
+    let header_start = |start: usize, lines: usize| match lines {
+        0 => start,
+        _ => start + 1,
+    };
+    let mut text = format!(
+        "@@ -{},{} +{},{} @@",
+        header_start(hunk.old_start, hunk.old_lines),
+        hunk.old_lines,
+        header_start(hunk.new_start, hunk.new_lines),
+        hunk.new_lines
+    );
+    let removed = old.split('\n').skip(hunk.old_start).take(hunk.old_lines);
+    let added = new.split('\n').skip(hunk.new_start).take(hunk.new_lines);
+    for line in removed {
+        text.push_str(format!("\n-{}", line).as_str());
+    }
+    for line in added {
+        text.push_str(format!("\n+{}", line).as_str());
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hunk(old_start: usize, old_lines: usize, new_start: usize, new_lines: usize) -> Hunk {
+        Hunk {
+            old_start,
+            old_lines,
+            new_start,
+            new_lines,
+        }
+    }
+
+    #[test]
+    fn must_find_added_modified_and_deleted_lines() {
+        let old = "a\nb\nc\nd\ne\nf";
+        let new = "a\nnew\nb\nC\nd\nf";
+        let hunks = diff_lines(old, new);
+        assert_eq!(
+            hunks,
+            vec![hunk(1, 0, 1, 1), hunk(2, 1, 3, 1), hunk(4, 1, 5, 0)]
+        );
+        let kinds: Vec<HunkKind> = hunks.iter().map(|h| h.kind()).collect();
+        assert_eq!(
+            kinds,
+            vec![HunkKind::Added, HunkKind::Modified, HunkKind::Deleted]
+        );
+        assert_eq!(hunks[2].contains_line(4), true);
+        assert_eq!(hunks[2].contains_line(5), true);
+        assert_eq!(hunks[2].contains_line(3), false);
+        assert_eq!(diff_lines(old, old), vec![]);
+        assert_eq!(diff_lines("", "a\nb"), vec![hunk(0, 1, 0, 2)]);
+    }
+
+    #[test]
+    fn must_report_large_changes_as_one_hunk() {
+        let added: Vec<String> = (0..5000).map(|i| format!("line {}", i)).collect();
+        let added = added.join("\n");
+        assert_eq!(diff_lines("", &added), vec![hunk(0, 1, 0, 5000)]);
+        let rewritten = added.replace("line", "row");
+        assert_eq!(diff_lines(&added, &rewritten), vec![hunk(0, 5000, 0, 5000)]);
+    }
+
+    #[test]
+    fn must_revert_hunk_and_describe_it() {
+        let old = "a\nb\nc";
+        let new = "a\nB\nc\nd";
+        let hunks = diff_lines(old, new);
+        assert_eq!(hunks, vec![hunk(1, 1, 1, 1), hunk(3, 0, 3, 1)]);
+        assert_eq!(revert_hunk(old, new, &hunks[0]), "a\nb\nc\nd".to_owned());
+        assert_eq!(revert_hunk(old, new, &hunks[1]), "a\nB\nc".to_owned());
+        assert_eq!(
+            hunk_text(old, new, &hunks[0]),
+            "@@ -2,1 +2,1 @@\n-b\n+B".to_owned()
+        );
+        assert_eq!(
+            hunk_text(old, new, &hunks[1]),
+            "@@ -3,0 +4,1 @@\n+d".to_owned()
+        );
+    }
+
+    #[test]
+    fn must_apply_hunk_and_find_changed_characters() {
+        let old = "a\nb\nc";
+        let new = "a\nB\nc\nd";
+        let hunks = diff_lines(old, new);
+        assert_eq!(apply_hunk(old, new, &hunks[0]), "a\nB\nc".to_owned());
+        assert_eq!(apply_hunk(old, new, &hunks[1]), "a\nb\nc\nd".to_owned());
+        assert_eq!(diff_chars("let a = 1;", "let b = 1;"), (4..5, 4..5));
+        assert_eq!(diff_chars("foo()", "foo(bar)"), (4..4, 4..7));
+        assert_eq!(diff_chars("same", "same"), (4..4, 4..4));
+    }
+
+    #[test]
+    fn must_map_line_to_old_text() {
+        let hunks = diff_lines("a\nb\nc\nd", "a\nx\ny\nb\nd");
+        assert_eq!(hunks, vec![hunk(1, 0, 1, 2), hunk(2, 1, 4, 0)]);
+        assert_eq!(old_line(&hunks, 0), 0);
+        assert_eq!(old_line(&hunks, 2), 1);
+        assert_eq!(old_line(&hunks, 3), 1);
+        assert_eq!(old_line(&hunks, 4), 3);
+    }
+
+    #[test]
+    fn must_map_line_to_new_text() {
+        let hunks = diff_lines("a\nb\nc\nd", "a\nx\ny\nb\nd");
+        assert_eq!(new_line(&hunks, 0), 0);
+        assert_eq!(new_line(&hunks, 1), 3);
+        assert_eq!(new_line(&hunks, 2), 4);
+        assert_eq!(new_line(&hunks, 3), 4);
+
+        let hunks = diff_lines("a\nb\nc", "a\nB\nB2\nB3\nc");
+        assert_eq!(new_line(&hunks, 1), 1);
+        assert_eq!(new_line(&hunks, 2), 4);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::app::git::*;
+
+/// Adds file to index, deleted file is removed from it
+pub fn stage_file(dir: &str, path: &str) -> Result<(), String> {
+    run_git(dir, &["add", "-A", "--", path]).map(|_| ())
+}
+
+/// Takes file back from index, its content stays in working tree
+pub fn unstage_file(dir: &str, path: &str) -> Result<(), String> {
+    run_git(dir, &["reset", "-q", "--", path]).map(|_| ())
+}
+
+/// Applies hunk turning `old` text of file into `new` one to index.
+///
+/// Reversed hunk turns `new` back into `old`, file must be tracked.
+pub fn apply_hunk_to_index(
+    path: &str,
+    old: &str,
+    new: &str,
+    hunk: &Hunk,
+    reverse: bool,
+) -> Result<(), String> {
+    let (dir, name) = split_path(path).ok_or_else(|| format!("Invalid path {}", path))?;
+    let root = run_git(dir, &["rev-parse", "--show-toplevel"])?;
+    let tracked = run_git(dir, &["ls-files", "--full-name", "--", name])?;
+    let relative = match tracked.lines().next() {
+        Some(relative) => relative.to_owned(),
+        None => return Err(format!("{} is not tracked", path)),
+    };
+    let patch = format!(
+        "--- a/{0}\n+++ b/{0}\n{1}\n",
+        relative,
+        hunk_text(old, new, hunk)
+    );
+    let mut args = vec!["apply", "--cached", "--unidiff-zero"];
+    if reverse {
+        args.push("-R");
+    }
+    args.push("-");
+    run_git_with_input(root.trim(), args.as_slice(), patch.as_str()).map(|_| ())
+}
+
+/// Commits staged changes, message can not be empty
+pub fn commit(dir: &str, message: &str) -> Result<(), String> {
+    if message.trim().is_empty() {
+        return Err("Commit message is empty".to_owned());
+    }
+    run_git(dir, &["commit", "-q", "-m", message]).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use std::fs;
+
+    #[test]
+    fn must_stage_and_unstage_file() {
+        let dir = git_repository("stage-file");
+        let path = format!("{}/a.txt", dir);
+        fs::write(&path, "changed\n").unwrap();
+        let status = || repository_status(dir.as_str()).unwrap().changes()[0].clone();
+        assert_eq!(status().is_staged(), false);
+        assert_eq!(stage_file(dir.as_str(), path.as_str()), Ok(()));
+        assert_eq!(status().is_staged(), true);
+        assert_eq!(status().has_unstaged(), false);
+        assert_eq!(unstage_file(dir.as_str(), path.as_str()), Ok(()));
+        assert_eq!(status().is_staged(), false);
+    }
+
+    #[test]
+    fn must_stage_and_unstage_hunk() {
+        let dir = git_repository("stage-hunk");
+        let path = format!("{}/a.txt", dir);
+        let head = "first\nsecond\n";
+        let new = "zero\nfirst\nsecond\nthird\n";
+        fs::write(&path, new).unwrap();
+        let hunks = diff_lines(head, new);
+        assert_eq!(hunks.len(), 2);
+
+        assert_eq!(
+            apply_hunk_to_index(path.as_str(), head, new, &hunks[1], false),
+            Ok(())
+        );
+        let index = index_content(path.as_str()).unwrap();
+        assert_eq!(index, "first\nsecond\nthird\n".to_owned());
+
+        let hunks = diff_lines(head, index.as_str());
+        assert_eq!(
+            apply_hunk_to_index(path.as_str(), head, index.as_str(), &hunks[0], true),
+            Ok(())
+        );
+        assert_eq!(index_content(path.as_str()), Some(head.to_owned()));
+
+        let untracked = format!("{}/new.txt", dir);
+        fs::write(&untracked, "new\n").unwrap();
+        assert_eq!(
+            apply_hunk_to_index(untracked.as_str(), "", "new\n", &hunks[0], false).is_err(),
+            true
+        );
+    }
+
+    #[test]
+    fn must_commit_staged_changes() {
+        let dir = git_repository("commit");
+        let path = format!("{}/a.txt", dir);
+        fs::write(&path, "changed\n").unwrap();
+        stage_file(dir.as_str(), path.as_str()).unwrap();
+        assert_eq!(commit(dir.as_str(), " \n").is_err(), true);
+        assert_eq!(commit(dir.as_str(), "Change a"), Ok(()));
+        assert_eq!(head_content(path.as_str()), Some("changed\n".to_owned()));
+        assert_eq!(repository_status(dir.as_str()).unwrap().changes(), vec![]);
+    }
+}
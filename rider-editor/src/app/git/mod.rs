@@ -0,0 +1,124 @@
+pub mod blame;
+pub mod conflict;
+pub mod diff;
+pub mod index;
+pub mod status;
+
+pub use crate::app::git::blame::*;
+pub use crate::app::git::conflict::*;
+pub use crate::app::git::diff::*;
+pub use crate::app::git::index::*;
+pub use crate::app::git::status::*;
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+/// Runs git in given directory and returns its output, failure message comes from stderr
+pub fn run_git(dir: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .map_err(|e| format!("Failed to start git: {}", e))?;
+    git_output(args, output)
+}
+
+/// Runs git with input written to its stdin
+pub fn run_git_with_input(dir: &str, args: &[&str], input: &str) -> Result<String, String> {
+    let mut child = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to start git: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(input.as_bytes())
+            .map_err(|e| format!("Failed to write to git: {}", e))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to wait for git: {}", e))?;
+    git_output(args, output)
+}
+
+fn git_output(args: &[&str], output: Output) -> Result<String, String> {
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr
+            .lines()
+            .map(|line| line.trim())
+            .find(|line| !line.is_empty())
+            .map_or_else(|| output.status.to_string(), |line| line.to_owned());
+        return Err(format!("git {} failed: {}", args.join(" "), reason));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Content of file in HEAD of repository containing it.
+///
+/// `None` when file is not in repository or it is ignored, file which is not in HEAD is empty.
+pub fn head_content(path: &str) -> Option<String> {
+    let (dir, name) = split_path(path)?;
+    match run_git(dir, &["rev-parse", "--is-inside-work-tree"]) {
+        Ok(ref output) if output.trim() == "true" => (),
+        _ => return None,
+    }
+    if run_git(dir, &["check-ignore", "-q", name]).is_ok() {
+        return None;
+    }
+    let object = format!("HEAD:./{}", name);
+    Some(run_git(dir, &["show", object.as_str()]).unwrap_or_default())
+}
+
+/// Content of file in index, `None` when file is not tracked
+pub fn index_content(path: &str) -> Option<String> {
+    let (dir, name) = split_path(path)?;
+    let object = format!(":./{}", name);
+    run_git(dir, &["show", object.as_str()]).ok()
+}
+
+/// Directory of file, where git should run, and its name
+fn split_path(path: &str) -> Option<(&str, &str)> {
+    let path = Path::new(path);
+    let dir = path.parent()?.to_str()?;
+    let name = path.file_name()?.to_str()?;
+    Some((if dir.is_empty() { "." } else { dir }, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use std::fs;
+
+    #[test]
+    fn must_read_head_content() {
+        let dir = git_repository("head-content");
+        fs::write(format!("{}/a.txt", dir), "changed\n").unwrap();
+        fs::write(format!("{}/new.txt", dir), "new\n").unwrap();
+        fs::write(format!("{}/out.log", dir), "log\n").unwrap();
+        assert_eq!(
+            head_content(format!("{}/a.txt", dir).as_str()),
+            Some("first\nsecond\n".to_owned())
+        );
+        assert_eq!(
+            head_content(format!("{}/new.txt", dir).as_str()),
+            Some(String::new())
+        );
+        assert_eq!(head_content(format!("{}/out.log", dir).as_str()), None);
+        assert_eq!(head_content("/tmp/rider-not-in-repository.txt"), None);
+        assert_eq!(
+            index_content(format!("{}/a.txt", dir).as_str()),
+            Some("first\nsecond\n".to_owned())
+        );
+        assert_eq!(index_content(format!("{}/new.txt", dir).as_str()), None);
+        assert_eq!(
+            run_git(dir.as_str(), &["show", "HEAD:missing"]).is_err(),
+            true
+        );
+    }
+}
@@ -0,0 +1,282 @@
+use crate::app::git::run_git;
+
+/// State of path in repository, later variants win when status of directory is aggregated
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GitStatus {
+    Ignored,
+    Untracked,
+    Added,
+    Modified,
+    Conflicted,
+}
+
+impl GitStatus {
+    /// Letter shown next to name of tree entry
+    pub fn badge(&self) -> &'static str {
+        match self {
+            GitStatus::Ignored => "I",
+            GitStatus::Untracked => "U",
+            GitStatus::Added => "A",
+            GitStatus::Modified => "M",
+            GitStatus::Conflicted => "C",
+        }
+    }
+}
+
+/// Path reported by `git status`, `index` and `worktree` are columns of its short format.
+///
+/// Path is relative to root of repository, untracked and ignored directories end with `/`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StatusEntry {
+    pub path: String,
+    pub index: char,
+    pub worktree: char,
+}
+
+impl StatusEntry {
+    pub fn status(&self) -> GitStatus {
+        match (self.index, self.worktree) {
+            ('!', _) => GitStatus::Ignored,
+            ('?', _) => GitStatus::Untracked,
+            ('U', _) | (_, 'U') | ('A', 'A') | ('D', 'D') => GitStatus::Conflicted,
+            ('A', _) => GitStatus::Added,
+            _ => GitStatus::Modified,
+        }
+    }
+
+    /// Index differs from HEAD
+    pub fn is_staged(&self) -> bool {
+        self.index != ' ' && self.index != '?' && self.index != '!'
+    }
+
+    /// Working tree differs from index
+    pub fn has_unstaged(&self) -> bool {
+        self.worktree != ' '
+    }
+}
+
+/// Parses output of `git status --porcelain -z`, renamed files are reported with new path
+pub fn parse_status(output: &str) -> Vec<StatusEntry> {
+    let mut entries = vec![];
+    let mut records = output.split('\0');
+    while let Some(record) = records.next() {
+        let mut chars = record.chars();
+        let (index, worktree) = match (chars.next(), chars.next(), chars.next()) {
+            (Some(index), Some(worktree), Some(' ')) if record.len() > 3 => (index, worktree),
+            _ => continue,
+        };
+        // original path of rename or copy is next record
+        if index == 'R' || index == 'C' {
+            records.next();
+        }
+        entries.push(StatusEntry {
+            path: record[3..].to_owned(),
+            index,
+            worktree,
+        });
+    }
+    entries
+}
+
+/// Every changed, untracked and ignored path of repository
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GitStatuses {
+    root: String,
+    entries: Vec<StatusEntry>,
+}
+
+impl GitStatuses {
+    pub fn new(root: String, entries: Vec<StatusEntry>) -> Self {
+        Self { root, entries }
+    }
+
+    pub fn root(&self) -> &String {
+        &self.root
+    }
+
+    /// Entries which can be committed
+    pub fn changes(&self) -> Vec<StatusEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.status() != GitStatus::Ignored)
+            .cloned()
+            .collect()
+    }
+
+    pub fn absolute_path(&self, entry: &StatusEntry) -> String {
+        format!("{}/{}", self.root, entry.path.trim_end_matches('/'))
+    }
+
+    /// Status of file, files inside of untracked or ignored directory share its status
+    pub fn file_status(&self, path: &str) -> Option<GitStatus> {
+        let relative = self.relative(path)?;
+        self.entries
+            .iter()
+            .find(|entry| entry.path == relative)
+            .or_else(|| self.containing_directory(relative))
+            .map(|entry| entry.status())
+    }
+
+    /// Most important status of files inside of directory.
+    ///
+    /// Ignored files do not mark directory, only ignored directory itself does.
+    pub fn directory_status(&self, path: &str) -> Option<GitStatus> {
+        let relative = self.relative(path)?;
+        let prefix = match relative {
+            "" => String::new(),
+            _ => format!("{}/", relative),
+        };
+        if let Some(entry) = self.containing_directory(prefix.as_str()) {
+            return Some(entry.status());
+        }
+        self.entries
+            .iter()
+            .filter(|entry| entry.path.starts_with(prefix.as_str()))
+            .map(|entry| entry.status())
+            .filter(|status| *status != GitStatus::Ignored)
+            .max()
+    }
+
+    fn containing_directory(&self, relative: &str) -> Option<&StatusEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.path.ends_with('/') && relative.starts_with(entry.path.as_str()))
+    }
+
+    fn relative<'l>(&self, path: &'l str) -> Option<&'l str> {
+        if self.root.is_empty() || !path.starts_with(self.root.as_str()) {
+            return None;
+        }
+        match &path[self.root.len()..] {
+            "" => Some(""),
+            rest if rest.starts_with('/') => Some(&rest[1..]),
+            _ => None,
+        }
+    }
+}
+
+/// Status of repository containing directory, error when directory is not inside of one
+pub fn repository_status(dir: &str) -> Result<GitStatuses, String> {
+    let root = run_git(dir, &["rev-parse", "--show-toplevel"])?
+        .trim()
+        .to_owned();
+    let output = run_git(dir, &["status", "--porcelain", "-z", "--ignored"])?;
+    Ok(GitStatuses::new(root, parse_status(output.as_str())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use std::fs;
+
+    fn entry(path: &str, index: char, worktree: char) -> StatusEntry {
+        StatusEntry {
+            path: path.to_owned(),
+            index,
+            worktree,
+        }
+    }
+
+    #[test]
+    fn must_parse_porcelain_status() {
+        let output = " M a.rs\0R  new.rs\0old.rs\0UU src/c.rs\0A  src/d.rs\0?? tmp/\0!! out.log\0";
+        let entries = parse_status(output);
+        assert_eq!(
+            entries,
+            vec![
+                entry("a.rs", ' ', 'M'),
+                entry("new.rs", 'R', ' '),
+                entry("src/c.rs", 'U', 'U'),
+                entry("src/d.rs", 'A', ' '),
+                entry("tmp/", '?', '?'),
+                entry("out.log", '!', '!'),
+            ]
+        );
+        let statuses: Vec<GitStatus> = entries.iter().map(|e| e.status()).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                GitStatus::Modified,
+                GitStatus::Modified,
+                GitStatus::Conflicted,
+                GitStatus::Added,
+                GitStatus::Untracked,
+                GitStatus::Ignored,
+            ]
+        );
+        assert_eq!(entries[0].is_staged(), false);
+        assert_eq!(entries[0].has_unstaged(), true);
+        assert_eq!(entries[1].is_staged(), true);
+        assert_eq!(entries[1].has_unstaged(), false);
+        assert_eq!(entries[4].is_staged(), false);
+    }
+
+    #[test]
+    fn must_aggregate_status_of_directories() {
+        let statuses = GitStatuses::new(
+            "/repo".to_owned(),
+            vec![
+                entry("src/a.rs", ' ', 'M'),
+                entry("src/deep/b.rs", 'U', 'U'),
+                entry("tmp/", '?', '?'),
+                entry("target/", '!', '!'),
+            ],
+        );
+        assert_eq!(
+            statuses.file_status("/repo/src/a.rs"),
+            Some(GitStatus::Modified)
+        );
+        assert_eq!(
+            statuses.file_status("/repo/tmp/x/y.rs"),
+            Some(GitStatus::Untracked)
+        );
+        assert_eq!(statuses.file_status("/repo/README.md"), None);
+        assert_eq!(statuses.file_status("/other/src/a.rs"), None);
+        assert_eq!(
+            statuses.directory_status("/repo/src"),
+            Some(GitStatus::Conflicted)
+        );
+        assert_eq!(
+            statuses.directory_status("/repo/target/debug"),
+            Some(GitStatus::Ignored)
+        );
+        assert_eq!(
+            statuses.directory_status("/repo"),
+            Some(GitStatus::Conflicted)
+        );
+        assert_eq!(statuses.directory_status("/repo/srcs"), None);
+        assert_eq!(statuses.changes().len(), 3);
+        assert_eq!(
+            statuses.absolute_path(&statuses.changes()[2]),
+            "/repo/tmp".to_owned()
+        );
+    }
+
+    #[test]
+    fn must_read_repository_status() {
+        let dir = git_repository("status");
+        fs::write(format!("{}/a.txt", dir), "changed\n").unwrap();
+        fs::write(format!("{}/new.txt", dir), "new\n").unwrap();
+        fs::write(format!("{}/out.log", dir), "log\n").unwrap();
+        let statuses = repository_status(dir.as_str()).unwrap();
+        assert_eq!(statuses.root(), &dir);
+        assert_eq!(
+            statuses.file_status(format!("{}/a.txt", dir).as_str()),
+            Some(GitStatus::Modified)
+        );
+        assert_eq!(
+            statuses.file_status(format!("{}/new.txt", dir).as_str()),
+            Some(GitStatus::Untracked)
+        );
+        assert_eq!(
+            statuses.file_status(format!("{}/out.log", dir).as_str()),
+            Some(GitStatus::Ignored)
+        );
+        assert_eq!(
+            statuses.file_status(format!("{}/.gitignore", dir).as_str()),
+            None
+        );
+        assert_eq!(repository_status("/").is_err(), true);
+    }
+}
@@ -0,0 +1,102 @@
+use crate::app::symbol_index::Location;
+
+const MAX_JUMPS: usize = 100;
+
+/// Locations left by jumps, going back remembers current location so it's possible to go forward
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct JumpList {
+    back: Vec<Location>,
+    forward: Vec<Location>,
+}
+
+impl JumpList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remembers location before jump, forward history is dropped
+    pub fn push(&mut self, location: Location) {
+        self.forward.clear();
+        if self.back.last() == Some(&location) {
+            return;
+        }
+        self.back.push(location);
+        if self.back.len() > MAX_JUMPS {
+            self.back.remove(0);
+        }
+    }
+
+    pub fn back(&mut self, current: Location) -> Option<Location> {
+        let location = self.back.pop()?;
+        self.forward.push(current);
+        Some(location)
+    }
+
+    pub fn forward(&mut self, current: Location) -> Option<Location> {
+        let location = self.forward.pop()?;
+        self.back.push(current);
+        Some(location)
+    }
+
+    /// Location given by `back` can't be shown, it's forgotten and current one stays in place
+    pub fn drop_back(&mut self) {
+        self.forward.pop();
+    }
+
+    /// Location given by `forward` can't be shown, it's forgotten and current one stays in place
+    pub fn drop_forward(&mut self) {
+        self.back.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn location(line: usize) -> Location {
+        Location {
+            path: "a.rs".to_owned(),
+            line,
+            column: 0,
+        }
+    }
+
+    #[test]
+    fn must_go_back_and_forward() {
+        let mut jumps = JumpList::new();
+        assert_eq!(jumps.back(location(0)), None);
+        jumps.push(location(1));
+        jumps.push(location(2));
+        assert_eq!(jumps.back(location(3)), Some(location(2)));
+        assert_eq!(jumps.back(location(2)), Some(location(1)));
+        assert_eq!(jumps.back(location(1)), None);
+        assert_eq!(jumps.forward(location(1)), Some(location(2)));
+        assert_eq!(jumps.forward(location(2)), Some(location(3)));
+        assert_eq!(jumps.forward(location(3)), None);
+    }
+
+    #[test]
+    fn must_forget_locations_which_can_not_be_shown() {
+        let mut jumps = JumpList::new();
+        jumps.push(location(1));
+        jumps.push(location(2));
+        assert_eq!(jumps.back(location(3)), Some(location(2)));
+        jumps.drop_back();
+        assert_eq!(jumps.forward(location(3)), None);
+        assert_eq!(jumps.back(location(3)), Some(location(1)));
+        assert_eq!(jumps.forward(location(1)), Some(location(3)));
+        jumps.drop_forward();
+        assert_eq!(jumps.back(location(1)), None);
+    }
+
+    #[test]
+    fn must_drop_forward_history_on_new_jump() {
+        let mut jumps = JumpList::new();
+        jumps.push(location(1));
+        jumps.back(location(2));
+        jumps.push(location(1));
+        assert_eq!(jumps.forward(location(5)), None);
+        assert_eq!(jumps.back(location(5)), Some(location(1)));
+        assert_eq!(jumps.back(location(1)), None);
+    }
+}
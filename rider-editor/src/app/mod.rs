@@ -1,12 +1,36 @@
 pub mod app_state;
 pub mod application;
 pub mod caret_manager;
+pub mod cargo_runner;
+pub mod completion;
+pub mod diagnostics;
+pub mod external_change;
 pub mod file_content_manager;
+pub mod formatter;
+pub mod fs_watcher;
+pub mod fuzzy;
+pub mod git;
+pub mod jump_list;
+pub mod snippets;
+pub mod symbol_index;
+pub mod terminal;
 
 pub use crate::app::app_state::*;
 pub use crate::app::application::*;
 pub use crate::app::caret_manager::*;
+pub use crate::app::cargo_runner::*;
+pub use crate::app::completion::*;
+pub use crate::app::diagnostics::*;
+pub use crate::app::external_change::*;
 pub use crate::app::file_content_manager::*;
+pub use crate::app::formatter::*;
+pub use crate::app::fs_watcher::*;
+pub use crate::app::fuzzy::*;
+pub use crate::app::git::*;
+pub use crate::app::jump_list::*;
+pub use crate::app::snippets::*;
+pub use crate::app::symbol_index::*;
+pub use crate::app::terminal::*;
 
 pub trait Resize {
     fn resize_element(&mut self);
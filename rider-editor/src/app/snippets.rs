@@ -0,0 +1,593 @@
+use crate::app::file_content_manager::Replacement;
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Tab stop of expanded snippet, first range is edited and the rest mirror it
+#[derive(Clone, Debug, PartialEq)]
+pub struct TabStop {
+    pub index: usize,
+    pub ranges: Vec<(usize, usize)>,
+}
+
+/// Snippet body with resolved variables, ranges are char offsets in `text`.
+///
+/// Stops are ordered as they are visited, `$0` is always last.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Expansion {
+    pub text: String,
+    pub stops: Vec<TabStop>,
+}
+
+#[derive(Clone, Debug)]
+pub enum SnippetEdit {
+    Insert(String),
+    DeleteFront,
+}
+
+struct Placeholder {
+    index: usize,
+    start: usize,
+    end: usize,
+    has_default: bool,
+}
+
+struct Parser<'a> {
+    chars: Vec<char>,
+    index: usize,
+    text: Vec<char>,
+    placeholders: Vec<Placeholder>,
+    variables: &'a HashMap<String, String>,
+    defaults: &'a HashMap<usize, String>,
+    indent: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn new(
+        body: &str,
+        variables: &'a HashMap<String, String>,
+        defaults: &'a HashMap<usize, String>,
+        indent: &'a str,
+    ) -> Self {
+        Self {
+            chars: body.chars().collect(),
+            index: 0,
+            text: vec![],
+            placeholders: vec![],
+            variables,
+            defaults,
+            indent,
+        }
+    }
+
+    fn run(mut self) -> Self {
+        self.parse(false);
+        self
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).cloned()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        self.index += 1;
+        c
+    }
+
+    fn parse(&mut self, nested: bool) {
+        while let Some(c) = self.next() {
+            match c {
+                '\\' => match self.peek() {
+                    Some(escaped @ '$') | Some(escaped @ '}') | Some(escaped @ '\\') => {
+                        self.index += 1;
+                        self.text.push(escaped);
+                    }
+                    _ => self.text.push(c),
+                },
+                '}' if nested => return,
+                '$' => self.parse_dollar(),
+                '\n' => {
+                    self.text.push('\n');
+                    self.text.extend(self.indent.chars());
+                }
+                _ => self.text.push(c),
+            }
+        }
+    }
+
+    fn parse_dollar(&mut self) {
+        match self.peek() {
+            Some(c) if c.is_ascii_digit() => {
+                let index = self.number();
+                self.push_stop(index);
+            }
+            Some('{') => {
+                self.index += 1;
+                match self.peek() {
+                    Some(c) if c.is_ascii_digit() => {
+                        let index = self.number();
+                        let start = self.text.len();
+                        match self.next() {
+                            Some(':') => {
+                                self.parse(true);
+                                self.placeholders.push(Placeholder {
+                                    index,
+                                    start,
+                                    end: self.text.len(),
+                                    has_default: true,
+                                });
+                            }
+                            _ => self.push_stop(index),
+                        }
+                    }
+                    _ => {
+                        let name = self.name();
+                        let start = self.text.len();
+                        let placeholders = self.placeholders.len();
+                        if self.next() == Some(':') {
+                            self.parse(true);
+                        }
+                        if let Some(value) = self.variables.get(&name) {
+                            self.text.truncate(start);
+                            self.placeholders.truncate(placeholders);
+                            self.text.extend(value.chars());
+                        }
+                    }
+                }
+            }
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                let name = self.name();
+                match self.variables.get(&name) {
+                    Some(value) => self.text.extend(value.chars()),
+                    None => {
+                        self.text.push('$');
+                        self.text.extend(name.chars());
+                    }
+                }
+            }
+            _ => self.text.push('$'),
+        }
+    }
+
+    /// Tab stop without own text gets text of placeholder with the same index
+    fn push_stop(&mut self, index: usize) {
+        let start = self.text.len();
+        if let Some(default) = self.defaults.get(&index) {
+            self.text.extend(default.chars());
+        }
+        self.placeholders.push(Placeholder {
+            index,
+            start,
+            end: self.text.len(),
+            has_default: false,
+        });
+    }
+
+    fn number(&mut self) -> usize {
+        let mut number = 0;
+        while let Some(digit) = self.peek().and_then(|c| c.to_digit(10)) {
+            number = number * 10 + digit as usize;
+            self.index += 1;
+        }
+        number
+    }
+
+    fn name(&mut self) -> String {
+        let mut name = String::new();
+        while let Some(c) = self.peek() {
+            if !(c.is_alphanumeric() || c == '_') {
+                break;
+            }
+            name.push(c);
+            self.index += 1;
+        }
+        name
+    }
+}
+
+/// Expands snippet body, lines after first one are prefixed with indent.
+///
+/// Supported syntax: `$1`, `${1}`, `${1:default}`, `$NAME`, `${NAME}` and `${NAME:default}`,
+/// `\$`, `\}` and `\\` are escapes. Unknown `$NAME` is kept as is.
+pub fn expand(body: &str, variables: &HashMap<String, String>, indent: &str) -> Expansion {
+    let no_defaults = HashMap::new();
+    let first = Parser::new(body, variables, &no_defaults, indent).run();
+    let mut defaults: HashMap<usize, String> = HashMap::new();
+    for placeholder in first.placeholders.iter().filter(|p| p.has_default) {
+        defaults.entry(placeholder.index).or_insert_with(|| {
+            first.text[placeholder.start..placeholder.end]
+                .iter()
+                .collect()
+        });
+    }
+
+    let mut parsed = Parser::new(body, variables, &defaults, indent).run();
+    parsed
+        .placeholders
+        .sort_by_key(|p| (!p.has_default, p.start));
+    let mut indexes: Vec<usize> = parsed.placeholders.iter().map(|p| p.index).collect();
+    indexes.sort_by_key(|index| (*index == 0, *index));
+    indexes.dedup();
+    let mut stops: Vec<TabStop> = indexes
+        .into_iter()
+        .map(|index| TabStop {
+            index,
+            ranges: parsed
+                .placeholders
+                .iter()
+                .filter(|p| p.index == index)
+                .map(|p| (p.start, p.end))
+                .collect(),
+        })
+        .collect();
+    if stops.last().map_or(true, |stop| stop.index != 0) {
+        let end = parsed.text.len();
+        stops.push(TabStop {
+            index: 0,
+            ranges: vec![(end, end)],
+        });
+    }
+    Expansion {
+        text: parsed.text.iter().collect(),
+        stops,
+    }
+}
+
+/// Variables available in snippets for file at path, date and time are shifted by seconds of
+/// offset from UTC
+pub fn variables(path: &str, now: SystemTime, utc_offset: i64) -> HashMap<String, String> {
+    let file_path = Path::new(path);
+    let text = |part: Option<&OsStr>| part.and_then(|s| s.to_str()).unwrap_or_default().to_owned();
+    let seconds = now
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or(0)
+        + utc_offset;
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86400));
+    let seconds = seconds.rem_euclid(86400);
+    let mut variables = HashMap::new();
+    variables.insert("TM_FILEPATH".to_owned(), path.to_owned());
+    variables.insert("TM_FILENAME".to_owned(), text(file_path.file_name()));
+    variables.insert("TM_FILENAME_BASE".to_owned(), text(file_path.file_stem()));
+    variables.insert(
+        "TM_DIRECTORY".to_owned(),
+        text(file_path.parent().map(|p| p.as_os_str())),
+    );
+    variables.insert("CURRENT_YEAR".to_owned(), year.to_string());
+    variables.insert("CURRENT_MONTH".to_owned(), format!("{:02}", month));
+    variables.insert("CURRENT_DATE".to_owned(), format!("{:02}", day));
+    variables.insert(
+        "CURRENT_HOUR".to_owned(),
+        format!("{:02}", seconds / 3600 % 24),
+    );
+    variables.insert(
+        "CURRENT_MINUTE".to_owned(),
+        format!("{:02}", seconds / 60 % 60),
+    );
+    variables.insert("CURRENT_SECOND".to_owned(), format!("{:02}", seconds % 60));
+    variables
+}
+
+/// Seconds local time zone is ahead of UTC at given time
+pub fn local_utc_offset(now: SystemTime) -> i64 {
+    let seconds = now
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0) as libc::time_t;
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&seconds, &mut tm) }.is_null() {
+        return 0;
+    }
+    tm.tm_gmtoff as i64
+}
+
+/// Year, month and day of days since unix epoch
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = if z >= 0 { z } else { z - 146_096 } / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month as u32, day as u32)
+}
+
+/// Leading whitespace of line containing given char position
+pub fn line_indent(buffer: &str, position: usize) -> String {
+    let chars: Vec<char> = buffer.chars().take(position).collect();
+    let start = chars
+        .iter()
+        .rposition(|c| *c == '\n')
+        .map_or(0, |index| index + 1);
+    chars[start..]
+        .iter()
+        .take_while(|c| **c == ' ' || **c == '\t')
+        .collect()
+}
+
+/// Tab stops of inserted snippet in buffer positions.
+///
+/// Text of placeholder is replaced by first typed character, mirrors follow every change.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SnippetSession {
+    stops: Vec<TabStop>,
+    current: usize,
+    untouched: bool,
+}
+
+impl SnippetSession {
+    pub fn new(expansion: &Expansion, start: usize) -> Self {
+        let stops = expansion
+            .stops
+            .iter()
+            .map(|stop| TabStop {
+                index: stop.index,
+                ranges: stop
+                    .ranges
+                    .iter()
+                    .map(|(from, to)| (start + from, start + to))
+                    .collect(),
+            })
+            .collect();
+        Self {
+            stops,
+            current: 0,
+            untouched: true,
+        }
+    }
+
+    pub fn current(&self) -> Option<&TabStop> {
+        self.stops.get(self.current)
+    }
+
+    /// Caret position for current stop, end of its text
+    pub fn caret(&self) -> Option<usize> {
+        self.current().map(|stop| stop.ranges[0].1)
+    }
+
+    /// Session is over once caret reaches `$0`
+    pub fn is_finished(&self) -> bool {
+        self.current + 1 >= self.stops.len()
+    }
+
+    pub fn next_stop(&mut self) -> Option<usize> {
+        self.current += 1;
+        self.untouched = true;
+        self.caret()
+    }
+
+    /// Replacements which apply edit at caret to current stop and its mirrors and new caret position,
+    /// `None` when caret is outside of current stop
+    pub fn edit(
+        &mut self,
+        caret: usize,
+        edit: SnippetEdit,
+        buffer: &str,
+    ) -> Option<(Vec<Replacement>, usize)> {
+        let ranges = self.current()?.ranges.clone();
+        let (start, end) = ranges[0];
+        let chars: Vec<char> = buffer.chars().collect();
+        if caret < start || caret > end || end > chars.len() {
+            return None;
+        }
+        let mut text: Vec<char> = chars[start..end].to_vec();
+        let replace = self.untouched && start != end;
+        let offset = match edit {
+            SnippetEdit::Insert(ref inserted) if replace => {
+                text = inserted.chars().collect();
+                text.len()
+            }
+            SnippetEdit::Insert(ref inserted) => {
+                let at = caret - start;
+                text.splice(at..at, inserted.chars());
+                at + inserted.chars().count()
+            }
+            SnippetEdit::DeleteFront if replace => {
+                text.clear();
+                0
+            }
+            SnippetEdit::DeleteFront if caret > start => {
+                text.remove(caret - start - 1);
+                caret - start - 1
+            }
+            SnippetEdit::DeleteFront => return None,
+        };
+        self.untouched = false;
+
+        let text: String = text.into_iter().collect();
+        let new_len = text.chars().count();
+        let delta = new_len as isize - (end - start) as isize;
+        let mut replaced = ranges.clone();
+        replaced.sort();
+        let map = |position: usize| -> usize {
+            let mut shift = 0;
+            for &(from, to) in replaced.iter() {
+                if to <= position {
+                    shift += delta;
+                } else if from < position {
+                    return (from as isize + shift) as usize + new_len;
+                }
+            }
+            (position as isize + shift) as usize
+        };
+        let map_replaced = |from: usize| -> usize {
+            let before = replaced.iter().filter(|(_, to)| *to <= from).count() as isize;
+            let empty_here = replaced.iter().any(|&(f, t)| f == from && t == from) as isize;
+            (from as isize + (before - empty_here) * delta) as usize
+        };
+        for (index, stop) in self.stops.iter_mut().enumerate() {
+            for range in stop.ranges.iter_mut() {
+                *range = if index == self.current {
+                    let from = map_replaced(range.0);
+                    (from, from + new_len)
+                } else {
+                    (map(range.0), map(range.1))
+                };
+            }
+        }
+
+        let replacements = ranges
+            .iter()
+            .map(|&(from, to)| Replacement {
+                start: from,
+                end: to,
+                text: text.clone(),
+            })
+            .collect();
+        Some((replacements, self.stops[self.current].ranges[0].0 + offset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(buffer: &str, mut replacements: Vec<Replacement>) -> String {
+        let mut chars: Vec<char> = buffer.chars().collect();
+        replacements.sort_by(|a, b| b.start.cmp(&a.start));
+        for replacement in replacements {
+            chars.splice(replacement.start..replacement.end, replacement.text.chars());
+        }
+        chars.into_iter().collect()
+    }
+
+    #[test]
+    fn must_expand_tab_stops_and_placeholders() {
+        let result = expand("fn ${1:name}($2) {\n    $0\n}", &HashMap::new(), "  ");
+        assert_eq!(result.text, "fn name() {\n      \n  }".to_owned());
+        assert_eq!(
+            result.stops,
+            vec![
+                TabStop {
+                    index: 1,
+                    ranges: vec![(3, 7)],
+                },
+                TabStop {
+                    index: 2,
+                    ranges: vec![(8, 8)],
+                },
+                TabStop {
+                    index: 0,
+                    ranges: vec![(18, 18)],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn must_mirror_placeholder_and_add_final_stop() {
+        let result = expand("$1 ${1:a} ${2:b ${3:c}}", &HashMap::new(), "");
+        assert_eq!(result.text, "a a b c".to_owned());
+        let ranges: Vec<(usize, Vec<(usize, usize)>)> = result
+            .stops
+            .into_iter()
+            .map(|stop| (stop.index, stop.ranges))
+            .collect();
+        assert_eq!(
+            ranges,
+            vec![
+                (1, vec![(2, 3), (0, 1)]),
+                (2, vec![(4, 7)]),
+                (3, vec![(6, 7)]),
+                (0, vec![(7, 7)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn must_resolve_variables_and_escapes() {
+        let mut variables = HashMap::new();
+        variables.insert("TM_FILENAME".to_owned(), "main.rs".to_owned());
+        let result = expand(
+            "$TM_FILENAME ${TM_FILENAME} ${MISSING:x} $HOME \\$1 \\}",
+            &variables,
+            "",
+        );
+        assert_eq!(result.text, "main.rs main.rs x $HOME $1 }".to_owned());
+        assert_eq!(result.stops.len(), 1);
+    }
+
+    #[test]
+    fn must_build_variables() {
+        let now = UNIX_EPOCH + std::time::Duration::from_secs(951_826_332);
+        let variables = variables("/src/app/main.rs", now, 0);
+        let get = |name: &str| variables.get(name).cloned().unwrap_or_default();
+        assert_eq!(get("TM_FILENAME"), "main.rs".to_owned());
+        assert_eq!(get("TM_FILENAME_BASE"), "main".to_owned());
+        assert_eq!(get("TM_DIRECTORY"), "/src/app".to_owned());
+        assert_eq!(get("CURRENT_YEAR"), "2000".to_owned());
+        assert_eq!(get("CURRENT_MONTH"), "02".to_owned());
+        assert_eq!(get("CURRENT_DATE"), "29".to_owned());
+        assert_eq!(get("CURRENT_HOUR"), "12".to_owned());
+        assert_eq!(get("CURRENT_MINUTE"), "12".to_owned());
+        assert_eq!(get("CURRENT_SECOND"), "12".to_owned());
+
+        let variables = super::variables("/src/app/main.rs", now, 12 * 3600);
+        let get = |name: &str| variables.get(name).cloned().unwrap_or_default();
+        assert_eq!(get("CURRENT_MONTH"), "03".to_owned());
+        assert_eq!(get("CURRENT_DATE"), "01".to_owned());
+        assert_eq!(get("CURRENT_HOUR"), "00".to_owned());
+        let variables = super::variables("/src/app/main.rs", UNIX_EPOCH, -3600);
+        let get = |name: &str| variables.get(name).cloned().unwrap_or_default();
+        assert_eq!(get("CURRENT_YEAR"), "1969".to_owned());
+        assert_eq!(get("CURRENT_DATE"), "31".to_owned());
+        assert_eq!(get("CURRENT_HOUR"), "23".to_owned());
+        assert_eq!(local_utc_offset(now).abs() <= 14 * 3600, true);
+    }
+
+    #[test]
+    fn must_find_line_indent() {
+        assert_eq!(line_indent("fn a() {\n    fo", 15), "    ".to_owned());
+        assert_eq!(line_indent("\tfo", 3), "\t".to_owned());
+        assert_eq!(line_indent("fo", 2), "".to_owned());
+    }
+
+    #[test]
+    fn must_replace_placeholder_and_update_mirrors() {
+        let expansion = expand("let ${1:x} = $2; $1", &HashMap::new(), "");
+        let mut buffer = format!("> {}", expansion.text);
+        let mut session = SnippetSession::new(&expansion, 2);
+        assert_eq!(session.caret(), Some(7));
+
+        let (replacements, caret) = session
+            .edit(7, SnippetEdit::Insert("a".to_owned()), buffer.as_str())
+            .unwrap();
+        buffer = apply(buffer.as_str(), replacements);
+        assert_eq!(buffer, "> let a = ; a".to_owned());
+        assert_eq!(caret, 7);
+
+        let (replacements, caret) = session
+            .edit(7, SnippetEdit::Insert("bc".to_owned()), buffer.as_str())
+            .unwrap();
+        buffer = apply(buffer.as_str(), replacements);
+        assert_eq!(buffer, "> let abc = ; abc".to_owned());
+        assert_eq!(caret, 9);
+
+        let (replacements, caret) = session
+            .edit(9, SnippetEdit::DeleteFront, buffer.as_str())
+            .unwrap();
+        buffer = apply(buffer.as_str(), replacements);
+        assert_eq!(buffer, "> let ab = ; ab".to_owned());
+        assert_eq!(caret, 8);
+        assert_eq!(
+            session.edit(2, SnippetEdit::DeleteFront, buffer.as_str()),
+            None
+        );
+
+        assert_eq!(session.next_stop(), Some(11));
+        let (replacements, caret) = session
+            .edit(11, SnippetEdit::Insert("1".to_owned()), buffer.as_str())
+            .unwrap();
+        buffer = apply(buffer.as_str(), replacements);
+        assert_eq!(buffer, "> let ab = 1; ab".to_owned());
+        assert_eq!(caret, 12);
+        assert_eq!(session.is_finished(), false);
+        assert_eq!(session.next_stop(), Some(16));
+        assert_eq!(session.is_finished(), true);
+    }
+}
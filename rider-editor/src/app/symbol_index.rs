@@ -0,0 +1,345 @@
+use rider_config::LanguageMapping;
+use rider_lexers::outline;
+use rider_lexers::{Language, TokenType};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+const TAGS_FILE: &str = "tags";
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+pub const IGNORED_DIRECTORIES: [&str; 2] = ["target", "node_modules"];
+
+/// Place in file, line and column are counted in characters from 0
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Location {
+    pub path: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Clone, Debug, Default)]
+struct FileIndex {
+    definitions: Vec<(String, Location)>,
+    identifiers: HashMap<String, Vec<Location>>,
+}
+
+/// Definitions and identifier occurrences of every project file.
+///
+/// Files are indexed with lexers, definitions from ctags file are kept aside
+/// so saving file does not drop them.
+#[derive(Clone, Debug, Default)]
+pub struct SymbolIndex {
+    files: HashMap<String, FileIndex>,
+    tags: HashMap<String, Vec<Location>>,
+}
+
+impl SymbolIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Indexes every known file below root, `tags` file in root is read when it exists
+    pub fn scan(root: &str, mapping: &LanguageMapping) -> Self {
+        let mut index = Self::new();
+        for path in project_files(root) {
+            if fs::metadata(&path).map_or(true, |metadata| metadata.len() > MAX_FILE_SIZE) {
+                continue;
+            }
+            let language = match language_of(path.as_str(), mapping) {
+                Some(language) => language,
+                _ => continue,
+            };
+            if let Ok(buffer) = fs::read_to_string(&path) {
+                index.index_file(path.as_str(), buffer.as_str(), language);
+            }
+        }
+        if let Ok(content) = fs::read_to_string(Path::new(root).join(TAGS_FILE)) {
+            index.load_tags(root, content.as_str());
+        }
+        index
+    }
+
+    /// Replaces everything known about file with symbols of given buffer
+    pub fn index_file(&mut self, path: &str, buffer: &str, language: Language) {
+        let tokens = rider_lexers::parse(buffer.to_owned(), language);
+        let line_starts = line_starts(buffer);
+        let location = |line: usize, position: usize| Location {
+            path: path.to_owned(),
+            line,
+            column: position - line_starts.get(line).cloned().unwrap_or(0),
+        };
+        let mut file_index = FileIndex::default();
+        for symbol in outline::symbols(&tokens, language) {
+            let location = location(symbol.line, symbol.position);
+            file_index.definitions.push((symbol.name, location));
+        }
+        for token in tokens.iter() {
+            if let TokenType::Identifier { .. } = token {
+                file_index
+                    .identifiers
+                    .entry(token.text().clone())
+                    .or_insert_with(Vec::new)
+                    .push(location(token.line(), token.char_span().lo));
+            }
+        }
+        self.files.insert(path.to_owned(), file_index);
+    }
+
+    pub fn remove_file(&mut self, path: &str) {
+        self.files.remove(path);
+    }
+
+    /// Reads ctags file, entries without line number are located by their search pattern
+    pub fn load_tags(&mut self, root: &str, content: &str) {
+        let mut buffers: HashMap<String, Option<String>> = HashMap::new();
+        for line in content.lines().filter(|line| !line.starts_with("!_TAG_")) {
+            let mut fields = line.split('\t');
+            let (name, file, address) = match (fields.next(), fields.next(), fields.next()) {
+                (Some(name), Some(file), Some(address)) => (name, file, address),
+                _ => continue,
+            };
+            let path = Path::new(root).join(file).to_string_lossy().to_string();
+            let line_field = fields
+                .filter(|field| field.starts_with("line:"))
+                .filter_map(|field| field["line:".len()..].parse().ok())
+                .next();
+            let address = address.trim_end_matches(";\"");
+            let line = match address.parse::<usize>().ok().or(line_field) {
+                Some(line) if line > 0 => Some(line - 1),
+                _ => {
+                    let buffer = buffers
+                        .entry(path.clone())
+                        .or_insert_with(|| fs::read_to_string(&path).ok());
+                    buffer
+                        .as_ref()
+                        .and_then(|buffer| line_of_pattern(buffer, address))
+                }
+            };
+            if let Some(line) = line {
+                self.tags
+                    .entry(name.to_owned())
+                    .or_insert_with(Vec::new)
+                    .push(Location {
+                        path,
+                        line,
+                        column: 0,
+                    });
+            }
+        }
+    }
+
+    /// Definitions found by lexers first, then the ones known only from ctags
+    pub fn definitions(&self, name: &str) -> Vec<Location> {
+        let mut locations: Vec<Location> = self
+            .files
+            .values()
+            .flat_map(|file| file.definitions.iter())
+            .filter(|(symbol, _)| symbol == name)
+            .map(|(_, location)| location.clone())
+            .collect();
+        locations.sort();
+        for tag in self.tags.get(name).into_iter().flatten() {
+            if !locations
+                .iter()
+                .any(|l| l.path == tag.path && l.line == tag.line)
+            {
+                locations.push(tag.clone());
+            }
+        }
+        locations
+    }
+
+    pub fn references(&self, name: &str) -> Vec<Location> {
+        let mut locations: Vec<Location> = self
+            .files
+            .values()
+            .filter_map(|file| file.identifiers.get(name))
+            .flatten()
+            .cloned()
+            .collect();
+        locations.sort();
+        locations
+    }
+
+    pub fn files_count(&self) -> usize {
+        self.files.len()
+    }
+}
+
+/// Language of file by extension or by name for files like `Makefile`
+pub fn language_of(path: &str, mapping: &LanguageMapping) -> Option<Language> {
+    let file_path = Path::new(path);
+    file_path
+        .extension()
+        .or_else(|| file_path.file_name())
+        .and_then(|p| p.to_str())
+        .and_then(|ext| mapping.get(ext))
+        .cloned()
+}
+
+/// Files below root, hidden entries and build directories are skipped
+pub fn project_files(root: &str) -> Vec<String> {
+    let mut files = vec![];
+    let mut directories = vec![Path::new(root).to_path_buf()];
+    while let Some(directory) = directories.pop() {
+        let entries = match fs::read_dir(&directory) {
+            Ok(entries) => entries,
+            _ => continue,
+        };
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with('.') {
+                continue;
+            }
+            let metadata = match entry.metadata() {
+                Ok(metadata) => metadata,
+                _ => continue,
+            };
+            if metadata.is_dir() {
+                if !IGNORED_DIRECTORIES.contains(&name.as_str()) {
+                    directories.push(path);
+                }
+            } else if metadata.is_file() {
+                files.push(path.to_string_lossy().to_string());
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Identifier which contains character at position or ends right before it
+pub fn word_at(buffer: &str, position: usize) -> Option<String> {
+    let chars: Vec<char> = buffer.chars().collect();
+    let is_word = |c: &char| c.is_alphanumeric() || *c == '_';
+    let position = match chars.get(position) {
+        Some(c) if is_word(c) => position,
+        _ if position > 0 && chars.get(position - 1).map_or(false, is_word) => position - 1,
+        _ => return None,
+    };
+    let start = chars[..position]
+        .iter()
+        .rposition(|c| !is_word(c))
+        .map_or(0, |index| index + 1);
+    let end = chars[position..]
+        .iter()
+        .position(|c| !is_word(c))
+        .map_or(chars.len(), |index| position + index);
+    Some(chars[start..end].iter().collect())
+}
+
+/// Char index of first character of every line
+pub fn line_starts(buffer: &str) -> Vec<usize> {
+    let mut starts = vec![0];
+    for (index, c) in buffer.chars().enumerate() {
+        if c == '\n' {
+            starts.push(index + 1);
+        }
+    }
+    starts
+}
+
+/// Line matching ctags search pattern like `/^fn main() {$/`
+fn line_of_pattern(buffer: &str, pattern: &str) -> Option<usize> {
+    let pattern = pattern.trim_start_matches('/').trim_end_matches('/');
+    let from_start = pattern.starts_with('^');
+    let pattern = pattern.trim_start_matches('^');
+    let to_end = pattern.ends_with('$');
+    let pattern = pattern.trim_end_matches('$');
+    let pattern = pattern.replace("\\/", "/").replace("\\\\", "\\");
+    buffer.lines().position(|line| match (from_start, to_end) {
+        (true, true) => line == pattern,
+        (true, false) => line.starts_with(pattern.as_str()),
+        (false, true) => line.ends_with(pattern.as_str()),
+        (false, false) => line.contains(pattern.as_str()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn location(path: &str, line: usize, column: usize) -> Location {
+        Location {
+            path: path.to_owned(),
+            line,
+            column,
+        }
+    }
+
+    #[test]
+    fn must_find_definitions_and_references() {
+        let mut index = SymbolIndex::new();
+        index.index_file("a.rs", "fn main() {\n    run();\n}\n", Language::Rust);
+        index.index_file("b.rs", "pub fn run() {}\n", Language::Rust);
+        assert_eq!(index.definitions("run"), vec![location("b.rs", 0, 7)]);
+        assert_eq!(
+            index.references("run"),
+            vec![location("a.rs", 1, 4), location("b.rs", 0, 7)]
+        );
+        assert_eq!(index.definitions("missing"), vec![]);
+    }
+
+    #[test]
+    fn must_replace_file_on_reindex() {
+        let mut index = SymbolIndex::new();
+        index.index_file("a.rs", "fn a() {}", Language::Rust);
+        index.index_file("a.rs", "fn b() {}", Language::Rust);
+        assert_eq!(index.definitions("a"), vec![]);
+        assert_eq!(index.definitions("b"), vec![location("a.rs", 0, 3)]);
+        index.remove_file("a.rs");
+        assert_eq!(index.files_count(), 0);
+    }
+
+    #[test]
+    fn must_merge_ctags_definitions() {
+        let mut index = SymbolIndex::new();
+        index.index_file("/p/a.rs", "fn a() {}", Language::Rust);
+        index.load_tags(
+            "/p",
+            "!_TAG_FILE_FORMAT\t2\n\
+             a\ta.rs\t1;\"\tf\n\
+             b\tb.c\t/^int b() {$/;\"\tf\tline:4\n\
+             c\tc.c\t12;\"\tf\n",
+        );
+        assert_eq!(index.definitions("a"), vec![location("/p/a.rs", 0, 3)]);
+        assert_eq!(index.definitions("b"), vec![location("/p/b.c", 3, 0)]);
+        assert_eq!(index.definitions("c"), vec![location("/p/c.c", 11, 0)]);
+    }
+
+    #[test]
+    fn must_locate_ctags_pattern() {
+        let buffer = "int a;\nint main() {\n}\n";
+        assert_eq!(line_of_pattern(buffer, "/^int main() {$/"), Some(1));
+        assert_eq!(line_of_pattern(buffer, "/main/"), Some(1));
+        assert_eq!(line_of_pattern(buffer, "/^main/"), None);
+    }
+
+    #[test]
+    fn must_find_word_at_position() {
+        let buffer = "let foo_bar = baz;";
+        assert_eq!(word_at(buffer, 4), Some("foo_bar".to_owned()));
+        assert_eq!(word_at(buffer, 11), Some("foo_bar".to_owned()));
+        assert_eq!(word_at(buffer, 12), None);
+        assert_eq!(word_at(buffer, 17), Some("baz".to_owned()));
+        assert_eq!(word_at("", 0), None);
+    }
+
+    #[test]
+    fn must_scan_directory() {
+        let root = "/tmp/rider/test-symbol-index";
+        fs::create_dir_all(format!("{}/src", root)).unwrap();
+        fs::create_dir_all(format!("{}/target", root)).unwrap();
+        fs::write(format!("{}/src/lib.rs", root), "fn scanned() {}").unwrap();
+        fs::write(format!("{}/target/out.rs", root), "fn ignored() {}").unwrap();
+        let mut mapping = LanguageMapping::new();
+        mapping.insert("rs".to_owned(), Language::Rust);
+        let index = SymbolIndex::scan(root, &mapping);
+        assert_eq!(
+            index.definitions("scanned"),
+            vec![location(format!("{}/src/lib.rs", root).as_str(), 0, 3)]
+        );
+        assert_eq!(index.definitions("ignored"), vec![]);
+    }
+}
@@ -0,0 +1,312 @@
+pub mod parser;
+pub mod pty;
+pub mod screen;
+
+pub use crate::app::terminal::parser::*;
+pub use crate::app::terminal::pty::*;
+pub use crate::app::terminal::screen::*;
+
+use sdl2::keyboard::Keycode;
+use std::path::Path;
+
+/// Action of focused terminal panel, keys without own action are sent as input
+#[derive(Clone, Debug, PartialEq)]
+pub enum TerminalAction {
+    NewTab,
+    CloseTab,
+    NextTab,
+    PreviousTab,
+    ScrollUp,
+    ScrollDown,
+    Search,
+    Input(Vec<u8>),
+}
+
+/// Search in scrollback, typed text changes query and `Enter` moves to older match
+#[derive(Clone, Debug, PartialEq)]
+pub struct TerminalSearch {
+    pub query: String,
+    /// Line counted from oldest scrollback line and column of match
+    pub found: Option<(usize, usize)>,
+}
+
+/// Shell on pseudo-terminal together with its screen
+pub struct Terminal {
+    pty: Pty,
+    screen: Screen,
+    parser: Parser,
+    program: String,
+    scroll_offset: usize,
+    search: Option<TerminalSearch>,
+}
+
+impl Terminal {
+    pub fn spawn(program: &str, cwd: &str, cols: usize, rows: usize) -> Result<Self, String> {
+        Ok(Self {
+            pty: Pty::spawn(program, &[], cwd, cols, rows)?,
+            screen: Screen::new(cols, rows),
+            parser: Parser::new(),
+            program: program.to_owned(),
+            scroll_offset: 0,
+            search: None,
+        })
+    }
+
+    pub fn screen(&self) -> &Screen {
+        &self.screen
+    }
+
+    /// Title set by program or name of started program
+    pub fn title(&self) -> String {
+        match self.screen.title().as_str() {
+            "" => Path::new(self.program.as_str()).file_name().map_or_else(
+                || self.program.clone(),
+                |name| name.to_string_lossy().to_string(),
+            ),
+            title => title.to_owned(),
+        }
+    }
+
+    /// Applies output of program, returns false when there was none
+    pub fn poll(&mut self) -> bool {
+        let output = self.pty.read();
+        if output.is_empty() {
+            return false;
+        }
+        let scrollback = self.screen.scrollback_len();
+        self.parser.advance(&mut self.screen, &output);
+        // scrolled back view stays on the same lines
+        if self.scroll_offset > 0 {
+            let added = self.screen.scrollback_len().saturating_sub(scrollback);
+            self.scroll_by(added as isize);
+        }
+        let responses = self.screen.take_responses();
+        if !responses.is_empty() {
+            self.pty
+                .write(&responses)
+                .unwrap_or_else(|e| warn!("{}", e));
+        }
+        true
+    }
+
+    pub fn is_running(&mut self) -> bool {
+        self.pty.is_running()
+    }
+
+    pub fn resize(&mut self, cols: usize, rows: usize) {
+        if self.screen.cols() == cols.max(1) && self.screen.rows() == rows.max(1) {
+            return;
+        }
+        self.screen.resize(cols, rows);
+        self.pty.resize(self.screen.cols(), self.screen.rows());
+        self.scroll_by(0);
+    }
+
+    /// Sends keys to program or to search when it is active
+    pub fn input(&mut self, bytes: &[u8]) {
+        if self.search.is_some() {
+            return self.search_input(bytes);
+        }
+        self.scroll_offset = 0;
+        let bytes = match bytes {
+            [0x1b, b'[', key @ b'A'..=b'H'] if self.screen.application_cursor() => {
+                vec![0x1b, b'O', *key]
+            }
+            _ => bytes.to_vec(),
+        };
+        self.pty.write(&bytes).unwrap_or_else(|e| warn!("{}", e));
+    }
+
+    /// Lines scrolled back from bottom of output
+    pub fn scroll_offset(&self) -> usize {
+        self.scroll_offset
+    }
+
+    /// Positive number of lines scrolls back to older output
+    pub fn scroll_by(&mut self, lines: isize) {
+        let offset = (self.scroll_offset as isize + lines).max(0) as usize;
+        self.scroll_offset = offset.min(self.screen.scrollback_len());
+    }
+
+    /// Index of line shown in first row
+    pub fn first_visible_line(&self) -> usize {
+        self.screen.scrollback_len() - self.scroll_offset
+    }
+
+    pub fn search(&self) -> Option<&TerminalSearch> {
+        self.search.as_ref()
+    }
+
+    pub fn start_search(&mut self) {
+        self.search = Some(TerminalSearch {
+            query: String::new(),
+            found: None,
+        });
+    }
+
+    fn search_input(&mut self, bytes: &[u8]) {
+        for c in String::from_utf8_lossy(bytes).chars() {
+            let (query, found) = match self.search.as_mut() {
+                Some(search) => (&mut search.query, search.found),
+                None => return,
+            };
+            let before = match c {
+                '\u{1b}' => {
+                    self.search = None;
+                    return;
+                }
+                '\r' => found.map_or(self.screen.total_lines(), |(line, _)| line),
+                '\u{7f}' | '\u{8}' => {
+                    query.pop();
+                    self.screen.total_lines()
+                }
+                c if c.is_control() => continue,
+                c => {
+                    query.push(c);
+                    self.screen.total_lines()
+                }
+            };
+            let query = query.clone();
+            // after oldest match search starts again from bottom
+            let found = self.screen.find_before(query.as_str(), before).or_else(|| {
+                self.screen
+                    .find_before(query.as_str(), self.screen.total_lines())
+            });
+            if let Some((line, _)) = found {
+                self.show_line(line);
+            }
+            if let Some(search) = self.search.as_mut() {
+                search.found = found;
+            }
+        }
+    }
+
+    /// Scrolls so line is visible, it is centered when it was not
+    fn show_line(&mut self, line: usize) {
+        let rows = self.screen.rows();
+        let first = self.first_visible_line();
+        if line >= first && line < first + rows {
+            return;
+        }
+        let first = line
+            .saturating_sub(rows / 2)
+            .min(self.screen.scrollback_len());
+        self.scroll_offset = self.screen.scrollback_len() - first;
+    }
+}
+
+/// Bytes sent to terminal for key which does not produce text input
+pub fn key_bytes(keycode: Keycode, ctrl: bool) -> Option<Vec<u8>> {
+    let bytes: &[u8] = match keycode {
+        Keycode::Return | Keycode::KpEnter => b"\r",
+        Keycode::Backspace => b"\x7f",
+        Keycode::Tab => b"\t",
+        Keycode::Escape => b"\x1b",
+        Keycode::Up => b"\x1b[A",
+        Keycode::Down => b"\x1b[B",
+        Keycode::Right => b"\x1b[C",
+        Keycode::Left => b"\x1b[D",
+        Keycode::Home => b"\x1b[H",
+        Keycode::End => b"\x1b[F",
+        Keycode::Insert => b"\x1b[2~",
+        Keycode::Delete => b"\x1b[3~",
+        Keycode::PageUp => b"\x1b[5~",
+        Keycode::PageDown => b"\x1b[6~",
+        Keycode::F1 => b"\x1bOP",
+        Keycode::F2 => b"\x1bOQ",
+        Keycode::F3 => b"\x1bOR",
+        Keycode::F4 => b"\x1bOS",
+        Keycode::F5 => b"\x1b[15~",
+        Keycode::F6 => b"\x1b[17~",
+        Keycode::F7 => b"\x1b[18~",
+        Keycode::F8 => b"\x1b[19~",
+        Keycode::F9 => b"\x1b[20~",
+        Keycode::F10 => b"\x1b[21~",
+        Keycode::F11 => b"\x1b[23~",
+        Keycode::F12 => b"\x1b[24~",
+        Keycode::Space if ctrl => b"\x00",
+        Keycode::LeftBracket if ctrl => b"\x1b",
+        Keycode::Backslash if ctrl => b"\x1c",
+        Keycode::RightBracket if ctrl => b"\x1d",
+        // letters have codes of their lowercase ASCII characters
+        keycode
+            if ctrl
+                && keycode as i32 >= Keycode::A as i32
+                && keycode as i32 <= Keycode::Z as i32 =>
+        {
+            return Some(vec![(keycode as i32 as u8) & 0x1f]);
+        }
+        _ => return None,
+    };
+    Some(bytes.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use std::time::Duration;
+
+    fn wait_for<F>(terminal: &mut Terminal, done: F)
+    where
+        F: Fn(&mut Terminal) -> bool,
+    {
+        for _ in 0..500 {
+            terminal.poll();
+            if done(terminal) {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    fn contains(terminal: &Terminal, text: &str) -> bool {
+        let screen = terminal.screen();
+        (0..screen.total_lines()).any(|index| screen.line_text(index).contains(text))
+    }
+
+    #[test]
+    fn must_run_shell_and_search_its_output() {
+        let mut terminal = Terminal::spawn("/bin/sh", "/tmp", 40, 4).unwrap();
+        assert_eq!(terminal.title(), "sh".to_owned());
+        // keys typed before prompt would be echoed ahead of it
+        wait_for(&mut terminal, |t| !t.screen().line_text(0).is_empty());
+        terminal.input(b"for i in 1 2 3 4 5 6 7 8; do echo line$i; done\r");
+        wait_for(&mut terminal, |t| contains(t, "line8"));
+        assert_eq!(contains(&terminal, "line8"), true);
+        assert_eq!(terminal.screen().scrollback_len() > 0, true);
+
+        terminal.start_search();
+        terminal.input(b"LINE");
+        let found = terminal.search().and_then(|s| s.found);
+        let line8 = found.unwrap().0;
+        assert_eq!(terminal.screen().line_text(line8), "line8".to_owned());
+        terminal.input(b"\r");
+        let line7 = terminal.search().and_then(|s| s.found).unwrap().0;
+        assert_eq!(terminal.screen().line_text(line7), "line7".to_owned());
+        terminal.input(b"1\r");
+        let line1 = terminal.search().and_then(|s| s.found).unwrap().0;
+        assert_eq!(terminal.screen().line_text(line1), "line1".to_owned());
+        assert_eq!(terminal.first_visible_line() <= line1, true);
+        assert_eq!(terminal.scroll_offset() > 0, true);
+
+        terminal.input(b"\x1b");
+        assert_eq!(terminal.search(), None);
+        terminal.input(b"exit\r");
+        wait_for(&mut terminal, |t| !t.is_running());
+        assert_eq!(terminal.scroll_offset(), 0);
+        assert_eq!(terminal.is_running(), false);
+    }
+
+    #[test]
+    fn must_translate_keys() {
+        assert_eq!(key_bytes(Keycode::C, true), Some(vec![3]));
+        assert_eq!(key_bytes(Keycode::C, false), None);
+        assert_eq!(key_bytes(Keycode::Up, false), Some(b"\x1b[A".to_vec()));
+        assert_eq!(key_bytes(Keycode::Return, true), Some(b"\r".to_vec()));
+        assert_eq!(
+            key_bytes(Keycode::LeftBracket, true),
+            Some(b"\x1b".to_vec())
+        );
+    }
+}
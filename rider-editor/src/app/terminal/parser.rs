@@ -0,0 +1,406 @@
+use crate::app::terminal::screen::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum State {
+    Ground,
+    Escape,
+    /// Charset designation like `ESC ( B`, its final byte is skipped
+    EscapeIntermediate,
+    Csi,
+    Osc,
+    OscEscape,
+}
+
+/// Translates output of program into changes of screen.
+///
+/// Supports subset of VT100 and xterm sequences which is used by shells and
+/// full screen programs: colors, cursor movement, erasing, scroll regions and alternate screen.
+pub struct Parser {
+    state: State,
+    params: Vec<usize>,
+    param: Option<usize>,
+    private: Option<u8>,
+    osc: Vec<u8>,
+    utf8: Vec<u8>,
+}
+
+impl Parser {
+    pub fn new() -> Self {
+        Self {
+            state: State::Ground,
+            params: vec![],
+            param: None,
+            private: None,
+            osc: vec![],
+            utf8: vec![],
+        }
+    }
+
+    pub fn advance(&mut self, screen: &mut Screen, bytes: &[u8]) {
+        for byte in bytes.iter().cloned() {
+            self.advance_byte(screen, byte);
+        }
+    }
+
+    fn advance_byte(&mut self, screen: &mut Screen, byte: u8) {
+        match self.state {
+            State::Osc => return self.osc_byte(screen, byte),
+            State::OscEscape => {
+                // `ESC \` ends string, anything else aborts it
+                self.state = State::Ground;
+                if byte == b'\\' {
+                    self.dispatch_osc(screen);
+                }
+                return;
+            }
+            _ => (),
+        }
+        match byte {
+            0x1b => {
+                self.utf8.clear();
+                self.state = State::Escape;
+            }
+            0x00..=0x1f | 0x7f => self.execute(screen, byte),
+            _ => match self.state {
+                State::Ground => self.print_byte(screen, byte),
+                State::Escape => self.escape(screen, byte),
+                State::EscapeIntermediate => self.state = State::Ground,
+                State::Csi => self.csi_byte(screen, byte),
+                State::Osc | State::OscEscape => (),
+            },
+        }
+    }
+
+    fn execute(&mut self, screen: &mut Screen, byte: u8) {
+        match byte {
+            0x08 => screen.backspace(),
+            0x09 => screen.tab(),
+            0x0a | 0x0b | 0x0c => screen.line_feed(),
+            0x0d => screen.carriage_return(),
+            // cancel sequence
+            0x18 | 0x1a => self.state = State::Ground,
+            _ => (),
+        }
+    }
+
+    fn print_byte(&mut self, screen: &mut Screen, byte: u8) {
+        if byte < 0x80 {
+            self.utf8.clear();
+            return screen.print(byte as char);
+        }
+        if byte & 0xc0 != 0x80 {
+            self.utf8.clear();
+        }
+        self.utf8.push(byte);
+        let expected = match self.utf8[0] {
+            b if b & 0xe0 == 0xc0 => 2,
+            b if b & 0xf0 == 0xe0 => 3,
+            b if b & 0xf8 == 0xf0 => 4,
+            _ => {
+                self.utf8.clear();
+                return screen.print('\u{fffd}');
+            }
+        };
+        if self.utf8.len() < expected {
+            return;
+        }
+        let c = std::str::from_utf8(&self.utf8)
+            .ok()
+            .and_then(|s| s.chars().next())
+            .unwrap_or('\u{fffd}');
+        self.utf8.clear();
+        screen.print(c);
+    }
+
+    fn escape(&mut self, screen: &mut Screen, byte: u8) {
+        self.state = State::Ground;
+        match byte {
+            b'[' => {
+                self.params.clear();
+                self.param = None;
+                self.private = None;
+                self.state = State::Csi;
+            }
+            b']' => {
+                self.osc.clear();
+                self.state = State::Osc;
+            }
+            b'(' | b')' | b'*' | b'+' => self.state = State::EscapeIntermediate,
+            b'7' => screen.save_cursor(),
+            b'8' => screen.restore_cursor(),
+            b'D' => screen.line_feed(),
+            b'E' => {
+                screen.carriage_return();
+                screen.line_feed();
+            }
+            b'M' => screen.reverse_index(),
+            b'c' => screen.reset(),
+            _ => (),
+        }
+    }
+
+    fn osc_byte(&mut self, screen: &mut Screen, byte: u8) {
+        match byte {
+            0x07 => {
+                self.state = State::Ground;
+                self.dispatch_osc(screen);
+            }
+            0x1b => self.state = State::OscEscape,
+            _ => self.osc.push(byte),
+        }
+    }
+
+    fn dispatch_osc(&mut self, screen: &mut Screen) {
+        let text = String::from_utf8_lossy(&self.osc).to_string();
+        let mut parts = text.splitn(2, ';');
+        match (parts.next(), parts.next()) {
+            (Some("0"), Some(title)) | (Some("2"), Some(title)) => {
+                screen.set_title(title.to_owned())
+            }
+            _ => (),
+        }
+        self.osc.clear();
+    }
+
+    fn csi_byte(&mut self, screen: &mut Screen, byte: u8) {
+        match byte {
+            b'0'..=b'9' => {
+                let digit = (byte - b'0') as usize;
+                self.param = Some(
+                    self.param
+                        .unwrap_or(0)
+                        .saturating_mul(10)
+                        .saturating_add(digit),
+                );
+            }
+            b';' | b':' => self.params.push(self.param.take().unwrap_or(0)),
+            b'?' | b'>' | b'=' | b'<' => self.private = Some(byte),
+            // intermediate bytes are not used by supported sequences
+            0x20..=0x2f => (),
+            0x40..=0x7e => {
+                if let Some(param) = self.param.take() {
+                    self.params.push(param);
+                }
+                self.state = State::Ground;
+                self.dispatch_csi(screen, byte);
+            }
+            _ => self.state = State::Ground,
+        }
+    }
+
+    /// Parameter at index, missing and zero parameters use default
+    fn param_or(&self, index: usize, default: usize) -> usize {
+        match self.params.get(index) {
+            Some(0) | None => default,
+            Some(value) => *value,
+        }
+    }
+
+    fn dispatch_csi(&mut self, screen: &mut Screen, action: u8) {
+        if self.private == Some(b'?') {
+            return self.dispatch_private_mode(screen, action);
+        }
+        if self.private.is_some() {
+            return;
+        }
+        let n = self.param_or(0, 1);
+        let (row, col) = screen.cursor();
+        match action {
+            b'A' => screen.move_by(-(n as isize), 0),
+            b'B' | b'e' => screen.move_by(n as isize, 0),
+            b'C' | b'a' => screen.move_by(0, n as isize),
+            b'D' => screen.move_by(0, -(n as isize)),
+            b'E' => screen.move_to(row + n, 0),
+            b'F' => screen.move_to(row.saturating_sub(n), 0),
+            b'G' | b'`' => screen.move_to(row, n - 1),
+            b'd' => screen.move_to(n - 1, col),
+            b'H' | b'f' => screen.move_to(n - 1, self.param_or(1, 1) - 1),
+            b'J' => screen.erase_display(self.params.get(0).cloned().unwrap_or(0)),
+            b'K' => screen.erase_line(self.params.get(0).cloned().unwrap_or(0)),
+            b'L' => screen.insert_lines(n),
+            b'M' => screen.delete_lines(n),
+            b'@' => screen.insert_chars(n),
+            b'P' => screen.delete_chars(n),
+            b'X' => screen.erase_chars(n),
+            b'S' => screen.scroll_up(n),
+            b'T' => screen.scroll_down(n),
+            b'm' => self.select_graphic_rendition(screen),
+            b'r' => {
+                let bottom = self.param_or(1, screen.rows());
+                screen.set_scroll_region(self.param_or(0, 1) - 1, bottom - 1);
+            }
+            b's' => screen.save_cursor(),
+            b'u' => screen.restore_cursor(),
+            b'n' if self.params.get(0) == Some(&6) => {
+                let report = format!("\x1b[{};{}R", row + 1, col + 1);
+                screen.respond(report.as_bytes());
+            }
+            b'n' if self.params.get(0) == Some(&5) => screen.respond(b"\x1b[0n"),
+            b'c' => screen.respond(b"\x1b[?1;2c"),
+            _ => (),
+        }
+    }
+
+    fn dispatch_private_mode(&mut self, screen: &mut Screen, action: u8) {
+        let enabled = match action {
+            b'h' => true,
+            b'l' => false,
+            _ => return,
+        };
+        for mode in self.params.iter() {
+            match mode {
+                1 => screen.set_application_cursor(enabled),
+                25 => screen.set_cursor_visible(enabled),
+                47 | 1047 => screen.set_alternate(enabled),
+                1049 => {
+                    screen.set_alternate(enabled);
+                    if enabled {
+                        screen.erase_display(2);
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn select_graphic_rendition(&mut self, screen: &mut Screen) {
+        if self.params.is_empty() {
+            self.params.push(0);
+        }
+        let style = screen.style_mut();
+        let mut params = self.params.iter().cloned();
+        while let Some(param) = params.next() {
+            match param {
+                0 => *style = CellStyle::default(),
+                1 => style.bold = true,
+                7 => style.inverse = true,
+                22 => style.bold = false,
+                27 => style.inverse = false,
+                30..=37 => style.fg = TermColor::Indexed((param - 30) as u8),
+                38 => style.fg = extended_color(&mut params).unwrap_or(style.fg),
+                39 => style.fg = TermColor::Default,
+                40..=47 => style.bg = TermColor::Indexed((param - 40) as u8),
+                48 => style.bg = extended_color(&mut params).unwrap_or(style.bg),
+                49 => style.bg = TermColor::Default,
+                90..=97 => style.fg = TermColor::Indexed((param - 90 + 8) as u8),
+                100..=107 => style.bg = TermColor::Indexed((param - 100 + 8) as u8),
+                _ => (),
+            }
+        }
+    }
+}
+
+/// Color after `38` or `48`, either `5;index` or `2;red;green;blue`
+fn extended_color<I>(params: &mut I) -> Option<TermColor>
+where
+    I: Iterator<Item = usize>,
+{
+    let byte = |value: usize| value.min(255) as u8;
+    match params.next()? {
+        5 => params.next().map(|index| TermColor::Indexed(byte(index))),
+        2 => {
+            let (r, g, b) = (params.next()?, params.next()?, params.next()?);
+            Some(TermColor::Rgb(byte(r), byte(g), byte(b)))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed(screen: &mut Screen, text: &str) {
+        Parser::new().advance(screen, text.as_bytes());
+    }
+
+    fn lines(screen: &Screen) -> Vec<String> {
+        (0..screen.total_lines())
+            .map(|index| screen.line_text(index))
+            .collect()
+    }
+
+    #[test]
+    fn must_print_text_and_move_cursor() {
+        let mut screen = Screen::new(10, 3);
+        feed(&mut screen, "ab\r\nżółw\x1b[1;8Hx\x1b[2;3H\x1b[Ky\x1b[3G");
+        assert_eq!(lines(&screen), vec!["ab     x", "żóy", ""]);
+        assert_eq!(screen.cursor(), (1, 2));
+    }
+
+    #[test]
+    fn must_keep_state_between_chunks() {
+        let mut screen = Screen::new(10, 2);
+        let mut parser = Parser::new();
+        let bytes = "a\x1b[31mż\x1b]0;vim\x07".as_bytes();
+        for byte in bytes.iter() {
+            parser.advance(&mut screen, &[*byte]);
+        }
+        assert_eq!(lines(&screen), vec!["aż", ""]);
+        assert_eq!(screen.title(), &"vim".to_owned());
+        let cell = screen.line(0).unwrap()[1];
+        assert_eq!(cell.style.fg, TermColor::Indexed(1));
+    }
+
+    #[test]
+    fn must_apply_graphic_rendition() {
+        let mut screen = Screen::new(10, 1);
+        feed(
+            &mut screen,
+            "\x1b[1;7;38;5;200;48;2;1;2;3ma\x1b[22;39mb\x1b[0;94mc\x1b[md",
+        );
+        let styles: Vec<CellStyle> = screen.line(0).unwrap()[..4]
+            .iter()
+            .map(|cell| cell.style)
+            .collect();
+        let first = CellStyle {
+            fg: TermColor::Indexed(200),
+            bg: TermColor::Rgb(1, 2, 3),
+            bold: true,
+            inverse: true,
+        };
+        let second = CellStyle {
+            fg: TermColor::Default,
+            bold: false,
+            ..first
+        };
+        let third = CellStyle {
+            fg: TermColor::Indexed(12),
+            ..CellStyle::default()
+        };
+        assert_eq!(styles, vec![first, second, third, CellStyle::default()]);
+    }
+
+    #[test]
+    fn must_switch_alternate_screen_and_modes() {
+        let mut screen = Screen::new(6, 2);
+        feed(&mut screen, "$ vim\x1b[?1049h\x1b[?1h\x1b[?25l\x1b[Hfile");
+        assert_eq!(lines(&screen), vec!["file", ""]);
+        assert_eq!(screen.application_cursor(), true);
+        assert_eq!(screen.cursor_visible(), false);
+        feed(&mut screen, "\x1b[?1049l\x1b[?1l\x1b[?25h");
+        assert_eq!(lines(&screen), vec!["$ vim", ""]);
+        assert_eq!(screen.application_cursor(), false);
+        assert_eq!(screen.cursor_visible(), true);
+    }
+
+    #[test]
+    fn must_answer_status_requests() {
+        let mut screen = Screen::new(10, 5);
+        feed(&mut screen, "\x1b[3;4H\x1b[6n\x1b[c");
+        assert_eq!(screen.take_responses(), b"\x1b[3;4R\x1b[?1;2c".to_vec());
+        assert_eq!(screen.take_responses(), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn must_use_scroll_region() {
+        let mut screen = Screen::new(4, 4);
+        feed(
+            &mut screen,
+            "1\r\n2\r\n3\r\n4\x1b[2;3r\x1b[3;1H\n\x1b[2;1H\x1bM",
+        );
+        assert_eq!(lines(&screen), vec!["1", "", "3", "4"]);
+        feed(&mut screen, "\x1b[r\x1b[4;1H\n");
+        assert_eq!(screen.scrollback_len(), 1);
+    }
+}
@@ -0,0 +1,203 @@
+use std::fs::File;
+use std::io::{Read, Write};
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+use std::thread;
+
+/// Program running on pseudo-terminal, its output is read in background
+pub struct Pty {
+    master: File,
+    child: Child,
+    receiver: Receiver<Vec<u8>>,
+    closed: bool,
+}
+
+impl Pty {
+    /// Starts program as session leader with pty as its controlling terminal
+    pub fn spawn(
+        program: &str,
+        args: &[String],
+        cwd: &str,
+        cols: usize,
+        rows: usize,
+    ) -> Result<Self, String> {
+        let (master, slave) = open_pty(cols, rows)?;
+        let stdio = |fd: RawFd| -> Result<Stdio, String> {
+            match unsafe { libc::dup(fd) } {
+                -1 => Err(std::io::Error::last_os_error().to_string()),
+                fd => Ok(unsafe { Stdio::from_raw_fd(fd) }),
+            }
+        };
+        let mut command = Command::new(program);
+        command
+            .args(args)
+            .current_dir(cwd)
+            .env("TERM", "xterm-256color")
+            .stdin(stdio(slave.as_raw_fd())?)
+            .stdout(stdio(slave.as_raw_fd())?)
+            .stderr(stdio(slave.as_raw_fd())?);
+        unsafe {
+            command.pre_exec(|| {
+                if libc::setsid() == -1 || libc::ioctl(0, libc::TIOCSCTTY as _, 0) == -1 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        let child = command
+            .spawn()
+            .map_err(|e| format!("Failed to start {}: {}", program, e))?;
+        drop(slave);
+
+        let mut reader = master.try_clone().map_err(|e| e.to_string())?;
+        let (sender, receiver) = channel();
+        thread::spawn(move || {
+            let mut buffer = [0u8; 4096];
+            // reading fails once program and its children close terminal
+            while let Ok(count) = reader.read(&mut buffer) {
+                if count == 0 || sender.send(buffer[..count].to_vec()).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self {
+            master,
+            child,
+            receiver,
+            closed: false,
+        })
+    }
+
+    /// Output received since last call
+    pub fn read(&mut self) -> Vec<u8> {
+        let mut output = vec![];
+        loop {
+            match self.receiver.try_recv() {
+                Ok(bytes) => output.extend(bytes),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.closed = true;
+                    break;
+                }
+            }
+        }
+        output
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) -> Result<(), String> {
+        self.master
+            .write_all(bytes)
+            .and_then(|_| self.master.flush())
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn resize(&self, cols: usize, rows: usize) {
+        let size = window_size(cols, rows);
+        unsafe {
+            libc::ioctl(self.master.as_raw_fd(), libc::TIOCSWINSZ as _, &size);
+        }
+    }
+
+    /// False once program exited and all its output was read
+    pub fn is_running(&mut self) -> bool {
+        !self.closed
+            || self
+                .child
+                .try_wait()
+                .map_or(false, |status| status.is_none())
+    }
+}
+
+impl Drop for Pty {
+    fn drop(&mut self) {
+        if let Ok(None) = self.child.try_wait() {
+            self.child.kill().ok();
+            self.child.wait().ok();
+        }
+    }
+}
+
+fn window_size(cols: usize, rows: usize) -> libc::winsize {
+    libc::winsize {
+        ws_row: rows.min(u16::max_value() as usize) as u16,
+        ws_col: cols.min(u16::max_value() as usize) as u16,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    }
+}
+
+fn open_pty(cols: usize, rows: usize) -> Result<(File, File), String> {
+    let mut master: libc::c_int = -1;
+    let mut slave: libc::c_int = -1;
+    let mut size = window_size(cols, rows);
+    let result = unsafe {
+        libc::openpty(
+            &mut master,
+            &mut slave,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            &mut size,
+        )
+    };
+    if result != 0 {
+        return Err(format!(
+            "Failed to open terminal: {}",
+            std::io::Error::last_os_error()
+        ));
+    }
+    unsafe {
+        libc::fcntl(master, libc::F_SETFD, libc::FD_CLOEXEC);
+        libc::fcntl(slave, libc::F_SETFD, libc::FD_CLOEXEC);
+        Ok((File::from_raw_fd(master), File::from_raw_fd(slave)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn read_until(pty: &mut Pty, expected: &str) -> String {
+        let mut output = vec![];
+        for _ in 0..500 {
+            output.extend(pty.read());
+            if String::from_utf8_lossy(&output).contains(expected) {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        String::from_utf8_lossy(&output).to_string()
+    }
+
+    #[test]
+    fn must_run_program_on_terminal() {
+        let args = vec![
+            "-c".to_owned(),
+            "stty size; [ -t 0 ] && pwd; read line; echo \"got $line\"".to_owned(),
+        ];
+        let mut pty = Pty::spawn("/bin/sh", &args, "/tmp", 80, 24).unwrap();
+        let output = read_until(&mut pty, "/tmp");
+        assert_eq!(output.contains("24 80"), true);
+        assert_eq!(output.contains("/tmp"), true);
+
+        pty.write(b"hello\r").unwrap();
+        let output = read_until(&mut pty, "got hello");
+        assert_eq!(output.contains("got hello"), true);
+        for _ in 0..500 {
+            pty.read();
+            if !pty.is_running() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(pty.is_running(), false);
+    }
+
+    #[test]
+    fn must_fail_to_start_missing_program() {
+        let result = Pty::spawn("/tmp/rider/no-such-shell", &[], "/tmp", 80, 24);
+        assert_eq!(result.is_err(), true);
+    }
+}
@@ -0,0 +1,558 @@
+use std::collections::VecDeque;
+
+pub const MAX_SCROLLBACK: usize = 10_000;
+const TAB_WIDTH: usize = 8;
+
+/// Color of cell, indexed colors are 256 xterm colors
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TermColor {
+    Default,
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CellStyle {
+    pub fg: TermColor,
+    pub bg: TermColor,
+    pub bold: bool,
+    pub inverse: bool,
+}
+
+impl Default for CellStyle {
+    fn default() -> Self {
+        Self {
+            fg: TermColor::Default,
+            bg: TermColor::Default,
+            bold: false,
+            inverse: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cell {
+    pub c: char,
+    pub style: CellStyle,
+}
+
+impl Cell {
+    /// Erased cell keeps background of current style like xterm does
+    fn blank(style: CellStyle) -> Self {
+        Self {
+            c: ' ',
+            style: CellStyle {
+                bg: style.bg,
+                ..CellStyle::default()
+            },
+        }
+    }
+}
+
+type Line = Vec<Cell>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct SavedCursor {
+    row: usize,
+    col: usize,
+    style: CellStyle,
+}
+
+/// Grid of terminal cells with scrollback and alternate screen.
+///
+/// Rows and columns are counted from 0, lines which scroll out of top of
+/// main screen are moved to scrollback.
+pub struct Screen {
+    cols: usize,
+    rows: usize,
+    lines: Vec<Line>,
+    scrollback: VecDeque<Line>,
+    main_lines: Option<Vec<Line>>,
+    row: usize,
+    col: usize,
+    wrap_pending: bool,
+    saved_cursor: SavedCursor,
+    scroll_top: usize,
+    scroll_bottom: usize,
+    style: CellStyle,
+    title: String,
+    cursor_visible: bool,
+    application_cursor: bool,
+    responses: Vec<u8>,
+}
+
+impl Screen {
+    pub fn new(cols: usize, rows: usize) -> Self {
+        let cols = cols.max(1);
+        let rows = rows.max(1);
+        Self {
+            cols,
+            rows,
+            lines: vec![vec![Cell::blank(CellStyle::default()); cols]; rows],
+            scrollback: VecDeque::new(),
+            main_lines: None,
+            row: 0,
+            col: 0,
+            wrap_pending: false,
+            saved_cursor: SavedCursor {
+                row: 0,
+                col: 0,
+                style: CellStyle::default(),
+            },
+            scroll_top: 0,
+            scroll_bottom: rows - 1,
+            style: CellStyle::default(),
+            title: String::new(),
+            cursor_visible: true,
+            application_cursor: false,
+            responses: vec![],
+        }
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Row and column of cursor on screen
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.row, self.col)
+    }
+
+    pub fn cursor_visible(&self) -> bool {
+        self.cursor_visible
+    }
+
+    pub fn set_cursor_visible(&mut self, visible: bool) {
+        self.cursor_visible = visible;
+    }
+
+    /// Cursor keys should send `ESC O` sequences instead of `ESC [` ones
+    pub fn application_cursor(&self) -> bool {
+        self.application_cursor
+    }
+
+    pub fn set_application_cursor(&mut self, application_cursor: bool) {
+        self.application_cursor = application_cursor;
+    }
+
+    pub fn is_alternate(&self) -> bool {
+        self.main_lines.is_some()
+    }
+
+    pub fn title(&self) -> &String {
+        &self.title
+    }
+
+    pub fn set_title(&mut self, title: String) {
+        self.title = title;
+    }
+
+    pub fn style_mut(&mut self) -> &mut CellStyle {
+        &mut self.style
+    }
+
+    /// Answer to program, for example cursor position report
+    pub fn respond(&mut self, bytes: &[u8]) {
+        self.responses.extend_from_slice(bytes);
+    }
+
+    pub fn take_responses(&mut self) -> Vec<u8> {
+        std::mem::replace(&mut self.responses, vec![])
+    }
+
+    pub fn scrollback_len(&self) -> usize {
+        self.scrollback.len()
+    }
+
+    /// Number of scrollback and screen lines
+    pub fn total_lines(&self) -> usize {
+        self.scrollback.len() + self.rows
+    }
+
+    /// Line by index counted from oldest scrollback line
+    pub fn line(&self, index: usize) -> Option<&Line> {
+        match index.checked_sub(self.scrollback.len()) {
+            Some(row) => self.lines.get(row),
+            None => self.scrollback.get(index),
+        }
+    }
+
+    pub fn line_text(&self, index: usize) -> String {
+        self.line(index)
+            .map(|line| line.iter().map(|cell| cell.c).collect::<String>())
+            .unwrap_or_default()
+            .trim_end()
+            .to_owned()
+    }
+
+    /// Last match of query before line, returns line index and column.
+    ///
+    /// Letters case is ignored.
+    pub fn find_before(&self, query: &str, before: usize) -> Option<(usize, usize)> {
+        if query.is_empty() {
+            return None;
+        }
+        let query = query.to_lowercase();
+        (0..before.min(self.total_lines())).rev().find_map(|index| {
+            let text = self.line_text(index).to_lowercase();
+            text.rfind(query.as_str())
+                .map(|offset| (index, text[..offset].chars().count()))
+        })
+    }
+
+    pub fn print(&mut self, c: char) {
+        if self.wrap_pending {
+            self.wrap_pending = false;
+            self.col = 0;
+            self.line_feed();
+        }
+        self.lines[self.row][self.col] = Cell {
+            c,
+            style: self.style,
+        };
+        if self.col + 1 == self.cols {
+            self.wrap_pending = true;
+        } else {
+            self.col += 1;
+        }
+    }
+
+    pub fn line_feed(&mut self) {
+        self.wrap_pending = false;
+        if self.row == self.scroll_bottom {
+            self.scroll_up(1);
+        } else if self.row + 1 < self.rows {
+            self.row += 1;
+        }
+    }
+
+    pub fn reverse_index(&mut self) {
+        self.wrap_pending = false;
+        if self.row == self.scroll_top {
+            self.scroll_down(1);
+        } else if self.row > 0 {
+            self.row -= 1;
+        }
+    }
+
+    pub fn carriage_return(&mut self) {
+        self.wrap_pending = false;
+        self.col = 0;
+    }
+
+    pub fn backspace(&mut self) {
+        self.wrap_pending = false;
+        self.col = self.col.saturating_sub(1);
+    }
+
+    pub fn tab(&mut self) {
+        let next = (self.col / TAB_WIDTH + 1) * TAB_WIDTH;
+        self.col = next.min(self.cols - 1);
+    }
+
+    /// Scrolls region up, lines leaving top of main screen go to scrollback
+    pub fn scroll_up(&mut self, count: usize) {
+        let to_scrollback = self.scroll_top == 0 && !self.is_alternate();
+        self.shift_up(count, to_scrollback);
+    }
+
+    fn shift_up(&mut self, count: usize, to_scrollback: bool) {
+        for _ in 0..count.min(self.scroll_bottom - self.scroll_top + 1) {
+            let line = self.lines.remove(self.scroll_top);
+            self.lines
+                .insert(self.scroll_bottom, vec![Cell::blank(self.style); self.cols]);
+            if to_scrollback {
+                self.scrollback.push_back(line);
+                if self.scrollback.len() > MAX_SCROLLBACK {
+                    self.scrollback.pop_front();
+                }
+            }
+        }
+    }
+
+    pub fn scroll_down(&mut self, count: usize) {
+        for _ in 0..count.min(self.scroll_bottom - self.scroll_top + 1) {
+            self.lines.remove(self.scroll_bottom);
+            self.lines
+                .insert(self.scroll_top, vec![Cell::blank(self.style); self.cols]);
+        }
+    }
+
+    pub fn move_to(&mut self, row: usize, col: usize) {
+        self.wrap_pending = false;
+        self.row = row.min(self.rows - 1);
+        self.col = col.min(self.cols - 1);
+    }
+
+    pub fn move_by(&mut self, rows: isize, cols: isize) {
+        let row = (self.row as isize + rows).max(0) as usize;
+        let col = (self.col as isize + cols).max(0) as usize;
+        self.move_to(row, col);
+    }
+
+    /// 0 erases from cursor to end, 1 from start to cursor, 2 whole screen and 3 also scrollback
+    pub fn erase_display(&mut self, mode: usize) {
+        let blank = Cell::blank(self.style);
+        match mode {
+            0 => {
+                self.erase_line(0);
+                for line in self.lines[self.row + 1..].iter_mut() {
+                    *line = vec![blank; self.cols];
+                }
+            }
+            1 => {
+                self.erase_line(1);
+                for line in self.lines[..self.row].iter_mut() {
+                    *line = vec![blank; self.cols];
+                }
+            }
+            _ => {
+                for line in self.lines.iter_mut() {
+                    *line = vec![blank; self.cols];
+                }
+                if mode == 3 {
+                    self.scrollback.clear();
+                }
+            }
+        }
+    }
+
+    /// 0 erases from cursor to end of line, 1 from start of line to cursor, 2 whole line
+    pub fn erase_line(&mut self, mode: usize) {
+        let blank = Cell::blank(self.style);
+        let (start, end) = match mode {
+            0 => (self.col, self.cols),
+            1 => (0, self.col + 1),
+            _ => (0, self.cols),
+        };
+        for cell in self.lines[self.row][start..end].iter_mut() {
+            *cell = blank;
+        }
+    }
+
+    pub fn erase_chars(&mut self, count: usize) {
+        let blank = Cell::blank(self.style);
+        let end = (self.col + count.max(1)).min(self.cols);
+        for cell in self.lines[self.row][self.col..end].iter_mut() {
+            *cell = blank;
+        }
+    }
+
+    pub fn insert_chars(&mut self, count: usize) {
+        let blank = Cell::blank(self.style);
+        let line = &mut self.lines[self.row];
+        for _ in 0..count.max(1).min(self.cols - self.col) {
+            line.insert(self.col, blank);
+            line.pop();
+        }
+    }
+
+    pub fn delete_chars(&mut self, count: usize) {
+        let blank = Cell::blank(self.style);
+        let line = &mut self.lines[self.row];
+        for _ in 0..count.max(1).min(self.cols - self.col) {
+            line.remove(self.col);
+            line.push(blank);
+        }
+    }
+
+    pub fn insert_lines(&mut self, count: usize) {
+        if self.row < self.scroll_top || self.row > self.scroll_bottom {
+            return;
+        }
+        let top = self.scroll_top;
+        self.scroll_top = self.row;
+        self.scroll_down(count.max(1));
+        self.scroll_top = top;
+        self.col = 0;
+    }
+
+    pub fn delete_lines(&mut self, count: usize) {
+        if self.row < self.scroll_top || self.row > self.scroll_bottom {
+            return;
+        }
+        let top = self.scroll_top;
+        self.scroll_top = self.row;
+        self.shift_up(count.max(1), false);
+        self.scroll_top = top;
+        self.col = 0;
+    }
+
+    /// Limits scrolling to rows between top and bottom, both included
+    pub fn set_scroll_region(&mut self, top: usize, bottom: usize) {
+        let bottom = bottom.min(self.rows - 1);
+        if top >= bottom {
+            return;
+        }
+        self.scroll_top = top;
+        self.scroll_bottom = bottom;
+        self.move_to(0, 0);
+    }
+
+    pub fn save_cursor(&mut self) {
+        self.saved_cursor = SavedCursor {
+            row: self.row,
+            col: self.col,
+            style: self.style,
+        };
+    }
+
+    pub fn restore_cursor(&mut self) {
+        let saved = self.saved_cursor;
+        self.style = saved.style;
+        self.move_to(saved.row, saved.col);
+    }
+
+    /// Switches to empty alternate screen, main screen is restored when it is left
+    pub fn set_alternate(&mut self, alternate: bool) {
+        if alternate == self.is_alternate() {
+            return;
+        }
+        let blank = vec![vec![Cell::blank(CellStyle::default()); self.cols]; self.rows];
+        if alternate {
+            self.save_cursor();
+            self.main_lines = Some(std::mem::replace(&mut self.lines, blank));
+        } else {
+            self.lines = self.main_lines.take().unwrap_or(blank);
+            self.restore_cursor();
+        }
+        self.scroll_top = 0;
+        self.scroll_bottom = self.rows - 1;
+    }
+
+    pub fn reset(&mut self) {
+        let title = self.title.clone();
+        *self = Self::new(self.cols, self.rows);
+        self.title = title;
+    }
+
+    /// Changes size keeping cursor line visible, lines above it go to scrollback
+    pub fn resize(&mut self, cols: usize, rows: usize) {
+        let cols = cols.max(1);
+        let rows = rows.max(1);
+        if cols == self.cols && rows == self.rows {
+            return;
+        }
+        let resize_line = |line: &mut Line| line.resize(cols, Cell::blank(CellStyle::default()));
+        self.lines.iter_mut().for_each(resize_line);
+        if let Some(lines) = self.main_lines.as_mut() {
+            lines.iter_mut().for_each(resize_line);
+            lines.resize(rows, vec![Cell::blank(CellStyle::default()); cols]);
+        }
+        if self.row >= rows {
+            let overflow = self.row + 1 - rows;
+            for line in self.lines.drain(..overflow) {
+                if self.main_lines.is_none() {
+                    self.scrollback.push_back(line);
+                }
+            }
+            self.row -= overflow;
+        }
+        self.lines
+            .resize(rows, vec![Cell::blank(CellStyle::default()); cols]);
+        self.cols = cols;
+        self.rows = rows;
+        self.scroll_top = 0;
+        self.scroll_bottom = rows - 1;
+        self.wrap_pending = false;
+        self.move_to(self.row, self.col);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn print(screen: &mut Screen, text: &str) {
+        for c in text.chars() {
+            match c {
+                '\n' => {
+                    screen.carriage_return();
+                    screen.line_feed();
+                }
+                c => screen.print(c),
+            }
+        }
+    }
+
+    fn texts(screen: &Screen) -> Vec<String> {
+        (0..screen.total_lines())
+            .map(|index| screen.line_text(index))
+            .collect()
+    }
+
+    #[test]
+    fn must_wrap_and_scroll_into_scrollback() {
+        let mut screen = Screen::new(4, 2);
+        print(&mut screen, "abcdef\ngh");
+        assert_eq!(texts(&screen), vec!["abcd", "ef", "gh"]);
+        assert_eq!(screen.scrollback_len(), 1);
+        assert_eq!(screen.cursor(), (1, 2));
+        assert_eq!(screen.find_before("EF", screen.total_lines()), Some((1, 0)));
+        assert_eq!(screen.find_before("ef", 1), None);
+    }
+
+    #[test]
+    fn must_erase_and_edit_lines() {
+        let mut screen = Screen::new(5, 3);
+        print(&mut screen, "aaaaa\nbbbbb\nccc");
+        screen.move_to(1, 2);
+        screen.erase_line(0);
+        screen.move_to(0, 1);
+        screen.delete_chars(2);
+        screen.move_to(2, 0);
+        screen.insert_chars(1);
+        assert_eq!(texts(&screen), vec!["aaa", "bb", " ccc"]);
+
+        screen.move_to(1, 3);
+        screen.insert_lines(1);
+        assert_eq!(texts(&screen), vec!["aaa", "", "bb"]);
+        screen.delete_lines(1);
+        assert_eq!(texts(&screen), vec!["aaa", "bb", ""]);
+        screen.erase_display(2);
+        assert_eq!(texts(&screen), vec!["", "", ""]);
+    }
+
+    #[test]
+    fn must_keep_main_screen_while_alternate_is_used() {
+        let mut screen = Screen::new(4, 2);
+        print(&mut screen, "main");
+        screen.set_alternate(true);
+        assert_eq!(texts(&screen), vec!["", ""]);
+        print(&mut screen, "a\nb\nc");
+        assert_eq!(screen.scrollback_len(), 0);
+        screen.set_alternate(false);
+        assert_eq!(texts(&screen), vec!["main", ""]);
+        assert_eq!(screen.cursor(), (0, 3));
+    }
+
+    #[test]
+    fn must_scroll_only_inside_region() {
+        let mut screen = Screen::new(3, 4);
+        print(&mut screen, "1\n2\n3\n4");
+        screen.set_scroll_region(1, 2);
+        screen.move_to(2, 0);
+        screen.line_feed();
+        assert_eq!(texts(&screen), vec!["1", "3", "", "4"]);
+        screen.move_to(1, 0);
+        screen.reverse_index();
+        assert_eq!(texts(&screen), vec!["1", "", "3", "4"]);
+        assert_eq!(screen.scrollback_len(), 0);
+    }
+
+    #[test]
+    fn must_resize_keeping_cursor_line() {
+        let mut screen = Screen::new(4, 3);
+        print(&mut screen, "a\nb\nc");
+        screen.resize(2, 2);
+        assert_eq!(texts(&screen), vec!["a", "b", "c"]);
+        assert_eq!(screen.cursor(), (1, 1));
+        screen.resize(6, 4);
+        assert_eq!(screen.rows(), 4);
+        assert_eq!(screen.line(1).map(|line| line.len()), Some(6));
+    }
+}
@@ -0,0 +1,384 @@
+use crate::lsp::*;
+use rider_config::LanguageServer;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufReader, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{channel, Receiver};
+use std::thread;
+use std::time::Duration;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Request {
+    Initialize,
+    Hover,
+    Completion,
+    Definition,
+    Formatting(String),
+    Shutdown,
+}
+
+/// Language server process speaking JSON-RPC over stdio.
+///
+/// Messages written before server answered `initialize` are queued and sent once it did.
+pub struct LspClient {
+    child: Child,
+    stdin: ChildStdin,
+    receiver: Receiver<Value>,
+    language_id: String,
+    next_id: u64,
+    pending: HashMap<u64, Request>,
+    initialized: bool,
+    queue: Vec<Value>,
+    versions: HashMap<String, u64>,
+}
+
+impl LspClient {
+    pub fn start(server: &LanguageServer, root_path: &str) -> Result<Self, String> {
+        let mut command = Command::new(&server.command);
+        command
+            .args(&server.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null());
+        if Path::new(root_path).is_dir() {
+            command.current_dir(root_path);
+        }
+        let mut child = command
+            .spawn()
+            .map_err(|e| format!("Failed to start {}: {}", server.command, e))?;
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| "Language server has no stdin".to_string())?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| "Language server has no stdout".to_string())?;
+        let (sender, receiver) = channel();
+        thread::spawn(move || {
+            let mut reader = BufReader::new(stdout);
+            while let Ok(Some(message)) = read_message(&mut reader) {
+                if sender.send(message).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut client = Self {
+            child,
+            stdin,
+            receiver,
+            language_id: server.language_id.clone(),
+            next_id: 0,
+            pending: HashMap::new(),
+            initialized: false,
+            queue: vec![],
+            versions: HashMap::new(),
+        };
+        let params = json!({
+            "processId": std::process::id(),
+            "rootUri": path_to_uri(root_path),
+            "capabilities": {
+                "textDocument": {
+                    "synchronization": { "didSave": true },
+                    "hover": { "contentFormat": ["plaintext", "markdown"] },
+                    "completion": { "completionItem": { "snippetSupport": false } },
+                    "definition": { "linkSupport": true },
+                    "formatting": {},
+                    "publishDiagnostics": {}
+                }
+            }
+        });
+        client.request("initialize", params, Request::Initialize);
+        Ok(client)
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.initialized
+    }
+
+    pub fn is_open(&self, path: &str) -> bool {
+        self.versions.contains_key(path)
+    }
+
+    pub fn did_open(&mut self, path: &str, text: &str) {
+        self.versions.insert(path.to_owned(), 1);
+        let params = json!({
+            "textDocument": {
+                "uri": path_to_uri(path),
+                "languageId": self.language_id,
+                "version": 1,
+                "text": text
+            }
+        });
+        self.notify("textDocument/didOpen", params);
+    }
+
+    pub fn did_change(&mut self, path: &str, changes: &[TextChange]) {
+        let version = match self.versions.get_mut(path) {
+            Some(version) => {
+                *version += 1;
+                *version
+            }
+            _ => return,
+        };
+        let changes: Vec<Value> = changes.iter().map(|change| change.to_json()).collect();
+        let params = json!({
+            "textDocument": { "uri": path_to_uri(path), "version": version },
+            "contentChanges": changes
+        });
+        self.notify("textDocument/didChange", params);
+    }
+
+    pub fn did_save(&mut self, path: &str) {
+        if self.is_open(path) {
+            let params = json!({ "textDocument": { "uri": path_to_uri(path) } });
+            self.notify("textDocument/didSave", params);
+        }
+    }
+
+    pub fn did_close(&mut self, path: &str) {
+        if self.versions.remove(path).is_some() {
+            let params = json!({ "textDocument": { "uri": path_to_uri(path) } });
+            self.notify("textDocument/didClose", params);
+        }
+    }
+
+    pub fn hover(&mut self, path: &str, position: Position) {
+        let params = text_document_position(path, position);
+        self.request("textDocument/hover", params, Request::Hover);
+    }
+
+    pub fn completion(&mut self, path: &str, position: Position) {
+        let params = text_document_position(path, position);
+        self.request("textDocument/completion", params, Request::Completion);
+    }
+
+    pub fn definition(&mut self, path: &str, position: Position) {
+        let params = text_document_position(path, position);
+        self.request("textDocument/definition", params, Request::Definition);
+    }
+
+    pub fn formatting(&mut self, path: &str, tab_size: usize) {
+        let params = json!({
+            "textDocument": { "uri": path_to_uri(path) },
+            "options": { "tabSize": tab_size, "insertSpaces": true }
+        });
+        self.request(
+            "textDocument/formatting",
+            params,
+            Request::Formatting(path.to_owned()),
+        );
+    }
+
+    /// Events from messages received since last call
+    pub fn poll(&mut self) -> Vec<LspEvent> {
+        let mut events = vec![];
+        while let Ok(message) = self.receiver.try_recv() {
+            if let Some(event) = self.handle_message(message) {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    fn handle_message(&mut self, message: Value) -> Option<LspEvent> {
+        if let Some(method) = message.get("method").and_then(|m| m.as_str()) {
+            if let Some(id) = message.get("id") {
+                let response = json!({ "jsonrpc": "2.0", "id": id, "result": null });
+                self.write(&response);
+                return None;
+            }
+            return match method {
+                "textDocument/publishDiagnostics" => parse_diagnostics(&message["params"]),
+                "window/showMessage" if message["params"]["type"] == 1 => message["params"]
+                    ["message"]
+                    .as_str()
+                    .map(|text| LspEvent::Error(text.to_owned())),
+                _ => None,
+            };
+        }
+        let id = message.get("id").and_then(|id| id.as_u64())?;
+        let request = self.pending.remove(&id)?;
+        if let Some(error) = message.get("error") {
+            let text = error["message"].as_str().unwrap_or("Unknown error");
+            return Some(LspEvent::Error(text.to_owned()));
+        }
+        let result = &message["result"];
+        match request {
+            Request::Initialize => {
+                self.initialized = true;
+                self.write(&json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }));
+                for message in std::mem::replace(&mut self.queue, vec![]) {
+                    self.write(&message);
+                }
+                None
+            }
+            Request::Hover => parse_hover(result).map(LspEvent::Hover),
+            Request::Completion => Some(LspEvent::Completion(parse_completion(result))),
+            Request::Definition => Some(LspEvent::Definition(parse_locations(result))),
+            Request::Formatting(path) => Some(LspEvent::Formatting {
+                path,
+                edits: parse_text_edits(result),
+            }),
+            Request::Shutdown => None,
+        }
+    }
+
+    fn request(&mut self, method: &str, params: Value, request: Request) {
+        self.next_id += 1;
+        self.pending.insert(self.next_id, request);
+        let message = json!({
+            "jsonrpc": "2.0",
+            "id": self.next_id,
+            "method": method,
+            "params": params
+        });
+        self.send(message);
+    }
+
+    fn notify(&mut self, method: &str, params: Value) {
+        let message = json!({ "jsonrpc": "2.0", "method": method, "params": params });
+        self.send(message);
+    }
+
+    fn send(&mut self, message: Value) {
+        if self.initialized || message["method"] == "initialize" {
+            self.write(&message);
+        } else {
+            self.queue.push(message);
+        }
+    }
+
+    fn write(&mut self, message: &Value) {
+        let result = self
+            .stdin
+            .write_all(encode(message).as_slice())
+            .and_then(|_| self.stdin.flush());
+        if let Err(e) = result {
+            error!("Failed to write to language server: {}", e);
+        }
+    }
+}
+
+impl Drop for LspClient {
+    /// Asks server to exit and kills it when it does not in short time
+    fn drop(&mut self) {
+        if self.initialized {
+            self.request("shutdown", Value::Null, Request::Shutdown);
+            self.notify("exit", Value::Null);
+            for _ in 0..20 {
+                if let Ok(Some(_)) = self.child.try_wait() {
+                    return;
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+        }
+        self.child.kill().unwrap_or_else(|e| warn!("{}", e));
+        self.child.wait().ok();
+    }
+}
+
+fn text_document_position(path: &str, position: Position) -> Value {
+    json!({
+        "textDocument": { "uri": path_to_uri(path) },
+        "position": position.to_json()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+
+    fn wait_for_events(client: &mut LspClient, count: usize) -> Vec<LspEvent> {
+        let mut events = vec![];
+        for _ in 0..500 {
+            events.extend(client.poll());
+            if events.len() >= count {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        events
+    }
+
+    #[test]
+    fn must_sync_document_incrementally() {
+        let server = lsp_stub_server("client-sync", r#"{ "echoText": true }"#);
+        let mut client = LspClient::start(&server, "/tmp").unwrap();
+        let path = "/tmp/rider/lsp/main.rs";
+        client.did_open(path, "fn main() {}\n");
+        let old = "fn main() {}\n";
+        let new = "fn main() {\n    run();\n}\n";
+        client.did_change(path, &[text_change(old, new).unwrap()]);
+
+        let events = wait_for_events(&mut client, 2);
+        assert_eq!(client.is_initialized(), true);
+        let texts: Vec<String> = events
+            .iter()
+            .filter_map(|event| match event {
+                LspEvent::Diagnostics { diagnostics, .. } => {
+                    diagnostics.last().map(|d| d.message.clone())
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(texts, vec![old.to_owned(), new.to_owned()]);
+    }
+
+    #[test]
+    fn must_translate_responses() {
+        let server = lsp_stub_server(
+            "client-responses",
+            r#"{
+                "responses": {
+                    "textDocument/hover": { "contents": { "kind": "plaintext", "value": "fn main()" } },
+                    "textDocument/completion": [{ "label": "main" }],
+                    "textDocument/definition": {
+                        "uri": "file:///tmp/a.rs",
+                        "range": { "start": { "line": 1, "character": 3 }, "end": { "line": 1, "character": 7 } }
+                    },
+                    "textDocument/formatting": [{
+                        "range": { "start": { "line": 0, "character": 0 }, "end": { "line": 0, "character": 2 } },
+                        "newText": "fn"
+                    }]
+                }
+            }"#,
+        );
+        let mut client = LspClient::start(&server, "/tmp").unwrap();
+        let path = "/tmp/a.rs";
+        client.hover(path, Position::new(0, 0));
+        client.completion(path, Position::new(0, 0));
+        client.definition(path, Position::new(0, 0));
+        client.formatting(path, 4);
+
+        let range = |a, b, c, d| Range::new(Position::new(a, b), Position::new(c, d));
+        assert_eq!(
+            wait_for_events(&mut client, 4),
+            vec![
+                LspEvent::Hover("fn main()".to_owned()),
+                LspEvent::Completion(vec!["main".to_owned()]),
+                LspEvent::Definition(vec![LspLocation {
+                    path: path.to_owned(),
+                    range: range(1, 3, 1, 7),
+                }]),
+                LspEvent::Formatting {
+                    path: path.to_owned(),
+                    edits: vec![TextEdit {
+                        range: range(0, 0, 0, 2),
+                        new_text: "fn".to_owned(),
+                    }],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn must_fail_to_start_missing_server() {
+        let server = LanguageServer::new("/tmp/rider/no-such-language-server", "rust");
+        assert_eq!(LspClient::start(&server, "/tmp").is_err(), true);
+    }
+}
@@ -0,0 +1,80 @@
+use crate::lsp::*;
+use rider_config::Config;
+use rider_lexers::Language;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Language servers of project, each one is started on first use
+pub struct LspManager {
+    root_path: String,
+    clients: BTreeMap<Language, LspClient>,
+    failed: BTreeSet<Language>,
+}
+
+impl LspManager {
+    pub fn new(root_path: String) -> Self {
+        Self {
+            root_path,
+            clients: BTreeMap::new(),
+            failed: BTreeSet::new(),
+        }
+    }
+
+    /// Running servers are stopped, new ones start in given directory
+    pub fn set_root_path(&mut self, root_path: String) {
+        if self.root_path != root_path {
+            self.clients.clear();
+            self.failed.clear();
+            self.root_path = root_path;
+        }
+    }
+
+    /// Client for language when server is configured, server which failed to start is not retried
+    pub fn client(&mut self, language: Language, config: &Config) -> Option<&mut LspClient> {
+        if !self.clients.contains_key(&language) && !self.failed.contains(&language) {
+            let server = config.language_server(language)?;
+            match LspClient::start(server, self.root_path.as_str()) {
+                Ok(client) => {
+                    info!("Started language server {}", server.command);
+                    self.clients.insert(language, client);
+                }
+                Err(e) => {
+                    error!("{}", e);
+                    self.failed.insert(language);
+                }
+            }
+        }
+        self.clients.get_mut(&language)
+    }
+
+    pub fn poll(&mut self) -> Vec<LspEvent> {
+        self.clients
+            .values_mut()
+            .flat_map(|client| client.poll())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+
+    #[test]
+    fn must_start_configured_server_once() {
+        let mut config = Config::new();
+        config.language_servers_mut().clear();
+        config.set_language_server(Language::Rust, lsp_stub_server("manager", "{}"));
+        config.set_language_server(
+            Language::Python,
+            rider_config::LanguageServer::new("/tmp/rider/no-such-language-server", "python"),
+        );
+        let mut manager = LspManager::new("/tmp".to_owned());
+
+        assert_eq!(manager.client(Language::Rust, &config).is_some(), true);
+        assert_eq!(manager.client(Language::Rust, &config).is_some(), true);
+        assert_eq!(manager.clients.len(), 1);
+        assert_eq!(manager.client(Language::Python, &config).is_none(), true);
+        assert_eq!(manager.failed.contains(&Language::Python), true);
+        assert_eq!(manager.client(Language::Toml, &config).is_none(), true);
+    }
+}
@@ -0,0 +1,9 @@
+pub mod client;
+pub mod manager;
+pub mod protocol;
+pub mod transport;
+
+pub use crate::lsp::client::*;
+pub use crate::lsp::manager::*;
+pub use crate::lsp::protocol::*;
+pub use crate::lsp::transport::*;
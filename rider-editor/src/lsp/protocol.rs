@@ -0,0 +1,421 @@
+use crate::app::DiagnosticSeverity;
+use serde_json::{json, Value};
+
+/// Position in document, `character` counts UTF-16 code units as LSP requires
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Position {
+    pub line: usize,
+    pub character: usize,
+}
+
+impl Position {
+    pub fn new(line: usize, character: usize) -> Self {
+        Self { line, character }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({ "line": self.line, "character": self.character })
+    }
+
+    pub fn from_json(value: &Value) -> Option<Self> {
+        Some(Self {
+            line: value.get("line")?.as_u64()? as usize,
+            character: value.get("character")?.as_u64()? as usize,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Range {
+    pub fn new(start: Position, end: Position) -> Self {
+        Self { start, end }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({ "start": self.start.to_json(), "end": self.end.to_json() })
+    }
+
+    pub fn from_json(value: &Value) -> Option<Self> {
+        Some(Self {
+            start: Position::from_json(value.get("start")?)?,
+            end: Position::from_json(value.get("end")?)?,
+        })
+    }
+}
+
+/// Incremental document change, range is in text before change
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextChange {
+    pub range: Range,
+    pub text: String,
+}
+
+impl TextChange {
+    pub fn to_json(&self) -> Value {
+        json!({ "range": self.range.to_json(), "text": self.text })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LspDiagnostic {
+    pub range: Range,
+    pub severity: DiagnosticSeverity,
+    pub message: String,
+    pub source: String,
+}
+
+impl LspDiagnostic {
+    pub fn from_json(value: &Value) -> Option<Self> {
+        let severity = match value.get("severity").and_then(|s| s.as_u64()) {
+            Some(2) => DiagnosticSeverity::Warning,
+            Some(3) => DiagnosticSeverity::Information,
+            Some(4) => DiagnosticSeverity::Hint,
+            _ => DiagnosticSeverity::Error,
+        };
+        Some(Self {
+            range: Range::from_json(value.get("range")?)?,
+            severity,
+            message: value.get("message")?.as_str()?.to_owned(),
+            source: value
+                .get("source")
+                .and_then(|s| s.as_str())
+                .unwrap_or_default()
+                .to_owned(),
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextEdit {
+    pub range: Range,
+    pub new_text: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LspLocation {
+    pub path: String,
+    pub range: Range,
+}
+
+/// Server message translated for editor
+#[derive(Clone, Debug, PartialEq)]
+pub enum LspEvent {
+    Diagnostics {
+        path: String,
+        diagnostics: Vec<LspDiagnostic>,
+    },
+    Hover(String),
+    Completion(Vec<String>),
+    Definition(Vec<LspLocation>),
+    Formatting {
+        path: String,
+        edits: Vec<TextEdit>,
+    },
+    Error(String),
+}
+
+pub fn path_to_uri(path: &str) -> String {
+    let mut uri = "file://".to_owned();
+    for byte in path.bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(format!("%{:02X}", byte).as_str()),
+        }
+    }
+    uri
+}
+
+pub fn uri_to_path(uri: &str) -> Option<String> {
+    if !uri.starts_with("file://") {
+        return None;
+    }
+    let encoded = &uri.as_bytes()["file://".len()..];
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut index = 0;
+    while index < encoded.len() {
+        match encoded[index] {
+            b'%' if index + 2 < encoded.len() => {
+                let hex = std::str::from_utf8(&encoded[index + 1..index + 3]).ok()?;
+                bytes.push(u8::from_str_radix(hex, 16).ok()?);
+                index += 3;
+            }
+            byte => {
+                bytes.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// LSP position of given char position
+pub fn position_at(buffer: &str, position: usize) -> Position {
+    let mut result = Position::default();
+    for c in buffer.chars().take(position) {
+        if c == '\n' {
+            result.line += 1;
+            result.character = 0;
+        } else {
+            result.character += c.len_utf16();
+        }
+    }
+    result
+}
+
+/// Char position of LSP position, clamped to line end and buffer end
+pub fn offset_at(buffer: &str, position: Position) -> usize {
+    let mut line = 0;
+    let mut units = 0;
+    for (index, c) in buffer.chars().enumerate() {
+        if line == position.line && (units >= position.character || c == '\n') {
+            return index;
+        }
+        if c == '\n' {
+            line += 1;
+        } else if line == position.line {
+            units += c.len_utf16();
+        }
+    }
+    buffer.chars().count()
+}
+
+/// Smallest change turning old text into new one, `None` when both are equal
+pub fn text_change(old: &str, new: &str) -> Option<TextChange> {
+    if old == new {
+        return None;
+    }
+    let old_chars: Vec<char> = old.chars().collect();
+    let new_chars: Vec<char> = new.chars().collect();
+    let prefix = old_chars
+        .iter()
+        .zip(new_chars.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old_chars[prefix..]
+        .iter()
+        .rev()
+        .zip(new_chars[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    Some(TextChange {
+        range: Range::new(
+            position_at(old, prefix),
+            position_at(old, old_chars.len() - suffix),
+        ),
+        text: new_chars[prefix..new_chars.len() - suffix].iter().collect(),
+    })
+}
+
+/// `textDocument/publishDiagnostics` params
+pub fn parse_diagnostics(params: &Value) -> Option<LspEvent> {
+    let path = uri_to_path(params.get("uri")?.as_str()?)?;
+    let diagnostics = params
+        .get("diagnostics")?
+        .as_array()?
+        .iter()
+        .filter_map(LspDiagnostic::from_json)
+        .collect();
+    Some(LspEvent::Diagnostics { path, diagnostics })
+}
+
+/// Text of `Hover` result, `MarkedString`, `MarkedString[]` and `MarkupContent` are supported
+pub fn parse_hover(result: &Value) -> Option<String> {
+    fn marked(value: &Value) -> Option<String> {
+        match value {
+            Value::String(text) => Some(text.clone()),
+            Value::Object(_) => value.get("value")?.as_str().map(|s| s.to_owned()),
+            _ => None,
+        }
+    }
+    let contents = result.get("contents")?;
+    let text = match contents {
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(marked)
+            .collect::<Vec<String>>()
+            .join("\n"),
+        _ => marked(contents)?,
+    };
+    match text.trim() {
+        "" => None,
+        text => Some(text.to_owned()),
+    }
+}
+
+/// Labels of `CompletionItem[]` or `CompletionList` result
+pub fn parse_completion(result: &Value) -> Vec<String> {
+    let items = match result {
+        Value::Array(items) => items,
+        Value::Object(_) => match result.get("items").and_then(|items| items.as_array()) {
+            Some(items) => items,
+            _ => return vec![],
+        },
+        _ => return vec![],
+    };
+    items
+        .iter()
+        .filter_map(|item| {
+            item.get("filterText")
+                .or_else(|| item.get("label"))
+                .and_then(|label| label.as_str())
+                .map(|label| label.to_owned())
+        })
+        .collect()
+}
+
+/// `Location`, `Location[]` or `LocationLink[]` result
+pub fn parse_locations(result: &Value) -> Vec<LspLocation> {
+    fn location(value: &Value) -> Option<LspLocation> {
+        let uri = value.get("uri").or_else(|| value.get("targetUri"))?;
+        let range = value
+            .get("targetSelectionRange")
+            .or_else(|| value.get("range"))?;
+        Some(LspLocation {
+            path: uri_to_path(uri.as_str()?)?,
+            range: Range::from_json(range)?,
+        })
+    }
+    match result {
+        Value::Array(values) => values.iter().filter_map(location).collect(),
+        Value::Object(_) => location(result).into_iter().collect(),
+        _ => vec![],
+    }
+}
+
+/// `TextEdit[]` result of formatting
+pub fn parse_text_edits(result: &Value) -> Vec<TextEdit> {
+    result
+        .as_array()
+        .map(|edits| {
+            edits
+                .iter()
+                .filter_map(|edit| {
+                    Some(TextEdit {
+                        range: Range::from_json(edit.get("range")?)?,
+                        new_text: edit.get("newText")?.as_str()?.to_owned(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn must_convert_path_and_uri() {
+        assert_eq!(path_to_uri("/tmp/a b/ż.rs"), "file:///tmp/a%20b/%C5%BC.rs");
+        assert_eq!(
+            uri_to_path("file:///tmp/a%20b/%C5%BC.rs"),
+            Some("/tmp/a b/ż.rs".to_owned())
+        );
+        assert_eq!(uri_to_path("https://example.com"), None);
+    }
+
+    #[test]
+    fn must_convert_positions_with_utf16() {
+        let buffer = "fn\n𝔸b\nc";
+        assert_eq!(position_at(buffer, 4), Position::new(1, 2));
+        assert_eq!(position_at(buffer, 7), Position::new(2, 1));
+        assert_eq!(offset_at(buffer, Position::new(1, 2)), 4);
+        assert_eq!(offset_at(buffer, Position::new(1, 40)), 5);
+        assert_eq!(offset_at(buffer, Position::new(9, 0)), 7);
+    }
+
+    #[test]
+    fn must_find_text_change() {
+        assert_eq!(text_change("abc", "abc"), None);
+        assert_eq!(
+            text_change("fn a() {}\n", "fn ab() {}\n"),
+            Some(TextChange {
+                range: Range::new(Position::new(0, 4), Position::new(0, 4)),
+                text: "b".to_owned(),
+            })
+        );
+        assert_eq!(
+            text_change("a\nbc\nd", "a\nd"),
+            Some(TextChange {
+                range: Range::new(Position::new(1, 0), Position::new(2, 0)),
+                text: "".to_owned(),
+            })
+        );
+    }
+
+    #[test]
+    fn must_parse_server_results() {
+        let hover = json!({ "contents": { "kind": "markdown", "value": "fn main()" } });
+        assert_eq!(parse_hover(&hover), Some("fn main()".to_owned()));
+        let hover = json!({ "contents": ["a", { "language": "rust", "value": "b" }] });
+        assert_eq!(parse_hover(&hover), Some("a\nb".to_owned()));
+        assert_eq!(parse_hover(&json!({ "contents": "" })), None);
+
+        let completion =
+            json!({ "items": [{ "label": "main" }, { "label": "m()", "filterText": "m" }] });
+        assert_eq!(
+            parse_completion(&completion),
+            vec!["main".to_owned(), "m".to_owned()]
+        );
+
+        let range =
+            json!({ "start": { "line": 1, "character": 2 }, "end": { "line": 1, "character": 3 } });
+        let locations = json!([
+            { "uri": "file:///a.rs", "range": range },
+            { "targetUri": "file:///b.rs", "targetRange": range, "targetSelectionRange": range }
+        ]);
+        let expected_range = Range::new(Position::new(1, 2), Position::new(1, 3));
+        assert_eq!(
+            parse_locations(&locations),
+            vec![
+                LspLocation {
+                    path: "/a.rs".to_owned(),
+                    range: expected_range,
+                },
+                LspLocation {
+                    path: "/b.rs".to_owned(),
+                    range: expected_range,
+                },
+            ]
+        );
+        assert_eq!(
+            parse_text_edits(&json!([{ "range": range, "newText": "x" }])),
+            vec![TextEdit {
+                range: expected_range,
+                new_text: "x".to_owned(),
+            }]
+        );
+    }
+
+    #[test]
+    fn must_parse_diagnostics() {
+        let params = json!({
+            "uri": "file:///a.rs",
+            "diagnostics": [{
+                "range": { "start": { "line": 0, "character": 0 }, "end": { "line": 0, "character": 1 } },
+                "severity": 2,
+                "message": "unused",
+                "source": "rustc"
+            }]
+        });
+        assert_eq!(
+            parse_diagnostics(&params),
+            Some(LspEvent::Diagnostics {
+                path: "/a.rs".to_owned(),
+                diagnostics: vec![LspDiagnostic {
+                    range: Range::new(Position::new(0, 0), Position::new(0, 1)),
+                    severity: DiagnosticSeverity::Warning,
+                    message: "unused".to_owned(),
+                    source: "rustc".to_owned(),
+                }],
+            })
+        );
+    }
+}
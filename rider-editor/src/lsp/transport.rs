@@ -0,0 +1,67 @@
+use serde_json::Value;
+use std::io::BufRead;
+
+/// JSON-RPC message with `Content-Length` header
+pub fn encode(message: &Value) -> Vec<u8> {
+    let body = message.to_string();
+    format!("Content-Length: {}\r\n\r\n{}", body.len(), body).into_bytes()
+}
+
+/// Reads next message, `None` when stream is closed
+pub fn read_message<R: BufRead>(reader: &mut R) -> Result<Option<Value>, String> {
+    let mut length: Option<usize> = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).map_err(|e| e.to_string())? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim();
+        if line.is_empty() {
+            break;
+        }
+        let mut parts = line.splitn(2, ':');
+        match (parts.next(), parts.next()) {
+            (Some(name), Some(value)) if name.eq_ignore_ascii_case("Content-Length") => {
+                length = Some(
+                    value
+                        .trim()
+                        .parse()
+                        .map_err(|_| format!("Invalid content length {}", value.trim()))?,
+                );
+            }
+            _ => (),
+        }
+    }
+    let length = length.ok_or_else(|| "Message without content length".to_string())?;
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).map_err(|e| e.to_string())?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Cursor;
+
+    #[test]
+    fn must_encode_and_read_messages() {
+        let first = json!({ "jsonrpc": "2.0", "id": 1, "result": "żółw" });
+        let second = json!({ "jsonrpc": "2.0", "method": "exit" });
+        let mut bytes = encode(&first);
+        bytes.extend(b"Content-Type: application/vscode-jsonrpc; charset=utf-8\r\n".iter());
+        bytes.extend(encode(&second));
+        let mut reader = Cursor::new(bytes);
+        assert_eq!(read_message(&mut reader), Ok(Some(first)));
+        assert_eq!(read_message(&mut reader), Ok(Some(second)));
+        assert_eq!(read_message(&mut reader), Ok(None));
+    }
+
+    #[test]
+    fn must_fail_for_message_without_length() {
+        let mut reader = Cursor::new(b"Content-Type: a\r\n\r\n{}".to_vec());
+        assert_eq!(read_message(&mut reader).is_err(), true);
+    }
+}
@@ -9,6 +9,7 @@ extern crate dirs;
 #[macro_use]
 extern crate log;
 extern crate lazy_static;
+extern crate libc;
 extern crate rand;
 extern crate rider_config;
 extern crate rider_derive;
@@ -26,6 +27,7 @@ use simplelog::*;
 use std::fs::File;
 
 pub mod app;
+pub mod lsp;
 pub mod renderer;
 #[macro_use]
 pub mod tests;
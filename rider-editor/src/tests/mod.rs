@@ -7,6 +7,7 @@ use crate::ui::CanvasAccess;
 use rider_config::Config;
 use rider_config::ConfigAccess;
 use rider_config::ConfigHolder;
+use rider_config::LanguageServer;
 use sdl2::pixels::{Color, PixelFormatEnum};
 use sdl2::rect::Point;
 use sdl2::rect::Rect;
@@ -16,7 +17,7 @@ use std::collections::HashMap;
 use std::fmt::Debug;
 use std::fmt::Error;
 use std::fmt::Formatter;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::sync::*;
 
@@ -35,9 +36,99 @@ pub fn build_path(path: String) {
 pub fn build_config() -> Arc<RwLock<Config>> {
     let mut config = Config::new();
     config.set_theme(config.editor_config().current_theme().clone());
+    // installed language servers and formatters must not be started by tests
+    config.language_servers_mut().clear();
+    config.formatters_mut().clear();
     Arc::new(RwLock::new(config))
 }
 
+/// Builds `rider-lsp-stub` once and returns server which follows given script.
+///
+/// Stub is built with profile and target of running tests, next to their binaries.
+#[cfg_attr(tarpaulin, skip)]
+pub fn lsp_stub_server(name: &str, script: &str) -> LanguageServer {
+    static BUILD: Once = Once::new();
+    let profile_dir = test_profile_dir();
+    BUILD.call_once(|| build_lsp_stub(&profile_dir));
+    let script_dir = std::env::temp_dir()
+        .join(format!("rider-lsp-stub-{}", std::process::id()))
+        .join(name);
+    std::fs::create_dir_all(&script_dir).unwrap();
+    let script_path = script_dir.join("script.json");
+    std::fs::write(&script_path, script).unwrap();
+    LanguageServer {
+        command: profile_dir
+            .join("rider-lsp-stub")
+            .to_str()
+            .unwrap()
+            .to_owned(),
+        args: vec![script_path.to_str().unwrap().to_owned()],
+        language_id: "rust".to_owned(),
+    }
+}
+
+/// Directory of running test binary, which lives in `<target>/[<triple>/]<profile>/deps`
+#[cfg_attr(tarpaulin, skip)]
+fn test_profile_dir() -> PathBuf {
+    let exe = std::env::current_exe().unwrap();
+    exe.ancestors().nth(2).unwrap().to_path_buf()
+}
+
+/// Target directory holds `CACHEDIR.TAG`, directory between it and profile one names target
+#[cfg_attr(tarpaulin, skip)]
+fn build_lsp_stub(profile_dir: &Path) {
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_owned());
+    let mut command = std::process::Command::new(cargo);
+    command
+        .args(&["build", "-q", "-p", "rider-lsp-stub"])
+        .current_dir(env!("CARGO_MANIFEST_DIR"));
+    let profile = profile_dir.file_name().unwrap();
+    if profile != "debug" {
+        command.arg("--profile").arg(profile);
+    }
+    let parent = profile_dir.parent().unwrap();
+    if parent.join("CACHEDIR.TAG").exists() {
+        command.arg("--target-dir").arg(parent);
+    } else {
+        command
+            .arg("--target-dir")
+            .arg(parent.parent().unwrap())
+            .arg("--target")
+            .arg(parent.file_name().unwrap());
+    }
+    assert_eq!(command.status().unwrap().success(), true);
+}
+
+/// Shell script standing in for external tool such as cargo, given script body receives its arguments
+#[cfg_attr(tarpaulin, skip)]
+pub fn fake_program(name: &str, script: &str) -> String {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = format!("/tmp/rider/fake-programs/{}.sh", name);
+    std::fs::create_dir_all("/tmp/rider/fake-programs").unwrap();
+    std::fs::write(&path, format!("#!/bin/sh\n{}", script)).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+/// Git repository with `a.txt` committed and `*.log` files ignored, previous one is removed
+#[cfg_attr(tarpaulin, skip)]
+pub fn git_repository(name: &str) -> String {
+    use crate::app::git::run_git;
+
+    let dir = format!("/tmp/rider/git/{}", name);
+    std::fs::remove_dir_all(&dir).ok();
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(format!("{}/.gitignore", dir), "*.log\n").unwrap();
+    std::fs::write(format!("{}/a.txt", dir), "first\nsecond\n").unwrap();
+    run_git(&dir, &["init", "-q"]).unwrap();
+    run_git(&dir, &["config", "user.name", "rider"]).unwrap();
+    run_git(&dir, &["config", "user.email", "rider@example.com"]).unwrap();
+    run_git(&dir, &["add", "."]).unwrap();
+    run_git(&dir, &["commit", "-q", "-m", "init"]).unwrap();
+    dir
+}
+
 #[cfg_attr(tarpaulin, skip)]
 #[derive(Debug, PartialEq)]
 pub enum CanvasShape {
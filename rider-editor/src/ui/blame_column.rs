@@ -0,0 +1,262 @@
+use crate::app::git::{relative_date, Blame, BlameCommit};
+use crate::renderer::renderer::Renderer;
+use crate::ui::*;
+use rider_config::ConfigAccess;
+use rider_config::ConfigHolder;
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const COLUMN_CHARACTERS: u32 = 40;
+const AUTHOR_CHARACTERS: usize = 14;
+const PADDING: i32 = 4;
+
+/// Commit, author and age of every line of current file, shown left of editor
+pub struct BlameColumn {
+    path: Option<String>,
+    blame: Blame,
+    labels: HashMap<String, Label>,
+    visible: bool,
+    width: u32,
+    dest: Rect,
+    border_color: Color,
+    config: ConfigAccess,
+}
+
+impl ConfigHolder for BlameColumn {
+    fn config(&self) -> &ConfigAccess {
+        &self.config
+    }
+}
+
+impl BlameColumn {
+    pub fn new(config: ConfigAccess) -> Self {
+        let border_color = config.read().unwrap().theme().border_color().into();
+        Self {
+            path: None,
+            blame: Blame::default(),
+            labels: HashMap::new(),
+            visible: false,
+            width: 0,
+            dest: Rect::new(0, 0, 1, 1),
+            border_color,
+            config,
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn show(&mut self) {
+        self.visible = true;
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+    }
+
+    /// File which lines are annotated
+    pub fn path(&self) -> Option<&String> {
+        self.path.as_ref()
+    }
+
+    pub fn blame(&self) -> &Blame {
+        &self.blame
+    }
+
+    /// Annotates lines of file, `now` is in seconds since epoch
+    pub fn set_blame<R>(&mut self, path: String, blame: Blame, now: i64, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let mut labels = HashMap::new();
+        for commit in blame.commits() {
+            let mut label = Label::new(annotation(commit, now), Arc::clone(&self.config));
+            label.prepare_ui(renderer);
+            labels.insert(commit.id.clone(), label);
+        }
+        self.width = renderer.load_character_size('W').width() * COLUMN_CHARACTERS;
+        self.path = Some(path);
+        self.blame = blame;
+        self.labels = labels;
+    }
+
+    /// Removes annotations, column stays visible and empty
+    pub fn clear(&mut self, path: String) {
+        self.path = Some(path);
+        self.blame = Blame::default();
+        self.labels.clear();
+    }
+
+    /// Space taken left of editor, hidden column takes none
+    pub fn width(&self) -> u32 {
+        if self.visible {
+            self.width
+        } else {
+            0
+        }
+    }
+
+    pub fn dest(&self) -> &Rect {
+        &self.dest
+    }
+
+    pub fn set_dest(&mut self, rect: &Rect) {
+        self.dest = rect.clone();
+    }
+
+    /// Line of file at window point, `top` is y of first line of file
+    pub fn line_at_point(&self, file: &EditorFile, top: i32, point: &Point) -> Option<usize> {
+        if !self.visible || !self.dest.contains_point(*point) {
+            return None;
+        }
+        let line_height = file.line_height() as i32;
+        file.line_tops(top).iter().position(|line_top| {
+            line_top.map_or(false, |y| point.y() >= y && point.y() < y + line_height)
+        })
+    }
+
+    pub fn commit_at_point(
+        &self,
+        file: &EditorFile,
+        top: i32,
+        point: &Point,
+    ) -> Option<&BlameCommit> {
+        self.line_at_point(file, top, point)
+            .and_then(|line| self.blame.commit_at(line))
+    }
+
+    /// Draws annotation of every visible line of file, `top` is y of its first line
+    pub fn render<C, R>(&self, canvas: &mut C, renderer: &mut R, file: &EditorFile, top: i32)
+    where
+        C: CanvasAccess,
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if !self.visible {
+            return;
+        }
+        let clipping = canvas.clip_rect();
+        canvas.set_clipping(self.dest.clone());
+        let line_height = file.line_height() as i32;
+        for (line, line_top) in file.line_tops(top).iter().enumerate() {
+            let y = match line_top {
+                Some(y) if *y + line_height >= self.dest.y() && *y <= self.dest.bottom() => *y,
+                _ => continue,
+            };
+            let label = self
+                .blame
+                .commit_at(line)
+                .and_then(|commit| self.labels.get(&commit.id));
+            if let Some(label) = label {
+                // label draws its text NAME_MARGIN right of position it gets
+                let position = Point::new(self.dest.x() + PADDING - NAME_MARGIN, y);
+                label.render(canvas, renderer, &RenderContext::ParentPosition(position));
+            }
+        }
+        let x = self.dest.right() - 1;
+        canvas
+            .render_line(
+                Point::new(x, self.dest.y()),
+                Point::new(x, self.dest.bottom()),
+                self.border_color,
+            )
+            .unwrap_or_else(|_| panic!("Failed to render blame column border!"));
+        canvas.set_clip_rect(clipping);
+    }
+}
+
+/// Text shown next to line of commit
+fn annotation(commit: &BlameCommit, now: i64) -> String {
+    if !commit.is_committed() {
+        return "Not committed yet".to_owned();
+    }
+    let author: String = commit.author.chars().take(AUTHOR_CHARACTERS).collect();
+    format!(
+        "{} {:<width$} {}",
+        commit.short_id(),
+        author,
+        relative_date(commit.time, now),
+        width = AUTHOR_CHARACTERS
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use rider_derive::*;
+
+    fn blame() -> Blame {
+        let output = "1111111111111111111111111111111111111111 1 1 1\n\
+                      author Ann Marie Christina Long\nauthor-time 0\nsummary Init\n\ta\n\
+                      0000000000000000000000000000000000000000 2 2 1\n\
+                      author Not Committed Yet\nauthor-time 500\nsummary Version of a.txt\n\tb\n";
+        Blame::parse(output)
+    }
+
+    #[test]
+    fn assert_annotate_lines() {
+        let blame = blame();
+        assert_eq!(
+            annotation(blame.commit_at(0).unwrap(), 2 * 86400),
+            "1111111 Ann Marie Chri 2 days ago".to_owned()
+        );
+        assert_eq!(
+            annotation(blame.commit_at(1).unwrap(), 600),
+            "Not committed yet".to_owned()
+        );
+    }
+
+    #[test]
+    fn assert_find_commit_at_point() {
+        build_test_renderer!(renderer);
+        let mut file = EditorFile::new("/a.txt".to_owned(), "a\nb".to_owned(), config.clone());
+        file.prepare_ui(&mut renderer);
+        let mut widget = BlameColumn::new(config);
+        widget.set_blame("/a.txt".to_owned(), blame(), 0, &mut renderer);
+        assert_eq!(widget.path(), Some(&"/a.txt".to_owned()));
+        assert_eq!(widget.width(), 0);
+        widget.show();
+        assert_eq!(widget.width(), 13 * COLUMN_CHARACTERS);
+        widget.set_dest(&Rect::new(10, 20, widget.width(), 200));
+
+        let point = Point::new(15, 20 + 14 + 1);
+        assert_eq!(widget.line_at_point(&file, 20, &point), Some(1));
+        assert_eq!(
+            widget
+                .commit_at_point(&file, 20, &point)
+                .map(|c| c.is_committed()),
+            Some(false)
+        );
+        assert_eq!(widget.line_at_point(&file, 20, &Point::new(5, 25)), None);
+        widget.clear("/b.txt".to_owned());
+        assert_eq!(widget.commit_at_point(&file, 20, &point), None);
+    }
+
+    #[test]
+    fn assert_render() {
+        build_test_renderer!(renderer);
+        let mut file = EditorFile::new("/a.txt".to_owned(), "a\nb".to_owned(), config.clone());
+        file.prepare_ui(&mut renderer);
+        let border: Color = config.read().unwrap().theme().border_color().into();
+        let mut canvas = CanvasMock::new();
+        let mut widget = BlameColumn::new(config);
+        widget.set_blame("/a.txt".to_owned(), blame(), 0, &mut renderer);
+        widget.render(&mut canvas, &mut renderer, &file, 0);
+        assert_eq!(canvas.lines.len(), 0);
+
+        widget.show();
+        widget.set_dest(&Rect::new(10, 20, 100, 200));
+        widget.render(&mut canvas, &mut renderer, &file, 20);
+        assert_eq!(
+            canvas.lines,
+            vec![RendererRect::new(
+                Rect::new(109, 20, 109, 220),
+                border,
+                CanvasShape::Line
+            )]
+        );
+    }
+}
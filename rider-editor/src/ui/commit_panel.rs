@@ -0,0 +1,406 @@
+use crate::app::git::{GitStatuses, StatusEntry};
+use crate::app::UpdateResult as UR;
+use crate::renderer::renderer::Renderer;
+use crate::ui::*;
+use crate::ui::{RenderContext as RC, UpdateContext as UC};
+use rider_config::ConfigAccess;
+use rider_config::ConfigHolder;
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use std::sync::Arc;
+
+const PADDING: i32 = 4;
+const PANEL_HEIGHT: u32 = 240;
+const MESSAGE_PREFIX: &str = "Message: ";
+
+/// Changed files of repository at bottom of window with message of next commit.
+///
+/// Clicking file stages it, clicking staged one takes it back from index.
+/// Focused panel receives typed text as commit message.
+pub struct CommitPanel {
+    root: String,
+    entries: Vec<StatusEntry>,
+    message: String,
+    labels: Vec<Label>,
+    message_rows: usize,
+    row_height: u32,
+    visible: bool,
+    focused: bool,
+    dest: Rect,
+    background_color: Color,
+    border_color: Color,
+    config: ConfigAccess,
+}
+
+impl ConfigHolder for CommitPanel {
+    fn config(&self) -> &ConfigAccess {
+        &self.config
+    }
+}
+
+impl CommitPanel {
+    pub fn new(config: ConfigAccess) -> Self {
+        let (background_color, border_color, width, height) = {
+            let c = config.read().unwrap();
+            let theme = c.theme();
+            (
+                theme.background().into(),
+                theme.border_color().into(),
+                c.width(),
+                c.height(),
+            )
+        };
+        Self {
+            root: String::new(),
+            entries: vec![],
+            message: String::new(),
+            labels: vec![],
+            message_rows: 0,
+            row_height: 0,
+            visible: false,
+            focused: false,
+            dest: panel_rect(width, height),
+            background_color,
+            border_color,
+            config,
+        }
+    }
+
+    /// Replaces listed files with changes of repository
+    pub fn set_statuses<R>(&mut self, statuses: &GitStatuses, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        self.root = statuses.root().clone();
+        self.entries = statuses.changes();
+        self.prepare_ui(renderer);
+    }
+
+    pub fn entries(&self) -> &Vec<StatusEntry> {
+        &self.entries
+    }
+
+    /// Listed file with given absolute path
+    pub fn entry(&self, path: &str) -> Option<&StatusEntry> {
+        self.entries
+            .iter()
+            .find(|entry| self.absolute_path(entry) == path)
+    }
+
+    fn absolute_path(&self, entry: &StatusEntry) -> String {
+        format!("{}/{}", self.root, entry.path.trim_end_matches('/'))
+    }
+
+    pub fn message(&self) -> &String {
+        &self.message
+    }
+
+    pub fn push_message<R>(&mut self, text: &str, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        self.message.push_str(text);
+        self.prepare_ui(renderer);
+    }
+
+    pub fn pop_message<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        self.message.pop();
+        self.prepare_ui(renderer);
+    }
+
+    pub fn clear_message<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        self.message.clear();
+        self.prepare_ui(renderer);
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.visible && self.focused
+    }
+
+    pub fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    pub fn show(&mut self) {
+        self.visible = true;
+        self.focused = true;
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.focused = false;
+    }
+
+    fn entry_text(entry: &StatusEntry) -> String {
+        let staged = match (entry.is_staged(), entry.has_unstaged()) {
+            (true, false) => "[x]",
+            (true, true) => "[~]",
+            _ => "[ ]",
+        };
+        format!("{} {} {}", staged, entry.status().badge(), entry.path)
+    }
+
+    fn visible_rows(&self) -> usize {
+        match self.row_height {
+            0 => 0,
+            row_height => {
+                let content = self.dest.height().saturating_sub((2 * PADDING) as u32);
+                (content / row_height) as usize
+            }
+        }
+    }
+}
+
+impl Widget for CommitPanel {
+    fn texture_path(&self) -> Option<String> {
+        None
+    }
+
+    fn dest(&self) -> &Rect {
+        &self.dest
+    }
+
+    fn set_dest(&mut self, rect: &Rect) {
+        self.dest = rect.clone();
+    }
+
+    fn source(&self) -> &Rect {
+        self.dest()
+    }
+
+    fn set_source(&mut self, rect: &Rect) {
+        self.set_dest(rect)
+    }
+
+    fn update(&mut self, _ticks: i32, _context: &UC) -> UR {
+        let (width, height) = {
+            let c = self.config.read().unwrap();
+            (c.width(), c.height())
+        };
+        self.dest = panel_rect(width, height);
+        UR::NoOp
+    }
+
+    fn on_left_click(&mut self, point: &Point, context: &UC) -> UR {
+        self.focused = true;
+        let dest = match context {
+            UC::ParentPosition(p) => move_render_point(*p, &self.dest),
+            _ => self.dest,
+        };
+        let y = point.y() - (dest.y() + PADDING);
+        if y < 0 || self.row_height == 0 {
+            return UR::NoOp;
+        }
+        let row = y as usize / self.row_height as usize;
+        // title and message are above files
+        let first_entry_row = 1 + self.message_rows;
+        if row < first_entry_row || row >= self.labels.len() {
+            return UR::NoOp;
+        }
+        match self.entries.get(row - first_entry_row) {
+            Some(entry) => UR::ToggleStaged(self.absolute_path(entry)),
+            _ => UR::NoOp,
+        }
+    }
+
+    fn is_left_click_target(&self, point: &Point, context: &UC) -> bool {
+        if !self.is_visible() {
+            return false;
+        }
+        match context {
+            UC::ParentPosition(p) => move_render_point(*p, &self.dest),
+            _ => self.dest,
+        }
+        .contains_point(*point)
+    }
+
+    fn render<C, R>(&self, canvas: &mut C, renderer: &mut R, context: &RenderContext)
+    where
+        C: CanvasAccess,
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if !self.is_visible() {
+            return;
+        }
+        let dest = match context {
+            RC::ParentPosition(p) => move_render_point(*p, &self.dest),
+            _ => self.dest,
+        };
+        canvas.set_clip_rect(None);
+        canvas
+            .render_rect(dest, self.background_color)
+            .unwrap_or_else(|_| panic!("Failed to render commit panel background!"));
+        canvas
+            .render_border(dest, self.border_color)
+            .unwrap_or_else(|_| panic!("Failed to render commit panel border!"));
+
+        let rows_start = dest.top_left() + Point::new(PADDING, PADDING);
+        for label in self.labels.iter() {
+            label.render(canvas, renderer, &RC::ParentPosition(rows_start));
+        }
+    }
+
+    fn prepare_ui<'l, T>(&mut self, renderer: &mut T)
+    where
+        T: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        self.row_height = renderer.load_character_size('W').height();
+        let title = match self.entries.len() {
+            0 => "No changes".to_owned(),
+            count => format!(
+                "{} changed files, click to stage or unstage, CTRL+Enter commits",
+                count
+            ),
+        };
+        let mut rows = vec![title];
+        let mut message_rows = 0;
+        for (index, line) in self.message.split('\n').enumerate() {
+            let prefix = if index == 0 { MESSAGE_PREFIX } else { "" };
+            rows.push(format!("{}{}", prefix, line));
+            message_rows += 1;
+        }
+        self.message_rows = message_rows;
+        rows.extend(self.entries.iter().map(CommitPanel::entry_text));
+        rows.truncate(self.visible_rows());
+
+        let mut labels = vec![];
+        for (index, text) in rows.into_iter().enumerate() {
+            // empty label has no size
+            let text = if text.is_empty() {
+                " ".to_owned()
+            } else {
+                text
+            };
+            let mut label = Label::new(text, Arc::clone(&self.config));
+            label.prepare_ui(renderer);
+            let mut dest = label.dest().clone();
+            dest.set_y(index as i32 * self.row_height as i32);
+            label.set_dest(&dest);
+            labels.push(label);
+        }
+        self.labels = labels;
+    }
+}
+
+fn panel_rect(width: u32, height: u32) -> Rect {
+    let panel_height = PANEL_HEIGHT.min(height / 2).max(1);
+    Rect::new(
+        0,
+        (height - panel_height) as i32,
+        width.max(1),
+        panel_height,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use rider_derive::*;
+
+    fn statuses() -> GitStatuses {
+        GitStatuses::new(
+            "/repo".to_owned(),
+            vec![
+                StatusEntry {
+                    path: "a.rs".to_owned(),
+                    index: 'M',
+                    worktree: ' ',
+                },
+                StatusEntry {
+                    path: "tmp/".to_owned(),
+                    index: '?',
+                    worktree: '?',
+                },
+                StatusEntry {
+                    path: "out.log".to_owned(),
+                    index: '!',
+                    worktree: '!',
+                },
+            ],
+        )
+    }
+
+    #[test]
+    fn assert_list_changes_and_message() {
+        build_test_renderer!(renderer);
+        let mut widget = CommitPanel::new(config);
+        assert_eq!(widget.is_visible(), false);
+        widget.show();
+        assert_eq!(widget.is_focused(), true);
+        widget.set_statuses(&statuses(), &mut renderer);
+        assert_eq!(widget.entries().len(), 2);
+        assert_eq!(
+            widget.entry("/repo/tmp").map(|e| e.path.clone()),
+            Some("tmp/".to_owned())
+        );
+        widget.push_message("Fix", &mut renderer);
+        widget.push_message("\n", &mut renderer);
+        widget.push_message("body", &mut renderer);
+        widget.pop_message(&mut renderer);
+        assert_eq!(widget.message(), &"Fix\nbod".to_owned());
+        let names: Vec<String> = widget.labels.iter().map(|l| l.name()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "2 changed files, click to stage or unstage, CTRL+Enter commits".to_owned(),
+                "Message: Fix".to_owned(),
+                "bod".to_owned(),
+                "[x] M a.rs".to_owned(),
+                "[ ] U tmp/".to_owned(),
+            ]
+        );
+        widget.clear_message(&mut renderer);
+        assert_eq!(widget.message(), &String::new());
+        widget.hide();
+        assert_eq!(widget.is_focused(), false);
+    }
+
+    #[test]
+    fn assert_on_left_click_toggles_staged() {
+        build_test_renderer!(renderer);
+        let mut widget = CommitPanel::new(config);
+        widget.show();
+        widget.set_statuses(&statuses(), &mut renderer);
+        let row_y = |row: i32| widget.dest().y() + PADDING + row * widget.row_height as i32 + 1;
+        let message = Point::new(10, row_y(1));
+        let second = Point::new(10, row_y(3));
+        assert_eq!(
+            widget.is_left_click_target(&second, &UpdateContext::Nothing),
+            true
+        );
+        widget.set_focused(false);
+        assert_eq!(
+            widget.on_left_click(&message, &UpdateContext::Nothing),
+            UR::NoOp
+        );
+        assert_eq!(widget.is_focused(), true);
+        assert_eq!(
+            widget.on_left_click(&second, &UpdateContext::Nothing),
+            UR::ToggleStaged("/repo/tmp".to_owned())
+        );
+    }
+
+    #[test]
+    fn assert_render() {
+        build_test_renderer!(renderer);
+        let mut canvas = CanvasMock::new();
+        let mut widget = CommitPanel::new(config);
+        widget.render(&mut canvas, &mut renderer, &RenderContext::Nothing);
+        assert_eq!(canvas.rects.len(), 0);
+        widget.show();
+        widget.set_statuses(&statuses(), &mut renderer);
+        widget.render(&mut canvas, &mut renderer, &RenderContext::Nothing);
+        assert_eq!(canvas.borders.len(), 1);
+    }
+}
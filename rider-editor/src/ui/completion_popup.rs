@@ -0,0 +1,300 @@
+use crate::app::completion::CompletionItem;
+use crate::app::UpdateResult as UR;
+use crate::renderer::renderer::Renderer;
+use crate::ui::*;
+use crate::ui::{RenderContext as RC, UpdateContext as UC};
+use rider_config::ConfigAccess;
+use rider_config::ConfigHolder;
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use std::sync::Arc;
+
+const PADDING: i32 = 4;
+
+/// List of words and snippets shown below caret, hidden when it has no items
+pub struct CompletionPopup {
+    prefix: String,
+    items: Vec<CompletionItem>,
+    selected: usize,
+    labels: Vec<Label>,
+    row_height: u32,
+    dest: Rect,
+    background_color: Color,
+    border_color: Color,
+    selected_color: Color,
+    config: ConfigAccess,
+}
+
+impl ConfigHolder for CompletionPopup {
+    fn config(&self) -> &ConfigAccess {
+        &self.config
+    }
+}
+
+impl CompletionPopup {
+    pub fn new(config: ConfigAccess) -> Self {
+        let (background_color, border_color, selected_color) = {
+            let c = config.read().unwrap();
+            let theme = c.theme();
+            (
+                theme.background().into(),
+                theme.border_color().into(),
+                theme.caret().bright().color().into(),
+            )
+        };
+        Self {
+            prefix: String::new(),
+            items: vec![],
+            selected: 0,
+            labels: vec![],
+            row_height: 0,
+            dest: Rect::new(0, 0, 1, 1),
+            background_color,
+            border_color,
+            selected_color,
+            config,
+        }
+    }
+
+    /// Shows items for typed prefix with top left corner at given point
+    pub fn show<R>(
+        &mut self,
+        prefix: String,
+        items: Vec<CompletionItem>,
+        point: Point,
+        renderer: &mut R,
+    ) where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        self.prefix = prefix;
+        self.items = items;
+        self.selected = 0;
+        self.dest.set_x(point.x());
+        self.dest.set_y(point.y());
+        self.prepare_ui(renderer);
+    }
+
+    pub fn hide(&mut self) {
+        self.prefix.clear();
+        self.items.clear();
+        self.labels.clear();
+        self.selected = 0;
+    }
+
+    pub fn is_visible(&self) -> bool {
+        !self.items.is_empty()
+    }
+
+    pub fn prefix(&self) -> &String {
+        &self.prefix
+    }
+
+    pub fn items(&self) -> &Vec<CompletionItem> {
+        &self.items
+    }
+
+    pub fn selected_item(&self) -> Option<&CompletionItem> {
+        self.items.get(self.selected)
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.items.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        if self.selected > 0 {
+            self.selected -= 1;
+        }
+    }
+}
+
+impl Widget for CompletionPopup {
+    fn texture_path(&self) -> Option<String> {
+        None
+    }
+
+    fn dest(&self) -> &Rect {
+        &self.dest
+    }
+
+    fn set_dest(&mut self, rect: &Rect) {
+        self.dest = rect.clone();
+    }
+
+    fn source(&self) -> &Rect {
+        self.dest()
+    }
+
+    fn set_source(&mut self, rect: &Rect) {
+        self.set_dest(rect)
+    }
+
+    fn on_left_click(&mut self, point: &Point, context: &UC) -> UR {
+        let dest = match context {
+            UC::ParentPosition(p) => move_render_point(*p, &self.dest),
+            _ => self.dest,
+        };
+        let y = point.y() - (dest.y() + PADDING);
+        if y < 0 || self.row_height == 0 {
+            return UR::NoOp;
+        }
+        let row = y as usize / self.row_height as usize;
+        if row >= self.items.len() {
+            return UR::NoOp;
+        }
+        self.selected = row;
+        UR::AcceptCompletion
+    }
+
+    fn is_left_click_target(&self, point: &Point, context: &UC) -> bool {
+        if !self.is_visible() {
+            return false;
+        }
+        match context {
+            UC::ParentPosition(p) => move_render_point(*p, &self.dest),
+            _ => self.dest,
+        }
+        .contains_point(*point)
+    }
+
+    fn render<C, R>(&self, canvas: &mut C, renderer: &mut R, context: &RenderContext)
+    where
+        C: CanvasAccess,
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if !self.is_visible() {
+            return;
+        }
+        let dest = match context {
+            RC::ParentPosition(p) => move_render_point(*p, &self.dest),
+            _ => self.dest,
+        };
+        canvas.set_clip_rect(None);
+        canvas
+            .render_rect(dest, self.background_color)
+            .unwrap_or_else(|_| panic!("Failed to render completion popup background!"));
+        canvas
+            .render_border(dest, self.border_color)
+            .unwrap_or_else(|_| panic!("Failed to render completion popup border!"));
+
+        let rows_start = dest.top_left() + Point::new(PADDING, PADDING);
+        for label in self.labels.iter() {
+            label.render(canvas, renderer, &RC::ParentPosition(rows_start));
+        }
+        let row = Rect::new(
+            rows_start.x(),
+            rows_start.y() + self.selected as i32 * self.row_height as i32,
+            dest.width() - (2 * PADDING) as u32,
+            self.row_height,
+        );
+        canvas
+            .render_border(row, self.selected_color)
+            .unwrap_or_else(|_| panic!("Failed to render completion popup selected item!"));
+    }
+
+    fn prepare_ui<'l, T>(&mut self, renderer: &mut T)
+    where
+        T: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let mut labels = vec![];
+        let mut width: u32 = 0;
+        for (index, item) in self.items.iter().enumerate() {
+            let mut label = Label::new(item.label(), Arc::clone(&self.config));
+            label.prepare_ui(renderer);
+            self.row_height = label.dest().height();
+            let mut dest = label.dest().clone();
+            dest.set_y(index as i32 * self.row_height as i32);
+            label.set_dest(&dest);
+            width = width.max(dest.width());
+            labels.push(label);
+        }
+        self.labels = labels;
+        self.dest.set_width(width + (2 * PADDING) as u32);
+        self.dest
+            .set_height(self.labels.len() as u32 * self.row_height + (2 * PADDING) as u32);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use rider_derive::*;
+
+    fn items() -> Vec<CompletionItem> {
+        vec!["main", "max", "format"]
+            .into_iter()
+            .map(|word| CompletionItem::Word(word.to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn assert_show_and_hide() {
+        build_test_renderer!(renderer);
+        let mut widget = CompletionPopup::new(config);
+        assert_eq!(widget.is_visible(), false);
+        widget.show("ma".to_owned(), items(), Point::new(10, 20), &mut renderer);
+        assert_eq!(widget.is_visible(), true);
+        assert_eq!(widget.prefix(), &"ma".to_owned());
+        assert_eq!(widget.dest().top_left(), Point::new(10, 20));
+        assert_eq!(
+            widget.dest().height(),
+            3 * widget.row_height + (2 * PADDING) as u32
+        );
+        widget.hide();
+        assert_eq!(widget.is_visible(), false);
+        assert_eq!(widget.selected_item(), None);
+    }
+
+    #[test]
+    fn assert_select_item() {
+        build_test_renderer!(renderer);
+        let mut widget = CompletionPopup::new(config);
+        widget.show("ma".to_owned(), items(), Point::new(0, 0), &mut renderer);
+        widget.select_previous();
+        assert_eq!(
+            widget.selected_item().map(|item| item.label()),
+            Some("main".to_owned())
+        );
+        widget.select_next();
+        widget.select_next();
+        widget.select_next();
+        assert_eq!(
+            widget.selected_item().map(|item| item.label()),
+            Some("format".to_owned())
+        );
+    }
+
+    #[test]
+    fn assert_on_left_click_accepts_item() {
+        build_test_renderer!(renderer);
+        let mut widget = CompletionPopup::new(config);
+        widget.show("ma".to_owned(), items(), Point::new(0, 0), &mut renderer);
+        let row_height = widget.row_height as i32;
+        let point = Point::new(PADDING, PADDING + row_height + row_height / 2);
+        assert_eq!(
+            widget.is_left_click_target(&point, &UpdateContext::Nothing),
+            true
+        );
+        assert_eq!(
+            widget.on_left_click(&point, &UpdateContext::Nothing),
+            UR::AcceptCompletion
+        );
+        assert_eq!(
+            widget.selected_item().map(|item| item.label()),
+            Some("max".to_owned())
+        );
+    }
+
+    #[test]
+    fn assert_render() {
+        build_test_renderer!(renderer);
+        let mut canvas = CanvasMock::new();
+        let mut widget = CompletionPopup::new(config);
+        widget.render(&mut canvas, &mut renderer, &RenderContext::Nothing);
+        widget.show("ma".to_owned(), items(), Point::new(0, 0), &mut renderer);
+        widget.render(&mut canvas, &mut renderer, &RenderContext::Nothing);
+    }
+}
@@ -0,0 +1,662 @@
+use crate::app::git::*;
+use crate::app::UpdateResult as UR;
+use crate::renderer::renderer::Renderer;
+use crate::ui::*;
+use crate::ui::{RenderContext as RC, UpdateContext as UC};
+use rider_config::ConfigAccess;
+use rider_config::ConfigHolder;
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use std::sync::Arc;
+
+const PADDING: i32 = 4;
+const GUTTER_WIDTH: u32 = 40;
+const SCROLL_LINES: isize = 3;
+
+/// Origin of text shown on one side of diff view
+#[derive(Clone, Debug, PartialEq)]
+pub enum DiffSource {
+    Buffer(String),
+    File(String),
+    Disk(String),
+    Head(String),
+}
+
+impl DiffSource {
+    pub fn path(&self) -> &String {
+        match self {
+            DiffSource::Buffer(path)
+            | DiffSource::File(path)
+            | DiffSource::Disk(path)
+            | DiffSource::Head(path) => path,
+        }
+    }
+
+    pub fn title(&self) -> String {
+        match self {
+            DiffSource::Buffer(path) => format!("{} (buffer)", path),
+            DiffSource::File(path) => path.clone(),
+            DiffSource::Disk(path) => format!("{} (disk)", path),
+            DiffSource::Head(path) => format!("{} (HEAD)", path),
+        }
+    }
+
+    /// Changes can be copied only into opened buffer or other file
+    pub fn is_editable(&self) -> bool {
+        match self {
+            DiffSource::Buffer(_) | DiffSource::File(_) => true,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DiffSide {
+    Left,
+    Right,
+}
+
+/// Lines shown next to each other, side without line is filler of shorter side of hunk
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiffRow {
+    pub left: Option<usize>,
+    pub right: Option<usize>,
+    pub hunk: Option<usize>,
+}
+
+/// Rows of both texts aligned by their hunks
+pub fn diff_rows(left_lines: usize, hunks: &[Hunk]) -> Vec<DiffRow> {
+    let mut rows = vec![];
+    let (mut left, mut right) = (0, 0);
+    let push_equal = |rows: &mut Vec<DiffRow>, left: &mut usize, right: &mut usize| {
+        rows.push(DiffRow {
+            left: Some(*left),
+            right: Some(*right),
+            hunk: None,
+        });
+        *left += 1;
+        *right += 1;
+    };
+    for (index, hunk) in hunks.iter().enumerate() {
+        while left < hunk.old_start {
+            push_equal(&mut rows, &mut left, &mut right);
+        }
+        for line in 0..hunk.old_lines.max(hunk.new_lines) {
+            rows.push(DiffRow {
+                left: Some(left + line).filter(|_| line < hunk.old_lines),
+                right: Some(right + line).filter(|_| line < hunk.new_lines),
+                hunk: Some(index),
+            });
+        }
+        left += hunk.old_lines;
+        right += hunk.new_lines;
+    }
+    while left < left_lines {
+        push_equal(&mut rows, &mut left, &mut right);
+    }
+    rows
+}
+
+/// Two texts side by side with their changes highlighted, both sides scroll together.
+///
+/// Arrows between sides copy change to the side they point at.
+pub struct DiffView {
+    left_source: DiffSource,
+    left_text: String,
+    right_source: DiffSource,
+    right_text: String,
+    hunks: Vec<Hunk>,
+    rows: Vec<DiffRow>,
+    first_visible: usize,
+    titles: Vec<Label>,
+    left_labels: Vec<Option<Label>>,
+    right_labels: Vec<Option<Label>>,
+    arrows: Vec<Label>,
+    char_size: Rect,
+    dest: Rect,
+    background_color: Color,
+    border_color: Color,
+    config: ConfigAccess,
+}
+
+impl ConfigHolder for DiffView {
+    fn config(&self) -> &ConfigAccess {
+        &self.config
+    }
+}
+
+impl DiffView {
+    pub fn new(
+        left: (DiffSource, String),
+        right: (DiffSource, String),
+        config: ConfigAccess,
+    ) -> Self {
+        let (background_color, border_color, dest) = {
+            let c = config.read().unwrap();
+            let theme = c.theme();
+            (
+                theme.background().into(),
+                theme.border_color().into(),
+                view_rect(c.width(), c.height(), c.menu_height()),
+            )
+        };
+        let mut view = Self {
+            left_source: left.0,
+            left_text: left.1,
+            right_source: right.0,
+            right_text: right.1,
+            hunks: vec![],
+            rows: vec![],
+            first_visible: 0,
+            titles: vec![],
+            left_labels: vec![],
+            right_labels: vec![],
+            arrows: vec![],
+            char_size: Rect::new(0, 0, 0, 0),
+            dest,
+            background_color,
+            border_color,
+            config,
+        };
+        view.compare();
+        view
+    }
+
+    pub fn side_source(&self, side: DiffSide) -> &DiffSource {
+        match side {
+            DiffSide::Left => &self.left_source,
+            DiffSide::Right => &self.right_source,
+        }
+    }
+
+    pub fn text(&self, side: DiffSide) -> &String {
+        match side {
+            DiffSide::Left => &self.left_text,
+            DiffSide::Right => &self.right_text,
+        }
+    }
+
+    pub fn hunks(&self) -> &Vec<Hunk> {
+        &self.hunks
+    }
+
+    pub fn rows(&self) -> &Vec<DiffRow> {
+        &self.rows
+    }
+
+    pub fn first_visible(&self) -> usize {
+        self.first_visible
+    }
+
+    /// Replaces text of one side, changes are compared again
+    pub fn set_text<R>(&mut self, side: DiffSide, text: String, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        match side {
+            DiffSide::Left => self.left_text = text,
+            DiffSide::Right => self.right_text = text,
+        }
+        self.compare();
+        self.first_visible = self.first_visible.min(self.rows.len().saturating_sub(1));
+        self.prepare_ui(renderer);
+    }
+
+    /// Text of target side after change is copied into it from other side
+    pub fn copied_text(&self, hunk: usize, target: DiffSide) -> Result<String, String> {
+        let source = self.side_source(target);
+        if !source.is_editable() {
+            return Err(format!("{} is read only", source.title()));
+        }
+        let hunk = self
+            .hunks
+            .get(hunk)
+            .ok_or_else(|| "Change does not exist".to_owned())?;
+        let (left, right) = (self.left_text.as_str(), self.right_text.as_str());
+        Ok(match target {
+            DiffSide::Left => apply_hunk(left, right, hunk),
+            DiffSide::Right => revert_hunk(left, right, hunk),
+        })
+    }
+
+    /// Moves both sides by given number of wheel steps
+    pub fn scroll<R>(&mut self, steps: i32, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let last = self.rows.len().saturating_sub(1) as isize;
+        let first = self.first_visible as isize + steps as isize * SCROLL_LINES;
+        self.first_visible = first.max(0).min(last) as usize;
+        self.prepare_ui(renderer);
+    }
+
+    fn compare(&mut self) {
+        self.hunks = diff_lines(self.left_text.as_str(), self.right_text.as_str());
+        let left_lines = self.left_text.split('\n').count();
+        self.rows = diff_rows(left_lines, &self.hunks);
+    }
+
+    fn row_height(&self) -> u32 {
+        self.char_size.height()
+    }
+
+    fn visible_rows(&self) -> usize {
+        match self.row_height() {
+            0 => 0,
+            row_height => {
+                let content = self.dest.height().saturating_sub((2 * PADDING) as u32);
+                (content / row_height).saturating_sub(1) as usize
+            }
+        }
+    }
+
+    fn column_width(&self) -> u32 {
+        self.dest.width().saturating_sub(GUTTER_WIDTH) / 2
+    }
+
+    /// Columns of left text, arrows and right text
+    fn columns(&self, dest: &Rect) -> (Rect, Rect, Rect) {
+        let width = self.column_width();
+        let top = dest.y() + PADDING + self.row_height() as i32;
+        let height = dest
+            .height()
+            .saturating_sub(PADDING as u32 + self.row_height());
+        let left = Rect::new(dest.x(), top, width.max(1), height.max(1));
+        let gutter = Rect::new(left.right(), top, GUTTER_WIDTH, height.max(1));
+        let right = Rect::new(gutter.right(), top, width.max(1), height.max(1));
+        (left, gutter, right)
+    }
+
+    fn is_hunk_start(&self, row: usize) -> bool {
+        match self.rows.get(row).and_then(|r| r.hunk) {
+            Some(hunk) => row == 0 || self.rows[row - 1].hunk != Some(hunk),
+            _ => false,
+        }
+    }
+
+    fn row_colors(&self, row: &DiffRow) -> (Option<Color>, Option<Color>) {
+        let hunk = match row.hunk.and_then(|hunk| self.hunks.get(hunk)) {
+            Some(hunk) => hunk,
+            _ => return (None, None),
+        };
+        let config = self.config.read().unwrap();
+        let diff = config.theme().diff();
+        match hunk.kind() {
+            HunkKind::Added => (None, Some(diff.add.color().into())),
+            HunkKind::Deleted => (Some(diff.delete.color().into()), None),
+            HunkKind::Modified => (
+                row.left.map(|_| diff.change.color().into()),
+                row.right.map(|_| diff.change.color().into()),
+            ),
+        }
+    }
+
+    fn line(text: &str, line: usize) -> &str {
+        text.split('\n').nth(line).unwrap_or_default()
+    }
+
+    /// Characters changed inside of line of modified hunk get color of deleted and added text
+    fn render_changed_characters<C>(&self, canvas: &mut C, row: &DiffRow, y: i32, dest: &Rect)
+    where
+        C: CanvasAccess,
+    {
+        let (left, right) = match (row.left, row.right) {
+            (Some(left), Some(right)) if row.hunk.is_some() => (left, right),
+            _ => return,
+        };
+        let (left_range, right_range) = diff_chars(
+            Self::line(self.left_text.as_str(), left),
+            Self::line(self.right_text.as_str(), right),
+        );
+        let (delete, add): (Color, Color) = {
+            let config = self.config.read().unwrap();
+            let diff = config.theme().diff();
+            (diff.delete.color().into(), diff.add.color().into())
+        };
+        let (left_column, _, right_column) = self.columns(dest);
+        let char_width = self.char_size.width() as i32;
+        for (column, range, color) in vec![
+            (left_column, left_range, delete),
+            (right_column, right_range, add),
+        ] {
+            if range.start == range.end {
+                continue;
+            }
+            let rect = Rect::new(
+                column.x() + PADDING + range.start as i32 * char_width,
+                y,
+                (range.end - range.start) as u32 * char_width as u32,
+                self.row_height(),
+            );
+            canvas
+                .render_rect(rect, color)
+                .unwrap_or_else(|_| panic!("Failed to render changed characters!"));
+        }
+    }
+}
+
+/// Labels draw text NAME_MARGIN right of position they get
+fn text_position(x: i32, y: i32) -> RC {
+    RC::ParentPosition(Point::new(x - NAME_MARGIN, y))
+}
+
+impl Widget for DiffView {
+    fn texture_path(&self) -> Option<String> {
+        None
+    }
+
+    fn dest(&self) -> &Rect {
+        &self.dest
+    }
+
+    fn set_dest(&mut self, rect: &Rect) {
+        self.dest = rect.clone();
+    }
+
+    fn source(&self) -> &Rect {
+        self.dest()
+    }
+
+    fn set_source(&mut self, rect: &Rect) {
+        self.set_dest(rect)
+    }
+
+    fn update(&mut self, _ticks: i32, _context: &UC) -> UR {
+        self.dest = {
+            let c = self.config.read().unwrap();
+            view_rect(c.width(), c.height(), c.menu_height())
+        };
+        UR::NoOp
+    }
+
+    fn on_left_click(&mut self, point: &Point, context: &UC) -> UR {
+        let dest = match context {
+            UC::ParentPosition(p) => move_render_point(*p, &self.dest),
+            _ => self.dest,
+        };
+        let (_, gutter, _) = self.columns(&dest);
+        if self.row_height() == 0 || !gutter.contains_point(*point) {
+            return UR::NoOp;
+        }
+        let row =
+            self.first_visible + (point.y() - gutter.y()) as usize / self.row_height() as usize;
+        if !self.is_hunk_start(row) {
+            return UR::NoOp;
+        }
+        let hunk = self.rows[row].hunk.unwrap_or_default();
+        match point.x() < gutter.x() + GUTTER_WIDTH as i32 / 2 {
+            true => UR::CopyHunk(hunk, DiffSide::Left),
+            false => UR::CopyHunk(hunk, DiffSide::Right),
+        }
+    }
+
+    fn is_left_click_target(&self, point: &Point, context: &UC) -> bool {
+        match context {
+            UC::ParentPosition(p) => move_render_point(*p, &self.dest),
+            _ => self.dest,
+        }
+        .contains_point(*point)
+    }
+
+    fn render<C, R>(&self, canvas: &mut C, renderer: &mut R, context: &RenderContext)
+    where
+        C: CanvasAccess,
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let dest = match context {
+            RC::ParentPosition(p) => move_render_point(*p, &self.dest),
+            _ => self.dest,
+        };
+        canvas.set_clip_rect(None);
+        canvas
+            .render_rect(dest, self.background_color)
+            .unwrap_or_else(|_| panic!("Failed to render diff view background!"));
+        canvas
+            .render_border(dest, self.border_color)
+            .unwrap_or_else(|_| panic!("Failed to render diff view border!"));
+
+        let (left_column, gutter, right_column) = self.columns(&dest);
+        let title_y = dest.y() + PADDING;
+        for (title, column) in self.titles.iter().zip(vec![left_column, right_column]) {
+            title.render(
+                canvas,
+                renderer,
+                &text_position(column.x() + PADDING, title_y),
+            );
+        }
+
+        let mut arrows = self.arrows.iter();
+        for (index, row) in self.rows[self.first_visible..]
+            .iter()
+            .take(self.left_labels.len())
+            .enumerate()
+        {
+            let y = left_column.y() + index as i32 * self.row_height() as i32;
+            let (left_color, right_color) = self.row_colors(row);
+            for (column, color) in vec![(left_column, left_color), (right_column, right_color)] {
+                if let Some(color) = color {
+                    let rect = Rect::new(column.x(), y, column.width(), self.row_height());
+                    canvas
+                        .render_rect(rect, color)
+                        .unwrap_or_else(|_| panic!("Failed to render changed line!"));
+                }
+            }
+            self.render_changed_characters(canvas, row, y, &dest);
+
+            for (labels, column) in vec![
+                (&self.left_labels, left_column),
+                (&self.right_labels, right_column),
+            ] {
+                if let Some(label) = labels[index].as_ref() {
+                    canvas.set_clip_rect(Some(column));
+                    label.render(canvas, renderer, &text_position(column.x() + PADDING, y));
+                }
+            }
+            canvas.set_clip_rect(None);
+
+            if self.is_hunk_start(self.first_visible + index) {
+                let half = GUTTER_WIDTH as i32 / 2;
+                for x in vec![gutter.x() + PADDING, gutter.x() + half + PADDING] {
+                    if let Some(arrow) = arrows.next() {
+                        arrow.render(canvas, renderer, &text_position(x, y));
+                    }
+                }
+            }
+        }
+    }
+
+    fn prepare_ui<'l, T>(&mut self, renderer: &mut T)
+    where
+        T: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        self.char_size = renderer.load_character_size('W');
+        let config = Arc::clone(&self.config);
+        let label = |text: &str, renderer: &mut T| {
+            let mut label = Label::new(text.to_owned(), Arc::clone(&config));
+            label.prepare_ui(renderer);
+            label
+        };
+        self.titles = vec![
+            label(self.left_source.title().as_str(), renderer),
+            label(self.right_source.title().as_str(), renderer),
+        ];
+
+        let mut left_labels = vec![];
+        let mut right_labels = vec![];
+        let mut arrows = vec![];
+        let end = (self.first_visible + self.visible_rows()).min(self.rows.len());
+        for row in self.first_visible..end {
+            for (line, text, labels) in vec![
+                (self.rows[row].left, &self.left_text, &mut left_labels),
+                (self.rows[row].right, &self.right_text, &mut right_labels),
+            ] {
+                // empty label has no size
+                labels.push(
+                    line.map(|line| Self::line(text.as_str(), line))
+                        .filter(|text| !text.is_empty())
+                        .map(|text| label(text, renderer)),
+                );
+            }
+            if self.is_hunk_start(row) {
+                arrows.push(label("<", renderer));
+                arrows.push(label(">", renderer));
+            }
+        }
+        self.left_labels = left_labels;
+        self.right_labels = right_labels;
+        self.arrows = arrows;
+    }
+}
+
+fn view_rect(width: u32, height: u32, menu_height: u16) -> Rect {
+    let top = menu_height as u32;
+    Rect::new(
+        0,
+        top as i32,
+        width.max(1),
+        height.saturating_sub(top).max(1),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use rider_derive::*;
+
+    fn row(left: Option<usize>, right: Option<usize>, hunk: Option<usize>) -> DiffRow {
+        DiffRow { left, right, hunk }
+    }
+
+    #[test]
+    fn assert_align_rows_of_hunks() {
+        let hunks = diff_lines("a\nb\nc\nd", "a\nB\nx\nc");
+        assert_eq!(
+            diff_rows(4, &hunks),
+            vec![
+                row(Some(0), Some(0), None),
+                row(Some(1), Some(1), Some(0)),
+                row(None, Some(2), Some(0)),
+                row(Some(2), Some(3), None),
+                row(Some(3), None, Some(1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn assert_copy_hunks_between_sides() {
+        build_test_renderer!(renderer);
+        let mut view = DiffView::new(
+            (DiffSource::File("/a.txt".to_owned()), "a\nb\nc".to_owned()),
+            (
+                DiffSource::Buffer("/b.txt".to_owned()),
+                "a\nB\nc\nd".to_owned(),
+            ),
+            config.clone(),
+        );
+        view.prepare_ui(&mut renderer);
+        assert_eq!(view.hunks().len(), 2);
+        assert_eq!(
+            view.copied_text(0, DiffSide::Right),
+            Ok("a\nb\nc\nd".to_owned())
+        );
+        assert_eq!(
+            view.copied_text(1, DiffSide::Left),
+            Ok("a\nb\nc\nd".to_owned())
+        );
+        assert_eq!(view.copied_text(2, DiffSide::Left).is_err(), true);
+
+        let text = view.copied_text(0, DiffSide::Left).unwrap();
+        view.set_text(DiffSide::Left, text, &mut renderer);
+        assert_eq!(view.text(DiffSide::Left), &"a\nB\nc".to_owned());
+        assert_eq!(view.hunks().len(), 1);
+
+        let head = DiffView::new(
+            (DiffSource::Head("/b.txt".to_owned()), "a".to_owned()),
+            (DiffSource::Buffer("/b.txt".to_owned()), "b".to_owned()),
+            config,
+        );
+        assert_eq!(
+            head.copied_text(0, DiffSide::Left),
+            Err("/b.txt (HEAD) is read only".to_owned())
+        );
+        assert_eq!(head.copied_text(0, DiffSide::Right), Ok("a".to_owned()));
+    }
+
+    #[test]
+    fn assert_click_on_arrows_and_scroll_together() {
+        build_test_renderer!(renderer);
+        let left: Vec<String> = (0..300).map(|i| format!("line {}", i)).collect();
+        let mut right = left.clone();
+        right[200] = "changed".to_owned();
+        let mut view = DiffView::new(
+            (DiffSource::Disk("/a.txt".to_owned()), left.join("\n")),
+            (DiffSource::Buffer("/a.txt".to_owned()), right.join("\n")),
+            config,
+        );
+        view.prepare_ui(&mut renderer);
+        assert_eq!(view.left_labels.len(), view.visible_rows());
+        assert_eq!(view.arrows.len(), 0);
+
+        view.scroll(66, &mut renderer);
+        assert_eq!(view.first_visible(), 198);
+        assert_eq!(view.arrows.len(), 2);
+        assert_eq!(
+            view.left_labels[0].as_ref().map(|l| l.name()),
+            Some("line 198".to_owned())
+        );
+
+        let (_, gutter, _) = view.columns(&view.dest().clone());
+        let y = gutter.y() + 2 * view.row_height() as i32 + 1;
+        assert_eq!(
+            view.on_left_click(&Point::new(gutter.x() + 1, y), &UpdateContext::Nothing),
+            UR::CopyHunk(0, DiffSide::Left)
+        );
+        assert_eq!(
+            view.on_left_click(&Point::new(gutter.right() - 1, y), &UpdateContext::Nothing),
+            UR::CopyHunk(0, DiffSide::Right)
+        );
+        assert_eq!(
+            view.on_left_click(&Point::new(gutter.x() + 1, y + 20), &UpdateContext::Nothing),
+            UR::NoOp
+        );
+
+        view.scroll(-100, &mut renderer);
+        assert_eq!(view.first_visible(), 0);
+    }
+
+    #[test]
+    fn assert_render_changes() {
+        build_test_renderer!(renderer);
+        let change: Color = config.read().unwrap().theme().diff().change.color().into();
+        let mut canvas = CanvasMock::new();
+        let mut view = DiffView::new(
+            (
+                DiffSource::Head("/a.txt".to_owned()),
+                "let a = 1;".to_owned(),
+            ),
+            (
+                DiffSource::Buffer("/a.txt".to_owned()),
+                "let b = 1;".to_owned(),
+            ),
+            config,
+        );
+        view.prepare_ui(&mut renderer);
+        view.render(&mut canvas, &mut renderer, &RenderContext::Nothing);
+        let changed = canvas
+            .rects
+            .iter()
+            .filter(|r| r.shape == CanvasShape::Rectangle && r.color == change)
+            .count();
+        assert_eq!(changed, 2);
+        // "a" and "b" have 1 character
+        let (left, _, _) = view.columns(&view.dest().clone());
+        assert_eq!(
+            canvas
+                .rects
+                .iter()
+                .any(|r| r.rect == Rect::new(left.x() + PADDING + 4 * 13, left.y(), 13, 14)),
+            true
+        );
+    }
+}
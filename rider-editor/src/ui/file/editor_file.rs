@@ -1,6 +1,13 @@
 use sdl2::rect::{Point, Rect};
+use std::collections::BTreeSet;
 use std::sync::*;
+use std::time::Instant;
 
+use crate::app::diagnostics::Diagnostic;
+use crate::app::external_change::DiskVersion;
+use crate::app::file_content_manager::UndoStack;
+use crate::app::git::{diff_lines, parse_conflicts, Conflict, Hunk};
+use crate::app::symbol_index::line_starts;
 use crate::app::UpdateResult as UR;
 use crate::renderer::renderer::Renderer;
 use crate::ui::file::editor_file_section::EditorFileSection;
@@ -8,41 +15,109 @@ use crate::ui::text_character::TextCharacter;
 use crate::ui::*;
 use rider_config::Config;
 use rider_config::ConfigHolder;
+use rider_lexers::markdown;
+use rider_lexers::outline::Symbol;
+use rider_lexers::{Language, TokenType};
 
 #[derive(Clone, Debug)]
 pub struct EditorFile {
     path: String,
     sections: Vec<EditorFileSection>,
+    folds: Folds,
     dest: Rect,
     buffer: String,
     config: Arc<RwLock<Config>>,
     line_height: u32,
+    diagnostics: Vec<Diagnostic>,
+    git_base: Option<Arc<String>>,
+    hunks: Vec<Hunk>,
+    hunks_outdated_since: Option<Instant>,
+    conflicts: Vec<Conflict>,
+    disk_version: Option<Arc<DiskVersion>>,
+    undo_stack: UndoStack,
 }
 
 impl EditorFile {
     pub fn new(path: String, buffer: String, config: Arc<RwLock<Config>>) -> Self {
         use std::path::Path;
-        let ext = Path::new(&path)
+        let file_path = Path::new(&path);
+        let ext = file_path
             .extension()
+            .or_else(|| file_path.file_name())
             .and_then(|p| p.to_str())
             .map_or("txt", |s| s)
             .to_string();
-        let sections = vec![EditorFileSection::new(
-            buffer.clone(),
-            ext,
-            Arc::clone(&config),
-        )];
+        let language = config
+            .read()
+            .unwrap()
+            .extensions_mapping()
+            .get(ext.as_str())
+            .cloned();
+        let sections = match language {
+            Some(Language::Markdown) => Self::markdown_sections(&buffer, &config),
+            _ => vec![EditorFileSection::new(
+                buffer.clone(),
+                ext,
+                Arc::clone(&config),
+            )],
+        };
+
+        let folds = Folds::new(
+            sections
+                .iter()
+                .flat_map(|section| section.fold_regions().clone())
+                .collect(),
+        );
+
+        let conflicts = parse_conflicts(buffer.as_str());
 
         Self {
             path,
             sections,
+            folds,
             dest: Rect::new(0, 0, 0, 0),
             buffer,
             config,
             line_height: 0,
+            diagnostics: vec![],
+            git_base: None,
+            hunks: vec![],
+            hunks_outdated_since: None,
+            conflicts,
+            disk_version: None,
+            undo_stack: UndoStack::default(),
         }
     }
 
+    fn markdown_sections(buffer: &String, config: &Arc<RwLock<Config>>) -> Vec<EditorFileSection> {
+        markdown::sections(buffer.as_str())
+            .into_iter()
+            .map(|section| {
+                let language = match section.kind {
+                    markdown::SectionKind::Markdown => Language::Markdown,
+                    markdown::SectionKind::FencedCode { ref info } => Language::from_name(info)
+                        .or_else(|| {
+                            config
+                                .read()
+                                .unwrap()
+                                .extensions_mapping()
+                                .get(info.as_str())
+                                .cloned()
+                        })
+                        .unwrap_or(Language::PlainText),
+                };
+                EditorFileSection::with_language(
+                    section.text,
+                    language,
+                    section.span.lo,
+                    section.char_span.lo,
+                    section.line,
+                    Arc::clone(config),
+                )
+            })
+            .collect()
+    }
+
     pub fn buffer(&self) -> String {
         self.buffer.clone()
     }
@@ -63,14 +138,238 @@ impl EditorFile {
         self.line_height
     }
 
+    /// Position of bracket paired with the one at given char position
+    pub fn matching_bracket(&self, position: usize) -> Option<usize> {
+        self.sections
+            .iter()
+            .find_map(|section| section.brackets().matching(position))
+    }
+
+    /// Bracket pair next to caret, bracket after caret is preferred
+    pub fn bracket_pair_near(&self, caret: usize) -> Option<(usize, usize)> {
+        self.sections
+            .iter()
+            .find_map(|section| section.brackets().pair_near(caret))
+    }
+
+    /// Outline of whole file, fenced code of markdown contributes its own symbols
+    pub fn symbols(&self) -> Vec<Symbol> {
+        self.sections
+            .iter()
+            .flat_map(|section| section.symbols().clone())
+            .collect()
+    }
+
+    /// Identifiers and keywords used in file together with keywords of its languages
+    pub fn words(&self) -> BTreeSet<String> {
+        let mut words = BTreeSet::new();
+        for section in self.sections.iter() {
+            for token in section.tokens() {
+                match token.token_type() {
+                    TokenType::Identifier { .. } | TokenType::Keyword { .. } => (),
+                    _ => continue,
+                }
+                let text = token.token_type().text();
+                if text.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                    words.insert(text.clone());
+                }
+            }
+            words.extend(
+                rider_lexers::keywords(section.language())
+                    .into_iter()
+                    .map(|keyword| keyword.to_owned()),
+            );
+        }
+        words
+    }
+
     pub fn get_section_at_mut(&mut self, index: usize) -> Option<&mut EditorFileSection> {
         self.sections.get_mut(index)
     }
 
+    pub fn folds(&self) -> &Folds {
+        &self.folds
+    }
+
+    /// Versions of buffer from before its edits
+    pub fn undo_stack(&self) -> &UndoStack {
+        &self.undo_stack
+    }
+
+    pub fn take_undo_stack(&mut self) -> UndoStack {
+        std::mem::replace(&mut self.undo_stack, UndoStack::default())
+    }
+
+    pub fn set_undo_stack(&mut self, undo_stack: UndoStack) {
+        self.undo_stack = undo_stack;
+    }
+
+    pub fn fold(&mut self, line: usize) -> bool {
+        let folded = self.folds.fold(line);
+        self.refresh_characters_position();
+        folded
+    }
+
+    pub fn unfold(&mut self, line: usize) -> bool {
+        let unfolded = self.folds.unfold(line);
+        self.refresh_characters_position();
+        unfolded
+    }
+
+    pub fn fold_all(&mut self) {
+        self.folds.fold_all();
+        self.refresh_characters_position();
+    }
+
+    pub fn unfold_all(&mut self) {
+        self.folds.unfold_all();
+        self.refresh_characters_position();
+    }
+
+    pub fn fold_level(&mut self, level: usize) {
+        self.folds.fold_level(level);
+        self.refresh_characters_position();
+    }
+
+    /// Keeps folds of file which was replaced by this one after edit at given line
+    pub fn restore_folds(&mut self, previous: &EditorFile, edited_line: usize) {
+        let delta = self.lines_count() as isize - previous.lines_count() as isize;
+        self.folds.restore(&previous.folds, edited_line, delta);
+        self.refresh_characters_position();
+    }
+
+    /// Char position of column in line, column is limited to line length
+    pub fn position_at(&self, line: usize, column: usize) -> Option<usize> {
+        let starts = line_starts(self.buffer.as_str());
+        let start = *starts.get(line)?;
+        let end = starts
+            .get(line + 1)
+            .map_or_else(|| self.buffer.chars().count(), |next| next - 1);
+        Some(start + column.min(end - start))
+    }
+
+    pub fn diagnostics(&self) -> &Vec<Diagnostic> {
+        &self.diagnostics
+    }
+
+    pub fn set_diagnostics(&mut self, diagnostics: Vec<Diagnostic>) {
+        self.diagnostics = diagnostics;
+    }
+
+    /// Char positions covered by diagnostic, empty range covers one character
+    pub fn diagnostic_range(&self, diagnostic: &Diagnostic) -> Option<(usize, usize)> {
+        let start = self.position_at(diagnostic.location.line, diagnostic.location.column)?;
+        let end = self
+            .position_at(diagnostic.end_line, diagnostic.end_column)
+            .unwrap_or_else(|| self.buffer.chars().count());
+        Some((start, end.max(start + 1)))
+    }
+
+    /// Most severe diagnostic covering char position
+    pub fn diagnostic_at(&self, position: usize) -> Option<&Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| {
+                self.diagnostic_range(diagnostic)
+                    .map_or(false, |(start, end)| start <= position && position < end)
+            })
+            .min_by_key(|diagnostic| diagnostic.severity)
+    }
+
+    /// Version of file from HEAD of its repository, `None` when it is not in repository
+    pub fn git_base(&self) -> Option<&Arc<String>> {
+        self.git_base.as_ref()
+    }
+
+    pub fn set_git_base(&mut self, git_base: Option<Arc<String>>) {
+        self.hunks = git_base.as_ref().map_or_else(Vec::new, |base| {
+            diff_lines(base.as_str(), self.buffer.as_str())
+        });
+        self.git_base = git_base;
+        self.hunks_outdated_since = None;
+    }
+
+    /// Takes git base and hunks of previous version of file without comparing them with buffer,
+    /// hunks are outdated until `refresh_hunks`
+    pub fn carry_git_base(&mut self, file: &EditorFile) {
+        self.git_base = file.git_base.clone();
+        self.hunks = file.hunks.clone();
+        self.hunks_outdated_since = match self.git_base {
+            Some(_) => file.hunks_outdated_since.or_else(|| Some(Instant::now())),
+            _ => None,
+        };
+    }
+
+    /// Time of first edit hunks were not compared with, `None` when they are up to date
+    pub fn hunks_outdated_since(&self) -> Option<Instant> {
+        self.hunks_outdated_since
+    }
+
+    pub fn refresh_hunks(&mut self) {
+        if self.hunks_outdated_since.is_some() {
+            let git_base = self.git_base.clone();
+            self.set_git_base(git_base);
+        }
+    }
+
+    /// File as it was when buffer was read or saved, `None` when buffer is not backed by disk
+    pub fn disk_version(&self) -> Option<&Arc<DiskVersion>> {
+        self.disk_version.as_ref()
+    }
+
+    pub fn set_disk_version(&mut self, disk_version: Option<Arc<DiskVersion>>) {
+        self.disk_version = disk_version;
+    }
+
+    /// Buffer differs from its last read or saved version
+    pub fn is_modified(&self) -> bool {
+        self.disk_version
+            .as_ref()
+            .map_or(false, |version| version.content != self.buffer)
+    }
+
+    /// Lines changed since HEAD
+    pub fn hunks(&self) -> &Vec<Hunk> {
+        &self.hunks
+    }
+
+    pub fn hunk_at_line(&self, line: usize) -> Option<&Hunk> {
+        self.hunks.iter().find(|hunk| hunk.contains_line(line))
+    }
+
+    /// Regions between merge conflict markers
+    pub fn conflicts(&self) -> &Vec<Conflict> {
+        &self.conflicts
+    }
+
+    pub fn conflict_at_line(&self, line: usize) -> Option<&Conflict> {
+        self.conflicts
+            .iter()
+            .find(|conflict| conflict.contains_line(line))
+    }
+
+    pub fn lines_count(&self) -> usize {
+        self.buffer.matches('\n').count() + 1
+    }
+
+    /// Y of every line when first line starts at given one, `None` for lines hidden in fold
+    pub fn line_tops(&self, top: i32) -> Vec<Option<i32>> {
+        let mut row = 0;
+        (0..=self.lines_count())
+            .map(|line| {
+                if self.folds.is_hidden(line) {
+                    return None;
+                }
+                row += 1;
+                Some(top + ((row - 1) * self.line_height) as i32)
+            })
+            .collect()
+    }
+
     fn refresh_characters_position(&mut self) {
         let mut current: Rect = Rect::new(0, 0, 0, 0);
         for section in self.sections.iter_mut() {
-            section.update_positions(&mut current);
+            section.update_positions(&mut current, &self.folds);
         }
     }
 
@@ -301,10 +600,14 @@ impl<'a> Iterator for EditorFileIterator<'a> {
 mod tests {
     use crate::tests::*;
 
+    use crate::app::external_change::DiskVersion;
+    use crate::app::git::HunkKind;
     use crate::app::UpdateResult;
     use crate::ui::*;
     use rider_derive::*;
+    use rider_lexers::Language;
     use sdl2::rect::{Point, Rect};
+    use std::sync::Arc;
 
     #[test]
     fn check_get_line() {
@@ -320,6 +623,163 @@ mod tests {
         assert_eq!(result.is_some(), false);
     }
 
+    #[test]
+    fn check_hunks() {
+        let config = build_config();
+        let mut file = EditorFile::new("./foo.txt".to_owned(), "a\nB\nc\nd".to_owned(), config);
+        assert_eq!(file.hunks().len(), 0);
+        file.set_git_base(Some(Arc::new("a\nb\nc".to_owned())));
+        assert_eq!(file.hunks().len(), 2);
+        assert_eq!(file.hunk_at_line(0), None);
+        assert_eq!(
+            file.hunk_at_line(1).map(|hunk| hunk.kind()),
+            Some(HunkKind::Modified)
+        );
+        assert_eq!(
+            file.hunk_at_line(3).map(|hunk| hunk.kind()),
+            Some(HunkKind::Added)
+        );
+        file.set_git_base(None);
+        assert_eq!(file.hunks().len(), 0);
+    }
+
+    #[test]
+    fn check_carried_hunks() {
+        let config = build_config();
+        let mut file =
+            EditorFile::new("./foo.txt".to_owned(), "a\nB\nc".to_owned(), config.clone());
+        file.set_git_base(Some(Arc::new("a\nb\nc".to_owned())));
+        let mut edited = EditorFile::new("./foo.txt".to_owned(), "a\nb\nc".to_owned(), config);
+        edited.carry_git_base(&file);
+        assert_eq!(edited.hunks().len(), 1);
+        assert_eq!(edited.hunks_outdated_since().is_some(), true);
+        edited.refresh_hunks();
+        assert_eq!(edited.hunks().len(), 0);
+        assert_eq!(edited.hunks_outdated_since(), None);
+    }
+
+    #[test]
+    fn check_modified() {
+        let config = build_config();
+        let mut file = EditorFile::new("./foo.txt".to_owned(), "a\nb".to_owned(), config);
+        assert_eq!(file.is_modified(), false);
+        let version = DiskVersion {
+            content: "a\nb".to_owned(),
+            modified: None,
+        };
+        file.set_disk_version(Some(Arc::new(version.clone())));
+        assert_eq!(file.is_modified(), false);
+        file.set_disk_version(Some(Arc::new(DiskVersion {
+            content: "a".to_owned(),
+            ..version
+        })));
+        assert_eq!(file.is_modified(), true);
+        assert_eq!(
+            file.disk_version().map(|v| v.content.clone()),
+            Some("a".to_owned())
+        );
+    }
+
+    #[test]
+    fn check_conflicts() {
+        let config = build_config();
+        let buffer = "a\n<<<<<<< HEAD\nb\n=======\nc\n>>>>>>> topic\n".to_owned();
+        let file = EditorFile::new("./foo.txt".to_owned(), buffer, config);
+        assert_eq!(file.conflicts().len(), 1);
+        assert_eq!(file.conflict_at_line(0), None);
+        assert_eq!(
+            file.conflict_at_line(3).map(|c| c.theirs.clone()),
+            Some(4..5)
+        );
+    }
+
+    #[test]
+    fn check_diagnostic_at() {
+        use crate::app::{Diagnostic, DiagnosticSeverity, Location};
+        let config = build_config();
+        let mut file = EditorFile::new("./foo.rs".to_owned(), "ab\ncde\n".to_owned(), config);
+        let diagnostic = |line, column, end_column, severity| Diagnostic {
+            location: Location {
+                path: "./foo.rs".to_owned(),
+                line,
+                column,
+            },
+            end_line: line,
+            end_column,
+            severity,
+            message: String::new(),
+            source: String::new(),
+        };
+        file.set_diagnostics(vec![
+            diagnostic(1, 0, 3, DiagnosticSeverity::Warning),
+            diagnostic(1, 1, 1, DiagnosticSeverity::Error),
+        ]);
+        assert_eq!(file.diagnostic_range(&file.diagnostics()[1]), Some((4, 5)));
+        assert_eq!(file.diagnostic_at(1), None);
+        assert_eq!(
+            file.diagnostic_at(3).map(|d| d.severity),
+            Some(DiagnosticSeverity::Warning)
+        );
+        assert_eq!(
+            file.diagnostic_at(4).map(|d| d.severity),
+            Some(DiagnosticSeverity::Error)
+        );
+    }
+
+    #[test]
+    fn check_bracket_pair_near() {
+        let config = build_config();
+        let file = EditorFile::new("./foo.rs".to_owned(), "f(a[1])".to_owned(), config);
+        assert_eq!(file.matching_bracket(1), Some(6));
+        assert_eq!(file.bracket_pair_near(3), Some((3, 5)));
+        assert_eq!(file.bracket_pair_near(7), Some((6, 1)));
+        assert_eq!(file.bracket_pair_near(0), None);
+    }
+
+    #[test]
+    fn check_position_at() {
+        let config = build_config();
+        let file = EditorFile::new("./foo.rs".to_owned(), "ab\ncde\n".to_owned(), config);
+        assert_eq!(file.position_at(1, 1), Some(4));
+        assert_eq!(file.position_at(0, 10), Some(2));
+        assert_eq!(file.position_at(2, 0), Some(7));
+        assert_eq!(file.position_at(3, 0), None);
+    }
+
+    #[test]
+    fn check_symbols_of_markdown_with_code() {
+        let config = build_config();
+        let buffer = "# Title\n```rust\nfn main() {}\n```\n## Next\n".to_owned();
+        let file = EditorFile::new("./foo.md".to_owned(), buffer, config);
+        let symbols: Vec<(String, usize, usize)> = file
+            .symbols()
+            .into_iter()
+            .map(|symbol| (symbol.name, symbol.line, symbol.position))
+            .collect();
+        assert_eq!(
+            symbols,
+            vec![
+                ("Title".to_owned(), 0, 0),
+                ("main".to_owned(), 2, 19),
+                ("Next".to_owned(), 4, 33),
+            ]
+        );
+    }
+
+    #[test]
+    fn check_words() {
+        let config = build_config();
+        let buffer = "let foo_bar = baz(\"text\");".to_owned();
+        let file = EditorFile::new("./foo.rs".to_owned(), buffer, config);
+        let words = file.words();
+        assert!(words.contains("foo_bar"));
+        assert!(words.contains("baz"));
+        assert!(words.contains("let"));
+        assert!(words.contains("while"));
+        assert!(!words.contains("text"));
+        assert!(!words.contains("="));
+    }
+
     //##################################################
     // iterator
     //##################################################
@@ -344,6 +804,38 @@ mod tests {
         }
     }
 
+    //##################################################
+    // markdown
+    //##################################################
+
+    #[test]
+    fn assert_markdown_fenced_code_sections() {
+        let config = build_config();
+        let buffer = "# Foo\n```rust\nfn a() {}\n```\nbar\n".to_owned();
+        let widget = EditorFile::new("./README.md".to_owned(), buffer, config);
+        let languages: Vec<Language> = widget.sections().iter().map(|s| s.language()).collect();
+        assert_eq!(
+            languages,
+            vec![
+                Language::Markdown,
+                Language::Rust,
+                Language::Markdown,
+                Language::Markdown,
+            ]
+        );
+    }
+
+    #[test]
+    fn assert_markdown_section_positions() {
+        build_test_renderer!(renderer);
+        let buffer = "a\n```toml\nb = 1\n```\n".to_owned();
+        let mut widget = EditorFile::new("./README.md".to_owned(), buffer, config.clone());
+        widget.prepare_ui(&mut renderer);
+        let c = widget.get_character_at(14).unwrap();
+        assert_eq!(c.text_character(), '1');
+        assert_eq!(c.line(), 2);
+    }
+
     //##################################################
     // path
     //##################################################
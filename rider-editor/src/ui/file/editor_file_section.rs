@@ -9,11 +9,18 @@ use crate::ui::*;
 use rider_config::Config;
 use rider_config::ConfigHolder;
 use rider_lexers;
+use rider_lexers::brackets::Brackets;
+use rider_lexers::folding::{self, FoldRegion};
+use rider_lexers::outline::{self, Symbol};
 use rider_lexers::Language;
+use rider_lexers::TokenType;
 
 #[derive(Clone, Debug)]
 pub struct EditorFileSection {
     tokens: Vec<EditorFileToken>,
+    brackets: Brackets,
+    fold_regions: Vec<FoldRegion>,
+    symbols: Vec<Symbol>,
     language: Language,
     config: Arc<RwLock<Config>>,
     dest: Rect,
@@ -28,7 +35,32 @@ impl EditorFileSection {
             .get(ext.as_str())
             .unwrap_or(&Language::PlainText)
             .clone();
-        let lexer_tokens = rider_lexers::parse(buffer.clone(), language.clone());
+        Self::with_language(buffer, language, 0, 0, 0, config)
+    }
+
+    /// Builds section for part of file starting at given byte and char offsets and line
+    pub fn with_language(
+        buffer: String,
+        language: Language,
+        start: usize,
+        char_start: usize,
+        line: usize,
+        config: Arc<RwLock<Config>>,
+    ) -> Self {
+        let lexer_tokens: Vec<TokenType> = rider_lexers::parse(buffer.clone(), language.clone())
+            .into_iter()
+            .map(|t| {
+                t.move_to(
+                    t.line() + line,
+                    t.character(),
+                    t.span().shifted(start),
+                    t.char_span().shifted(char_start),
+                )
+            })
+            .collect();
+        let brackets = Brackets::new(lexer_tokens.iter());
+        let fold_regions = folding::regions(&lexer_tokens);
+        let symbols = outline::symbols(&lexer_tokens, language);
 
         let mut tokens: Vec<EditorFileToken> = vec![];
         let mut iterator = lexer_tokens.iter().peekable();
@@ -38,15 +70,19 @@ impl EditorFileSection {
                 _ => break,
             };
             let next = iterator.peek();
-            let token = EditorFileToken::new(
+            let mut token = EditorFileToken::new(
                 token_type,
                 next.map_or(true, |t| t.is_new_line()),
                 config.clone(),
             );
+            token.mark_unmatched_bracket(&brackets);
             tokens.push(token);
         }
         Self {
             tokens,
+            brackets,
+            fold_regions,
+            symbols,
             language,
             config,
             dest: Rect::new(0, 0, 0, 0),
@@ -57,9 +93,9 @@ impl EditorFileSection {
         self.language
     }
 
-    pub fn update_positions(&mut self, current: &mut Rect) {
+    pub fn update_positions(&mut self, current: &mut Rect, folds: &Folds) {
         for c in self.tokens.iter_mut() {
-            c.update_position(current);
+            c.update_visible_position(current, folds);
         }
     }
 
@@ -71,6 +107,18 @@ impl EditorFileSection {
     pub fn tokens(&self) -> &Vec<EditorFileToken> {
         &self.tokens
     }
+
+    pub fn brackets(&self) -> &Brackets {
+        &self.brackets
+    }
+
+    pub fn fold_regions(&self) -> &Vec<FoldRegion> {
+        &self.fold_regions
+    }
+
+    pub fn symbols(&self) -> &Vec<Symbol> {
+        &self.symbols
+    }
 }
 
 impl Widget for EditorFileSection {
@@ -155,7 +203,8 @@ impl TextWidget for EditorFileSection {
         let mut current_line_width = 0;
         let mut max_line_width = 0;
         let mut height = 0;
-        for (index, token) in self.tokens.iter().enumerate() {
+        let visible = self.tokens.iter().filter(|token| !token.is_hidden());
+        for (index, token) in visible.enumerate() {
             let r = token.full_rect();
 
             if index == 0 {
@@ -288,6 +337,30 @@ mod tests {
         assert_eq!(widget.tokens_count(), 8);
     }
 
+    #[test]
+    fn assert_with_language() {
+        build_test_renderer!(renderer);
+        let mut widget =
+            EditorFileSection::with_language("a = 1".to_owned(), Language::Toml, 10, 10, 2, config);
+        widget.prepare_ui(&mut renderer);
+        assert_eq!(widget.language(), Language::Toml);
+        assert_eq!(widget.tokens_count(), 5);
+        let c = widget.get_character_at(14).unwrap();
+        assert_eq!(c.text_character(), '1');
+        assert_eq!(c.line(), 2);
+    }
+
+    #[test]
+    fn assert_brackets_are_shifted() {
+        let config = build_config();
+        let widget =
+            EditorFileSection::with_language("f(x) )".to_owned(), Language::Rust, 4, 3, 1, config);
+        assert_eq!(widget.brackets().matching(4), Some(6));
+        assert_eq!(widget.brackets().unmatched(), &vec![8]);
+        assert_eq!(widget.tokens()[5].is_unmatched_bracket(), true);
+        assert_eq!(widget.tokens()[1].is_unmatched_bracket(), false);
+    }
+
     #[test]
     fn assert_simple_char_iteration() {
         build_test_renderer!(renderer);
@@ -3,6 +3,7 @@ use crate::renderer::*;
 use crate::ui::*;
 use rider_config::Config;
 use rider_config::ConfigHolder;
+use rider_lexers::brackets::Brackets;
 use rider_lexers::TokenType;
 use sdl2::pixels::Color;
 use sdl2::rect::{Point, Rect};
@@ -11,6 +12,7 @@ use std::sync::*;
 #[derive(Clone, Debug)]
 pub struct EditorFileToken {
     last_in_line: bool,
+    unmatched_bracket: bool,
     characters: Vec<TextCharacter>,
     token_type: TokenType,
     config: Arc<RwLock<Config>>,
@@ -21,6 +23,7 @@ impl EditorFileToken {
     pub fn new(token_type: &TokenType, last_in_line: bool, config: Arc<RwLock<Config>>) -> Self {
         Self {
             last_in_line,
+            unmatched_bracket: false,
             characters: vec![],
             token_type: token_type.clone(),
             config,
@@ -36,12 +39,43 @@ impl EditorFileToken {
         self.token_type.is_new_line()
     }
 
+    /// Marks bracket without pair so it's rendered with error color
+    pub fn mark_unmatched_bracket(&mut self, brackets: &Brackets) {
+        let span = self.token_type.char_span();
+        self.unmatched_bracket = match self.token_type {
+            TokenType::Separator { .. } if !span.is_empty() => brackets.is_unmatched(span.hi - 1),
+            _ => false,
+        };
+    }
+
+    pub fn is_unmatched_bracket(&self) -> bool {
+        self.unmatched_bracket
+    }
+
     pub fn update_position(&mut self, current: &mut Rect) {
+        self.update_visible_position(current, &Folds::default());
+    }
+
+    /// Lays out characters skipping lines hidden by folds
+    pub fn update_visible_position(&mut self, current: &mut Rect, folds: &Folds) {
         for text_character in self.characters.iter_mut() {
-            text_character.update_position(current);
+            if folds.is_hidden(text_character.line()) {
+                text_character.hide(current);
+            } else {
+                text_character.update_position(current);
+            }
         }
     }
 
+    pub fn is_hidden(&self) -> bool {
+        !self.characters.is_empty() && self.characters.iter().all(|c| c.is_hidden())
+    }
+
+    #[inline]
+    pub fn token_type(&self) -> &TokenType {
+        &self.token_type
+    }
+
     #[inline]
     pub fn characters(&self) -> &Vec<TextCharacter> {
         &self.characters
@@ -50,6 +84,9 @@ impl EditorFileToken {
     fn token_to_color(&self, config: &Arc<RwLock<Config>>) -> Color {
         let config = config.read().unwrap();
         let ch = config.theme().code_highlighting();
+        if self.unmatched_bracket {
+            return ch.error().color().into();
+        }
         match &self.token_type {
             &TokenType::Whitespace { .. } => ch.whitespace().color().into(),
             &TokenType::Keyword { .. } => ch.keyword().color().into(),
@@ -59,6 +96,10 @@ impl EditorFileToken {
             &TokenType::Comment { .. } => ch.comment().color().into(),
             &TokenType::Operator { .. } => ch.operator().color().into(),
             &TokenType::Separator { .. } => ch.separator().color().into(),
+            &TokenType::Error { .. } => ch.error().color().into(),
+            &TokenType::DiffAdd { .. } => config.theme().diff().add.color().into(),
+            &TokenType::DiffDelete { .. } => config.theme().diff().delete.color().into(),
+            &TokenType::DiffChange { .. } => config.theme().diff().change.color().into(),
         }
     }
 
@@ -204,18 +245,22 @@ impl Widget for EditorFileToken {
         }
         let color: Color = self.token_to_color(&renderer.config());
         let chars: Vec<char> = self.token_type.text().chars().collect();
+        let mut line = self.token_type.line();
         for (index, c) in chars.iter().enumerate() {
             let last_in_line = self.last_in_line && index + 1 == chars.len();
             let mut text_character: TextCharacter = TextCharacter::new(
                 c.clone(),
-                self.token_type.start() + index,
-                self.token_type.line(),
+                self.token_type.char_span().lo + index,
+                line,
                 last_in_line,
                 color,
                 self.config.clone(),
             );
             text_character.prepare_ui(renderer);
             self.characters.push(text_character);
+            if *c == '\n' {
+                line += 1;
+            }
         }
     }
 }
@@ -251,7 +296,7 @@ mod tests {
     use super::*;
 
     use crate::tests::*;
-    use rider_lexers::Token;
+    use rider_lexers::{Span, Token};
     use sdl2::pixels::PixelFormatEnum;
     use sdl2::render::Texture;
     use sdl2::render::TextureCreator;
@@ -337,7 +382,7 @@ mod tests {
     fn assert_iterator() {
         let config = build_config();
         let token_type = TokenType::String {
-            token: Token::new("abcd".to_owned(), 0, 0, 0, 3),
+            token: Token::new("abcd".to_owned(), 0, 0, Span::new(0, 3), Span::new(0, 3)),
         };
         let token = EditorFileToken::new(&token_type, true, config.clone());
         for (i, c) in token.iter_char().enumerate() {
@@ -361,7 +406,7 @@ mod tests {
         let surface = Surface::new(1024, 800, PixelFormatEnum::RGBA8888).unwrap();
         let mut renderer = RendererMock::new(config.clone(), surface);
         let token_type = TokenType::Whitespace {
-            token: Token::new("".to_owned(), 0, 0, 0, 0),
+            token: Token::new("".to_owned(), 0, 0, Span::new(0, 0), Span::new(0, 0)),
         };
         let mut token = EditorFileToken::new(&token_type, false, config.clone());
         token.prepare_ui(&mut renderer);
@@ -373,7 +418,7 @@ mod tests {
         let surface = Surface::new(1024, 800, PixelFormatEnum::RGBA8888).unwrap();
         let mut renderer = RendererMock::new(config.clone(), surface);
         let token_type = TokenType::Keyword {
-            token: Token::new("".to_owned(), 0, 0, 0, 0),
+            token: Token::new("".to_owned(), 0, 0, Span::new(0, 0), Span::new(0, 0)),
         };
         let mut token = EditorFileToken::new(&token_type, false, config.clone());
         token.prepare_ui(&mut renderer);
@@ -385,7 +430,7 @@ mod tests {
         let surface = Surface::new(1024, 800, PixelFormatEnum::RGBA8888).unwrap();
         let mut renderer = RendererMock::new(config.clone(), surface);
         let token_type = TokenType::String {
-            token: Token::new("".to_owned(), 0, 0, 0, 0),
+            token: Token::new("".to_owned(), 0, 0, Span::new(0, 0), Span::new(0, 0)),
         };
         let mut token = EditorFileToken::new(&token_type, false, config.clone());
         token.prepare_ui(&mut renderer);
@@ -397,7 +442,7 @@ mod tests {
         let surface = Surface::new(1024, 800, PixelFormatEnum::RGBA8888).unwrap();
         let mut renderer = RendererMock::new(config.clone(), surface);
         let token_type = TokenType::Identifier {
-            token: Token::new("".to_owned(), 0, 0, 0, 0),
+            token: Token::new("".to_owned(), 0, 0, Span::new(0, 0), Span::new(0, 0)),
         };
         let mut token = EditorFileToken::new(&token_type, false, config.clone());
         token.prepare_ui(&mut renderer);
@@ -409,7 +454,7 @@ mod tests {
         let surface = Surface::new(1024, 800, PixelFormatEnum::RGBA8888).unwrap();
         let mut renderer = RendererMock::new(config.clone(), surface);
         let token_type = TokenType::Literal {
-            token: Token::new("".to_owned(), 0, 0, 0, 0),
+            token: Token::new("".to_owned(), 0, 0, Span::new(0, 0), Span::new(0, 0)),
         };
         let mut token = EditorFileToken::new(&token_type, false, config.clone());
         token.prepare_ui(&mut renderer);
@@ -421,7 +466,7 @@ mod tests {
         let surface = Surface::new(1024, 800, PixelFormatEnum::RGBA8888).unwrap();
         let mut renderer = RendererMock::new(config.clone(), surface);
         let token_type = TokenType::Comment {
-            token: Token::new("".to_owned(), 0, 0, 0, 0),
+            token: Token::new("".to_owned(), 0, 0, Span::new(0, 0), Span::new(0, 0)),
         };
         let mut token = EditorFileToken::new(&token_type, false, config.clone());
         token.prepare_ui(&mut renderer);
@@ -433,7 +478,7 @@ mod tests {
         let surface = Surface::new(1024, 800, PixelFormatEnum::RGBA8888).unwrap();
         let mut renderer = RendererMock::new(config.clone(), surface);
         let token_type = TokenType::Operator {
-            token: Token::new("".to_owned(), 0, 0, 0, 0),
+            token: Token::new("".to_owned(), 0, 0, Span::new(0, 0), Span::new(0, 0)),
         };
         let mut token = EditorFileToken::new(&token_type, false, config.clone());
         token.prepare_ui(&mut renderer);
@@ -445,7 +490,55 @@ mod tests {
         let surface = Surface::new(1024, 800, PixelFormatEnum::RGBA8888).unwrap();
         let mut renderer = RendererMock::new(config.clone(), surface);
         let token_type = TokenType::Separator {
-            token: Token::new("".to_owned(), 0, 0, 0, 0),
+            token: Token::new("".to_owned(), 0, 0, Span::new(0, 0), Span::new(0, 0)),
+        };
+        let mut token = EditorFileToken::new(&token_type, false, config.clone());
+        token.prepare_ui(&mut renderer);
+    }
+
+    #[test]
+    fn assert_error_to_color() {
+        let config = build_config();
+        let surface = Surface::new(1024, 800, PixelFormatEnum::RGBA8888).unwrap();
+        let mut renderer = RendererMock::new(config.clone(), surface);
+        let token_type = TokenType::Error {
+            token: Token::new("".to_owned(), 0, 0, Span::new(0, 0), Span::new(0, 0)),
+        };
+        let mut token = EditorFileToken::new(&token_type, false, config.clone());
+        token.prepare_ui(&mut renderer);
+    }
+
+    #[test]
+    fn assert_diff_add_to_color() {
+        let config = build_config();
+        let surface = Surface::new(1024, 800, PixelFormatEnum::RGBA8888).unwrap();
+        let mut renderer = RendererMock::new(config.clone(), surface);
+        let token_type = TokenType::DiffAdd {
+            token: Token::new("".to_owned(), 0, 0, Span::new(0, 0), Span::new(0, 0)),
+        };
+        let mut token = EditorFileToken::new(&token_type, false, config.clone());
+        token.prepare_ui(&mut renderer);
+    }
+
+    #[test]
+    fn assert_diff_delete_to_color() {
+        let config = build_config();
+        let surface = Surface::new(1024, 800, PixelFormatEnum::RGBA8888).unwrap();
+        let mut renderer = RendererMock::new(config.clone(), surface);
+        let token_type = TokenType::DiffDelete {
+            token: Token::new("".to_owned(), 0, 0, Span::new(0, 0), Span::new(0, 0)),
+        };
+        let mut token = EditorFileToken::new(&token_type, false, config.clone());
+        token.prepare_ui(&mut renderer);
+    }
+
+    #[test]
+    fn assert_diff_change_to_color() {
+        let config = build_config();
+        let surface = Surface::new(1024, 800, PixelFormatEnum::RGBA8888).unwrap();
+        let mut renderer = RendererMock::new(config.clone(), surface);
+        let token_type = TokenType::DiffChange {
+            token: Token::new("".to_owned(), 0, 0, Span::new(0, 0), Span::new(0, 0)),
         };
         let mut token = EditorFileToken::new(&token_type, false, config.clone());
         token.prepare_ui(&mut renderer);
@@ -459,7 +552,7 @@ mod tests {
     fn assert_is_last_in_line() {
         let config = build_config();
         let token = TokenType::String {
-            token: Token::new("".to_string(), 0, 0, 0, 0),
+            token: Token::new("".to_string(), 0, 0, Span::new(0, 0), Span::new(0, 0)),
         };
         let widget = EditorFileToken::new(&token, true, config);
         assert_eq!(widget.is_last_in_line(), true);
@@ -469,7 +562,7 @@ mod tests {
     fn assert_is_not_last_in_line() {
         let config = build_config();
         let token = TokenType::String {
-            token: Token::new("".to_string(), 0, 0, 0, 0),
+            token: Token::new("".to_string(), 0, 0, Span::new(0, 0), Span::new(0, 0)),
         };
         let widget = EditorFileToken::new(&token, false, config);
         assert_eq!(widget.is_last_in_line(), false);
@@ -479,7 +572,7 @@ mod tests {
     fn assert_is_new_line() {
         let config = build_config();
         let token = TokenType::Whitespace {
-            token: Token::new("\n".to_string(), 0, 0, 0, 0),
+            token: Token::new("\n".to_string(), 0, 0, Span::new(0, 0), Span::new(0, 0)),
         };
         let widget = EditorFileToken::new(&token, true, config);
         assert_eq!(widget.is_new_line(), true);
@@ -489,12 +582,39 @@ mod tests {
     fn assert_is_not_new_line() {
         let config = build_config();
         let token = TokenType::String {
-            token: Token::new("".to_string(), 0, 0, 0, 0),
+            token: Token::new("".to_string(), 0, 0, Span::new(0, 0), Span::new(0, 0)),
         };
         let widget = EditorFileToken::new(&token, false, config);
         assert_eq!(widget.is_new_line(), false);
     }
 
+    #[test]
+    fn assert_mark_unmatched_bracket() {
+        let config = build_config();
+        let token = TokenType::Separator {
+            token: Token::new("{".to_string(), 0, 2, Span::new(2, 3), Span::new(2, 3)),
+        };
+        let brackets = Brackets::new(vec![token.clone()].iter());
+        let mut widget = EditorFileToken::new(&token, false, config);
+        widget.mark_unmatched_bracket(&brackets);
+        assert_eq!(widget.is_unmatched_bracket(), true);
+    }
+
+    #[test]
+    fn assert_mark_matched_bracket() {
+        let config = build_config();
+        let open = TokenType::Separator {
+            token: Token::new("(".to_string(), 0, 0, Span::new(0, 1), Span::new(0, 1)),
+        };
+        let close = TokenType::Separator {
+            token: Token::new(")".to_string(), 0, 1, Span::new(1, 2), Span::new(1, 2)),
+        };
+        let brackets = Brackets::new(vec![open.clone(), close].iter());
+        let mut widget = EditorFileToken::new(&open, false, config);
+        widget.mark_unmatched_bracket(&brackets);
+        assert_eq!(widget.is_unmatched_bracket(), false);
+    }
+
     #[test]
     fn assert_empty_characters_update_position() {
         let config = build_config();
@@ -502,7 +622,7 @@ mod tests {
         //        let mut canvas = CanvasMock::new();
         let mut renderer = RendererMock::new(config.clone(), surface);
         let token = TokenType::String {
-            token: Token::new("".to_string(), 0, 0, 0, 0),
+            token: Token::new("".to_string(), 0, 0, Span::new(0, 0), Span::new(0, 0)),
         };
         let mut widget = EditorFileToken::new(&token, false, config);
         let mut rect = Rect::new(1, 2, 3, 4);
@@ -518,7 +638,13 @@ mod tests {
         //        let mut canvas = CanvasMock::new();
         let mut renderer = RendererMock::new(config.clone(), surface);
         let token = TokenType::String {
-            token: Token::new("foo bar".to_string(), 0, 0, 0, 0),
+            token: Token::new(
+                "foo bar".to_string(),
+                0,
+                0,
+                Span::new(0, 0),
+                Span::new(0, 0),
+            ),
         };
         let mut widget = EditorFileToken::new(&token, false, config);
         let mut rect = Rect::new(1, 2, 3, 4);
@@ -534,7 +660,13 @@ mod tests {
         //        let mut canvas = CanvasMock::new();
         let mut renderer = RendererMock::new(config.clone(), surface);
         let token = TokenType::String {
-            token: Token::new("foo bar".to_string(), 0, 0, 0, 0),
+            token: Token::new(
+                "foo bar".to_string(),
+                0,
+                0,
+                Span::new(0, 0),
+                Span::new(0, 0),
+            ),
         };
         let mut widget = EditorFileToken::new(&token, false, config);
         widget.prepare_ui(&mut renderer);
@@ -546,7 +678,7 @@ mod tests {
     fn assert_update_empty() {
         let config = build_config();
         let token = TokenType::String {
-            token: Token::new("".to_string(), 0, 0, 0, 0),
+            token: Token::new("".to_string(), 0, 0, Span::new(0, 0), Span::new(0, 0)),
         };
         let mut widget = EditorFileToken::new(&token, false, config);
         widget.update(0, &UpdateContext::Nothing);
@@ -560,7 +692,13 @@ mod tests {
         //        let mut canvas = CanvasMock::new();
         let mut renderer = RendererMock::new(config.clone(), surface);
         let token = TokenType::String {
-            token: Token::new("foo bar".to_string(), 0, 0, 0, 0),
+            token: Token::new(
+                "foo bar".to_string(),
+                0,
+                0,
+                Span::new(0, 0),
+                Span::new(0, 0),
+            ),
         };
         let mut widget = EditorFileToken::new(&token, false, config);
         widget.prepare_ui(&mut renderer);
@@ -575,7 +713,7 @@ mod tests {
         //        let mut canvas = CanvasMock::new();
         let mut renderer = RendererMock::new(config.clone(), surface);
         let token = TokenType::String {
-            token: Token::new("".to_string(), 0, 0, 0, 0),
+            token: Token::new("".to_string(), 0, 0, Span::new(0, 0), Span::new(0, 0)),
         };
         let mut widget = EditorFileToken::new(&token, false, config);
         widget.prepare_ui(&mut renderer);
@@ -590,7 +728,13 @@ mod tests {
         //        let mut canvas = CanvasMock::new();
         let mut renderer = RendererMock::new(config.clone(), surface);
         let token = TokenType::String {
-            token: Token::new("foo bar".to_string(), 0, 0, 0, 0),
+            token: Token::new(
+                "foo bar".to_string(),
+                0,
+                0,
+                Span::new(0, 0),
+                Span::new(0, 0),
+            ),
         };
         let mut widget = EditorFileToken::new(&token, false, config.clone());
         widget.prepare_ui(&mut renderer);
@@ -609,7 +753,13 @@ mod tests {
         //        let mut canvas = CanvasMock::new();
         let mut renderer = RendererMock::new(config.clone(), surface);
         let token = TokenType::String {
-            token: Token::new("foo bar".to_string(), 0, 0, 0, 0),
+            token: Token::new(
+                "foo bar".to_string(),
+                0,
+                0,
+                Span::new(0, 0),
+                Span::new(0, 0),
+            ),
         };
         let mut widget = EditorFileToken::new(&token, false, config.clone());
         widget.prepare_ui(&mut renderer);
@@ -628,7 +778,7 @@ mod tests {
         //        let mut canvas = CanvasMock::new();
         let mut renderer = RendererMock::new(config.clone(), surface);
         let token = TokenType::String {
-            token: Token::new("".to_string(), 0, 0, 0, 0),
+            token: Token::new("".to_string(), 0, 0, Span::new(0, 0), Span::new(0, 0)),
         };
         let mut widget = EditorFileToken::new(&token, false, config);
         widget.prepare_ui(&mut renderer);
@@ -644,7 +794,13 @@ mod tests {
         //        let mut canvas = CanvasMock::new();
         let mut renderer = RendererMock::new(config.clone(), surface);
         let token = TokenType::String {
-            token: Token::new("foo bar".to_string(), 0, 0, 0, 0),
+            token: Token::new(
+                "foo bar".to_string(),
+                0,
+                0,
+                Span::new(0, 0),
+                Span::new(0, 0),
+            ),
         };
         let mut widget = EditorFileToken::new(&token, false, config.clone());
         widget.prepare_ui(&mut renderer);
@@ -661,7 +817,7 @@ mod tests {
         let mut canvas = CanvasMock::new();
         let mut renderer = RendererMock::new(config.clone(), surface);
         let token = TokenType::String {
-            token: Token::new("".to_string(), 0, 0, 0, 0),
+            token: Token::new("".to_string(), 0, 0, Span::new(0, 0), Span::new(0, 0)),
         };
         let mut widget = EditorFileToken::new(&token, false, config);
         widget.prepare_ui(&mut renderer);
@@ -678,7 +834,13 @@ mod tests {
         let mut canvas = CanvasMock::new();
         let mut renderer = RendererMock::new(config.clone(), surface);
         let token = TokenType::String {
-            token: Token::new("foo bar".to_string(), 0, 0, 0, 0),
+            token: Token::new(
+                "foo bar".to_string(),
+                0,
+                0,
+                Span::new(0, 0),
+                Span::new(0, 0),
+            ),
         };
         let mut widget = EditorFileToken::new(&token, false, config.clone());
         widget.prepare_ui(&mut renderer);
@@ -694,7 +856,7 @@ mod tests {
         let mut canvas = CanvasMock::new();
         let mut renderer = RendererMock::new(config.clone(), surface);
         let token = TokenType::Whitespace {
-            token: Token::new("\n".to_string(), 0, 0, 0, 0),
+            token: Token::new("\n".to_string(), 0, 0, Span::new(0, 0), Span::new(0, 0)),
         };
         let mut widget = EditorFileToken::new(&token, false, config.clone());
         widget.prepare_ui(&mut renderer);
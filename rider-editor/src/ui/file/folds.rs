@@ -0,0 +1,212 @@
+use rider_lexers::folding::FoldRegion;
+
+/// Foldable regions of file and start lines of regions which are currently folded
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Folds {
+    regions: Vec<FoldRegion>,
+    folded: Vec<usize>,
+}
+
+impl Folds {
+    pub fn new(mut regions: Vec<FoldRegion>) -> Self {
+        regions.sort();
+        Self {
+            regions,
+            folded: vec![],
+        }
+    }
+
+    pub fn regions(&self) -> &Vec<FoldRegion> {
+        &self.regions
+    }
+
+    pub fn folded(&self) -> &Vec<usize> {
+        &self.folded
+    }
+
+    pub fn is_folded(&self, start_line: usize) -> bool {
+        self.folded.binary_search(&start_line).is_ok()
+    }
+
+    pub fn is_hidden(&self, line: usize) -> bool {
+        self.folded_regions().any(|region| region.hides(line))
+    }
+
+    /// Fold innermost region around line which is not folded yet
+    pub fn fold(&mut self, line: usize) -> bool {
+        let start_line = match self
+            .regions_around(line)
+            .filter(|region| !self.is_folded(region.start_line))
+            .last()
+        {
+            Some(region) => region.start_line,
+            _ => return false,
+        };
+        self.set_folded(start_line);
+        true
+    }
+
+    /// Unfold innermost folded region around line
+    pub fn unfold(&mut self, line: usize) -> bool {
+        let start_line = match self
+            .regions_around(line)
+            .filter(|region| self.is_folded(region.start_line))
+            .last()
+        {
+            Some(region) => region.start_line,
+            _ => return false,
+        };
+        self.folded.retain(|folded| *folded != start_line);
+        true
+    }
+
+    pub fn fold_all(&mut self) {
+        self.folded = self.regions.iter().map(|r| r.start_line).collect();
+    }
+
+    pub fn unfold_all(&mut self) {
+        self.folded.clear();
+    }
+
+    /// Fold every region nested at given level, 1 is top level
+    pub fn fold_level(&mut self, level: usize) {
+        let start_lines: Vec<usize> = self
+            .regions
+            .iter()
+            .filter(|region| region.level == level)
+            .map(|region| region.start_line)
+            .collect();
+        for start_line in start_lines {
+            self.set_folded(start_line);
+        }
+    }
+
+    /// Line itself or start of outermost folded region which hides it
+    pub fn visible_line(&self, line: usize) -> usize {
+        self.folded_regions()
+            .filter(|region| region.hides(line))
+            .map(|region| region.start_line)
+            .min()
+            .unwrap_or(line)
+    }
+
+    pub fn next_visible_line(&self, line: usize) -> usize {
+        let mut next = line + 1;
+        while self.is_hidden(next) {
+            next += 1;
+        }
+        next
+    }
+
+    /// Line displayed in given row when folded lines take no space
+    pub fn line_at_row(&self, row: usize) -> usize {
+        (0..row).fold(0, |line, _| self.next_visible_line(line))
+    }
+
+    pub fn previous_visible_line(&self, line: usize) -> Option<usize> {
+        line.checked_sub(1)
+            .map(|previous| self.visible_line(previous))
+    }
+
+    /// Keeps folds of previous version of file after edit at given line moved lines by `delta`
+    pub fn restore(&mut self, previous: &Folds, edited_line: usize, delta: isize) {
+        for start_line in previous.folded.iter() {
+            let start_line = if *start_line > edited_line {
+                *start_line as isize + delta
+            } else {
+                *start_line as isize
+            };
+            if start_line < 0 {
+                continue;
+            }
+            let start_line = start_line as usize;
+            if self.regions.iter().any(|r| r.start_line == start_line) {
+                self.set_folded(start_line);
+            }
+        }
+    }
+
+    fn set_folded(&mut self, start_line: usize) {
+        if let Err(index) = self.folded.binary_search(&start_line) {
+            self.folded.insert(index, start_line);
+        }
+    }
+
+    fn folded_regions<'a>(&'a self) -> impl Iterator<Item = &'a FoldRegion> {
+        self.regions
+            .iter()
+            .filter(move |region| self.is_folded(region.start_line))
+    }
+
+    fn regions_around<'a>(&'a self, line: usize) -> impl Iterator<Item = &'a FoldRegion> {
+        self.regions
+            .iter()
+            .filter(move |region| region.contains(line))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region(start_line: usize, end_line: usize, level: usize) -> FoldRegion {
+        FoldRegion {
+            start_line,
+            end_line,
+            level,
+        }
+    }
+
+    fn build_folds() -> Folds {
+        Folds::new(vec![region(5, 6, 1), region(0, 3, 1), region(1, 2, 2)])
+    }
+
+    #[test]
+    fn assert_fold_innermost_first() {
+        let mut folds = build_folds();
+        assert_eq!(folds.fold(2), true);
+        assert_eq!(folds.folded(), &vec![1]);
+        assert_eq!(folds.fold(2), true);
+        assert_eq!(folds.folded(), &vec![0, 1]);
+        assert_eq!(folds.fold(2), false);
+        assert_eq!(folds.fold(4), false);
+    }
+
+    #[test]
+    fn assert_unfold_innermost_first() {
+        let mut folds = build_folds();
+        folds.fold_all();
+        assert_eq!(folds.unfold(1), true);
+        assert_eq!(folds.folded(), &vec![0, 5]);
+        assert_eq!(folds.unfold(1), true);
+        assert_eq!(folds.folded(), &vec![5]);
+        assert_eq!(folds.unfold(1), false);
+    }
+
+    #[test]
+    fn assert_hidden_lines() {
+        let mut folds = build_folds();
+        folds.fold_level(2);
+        assert_eq!(folds.is_hidden(1), false);
+        assert_eq!(folds.is_hidden(2), true);
+        assert_eq!(folds.is_hidden(3), false);
+        folds.fold_all();
+        assert_eq!(folds.visible_line(2), 0);
+        assert_eq!(folds.next_visible_line(0), 4);
+        assert_eq!(folds.previous_visible_line(4), Some(0));
+        assert_eq!(folds.previous_visible_line(0), None);
+        assert_eq!(folds.line_at_row(1), 4);
+        assert_eq!(folds.line_at_row(2), 5);
+        folds.unfold_all();
+        assert_eq!(folds.is_hidden(2), false);
+    }
+
+    #[test]
+    fn assert_restore_moves_folds_after_edit() {
+        let mut previous = build_folds();
+        previous.fold_all();
+        let mut folds = Folds::new(vec![region(0, 3, 1), region(2, 3, 2), region(6, 7, 1)]);
+        folds.restore(&previous, 1, 1);
+        assert_eq!(folds.folded(), &vec![0, 6]);
+    }
+}
@@ -3,10 +3,12 @@ use sdl2::rect::Rect;
 pub mod editor_file;
 pub mod editor_file_section;
 pub mod editor_file_token;
+pub mod folds;
 
 pub use crate::ui::file::editor_file::*;
 pub use crate::ui::file::editor_file_section::*;
 pub use crate::ui::file::editor_file_token::*;
+pub use crate::ui::file::folds::*;
 use crate::ui::TextCharacter;
 
 pub trait TextCollection {
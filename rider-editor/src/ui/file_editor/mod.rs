@@ -1,4 +1,5 @@
 use crate::app::*;
+use crate::lsp::TextChange;
 use crate::renderer::renderer::Renderer;
 use crate::ui::caret::caret::Caret;
 use crate::ui::caret::caret_position::CaretPosition;
@@ -14,11 +15,20 @@ use crate::ui::RenderContext;
 use crate::ui::UpdateContext;
 use crate::ui::{move_render_point, ScrollView};
 use crate::ui::{CanvasAccess, Widget};
+use sdl2::pixels::Color;
 use sdl2::rect::Point;
 use sdl2::rect::Rect;
+use std::cmp;
+use std::collections::HashSet;
 use std::mem;
 use std::sync::*;
 
+const SQUIGGLE_HEIGHT: i32 = 2;
+const DIAGNOSTIC_MARKER_HEIGHT: u32 = 3;
+const GIT_MARKER_WIDTH: u32 = 3;
+const GIT_MARKER_MARGIN: i32 = 2;
+const GIT_DELETED_MARKER_HEIGHT: u32 = 2;
+
 pub trait FileAccess {
     fn has_file(&self) -> bool {
         self.file().is_some()
@@ -51,6 +61,10 @@ pub trait CaretAccess: FileAccess {
         };
     }
 
+    fn move_caret_to_matching_bracket(&mut self) {
+        caret_manager::move_caret_to_matching_bracket(self);
+    }
+
     fn set_caret_to_end_of_line(&mut self, line: i32) {
         let file = match self.file_mut() {
             Some(f) => f,
@@ -91,6 +105,7 @@ pub struct FileEditor {
     config: ConfigAccess,
     vertical_scroll_bar: VerticalScrollBar,
     horizontal_scroll_bar: HorizontalScrollBar,
+    changes: Vec<TextChange>,
 }
 
 impl Widget for FileEditor {
@@ -198,11 +213,17 @@ impl Widget for FileEditor {
             canvas.set_clipping(self.dest.clone());
         }
         match self.file() {
-            Some(file) => file.render(
-                canvas,
-                renderer,
-                &RenderContext::ParentPosition(self.render_start_point() + self.scroll()),
-            ),
+            Some(file) => {
+                self.render_conflicts(file, canvas, self.render_start_point() + self.scroll());
+                file.render(
+                    canvas,
+                    renderer,
+                    &RenderContext::ParentPosition(self.render_start_point() + self.scroll()),
+                );
+                self.render_fold_markers(file, canvas, self.render_start_point() + self.scroll());
+                self.render_bracket_pair(file, canvas, self.render_start_point() + self.scroll());
+                self.render_diagnostics(file, canvas, self.render_start_point() + self.scroll());
+            }
             _ => (),
         };
         self.caret.render(
@@ -214,6 +235,10 @@ impl Widget for FileEditor {
             .render(canvas, &RenderContext::ParentPosition(self.dest.top_left()));
         self.horizontal_scroll_bar
             .render(canvas, &RenderContext::ParentPosition(self.dest.top_left()));
+        if let Some(file) = self.file() {
+            self.render_diagnostic_markers(file, canvas);
+            self.render_git_markers(file, canvas, self.render_start_point() + self.scroll());
+        }
     }
 
     fn prepare_ui<T>(&mut self, renderer: &mut T)
@@ -244,6 +269,7 @@ impl FileEditor {
             vertical_scroll_bar: VerticalScrollBar::new(Arc::clone(&config)),
             horizontal_scroll_bar: HorizontalScrollBar::new(Arc::clone(&config)),
             file: None,
+            changes: vec![],
             config,
         }
     }
@@ -283,6 +309,346 @@ impl FileEditor {
         file_content_manager::delete_current_line(self, renderer)
     }
 
+    /// Restores buffer from before last edit, returns false when there is nothing to undo
+    pub fn undo<R>(&mut self, renderer: &mut R) -> bool
+    where
+        R: ConfigHolder + CharacterSizeManager + Renderer,
+    {
+        file_content_manager::undo(self, renderer)
+    }
+
+    pub fn fold_current(&mut self) {
+        let line = self.caret.line_number();
+        self.change_folds(|file| {
+            file.fold(line);
+        });
+    }
+
+    pub fn unfold_current(&mut self) {
+        let line = self.caret.line_number();
+        self.change_folds(|file| {
+            file.unfold(line);
+        });
+    }
+
+    pub fn fold_all(&mut self) {
+        self.change_folds(|file| file.fold_all());
+    }
+
+    pub fn unfold_all(&mut self) {
+        self.change_folds(|file| file.unfold_all());
+    }
+
+    pub fn fold_level(&mut self, level: usize) {
+        self.change_folds(|file| file.fold_level(level));
+    }
+
+    /// Moves caret to character at given position, folds which hide it are opened
+    pub fn jump_to(&mut self, position: usize) {
+        let line = match self.file().and_then(|file| file.get_character_at(position)) {
+            Some(c) => c.line(),
+            _ => return,
+        };
+        self.change_folds(
+            |file| {
+                while file.folds().is_hidden(line) && file.unfold(line) {}
+            },
+        );
+        if caret_manager::move_caret_to_position(self, position).is_some() {
+            self.scroll_to_caret();
+        }
+    }
+
+    /// Records edit of current file, changes are kept until taken
+    pub fn push_change(&mut self, change: TextChange) {
+        self.changes.push(change);
+    }
+
+    /// Edits of current file since last call in order they were made
+    pub fn take_changes(&mut self) -> Vec<TextChange> {
+        mem::replace(&mut self.changes, vec![])
+    }
+
+    /// Caret rectangle in window coordinates
+    pub fn caret_window_rect(&self) -> Rect {
+        move_render_point(self.render_start_point() + self.scroll(), self.caret.dest())
+    }
+
+    /// Scrolls vertically only when caret is outside of visible area
+    fn scroll_to_caret(&mut self) {
+        let y = self.caret.dest().y();
+        let height = self.caret.dest().height() as i32;
+        let viewport = self.vertical_scroll_bar.viewport() as i32;
+        let scroll = self.vertical_scroll_value();
+        if y < scroll || y + height > scroll + viewport {
+            self.vertical_scroll_bar
+                .scroll_to(cmp::max(0, y - viewport / 3));
+        }
+    }
+
+    /// Applies fold change and moves caret out of hidden lines
+    fn change_folds<F>(&mut self, change: F)
+    where
+        F: FnOnce(&mut EditorFile),
+    {
+        let (line, character) = match self.file.as_mut() {
+            Some(file) => {
+                change(file);
+                self.full_rect = file.full_rect();
+                (
+                    file.folds().visible_line(self.caret.line_number()),
+                    file.get_character_at(self.caret.text_position()),
+                )
+            }
+            _ => return,
+        };
+        match character {
+            Some(ref c) if line == self.caret.line_number() && !c.is_hidden() => {
+                let position = self.caret.position().clone();
+                self.caret.move_caret(position, c.dest().top_left());
+            }
+            _ => self.set_caret_to_end_of_line(line as i32),
+        }
+    }
+
+    /// Draws marker after first line of every fold region, filled when region is folded
+    fn render_fold_markers<C>(&self, file: &EditorFile, canvas: &mut C, render_point: Point)
+    where
+        C: CanvasAccess,
+    {
+        let size = file.line_height() / 2;
+        if size == 0 || file.folds().regions().is_empty() {
+            return;
+        }
+        let color: Color = self.config.read().unwrap().theme().border_color().into();
+        let start_lines: HashSet<usize> = file
+            .folds()
+            .regions()
+            .iter()
+            .map(|region| region.start_line)
+            .collect();
+        for c in file.iter_char() {
+            if !c.is_new_line() || c.is_hidden() || !start_lines.contains(&c.line()) {
+                continue;
+            }
+            let marker = Rect::new(
+                c.dest().x() + c.source().width() as i32 + size as i32 / 2,
+                c.dest().y() + size as i32 / 2,
+                size,
+                size,
+            );
+            let dest = move_render_point(render_point, &marker);
+            let result = if file.folds().is_folded(c.line()) {
+                canvas.render_rect(dest, color)
+            } else {
+                canvas.render_border(dest, color)
+            };
+            result.expect("Failed to draw fold marker");
+        }
+    }
+
+    /// Draws border around bracket next to caret and its pair
+    fn render_bracket_pair<C>(&self, file: &EditorFile, canvas: &mut C, render_point: Point)
+    where
+        C: CanvasAccess,
+    {
+        let (first, second) = match file.bracket_pair_near(self.caret.text_position()) {
+            Some(pair) => pair,
+            _ => return,
+        };
+        let color: Color = self
+            .config
+            .read()
+            .unwrap()
+            .theme()
+            .caret()
+            .bright()
+            .color()
+            .into();
+        for position in [first, second].iter() {
+            if let Some(text_character) = file.get_character_at(*position) {
+                canvas
+                    .render_border(
+                        move_render_point(render_point, text_character.dest()),
+                        color,
+                    )
+                    .expect("Failed to draw bracket pair border");
+            }
+        }
+    }
+
+    /// Draws wavy line below characters with diagnostic, most severe one is drawn last
+    fn render_diagnostics<C>(&self, file: &EditorFile, canvas: &mut C, render_point: Point)
+    where
+        C: CanvasAccess,
+    {
+        if file.diagnostics().is_empty() {
+            return;
+        }
+        let config = self.config.read().unwrap();
+        for diagnostic in file.diagnostics().iter().rev() {
+            let (start, end) = match file.diagnostic_range(diagnostic) {
+                Some(range) => range,
+                _ => continue,
+            };
+            let color = diagnostic.severity.color(config.theme());
+            for c in file.iter_char() {
+                if c.position() < start || c.position() >= end || c.is_hidden() {
+                    continue;
+                }
+                render_squiggle(canvas, move_render_point(render_point, c.dest()), color);
+            }
+        }
+    }
+
+    /// Draws marker of every diagnostic next to vertical scroll bar at its line
+    fn render_diagnostic_markers<C>(&self, file: &EditorFile, canvas: &mut C)
+    where
+        C: CanvasAccess,
+    {
+        if file.diagnostics().is_empty() {
+            return;
+        }
+        let config = self.config.read().unwrap();
+        let lines = file.lines_count() as u32;
+        let height = self.dest.height();
+        let mut diagnostics: Vec<&Diagnostic> = file.diagnostics().iter().collect();
+        diagnostics.sort_by_key(|diagnostic| cmp::Reverse(diagnostic.severity));
+        for diagnostic in diagnostics {
+            let y = (diagnostic.location.line as u32).min(lines - 1) * height / lines;
+            let marker = Rect::new(
+                self.vertical_scroll_bar.rect().x(),
+                y as i32,
+                self.vertical_scroll_bar.rect().width(),
+                DIAGNOSTIC_MARKER_HEIGHT,
+            );
+            canvas
+                .render_rect(
+                    move_render_point(self.dest.top_left(), &marker),
+                    diagnostic.severity.color(config.theme()),
+                )
+                .expect("Failed to draw diagnostic marker");
+        }
+    }
+
+    /// Fills background of merge conflict lines with color of their side
+    fn render_conflicts<C>(&self, file: &EditorFile, canvas: &mut C, render_point: Point)
+    where
+        C: CanvasAccess,
+    {
+        let line_height = file.line_height();
+        if file.conflicts().is_empty() || line_height == 0 {
+            return;
+        }
+        let tops = file.line_tops(render_point.y());
+        let (ours, theirs, base, marker): (Color, Color, Color, Color) = {
+            let config = self.config.read().unwrap();
+            let conflict = config.theme().conflict();
+            (
+                conflict.ours.color().into(),
+                conflict.theirs.color().into(),
+                conflict.base.color().into(),
+                conflict.marker.color().into(),
+            )
+        };
+        let mut render_line = |line: usize, color: Color| {
+            if let Some(top) = tops.get(line).cloned().unwrap_or(None) {
+                let rect = Rect::new(self.dest.x(), top, self.dest.width(), line_height);
+                canvas
+                    .render_rect(rect, color)
+                    .expect("Failed to draw conflict background");
+            }
+        };
+        for conflict in file.conflicts() {
+            for line in conflict.marker_lines() {
+                render_line(line, marker);
+            }
+            conflict
+                .ours
+                .clone()
+                .for_each(|line| render_line(line, ours));
+            if let Some(lines) = conflict.base.clone() {
+                lines.for_each(|line| render_line(line, base));
+            }
+            conflict
+                .theirs
+                .clone()
+                .for_each(|line| render_line(line, theirs));
+        }
+    }
+
+    /// Draws marker of every line changed since HEAD in gutter left of text.
+    ///
+    /// Deleted lines are marked with thin bar between lines surrounding them.
+    fn render_git_markers<C>(&self, file: &EditorFile, canvas: &mut C, render_point: Point)
+    where
+        C: CanvasAccess,
+    {
+        let line_height = file.line_height();
+        if file.hunks().is_empty() || line_height == 0 {
+            return;
+        }
+        let tops = file.line_tops(render_point.y());
+        let (add, change, delete): (Color, Color, Color) = {
+            let config = self.config.read().unwrap();
+            let diff = config.theme().diff();
+            (
+                diff.add.color().into(),
+                diff.change.color().into(),
+                diff.delete.color().into(),
+            )
+        };
+        let x = self.dest.x() - GIT_MARKER_WIDTH as i32 - GIT_MARKER_MARGIN;
+        let clipping = canvas.clip_rect();
+        canvas.set_clipping(Rect::new(
+            x,
+            self.dest.y(),
+            GIT_MARKER_WIDTH,
+            self.dest.height(),
+        ));
+        for hunk in file.hunks() {
+            let color = match hunk.kind() {
+                HunkKind::Added => add,
+                HunkKind::Modified => change,
+                HunkKind::Deleted => {
+                    if let Some(top) = tops.get(hunk.new_start).cloned().unwrap_or(None) {
+                        let marker = Rect::new(
+                            x,
+                            top - GIT_DELETED_MARKER_HEIGHT as i32 / 2,
+                            GIT_MARKER_WIDTH,
+                            GIT_DELETED_MARKER_HEIGHT,
+                        );
+                        canvas
+                            .render_rect(marker, delete)
+                            .expect("Failed to draw git marker");
+                    }
+                    continue;
+                }
+            };
+            for line in hunk.new_start..hunk.new_start + hunk.new_lines {
+                if let Some(top) = tops.get(line).cloned().unwrap_or(None) {
+                    let marker = Rect::new(x, top, GIT_MARKER_WIDTH, line_height);
+                    canvas
+                        .render_rect(marker, color)
+                        .expect("Failed to draw git marker");
+                }
+            }
+        }
+        canvas.set_clip_rect(clipping);
+    }
+
+    /// Char position of visible character at window point
+    pub fn position_at_point(&self, point: &Point) -> Option<usize> {
+        let file = self.file()?;
+        let moved_by = self
+            .scroll()
+            .offset(self.render_start_point().x(), self.render_start_point().y());
+        let scroll_context = UpdateContext::ScrolledBy(moved_by);
+        file.iter_char()
+            .find(|c| c.is_left_click_target(point, &scroll_context))
+            .map(|c| c.position())
+    }
+
     fn is_text_character_clicked(&self, point: &Point) -> bool {
         let file = match self.file() {
             Some(f) => f,
@@ -314,11 +680,34 @@ impl FileEditor {
         match (y, file.line_height()) {
             (y, _) if y <= 0 => 0,
             (_, 0) => 0,
-            (_, line_height) => y / (line_height as i32),
+            (_, line_height) => {
+                file.folds()
+                    .line_at_row((y / (line_height as i32)) as usize) as i32
+            }
         }
     }
 }
 
+/// Zigzag line along bottom edge of rect
+fn render_squiggle<C>(canvas: &mut C, rect: Rect, color: Color)
+where
+    C: CanvasAccess,
+{
+    let bottom = rect.bottom() - 1;
+    let top = bottom - SQUIGGLE_HEIGHT;
+    let mut x = rect.left();
+    let mut rising = true;
+    while x < rect.right() {
+        let next = cmp::min(x + SQUIGGLE_HEIGHT, rect.right());
+        let (from, to) = if rising { (bottom, top) } else { (top, bottom) };
+        canvas
+            .render_line(Point::new(x, from), Point::new(next, to), color)
+            .expect("Failed to draw diagnostic line");
+        rising = !rising;
+        x = next;
+    }
+}
+
 impl ScrollView<VerticalScrollBar, HorizontalScrollBar> for FileEditor {
     fn mut_horizontal_scroll_handler(&mut self) -> Option<&mut HorizontalScrollBar> {
         Some(&mut self.horizontal_scroll_bar)
@@ -632,11 +1021,16 @@ mod tests {
 
 #[cfg(test)]
 mod own_methods_tests {
+    use crate::app::{Diagnostic, DiagnosticSeverity, Location};
+    use crate::lsp::*;
     use crate::tests::*;
     use crate::ui::{CaretAccess, EditorFile, FileAccess, FileEditor};
+    use crate::ui::{CaretPosition, RenderContext, Widget};
     use rider_config::ConfigAccess;
     use rider_derive::*;
-    use sdl2::rect::Point;
+    use sdl2::pixels::Color;
+    use sdl2::rect::{Point, Rect};
+    use std::sync::Arc;
 
     fn build_testable_file<S>(buffer: S, config: ConfigAccess) -> EditorFile
     where
@@ -687,6 +1081,150 @@ mod own_methods_tests {
         assert_eq!(buffer, "hello world foo bar".to_owned());
     }
 
+    #[test]
+    fn record_changes_of_edits() {
+        build_test_renderer!(renderer);
+        let mut widget = FileEditor::new(config.clone());
+        widget.open_file(build_testable_file("foo bar", config));
+        widget.insert_text("a".to_owned(), &mut renderer);
+        widget.caret_mut().set_text_position(8);
+        widget.delete_front(&mut renderer);
+        let result = widget.take_changes();
+        let expected = vec![
+            TextChange {
+                range: Range::new(Position::new(0, 0), Position::new(0, 0)),
+                text: "a".to_owned(),
+            },
+            TextChange {
+                range: Range::new(Position::new(0, 7), Position::new(0, 8)),
+                text: "".to_owned(),
+            },
+        ];
+        assert_eq!(result, expected);
+        assert_eq!(widget.take_changes(), vec![]);
+    }
+
+    #[test]
+    fn render_diagnostics_kept_after_edit() {
+        build_test_renderer!(renderer);
+        let mut widget = FileEditor::new(config.clone());
+        let mut file = build_testable_file("foo bar", config.clone());
+        file.set_diagnostics(vec![Diagnostic {
+            location: Location {
+                path: file.path(),
+                line: 0,
+                column: 4,
+            },
+            end_line: 0,
+            end_column: 7,
+            severity: DiagnosticSeverity::Error,
+            message: "unknown".to_owned(),
+            source: String::new(),
+        }]);
+        widget.open_file(file);
+        widget.prepare_ui(&mut renderer);
+        widget.insert_text("a".to_owned(), &mut renderer);
+        assert_eq!(widget.file().map(|f| f.diagnostics().len()), Some(1));
+
+        let mut canvas = CanvasMock::new();
+        widget.render(&mut canvas, &mut renderer, &RenderContext::Nothing);
+        let color: Color = config
+            .read()
+            .unwrap()
+            .theme()
+            .code_highlighting()
+            .error()
+            .color()
+            .into();
+        assert_eq!(canvas.lines.iter().any(|line| line.color == color), true);
+        assert_eq!(canvas.rects.iter().any(|rect| rect.color == color), true);
+    }
+
+    #[test]
+    fn render_git_markers_updated_after_edit() {
+        build_test_renderer!(renderer);
+        let mut widget = FileEditor::new(config.clone());
+        let mut file = build_testable_file("a\nB\nc\nd", config.clone());
+        file.set_git_base(Some(Arc::new("a\nb\nc".to_owned())));
+        widget.open_file(file);
+        widget.prepare_ui(&mut renderer);
+
+        let (add, change): (Color, Color) = {
+            let config = config.read().unwrap();
+            let diff = config.theme().diff();
+            (diff.add.color().into(), diff.change.color().into())
+        };
+        let mut canvas = CanvasMock::new();
+        widget.render(&mut canvas, &mut renderer, &RenderContext::Nothing);
+        let (x, top) = (widget.dest().x() - 5, widget.dest().y());
+        let markers: Vec<&RendererRect> = canvas
+            .rects
+            .iter()
+            .filter(|rect| rect.rect.x() == x)
+            .collect();
+        assert_eq!(
+            markers,
+            vec![
+                &RendererRect::new(
+                    Rect::new(x, top + 14, 3, 14),
+                    change,
+                    CanvasShape::Rectangle
+                ),
+                &RendererRect::new(Rect::new(x, top + 42, 3, 14), add, CanvasShape::Rectangle),
+            ]
+        );
+
+        widget.insert_text("x".to_owned(), &mut renderer);
+        assert_eq!(widget.file().map(|f| f.hunks().len()), Some(2));
+        assert_eq!(
+            widget.file().and_then(|f| f.hunk_at_line(0)).is_some(),
+            false
+        );
+        widget.file_mut().unwrap().refresh_hunks();
+        assert_eq!(
+            widget.file().and_then(|f| f.hunk_at_line(0)).is_some(),
+            true
+        );
+    }
+
+    #[test]
+    fn render_conflict_backgrounds() {
+        build_test_renderer!(renderer);
+        let mut widget = FileEditor::new(config.clone());
+        let file = build_testable_file("<<<<<<< a\nb\n=======\nc\n>>>>>>> d", config.clone());
+        widget.open_file(file);
+        widget.prepare_ui(&mut renderer);
+
+        let (ours, theirs, marker): (Color, Color, Color) = {
+            let config = config.read().unwrap();
+            let conflict = config.theme().conflict();
+            (
+                conflict.ours.color().into(),
+                conflict.theirs.color().into(),
+                conflict.marker.color().into(),
+            )
+        };
+        let mut canvas = CanvasMock::new();
+        widget.render(&mut canvas, &mut renderer, &RenderContext::Nothing);
+        let (x, top, width) = (widget.dest().x(), widget.dest().y(), widget.dest().width());
+        let backgrounds: Vec<(i32, Color)> = canvas
+            .rects
+            .iter()
+            .filter(|rect| rect.rect.x() == x && rect.rect.width() == width)
+            .map(|rect| (rect.rect.y() - top, rect.color))
+            .collect();
+        assert_eq!(
+            backgrounds,
+            vec![
+                (0, marker),
+                (28, marker),
+                (56, marker),
+                (14, ours),
+                (42, theirs),
+            ]
+        );
+    }
+
     #[test]
     fn insert_new_line() {
         build_test_renderer!(renderer);
@@ -737,4 +1275,46 @@ mod own_methods_tests {
         widget.open_file(build_testable_file("foo bar", config));
         assert_eq!(widget.resolve_line_from_point(&Point::new(100, 100)), 0);
     }
+
+    #[test]
+    fn fold_current_moves_caret_out_of_hidden_lines() {
+        build_test_renderer!(renderer);
+        let mut widget = FileEditor::new(config.clone());
+        let mut file = EditorFile::new(
+            "test.rs".to_owned(),
+            "fn a() {\n    b\n}".to_owned(),
+            config,
+        );
+        file.prepare_ui(&mut renderer);
+        widget.open_file(file);
+        widget.prepare_ui(&mut renderer);
+        widget
+            .caret_mut()
+            .move_caret(CaretPosition::new(13, 1, 4), Point::new(0, 0));
+        widget.fold_current();
+        assert_eq!(widget.file().unwrap().folds().is_hidden(1), true);
+        assert_eq!(widget.caret().line_number(), 0);
+        widget.unfold_all();
+        assert_eq!(widget.file().unwrap().folds().is_hidden(1), false);
+    }
+
+    #[test]
+    fn jump_to_unfolds_hidden_position() {
+        build_test_renderer!(renderer);
+        let mut widget = FileEditor::new(config.clone());
+        let mut file = EditorFile::new(
+            "test.rs".to_owned(),
+            "fn a() {\n    fn b() {}\n}".to_owned(),
+            config,
+        );
+        file.prepare_ui(&mut renderer);
+        widget.open_file(file);
+        widget.prepare_ui(&mut renderer);
+        widget.fold_all();
+        assert_eq!(widget.file().unwrap().folds().is_hidden(1), true);
+        widget.jump_to(16);
+        assert_eq!(widget.file().unwrap().folds().is_hidden(1), false);
+        assert_eq!(widget.caret().text_position(), 16);
+        assert_eq!(widget.caret().line_number(), 1);
+    }
 }
@@ -21,6 +21,8 @@ pub struct DirectoryView {
     directories: Vec<DirectoryView>,
     name_label: Label,
     icon: Icon,
+    git_badge: GitBadge,
+    git_statuses: Option<Arc<GitStatuses>>,
 }
 
 impl std::ops::Deref for DirectoryView {
@@ -179,6 +181,9 @@ impl Widget for DirectoryView {
                 dest.y(),
             )),
         );
+        let name_end = dest.x() + (self.icon_width() + self.name_width()) as i32 + NAME_MARGIN;
+        self.git_badge
+            .render(canvas, renderer, Point::new(name_end, dest.y()));
 
         self.render_children::<C, R>(canvas, renderer, &mut dest);
     }
@@ -236,6 +241,8 @@ impl DirectoryView {
                 Rect::new(0, 0, DEFAULT_ICON_SIZE, DEFAULT_ICON_SIZE),
             ),
             name_label: Label::new(name, config.clone()),
+            git_badge: GitBadge::new(config.clone()),
+            git_statuses: None,
             icon: Icon::new(
                 config,
                 dir_texture_path,
@@ -305,8 +312,107 @@ impl DirectoryView {
         }
     }
 
-    pub fn refresh(&mut self) {
-        unimplemented!()
+    pub fn git_status(&self) -> Option<GitStatus> {
+        self.git_badge.status()
+    }
+
+    /// Shows git status of directory and its children, children read later get it too
+    pub fn set_git_statuses<R>(&mut self, statuses: &Arc<GitStatuses>, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        self.git_badge
+            .set_status(statuses.directory_status(self.path.as_str()), renderer);
+        for dir in self.directories.iter_mut() {
+            dir.set_git_statuses(statuses, renderer);
+        }
+        for file in self.files.iter_mut() {
+            file.set_git_status(statuses.file_status(file.path().as_str()), renderer);
+        }
+        self.git_statuses = Some(Arc::clone(statuses));
+    }
+
+    pub fn files(&self) -> &Vec<FileEntry> {
+        &self.files
+    }
+
+    pub fn directories(&self) -> &Vec<DirectoryView> {
+        &self.directories
+    }
+
+    /// Reads opened directory and its opened children again.
+    ///
+    /// Entries which still exist keep their views, so expanded directories stay expanded.
+    pub fn refresh<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if !self.opened {
+            return;
+        }
+        self.read_directory(renderer);
+        for dir in self.directories.iter_mut() {
+            dir.refresh(renderer);
+        }
+        self.calculate_size(renderer);
+    }
+
+    /// Refreshes directory with given path if it's shown, returns false when it's not below this one
+    pub fn refresh_directory<R>(&mut self, dir_path: &str, renderer: &mut R) -> bool
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if dir_path == self.path {
+            self.refresh(renderer);
+            return true;
+        }
+        if !self.opened || !dir_path.starts_with((self.path.clone() + "/").as_str()) {
+            return false;
+        }
+        for dir in self.directories.iter_mut() {
+            if dir.refresh_directory(dir_path, renderer) {
+                break;
+            }
+        }
+        self.calculate_size(renderer);
+        true
+    }
+
+    /// Opens and expands directory with given path and its parents, expanded ones are kept
+    pub fn expand_directory<R>(&mut self, dir_path: &str, renderer: &mut R) -> bool
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let is_child = dir_path.starts_with((self.path.clone() + "/").as_str());
+        if dir_path != self.path && !is_child {
+            return false;
+        }
+        if !self.opened {
+            self.opened = true;
+            self.read_directory(renderer);
+        }
+        self.expand_view();
+        if is_child {
+            for dir in self.directories.iter_mut() {
+                if dir.expand_directory(dir_path, renderer) {
+                    break;
+                }
+            }
+        }
+        self.calculate_size(renderer);
+        true
+    }
+
+    /// Paths of this and nested directories which are expanded, children of collapsed are skipped
+    pub fn expanded_directories(&self) -> Vec<String> {
+        if !self.expanded {
+            return vec![];
+        }
+        let mut paths = vec![self.path.clone()];
+        for dir in self.directories.iter() {
+            paths.extend(dir.expanded_directories());
+        }
+        paths
     }
 
     pub fn name(&self) -> String {
@@ -341,19 +447,27 @@ impl DirectoryView {
         }
     }
 
+    /// Adds entries missing in view and removes ones which are gone from disk
     fn read_directory<R>(&mut self, renderer: &mut R)
     where
         R: Renderer + CharacterSizeManager + ConfigHolder,
     {
-        let entries: fs::ReadDir = match fs::read_dir(self.path.clone()) {
-            Ok(d) => d,
-            _ => return,
+        let entries: Vec<fs::DirEntry> = match fs::read_dir(self.path.clone()) {
+            Ok(d) => d.filter_map(|e| e.ok()).collect(),
+            _ => vec![],
         };
-        for e in entries {
-            let entry = match e {
-                Ok(entry) => entry,
-                _ => continue,
-            };
+        let on_disk: Vec<(String, bool)> = entries
+            .iter()
+            .filter_map(|entry| {
+                let is_dir = entry.metadata().ok()?.is_dir();
+                Some((entry.path().to_str()?.to_string(), is_dir))
+            })
+            .collect();
+        self.directories
+            .retain(|dir| on_disk.contains(&(dir.path(), true)));
+        self.files
+            .retain(|file| on_disk.contains(&(file.path(), false)));
+        for entry in entries {
             let meta = match entry.metadata() {
                 Ok(meta) => meta,
                 _ => continue,
@@ -363,8 +477,14 @@ impl DirectoryView {
                     Some(p) => p.to_string(),
                     _ => continue,
                 };
+                if self.directories.iter().any(|dir| dir.path == path) {
+                    continue;
+                }
                 let mut directory_view = DirectoryView::new(path, Arc::clone(&self.config));
                 directory_view.prepare_ui(renderer);
+                if let Some(statuses) = self.git_statuses.as_ref() {
+                    directory_view.set_git_statuses(statuses, renderer);
+                }
                 self.directories.push(directory_view);
             } else if meta.is_file() {
                 let file_name = match entry.file_name().to_str() {
@@ -375,8 +495,15 @@ impl DirectoryView {
                     Some(p) => p.to_string(),
                     _ => continue,
                 };
+                if self.files.iter().any(|file| file.path() == path) {
+                    continue;
+                }
                 let mut file_entry = FileEntry::new(file_name, path, Arc::clone(&self.config));
                 file_entry.prepare_ui(renderer);
+                if let Some(statuses) = self.git_statuses.as_ref() {
+                    let status = statuses.file_status(file_entry.path().as_str());
+                    file_entry.set_git_status(status, renderer);
+                }
                 self.files.push(file_entry);
             }
         }
@@ -865,6 +992,94 @@ mod tests {
         );
     }
 
+    #[test]
+    fn must_show_git_status_of_children() {
+        let dir = git_repository("directory-view");
+        std::fs::create_dir_all(format!("{}/src", dir)).unwrap();
+        std::fs::write(format!("{}/src/b.txt", dir), "b\n").unwrap();
+        std::fs::write(format!("{}/a.txt", dir), "changed\n").unwrap();
+        std::fs::write(format!("{}/out.log", dir), "log\n").unwrap();
+
+        build_test_renderer!(renderer);
+        let mut widget = DirectoryView::new(dir.clone(), config);
+        widget.prepare_ui(&mut renderer);
+        let statuses = Arc::new(repository_status(dir.as_str()).unwrap());
+        widget.set_git_statuses(&statuses, &mut renderer);
+        widget.open_directory(dir.clone(), &mut renderer);
+        widget.open_directory(format!("{}/src", dir), &mut renderer);
+
+        assert_eq!(widget.git_status(), Some(GitStatus::Modified));
+        let files: Vec<(String, Option<GitStatus>)> = widget
+            .files()
+            .iter()
+            .map(|f| (f.name(), f.git_status()))
+            .collect();
+        assert_eq!(
+            files,
+            vec![
+                (".gitignore".to_owned(), None),
+                ("a.txt".to_owned(), Some(GitStatus::Modified)),
+                ("out.log".to_owned(), Some(GitStatus::Ignored)),
+            ]
+        );
+        let src = &widget.directories()[widget.directories().len() - 1];
+        assert_eq!(src.git_status(), Some(GitStatus::Untracked));
+        assert_eq!(src.files()[0].git_status(), Some(GitStatus::Untracked));
+    }
+
+    #[test]
+    fn must_refresh_and_keep_expanded_directories() {
+        let dir = "/tmp/rider-editor/directory-view-refresh".to_owned();
+        fs::remove_dir_all(&dir).unwrap_or_default();
+        build_path(dir.clone());
+        fs::create_dir_all(format!("{}/dir1/nested", dir)).unwrap();
+
+        build_test_renderer!(renderer);
+        let mut widget = DirectoryView::new(dir.clone(), config);
+        widget.prepare_ui(&mut renderer);
+        widget.open_directory(dir.clone(), &mut renderer);
+        widget.open_directory(format!("{}/dir1", dir), &mut renderer);
+        let height = widget.height();
+
+        fs::write(format!("{}/file0", dir), "").unwrap();
+        fs::remove_file(format!("{}/file2", dir)).unwrap();
+        fs::rename(format!("{}/dir2", dir), format!("{}/dir3", dir)).unwrap();
+        fs::write(format!("{}/dir1/file3", dir), "").unwrap();
+        assert_eq!(
+            widget.refresh_directory(format!("{}/dir1", dir).as_str(), &mut renderer),
+            true
+        );
+        let added = &widget.directories()[0].files()[0];
+        assert_eq!(added.name(), "file3".to_owned());
+        assert_eq!(
+            widget.height(),
+            height + added.height() + CHILD_MARGIN as u32
+        );
+        assert_eq!(widget.refresh_directory("/tmp/other", &mut renderer), false);
+
+        widget.refresh(&mut renderer);
+        let files: Vec<String> = widget.files().iter().map(|f| f.name()).collect();
+        assert_eq!(files, vec!["file0".to_owned(), "file1".to_owned()]);
+        let directories: Vec<String> = widget.directories().iter().map(|d| d.name()).collect();
+        assert_eq!(directories, vec!["dir1".to_owned(), "dir3".to_owned()]);
+        assert_eq!(
+            widget.expanded_directories(),
+            vec![dir.clone(), format!("{}/dir1", dir)]
+        );
+
+        widget.expand_directory(format!("{}/dir3", dir).as_str(), &mut renderer);
+        widget.expand_directory(format!("{}/dir1/nested", dir).as_str(), &mut renderer);
+        assert_eq!(
+            widget.expanded_directories(),
+            vec![
+                dir.clone(),
+                format!("{}/dir1", dir),
+                format!("{}/dir1/nested", dir),
+                format!("{}/dir3", dir),
+            ]
+        );
+    }
+
     #[test]
     fn check_config() {
         build_path("/tmp/rider-editor/directory-view-test".to_owned());
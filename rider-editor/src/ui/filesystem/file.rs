@@ -15,6 +15,7 @@ pub struct FileEntry {
     inner: WidgetInner,
     icon: Icon,
     label: Label,
+    git_badge: GitBadge,
 }
 
 impl std::ops::Deref for FileEntry {
@@ -77,7 +78,10 @@ impl Widget for FileEntry {
             canvas,
             renderer,
             &RenderContext::ParentPosition(Point::new(dest.x() + NAME_MARGIN, dest.y())),
-        )
+        );
+        let name_end = dest.x() + 2 * NAME_MARGIN + self.name_width() as i32;
+        self.git_badge
+            .render(canvas, renderer, Point::new(name_end, dest.y()));
     }
 
     fn prepare_ui<R>(&mut self, renderer: &mut R)
@@ -121,7 +125,8 @@ impl FileEntry {
                 Rect::new(0, 0, ICON_SRC_WIDTH, ICON_SRC_HEIGHT),
                 Rect::new(0, 0, ICON_DEST_WIDTH, ICON_DEST_HEIGHT),
             ),
-            label: Label::new(name.clone(), config),
+            label: Label::new(name.clone(), config.clone()),
+            git_badge: GitBadge::new(config),
         }
     }
 
@@ -149,6 +154,17 @@ impl FileEntry {
         self.path.clone()
     }
 
+    pub fn git_status(&self) -> Option<GitStatus> {
+        self.git_badge.status()
+    }
+
+    pub fn set_git_status<R>(&mut self, status: Option<GitStatus>, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        self.git_badge.set_status(status, renderer);
+    }
+
     pub fn full_dest(&self) -> Rect {
         Rect::new(
             self.dest.x(),
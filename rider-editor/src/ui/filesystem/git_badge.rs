@@ -0,0 +1,108 @@
+use crate::app::*;
+use crate::renderer::*;
+use crate::ui::*;
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+
+const BADGE_MARGIN: i32 = 6;
+const BADGE_PADDING: i32 = 2;
+
+/// Letter of git status on background of its color, drawn after name of tree entry
+pub struct GitBadge {
+    status: Option<GitStatus>,
+    label: Label,
+    config: ConfigAccess,
+}
+
+impl GitBadge {
+    pub fn new(config: ConfigAccess) -> Self {
+        Self {
+            status: None,
+            label: Label::new(String::new(), config.clone()),
+            config,
+        }
+    }
+
+    pub fn status(&self) -> Option<GitStatus> {
+        self.status
+    }
+
+    pub fn set_status<R>(&mut self, status: Option<GitStatus>, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if self.status == status {
+            return;
+        }
+        self.status = status;
+        let text = status.map_or("", |status| status.badge());
+        let mut label = Label::new(text.to_owned(), self.config.clone());
+        label.prepare_ui(renderer);
+        self.label = label;
+    }
+
+    fn color(&self, status: GitStatus) -> Color {
+        let config = self.config.read().unwrap();
+        let diff = config.theme().diff();
+        match status {
+            GitStatus::Ignored => config.theme().border_color().into(),
+            GitStatus::Untracked | GitStatus::Added => diff.add.color().into(),
+            GitStatus::Modified => diff.change.color().into(),
+            GitStatus::Conflicted => diff.delete.color().into(),
+        }
+    }
+
+    /// Draws badge right of point where name of entry ends
+    pub fn render<C, R>(&self, canvas: &mut C, renderer: &mut R, name_end: Point)
+    where
+        C: CanvasAccess,
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let status = match self.status {
+            Some(status) => status,
+            _ => return,
+        };
+        let rect = Rect::new(
+            name_end.x() + BADGE_MARGIN,
+            name_end.y(),
+            self.label.name_width() + 2 * BADGE_PADDING as u32,
+            self.label.dest().height(),
+        );
+        canvas
+            .render_rect(rect, self.color(status))
+            .unwrap_or_else(|_| panic!("Failed to render git status badge!"));
+        // label draws its text NAME_MARGIN right of position it gets
+        let position = Point::new(rect.x() + BADGE_PADDING - NAME_MARGIN, rect.y());
+        self.label
+            .render(canvas, renderer, &RenderContext::ParentPosition(position));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use rider_derive::*;
+
+    #[test]
+    fn must_render_status_color_after_name() {
+        build_test_renderer!(renderer);
+        let change: Color = config.read().unwrap().theme().diff().change.color().into();
+        let mut badge = GitBadge::new(config);
+        let mut canvas = CanvasMock::new();
+        badge.render(&mut canvas, &mut renderer, Point::new(10, 20));
+        assert_eq!(canvas.rects.len(), 0);
+
+        badge.set_status(Some(GitStatus::Modified), &mut renderer);
+        assert_eq!(badge.status(), Some(GitStatus::Modified));
+        badge.render(&mut canvas, &mut renderer, Point::new(10, 20));
+        assert_eq!(
+            canvas.rects.first(),
+            Some(&RendererRect::new(
+                Rect::new(16, 20, 17, 14),
+                change,
+                CanvasShape::Rectangle
+            ))
+        );
+    }
+}
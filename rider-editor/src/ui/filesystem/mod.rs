@@ -1,7 +1,9 @@
 pub use crate::ui::filesystem::directory::*;
 pub use crate::ui::filesystem::file::*;
+pub use crate::ui::filesystem::git_badge::*;
 
 pub mod directory;
 pub mod file;
+pub mod git_badge;
 
 pub const NAME_MARGIN: i32 = 20;
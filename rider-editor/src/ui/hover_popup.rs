@@ -0,0 +1,196 @@
+use crate::app::UpdateResult as UR;
+use crate::renderer::renderer::Renderer;
+use crate::ui::*;
+use crate::ui::{RenderContext as RC, UpdateContext as UC};
+use rider_config::ConfigAccess;
+use rider_config::ConfigHolder;
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use std::sync::Arc;
+
+const PADDING: i32 = 4;
+const MAX_LINES: usize = 20;
+
+/// Text from language server shown below caret, hidden when it has no text
+pub struct HoverPopup {
+    text: String,
+    labels: Vec<Label>,
+    dest: Rect,
+    background_color: Color,
+    border_color: Color,
+    config: ConfigAccess,
+}
+
+impl ConfigHolder for HoverPopup {
+    fn config(&self) -> &ConfigAccess {
+        &self.config
+    }
+}
+
+impl HoverPopup {
+    pub fn new(config: ConfigAccess) -> Self {
+        let (background_color, border_color) = {
+            let c = config.read().unwrap();
+            let theme = c.theme();
+            (theme.background().into(), theme.border_color().into())
+        };
+        Self {
+            text: String::new(),
+            labels: vec![],
+            dest: Rect::new(0, 0, 1, 1),
+            background_color,
+            border_color,
+            config,
+        }
+    }
+
+    /// Shows text with top left corner at given point, long text is cut
+    pub fn show<R>(&mut self, text: String, point: Point, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        self.text = text;
+        self.dest.set_x(point.x());
+        self.dest.set_y(point.y());
+        self.prepare_ui(renderer);
+    }
+
+    pub fn hide(&mut self) {
+        self.text.clear();
+        self.labels.clear();
+    }
+
+    pub fn is_visible(&self) -> bool {
+        !self.text.is_empty()
+    }
+
+    pub fn text(&self) -> &String {
+        &self.text
+    }
+}
+
+impl Widget for HoverPopup {
+    fn texture_path(&self) -> Option<String> {
+        None
+    }
+
+    fn dest(&self) -> &Rect {
+        &self.dest
+    }
+
+    fn set_dest(&mut self, rect: &Rect) {
+        self.dest = rect.clone();
+    }
+
+    fn source(&self) -> &Rect {
+        self.dest()
+    }
+
+    fn set_source(&mut self, rect: &Rect) {
+        self.set_dest(rect)
+    }
+
+    fn on_left_click(&mut self, _point: &Point, _context: &UC) -> UR {
+        self.hide();
+        UR::NoOp
+    }
+
+    fn is_left_click_target(&self, point: &Point, context: &UC) -> bool {
+        if !self.is_visible() {
+            return false;
+        }
+        match context {
+            UC::ParentPosition(p) => move_render_point(*p, &self.dest),
+            _ => self.dest,
+        }
+        .contains_point(*point)
+    }
+
+    fn render<C, R>(&self, canvas: &mut C, renderer: &mut R, context: &RenderContext)
+    where
+        C: CanvasAccess,
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if !self.is_visible() {
+            return;
+        }
+        let dest = match context {
+            RC::ParentPosition(p) => move_render_point(*p, &self.dest),
+            _ => self.dest,
+        };
+        canvas.set_clip_rect(None);
+        canvas
+            .render_rect(dest, self.background_color)
+            .unwrap_or_else(|_| panic!("Failed to render hover popup background!"));
+        canvas
+            .render_border(dest, self.border_color)
+            .unwrap_or_else(|_| panic!("Failed to render hover popup border!"));
+
+        let lines_start = dest.top_left() + Point::new(PADDING, PADDING);
+        for label in self.labels.iter() {
+            label.render(canvas, renderer, &RC::ParentPosition(lines_start));
+        }
+    }
+
+    fn prepare_ui<'l, T>(&mut self, renderer: &mut T)
+    where
+        T: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let mut labels = vec![];
+        let mut width: u32 = 0;
+        let mut height: u32 = 0;
+        for line in self.text.lines().take(MAX_LINES) {
+            // empty label has no size
+            let line = if line.is_empty() { " " } else { line };
+            let mut label = Label::new(line.to_owned(), Arc::clone(&self.config));
+            label.prepare_ui(renderer);
+            let mut dest = label.dest().clone();
+            dest.set_y(height as i32);
+            label.set_dest(&dest);
+            width = width.max(dest.width());
+            height += dest.height();
+            labels.push(label);
+        }
+        self.labels = labels;
+        self.dest.set_width(width + (2 * PADDING) as u32);
+        self.dest.set_height(height + (2 * PADDING) as u32);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use rider_derive::*;
+
+    #[test]
+    fn assert_show_and_hide() {
+        build_test_renderer!(renderer);
+        let mut widget = HoverPopup::new(config);
+        assert_eq!(widget.is_visible(), false);
+        widget.show(
+            "fn main()\n\nentry".to_owned(),
+            Point::new(10, 20),
+            &mut renderer,
+        );
+        assert_eq!(widget.is_visible(), true);
+        assert_eq!(widget.labels.len(), 3);
+        assert_eq!(widget.dest().top_left(), Point::new(10, 20));
+        assert_eq!(
+            widget.is_left_click_target(&Point::new(12, 22), &UpdateContext::Nothing),
+            true
+        );
+        widget.on_left_click(&Point::new(12, 22), &UpdateContext::Nothing);
+        assert_eq!(widget.is_visible(), false);
+    }
+
+    #[test]
+    fn assert_render() {
+        build_test_renderer!(renderer);
+        let mut canvas = CanvasMock::new();
+        let mut widget = HoverPopup::new(config);
+        widget.render(&mut canvas, &mut renderer, &RenderContext::Nothing);
+        widget.show("fn main()".to_owned(), Point::new(0, 0), &mut renderer);
+        widget.render(&mut canvas, &mut renderer, &RenderContext::Nothing);
+    }
+}
@@ -7,6 +7,7 @@ use sdl2::rect::{Point, Rect};
 
 pub const SAVE_BUTTON_OFFSET_LEFT: i32 = 16;
 pub const SAVE_BUTTON_OFFSET_TOP: i32 = 10;
+pub const STATUS_OFFSET_LEFT: i32 = SAVE_BUTTON_OFFSET_LEFT * 4;
 
 pub struct MenuBar {
     border_color: Color,
@@ -15,6 +16,7 @@ pub struct MenuBar {
     config: ConfigAccess,
     save_button: SaveButton,
     settings_button: SettingsButton,
+    status: Label,
 }
 
 impl MenuBar {
@@ -34,6 +36,7 @@ impl MenuBar {
             dest: Rect::new(0, 0, w as u32, h as u32),
             save_button: SaveButton::new(config.clone()),
             settings_button: SettingsButton::new(config.clone()),
+            status: Label::new(String::new(), config.clone()),
             config,
         }
     }
@@ -42,6 +45,24 @@ impl MenuBar {
         &self.background_color
     }
 
+    /// Message shown next to buttons, for example why formatting failed
+    pub fn status(&self) -> String {
+        self.status.name()
+    }
+
+    pub fn set_status<R>(&mut self, text: String, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let mut status = Label::new(text, self.config.clone());
+        status.prepare_ui(renderer);
+        let mut dest = status.dest().clone();
+        dest.set_x(STATUS_OFFSET_LEFT);
+        dest.set_y((self.dest.height() as i32 - dest.height() as i32).max(0) / 2);
+        status.set_dest(&dest);
+        self.status = status;
+    }
+
     pub fn render<C, R>(&self, canvas: &mut C, renderer: &mut R, context: &RenderContext)
     where
         C: CanvasAccess,
@@ -86,6 +107,12 @@ impl MenuBar {
                 relative_position.offset(SAVE_BUTTON_OFFSET_LEFT * 2, SAVE_BUTTON_OFFSET_TOP),
             ),
         );
+
+        self.status.render(
+            canvas,
+            renderer,
+            &RenderContext::ParentPosition(relative_position),
+        );
     }
 
     pub fn prepare_ui(&mut self) {
@@ -303,6 +330,22 @@ mod test_render {
     )]
     fn assert_settings_button() {}
 
+    #[test]
+    fn assert_set_status() {
+        build_test_renderer!(renderer);
+        let mut canvas = CanvasMock::new();
+        let mut widget = MenuBar::new(config.clone());
+        assert_eq!(widget.status(), String::new());
+        widget.set_status("rustfmt failed".to_owned(), &mut renderer);
+        assert_eq!(widget.status(), "rustfmt failed".to_owned());
+        assert_eq!(widget.status.dest().x(), STATUS_OFFSET_LEFT);
+        assert_eq!(
+            widget.status.dest().width(),
+            "rustfmt failed".len() as u32 * 13
+        );
+        widget.render(&mut canvas, &mut renderer, &RenderContext::Nothing);
+    }
+
     #[test]
     fn assert_update() {
         build_test_renderer!(renderer);
@@ -8,29 +8,43 @@ use crate::app::{UpdateResult as UR, UpdateResult};
 use crate::renderer::managers::*;
 use rider_config::*;
 
+pub mod blame_column;
 pub mod buttons;
 pub mod caret;
+pub mod commit_panel;
+pub mod completion_popup;
+pub mod diff_view;
 pub mod file;
 pub mod file_editor;
 pub mod filesystem;
+pub mod hover_popup;
 pub mod icon;
 pub mod label;
 pub mod menu_bar;
 pub mod modal;
+pub mod output_panel;
 pub mod project_tree;
 pub mod scroll_bar;
+pub mod terminal_panel;
 pub mod text_character;
 
+pub use self::blame_column::*;
 pub use self::buttons::*;
 pub use self::caret::*;
+pub use self::commit_panel::*;
+pub use self::completion_popup::*;
+pub use self::diff_view::*;
 pub use self::file::*;
 pub use self::file_editor::*;
 pub use self::filesystem::*;
+pub use self::hover_popup::*;
 pub use self::label::*;
 pub use self::menu_bar::*;
 pub use self::modal::*;
+pub use self::output_panel::*;
 pub use self::project_tree::*;
 pub use self::scroll_bar::*;
+pub use self::terminal_panel::*;
 pub use self::text_character::*;
 use crate::renderer::Renderer;
 
@@ -1,11 +1,14 @@
 pub mod open_file;
+pub mod picker;
 pub mod settings;
 
 pub use self::open_file::*;
+pub use self::picker::*;
 pub use self::settings::*;
 
 pub enum ModalType {
     OpenFile(OpenFile),
+    Picker(Picker),
     Settings(Settings),
 }
 
@@ -14,6 +17,7 @@ impl PartialEq for ModalType {
     fn eq(&self, other: &ModalType) -> bool {
         match (self, other) {
             (ModalType::OpenFile { .. }, ModalType::OpenFile { .. }) => true,
+            (ModalType::Picker { .. }, ModalType::Picker { .. }) => true,
             (ModalType::Settings { .. }, ModalType::Settings { .. }) => true,
             _ => false,
         }
@@ -25,6 +29,7 @@ impl std::fmt::Debug for ModalType {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
         let name = match self {
             ModalType::OpenFile(_) => "OpenFile",
+            ModalType::Picker(_) => "Picker",
             ModalType::Settings(_) => "Settings",
         };
         write!(f, "<Modal::{:?} {{}}", name)
@@ -0,0 +1,634 @@
+use crate::app::diagnostics::Diagnostic;
+use crate::app::external_change::ExternalChangeAction;
+use crate::app::fuzzy_filter;
+use crate::app::symbol_index::Location;
+use crate::app::UpdateResult as UR;
+use crate::renderer::renderer::Renderer;
+use crate::ui::*;
+use crate::ui::{RenderContext as RC, UpdateContext as UC};
+use rider_config::ConfigAccess;
+use rider_config::ConfigHolder;
+use rider_lexers::outline::Symbol;
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use std::path::Path;
+use std::sync::Arc;
+
+const CONTENT_MARGIN_LEFT: i32 = 16;
+const CONTENT_MARGIN_TOP: i32 = 24;
+const FILTER_MARGIN_BOTTOM: i32 = 8;
+const DEFAULT_ROW_HEIGHT: u32 = 16;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PickerTarget {
+    Position(usize),
+    Location(Location),
+    Compare(String),
+    ExternalChange(String, ExternalChangeAction),
+}
+
+/// Row of picker, `name` is matched against filter and `text` is displayed
+#[derive(Clone, Debug, PartialEq)]
+pub struct PickerEntry {
+    pub name: String,
+    pub text: String,
+    pub target: PickerTarget,
+}
+
+impl PickerEntry {
+    pub fn from_symbol(symbol: &Symbol) -> Self {
+        Self {
+            name: symbol.name.clone(),
+            text: format!(
+                "{}{} {}  :{}",
+                "  ".repeat(symbol.depth),
+                symbol.kind.label(),
+                symbol.name,
+                symbol.line + 1
+            ),
+            target: PickerTarget::Position(symbol.position),
+        }
+    }
+
+    /// Location displayed with path relative to given root
+    pub fn from_location(location: &Location, root: &str) -> Self {
+        let path = Path::new(location.path.as_str());
+        let path = path.strip_prefix(root).unwrap_or(path).to_string_lossy();
+        let text = format!("{}:{}", path, location.line + 1);
+        Self {
+            name: text.clone(),
+            text,
+            target: PickerTarget::Location(location.clone()),
+        }
+    }
+
+    /// File to compare current one with, displayed with path relative to given root
+    pub fn from_compared_file(path: &str, root: &str) -> Self {
+        let text = Path::new(path)
+            .strip_prefix(root)
+            .unwrap_or_else(|_| Path::new(path))
+            .to_string_lossy()
+            .to_string();
+        Self {
+            name: text.clone(),
+            text,
+            target: PickerTarget::Compare(path.to_owned()),
+        }
+    }
+
+    /// Diagnostic with severity, location relative to given root and message
+    pub fn from_diagnostic(diagnostic: &Diagnostic, root: &str) -> Self {
+        let location = &diagnostic.location;
+        let path = Path::new(location.path.as_str());
+        let path = path.strip_prefix(root).unwrap_or(path).to_string_lossy();
+        let text = format!(
+            "{} {}:{}  {}",
+            diagnostic.severity.label(),
+            path,
+            location.line + 1,
+            diagnostic.text().lines().next().unwrap_or_default()
+        );
+        Self {
+            name: text.clone(),
+            text,
+            target: PickerTarget::Location(location.clone()),
+        }
+    }
+
+    /// Answer to file changed on disk while its buffer was edited
+    pub fn from_external_change(path: &str, action: ExternalChangeAction) -> Self {
+        let text = action.label().to_owned();
+        Self {
+            name: text.clone(),
+            text,
+            target: PickerTarget::ExternalChange(path.to_owned(), action),
+        }
+    }
+
+    pub fn result(&self) -> UR {
+        match self.target {
+            PickerTarget::Position(position) => UR::JumpTo(position),
+            PickerTarget::Location(ref location) => UR::OpenLocation(location.clone()),
+            PickerTarget::Compare(ref path) => UR::CompareWithFile(path.clone()),
+            PickerTarget::ExternalChange(ref path, action) => {
+                UR::ResolveExternalChange(path.clone(), action)
+            }
+        }
+    }
+}
+
+/// Entries filtered with typed pattern, selected one is marked with border
+pub struct Picker {
+    title: String,
+    entries: Vec<PickerEntry>,
+    matches: Vec<PickerEntry>,
+    filter: String,
+    selected: usize,
+    filter_label: Label,
+    labels: Vec<Label>,
+    row_height: u32,
+    vertical_scroll_bar: VerticalScrollBar,
+    horizontal_scroll_bar: HorizontalScrollBar,
+    dest: Rect,
+    full_dest: Rect,
+    background_color: Color,
+    border_color: Color,
+    selected_color: Color,
+    config: ConfigAccess,
+}
+
+impl ScrollView<VerticalScrollBar, HorizontalScrollBar> for Picker {
+    fn mut_horizontal_scroll_handler(&mut self) -> Option<&mut HorizontalScrollBar> {
+        Some(&mut self.horizontal_scroll_bar)
+    }
+
+    fn horizontal_scroll_handler(&self) -> Option<&HorizontalScrollBar> {
+        Some(&self.horizontal_scroll_bar)
+    }
+
+    fn mut_vertical_scroll_handler(&mut self) -> Option<&mut VerticalScrollBar> {
+        Some(&mut self.vertical_scroll_bar)
+    }
+
+    fn vertical_scroll_handler(&self) -> Option<&VerticalScrollBar> {
+        Some(&self.vertical_scroll_bar)
+    }
+}
+
+impl ConfigHolder for Picker {
+    fn config(&self) -> &ConfigAccess {
+        &self.config
+    }
+}
+
+impl Picker {
+    pub fn new(
+        title: String,
+        entries: Vec<PickerEntry>,
+        width: u32,
+        height: u32,
+        config: ConfigAccess,
+    ) -> Self {
+        let (window_width, window_height, background_color, border_color, selected_color) = {
+            let c = config.read().unwrap();
+            let theme = c.theme();
+            (
+                c.width(),
+                c.height(),
+                theme.background().into(),
+                theme.border_color().into(),
+                theme.caret().bright().color().into(),
+            )
+        };
+        Self {
+            filter_label: Label::new(Self::filter_text(&title, ""), Arc::clone(&config)),
+            title,
+            matches: entries.clone(),
+            entries,
+            filter: String::new(),
+            selected: 0,
+            labels: vec![],
+            row_height: DEFAULT_ROW_HEIGHT,
+            vertical_scroll_bar: VerticalScrollBar::new(Arc::clone(&config)),
+            horizontal_scroll_bar: HorizontalScrollBar::new(Arc::clone(&config)),
+            dest: Rect::new(
+                (window_width / 2) as i32 - (width / 2) as i32,
+                (window_height / 2) as i32 - (height / 2) as i32,
+                width,
+                height,
+            ),
+            full_dest: Rect::new(0, 0, width, height),
+            background_color,
+            border_color,
+            selected_color,
+            config,
+        }
+    }
+
+    pub fn filter(&self) -> &String {
+        &self.filter
+    }
+
+    pub fn matches(&self) -> &Vec<PickerEntry> {
+        &self.matches
+    }
+
+    pub fn selected_entry(&self) -> Option<&PickerEntry> {
+        self.matches.get(self.selected)
+    }
+
+    /// Result of choosing selected entry
+    pub fn confirm(&self) -> UR {
+        self.selected_entry()
+            .map_or(UR::NoOp, |entry| entry.result())
+    }
+
+    pub fn push_filter<R>(&mut self, text: String, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        self.filter.push_str(text.as_str());
+        self.refresh_matches(renderer);
+    }
+
+    pub fn pop_filter<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if self.filter.pop().is_some() {
+            self.refresh_matches(renderer);
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.matches.len() {
+            self.selected += 1;
+            self.scroll_to_selected();
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        if self.selected > 0 {
+            self.selected -= 1;
+            self.scroll_to_selected();
+        }
+    }
+
+    pub fn full_rect(&self) -> &Rect {
+        &self.full_dest
+    }
+
+    fn refresh_matches<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        self.matches = fuzzy_filter(&self.entries, self.filter.as_str(), |entry| {
+            entry.name.as_str()
+        })
+        .into_iter()
+        .cloned()
+        .collect();
+        self.selected = 0;
+        self.vertical_scroll_bar.scroll_to(0);
+        self.filter_label = Label::new(
+            Self::filter_text(self.title.as_str(), self.filter.as_str()),
+            Arc::clone(&self.config),
+        );
+        self.prepare_ui(renderer);
+    }
+
+    fn filter_text(title: &str, filter: &str) -> String {
+        format!("{} > {}", title, filter)
+    }
+
+    /// Top of first row relative to modal
+    fn rows_top(&self) -> i32 {
+        CONTENT_MARGIN_TOP + self.filter_label.dest().height() as i32 + FILTER_MARGIN_BOTTOM
+    }
+
+    fn scroll_to_selected(&mut self) {
+        let top = self.selected as i32 * self.row_height as i32;
+        let viewport = self.dest.height() as i32 - self.rows_top() - CONTENT_MARGIN_TOP;
+        let scroll = self.vertical_scroll_value();
+        if top < scroll {
+            self.vertical_scroll_bar.scroll_to(top);
+        } else if top + self.row_height as i32 > scroll + viewport {
+            self.vertical_scroll_bar
+                .scroll_to(top + self.row_height as i32 - viewport);
+        }
+    }
+}
+
+impl Widget for Picker {
+    fn texture_path(&self) -> Option<String> {
+        None
+    }
+
+    fn dest(&self) -> &Rect {
+        &self.dest
+    }
+
+    fn set_dest(&mut self, rect: &Rect) {
+        self.dest = rect.clone();
+    }
+
+    fn source(&self) -> &Rect {
+        self.dest()
+    }
+
+    fn set_source(&mut self, rect: &Rect) {
+        self.set_dest(rect)
+    }
+
+    fn update(&mut self, ticks: i32, context: &UC) -> UR {
+        let (window_width, window_height, color, scroll_width, scroll_margin) = {
+            let c = self.config.read().unwrap();
+            (
+                c.width(),
+                c.height(),
+                c.theme().background().into(),
+                c.scroll().width(),
+                c.scroll().margin_right(),
+            )
+        };
+
+        self.dest
+            .set_x((window_width / 2) as i32 - (self.dest.width() / 2) as i32);
+        self.dest
+            .set_y((window_height / 2) as i32 - (self.dest.height() / 2) as i32);
+
+        self.background_color = color;
+
+        //        Scroll bars
+        self.vertical_scroll_bar
+            .set_full_size(self.full_dest.height()); // full dest
+        self.vertical_scroll_bar.set_viewport(self.dest.height());
+        self.vertical_scroll_bar
+            .set_location(self.dest.width() as i32 - (scroll_width as i32 + scroll_margin));
+        self.vertical_scroll_bar.update(ticks, context);
+
+        self.horizontal_scroll_bar
+            .set_full_size(self.full_dest.width()); // full dest
+        self.horizontal_scroll_bar.set_viewport(self.dest.width());
+        self.horizontal_scroll_bar
+            .set_location(self.dest.height() as i32 - (scroll_width as i32 + scroll_margin));
+        self.horizontal_scroll_bar.update(ticks, context);
+
+        // End
+        UR::NoOp
+    }
+
+    fn on_left_click(&mut self, point: &Point, context: &UC) -> UR {
+        let dest = match context {
+            UC::ParentPosition(p) => move_render_point(*p, &self.dest),
+            _ => self.dest,
+        };
+        let y = point.y() - (dest.y() + self.rows_top() + self.scroll().y());
+        if y < 0 {
+            return UR::NoOp;
+        }
+        let row = y as usize / self.row_height as usize;
+        match self.matches.get(row) {
+            Some(entry) => {
+                self.selected = row;
+                entry.result()
+            }
+            _ => UR::NoOp,
+        }
+    }
+
+    fn is_left_click_target(&self, point: &Point, context: &UC) -> bool {
+        let dest = match context {
+            UC::ParentPosition(p) => move_render_point(p.clone(), &self.dest()),
+            _ => self.dest().clone(),
+        };
+        dest.contains_point(point.clone())
+    }
+
+    fn render<C, R>(&self, canvas: &mut C, renderer: &mut R, context: &RenderContext)
+    where
+        C: CanvasAccess,
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let dest = match context {
+            RC::ParentPosition(p) => move_render_point(p.clone(), &self.dest),
+            _ => self.dest.clone(),
+        };
+
+        // Background
+        canvas.set_clipping(dest.clone());
+        canvas
+            .render_rect(dest, self.background_color)
+            .unwrap_or_else(|_| panic!("Failed to render picker modal background!"));
+        canvas
+            .render_border(dest, self.border_color)
+            .unwrap_or_else(|_| panic!("Failed to render picker modal border!"));
+
+        // filter
+        self.filter_label.render(
+            canvas,
+            renderer,
+            &RC::ParentPosition(
+                dest.top_left() + Point::new(CONTENT_MARGIN_LEFT, CONTENT_MARGIN_TOP),
+            ),
+        );
+
+        // entries
+        canvas.set_clipping(Rect::new(
+            dest.x(),
+            dest.y() + self.rows_top(),
+            dest.width(),
+            (dest.height() as i32 - self.rows_top()).max(1) as u32,
+        ));
+        let rows_start =
+            dest.top_left() + Point::new(CONTENT_MARGIN_LEFT, self.rows_top()) + self.scroll();
+        for label in self.labels.iter() {
+            label.render(canvas, renderer, &RC::ParentPosition(rows_start));
+        }
+        if self.selected_entry().is_some() {
+            let row = Rect::new(
+                rows_start.x(),
+                rows_start.y() + self.selected as i32 * self.row_height as i32,
+                dest.width() - (2 * CONTENT_MARGIN_LEFT) as u32,
+                self.row_height,
+            );
+            canvas
+                .render_border(row, self.selected_color)
+                .unwrap_or_else(|_| panic!("Failed to render picker selected entry border!"));
+        }
+
+        // Scroll bars
+        self.vertical_scroll_bar
+            .render(canvas, &RenderContext::ParentPosition(self.dest.top_left()));
+        self.horizontal_scroll_bar
+            .render(canvas, &RenderContext::ParentPosition(self.dest.top_left()));
+    }
+
+    fn prepare_ui<'l, T>(&mut self, renderer: &mut T)
+    where
+        T: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        self.filter_label.prepare_ui(renderer);
+        self.row_height = self.filter_label.dest().height();
+
+        let mut labels = vec![];
+        let mut width: u32 = 0;
+        for (index, entry) in self.matches.iter().enumerate() {
+            let mut label = Label::new(entry.text.clone(), Arc::clone(&self.config));
+            label.prepare_ui(renderer);
+            let mut dest = label.dest().clone();
+            dest.set_y(index as i32 * self.row_height as i32);
+            label.set_dest(&dest);
+            width = width.max(dest.width());
+            labels.push(label);
+        }
+        self.labels = labels;
+        self.full_dest = Rect::new(
+            0,
+            0,
+            width + (2 * CONTENT_MARGIN_LEFT) as u32,
+            self.labels.len() as u32 * self.row_height
+                + (self.rows_top() + CONTENT_MARGIN_TOP) as u32,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::DiagnosticSeverity;
+    use crate::tests::*;
+    use rider_derive::*;
+    use rider_lexers::outline::SymbolKind;
+
+    fn symbol(name: &str, line: usize, position: usize) -> Symbol {
+        Symbol {
+            name: name.to_owned(),
+            kind: SymbolKind::Function,
+            line,
+            position,
+            depth: 0,
+        }
+    }
+
+    fn entries() -> Vec<PickerEntry> {
+        vec![
+            symbol("main", 0, 3),
+            symbol("render", 4, 40),
+            symbol("run", 8, 80),
+        ]
+        .iter()
+        .map(PickerEntry::from_symbol)
+        .collect()
+    }
+
+    fn build_picker(width: u32, height: u32, config: ConfigAccess) -> Picker {
+        Picker::new("Symbols".to_owned(), entries(), width, height, config)
+    }
+
+    #[test]
+    fn assert_dest() {
+        let config = build_config();
+        let widget = build_picker(120, 130, config);
+        assert_eq!(widget.dest(), &Rect::new(452, 365, 120, 130));
+    }
+
+    #[test]
+    fn assert_filter_entries() {
+        build_test_renderer!(renderer);
+        let mut widget = build_picker(100, 100, config);
+        widget.prepare_ui(&mut renderer);
+        assert_eq!(widget.matches().len(), 3);
+        widget.push_filter("r".to_owned(), &mut renderer);
+        widget.push_filter("n".to_owned(), &mut renderer);
+        assert_eq!(widget.filter(), &"rn".to_owned());
+        let names: Vec<String> = widget.matches().iter().map(|s| s.name.clone()).collect();
+        assert_eq!(names, vec!["render".to_owned(), "run".to_owned()]);
+        widget.pop_filter(&mut renderer);
+        widget.pop_filter(&mut renderer);
+        widget.pop_filter(&mut renderer);
+        assert_eq!(widget.matches().len(), 3);
+    }
+
+    #[test]
+    fn assert_select_entry() {
+        build_test_renderer!(renderer);
+        let mut widget = build_picker(100, 100, config);
+        widget.prepare_ui(&mut renderer);
+        widget.select_previous();
+        assert_eq!(widget.confirm(), UR::JumpTo(3));
+        widget.select_next();
+        widget.select_next();
+        widget.select_next();
+        assert_eq!(widget.confirm(), UR::JumpTo(80));
+        widget.push_filter("x".to_owned(), &mut renderer);
+        assert_eq!(widget.selected_entry(), None);
+        assert_eq!(widget.confirm(), UR::NoOp);
+    }
+
+    #[test]
+    fn assert_on_left_click_returns_entry_result() {
+        build_test_renderer!(renderer);
+        let mut widget = build_picker(100, 100, config);
+        widget.prepare_ui(&mut renderer);
+        let row_height = widget.row_height as i32;
+        let point = widget.dest().top_left()
+            + Point::new(
+                CONTENT_MARGIN_LEFT,
+                widget.rows_top() + row_height + row_height / 2,
+            );
+        assert_eq!(
+            widget.on_left_click(&point, &UpdateContext::Nothing),
+            UR::JumpTo(40)
+        );
+        let point = widget.dest().top_left();
+        assert_eq!(
+            widget.on_left_click(&point, &UpdateContext::Nothing),
+            UR::NoOp
+        );
+    }
+
+    #[test]
+    fn assert_location_entry() {
+        let location = Location {
+            path: "/project/src/main.rs".to_owned(),
+            line: 4,
+            column: 2,
+        };
+        let entry = PickerEntry::from_location(&location, "/project");
+        assert_eq!(entry.text, "src/main.rs:5".to_owned());
+        assert_eq!(entry.result(), UR::OpenLocation(location));
+    }
+
+    #[test]
+    fn assert_compared_file_entry() {
+        let entry = PickerEntry::from_compared_file("/project/src/lib.rs", "/project");
+        assert_eq!(entry.text, "src/lib.rs".to_owned());
+        assert_eq!(
+            entry.result(),
+            UR::CompareWithFile("/project/src/lib.rs".to_owned())
+        );
+    }
+
+    #[test]
+    fn assert_external_change_entry() {
+        let entry = PickerEntry::from_external_change("/a.rs", ExternalChangeAction::Diff);
+        assert_eq!(entry.text, "Compare mine with disk".to_owned());
+        assert_eq!(
+            entry.result(),
+            UR::ResolveExternalChange("/a.rs".to_owned(), ExternalChangeAction::Diff)
+        );
+    }
+
+    #[test]
+    fn assert_diagnostic_entry() {
+        let location = Location {
+            path: "/project/src/main.rs".to_owned(),
+            line: 4,
+            column: 2,
+        };
+        let diagnostic = Diagnostic {
+            location: location.clone(),
+            end_line: 4,
+            end_column: 6,
+            severity: DiagnosticSeverity::Warning,
+            message: "unused variable\nremove it".to_owned(),
+            source: "rustc".to_owned(),
+        };
+        let entry = PickerEntry::from_diagnostic(&diagnostic, "/project");
+        assert_eq!(
+            entry.text,
+            "warning src/main.rs:5  rustc: unused variable".to_owned()
+        );
+        assert_eq!(entry.result(), UR::OpenLocation(location));
+    }
+
+    #[test]
+    fn assert_render() {
+        build_test_renderer!(renderer);
+        let mut canvas = CanvasMock::new();
+        let mut widget = build_picker(100, 100, config);
+        widget.prepare_ui(&mut renderer);
+        widget.render(&mut canvas, &mut renderer, &RenderContext::Nothing);
+    }
+}
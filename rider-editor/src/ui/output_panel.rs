@@ -0,0 +1,335 @@
+use crate::app::cargo_runner::OutputLine;
+use crate::app::UpdateResult as UR;
+use crate::renderer::renderer::Renderer;
+use crate::ui::*;
+use crate::ui::{RenderContext as RC, UpdateContext as UC};
+use rider_config::ConfigAccess;
+use rider_config::ConfigHolder;
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use std::sync::Arc;
+
+const PADDING: i32 = 4;
+const PANEL_HEIGHT: u32 = 200;
+const MAX_LINES: usize = 10_000;
+
+/// Task output at bottom of window, title is followed by newest lines which fit.
+///
+/// Clicking line with location opens it.
+pub struct OutputPanel {
+    title: String,
+    lines: Vec<OutputLine>,
+    title_label: Label,
+    labels: Vec<Label>,
+    first_visible: usize,
+    row_height: u32,
+    visible: bool,
+    dest: Rect,
+    background_color: Color,
+    border_color: Color,
+    config: ConfigAccess,
+}
+
+impl ConfigHolder for OutputPanel {
+    fn config(&self) -> &ConfigAccess {
+        &self.config
+    }
+}
+
+impl OutputPanel {
+    pub fn new(config: ConfigAccess) -> Self {
+        let (background_color, border_color, width, height) = {
+            let c = config.read().unwrap();
+            let theme = c.theme();
+            (
+                theme.background().into(),
+                theme.border_color().into(),
+                c.width(),
+                c.height(),
+            )
+        };
+        Self {
+            title: String::new(),
+            lines: vec![],
+            title_label: Label::new(String::new(), Arc::clone(&config)),
+            labels: vec![],
+            first_visible: 0,
+            row_height: 0,
+            visible: false,
+            dest: panel_rect(width, height),
+            background_color,
+            border_color,
+            config,
+        }
+    }
+
+    /// Removes lines and shows panel with new title
+    pub fn start<R>(&mut self, title: String, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        self.title = title;
+        self.lines.clear();
+        self.visible = true;
+        self.prepare_ui(renderer);
+    }
+
+    pub fn set_title<R>(&mut self, title: String, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        self.title = title;
+        self.prepare_ui(renderer);
+    }
+
+    /// Appends lines, oldest ones are dropped when there are too many
+    pub fn push_lines<R>(&mut self, lines: Vec<OutputLine>, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if lines.is_empty() {
+            return;
+        }
+        self.lines.extend(lines);
+        if self.lines.len() > MAX_LINES {
+            let overflow = self.lines.len() - MAX_LINES;
+            self.lines.drain(..overflow);
+        }
+        self.prepare_ui(renderer);
+    }
+
+    pub fn lines(&self) -> &Vec<OutputLine> {
+        &self.lines
+    }
+
+    pub fn title(&self) -> &String {
+        &self.title
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+    }
+
+    fn visible_rows(&self) -> usize {
+        match self.row_height {
+            0 => 0,
+            row_height => {
+                let content = self.dest.height().saturating_sub((2 * PADDING) as u32);
+                (content / row_height).saturating_sub(1) as usize
+            }
+        }
+    }
+}
+
+impl Widget for OutputPanel {
+    fn texture_path(&self) -> Option<String> {
+        None
+    }
+
+    fn dest(&self) -> &Rect {
+        &self.dest
+    }
+
+    fn set_dest(&mut self, rect: &Rect) {
+        self.dest = rect.clone();
+    }
+
+    fn source(&self) -> &Rect {
+        self.dest()
+    }
+
+    fn set_source(&mut self, rect: &Rect) {
+        self.set_dest(rect)
+    }
+
+    fn update(&mut self, _ticks: i32, _context: &UC) -> UR {
+        let (width, height) = {
+            let c = self.config.read().unwrap();
+            (c.width(), c.height())
+        };
+        self.dest = panel_rect(width, height);
+        UR::NoOp
+    }
+
+    fn on_left_click(&mut self, point: &Point, context: &UC) -> UR {
+        let dest = match context {
+            UC::ParentPosition(p) => move_render_point(*p, &self.dest),
+            _ => self.dest,
+        };
+        let y = point.y() - (dest.y() + PADDING + self.row_height as i32);
+        if y < 0 || self.row_height == 0 {
+            return UR::NoOp;
+        }
+        let row = y as usize / self.row_height as usize;
+        match self
+            .lines
+            .get(self.first_visible + row)
+            .filter(|_| row < self.labels.len())
+            .and_then(|line| line.location.clone())
+        {
+            Some(location) => UR::OpenLocation(location),
+            _ => UR::NoOp,
+        }
+    }
+
+    fn is_left_click_target(&self, point: &Point, context: &UC) -> bool {
+        if !self.is_visible() {
+            return false;
+        }
+        match context {
+            UC::ParentPosition(p) => move_render_point(*p, &self.dest),
+            _ => self.dest,
+        }
+        .contains_point(*point)
+    }
+
+    fn render<C, R>(&self, canvas: &mut C, renderer: &mut R, context: &RenderContext)
+    where
+        C: CanvasAccess,
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if !self.is_visible() {
+            return;
+        }
+        let dest = match context {
+            RC::ParentPosition(p) => move_render_point(*p, &self.dest),
+            _ => self.dest,
+        };
+        canvas.set_clip_rect(None);
+        canvas
+            .render_rect(dest, self.background_color)
+            .unwrap_or_else(|_| panic!("Failed to render output panel background!"));
+        canvas
+            .render_border(dest, self.border_color)
+            .unwrap_or_else(|_| panic!("Failed to render output panel border!"));
+
+        let rows_start = dest.top_left() + Point::new(PADDING, PADDING);
+        self.title_label
+            .render(canvas, renderer, &RC::ParentPosition(rows_start));
+        for label in self.labels.iter() {
+            label.render(canvas, renderer, &RC::ParentPosition(rows_start));
+        }
+    }
+
+    fn prepare_ui<'l, T>(&mut self, renderer: &mut T)
+    where
+        T: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let mut title_label = Label::new(self.title.clone(), Arc::clone(&self.config));
+        title_label.prepare_ui(renderer);
+        self.row_height = renderer.load_character_size('W').height();
+        self.title_label = title_label;
+
+        let rows = self.visible_rows();
+        self.first_visible = self.lines.len().saturating_sub(rows);
+        let mut labels = vec![];
+        for (index, line) in self.lines[self.first_visible..].iter().enumerate() {
+            // empty label has no size
+            let text = if line.text.is_empty() {
+                " "
+            } else {
+                line.text.as_str()
+            };
+            let mut label = Label::new(text.to_owned(), Arc::clone(&self.config));
+            label.prepare_ui(renderer);
+            let mut dest = label.dest().clone();
+            dest.set_y((index as i32 + 1) * self.row_height as i32);
+            label.set_dest(&dest);
+            labels.push(label);
+        }
+        self.labels = labels;
+    }
+}
+
+fn panel_rect(width: u32, height: u32) -> Rect {
+    let panel_height = PANEL_HEIGHT.min(height / 2).max(1);
+    Rect::new(
+        0,
+        (height - panel_height) as i32,
+        width.max(1),
+        panel_height,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::Location;
+    use crate::tests::*;
+    use rider_derive::*;
+
+    #[test]
+    fn assert_show_newest_lines() {
+        build_test_renderer!(renderer);
+        let mut widget = OutputPanel::new(config);
+        assert_eq!(widget.is_visible(), false);
+        widget.start("cargo check".to_owned(), &mut renderer);
+        assert_eq!(widget.is_visible(), true);
+        let lines = (0..100)
+            .map(|index| OutputLine::new(format!("line {}", index)))
+            .collect();
+        widget.push_lines(lines, &mut renderer);
+        assert_eq!(widget.lines().len(), 100);
+        assert_eq!(widget.labels.len(), widget.visible_rows());
+        assert_eq!(widget.first_visible, 100 - widget.visible_rows());
+        widget.toggle();
+        assert_eq!(widget.is_visible(), false);
+    }
+
+    #[test]
+    fn assert_on_left_click_opens_location() {
+        build_test_renderer!(renderer);
+        let mut widget = OutputPanel::new(config);
+        widget.start("cargo check".to_owned(), &mut renderer);
+        let location = Location {
+            path: "/tmp/a.rs".to_owned(),
+            line: 1,
+            column: 4,
+        };
+        widget.push_lines(
+            vec![
+                OutputLine::new("Compiling a".to_owned()),
+                OutputLine {
+                    text: "error: cannot find function".to_owned(),
+                    location: Some(location.clone()),
+                },
+            ],
+            &mut renderer,
+        );
+        let row_y =
+            |row: i32| widget.dest().y() + PADDING + (row + 1) * widget.row_height as i32 + 1;
+        let first = Point::new(10, row_y(0));
+        let second = Point::new(10, row_y(1));
+        assert_eq!(
+            widget.is_left_click_target(&second, &UpdateContext::Nothing),
+            true
+        );
+        assert_eq!(
+            widget.on_left_click(&first, &UpdateContext::Nothing),
+            UR::NoOp
+        );
+        assert_eq!(
+            widget.on_left_click(&second, &UpdateContext::Nothing),
+            UR::OpenLocation(location)
+        );
+    }
+
+    #[test]
+    fn assert_render() {
+        build_test_renderer!(renderer);
+        let mut canvas = CanvasMock::new();
+        let mut widget = OutputPanel::new(config);
+        widget.render(&mut canvas, &mut renderer, &RenderContext::Nothing);
+        widget.start("cargo test".to_owned(), &mut renderer);
+        widget.push_lines(vec![OutputLine::new("ok".to_owned())], &mut renderer);
+        widget.render(&mut canvas, &mut renderer, &RenderContext::Nothing);
+    }
+}
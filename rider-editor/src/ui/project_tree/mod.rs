@@ -1,4 +1,6 @@
 use crate::app::application::UpdateResult;
+use crate::app::fs_watcher::parent_directory;
+use crate::app::git::GitStatuses;
 use crate::renderer::renderer::Renderer;
 use crate::ui::filesystem::directory::DirectoryView;
 use crate::ui::horizontal_scroll_bar::HorizontalScrollBar;
@@ -70,16 +72,7 @@ impl Widget for ProjectTreeSidebar {
             dest.top_left() + Point::new(CONTENT_MARGIN_LEFT, CONTENT_MARGIN_TOP) + self.scroll(),
         );
         let res = self.dir_view.on_left_click(point, &context);
-        {
-            let dest = self.dir_view.dest();
-            let full_dest = Rect::new(
-                dest.x(),
-                dest.y(),
-                dest.width() + (2 * CONTENT_MARGIN_LEFT as u32),
-                dest.height() + (2 * CONTENT_MARGIN_TOP as u32),
-            );
-            self.full_dest = full_dest;
-        }
+        self.update_full_dest();
         res
     }
 
@@ -177,14 +170,69 @@ impl ProjectTreeSidebar {
         R: Renderer + CharacterSizeManager + ConfigHolder,
     {
         self.dir_view.open_directory(dir_path, renderer);
-        {
-            self.full_dest = Rect::new(
-                self.dir_view.dest().x(),
-                self.dir_view.dest().y(),
-                self.dir_view.dest().width() + (2 * CONTENT_MARGIN_LEFT as u32),
-                self.dir_view.dest().height() + (2 * CONTENT_MARGIN_TOP as u32),
-            );
+        self.update_full_dest();
+    }
+
+    /// Reads every expanded directory again, scroll and expanded directories are kept
+    pub fn refresh<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        self.dir_view.refresh(renderer);
+        self.update_full_dest();
+    }
+
+    /// Reads again directory containing created or removed entry
+    pub fn refresh_entry<R>(&mut self, entry_path: &str, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if let Some(parent) = parent_directory(entry_path) {
+            self.dir_view.refresh_directory(parent.as_str(), renderer);
+        }
+        self.update_full_dest();
+    }
+
+    /// Moves entry in tree, expanded directories inside moved one stay expanded
+    pub fn rename_entry<R>(&mut self, from: &str, to: &str, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let prefix = format!("{}/", from);
+        let expanded: Vec<String> = self
+            .dir_view
+            .expanded_directories()
+            .into_iter()
+            .filter(|path| path.as_str() == from || path.starts_with(prefix.as_str()))
+            .map(|path| format!("{}{}", to, &path[from.len()..]))
+            .collect();
+        self.refresh_entry(from, renderer);
+        self.refresh_entry(to, renderer);
+        for path in expanded {
+            self.dir_view.expand_directory(path.as_str(), renderer);
         }
+        self.update_full_dest();
+    }
+
+    pub fn expanded_directories(&self) -> Vec<String> {
+        self.dir_view.expanded_directories()
+    }
+
+    fn update_full_dest(&mut self) {
+        let dest = self.dir_view.dest();
+        self.full_dest = Rect::new(
+            dest.x(),
+            dest.y(),
+            dest.width() + (2 * CONTENT_MARGIN_LEFT as u32),
+            dest.height() + (2 * CONTENT_MARGIN_TOP as u32),
+        );
+    }
+
+    pub fn set_git_statuses<R>(&mut self, statuses: Arc<GitStatuses>, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        self.dir_view.set_git_statuses(&statuses, renderer);
     }
 }
 
@@ -391,4 +439,42 @@ mod tests {
         let context = UpdateContext::ParentPosition(Point::new(10, 10));
         assert_eq!(widget.is_left_click_target(&p, &context), false);
     }
+
+    //#######################################################################
+    // refresh
+    //#######################################################################
+
+    #[test]
+    fn assert_rename_entry_keeps_expanded_directories() {
+        let root = "/tmp/rider/project-tree-refresh";
+        std::fs::remove_dir_all(root).unwrap_or_default();
+        std::fs::create_dir_all(format!("{}/old/nested/deep", root)).unwrap();
+        let config = build_config();
+        let mut renderer = RendererMock::new(config.clone());
+        let mut widget = ProjectTreeSidebar::new(root.to_owned(), config);
+        widget.prepare_ui(&mut renderer);
+        widget.open_directory(format!("{}/old/nested", root), &mut renderer);
+        widget.scroll_by(0, 1);
+        let scroll = widget.scroll();
+
+        std::fs::rename(format!("{}/old", root), format!("{}/new", root)).unwrap();
+        widget.rename_entry(
+            format!("{}/old", root).as_str(),
+            format!("{}/new", root).as_str(),
+            &mut renderer,
+        );
+        assert_eq!(
+            widget.expanded_directories(),
+            vec![
+                root.to_owned(),
+                format!("{}/new", root),
+                format!("{}/new/nested", root),
+            ]
+        );
+        assert_eq!(widget.scroll(), scroll);
+
+        std::fs::remove_dir_all(format!("{}/new", root)).unwrap();
+        widget.refresh_entry(format!("{}/new", root).as_str(), &mut renderer);
+        assert_eq!(widget.expanded_directories(), vec![root.to_owned()]);
+    }
 }
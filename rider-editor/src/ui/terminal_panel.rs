@@ -0,0 +1,644 @@
+use crate::app::terminal::*;
+use crate::app::UpdateResult as UR;
+use crate::renderer::renderer::Renderer;
+use crate::renderer::*;
+use crate::ui::*;
+use crate::ui::{RenderContext as RC, UpdateContext as UC};
+use rider_config::ConfigAccess;
+use rider_config::ConfigHolder;
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use std::mem;
+use std::sync::Arc;
+
+const PADDING: i32 = 4;
+const PANEL_HEIGHT: u32 = 300;
+const TAB_MARGIN: i32 = 16;
+
+/// Tabs with shells at bottom of window, focused panel receives keyboard input.
+///
+/// Output is drawn as grid of cells sized like `W` character of editor font.
+pub struct TerminalPanel {
+    terminals: Vec<Terminal>,
+    current: usize,
+    titles: Vec<String>,
+    tab_labels: Vec<Label>,
+    search_label: Option<Label>,
+    visible: bool,
+    focused: bool,
+    cell: Rect,
+    dest: Rect,
+    background_color: Color,
+    border_color: Color,
+    text_color: Color,
+    highlight_color: Color,
+    config: ConfigAccess,
+}
+
+impl ConfigHolder for TerminalPanel {
+    fn config(&self) -> &ConfigAccess {
+        &self.config
+    }
+}
+
+impl TerminalPanel {
+    pub fn new(config: ConfigAccess) -> Self {
+        let (background_color, border_color, text_color, highlight_color, width, height) = {
+            let c = config.read().unwrap();
+            let theme = c.theme();
+            (
+                theme.background().into(),
+                theme.border_color().into(),
+                theme.code_highlighting().identifier().color().into(),
+                theme.caret().bright().color().into(),
+                c.width(),
+                c.height(),
+            )
+        };
+        Self {
+            terminals: vec![],
+            current: 0,
+            titles: vec![],
+            tab_labels: vec![],
+            search_label: None,
+            visible: false,
+            focused: false,
+            cell: Rect::new(0, 0, 1, 1),
+            dest: panel_rect(width, height),
+            background_color,
+            border_color,
+            text_color,
+            highlight_color,
+            config,
+        }
+    }
+
+    /// Starts program in new tab which becomes current one
+    pub fn open<R>(&mut self, program: &str, cwd: &str, renderer: &mut R) -> Result<(), String>
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        self.cell = renderer.load_character_size('W');
+        let (cols, rows) = self.grid_size();
+        let terminal = Terminal::spawn(program, cwd, cols, rows)?;
+        self.terminals.push(terminal);
+        self.current = self.terminals.len() - 1;
+        self.visible = true;
+        self.focused = true;
+        self.prepare_ui(renderer);
+        Ok(())
+    }
+
+    /// Closes current tab, panel is hidden when it was the last one
+    pub fn close_current<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if self.current < self.terminals.len() {
+            self.terminals.remove(self.current);
+        }
+        self.after_close(renderer);
+    }
+
+    pub fn select<R>(&mut self, index: usize, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if index < self.terminals.len() {
+            self.current = index;
+            self.prepare_ui(renderer);
+        }
+    }
+
+    pub fn next_tab<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if !self.terminals.is_empty() {
+            let index = (self.current + 1) % self.terminals.len();
+            self.select(index, renderer);
+        }
+    }
+
+    pub fn previous_tab<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if !self.terminals.is_empty() {
+            let count = self.terminals.len();
+            let index = (self.current + count - 1) % count;
+            self.select(index, renderer);
+        }
+    }
+
+    pub fn current(&self) -> Option<&Terminal> {
+        self.terminals.get(self.current)
+    }
+
+    pub fn current_mut(&mut self) -> Option<&mut Terminal> {
+        self.terminals.get_mut(self.current)
+    }
+
+    pub fn terminals(&self) -> &Vec<Terminal> {
+        &self.terminals
+    }
+
+    pub fn current_index(&self) -> usize {
+        self.current
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.visible && self.focused
+    }
+
+    pub fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    pub fn show(&mut self) {
+        self.visible = true;
+        self.focused = true;
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.focused = false;
+    }
+
+    /// Reads output of all tabs and removes ones whose program exited
+    pub fn poll<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if self.terminals.is_empty() {
+            return;
+        }
+        let count = self.terminals.len();
+        self.terminals = mem::replace(&mut self.terminals, vec![])
+            .into_iter()
+            .filter_map(|mut terminal| {
+                terminal.poll();
+                if terminal.is_running() {
+                    Some(terminal)
+                } else {
+                    None
+                }
+            })
+            .collect();
+        if self.terminals.len() != count {
+            return self.after_close(renderer);
+        }
+        let titles: Vec<String> = self.terminals.iter().map(|t| t.title()).collect();
+        if titles != self.titles {
+            self.prepare_ui(renderer);
+        }
+    }
+
+    pub fn input<R>(&mut self, bytes: &[u8], renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let searching = match self.current_mut() {
+            Some(terminal) => {
+                let searching = terminal.search().is_some();
+                terminal.input(bytes);
+                searching
+            }
+            None => return,
+        };
+        if searching {
+            self.prepare_ui(renderer);
+        }
+    }
+
+    /// Positive number of lines scrolls back to older output
+    pub fn scroll(&mut self, lines: isize) {
+        if let Some(terminal) = self.current_mut() {
+            terminal.scroll_by(lines);
+        }
+    }
+
+    pub fn start_search<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if let Some(terminal) = self.current_mut() {
+            terminal.start_search();
+        }
+        self.prepare_ui(renderer);
+    }
+
+    /// Number of columns and rows which fit below tabs
+    pub fn grid_size(&self) -> (usize, usize) {
+        let (cell_width, cell_height) = (self.cell.width().max(1), self.cell.height().max(1));
+        let width = self.dest.width().saturating_sub(2 * PADDING as u32);
+        let height = self
+            .dest
+            .height()
+            .saturating_sub(3 * PADDING as u32 + cell_height);
+        (
+            (width / cell_width).max(1) as usize,
+            (height / cell_height).max(1) as usize,
+        )
+    }
+
+    fn after_close<R>(&mut self, renderer: &mut R)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if self.terminals.is_empty() {
+            self.current = 0;
+            self.hide();
+        } else if self.current >= self.terminals.len() {
+            self.current = self.terminals.len() - 1;
+        }
+        self.prepare_ui(renderer);
+    }
+
+    fn grid_start(&self, dest: &Rect) -> Point {
+        dest.top_left() + Point::new(PADDING, 2 * PADDING + self.cell.height() as i32)
+    }
+
+    fn render_grid<C, R>(&self, canvas: &mut C, renderer: &mut R, start: Point)
+    where
+        C: CanvasAccess,
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        let terminal = match self.current() {
+            Some(terminal) => terminal,
+            None => return,
+        };
+        let screen = terminal.screen();
+        let font_details: FontDetails = renderer.config().read().unwrap().editor_config().into();
+        let (cell_width, cell_height) = (self.cell.width(), self.cell.height());
+        let first = terminal.first_visible_line();
+        let found = terminal.search().and_then(|search| {
+            search
+                .found
+                .map(|(line, column)| (line, column, search.query.chars().count()))
+        });
+
+        for row in 0..screen.rows() {
+            let line = match screen.line(first + row) {
+                Some(line) => line,
+                None => break,
+            };
+            let y = start.y() + (row as u32 * cell_height) as i32;
+            for (column, cell) in line.iter().enumerate() {
+                let x = start.x() + (column as u32 * cell_width) as i32;
+                let dest = Rect::new(x, y, cell_width, cell_height);
+                let (fg, bg) = self.cell_colors(&cell.style);
+                let matched = found.map_or(false, |(line, start, len)| {
+                    line == first + row && column >= start && column < start + len
+                });
+                if matched {
+                    canvas
+                        .render_rect(dest, self.highlight_color)
+                        .unwrap_or_else(|_| panic!("Failed to render terminal search match!"));
+                } else if let Some(bg) = bg {
+                    canvas
+                        .render_rect(dest, bg)
+                        .unwrap_or_else(|_| panic!("Failed to render terminal cell!"));
+                }
+                if cell.c == ' ' {
+                    continue;
+                }
+                let mut details = TextDetails {
+                    text: cell.c.to_string(),
+                    color: fg,
+                    font: font_details.clone(),
+                };
+                let size = renderer.load_character_size(cell.c);
+                if let Ok(texture) = renderer.load_text_tex(&mut details, font_details.clone()) {
+                    let glyph = Rect::new(x, y, size.width(), size.height());
+                    canvas
+                        .render_image(texture, size, glyph)
+                        .unwrap_or_else(|_| panic!("Failed to render terminal character!"));
+                }
+            }
+        }
+
+        let (row, column) = screen.cursor();
+        if self.is_focused()
+            && screen.cursor_visible()
+            && terminal.scroll_offset() == 0
+            && terminal.search().is_none()
+        {
+            let dest = Rect::new(
+                start.x() + (column as u32 * cell_width) as i32,
+                start.y() + (row as u32 * cell_height) as i32,
+                cell_width,
+                cell_height,
+            );
+            canvas
+                .render_border(dest, self.highlight_color)
+                .unwrap_or_else(|_| panic!("Failed to render terminal cursor!"));
+        }
+    }
+
+    /// Foreground and background of cell, default background is not drawn
+    fn cell_colors(&self, style: &CellStyle) -> (Color, Option<Color>) {
+        let fg = match style.fg {
+            TermColor::Indexed(index) if style.bold && index < 8 => TermColor::Indexed(index + 8),
+            fg => fg,
+        };
+        let fg = term_color(fg).unwrap_or(self.text_color);
+        let bg = term_color(style.bg);
+        if style.inverse {
+            (bg.unwrap_or(self.background_color), Some(fg))
+        } else {
+            (fg, bg)
+        }
+    }
+}
+
+impl Widget for TerminalPanel {
+    fn texture_path(&self) -> Option<String> {
+        None
+    }
+
+    fn dest(&self) -> &Rect {
+        &self.dest
+    }
+
+    fn set_dest(&mut self, rect: &Rect) {
+        self.dest = rect.clone();
+    }
+
+    fn source(&self) -> &Rect {
+        self.dest()
+    }
+
+    fn set_source(&mut self, rect: &Rect) {
+        self.set_dest(rect)
+    }
+
+    fn update(&mut self, _ticks: i32, _context: &UC) -> UR {
+        let (width, height) = {
+            let c = self.config.read().unwrap();
+            (c.width(), c.height())
+        };
+        self.dest = panel_rect(width, height);
+        let (cols, rows) = self.grid_size();
+        for terminal in self.terminals.iter_mut() {
+            terminal.resize(cols, rows);
+        }
+        UR::NoOp
+    }
+
+    fn on_left_click(&mut self, point: &Point, context: &UC) -> UR {
+        self.focused = true;
+        let dest = match context {
+            UC::ParentPosition(p) => move_render_point(*p, &self.dest),
+            _ => self.dest,
+        };
+        let start = dest.top_left() + Point::new(PADDING, PADDING);
+        let clicked = self
+            .tab_labels
+            .iter()
+            .position(|label| move_render_point(start, label.dest()).contains_point(*point));
+        if let Some(index) = clicked {
+            self.current = index;
+        }
+        UR::NoOp
+    }
+
+    fn is_left_click_target(&self, point: &Point, context: &UC) -> bool {
+        if !self.is_visible() {
+            return false;
+        }
+        match context {
+            UC::ParentPosition(p) => move_render_point(*p, &self.dest),
+            _ => self.dest,
+        }
+        .contains_point(*point)
+    }
+
+    fn render<C, R>(&self, canvas: &mut C, renderer: &mut R, context: &RenderContext)
+    where
+        C: CanvasAccess,
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        if !self.is_visible() {
+            return;
+        }
+        let dest = match context {
+            RC::ParentPosition(p) => move_render_point(*p, &self.dest),
+            _ => self.dest,
+        };
+        canvas.set_clip_rect(None);
+        canvas
+            .render_rect(dest, self.background_color)
+            .unwrap_or_else(|_| panic!("Failed to render terminal panel background!"));
+        canvas
+            .render_border(dest, self.border_color)
+            .unwrap_or_else(|_| panic!("Failed to render terminal panel border!"));
+
+        let tabs_start = dest.top_left() + Point::new(PADDING, PADDING);
+        for (index, label) in self.tab_labels.iter().enumerate() {
+            label.render(canvas, renderer, &RC::ParentPosition(tabs_start));
+            if index == self.current {
+                canvas
+                    .render_border(
+                        move_render_point(tabs_start, label.dest()),
+                        self.highlight_color,
+                    )
+                    .unwrap_or_else(|_| panic!("Failed to render terminal tab border!"));
+            }
+        }
+        if let Some(label) = self.search_label.as_ref() {
+            label.render(canvas, renderer, &RC::ParentPosition(tabs_start));
+        }
+
+        let start = self.grid_start(&dest);
+        self.render_grid(canvas, renderer, start);
+    }
+
+    fn prepare_ui<'l, T>(&mut self, renderer: &mut T)
+    where
+        T: Renderer + CharacterSizeManager + ConfigHolder,
+    {
+        self.cell = renderer.load_character_size('W');
+        self.titles = self.terminals.iter().map(|t| t.title()).collect();
+        let mut x = 0;
+        let mut labels = vec![];
+        for title in self.titles.iter() {
+            let mut label = Label::new(title.clone(), Arc::clone(&self.config));
+            label.prepare_ui(renderer);
+            let mut dest = label.dest().clone();
+            dest.set_x(x);
+            dest.set_y(0);
+            label.set_dest(&dest);
+            x += dest.width() as i32 + TAB_MARGIN;
+            labels.push(label);
+        }
+        self.tab_labels = labels;
+
+        let query = self
+            .current()
+            .and_then(|terminal| terminal.search())
+            .map(|search| format!("search: {}", search.query));
+        self.search_label = query.map(|query| {
+            let mut label = Label::new(query, Arc::clone(&self.config));
+            label.prepare_ui(renderer);
+            let mut dest = label.dest().clone();
+            dest.set_x(x + TAB_MARGIN);
+            dest.set_y(0);
+            label.set_dest(&dest);
+            label
+        });
+    }
+}
+
+fn panel_rect(width: u32, height: u32) -> Rect {
+    let panel_height = PANEL_HEIGHT.min(height / 2).max(1);
+    Rect::new(
+        0,
+        (height - panel_height) as i32,
+        width.max(1),
+        panel_height,
+    )
+}
+
+/// Color of cell, `None` means theme color is used
+fn term_color(color: TermColor) -> Option<Color> {
+    match color {
+        TermColor::Default => None,
+        TermColor::Indexed(index) => Some(indexed_color(index)),
+        TermColor::Rgb(r, g, b) => Some(Color::RGB(r, g, b)),
+    }
+}
+
+/// One of 256 xterm colors: 16 basic colors, 6x6x6 cube and grayscale ramp
+fn indexed_color(index: u8) -> Color {
+    const BASIC: [(u8, u8, u8); 16] = [
+        (0, 0, 0),
+        (205, 0, 0),
+        (0, 205, 0),
+        (205, 205, 0),
+        (0, 0, 238),
+        (205, 0, 205),
+        (0, 205, 205),
+        (229, 229, 229),
+        (127, 127, 127),
+        (255, 0, 0),
+        (0, 255, 0),
+        (255, 255, 0),
+        (92, 92, 255),
+        (255, 0, 255),
+        (0, 255, 255),
+        (255, 255, 255),
+    ];
+    match index {
+        0..=15 => {
+            let (r, g, b) = BASIC[index as usize];
+            Color::RGB(r, g, b)
+        }
+        16..=231 => {
+            let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+            let index = index - 16;
+            Color::RGB(level(index / 36), level(index / 6 % 6), level(index % 6))
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            Color::RGB(gray, gray, gray)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use rider_derive::*;
+    use std::thread;
+    use std::time::Duration;
+
+    fn poll_until<R, F>(widget: &mut TerminalPanel, renderer: &mut R, done: F)
+    where
+        R: Renderer + CharacterSizeManager + ConfigHolder,
+        F: Fn(&TerminalPanel) -> bool,
+    {
+        for _ in 0..500 {
+            widget.poll(renderer);
+            if done(widget) {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn assert_open_render_and_close_tabs() {
+        build_test_renderer!(renderer);
+        let mut canvas = CanvasMock::new();
+        let mut widget = TerminalPanel::new(config);
+        widget.render(&mut canvas, &mut renderer, &RenderContext::Nothing);
+        assert_eq!(widget.is_visible(), false);
+
+        widget.open("/bin/sh", "/tmp", &mut renderer).unwrap();
+        widget.open("/bin/sh", "/tmp", &mut renderer).unwrap();
+        assert_eq!(widget.is_visible(), true);
+        assert_eq!(widget.is_focused(), true);
+        assert_eq!(widget.terminals().len(), 2);
+        assert_eq!(widget.current_index(), 1);
+        assert_eq!(widget.tab_labels.len(), 2);
+        let (cols, rows) = widget.grid_size();
+        let screen = widget.current().unwrap().screen();
+        assert_eq!((screen.cols(), screen.rows()), (cols, rows));
+
+        widget.input(b"echo ready\r", &mut renderer);
+        poll_until(&mut widget, &mut renderer, |w| {
+            let screen = w.current().unwrap().screen();
+            (0..screen.total_lines()).any(|i| screen.line_text(i) == "ready")
+        });
+        widget.start_search(&mut renderer);
+        widget.input(b"ready", &mut renderer);
+        assert_eq!(widget.search_label.is_some(), true);
+        widget.render(&mut canvas, &mut renderer, &RenderContext::Nothing);
+
+        widget.next_tab(&mut renderer);
+        assert_eq!(widget.current_index(), 0);
+        widget.previous_tab(&mut renderer);
+        assert_eq!(widget.current_index(), 1);
+        widget.close_current(&mut renderer);
+        assert_eq!(widget.terminals().len(), 1);
+        assert_eq!(widget.current_index(), 0);
+        widget.input(b"exit\r", &mut renderer);
+        poll_until(&mut widget, &mut renderer, |w| w.terminals().is_empty());
+        assert_eq!(widget.terminals().len(), 0);
+        assert_eq!(widget.is_visible(), false);
+    }
+
+    #[test]
+    fn assert_on_left_click_selects_tab() {
+        build_test_renderer!(renderer);
+        let mut widget = TerminalPanel::new(config);
+        widget.open("/bin/sh", "/tmp", &mut renderer).unwrap();
+        widget.open("/bin/sh", "/tmp", &mut renderer).unwrap();
+        widget.set_focused(false);
+        let first = widget.tab_labels[0].dest().clone();
+        let point =
+            widget.dest().top_left() + Point::new(PADDING + first.x() + 1, PADDING + first.y() + 1);
+        assert_eq!(
+            widget.is_left_click_target(&point, &UpdateContext::Nothing),
+            true
+        );
+        widget.on_left_click(&point, &UpdateContext::Nothing);
+        assert_eq!(widget.current_index(), 0);
+        assert_eq!(widget.is_focused(), true);
+    }
+
+    #[test]
+    fn assert_indexed_colors() {
+        assert_eq!(indexed_color(1), Color::RGB(205, 0, 0));
+        assert_eq!(indexed_color(16), Color::RGB(0, 0, 0));
+        assert_eq!(indexed_color(196), Color::RGB(255, 0, 0));
+        assert_eq!(indexed_color(232), Color::RGB(8, 8, 8));
+        assert_eq!(term_color(TermColor::Default), None);
+    }
+}
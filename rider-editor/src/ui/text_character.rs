@@ -20,6 +20,7 @@ pub struct TextCharacter {
     position: usize,
     line: usize,
     last_in_line: bool,
+    hidden: bool,
     source: Rect,
     dest: Rect,
     color: Color,
@@ -40,6 +41,7 @@ impl TextCharacter {
             position,
             line,
             last_in_line,
+            hidden: false,
             source: Rect::new(0, 0, 0, 0),
             dest: Rect::new(0, 0, 0, 0),
             color,
@@ -55,7 +57,19 @@ impl TextCharacter {
         &self.color
     }
 
+    pub fn is_hidden(&self) -> bool {
+        self.hidden
+    }
+
+    /// Character on folded line, it takes no space and is neither rendered nor clickable
+    pub fn hide(&mut self, current: &Rect) {
+        self.hidden = true;
+        self.dest.set_x(current.x());
+        self.dest.set_y(current.y());
+    }
+
     pub fn update_position(&mut self, current: &mut Rect) {
+        self.hidden = false;
         if self.is_new_line() {
             let y = self.source.height() as i32;
             self.dest.set_x(current.x());
@@ -110,6 +124,19 @@ impl Widget for TextCharacter {
         self.source = rect.clone();
     }
 
+    fn is_left_click_target(&self, point: &Point, context: &UpdateContext) -> bool {
+        if self.hidden {
+            return false;
+        }
+        match *context {
+            UpdateContext::ParentPosition(p) | UpdateContext::ScrolledBy(p) => {
+                move_render_point(p.clone(), &self.dest)
+            }
+            _ => self.dest.clone(),
+        }
+        .contains_point(point.clone())
+    }
+
     fn on_left_click(&mut self, _point: &Point, _context: &UpdateContext) -> UR {
         UR::MoveCaret(
             self.dest.clone(),
@@ -126,6 +153,9 @@ impl Widget for TextCharacter {
         C: CanvasAccess,
         R: Renderer + CharacterSizeManager + ConfigHolder,
     {
+        if self.hidden {
+            return;
+        }
         let font_details: FontDetails = renderer.config().read().unwrap().editor_config().into();
 
         let c = match self.text_character.clone() {
@@ -334,7 +364,7 @@ mod test_getters {
 mod test_own_methods {
     use crate::tests::*;
     use crate::ui::*;
-    use sdl2::rect::Rect;
+    use sdl2::rect::{Point, Rect};
     use std::sync::*;
 
     #[test]
@@ -376,6 +406,32 @@ mod test_own_methods {
         assert_eq!(widget.dest(), &Rect::new(10, 23, 70, 80));
         assert_eq!(widget.source(), &Rect::new(50, 60, 70, 80));
     }
+
+    #[test]
+    fn must_not_move_current_when_hidden() {
+        let config = build_config();
+        let mut widget = TextCharacter::new(
+            'W',
+            0,
+            0,
+            true,
+            sdl2::pixels::Color::RGB(0, 0, 0),
+            Arc::clone(&config),
+        );
+        widget.set_dest(&Rect::new(10, 20, 30, 40));
+        widget.set_source(&Rect::new(50, 60, 70, 80));
+        let current = Rect::new(10, 23, 0, 0);
+        widget.hide(&current);
+        assert_eq!(widget.is_hidden(), true);
+        assert_eq!(widget.dest(), &Rect::new(10, 23, 30, 40));
+        assert_eq!(
+            widget.is_left_click_target(&Point::new(11, 24), &UpdateContext::Nothing),
+            false
+        );
+        let mut current = current;
+        widget.update_position(&mut current);
+        assert_eq!(widget.is_hidden(), false);
+    }
 }
 
 #[cfg(test)]
@@ -18,6 +18,14 @@ pub fn create(directories: &Directories) -> std::io::Result<()> {
         fs::create_dir_all(&directories.log_dir)?;
     }
 
+    if !directories.syntaxes_dir.exists() {
+        fs::create_dir_all(&directories.syntaxes_dir)?;
+    }
+
+    if !directories.snippets_dir.exists() {
+        fs::create_dir_all(&directories.snippets_dir)?;
+    }
+
     if !directories.project_dir.exists() {
         fs::create_dir_all(&directories.project_dir)?;
     }
@@ -83,6 +91,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn assert_create_syntaxes() {
+        let unique = Uuid::new_v4();
+        let test_path = join("/tmp/rider-tests".to_owned(), unique.to_string());
+        create_dir_all(test_path.clone()).unwrap();
+        let directories = Directories::new(Some(test_path.clone()), None);
+        assert_eq!(create(&directories).is_ok(), true);
+        assert_eq!(
+            Path::new(join(test_path.clone(), "rider/syntaxes".to_owned()).as_str()).exists(),
+            true
+        );
+    }
+
+    #[test]
+    fn assert_create_snippets() {
+        let unique = Uuid::new_v4();
+        let test_path = join("/tmp/rider-tests".to_owned(), unique.to_string());
+        create_dir_all(test_path.clone()).unwrap();
+        let directories = Directories::new(Some(test_path.clone()), None);
+        assert_eq!(create(&directories).is_ok(), true);
+        assert_eq!(
+            Path::new(join(test_path.clone(), "rider/snippets".to_owned()).as_str()).exists(),
+            true
+        );
+    }
+
     #[test]
     fn assert_create_themes() {
         let unique = Uuid::new_v4();
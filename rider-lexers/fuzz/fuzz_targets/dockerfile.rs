@@ -0,0 +1,12 @@
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate rider_lexers;
+
+use rider_lexers::{invariants, Language};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(text) = std::str::from_utf8(data) {
+        invariants::check(text, Language::Dockerfile).unwrap();
+    }
+});
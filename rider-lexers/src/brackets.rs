@@ -0,0 +1,159 @@
+use crate::*;
+use std::collections::HashMap;
+
+/// Bracket pairs found in separator tokens, positions are char offsets.
+///
+/// Strings and comments are separate token types so brackets inside them are never paired.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Brackets {
+    pairs: HashMap<usize, usize>,
+    unmatched: Vec<usize>,
+}
+
+impl Brackets {
+    pub fn new<'a, I>(tokens: I) -> Self
+    where
+        I: IntoIterator<Item = &'a TokenType>,
+    {
+        let mut brackets = Self::default();
+        let mut stack: Vec<(char, usize)> = vec![];
+        for token in tokens {
+            let (bracket, position) = match bracket_of(token) {
+                Some(b) => b,
+                _ => continue,
+            };
+            match closing_for(bracket) {
+                Some(closing) => stack.push((closing, position)),
+                None => match stack.last() {
+                    Some(&(closing, open)) if closing == bracket => {
+                        stack.pop();
+                        brackets.pairs.insert(open, position);
+                        brackets.pairs.insert(position, open);
+                    }
+                    _ => brackets.unmatched.push(position),
+                },
+            }
+        }
+        brackets
+            .unmatched
+            .extend(stack.into_iter().map(|(_, position)| position));
+        brackets.unmatched.sort();
+        brackets
+    }
+
+    /// Position of bracket paired with bracket at given position
+    pub fn matching(&self, position: usize) -> Option<usize> {
+        self.pairs.get(&position).cloned()
+    }
+
+    /// Pair for bracket right after caret or, if there is none, right before it
+    pub fn pair_near(&self, caret: usize) -> Option<(usize, usize)> {
+        self.matching(caret)
+            .map(|other| (caret, other))
+            .or_else(|| {
+                caret
+                    .checked_sub(1)
+                    .and_then(|before| self.matching(before).map(|other| (before, other)))
+            })
+    }
+
+    /// Matched pairs as opening and closing position, ordered by opening bracket
+    pub fn pairs(&self) -> Vec<(usize, usize)> {
+        let mut pairs: Vec<(usize, usize)> = self
+            .pairs
+            .iter()
+            .filter(|(open, close)| open < close)
+            .map(|(open, close)| (*open, *close))
+            .collect();
+        pairs.sort();
+        pairs
+    }
+
+    pub fn is_unmatched(&self, position: usize) -> bool {
+        self.unmatched.binary_search(&position).is_ok()
+    }
+
+    pub fn unmatched(&self) -> &Vec<usize> {
+        &self.unmatched
+    }
+}
+
+/// Bracket char and its position, `${` opens template substitution
+pub(crate) fn bracket_of(token: &TokenType) -> Option<(char, usize)> {
+    let token = match token {
+        TokenType::Separator { token } => token,
+        _ => return None,
+    };
+    let bracket = match token.text().as_str() {
+        "${" => '{',
+        "(" | ")" | "[" | "]" | "{" | "}" => token.text().chars().next()?,
+        _ => return None,
+    };
+    Some((bracket, token.char_span().hi - 1))
+}
+
+fn closing_for(c: char) -> Option<char> {
+    match c {
+        '(' => Some(')'),
+        '[' => Some(']'),
+        '{' => Some('}'),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brackets(text: &str, language: Language) -> Brackets {
+        Brackets::new(parse(text.to_owned(), language).iter())
+    }
+
+    #[test]
+    fn must_pair_nested_brackets() {
+        let result = brackets("fn a() { [1] }", Language::Rust);
+        assert_eq!(result.matching(4), Some(5));
+        assert_eq!(result.matching(7), Some(13));
+        assert_eq!(result.matching(13), Some(7));
+        assert_eq!(result.matching(9), Some(11));
+        assert_eq!(result.unmatched().is_empty(), true);
+        assert_eq!(result.pairs(), vec![(4, 5), (7, 13), (9, 11)]);
+    }
+
+    #[test]
+    fn must_ignore_brackets_in_strings_and_comments() {
+        let result = brackets("{ \"}\" // }\n}", Language::C);
+        assert_eq!(result.matching(0), Some(11));
+        assert_eq!(result.unmatched().is_empty(), true);
+    }
+
+    #[test]
+    fn must_flag_unmatched_brackets() {
+        let result = brackets("(] {", Language::Rust);
+        assert_eq!(result.unmatched(), &vec![0, 1, 3]);
+        assert_eq!(result.is_unmatched(1), true);
+        assert_eq!(result.is_unmatched(2), false);
+    }
+
+    #[test]
+    fn must_use_char_positions() {
+        let result = brackets("[\"ąę\", [1]]", Language::Json);
+        assert_eq!(result.matching(0), Some(10));
+    }
+
+    #[test]
+    fn must_pair_template_substitution() {
+        let result = brackets("`${a}`", Language::JavaScript);
+        assert_eq!(result.matching(2), Some(4));
+    }
+
+    #[test]
+    fn must_find_pair_near_caret() {
+        let result = brackets("(a)", Language::Rust);
+        assert_eq!(result.pair_near(0), Some((0, 2)));
+        assert_eq!(result.pair_near(1), Some((0, 2)));
+        assert_eq!(result.pair_near(3), Some((2, 0)));
+        assert_eq!(result.pair_near(2), Some((2, 0)));
+        assert_eq!(brackets("a b", Language::Rust).pair_near(1), None);
+    }
+}
@@ -0,0 +1,560 @@
+pub mod lexer {
+    use crate::*;
+
+    pub const KEYWORDS: &[&str] = &[
+        "auto", "bool", "break", "case", "char", "const", "continue", "default", "do", "double",
+        "else", "enum", "extern", "false", "float", "for", "goto", "if", "inline", "int", "long",
+        "register", "restrict", "return", "short", "signed", "sizeof", "static", "struct",
+        "switch", "true", "typedef", "union", "unsigned", "void", "volatile", "while", "_Bool",
+        "NULL",
+    ];
+
+    pub const CPP_KEYWORDS: &[&str] = &[
+        "alignas",
+        "alignof",
+        "asm",
+        "catch",
+        "char8_t",
+        "char16_t",
+        "char32_t",
+        "class",
+        "co_await",
+        "co_return",
+        "co_yield",
+        "concept",
+        "const_cast",
+        "consteval",
+        "constexpr",
+        "constinit",
+        "decltype",
+        "delete",
+        "dynamic_cast",
+        "explicit",
+        "export",
+        "final",
+        "friend",
+        "mutable",
+        "namespace",
+        "new",
+        "noexcept",
+        "nullptr",
+        "operator",
+        "override",
+        "private",
+        "protected",
+        "public",
+        "reinterpret_cast",
+        "requires",
+        "static_assert",
+        "static_cast",
+        "template",
+        "this",
+        "thread_local",
+        "throw",
+        "try",
+        "typeid",
+        "typename",
+        "using",
+        "virtual",
+        "wchar_t",
+    ];
+
+    pub struct Lexer {
+        content: String,
+        buffer: String,
+        line: usize,
+        character: usize,
+        start: usize,
+        char_start: usize,
+        cpp: bool,
+    }
+
+    impl Lexer {
+        pub fn new(content: String) -> Self {
+            Self {
+                content,
+                buffer: String::new(),
+                line: 0,
+                character: 0,
+                start: 0,
+                char_start: 0,
+                cpp: false,
+            }
+        }
+
+        pub fn cpp(content: String) -> Self {
+            Self {
+                cpp: true,
+                ..Self::new(content)
+            }
+        }
+
+        pub fn tokenize(&mut self) -> Vec<TokenType> {
+            let mut tokens = vec![];
+            let chars: Vec<char> = self.content.chars().collect();
+            let mut line_start = true;
+            let mut include = false;
+            let mut index = 0;
+            while index < chars.len() {
+                let c = chars[index];
+                let next = chars.get(index + 1).cloned();
+                index = match c {
+                    ' ' | '\t' | '\r' | '\n' => {
+                        if c == '\n' {
+                            line_start = true;
+                            include = false;
+                        }
+                        self.push_range(&chars, index, index + 1, &mut tokens, |b| {
+                            lexer_whitespace!(b)
+                        })
+                    }
+                    '/' if next == Some('/') => {
+                        let len = run_length(&chars, index, |c| c != '\n');
+                        self.push_range(&chars, index, index + len, &mut tokens, |b| {
+                            lexer_comment!(b)
+                        })
+                    }
+                    '/' if next == Some('*') => match find(&chars, index + 2, &['*', '/']) {
+                        Some(end) => self
+                            .push_range(&chars, index, end + 2, &mut tokens, |b| lexer_comment!(b)),
+                        None => self.push_range(&chars, index, chars.len(), &mut tokens, |b| {
+                            lexer_error!(b)
+                        }),
+                    },
+                    '#' if line_start => {
+                        let spaces = run_length(&chars, index + 1, |c| c == ' ' || c == '\t');
+                        let word = run_length(&chars, index + 1 + spaces, is_identifier_part);
+                        let end = index + 1 + spaces + word;
+                        let directive: String = chars[end - word..end].iter().collect();
+                        include = directive == "include" || directive == "import";
+                        self.push_range(&chars, index, end, &mut tokens, |b| lexer_keyword!(b))
+                    }
+                    '<' if include => match chars[index..]
+                        .iter()
+                        .take_while(|c| **c != '\n')
+                        .position(|c| *c == '>')
+                    {
+                        Some(len) => {
+                            self.push_range(&chars, index, index + len + 1, &mut tokens, |b| {
+                                lexer_string!(b)
+                            })
+                        }
+                        None => self.push_range(&chars, index, index + 1, &mut tokens, |b| {
+                            lexer_operator!(b)
+                        }),
+                    },
+                    '"' => self.tokenize_string(&chars, index, 0, &mut tokens),
+                    '\'' => self.tokenize_char(&chars, index, 0, &mut tokens),
+                    '0'..='9' => {
+                        let len = self.number_length(&chars, index);
+                        self.push_range(&chars, index, index + len, &mut tokens, |b| {
+                            lexer_literal!(b)
+                        })
+                    }
+                    '.' if next.map_or(false, |c| c.is_ascii_digit()) => {
+                        let len = self.number_length(&chars, index);
+                        self.push_range(&chars, index, index + len, &mut tokens, |b| {
+                            lexer_literal!(b)
+                        })
+                    }
+                    ':' if next == Some(':') => {
+                        self.push_range(&chars, index, index + 2, &mut tokens, |b| {
+                            lexer_separator!(b)
+                        })
+                    }
+                    '(' | ')' | '[' | ']' | '{' | '}' | ',' | ':' | ';' | '.' => {
+                        self.push_range(&chars, index, index + 1, &mut tokens, |b| {
+                            lexer_separator!(b)
+                        })
+                    }
+                    _ if is_operator(c) => {
+                        let len = run_length(&chars, index, is_operator);
+                        self.push_range(&chars, index, index + len, &mut tokens, |b| {
+                            lexer_operator!(b)
+                        })
+                    }
+                    _ if is_identifier_start(c) => {
+                        let len = run_length(&chars, index, is_identifier_part);
+                        let word: String = chars[index..index + len].iter().collect();
+                        match chars.get(index + len) {
+                            Some('"') if self.is_raw_prefix(&word) => {
+                                self.tokenize_raw_string(&chars, index, len, &mut tokens)
+                            }
+                            Some('"') if is_string_prefix(&word) => {
+                                self.tokenize_string(&chars, index, len, &mut tokens)
+                            }
+                            Some('\'') if is_string_prefix(&word) => {
+                                self.tokenize_char(&chars, index, len, &mut tokens)
+                            }
+                            _ if self.is_keyword(&word) => {
+                                self.push_range(&chars, index, index + len, &mut tokens, |b| {
+                                    lexer_keyword!(b)
+                                })
+                            }
+                            _ => self.push_range(&chars, index, index + len, &mut tokens, |b| {
+                                lexer_identifier!(b)
+                            }),
+                        }
+                    }
+                    _ => self.push_range(&chars, index, index + 1, &mut tokens, |b| {
+                        lexer_identifier!(b)
+                    }),
+                };
+                if !c.is_whitespace() {
+                    line_start = false;
+                }
+            }
+            tokens
+        }
+
+        fn is_keyword(&self, word: &str) -> bool {
+            KEYWORDS.contains(&word) || (self.cpp && CPP_KEYWORDS.contains(&word))
+        }
+
+        fn is_raw_prefix(&self, word: &str) -> bool {
+            self.cpp
+                && match word {
+                    "R" | "LR" | "uR" | "UR" | "u8R" => true,
+                    _ => false,
+                }
+        }
+
+        fn tokenize_string(
+            &mut self,
+            chars: &[char],
+            from: usize,
+            prefix: usize,
+            tokens: &mut Vec<TokenType>,
+        ) -> usize {
+            match find_closing(chars, from + prefix, '"') {
+                Some(end) => self.push_range(chars, from, end + 1, tokens, |b| lexer_string!(b)),
+                None => {
+                    let len = run_length(chars, from, |c| c != '\n');
+                    self.push_range(chars, from, from + len, tokens, |b| lexer_error!(b))
+                }
+            }
+        }
+
+        fn tokenize_char(
+            &mut self,
+            chars: &[char],
+            from: usize,
+            prefix: usize,
+            tokens: &mut Vec<TokenType>,
+        ) -> usize {
+            match find_closing(chars, from + prefix, '\'') {
+                Some(end) => self.push_range(chars, from, end + 1, tokens, |b| lexer_literal!(b)),
+                None => {
+                    let len = run_length(chars, from, |c| c != '\n');
+                    self.push_range(chars, from, from + len, tokens, |b| lexer_error!(b))
+                }
+            }
+        }
+
+        /// C++ raw string `R"delimiter( ... )delimiter"`, may span many lines
+        fn tokenize_raw_string(
+            &mut self,
+            chars: &[char],
+            from: usize,
+            prefix: usize,
+            tokens: &mut Vec<TokenType>,
+        ) -> usize {
+            let quote = from + prefix;
+            let delimiter = run_length(chars, quote + 1, |c| c != '(' && !c.is_whitespace());
+            if chars.get(quote + 1 + delimiter) != Some(&'(') {
+                return self.tokenize_string(chars, from, prefix, tokens);
+            }
+            let mut closing = vec![')'];
+            closing.extend_from_slice(&chars[quote + 1..quote + 1 + delimiter]);
+            closing.push('"');
+            match find(chars, quote + 2 + delimiter, &closing) {
+                Some(end) => self.push_range(chars, from, end + closing.len(), tokens, |b| {
+                    lexer_string!(b)
+                }),
+                None => self.push_range(chars, from, chars.len(), tokens, |b| lexer_error!(b)),
+            }
+        }
+
+        fn number_length(&self, chars: &[char], from: usize) -> usize {
+            let cpp = self.cpp;
+            let separator = |c: char| cpp && c == '\'';
+            let radix = match (chars.get(from), chars.get(from + 1)) {
+                (Some('0'), Some('x')) | (Some('0'), Some('X')) => 16,
+                (Some('0'), Some('b')) | (Some('0'), Some('B')) => 2,
+                _ => 10,
+            };
+            let mut index = if radix != 10 {
+                from + 2 + run_length(chars, from + 2, |c| c.is_digit(radix) || separator(c))
+            } else {
+                let mut index =
+                    from + run_length(chars, from, |c| c.is_ascii_digit() || separator(c));
+                if chars.get(index) == Some(&'.') {
+                    index += 1 + run_length(chars, index + 1, |c| c.is_ascii_digit());
+                }
+                if let Some('e') | Some('E') = chars.get(index) {
+                    let mut exponent = index + 1;
+                    if let Some('+') | Some('-') = chars.get(exponent) {
+                        exponent += 1;
+                    }
+                    let digits = run_length(chars, exponent, |c| c.is_ascii_digit());
+                    if digits > 0 {
+                        index = exponent + digits;
+                    }
+                }
+                index
+            };
+            index += run_length(chars, index, |c| "uUlLfF".contains(c));
+            index - from
+        }
+
+        fn push_range<F>(
+            &mut self,
+            chars: &[char],
+            from: usize,
+            to: usize,
+            tokens: &mut Vec<TokenType>,
+            builder: F,
+        ) -> usize
+        where
+            F: Fn(&Lexer) -> TokenType,
+        {
+            if from < to {
+                self.buffer = chars[from..to].iter().collect();
+                tokens.push(builder(&self));
+                self.clear();
+            }
+            to
+        }
+
+        fn clear(&mut self) {
+            for c in self.buffer.chars() {
+                if c == '\n' {
+                    self.line += 1;
+                    self.character = 0;
+                } else {
+                    self.character += 1;
+                }
+            }
+            self.start += self.buffer.len();
+            self.char_start += self.buffer.chars().count();
+            self.buffer.clear();
+        }
+    }
+
+    fn run_length<F>(chars: &[char], from: usize, f: F) -> usize
+    where
+        F: Fn(char) -> bool,
+    {
+        chars[from..].iter().take_while(|c| f(**c)).count()
+    }
+
+    fn find(chars: &[char], from: usize, needle: &[char]) -> Option<usize> {
+        (from..chars.len()).find(|index| chars[*index..].starts_with(needle))
+    }
+
+    /// Finds closing quote on the same line, skips escaped characters
+    fn find_closing(chars: &[char], quote: usize, c: char) -> Option<usize> {
+        let mut index = quote + 1;
+        while index < chars.len() {
+            match chars[index] {
+                '\\' => index += 2,
+                '\n' => return None,
+                current if current == c => return Some(index),
+                _ => index += 1,
+            }
+        }
+        None
+    }
+
+    fn is_identifier_start(c: char) -> bool {
+        c.is_alphabetic() || c == '_'
+    }
+
+    fn is_identifier_part(c: char) -> bool {
+        c.is_alphanumeric() || c == '_'
+    }
+
+    fn is_operator(c: char) -> bool {
+        "+-*/%=<>!&|^~?".contains(c)
+    }
+
+    fn is_string_prefix(word: &str) -> bool {
+        match word {
+            "L" | "u" | "U" | "u8" => true,
+            _ => false,
+        }
+    }
+
+    impl TokenBuilder for Lexer {
+        fn text(&self) -> String {
+            self.buffer.to_string()
+        }
+
+        fn line(&self) -> usize {
+            self.line
+        }
+
+        fn character(&self) -> usize {
+            self.character
+        }
+
+        fn start(&self) -> usize {
+            self.start
+        }
+
+        fn end(&self, text: &String) -> usize {
+            self.start + text.len()
+        }
+
+        fn char_start(&self) -> usize {
+            self.char_start
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        struct BuilderMock {
+            line: usize,
+            character: usize,
+            start: usize,
+            char_start: usize,
+            text: String,
+        }
+
+        impl TokenBuilder for BuilderMock {
+            fn text(&self) -> String {
+                self.text.clone()
+            }
+
+            fn line(&self) -> usize {
+                self.line
+            }
+
+            fn character(&self) -> usize {
+                self.character
+            }
+
+            fn start(&self) -> usize {
+                self.start
+            }
+
+            fn end(&self, current_text: &String) -> usize {
+                self.start + current_text.len()
+            }
+
+            fn char_start(&self) -> usize {
+                self.char_start
+            }
+        }
+
+        macro_rules! builder {
+            ($text: expr, $line: expr, $character: expr, $start: expr) => {
+                BuilderMock {
+                    line: $line,
+                    character: $character,
+                    start: $start,
+                    char_start: $start,
+                    text: $text.to_owned(),
+                }
+            };
+        }
+
+        #[test]
+        fn parse_empty() {
+            let code = "".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_include() {
+            let code = "#include <stdio.h>\nint a;".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_keyword!(builder!("#include", 0, 0, 0)),
+                lexer_whitespace!(builder!(" ", 0, 8, 8)),
+                lexer_string!(builder!("<stdio.h>", 0, 9, 9)),
+                lexer_whitespace!(builder!("\n", 0, 18, 18)),
+                lexer_keyword!(builder!("int", 1, 0, 19)),
+                lexer_whitespace!(builder!(" ", 1, 3, 22)),
+                lexer_identifier!(builder!("a", 1, 4, 23)),
+                lexer_separator!(builder!(";", 1, 5, 24)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_literals() {
+            let code = "'a' L\"b\" 10UL /* c */".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_literal!(builder!("'a'", 0, 0, 0)),
+                lexer_whitespace!(builder!(" ", 0, 3, 3)),
+                lexer_string!(builder!("L\"b\"", 0, 4, 4)),
+                lexer_whitespace!(builder!(" ", 0, 8, 8)),
+                lexer_literal!(builder!("10UL", 0, 9, 9)),
+                lexer_whitespace!(builder!(" ", 0, 13, 13)),
+                lexer_comment!(builder!("/* c */", 0, 14, 14)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_cpp_raw_string() {
+            let code = "auto s = R\"x(a\")\nb)x\";".to_owned();
+            let mut lexer = Lexer::cpp(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_keyword!(builder!("auto", 0, 0, 0)),
+                lexer_whitespace!(builder!(" ", 0, 4, 4)),
+                lexer_identifier!(builder!("s", 0, 5, 5)),
+                lexer_whitespace!(builder!(" ", 0, 6, 6)),
+                lexer_operator!(builder!("=", 0, 7, 7)),
+                lexer_whitespace!(builder!(" ", 0, 8, 8)),
+                lexer_string!(builder!("R\"x(a\")\nb)x\"", 0, 9, 9)),
+                lexer_separator!(builder!(";", 1, 4, 21)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_cpp_keywords() {
+            let code = "std::vector<int> v; // d".to_owned();
+            let mut lexer = Lexer::cpp(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_identifier!(builder!("std", 0, 0, 0)),
+                lexer_separator!(builder!("::", 0, 3, 3)),
+                lexer_identifier!(builder!("vector", 0, 5, 5)),
+                lexer_operator!(builder!("<", 0, 11, 11)),
+                lexer_keyword!(builder!("int", 0, 12, 12)),
+                lexer_operator!(builder!(">", 0, 15, 15)),
+                lexer_whitespace!(builder!(" ", 0, 16, 16)),
+                lexer_identifier!(builder!("v", 0, 17, 17)),
+                lexer_separator!(builder!(";", 0, 18, 18)),
+                lexer_whitespace!(builder!(" ", 0, 19, 19)),
+                lexer_comment!(builder!("// d", 0, 20, 20)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_unterminated_string() {
+            let code = "\"a\nb".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_error!(builder!("\"a", 0, 0, 0)),
+                lexer_whitespace!(builder!("\n", 0, 2, 2)),
+                lexer_identifier!(builder!("b", 1, 0, 3)),
+            ];
+            assert_eq!(result, expected)
+        }
+    }
+}
@@ -0,0 +1,262 @@
+pub mod lexer {
+    use crate::*;
+
+    pub struct Lexer {
+        content: String,
+        buffer: String,
+        line: usize,
+        character: usize,
+        start: usize,
+        char_start: usize,
+        old_lines: usize,
+        new_lines: usize,
+    }
+
+    impl Lexer {
+        pub fn new(content: String) -> Self {
+            Self {
+                content,
+                buffer: String::new(),
+                line: 0,
+                character: 0,
+                start: 0,
+                char_start: 0,
+                old_lines: 0,
+                new_lines: 0,
+            }
+        }
+
+        pub fn tokenize(&mut self) -> Vec<TokenType> {
+            let mut tokens = vec![];
+            let content = self.content.clone();
+            for text in content.split_inclusive('\n') {
+                let line = text.trim_end_matches('\n');
+                if !line.is_empty() {
+                    self.tokenize_line(line, &mut tokens);
+                }
+                if text.ends_with('\n') {
+                    self.push("\n", &mut tokens, |b| lexer_whitespace!(b));
+                }
+            }
+            tokens
+        }
+
+        fn tokenize_line(&mut self, line: &str, tokens: &mut Vec<TokenType>) {
+            let in_hunk = self.old_lines > 0 || self.new_lines > 0;
+            match line.chars().next() {
+                Some('+') if in_hunk => {
+                    self.new_lines = self.new_lines.saturating_sub(1);
+                    self.push(line, tokens, |b| lexer_diff_add!(b));
+                }
+                Some('-') if in_hunk => {
+                    self.old_lines = self.old_lines.saturating_sub(1);
+                    self.push(line, tokens, |b| lexer_diff_delete!(b));
+                }
+                Some(' ') if in_hunk => {
+                    self.old_lines = self.old_lines.saturating_sub(1);
+                    self.new_lines = self.new_lines.saturating_sub(1);
+                    self.push(line, tokens, |b| lexer_identifier!(b));
+                }
+                Some('\\') => self.push(line, tokens, |b| lexer_comment!(b)),
+                _ if line.starts_with("@@") => {
+                    let (old_lines, new_lines) = hunk_sizes(line);
+                    self.old_lines = old_lines;
+                    self.new_lines = new_lines;
+                    self.push(line, tokens, |b| lexer_diff_change!(b));
+                }
+                _ if is_file_header(line) => {
+                    self.old_lines = 0;
+                    self.new_lines = 0;
+                    self.push(line, tokens, |b| lexer_keyword!(b));
+                }
+                _ => self.push(line, tokens, |b| lexer_comment!(b)),
+            }
+        }
+
+        fn push<F>(&mut self, text: &str, tokens: &mut Vec<TokenType>, builder: F)
+        where
+            F: Fn(&Lexer) -> TokenType,
+        {
+            self.buffer.push_str(text);
+            tokens.push(builder(&self));
+            self.clear();
+        }
+
+        fn clear(&mut self) {
+            for c in self.buffer.chars() {
+                if c == '\n' {
+                    self.line += 1;
+                    self.character = 0;
+                } else {
+                    self.character += 1;
+                }
+            }
+            self.start += self.buffer.len();
+            self.char_start += self.buffer.chars().count();
+            self.buffer.clear();
+        }
+    }
+
+    fn is_file_header(line: &str) -> bool {
+        [
+            "diff ",
+            "index ",
+            "--- ",
+            "+++ ",
+            "new file",
+            "deleted file",
+            "similarity",
+            "rename ",
+        ]
+        .iter()
+        .any(|prefix| line.starts_with(prefix))
+    }
+
+    /// Reads line counts from `@@ -1,3 +1,4 @@` header, missing count means 1
+    fn hunk_sizes(line: &str) -> (usize, usize) {
+        let mut old_lines = 0;
+        let mut new_lines = 0;
+        for range in line.trim_start_matches('@').split_whitespace() {
+            let count = |r: &str| match r.find(',') {
+                Some(index) => r[index + 1..].parse().unwrap_or(0),
+                None => 1,
+            };
+            if range.starts_with('-') {
+                old_lines = count(range);
+            } else if range.starts_with('+') {
+                new_lines = count(range);
+            } else if range.starts_with("@@") {
+                break;
+            }
+        }
+        (old_lines, new_lines)
+    }
+
+    impl TokenBuilder for Lexer {
+        fn text(&self) -> String {
+            self.buffer.to_string()
+        }
+
+        fn line(&self) -> usize {
+            self.line
+        }
+
+        fn character(&self) -> usize {
+            self.character
+        }
+
+        fn start(&self) -> usize {
+            self.start
+        }
+
+        fn end(&self, text: &String) -> usize {
+            self.start + text.len()
+        }
+
+        fn char_start(&self) -> usize {
+            self.char_start
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        struct BuilderMock {
+            line: usize,
+            character: usize,
+            start: usize,
+            char_start: usize,
+            text: String,
+        }
+
+        impl TokenBuilder for BuilderMock {
+            fn text(&self) -> String {
+                self.text.clone()
+            }
+
+            fn line(&self) -> usize {
+                self.line
+            }
+
+            fn character(&self) -> usize {
+                self.character
+            }
+
+            fn start(&self) -> usize {
+                self.start
+            }
+
+            fn end(&self, current_text: &String) -> usize {
+                self.start + current_text.len()
+            }
+
+            fn char_start(&self) -> usize {
+                self.char_start
+            }
+        }
+
+        macro_rules! builder {
+            ($text: expr, $line: expr, $character: expr, $start: expr) => {
+                BuilderMock {
+                    line: $line,
+                    character: $character,
+                    start: $start,
+                    char_start: $start,
+                    text: $text.to_owned(),
+                }
+            };
+        }
+
+        #[test]
+        fn parse_empty() {
+            let code = "".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_hunk() {
+            let code = "--- a\n+++ b\n@@ -1,2 +1 @@\n a\n-b\n".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_keyword!(builder!("--- a", 0, 0, 0)),
+                lexer_whitespace!(builder!("\n", 0, 5, 5)),
+                lexer_keyword!(builder!("+++ b", 1, 0, 6)),
+                lexer_whitespace!(builder!("\n", 1, 5, 11)),
+                lexer_diff_change!(builder!("@@ -1,2 +1 @@", 2, 0, 12)),
+                lexer_whitespace!(builder!("\n", 2, 13, 25)),
+                lexer_identifier!(builder!(" a", 3, 0, 26)),
+                lexer_whitespace!(builder!("\n", 3, 2, 28)),
+                lexer_diff_delete!(builder!("-b", 4, 0, 29)),
+                lexer_whitespace!(builder!("\n", 4, 2, 31)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_removed_line_looking_like_header() {
+            let code = "@@ -1 +1 @@\n--- x\n+++ y\n".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_diff_change!(builder!("@@ -1 +1 @@", 0, 0, 0)),
+                lexer_whitespace!(builder!("\n", 0, 11, 11)),
+                lexer_diff_delete!(builder!("--- x", 1, 0, 12)),
+                lexer_whitespace!(builder!("\n", 1, 5, 17)),
+                lexer_diff_add!(builder!("+++ y", 2, 0, 18)),
+                lexer_whitespace!(builder!("\n", 2, 5, 23)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn must_read_hunk_sizes() {
+            assert_eq!(hunk_sizes("@@ -1,3 +1,4 @@ fn main()"), (3, 4));
+            assert_eq!(hunk_sizes("@@ -1 +0,0 @@"), (1, 0));
+        }
+    }
+}
@@ -0,0 +1,362 @@
+pub mod lexer {
+    use crate::*;
+
+    pub const INSTRUCTIONS: &[&str] = &[
+        "ADD",
+        "ARG",
+        "CMD",
+        "COPY",
+        "ENTRYPOINT",
+        "ENV",
+        "EXPOSE",
+        "FROM",
+        "HEALTHCHECK",
+        "LABEL",
+        "MAINTAINER",
+        "ONBUILD",
+        "RUN",
+        "SHELL",
+        "STOPSIGNAL",
+        "USER",
+        "VOLUME",
+        "WORKDIR",
+    ];
+
+    pub struct Lexer {
+        content: String,
+        buffer: String,
+        line: usize,
+        character: usize,
+        start: usize,
+        char_start: usize,
+    }
+
+    impl Lexer {
+        pub fn new(content: String) -> Self {
+            Self {
+                content,
+                buffer: String::new(),
+                line: 0,
+                character: 0,
+                start: 0,
+                char_start: 0,
+            }
+        }
+
+        pub fn tokenize(&mut self) -> Vec<TokenType> {
+            let mut tokens = vec![];
+            let content = self.content.clone();
+            let mut continued = false;
+            for text in content.split_inclusive('\n') {
+                let chars: Vec<char> = text.trim_end_matches('\n').chars().collect();
+                let indent = run_length(&chars, 0, |c| c == ' ' || c == '\t');
+                for index in 0..indent {
+                    self.push_range(&chars, index, index + 1, &mut tokens, |b| {
+                        lexer_whitespace!(b)
+                    });
+                }
+                if chars.get(indent) == Some(&'#') {
+                    self.push_range(&chars, indent, chars.len(), &mut tokens, |b| {
+                        lexer_comment!(b)
+                    });
+                } else if continued {
+                    self.tokenize_arguments(&chars, indent, &mut tokens);
+                } else {
+                    let len = run_length(&chars, indent, |c| !c.is_whitespace());
+                    let word: String = chars[indent..indent + len].iter().collect();
+                    let index = if INSTRUCTIONS.contains(&word.to_uppercase().as_str()) {
+                        self.push_range(&chars, indent, indent + len, &mut tokens, |b| {
+                            lexer_keyword!(b)
+                        })
+                    } else {
+                        indent
+                    };
+                    self.tokenize_arguments(&chars, index, &mut tokens);
+                }
+                if !chars.iter().all(|c| c.is_whitespace()) {
+                    continued = chars
+                        .iter()
+                        .rev()
+                        .find(|c| !c.is_whitespace())
+                        .map_or(false, |c| *c == '\\');
+                }
+                if text.ends_with('\n') {
+                    self.push_range(&['\n'], 0, 1, &mut tokens, |b| lexer_whitespace!(b));
+                }
+            }
+            tokens
+        }
+
+        fn tokenize_arguments(&mut self, chars: &[char], from: usize, tokens: &mut Vec<TokenType>) {
+            let mut index = from;
+            while index < chars.len() {
+                let c = chars[index];
+                index = match c {
+                    ' ' | '\t' | '\r' => {
+                        self.push_range(chars, index, index + 1, tokens, |b| lexer_whitespace!(b))
+                    }
+                    '\\' if chars[index + 1..].iter().all(|c| c.is_whitespace()) => {
+                        self.push_range(chars, index, index + 1, tokens, |b| lexer_operator!(b))
+                    }
+                    '"' | '\'' => self.tokenize_string(chars, index, tokens),
+                    '$' => self.tokenize_variable(chars, index, tokens),
+                    '[' | ']' | ',' => {
+                        self.push_range(chars, index, index + 1, tokens, |b| lexer_separator!(b))
+                    }
+                    '=' => self.push_range(chars, index, index + 1, tokens, |b| lexer_operator!(b)),
+                    '-' if chars.get(index + 1) == Some(&'-') => {
+                        let len = run_length(chars, index, |c| !c.is_whitespace() && c != '=');
+                        self.push_range(chars, index, index + len, tokens, |b| lexer_operator!(b))
+                    }
+                    _ => {
+                        let len = run_length(chars, index, |c| {
+                            !c.is_whitespace() && !"\"'$=,[]".contains(c)
+                        })
+                        .max(1);
+                        let word: String = chars[index..index + len].iter().collect();
+                        if word.eq_ignore_ascii_case("as") {
+                            self.push_range(chars, index, index + len, tokens, |b| {
+                                lexer_keyword!(b)
+                            })
+                        } else if word.starts_with(|c: char| c.is_ascii_digit())
+                            && word.chars().all(|c| c.is_ascii_alphanumeric() || c == '/')
+                        {
+                            self.push_range(chars, index, index + len, tokens, |b| {
+                                lexer_literal!(b)
+                            })
+                        } else {
+                            self.push_range(chars, index, index + len, tokens, |b| {
+                                lexer_identifier!(b)
+                            })
+                        }
+                    }
+                };
+            }
+        }
+
+        fn tokenize_string(
+            &mut self,
+            chars: &[char],
+            from: usize,
+            tokens: &mut Vec<TokenType>,
+        ) -> usize {
+            let quote = chars[from];
+            let mut index = from + 1;
+            while index < chars.len() {
+                match chars[index] {
+                    '\\' => index += 2,
+                    c if c == quote => {
+                        return self
+                            .push_range(chars, from, index + 1, tokens, |b| lexer_string!(b));
+                    }
+                    _ => index += 1,
+                }
+            }
+            self.push_range(chars, from, chars.len(), tokens, |b| lexer_error!(b))
+        }
+
+        fn tokenize_variable(
+            &mut self,
+            chars: &[char],
+            from: usize,
+            tokens: &mut Vec<TokenType>,
+        ) -> usize {
+            match chars.get(from + 1) {
+                Some('{') => match chars[from..].iter().position(|c| *c == '}') {
+                    Some(len) => {
+                        self.push_range(chars, from, from + len + 1, tokens, |b| lexer_literal!(b))
+                    }
+                    None => self.push_range(chars, from, chars.len(), tokens, |b| lexer_error!(b)),
+                },
+                Some(c) if c.is_alphabetic() || *c == '_' => {
+                    let len = run_length(chars, from + 1, |c| c.is_alphanumeric() || c == '_');
+                    self.push_range(chars, from, from + 1 + len, tokens, |b| lexer_literal!(b))
+                }
+                _ => self.push_range(chars, from, from + 1, tokens, |b| lexer_identifier!(b)),
+            }
+        }
+
+        fn push_range<F>(
+            &mut self,
+            chars: &[char],
+            from: usize,
+            to: usize,
+            tokens: &mut Vec<TokenType>,
+            builder: F,
+        ) -> usize
+        where
+            F: Fn(&Lexer) -> TokenType,
+        {
+            if from < to {
+                self.buffer = chars[from..to].iter().collect();
+                tokens.push(builder(&self));
+                self.clear();
+            }
+            to
+        }
+
+        fn clear(&mut self) {
+            for c in self.buffer.chars() {
+                if c == '\n' {
+                    self.line += 1;
+                    self.character = 0;
+                } else {
+                    self.character += 1;
+                }
+            }
+            self.start += self.buffer.len();
+            self.char_start += self.buffer.chars().count();
+            self.buffer.clear();
+        }
+    }
+
+    fn run_length<F>(chars: &[char], from: usize, f: F) -> usize
+    where
+        F: Fn(char) -> bool,
+    {
+        chars[from..].iter().take_while(|c| f(**c)).count()
+    }
+
+    impl TokenBuilder for Lexer {
+        fn text(&self) -> String {
+            self.buffer.to_string()
+        }
+
+        fn line(&self) -> usize {
+            self.line
+        }
+
+        fn character(&self) -> usize {
+            self.character
+        }
+
+        fn start(&self) -> usize {
+            self.start
+        }
+
+        fn end(&self, text: &String) -> usize {
+            self.start + text.len()
+        }
+
+        fn char_start(&self) -> usize {
+            self.char_start
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        struct BuilderMock {
+            line: usize,
+            character: usize,
+            start: usize,
+            char_start: usize,
+            text: String,
+        }
+
+        impl TokenBuilder for BuilderMock {
+            fn text(&self) -> String {
+                self.text.clone()
+            }
+
+            fn line(&self) -> usize {
+                self.line
+            }
+
+            fn character(&self) -> usize {
+                self.character
+            }
+
+            fn start(&self) -> usize {
+                self.start
+            }
+
+            fn end(&self, current_text: &String) -> usize {
+                self.start + current_text.len()
+            }
+
+            fn char_start(&self) -> usize {
+                self.char_start
+            }
+        }
+
+        macro_rules! builder {
+            ($text: expr, $line: expr, $character: expr, $start: expr) => {
+                BuilderMock {
+                    line: $line,
+                    character: $character,
+                    start: $start,
+                    char_start: $start,
+                    text: $text.to_owned(),
+                }
+            };
+        }
+
+        #[test]
+        fn parse_empty() {
+            let code = "".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_from() {
+            let code = "FROM rust AS build".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_keyword!(builder!("FROM", 0, 0, 0)),
+                lexer_whitespace!(builder!(" ", 0, 4, 4)),
+                lexer_identifier!(builder!("rust", 0, 5, 5)),
+                lexer_whitespace!(builder!(" ", 0, 9, 9)),
+                lexer_keyword!(builder!("AS", 0, 10, 10)),
+                lexer_whitespace!(builder!(" ", 0, 12, 12)),
+                lexer_identifier!(builder!("build", 0, 13, 13)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_arguments() {
+            let code = "COPY --from=a $B [\"c\"]".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_keyword!(builder!("COPY", 0, 0, 0)),
+                lexer_whitespace!(builder!(" ", 0, 4, 4)),
+                lexer_operator!(builder!("--from", 0, 5, 5)),
+                lexer_operator!(builder!("=", 0, 11, 11)),
+                lexer_identifier!(builder!("a", 0, 12, 12)),
+                lexer_whitespace!(builder!(" ", 0, 13, 13)),
+                lexer_literal!(builder!("$B", 0, 14, 14)),
+                lexer_whitespace!(builder!(" ", 0, 16, 16)),
+                lexer_separator!(builder!("[", 0, 17, 17)),
+                lexer_string!(builder!("\"c\"", 0, 18, 18)),
+                lexer_separator!(builder!("]", 0, 21, 21)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_continuation() {
+            let code = "RUN a \\\n  run".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_keyword!(builder!("RUN", 0, 0, 0)),
+                lexer_whitespace!(builder!(" ", 0, 3, 3)),
+                lexer_identifier!(builder!("a", 0, 4, 4)),
+                lexer_whitespace!(builder!(" ", 0, 5, 5)),
+                lexer_operator!(builder!("\\", 0, 6, 6)),
+                lexer_whitespace!(builder!("\n", 0, 7, 7)),
+                lexer_whitespace!(builder!(" ", 1, 0, 8)),
+                lexer_whitespace!(builder!(" ", 1, 1, 9)),
+                lexer_identifier!(builder!("run", 1, 2, 10)),
+            ];
+            assert_eq!(result, expected)
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use crate::brackets::{bracket_of, Brackets};
+use crate::*;
+use std::collections::HashMap;
+
+/// Lines which can be folded, first line stays visible and following lines up to `end_line` are hidden
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FoldRegion {
+    pub start_line: usize,
+    pub end_line: usize,
+    pub level: usize,
+}
+
+impl FoldRegion {
+    pub fn contains(&self, line: usize) -> bool {
+        self.start_line <= line && line <= self.end_line
+    }
+
+    pub fn hides(&self, line: usize) -> bool {
+        self.start_line < line && line <= self.end_line
+    }
+}
+
+/// Foldable regions built from multi-line bracket pairs and indentation blocks.
+///
+/// When both start at the same line the longer one wins. Level 1 means region is not nested.
+pub fn regions(tokens: &[TokenType]) -> Vec<FoldRegion> {
+    let mut ranges = bracket_ranges(tokens);
+    ranges.append(&mut indentation_ranges(tokens));
+    ranges.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+    ranges.dedup_by_key(|range| range.0);
+
+    let mut parents: Vec<usize> = vec![];
+    ranges
+        .into_iter()
+        .map(|(start_line, end_line)| {
+            while parents.last().map_or(false, |end| *end < start_line) {
+                parents.pop();
+            }
+            parents.push(end_line);
+            FoldRegion {
+                start_line,
+                end_line,
+                level: parents.len(),
+            }
+        })
+        .collect()
+}
+
+/// Opening bracket line up to line before closing bracket, so closing bracket stays visible
+fn bracket_ranges(tokens: &[TokenType]) -> Vec<(usize, usize)> {
+    let lines: HashMap<usize, usize> = tokens
+        .iter()
+        .filter_map(|token| bracket_of(token).map(|(_, position)| (position, token.line())))
+        .collect();
+    Brackets::new(tokens.iter())
+        .pairs()
+        .into_iter()
+        .filter_map(|(open, close)| {
+            let start = lines[&open];
+            let end = lines[&close].checked_sub(1)?;
+            if end > start {
+                Some((start, end))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Line followed by more indented lines, trailing blank lines are not part of region
+fn indentation_ranges(tokens: &[TokenType]) -> Vec<(usize, usize)> {
+    let first_line = match tokens.first() {
+        Some(token) => token.line(),
+        _ => return vec![],
+    };
+    let text: String = tokens.iter().map(|token| token.text().as_str()).collect();
+    let indents: Vec<Option<usize>> = text.split('\n').map(indentation).collect();
+    let mut ranges = vec![];
+    for (index, indent) in indents.iter().enumerate() {
+        let indent = match indent {
+            Some(indent) => indent,
+            _ => continue,
+        };
+        let mut end = None;
+        for (offset, other) in indents[index + 1..].iter().enumerate() {
+            match other {
+                Some(other) if other > indent => end = Some(index + 1 + offset),
+                Some(_) => break,
+                None => (),
+            }
+        }
+        if let Some(end) = end {
+            ranges.push((first_line + index, first_line + end));
+        }
+    }
+    ranges
+}
+
+/// Width of leading whitespace with tab counted as 4, `None` for blank line
+fn indentation(line: &str) -> Option<usize> {
+    let mut width = 0;
+    for c in line.chars() {
+        match c {
+            ' ' => width += 1,
+            '\t' => width += 4,
+            '\r' => (),
+            _ => return Some(width),
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn regions_of(text: &str, language: Language) -> Vec<(usize, usize, usize)> {
+        regions(&parse(text.to_owned(), language))
+            .into_iter()
+            .map(|r| (r.start_line, r.end_line, r.level))
+            .collect()
+    }
+
+    #[test]
+    fn must_return_nothing_for_empty_text() {
+        assert_eq!(regions_of("", Language::Rust), vec![]);
+    }
+
+    #[test]
+    fn must_fold_brackets_up_to_closing_line() {
+        let code = "fn a() {\n    if b {\n        c();\n    }\n}\n";
+        assert_eq!(regions_of(code, Language::Rust), vec![(0, 3, 1), (1, 2, 2)]);
+    }
+
+    #[test]
+    fn must_fold_unindented_brackets() {
+        let code = "[\n1,\n2\n]";
+        assert_eq!(regions_of(code, Language::Json), vec![(0, 2, 1)]);
+    }
+
+    #[test]
+    fn must_fold_by_indentation() {
+        let code = "def a():\n    b = 1\n\n    if b:\n        pass\n\nc = 2\n";
+        assert_eq!(
+            regions_of(code, Language::Python),
+            vec![(0, 4, 1), (3, 4, 2)]
+        );
+    }
+
+    #[test]
+    fn must_skip_brackets_closed_on_next_line() {
+        let code = "a(\n)";
+        assert_eq!(regions_of(code, Language::C), vec![]);
+    }
+
+    #[test]
+    fn must_check_hidden_lines() {
+        let region = FoldRegion {
+            start_line: 2,
+            end_line: 4,
+            level: 1,
+        };
+        assert_eq!(region.hides(2), false);
+        assert_eq!(region.hides(4), true);
+        assert_eq!(region.contains(2), true);
+        assert_eq!(region.contains(5), false);
+    }
+}
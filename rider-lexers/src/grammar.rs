@@ -0,0 +1,471 @@
+use crate::*;
+use regex::Regex;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+pub const MAIN_STATE: &str = "main";
+
+lazy_static! {
+    static ref GRAMMARS: RwLock<Vec<Arc<Grammar>>> = RwLock::new(vec![]);
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RuleDefinition {
+    #[serde(rename = "match")]
+    pub pattern: String,
+    pub token: TokenKind,
+    #[serde(default)]
+    pub push: Option<String>,
+    #[serde(default)]
+    pub pop: bool,
+}
+
+/// Grammar file content, for example:
+///
+/// ```json
+/// {
+///   "name": "ini",
+///   "extensions": ["ini"],
+///   "states": {
+///     "main": [
+///       { "match": ";.*", "token": "comment" },
+///       { "match": "\"", "token": "string", "push": "string" }
+///     ],
+///     "string": [
+///       { "match": "[^\"]+", "token": "string" },
+///       { "match": "\"", "token": "string", "pop": true }
+///     ]
+///   }
+/// }
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct GrammarDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    pub states: HashMap<String, Vec<RuleDefinition>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GrammarError {
+    InvalidFormat(String),
+    MissingMainState,
+    UnknownState {
+        state: String,
+        target: String,
+    },
+    InvalidRegex {
+        state: String,
+        pattern: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for GrammarError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GrammarError::InvalidFormat(message) => write!(f, "invalid grammar: {}", message),
+            GrammarError::MissingMainState => write!(f, "missing \"{}\" state", MAIN_STATE),
+            GrammarError::UnknownState { state, target } => {
+                write!(f, "state \"{}\" pushes unknown state \"{}\"", state, target)
+            }
+            GrammarError::InvalidRegex {
+                state,
+                pattern,
+                message,
+            } => write!(
+                f,
+                "invalid pattern {:?} in state \"{}\": {}",
+                pattern, state, message
+            ),
+        }
+    }
+}
+
+/// Rule with pattern anchored at current position, zero-length matches are ignored
+#[derive(Debug, Clone)]
+pub struct Rule {
+    regex: Regex,
+    kind: TokenKind,
+    push: Option<String>,
+    pop: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Grammar {
+    name: String,
+    extensions: Vec<String>,
+    states: HashMap<String, Vec<Rule>>,
+}
+
+impl Grammar {
+    pub fn from_json(content: &str) -> Result<Self, GrammarError> {
+        let definition: GrammarDefinition = serde_json::from_str(content)
+            .map_err(|e| GrammarError::InvalidFormat(e.to_string()))?;
+        Self::compile(definition)
+    }
+
+    pub fn compile(definition: GrammarDefinition) -> Result<Self, GrammarError> {
+        if !definition.states.contains_key(MAIN_STATE) {
+            return Err(GrammarError::MissingMainState);
+        }
+        let mut states = HashMap::new();
+        for (state, rules) in definition.states.iter() {
+            let mut compiled = vec![];
+            for rule in rules {
+                if let Some(target) = rule.push.as_ref() {
+                    if !definition.states.contains_key(target) {
+                        return Err(GrammarError::UnknownState {
+                            state: state.clone(),
+                            target: target.clone(),
+                        });
+                    }
+                }
+                let regex = Regex::new(format!("^(?:{})", rule.pattern).as_str()).map_err(|e| {
+                    GrammarError::InvalidRegex {
+                        state: state.clone(),
+                        pattern: rule.pattern.clone(),
+                        message: e.to_string(),
+                    }
+                })?;
+                compiled.push(Rule {
+                    regex,
+                    kind: rule.token,
+                    push: rule.push.clone(),
+                    pop: rule.pop,
+                });
+            }
+            states.insert(state.clone(), compiled);
+        }
+        Ok(Self {
+            name: definition.name,
+            extensions: definition.extensions,
+            states,
+        })
+    }
+
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn extensions(&self) -> &Vec<String> {
+        &self.extensions
+    }
+
+    pub fn tokenize(&self, text: String) -> Vec<TokenType> {
+        lexer::Lexer::new(text, self).tokenize()
+    }
+}
+
+/// Registers grammar as language, grammar with the same name is replaced
+pub fn register(grammar: Grammar) -> Language {
+    let mut grammars = GRAMMARS.write().unwrap();
+    let grammar = Arc::new(grammar);
+    match grammars.iter().position(|g| g.name() == grammar.name()) {
+        Some(index) => {
+            grammars[index] = grammar;
+            Language::Custom(index)
+        }
+        None => {
+            grammars.push(grammar);
+            Language::Custom(grammars.len() - 1)
+        }
+    }
+}
+
+pub fn find(name: &str) -> Option<Language> {
+    GRAMMARS
+        .read()
+        .unwrap()
+        .iter()
+        .position(|g| g.name().eq_ignore_ascii_case(name))
+        .map(Language::Custom)
+}
+
+pub fn get(id: usize) -> Option<Arc<Grammar>> {
+    GRAMMARS.read().unwrap().get(id).cloned()
+}
+
+pub mod lexer {
+    use super::*;
+
+    pub struct Lexer<'a> {
+        content: String,
+        grammar: &'a Grammar,
+        stack: Vec<&'a str>,
+        buffer: String,
+        line: usize,
+        character: usize,
+        start: usize,
+        char_start: usize,
+    }
+
+    impl<'a> Lexer<'a> {
+        pub fn new(content: String, grammar: &'a Grammar) -> Self {
+            Self {
+                content,
+                grammar,
+                stack: vec![MAIN_STATE],
+                buffer: String::new(),
+                line: 0,
+                character: 0,
+                start: 0,
+                char_start: 0,
+            }
+        }
+
+        pub fn tokenize(&mut self) -> Vec<TokenType> {
+            let mut tokens = vec![];
+            let content = self.content.clone();
+            let mut unmatched = String::new();
+            let mut index = 0;
+            while index < content.len() {
+                let rest = &content[index..];
+                match self.find_rule(rest) {
+                    Some((rule, len)) => {
+                        self.push_unmatched(&mut unmatched, &mut tokens);
+                        self.push(&rest[..len], rule.kind, &mut tokens);
+                        if rule.pop && self.stack.len() > 1 {
+                            self.stack.pop();
+                        }
+                        if let Some(target) = rule.push.as_ref() {
+                            self.stack.push(target.as_str());
+                        }
+                        index += len;
+                    }
+                    None => {
+                        let c = rest.chars().next().unwrap();
+                        if c.is_whitespace() {
+                            self.push_unmatched(&mut unmatched, &mut tokens);
+                            self.push(&rest[..c.len_utf8()], TokenKind::Whitespace, &mut tokens);
+                        } else {
+                            unmatched.push(c);
+                        }
+                        index += c.len_utf8();
+                    }
+                }
+            }
+            self.push_unmatched(&mut unmatched, &mut tokens);
+            tokens
+        }
+
+        fn find_rule(&self, rest: &str) -> Option<(&'a Rule, usize)> {
+            let state = self.stack.last().cloned().unwrap_or(MAIN_STATE);
+            self.grammar
+                .states
+                .get(state)?
+                .iter()
+                .filter_map(|rule| rule.regex.find(rest).map(|m| (rule, m.end())))
+                .find(|(_, len)| *len > 0)
+        }
+
+        fn push_unmatched(&mut self, unmatched: &mut String, tokens: &mut Vec<TokenType>) {
+            if !unmatched.is_empty() {
+                self.push(unmatched.as_str(), TokenKind::Identifier, tokens);
+                unmatched.clear();
+            }
+        }
+
+        /// New lines are always separate whitespace tokens
+        fn push(&mut self, text: &str, kind: TokenKind, tokens: &mut Vec<TokenType>) {
+            for part in text.split_inclusive('\n') {
+                let body = part.trim_end_matches('\n');
+                if !body.is_empty() {
+                    self.buffer.push_str(body);
+                    tokens.push(kind.build(self));
+                    self.clear();
+                }
+                if part.ends_with('\n') {
+                    self.buffer.push('\n');
+                    tokens.push(lexer_whitespace!(self));
+                    self.clear();
+                }
+            }
+        }
+
+        fn clear(&mut self) {
+            for c in self.buffer.chars() {
+                if c == '\n' {
+                    self.line += 1;
+                    self.character = 0;
+                } else {
+                    self.character += 1;
+                }
+            }
+            self.start += self.buffer.len();
+            self.char_start += self.buffer.chars().count();
+            self.buffer.clear();
+        }
+    }
+
+    impl<'a> TokenBuilder for Lexer<'a> {
+        fn text(&self) -> String {
+            self.buffer.to_string()
+        }
+
+        fn line(&self) -> usize {
+            self.line
+        }
+
+        fn character(&self) -> usize {
+            self.character
+        }
+
+        fn start(&self) -> usize {
+            self.start
+        }
+
+        fn end(&self, text: &String) -> usize {
+            self.start + text.len()
+        }
+
+        fn char_start(&self) -> usize {
+            self.char_start
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct BuilderMock {
+        line: usize,
+        character: usize,
+        start: usize,
+        char_start: usize,
+        text: String,
+    }
+
+    impl TokenBuilder for BuilderMock {
+        fn text(&self) -> String {
+            self.text.clone()
+        }
+
+        fn line(&self) -> usize {
+            self.line
+        }
+
+        fn character(&self) -> usize {
+            self.character
+        }
+
+        fn start(&self) -> usize {
+            self.start
+        }
+
+        fn end(&self, current_text: &String) -> usize {
+            self.start + current_text.len()
+        }
+
+        fn char_start(&self) -> usize {
+            self.char_start
+        }
+    }
+
+    macro_rules! builder {
+        ($text: expr, $line: expr, $character: expr, $start: expr) => {
+            BuilderMock {
+                line: $line,
+                character: $character,
+                start: $start,
+                char_start: $start,
+                text: $text.to_owned(),
+            }
+        };
+    }
+
+    const INI: &str = r#"{
+        "name": "ini-test",
+        "extensions": ["ini"],
+        "states": {
+            "main": [
+                { "match": ";.*", "token": "comment" },
+                { "match": "\\[[^\\]]*\\]", "token": "keyword" },
+                { "match": "=", "token": "operator" },
+                { "match": "\"", "token": "string", "push": "string" }
+            ],
+            "string": [
+                { "match": "[^\"]+", "token": "string" },
+                { "match": "\"", "token": "string", "pop": true }
+            ]
+        }
+    }"#;
+
+    #[test]
+    fn must_parse_with_states() {
+        let grammar = Grammar::from_json(INI).unwrap();
+        let result = grammar.tokenize("[a]\nk=\"v\" ;c".to_owned());
+        let expected = vec![
+            lexer_keyword!(builder!("[a]", 0, 0, 0)),
+            lexer_whitespace!(builder!("\n", 0, 3, 3)),
+            lexer_identifier!(builder!("k", 1, 0, 4)),
+            lexer_operator!(builder!("=", 1, 1, 5)),
+            lexer_string!(builder!("\"", 1, 2, 6)),
+            lexer_string!(builder!("v", 1, 3, 7)),
+            lexer_string!(builder!("\"", 1, 4, 8)),
+            lexer_whitespace!(builder!(" ", 1, 5, 9)),
+            lexer_comment!(builder!(";c", 1, 6, 10)),
+        ];
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn must_split_multiline_match() {
+        let grammar = Grammar::from_json(INI).unwrap();
+        let result = grammar.tokenize("\"a\nb\"".to_owned());
+        let expected = vec![
+            lexer_string!(builder!("\"", 0, 0, 0)),
+            lexer_string!(builder!("a", 0, 1, 1)),
+            lexer_whitespace!(builder!("\n", 0, 2, 2)),
+            lexer_string!(builder!("b", 1, 0, 3)),
+            lexer_string!(builder!("\"", 1, 1, 4)),
+        ];
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn must_report_missing_main_state() {
+        let result = Grammar::from_json(r#"{ "name": "x", "states": {} }"#);
+        assert_eq!(result.err(), Some(GrammarError::MissingMainState));
+    }
+
+    #[test]
+    fn must_report_unknown_state() {
+        let content = r#"{ "name": "x", "states": {
+            "main": [{ "match": "a", "token": "keyword", "push": "b" }]
+        } }"#;
+        let result = Grammar::from_json(content);
+        assert_eq!(
+            result.err(),
+            Some(GrammarError::UnknownState {
+                state: "main".to_owned(),
+                target: "b".to_owned(),
+            })
+        );
+    }
+
+    #[test]
+    fn must_report_invalid_regex() {
+        let content = r#"{ "name": "x", "states": {
+            "main": [{ "match": "(", "token": "keyword" }]
+        } }"#;
+        match Grammar::from_json(content) {
+            Err(GrammarError::InvalidRegex { state, pattern, .. }) => {
+                assert_eq!(state, "main".to_owned());
+                assert_eq!(pattern, "(".to_owned());
+            }
+            other => panic!("unexpected result {:?}", other.map(|g| g.name().clone())),
+        }
+    }
+
+    #[test]
+    fn must_register_grammar_as_language() {
+        let grammar = Grammar::from_json(INI).unwrap();
+        let language = register(grammar);
+        assert_eq!(find("INI-TEST"), Some(language));
+        assert_eq!(Language::from_name("ini-test"), Some(language));
+        assert_eq!(parse("[a]".to_owned(), language).len(), 1);
+    }
+}
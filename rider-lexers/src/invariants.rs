@@ -0,0 +1,182 @@
+use crate::*;
+
+/// Built-in languages which can be tokenized without registering anything
+pub const LANGUAGES: &[Language] = &[
+    Language::PlainText,
+    Language::Rust,
+    Language::Toml,
+    Language::Markdown,
+    Language::Json,
+    Language::Yaml,
+    Language::Python,
+    Language::JavaScript,
+    Language::TypeScript,
+    Language::C,
+    Language::Cpp,
+    Language::Shell,
+    Language::Makefile,
+    Language::Dockerfile,
+    Language::Diff,
+];
+
+/// Checks that tokens are non-empty, contiguous and concatenate back to given text
+pub fn check_coverage(text: &str, tokens: &[TokenType]) -> Result<(), String> {
+    let mut offset = 0;
+    for (index, token) in tokens.iter().enumerate() {
+        if token.text().is_empty() {
+            return Err(format!("token {} is empty: {:?}", index, token));
+        }
+        if token.start() != offset {
+            return Err(format!(
+                "token {} starts at {} but previous ended at {}: {:?}",
+                index,
+                token.start(),
+                offset,
+                token
+            ));
+        }
+        if token.end() != token.start() + token.text().len() {
+            return Err(format!(
+                "token {} end {} does not match its text length: {:?}",
+                index,
+                token.end(),
+                token
+            ));
+        }
+        if text.get(token.start()..token.end()) != Some(token.text().as_str()) {
+            return Err(format!(
+                "token {} text differs from input at {}..{}: {:?}",
+                index,
+                token.start(),
+                token.end(),
+                token
+            ));
+        }
+        offset = token.end();
+    }
+    if offset != text.len() {
+        return Err(format!("tokens cover {} of {} bytes", offset, text.len()));
+    }
+    Ok(())
+}
+
+/// Checks that char span, line and character (counted in chars) of every token match its start offset
+pub fn check_positions(text: &str, tokens: &[TokenType]) -> Result<(), String> {
+    for (index, token) in tokens.iter().enumerate() {
+        let before = match text.get(..token.start()) {
+            Some(before) => before,
+            None => return Err(format!("token {} starts inside char: {:?}", index, token)),
+        };
+        let line = before.matches('\n').count();
+        let character = match before.rfind('\n') {
+            Some(pos) => before[pos + 1..].chars().count(),
+            None => before.chars().count(),
+        };
+        let char_start = before.chars().count();
+        let char_span = Span::new(char_start, char_start + token.text().chars().count());
+        if token.char_span() != char_span {
+            return Err(format!(
+                "token {} expected at chars {}..{}: {:?}",
+                index, char_span.lo, char_span.hi, token
+            ));
+        }
+        if token.line() != line || token.character() != character {
+            return Err(format!(
+                "token {} expected at {}:{} but found at {}:{}: {:?}",
+                index,
+                line,
+                character,
+                token.line(),
+                token.character(),
+                token
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Tokenizes text and checks all invariants
+pub fn check(text: &str, language: Language) -> Result<(), String> {
+    let tokens = parse(text.to_string(), language);
+    check_coverage(text, &tokens)?;
+    check_positions(text, &tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn token(
+        text: &str,
+        line: usize,
+        character: usize,
+        start: usize,
+        char_start: usize,
+    ) -> TokenType {
+        TokenType::Identifier {
+            token: Token::new(
+                text.to_owned(),
+                line,
+                character,
+                Span::new(start, start + text.len()),
+                Span::new(char_start, char_start + text.chars().count()),
+            ),
+        }
+    }
+
+    #[test]
+    fn must_accept_contiguous_tokens() {
+        let tokens = vec![
+            token("ą", 0, 0, 0, 0),
+            token("\n", 0, 1, 2, 1),
+            token("b", 1, 0, 3, 2),
+        ];
+        assert_eq!(check_coverage("ą\nb", &tokens), Ok(()));
+        assert_eq!(check_positions("ą\nb", &tokens), Ok(()));
+    }
+
+    #[test]
+    fn must_reject_gap() {
+        let tokens = vec![token("a", 0, 0, 0, 0), token("c", 0, 2, 2, 2)];
+        assert_eq!(check_coverage("abc", &tokens).is_err(), true);
+    }
+
+    #[test]
+    fn must_reject_missing_tail() {
+        let tokens = vec![token("a", 0, 0, 0, 0)];
+        assert_eq!(check_coverage("ab", &tokens).is_err(), true);
+    }
+
+    #[test]
+    fn must_reject_wrong_character() {
+        let tokens = vec![token("\n", 0, 0, 0, 0), token("b", 1, 1, 1, 1)];
+        assert_eq!(check_positions("\nb", &tokens).is_err(), true);
+    }
+
+    #[test]
+    fn must_reject_byte_offset_as_char_offset() {
+        let tokens = vec![token("ą", 0, 0, 0, 0), token("b", 0, 1, 2, 2)];
+        assert_eq!(check_positions("ąb", &tokens).is_err(), true);
+    }
+
+    fn check_all(text: &str) -> Result<(), TestCaseError> {
+        for language in LANGUAGES {
+            check(text, *language)
+                .map_err(|e| TestCaseError::fail(format!("{:?}: {}", language, e)))?;
+        }
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn must_cover_source_like_input(text in "[a-z0-9 \t\n\"'`#$@/*\\\\{}()<>:;,.=+-]{0,64}") {
+            check_all(text.as_str())?;
+        }
+
+        #[test]
+        fn must_cover_arbitrary_input(text in "\\PC{0,32}") {
+            check_all(text.as_str())?;
+        }
+    }
+}
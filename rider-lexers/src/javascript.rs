@@ -0,0 +1,573 @@
+pub mod lexer {
+    use crate::*;
+
+    pub const KEYWORDS: &[&str] = &[
+        "async",
+        "await",
+        "break",
+        "case",
+        "catch",
+        "class",
+        "const",
+        "continue",
+        "debugger",
+        "default",
+        "delete",
+        "do",
+        "else",
+        "export",
+        "extends",
+        "false",
+        "finally",
+        "for",
+        "function",
+        "get",
+        "if",
+        "import",
+        "in",
+        "instanceof",
+        "let",
+        "new",
+        "null",
+        "of",
+        "return",
+        "set",
+        "static",
+        "super",
+        "switch",
+        "this",
+        "throw",
+        "true",
+        "try",
+        "typeof",
+        "undefined",
+        "var",
+        "void",
+        "while",
+        "with",
+        "yield",
+    ];
+
+    pub const TYPESCRIPT_KEYWORDS: &[&str] = &[
+        "abstract",
+        "any",
+        "as",
+        "asserts",
+        "bigint",
+        "boolean",
+        "declare",
+        "enum",
+        "implements",
+        "infer",
+        "interface",
+        "is",
+        "keyof",
+        "module",
+        "namespace",
+        "never",
+        "number",
+        "object",
+        "private",
+        "protected",
+        "public",
+        "readonly",
+        "string",
+        "symbol",
+        "type",
+        "unique",
+        "unknown",
+    ];
+
+    pub struct Lexer {
+        content: String,
+        buffer: String,
+        line: usize,
+        character: usize,
+        start: usize,
+        char_start: usize,
+        typescript: bool,
+    }
+
+    impl Lexer {
+        pub fn new(content: String) -> Self {
+            Self {
+                content,
+                buffer: String::new(),
+                line: 0,
+                character: 0,
+                start: 0,
+                char_start: 0,
+                typescript: false,
+            }
+        }
+
+        pub fn typescript(content: String) -> Self {
+            Self {
+                typescript: true,
+                ..Self::new(content)
+            }
+        }
+
+        pub fn tokenize(&mut self) -> Vec<TokenType> {
+            let mut tokens = vec![];
+            let chars: Vec<char> = self.content.chars().collect();
+            self.tokenize_range(&chars, 0, chars.len(), &mut tokens);
+            tokens
+        }
+
+        fn tokenize_range(
+            &mut self,
+            chars: &[char],
+            from: usize,
+            to: usize,
+            tokens: &mut Vec<TokenType>,
+        ) {
+            let chars = &chars[..to];
+            let mut index = from;
+            while index < chars.len() {
+                let c = chars[index];
+                let next = chars.get(index + 1).cloned();
+                index = match c {
+                    ' ' | '\t' | '\r' | '\n' => {
+                        self.push_range(chars, index, index + 1, tokens, |b| lexer_whitespace!(b))
+                    }
+                    '/' if next == Some('/') => {
+                        let len = run_length(chars, index, |c| c != '\n');
+                        self.push_range(chars, index, index + len, tokens, |b| lexer_comment!(b))
+                    }
+                    '/' if next == Some('*') => {
+                        match find(chars, index + 2, &['*', '/']) {
+                            Some(end) => self
+                                .push_range(chars, index, end + 2, tokens, |b| lexer_comment!(b)),
+                            None => self
+                                .push_range(chars, index, chars.len(), tokens, |b| lexer_error!(b)),
+                        }
+                    }
+                    '/' if regex_allowed(tokens) => self.tokenize_regex(chars, index, tokens),
+                    '"' | '\'' => self.tokenize_string(chars, index, tokens),
+                    '`' => self.tokenize_template(chars, index, tokens),
+                    '0'..='9' => {
+                        let len = number_length(chars, index);
+                        self.push_range(chars, index, index + len, tokens, |b| lexer_literal!(b))
+                    }
+                    '.' if next.map_or(false, |c| c.is_ascii_digit()) => {
+                        let len = number_length(chars, index);
+                        self.push_range(chars, index, index + len, tokens, |b| lexer_literal!(b))
+                    }
+                    '(' | ')' | '[' | ']' | '{' | '}' | ',' | ':' | ';' | '.' => {
+                        self.push_range(chars, index, index + 1, tokens, |b| lexer_separator!(b))
+                    }
+                    _ if is_operator(c) => {
+                        let len = run_length(chars, index, is_operator);
+                        self.push_range(chars, index, index + len, tokens, |b| lexer_operator!(b))
+                    }
+                    _ if is_identifier_start(c) => {
+                        let len = run_length(chars, index, is_identifier_part);
+                        let word: String = chars[index..index + len].iter().collect();
+                        if self.is_keyword(&word) {
+                            self.push_range(chars, index, index + len, tokens, |b| {
+                                lexer_keyword!(b)
+                            })
+                        } else {
+                            self.push_range(chars, index, index + len, tokens, |b| {
+                                lexer_identifier!(b)
+                            })
+                        }
+                    }
+                    _ => self.push_range(chars, index, index + 1, tokens, |b| lexer_identifier!(b)),
+                };
+            }
+        }
+
+        fn is_keyword(&self, word: &str) -> bool {
+            KEYWORDS.contains(&word) || (self.typescript && TYPESCRIPT_KEYWORDS.contains(&word))
+        }
+
+        fn tokenize_string(
+            &mut self,
+            chars: &[char],
+            from: usize,
+            tokens: &mut Vec<TokenType>,
+        ) -> usize {
+            let quote = chars[from];
+            let mut index = from + 1;
+            while index < chars.len() {
+                match chars[index] {
+                    '\\' => index += 2,
+                    '\n' => break,
+                    c if c == quote => {
+                        return self
+                            .push_range(chars, from, index + 1, tokens, |b| lexer_string!(b));
+                    }
+                    _ => index += 1,
+                }
+            }
+            let end = index.min(chars.len());
+            self.push_range(chars, from, end, tokens, |b| lexer_error!(b))
+        }
+
+        fn tokenize_template(
+            &mut self,
+            chars: &[char],
+            from: usize,
+            tokens: &mut Vec<TokenType>,
+        ) -> usize {
+            let mut segment = from;
+            let mut index = from + 1;
+            while index < chars.len() {
+                match chars[index] {
+                    '\\' => index += 2,
+                    '$' if chars.get(index + 1) == Some(&'{') => {
+                        let end = match matching_brace(chars, index + 1) {
+                            Some(end) => end,
+                            None => break,
+                        };
+                        self.push_range(chars, segment, index, tokens, |b| lexer_string!(b));
+                        self.push_range(chars, index, index + 2, tokens, |b| lexer_separator!(b));
+                        self.tokenize_range(chars, index + 2, end, tokens);
+                        self.push_range(chars, end, end + 1, tokens, |b| lexer_separator!(b));
+                        segment = end + 1;
+                        index = end + 1;
+                    }
+                    '`' => {
+                        return self
+                            .push_range(chars, segment, index + 1, tokens, |b| lexer_string!(b));
+                    }
+                    _ => index += 1,
+                }
+            }
+            self.push_range(chars, segment, chars.len(), tokens, |b| lexer_error!(b))
+        }
+
+        fn tokenize_regex(
+            &mut self,
+            chars: &[char],
+            from: usize,
+            tokens: &mut Vec<TokenType>,
+        ) -> usize {
+            let mut index = from + 1;
+            let mut class = false;
+            while index < chars.len() {
+                match chars[index] {
+                    '\\' => index += 2,
+                    '\n' => break,
+                    '[' => {
+                        class = true;
+                        index += 1;
+                    }
+                    ']' => {
+                        class = false;
+                        index += 1;
+                    }
+                    '/' if !class => {
+                        let flags = run_length(chars, index + 1, |c| c.is_ascii_alphabetic());
+                        return self.push_range(chars, from, index + 1 + flags, tokens, |b| {
+                            lexer_literal!(b)
+                        });
+                    }
+                    _ => index += 1,
+                }
+            }
+            self.push_range(chars, from, from + 1, tokens, |b| lexer_operator!(b))
+        }
+
+        fn push_range<F>(
+            &mut self,
+            chars: &[char],
+            from: usize,
+            to: usize,
+            tokens: &mut Vec<TokenType>,
+            builder: F,
+        ) -> usize
+        where
+            F: Fn(&Lexer) -> TokenType,
+        {
+            if from < to {
+                self.buffer = chars[from..to].iter().collect();
+                tokens.push(builder(&self));
+                self.clear();
+            }
+            to
+        }
+
+        fn clear(&mut self) {
+            for c in self.buffer.chars() {
+                if c == '\n' {
+                    self.line += 1;
+                    self.character = 0;
+                } else {
+                    self.character += 1;
+                }
+            }
+            self.start += self.buffer.len();
+            self.char_start += self.buffer.chars().count();
+            self.buffer.clear();
+        }
+    }
+
+    fn run_length<F>(chars: &[char], from: usize, f: F) -> usize
+    where
+        F: Fn(char) -> bool,
+    {
+        chars[from..].iter().take_while(|c| f(**c)).count()
+    }
+
+    fn find(chars: &[char], from: usize, needle: &[char]) -> Option<usize> {
+        (from..chars.len()).find(|index| chars[*index..].starts_with(needle))
+    }
+
+    fn matching_brace(chars: &[char], from: usize) -> Option<usize> {
+        let mut depth = 0;
+        for (index, c) in chars.iter().enumerate().skip(from) {
+            match c {
+                '{' => depth += 1,
+                '}' if depth == 1 => return Some(index),
+                '}' => depth -= 1,
+                _ => (),
+            }
+        }
+        None
+    }
+
+    /// Slash starts regular expression only where value is expected
+    fn regex_allowed(tokens: &[TokenType]) -> bool {
+        let last = tokens.iter().rev().find(|t| match t {
+            TokenType::Whitespace { .. } | TokenType::Comment { .. } => false,
+            _ => true,
+        });
+        match last {
+            None => true,
+            Some(TokenType::Operator { .. }) => true,
+            Some(TokenType::Keyword { token }) => match token.text().as_str() {
+                "this" | "super" | "true" | "false" | "null" | "undefined" => false,
+                _ => true,
+            },
+            Some(TokenType::Separator { token }) => match token.text().as_str() {
+                ")" | "]" | "}" => false,
+                _ => true,
+            },
+            _ => false,
+        }
+    }
+
+    fn is_identifier_start(c: char) -> bool {
+        c.is_alphabetic() || c == '_' || c == '$'
+    }
+
+    fn is_identifier_part(c: char) -> bool {
+        c.is_alphanumeric() || c == '_' || c == '$'
+    }
+
+    fn is_operator(c: char) -> bool {
+        "+-*/%=<>!&|^~?@#".contains(c)
+    }
+
+    fn number_length(chars: &[char], from: usize) -> usize {
+        let radix = match (chars.get(from), chars.get(from + 1)) {
+            (Some('0'), Some('x')) | (Some('0'), Some('X')) => 16,
+            (Some('0'), Some('o')) | (Some('0'), Some('O')) => 8,
+            (Some('0'), Some('b')) | (Some('0'), Some('B')) => 2,
+            _ => 10,
+        };
+        let mut index = if radix != 10 {
+            from + 2 + run_length(chars, from + 2, |c| c.is_digit(radix) || c == '_')
+        } else {
+            let mut index = from + run_length(chars, from, |c| c.is_ascii_digit() || c == '_');
+            if chars.get(index) == Some(&'.') {
+                index += 1 + run_length(chars, index + 1, |c| c.is_ascii_digit() || c == '_');
+            }
+            if let Some('e') | Some('E') = chars.get(index) {
+                let mut exponent = index + 1;
+                if let Some('+') | Some('-') = chars.get(exponent) {
+                    exponent += 1;
+                }
+                let digits = run_length(chars, exponent, |c| c.is_ascii_digit());
+                if digits > 0 {
+                    index = exponent + digits;
+                }
+            }
+            index
+        };
+        if chars.get(index) == Some(&'n') {
+            index += 1;
+        }
+        index - from
+    }
+
+    impl TokenBuilder for Lexer {
+        fn text(&self) -> String {
+            self.buffer.to_string()
+        }
+
+        fn line(&self) -> usize {
+            self.line
+        }
+
+        fn character(&self) -> usize {
+            self.character
+        }
+
+        fn start(&self) -> usize {
+            self.start
+        }
+
+        fn end(&self, text: &String) -> usize {
+            self.start + text.len()
+        }
+
+        fn char_start(&self) -> usize {
+            self.char_start
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        struct BuilderMock {
+            line: usize,
+            character: usize,
+            start: usize,
+            char_start: usize,
+            text: String,
+        }
+
+        impl TokenBuilder for BuilderMock {
+            fn text(&self) -> String {
+                self.text.clone()
+            }
+
+            fn line(&self) -> usize {
+                self.line
+            }
+
+            fn character(&self) -> usize {
+                self.character
+            }
+
+            fn start(&self) -> usize {
+                self.start
+            }
+
+            fn end(&self, current_text: &String) -> usize {
+                self.start + current_text.len()
+            }
+
+            fn char_start(&self) -> usize {
+                self.char_start
+            }
+        }
+
+        macro_rules! builder {
+            ($text: expr, $line: expr, $character: expr, $start: expr) => {
+                BuilderMock {
+                    line: $line,
+                    character: $character,
+                    start: $start,
+                    char_start: $start,
+                    text: $text.to_owned(),
+                }
+            };
+        }
+
+        #[test]
+        fn parse_empty() {
+            let code = "".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_declaration() {
+            let code = "const a = 'b'; // c".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_keyword!(builder!("const", 0, 0, 0)),
+                lexer_whitespace!(builder!(" ", 0, 5, 5)),
+                lexer_identifier!(builder!("a", 0, 6, 6)),
+                lexer_whitespace!(builder!(" ", 0, 7, 7)),
+                lexer_operator!(builder!("=", 0, 8, 8)),
+                lexer_whitespace!(builder!(" ", 0, 9, 9)),
+                lexer_string!(builder!("'b'", 0, 10, 10)),
+                lexer_separator!(builder!(";", 0, 13, 13)),
+                lexer_whitespace!(builder!(" ", 0, 14, 14)),
+                lexer_comment!(builder!("// c", 0, 15, 15)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_template_literal() {
+            let code = "`a${b}\nc`".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_string!(builder!("`a", 0, 0, 0)),
+                lexer_separator!(builder!("${", 0, 2, 2)),
+                lexer_identifier!(builder!("b", 0, 4, 4)),
+                lexer_separator!(builder!("}", 0, 5, 5)),
+                lexer_string!(builder!("\nc`", 0, 6, 6)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_block_comment() {
+            let code = "/* a\nb */x".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_comment!(builder!("/* a\nb */", 0, 0, 0)),
+                lexer_identifier!(builder!("x", 1, 4, 9)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_regex_and_division() {
+            let code = "x = a / 2 + /[/]b/g".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_identifier!(builder!("x", 0, 0, 0)),
+                lexer_whitespace!(builder!(" ", 0, 1, 1)),
+                lexer_operator!(builder!("=", 0, 2, 2)),
+                lexer_whitespace!(builder!(" ", 0, 3, 3)),
+                lexer_identifier!(builder!("a", 0, 4, 4)),
+                lexer_whitespace!(builder!(" ", 0, 5, 5)),
+                lexer_operator!(builder!("/", 0, 6, 6)),
+                lexer_whitespace!(builder!(" ", 0, 7, 7)),
+                lexer_literal!(builder!("2", 0, 8, 8)),
+                lexer_whitespace!(builder!(" ", 0, 9, 9)),
+                lexer_operator!(builder!("+", 0, 10, 10)),
+                lexer_whitespace!(builder!(" ", 0, 11, 11)),
+                lexer_literal!(builder!("/[/]b/g", 0, 12, 12)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_typescript_keywords() {
+            let code = "interface A".to_owned();
+            let result = Lexer::typescript(code.clone()).tokenize();
+            assert_eq!(result[0], lexer_keyword!(builder!("interface", 0, 0, 0)));
+            let result = Lexer::new(code).tokenize();
+            assert_eq!(result[0], lexer_identifier!(builder!("interface", 0, 0, 0)));
+        }
+
+        #[test]
+        fn parse_unterminated_string() {
+            let code = "\"a\n".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_error!(builder!("\"a", 0, 0, 0)),
+                lexer_whitespace!(builder!("\n", 0, 2, 2)),
+            ];
+            assert_eq!(result, expected)
+        }
+    }
+}
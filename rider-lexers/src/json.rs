@@ -0,0 +1,351 @@
+pub mod lexer {
+    use crate::*;
+
+    pub struct Lexer {
+        content: String,
+        buffer: String,
+        line: usize,
+        character: usize,
+        start: usize,
+        char_start: usize,
+    }
+
+    impl Lexer {
+        pub fn new(content: String) -> Self {
+            Self {
+                content,
+                buffer: String::new(),
+                line: 0,
+                character: 0,
+                start: 0,
+                char_start: 0,
+            }
+        }
+
+        pub fn tokenize(&mut self) -> Vec<TokenType> {
+            let mut tokens = vec![];
+            let chars: Vec<char> = self.content.chars().collect();
+            let mut index = 0;
+            while index < chars.len() {
+                let c = chars[index];
+                index = match c {
+                    ' ' | '\t' | '\r' | '\n' => {
+                        self.push_range(&chars, index, index + 1, &mut tokens, |b| {
+                            lexer_whitespace!(b)
+                        })
+                    }
+                    ',' if is_trailing_comma(&chars, index) => {
+                        self.push_range(&chars, index, index + 1, &mut tokens, |b| lexer_error!(b))
+                    }
+                    '{' | '}' | '[' | ']' | ',' | ':' => {
+                        self.push_range(&chars, index, index + 1, &mut tokens, |b| {
+                            lexer_separator!(b)
+                        })
+                    }
+                    '"' => self.tokenize_string(&chars, index, &mut tokens),
+                    '-' | '0'..='9' => {
+                        let len = number_length(&chars, index);
+                        if len == 0 {
+                            self.push_range(&chars, index, index + 1, &mut tokens, |b| {
+                                lexer_error!(b)
+                            })
+                        } else {
+                            self.push_range(&chars, index, index + len, &mut tokens, |b| {
+                                lexer_literal!(b)
+                            })
+                        }
+                    }
+                    _ if c.is_alphanumeric() || c == '_' => {
+                        let len = run_length(&chars, index, |c| c.is_alphanumeric() || c == '_');
+                        let word: String = chars[index..index + len].iter().collect();
+                        match word.as_str() {
+                            "true" | "false" | "null" => {
+                                self.push_range(&chars, index, index + len, &mut tokens, |b| {
+                                    lexer_keyword!(b)
+                                })
+                            }
+                            _ => self.push_range(&chars, index, index + len, &mut tokens, |b| {
+                                lexer_error!(b)
+                            }),
+                        }
+                    }
+                    _ => {
+                        self.push_range(&chars, index, index + 1, &mut tokens, |b| lexer_error!(b))
+                    }
+                };
+            }
+            tokens
+        }
+
+        fn tokenize_string(
+            &mut self,
+            chars: &[char],
+            index: usize,
+            tokens: &mut Vec<TokenType>,
+        ) -> usize {
+            let mut end = index + 1;
+            let mut escaped = false;
+            while end < chars.len() {
+                match chars[end] {
+                    '\n' => break,
+                    '"' if !escaped => {
+                        let next = skip_whitespace(chars, end + 1);
+                        return if chars.get(next) == Some(&':') {
+                            self.push_range(chars, index, end + 1, tokens, |b| lexer_identifier!(b))
+                        } else {
+                            self.push_range(chars, index, end + 1, tokens, |b| lexer_string!(b))
+                        };
+                    }
+                    '\\' => escaped = !escaped,
+                    _ => escaped = false,
+                }
+                end += 1;
+            }
+            self.push_range(chars, index, end, tokens, |b| lexer_error!(b))
+        }
+
+        fn push_range<F>(
+            &mut self,
+            chars: &[char],
+            from: usize,
+            to: usize,
+            tokens: &mut Vec<TokenType>,
+            builder: F,
+        ) -> usize
+        where
+            F: Fn(&Lexer) -> TokenType,
+        {
+            self.buffer = chars[from..to].iter().collect();
+            tokens.push(builder(&self));
+            self.clear();
+            to
+        }
+
+        fn clear(&mut self) {
+            for c in self.buffer.chars() {
+                if c == '\n' {
+                    self.line += 1;
+                    self.character = 0;
+                } else {
+                    self.character += 1;
+                }
+            }
+            self.start += self.buffer.len();
+            self.char_start += self.buffer.chars().count();
+            self.buffer.clear();
+        }
+    }
+
+    fn run_length<F>(chars: &[char], from: usize, f: F) -> usize
+    where
+        F: Fn(char) -> bool,
+    {
+        chars[from..].iter().take_while(|c| f(**c)).count()
+    }
+
+    fn skip_whitespace(chars: &[char], from: usize) -> usize {
+        from + run_length(chars, from, |c| c.is_whitespace())
+    }
+
+    fn is_trailing_comma(chars: &[char], index: usize) -> bool {
+        match chars.get(skip_whitespace(chars, index + 1)) {
+            Some('}') | Some(']') => true,
+            _ => false,
+        }
+    }
+
+    fn number_length(chars: &[char], from: usize) -> usize {
+        let mut index = from;
+        if chars.get(index) == Some(&'-') {
+            index += 1;
+        }
+        let digits = run_length(chars, index, |c| c.is_ascii_digit());
+        if digits == 0 {
+            return 0;
+        }
+        index += digits;
+        if chars.get(index) == Some(&'.') {
+            let fraction = run_length(chars, index + 1, |c| c.is_ascii_digit());
+            if fraction > 0 {
+                index += fraction + 1;
+            }
+        }
+        if let Some('e') | Some('E') = chars.get(index) {
+            let mut exponent = index + 1;
+            if let Some('+') | Some('-') = chars.get(exponent) {
+                exponent += 1;
+            }
+            let digits = run_length(chars, exponent, |c| c.is_ascii_digit());
+            if digits > 0 {
+                index = exponent + digits;
+            }
+        }
+        index - from
+    }
+
+    impl TokenBuilder for Lexer {
+        fn text(&self) -> String {
+            self.buffer.to_string()
+        }
+
+        fn line(&self) -> usize {
+            self.line
+        }
+
+        fn character(&self) -> usize {
+            self.character
+        }
+
+        fn start(&self) -> usize {
+            self.start
+        }
+
+        fn end(&self, text: &String) -> usize {
+            self.start + text.len()
+        }
+
+        fn char_start(&self) -> usize {
+            self.char_start
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        struct BuilderMock {
+            line: usize,
+            character: usize,
+            start: usize,
+            char_start: usize,
+            text: String,
+        }
+
+        impl TokenBuilder for BuilderMock {
+            fn text(&self) -> String {
+                self.text.clone()
+            }
+
+            fn line(&self) -> usize {
+                self.line
+            }
+
+            fn character(&self) -> usize {
+                self.character
+            }
+
+            fn start(&self) -> usize {
+                self.start
+            }
+
+            fn end(&self, current_text: &String) -> usize {
+                self.start + current_text.len()
+            }
+
+            fn char_start(&self) -> usize {
+                self.char_start
+            }
+        }
+
+        macro_rules! builder {
+            ($text: expr, $line: expr, $character: expr, $start: expr) => {
+                BuilderMock {
+                    line: $line,
+                    character: $character,
+                    start: $start,
+                    char_start: $start,
+                    text: $text.to_owned(),
+                }
+            };
+        }
+
+        #[test]
+        fn parse_empty() {
+            let code = "".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_key_and_value() {
+            let code = "{\"a\": \"b\"}".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_separator!(builder!("{", 0, 0, 0)),
+                lexer_identifier!(builder!("\"a\"", 0, 1, 1)),
+                lexer_separator!(builder!(":", 0, 4, 4)),
+                lexer_whitespace!(builder!(" ", 0, 5, 5)),
+                lexer_string!(builder!("\"b\"", 0, 6, 6)),
+                lexer_separator!(builder!("}", 0, 9, 9)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_literals() {
+            let code = "[-1.5e3,true,null]".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_separator!(builder!("[", 0, 0, 0)),
+                lexer_literal!(builder!("-1.5e3", 0, 1, 1)),
+                lexer_separator!(builder!(",", 0, 7, 7)),
+                lexer_keyword!(builder!("true", 0, 8, 8)),
+                lexer_separator!(builder!(",", 0, 12, 12)),
+                lexer_keyword!(builder!("null", 0, 13, 13)),
+                lexer_separator!(builder!("]", 0, 17, 17)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_escaped_quote() {
+            let code = "\"a\\\"b\"".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![lexer_string!(builder!("\"a\\\"b\"", 0, 0, 0))];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_unterminated_string() {
+            let code = "[\"a\n]".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_separator!(builder!("[", 0, 0, 0)),
+                lexer_error!(builder!("\"a", 0, 1, 1)),
+                lexer_whitespace!(builder!("\n", 0, 3, 3)),
+                lexer_separator!(builder!("]", 1, 0, 4)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_trailing_comma() {
+            let code = "[1,\n]".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_separator!(builder!("[", 0, 0, 0)),
+                lexer_literal!(builder!("1", 0, 1, 1)),
+                lexer_error!(builder!(",", 0, 2, 2)),
+                lexer_whitespace!(builder!("\n", 0, 3, 3)),
+                lexer_separator!(builder!("]", 1, 0, 4)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_invalid_word() {
+            let code = "foo".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![lexer_error!(builder!("foo", 0, 0, 0))];
+            assert_eq!(result, expected)
+        }
+    }
+}
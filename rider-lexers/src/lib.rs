@@ -1,13 +1,35 @@
+#[macro_use]
+extern crate lazy_static;
 extern crate log;
 //#[macro_use]
 extern crate plex;
+extern crate regex;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
 extern crate simplelog;
 
 use std::ops::Deref;
 
+pub mod brackets;
+pub mod c_lang;
+pub mod diff;
+pub mod dockerfile;
+pub mod folding;
+pub mod grammar;
+pub mod invariants;
+pub mod javascript;
+pub mod json;
+pub mod makefile;
+pub mod markdown;
+pub mod outline;
 pub mod plain;
+pub mod python;
 pub mod rust_lang;
+pub mod shell;
 pub mod toml;
+pub mod yaml;
 
 #[macro_export]
 macro_rules! lexer_whitespace {
@@ -17,9 +39,17 @@ macro_rules! lexer_whitespace {
         let character = $provider.character();
         let start = $provider.start();
         let end = $provider.end(&text);
+        let char_start = $provider.char_start();
+        let char_end = char_start + text.chars().count();
 
         TokenType::Whitespace {
-            token: Token::new(text, line, character, start, end),
+            token: Token::new(
+                text,
+                line,
+                character,
+                Span::new(start, end),
+                Span::new(char_start, char_end),
+            ),
         }
     }};
 }
@@ -31,9 +61,17 @@ macro_rules! lexer_keyword {
         let character = $provider.character();
         let start = $provider.start();
         let end = $provider.end(&text);
+        let char_start = $provider.char_start();
+        let char_end = char_start + text.chars().count();
 
         TokenType::Keyword {
-            token: Token::new(text, line, character, start, end),
+            token: Token::new(
+                text,
+                line,
+                character,
+                Span::new(start, end),
+                Span::new(char_start, char_end),
+            ),
         }
     }};
 }
@@ -45,9 +83,17 @@ macro_rules! lexer_string {
         let character = $provider.character();
         let start = $provider.start();
         let end = $provider.end(&text);
+        let char_start = $provider.char_start();
+        let char_end = char_start + text.chars().count();
 
         TokenType::String {
-            token: Token::new(text, line, character, start, end),
+            token: Token::new(
+                text,
+                line,
+                character,
+                Span::new(start, end),
+                Span::new(char_start, char_end),
+            ),
         }
     }};
 }
@@ -59,9 +105,17 @@ macro_rules! lexer_identifier {
         let character = $provider.character();
         let start = $provider.start();
         let end = $provider.end(&text);
+        let char_start = $provider.char_start();
+        let char_end = char_start + text.chars().count();
 
         TokenType::Identifier {
-            token: Token::new(text, line, character, start, end),
+            token: Token::new(
+                text,
+                line,
+                character,
+                Span::new(start, end),
+                Span::new(char_start, char_end),
+            ),
         }
     }};
 }
@@ -73,9 +127,17 @@ macro_rules! lexer_literal {
         let character = $provider.character();
         let start = $provider.start();
         let end = $provider.end(&text);
+        let char_start = $provider.char_start();
+        let char_end = char_start + text.chars().count();
 
         TokenType::Literal {
-            token: Token::new(text, line, character, start, end),
+            token: Token::new(
+                text,
+                line,
+                character,
+                Span::new(start, end),
+                Span::new(char_start, char_end),
+            ),
         }
     }};
 }
@@ -87,9 +149,17 @@ macro_rules! lexer_comment {
         let character = $provider.character();
         let start = $provider.start();
         let end = $provider.end(&text);
+        let char_start = $provider.char_start();
+        let char_end = char_start + text.chars().count();
 
         TokenType::Comment {
-            token: Token::new(text, line, character, start, end),
+            token: Token::new(
+                text,
+                line,
+                character,
+                Span::new(start, end),
+                Span::new(char_start, char_end),
+            ),
         }
     }};
 }
@@ -101,9 +171,17 @@ macro_rules! lexer_operator {
         let character = $provider.character();
         let start = $provider.start();
         let end = $provider.end(&text);
+        let char_start = $provider.char_start();
+        let char_end = char_start + text.chars().count();
 
         TokenType::Operator {
-            token: Token::new(text, line, character, start, end),
+            token: Token::new(
+                text,
+                line,
+                character,
+                Span::new(start, end),
+                Span::new(char_start, char_end),
+            ),
         }
     }};
 }
@@ -115,9 +193,108 @@ macro_rules! lexer_separator {
         let character = $provider.character();
         let start = $provider.start();
         let end = $provider.end(&text);
+        let char_start = $provider.char_start();
+        let char_end = char_start + text.chars().count();
 
         TokenType::Separator {
-            token: Token::new(text, line, character, start, end),
+            token: Token::new(
+                text,
+                line,
+                character,
+                Span::new(start, end),
+                Span::new(char_start, char_end),
+            ),
+        }
+    }};
+}
+#[macro_export]
+macro_rules! lexer_error {
+    ($provider: expr) => {{
+        let text = $provider.text();
+        let line = $provider.line();
+        let character = $provider.character();
+        let start = $provider.start();
+        let end = $provider.end(&text);
+        let char_start = $provider.char_start();
+        let char_end = char_start + text.chars().count();
+
+        TokenType::Error {
+            token: Token::new(
+                text,
+                line,
+                character,
+                Span::new(start, end),
+                Span::new(char_start, char_end),
+            ),
+        }
+    }};
+}
+
+#[macro_export]
+macro_rules! lexer_diff_add {
+    ($provider: expr) => {{
+        let text = $provider.text();
+        let line = $provider.line();
+        let character = $provider.character();
+        let start = $provider.start();
+        let end = $provider.end(&text);
+        let char_start = $provider.char_start();
+        let char_end = char_start + text.chars().count();
+
+        TokenType::DiffAdd {
+            token: Token::new(
+                text,
+                line,
+                character,
+                Span::new(start, end),
+                Span::new(char_start, char_end),
+            ),
+        }
+    }};
+}
+
+#[macro_export]
+macro_rules! lexer_diff_delete {
+    ($provider: expr) => {{
+        let text = $provider.text();
+        let line = $provider.line();
+        let character = $provider.character();
+        let start = $provider.start();
+        let end = $provider.end(&text);
+        let char_start = $provider.char_start();
+        let char_end = char_start + text.chars().count();
+
+        TokenType::DiffDelete {
+            token: Token::new(
+                text,
+                line,
+                character,
+                Span::new(start, end),
+                Span::new(char_start, char_end),
+            ),
+        }
+    }};
+}
+
+#[macro_export]
+macro_rules! lexer_diff_change {
+    ($provider: expr) => {{
+        let text = $provider.text();
+        let line = $provider.line();
+        let character = $provider.character();
+        let start = $provider.start();
+        let end = $provider.end(&text);
+        let char_start = $provider.char_start();
+        let char_end = char_start + text.chars().count();
+
+        TokenType::DiffChange {
+            token: Token::new(
+                text,
+                line,
+                character,
+                Span::new(start, end),
+                Span::new(char_start, char_end),
+            ),
         }
     }};
 }
@@ -128,6 +305,7 @@ pub trait TokenBuilder {
     fn character(&self) -> usize;
     fn start(&self) -> usize;
     fn end(&self, current_text: &String) -> usize;
+    fn char_start(&self) -> usize;
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Ord, Eq)]
@@ -135,6 +313,72 @@ pub enum Language {
     PlainText,
     Rust,
     Toml,
+    Markdown,
+    Json,
+    Yaml,
+    Python,
+    JavaScript,
+    TypeScript,
+    C,
+    Cpp,
+    Shell,
+    Makefile,
+    Dockerfile,
+    Diff,
+    Custom(usize),
+}
+
+impl Language {
+    pub fn from_name(name: &str) -> Option<Language> {
+        match name.to_lowercase().as_str() {
+            "text" | "txt" | "plain" => Some(Language::PlainText),
+            "rust" | "rs" => Some(Language::Rust),
+            "toml" => Some(Language::Toml),
+            "markdown" | "md" => Some(Language::Markdown),
+            "json" => Some(Language::Json),
+            "yaml" | "yml" => Some(Language::Yaml),
+            "python" | "py" => Some(Language::Python),
+            "javascript" | "js" | "jsx" => Some(Language::JavaScript),
+            "typescript" | "ts" | "tsx" => Some(Language::TypeScript),
+            "c" | "h" => Some(Language::C),
+            "cpp" | "c++" | "cxx" | "hpp" => Some(Language::Cpp),
+            "shell" | "sh" | "bash" | "zsh" => Some(Language::Shell),
+            "makefile" | "make" | "mk" => Some(Language::Makefile),
+            "dockerfile" | "docker" => Some(Language::Dockerfile),
+            "diff" | "patch" => Some(Language::Diff),
+            _ => grammar::find(name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenKind {
+    Whitespace,
+    Keyword,
+    String,
+    Identifier,
+    Literal,
+    Comment,
+    Operator,
+    Separator,
+    Error,
+}
+
+impl TokenKind {
+    pub fn build<B: TokenBuilder>(self, builder: &B) -> TokenType {
+        match self {
+            TokenKind::Whitespace => lexer_whitespace!(builder),
+            TokenKind::Keyword => lexer_keyword!(builder),
+            TokenKind::String => lexer_string!(builder),
+            TokenKind::Identifier => lexer_identifier!(builder),
+            TokenKind::Literal => lexer_literal!(builder),
+            TokenKind::Comment => lexer_comment!(builder),
+            TokenKind::Operator => lexer_operator!(builder),
+            TokenKind::Separator => lexer_separator!(builder),
+            TokenKind::Error => lexer_error!(builder),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -147,34 +391,50 @@ pub enum TokenType {
     Comment { token: Token },
     Operator { token: Token },
     Separator { token: Token },
+    Error { token: Token },
+    DiffAdd { token: Token },
+    DiffDelete { token: Token },
+    DiffChange { token: Token },
 }
 
 impl TokenType {
-    pub fn move_to(&self, line: usize, character: usize, start: usize, end: usize) -> Self {
+    pub fn move_to(&self, line: usize, character: usize, span: Span, char_span: Span) -> Self {
         match self {
             TokenType::Whitespace { token } => TokenType::Whitespace {
-                token: token.move_to(line, character, start, end),
+                token: token.move_to(line, character, span, char_span),
             },
             TokenType::Keyword { token } => TokenType::Keyword {
-                token: token.move_to(line, character, start, end),
+                token: token.move_to(line, character, span, char_span),
             },
             TokenType::String { token } => TokenType::String {
-                token: token.move_to(line, character, start, end),
+                token: token.move_to(line, character, span, char_span),
             },
             TokenType::Identifier { token } => TokenType::Identifier {
-                token: token.move_to(line, character, start, end),
+                token: token.move_to(line, character, span, char_span),
             },
             TokenType::Literal { token } => TokenType::Literal {
-                token: token.move_to(line, character, start, end),
+                token: token.move_to(line, character, span, char_span),
             },
             TokenType::Comment { token } => TokenType::Comment {
-                token: token.move_to(line, character, start, end),
+                token: token.move_to(line, character, span, char_span),
             },
             TokenType::Operator { token } => TokenType::Operator {
-                token: token.move_to(line, character, start, end),
+                token: token.move_to(line, character, span, char_span),
             },
             TokenType::Separator { token } => TokenType::Separator {
-                token: token.move_to(line, character, start, end),
+                token: token.move_to(line, character, span, char_span),
+            },
+            TokenType::Error { token } => TokenType::Error {
+                token: token.move_to(line, character, span, char_span),
+            },
+            TokenType::DiffAdd { token } => TokenType::DiffAdd {
+                token: token.move_to(line, character, span, char_span),
+            },
+            TokenType::DiffDelete { token } => TokenType::DiffDelete {
+                token: token.move_to(line, character, span, char_span),
+            },
+            TokenType::DiffChange { token } => TokenType::DiffChange {
+                token: token.move_to(line, character, span, char_span),
             },
         }
     }
@@ -207,22 +467,50 @@ impl Deref for TokenType {
             TokenType::Comment { token } => token,
             TokenType::Operator { token } => token,
             TokenType::Separator { token } => token,
+            TokenType::Error { token } => token,
+            TokenType::DiffAdd { token } => token,
+            TokenType::DiffDelete { token } => token,
+            TokenType::DiffChange { token } => token,
         }
     }
 }
 
+/// Half-open range `lo..hi`
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Span {
     pub lo: usize,
     pub hi: usize,
 }
 
+impl Span {
+    pub fn new(lo: usize, hi: usize) -> Self {
+        Self { lo, hi }
+    }
+
+    pub fn len(&self) -> usize {
+        self.hi - self.lo
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lo == self.hi
+    }
+
+    pub fn shifted(&self, offset: usize) -> Self {
+        Self {
+            lo: self.lo + offset,
+            hi: self.hi + offset,
+        }
+    }
+}
+
+/// Token with position in source text, `span` is in bytes and `char_span` in chars.
+/// `line` and `character` are zero based and `character` is counted in chars.
 #[derive(Clone, PartialEq)]
 pub struct Token {
     line: usize,
     character: usize,
-    start: usize,
-    end: usize,
+    span: Span,
+    char_span: Span,
     text: String,
 }
 
@@ -235,21 +523,25 @@ impl std::fmt::Debug for Token {
         f.write_str(" character ")?;
         self.character.fmt(f)?;
         f.write_str(" start ")?;
-        self.start.fmt(f)?;
+        self.span.lo.fmt(f)?;
         f.write_str(" end ")?;
-        self.end.fmt(f)?;
+        self.span.hi.fmt(f)?;
+        f.write_str(" chars ")?;
+        self.char_span.lo.fmt(f)?;
+        f.write_str("..")?;
+        self.char_span.hi.fmt(f)?;
         f.write_str(" }")
     }
 }
 
 impl Token {
-    pub fn new(text: String, line: usize, character: usize, start: usize, end: usize) -> Self {
+    pub fn new(text: String, line: usize, character: usize, span: Span, char_span: Span) -> Self {
         Self {
             text,
             line,
             character,
-            start,
-            end,
+            span,
+            char_span,
         }
     }
 
@@ -265,21 +557,29 @@ impl Token {
         self.character
     }
 
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    pub fn char_span(&self) -> Span {
+        self.char_span
+    }
+
     pub fn start(&self) -> usize {
-        self.start
+        self.span.lo
     }
 
     pub fn end(&self) -> usize {
-        self.end
+        self.span.hi
     }
 
-    pub fn move_to(&self, line: usize, character: usize, start: usize, end: usize) -> Self {
+    pub fn move_to(&self, line: usize, character: usize, span: Span, char_span: Span) -> Self {
         Self {
             text: self.text.clone(),
             line,
             character,
-            start,
-            end,
+            span,
+            char_span,
         }
     }
 }
@@ -295,6 +595,44 @@ pub fn parse(text: String, language: Language) -> Vec<TokenType> {
             .map(|t| t.0)
             .collect(),
         Language::Toml => toml::lexer::Lexer::new(text).tokenize(),
+        Language::Markdown => markdown::lexer::Lexer::new(text).tokenize(),
+        Language::Json => json::lexer::Lexer::new(text).tokenize(),
+        Language::Yaml => yaml::lexer::Lexer::new(text).tokenize(),
+        Language::Python => python::lexer::Lexer::new(text).tokenize(),
+        Language::JavaScript => javascript::lexer::Lexer::new(text).tokenize(),
+        Language::TypeScript => javascript::lexer::Lexer::typescript(text).tokenize(),
+        Language::C => c_lang::lexer::Lexer::new(text).tokenize(),
+        Language::Cpp => c_lang::lexer::Lexer::cpp(text).tokenize(),
+        Language::Shell => shell::lexer::Lexer::new(text).tokenize(),
+        Language::Makefile => makefile::lexer::Lexer::new(text).tokenize(),
+        Language::Dockerfile => dockerfile::lexer::Lexer::new(text).tokenize(),
+        Language::Diff => diff::lexer::Lexer::new(text).tokenize(),
+        Language::Custom(id) => match grammar::get(id) {
+            Some(grammar) => grammar.tokenize(text),
+            None => parse(text, Language::PlainText),
+        },
+    }
+}
+
+/// Keywords of language offered by completion, custom grammars have none
+pub fn keywords(language: Language) -> Vec<&'static str> {
+    match language {
+        Language::Rust => rust_lang::lexer::KEYWORDS.to_vec(),
+        Language::Python => python::lexer::KEYWORDS.to_vec(),
+        Language::JavaScript => javascript::lexer::KEYWORDS.to_vec(),
+        Language::TypeScript => [
+            javascript::lexer::KEYWORDS,
+            javascript::lexer::TYPESCRIPT_KEYWORDS,
+        ]
+        .concat(),
+        Language::C => c_lang::lexer::KEYWORDS.to_vec(),
+        Language::Cpp => [c_lang::lexer::KEYWORDS, c_lang::lexer::CPP_KEYWORDS].concat(),
+        Language::Shell => shell::lexer::KEYWORDS.to_vec(),
+        Language::Makefile => makefile::lexer::DIRECTIVES.to_vec(),
+        Language::Dockerfile => dockerfile::lexer::INSTRUCTIONS.to_vec(),
+        Language::Json | Language::Yaml => vec!["true", "false", "null"],
+        Language::Toml => vec!["true", "false"],
+        _ => vec![],
     }
 }
 
@@ -319,9 +657,123 @@ mod tests {
         assert_eq!(result.len(), 3);
     }
 
+    #[test]
+    fn must_list_keywords() {
+        assert!(keywords(Language::Rust).contains(&"match"));
+        assert!(keywords(Language::Cpp).contains(&"while"));
+        assert!(keywords(Language::Cpp).contains(&"class"));
+        assert!(!keywords(Language::C).contains(&"class"));
+        assert!(keywords(Language::TypeScript).contains(&"interface"));
+        assert_eq!(keywords(Language::PlainText), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn must_parse_markdown() {
+        let buffer = "# foo\nbar";
+        let language = Language::Markdown;
+        let result = parse(buffer.to_string(), language);
+        assert_eq!(result.len(), 3);
+    }
+
+    #[test]
+    fn must_parse_json() {
+        let buffer = "{\"a\": 1}";
+        let language = Language::Json;
+        let result = parse(buffer.to_string(), language);
+        assert_eq!(result.len(), 6);
+    }
+
+    #[test]
+    fn must_parse_yaml() {
+        let buffer = "a: 1";
+        let language = Language::Yaml;
+        let result = parse(buffer.to_string(), language);
+        assert_eq!(result.len(), 4);
+    }
+
+    #[test]
+    fn must_parse_python() {
+        let buffer = "foo bar";
+        let language = Language::Python;
+        let result = parse(buffer.to_string(), language);
+        assert_eq!(result.len(), 3);
+    }
+
+    #[test]
+    fn must_parse_javascript() {
+        let buffer = "foo bar";
+        let language = Language::JavaScript;
+        let result = parse(buffer.to_string(), language);
+        assert_eq!(result.len(), 3);
+    }
+
+    #[test]
+    fn must_parse_typescript() {
+        let buffer = "foo bar";
+        let language = Language::TypeScript;
+        let result = parse(buffer.to_string(), language);
+        assert_eq!(result.len(), 3);
+    }
+
+    #[test]
+    fn must_parse_c() {
+        let buffer = "foo bar";
+        let language = Language::C;
+        let result = parse(buffer.to_string(), language);
+        assert_eq!(result.len(), 3);
+    }
+
+    #[test]
+    fn must_parse_cpp() {
+        let buffer = "foo bar";
+        let language = Language::Cpp;
+        let result = parse(buffer.to_string(), language);
+        assert_eq!(result.len(), 3);
+    }
+
+    #[test]
+    fn must_parse_shell() {
+        let buffer = "foo bar";
+        let language = Language::Shell;
+        let result = parse(buffer.to_string(), language);
+        assert_eq!(result.len(), 3);
+    }
+
+    #[test]
+    fn must_parse_makefile() {
+        let buffer = "foo bar";
+        let language = Language::Makefile;
+        let result = parse(buffer.to_string(), language);
+        assert_eq!(result.len(), 3);
+    }
+
+    #[test]
+    fn must_parse_dockerfile() {
+        let buffer = "foo bar";
+        let language = Language::Dockerfile;
+        let result = parse(buffer.to_string(), language);
+        assert_eq!(result.len(), 3);
+    }
+
+    #[test]
+    fn must_parse_diff() {
+        let buffer = "foo bar";
+        let language = Language::Diff;
+        let result = parse(buffer.to_string(), language);
+        assert_eq!(result.len(), 1);
+    }
+
+    #[test]
+    fn must_find_language_by_name() {
+        assert_eq!(Language::from_name("Rust"), Some(Language::Rust));
+        assert_eq!(Language::from_name("md"), Some(Language::Markdown));
+        assert_eq!(Language::from_name("patch"), Some(Language::Diff));
+        assert_eq!(Language::from_name("cobol"), None);
+    }
+
     #[test]
     fn must_return_valid_value_for_text() {
-        let token = Token::new("a".to_string(), 1, 2, 3, 4);
+        let token = Token::new("a".to_string(), 1, 2, Span::new(3, 4), Span::new(3, 4));
         let result = token.text();
         let text: String = "a".to_string();
         let expected = &text;
@@ -330,7 +782,7 @@ mod tests {
 
     #[test]
     fn must_return_valid_value_for_line() {
-        let token = Token::new("a".to_string(), 1, 2, 3, 4);
+        let token = Token::new("a".to_string(), 1, 2, Span::new(3, 4), Span::new(3, 4));
         let result = token.line();
         let expected = 1;
         assert_eq!(result, expected);
@@ -338,7 +790,7 @@ mod tests {
 
     #[test]
     fn must_return_valid_value_for_character() {
-        let token = Token::new("a".to_string(), 1, 2, 3, 4);
+        let token = Token::new("a".to_string(), 1, 2, Span::new(3, 4), Span::new(3, 4));
         let result = token.character();
         let expected = 2;
         assert_eq!(result, expected);
@@ -346,7 +798,7 @@ mod tests {
 
     #[test]
     fn must_return_valid_value_for_start() {
-        let token = Token::new("a".to_string(), 1, 2, 3, 4);
+        let token = Token::new("a".to_string(), 1, 2, Span::new(3, 4), Span::new(3, 4));
         let result = token.start();
         let expected = 3;
         assert_eq!(result, expected);
@@ -354,7 +806,7 @@ mod tests {
 
     #[test]
     fn must_return_valid_value_for_end() {
-        let token = Token::new("a".to_string(), 1, 2, 3, 4);
+        let token = Token::new("a".to_string(), 1, 2, Span::new(3, 4), Span::new(3, 4));
         let result = token.end();
         let expected = 4;
         assert_eq!(result, expected);
@@ -362,9 +814,9 @@ mod tests {
 
     #[test]
     fn must_return_valid_value_for_move_to() {
-        let token = Token::new("a".to_string(), 1, 2, 3, 4);
-        let result = token.move_to(5, 6, 7, 8);
-        let expected = Token::new("a".to_string(), 5, 6, 7, 8);
+        let token = Token::new("a".to_string(), 1, 2, Span::new(3, 4), Span::new(3, 4));
+        let result = token.move_to(5, 6, Span::new(7, 8), Span::new(6, 7));
+        let expected = Token::new("a".to_string(), 5, 6, Span::new(7, 8), Span::new(6, 7));
         assert_eq!(result, expected);
     }
 }
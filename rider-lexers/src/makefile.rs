@@ -0,0 +1,458 @@
+pub mod lexer {
+    use crate::*;
+
+    pub const DIRECTIVES: &[&str] = &[
+        "define", "else", "endef", "endif", "export", "ifdef", "ifeq", "ifndef", "ifneq",
+        "include", "-include", "sinclude", "override", "private", "undefine", "unexport", "vpath",
+    ];
+
+    pub struct Lexer {
+        content: String,
+        buffer: String,
+        line: usize,
+        character: usize,
+        start: usize,
+        char_start: usize,
+    }
+
+    impl Lexer {
+        pub fn new(content: String) -> Self {
+            Self {
+                content,
+                buffer: String::new(),
+                line: 0,
+                character: 0,
+                start: 0,
+                char_start: 0,
+            }
+        }
+
+        pub fn tokenize(&mut self) -> Vec<TokenType> {
+            let mut tokens = vec![];
+            let content = self.content.clone();
+            let mut recipe = false;
+            let mut define = false;
+            let mut continued_rule = false;
+            let mut continued_recipe = false;
+            for text in content.split_inclusive('\n') {
+                let chars: Vec<char> = text.trim_end_matches('\n').chars().collect();
+                let first_word = first_word(&chars);
+                let mut is_recipe = false;
+                if define && first_word != "endef" {
+                    self.push_range(&chars, 0, chars.len(), &mut tokens, |b| lexer_string!(b));
+                } else if recipe && (chars.first() == Some(&'\t') || continued_recipe) {
+                    self.tokenize_recipe(&chars, &mut tokens);
+                    is_recipe = true;
+                } else {
+                    let rule = self.tokenize_rule_line(&chars, &mut tokens, continued_rule);
+                    if !continued_rule && !first_word.is_empty() && !first_word.starts_with('#') {
+                        recipe = rule;
+                    }
+                    define = first_word == "define";
+                }
+                let continued = chars.last() == Some(&'\\');
+                continued_rule = continued && !is_recipe;
+                continued_recipe = continued && is_recipe;
+                if text.ends_with('\n') {
+                    self.push_range(&['\n'], 0, 1, &mut tokens, |b| lexer_whitespace!(b));
+                }
+            }
+            tokens
+        }
+
+        /// Returns true when line starts rule so following tab indented lines are recipes
+        fn tokenize_rule_line(
+            &mut self,
+            chars: &[char],
+            tokens: &mut Vec<TokenType>,
+            continued: bool,
+        ) -> bool {
+            let mut index = 0;
+            let mut rule = false;
+            let mut assignment = false;
+            let target_end = if continued {
+                None
+            } else {
+                find_rule_colon(chars)
+            };
+            while index < chars.len() {
+                let c = chars[index];
+                index = match c {
+                    ' ' | '\t' | '\r' => {
+                        self.push_range(chars, index, index + 1, tokens, |b| lexer_whitespace!(b))
+                    }
+                    '#' => {
+                        self.push_range(chars, index, chars.len(), tokens, |b| lexer_comment!(b))
+                    }
+                    '$' => self.tokenize_variable(chars, index, tokens),
+                    '\\' if index + 1 == chars.len() => {
+                        self.push_range(chars, index, index + 1, tokens, |b| lexer_operator!(b))
+                    }
+                    ':' | '+' | '?' | '!' if !assignment && is_assignment(chars, index) => {
+                        assignment = true;
+                        let len = run_length(chars, index, |c| c != '=') + 1;
+                        self.push_range(chars, index, index + len, tokens, |b| lexer_operator!(b))
+                    }
+                    '=' if !assignment => {
+                        assignment = true;
+                        self.push_range(chars, index, index + 1, tokens, |b| lexer_operator!(b))
+                    }
+                    ':' if !assignment && target_end == Some(index) => {
+                        rule = true;
+                        let len = run_length(chars, index, |c| c == ':');
+                        self.push_range(chars, index, index + len, tokens, |b| lexer_separator!(b))
+                    }
+                    ';' if rule => {
+                        let index = self
+                            .push_range(chars, index, index + 1, tokens, |b| lexer_separator!(b));
+                        self.tokenize_recipe_rest(chars, index, tokens);
+                        return true;
+                    }
+                    _ => {
+                        let mut len = run_length(chars, index, |c| !is_word_end(c)).max(1);
+                        if len > 1 && is_assignment(chars, index + len - 1) {
+                            len -= 1;
+                        }
+                        let word: String = chars[index..index + len].iter().collect();
+                        let before_target = target_end.map_or(false, |end| index < end);
+                        if index == first_word_index(chars) && DIRECTIVES.contains(&word.as_str()) {
+                            self.push_range(chars, index, index + len, tokens, |b| {
+                                lexer_keyword!(b)
+                            })
+                        } else if before_target && !assignment {
+                            self.push_range(chars, index, index + len, tokens, |b| {
+                                lexer_keyword!(b)
+                            })
+                        } else if assignment {
+                            self.push_range(chars, index, index + len, tokens, |b| lexer_string!(b))
+                        } else {
+                            self.push_range(chars, index, index + len, tokens, |b| {
+                                lexer_identifier!(b)
+                            })
+                        }
+                    }
+                };
+            }
+            rule
+        }
+
+        fn tokenize_recipe(&mut self, chars: &[char], tokens: &mut Vec<TokenType>) {
+            let indent = run_length(chars, 0, |c| c == '\t' || c == ' ');
+            for index in 0..indent {
+                self.push_range(chars, index, index + 1, tokens, |b| lexer_whitespace!(b));
+            }
+            let prefix = run_length(chars, indent, |c| c == '@' || c == '-' || c == '+');
+            let index = self.push_range(chars, indent, indent + prefix, tokens, |b| {
+                lexer_operator!(b)
+            });
+            self.tokenize_recipe_rest(chars, index, tokens);
+        }
+
+        fn tokenize_recipe_rest(
+            &mut self,
+            chars: &[char],
+            from: usize,
+            tokens: &mut Vec<TokenType>,
+        ) {
+            let mut index = from;
+            while index < chars.len() {
+                let c = chars[index];
+                index = match c {
+                    ' ' | '\t' | '\r' => {
+                        self.push_range(chars, index, index + 1, tokens, |b| lexer_whitespace!(b))
+                    }
+                    '#' if index == 0 || chars[index - 1].is_whitespace() => {
+                        self.push_range(chars, index, chars.len(), tokens, |b| lexer_comment!(b))
+                    }
+                    '$' => self.tokenize_variable(chars, index, tokens),
+                    '\'' | '"' => match chars[index + 1..].iter().position(|x| *x == c) {
+                        Some(len) => self.push_range(chars, index, index + len + 2, tokens, |b| {
+                            lexer_string!(b)
+                        }),
+                        None => {
+                            self.push_range(chars, index, chars.len(), tokens, |b| lexer_string!(b))
+                        }
+                    },
+                    '|' | '&' | '<' | '>' | ';' => {
+                        let len = run_length(chars, index, |c| "|&<>;".contains(c));
+                        self.push_range(chars, index, index + len, tokens, |b| lexer_operator!(b))
+                    }
+                    _ => {
+                        let len = run_length(chars, index, |c| {
+                            !c.is_whitespace() && !"$'\"|&<>;".contains(c)
+                        })
+                        .max(1);
+                        self.push_range(chars, index, index + len, tokens, |b| lexer_identifier!(b))
+                    }
+                };
+            }
+        }
+
+        fn tokenize_variable(
+            &mut self,
+            chars: &[char],
+            from: usize,
+            tokens: &mut Vec<TokenType>,
+        ) -> usize {
+            let close = match chars.get(from + 1) {
+                Some('(') => ')',
+                Some('{') => '}',
+                Some(_) => {
+                    return self.push_range(chars, from, from + 2, tokens, |b| lexer_literal!(b));
+                }
+                None => {
+                    return self
+                        .push_range(chars, from, from + 1, tokens, |b| lexer_identifier!(b));
+                }
+            };
+            let open = chars[from + 1];
+            let mut depth = 0;
+            for index in from + 1..chars.len() {
+                if chars[index] == open {
+                    depth += 1;
+                } else if chars[index] == close {
+                    depth -= 1;
+                    if depth == 0 {
+                        return self
+                            .push_range(chars, from, index + 1, tokens, |b| lexer_literal!(b));
+                    }
+                }
+            }
+            self.push_range(chars, from, chars.len(), tokens, |b| lexer_error!(b))
+        }
+
+        fn push_range<F>(
+            &mut self,
+            chars: &[char],
+            from: usize,
+            to: usize,
+            tokens: &mut Vec<TokenType>,
+            builder: F,
+        ) -> usize
+        where
+            F: Fn(&Lexer) -> TokenType,
+        {
+            if from < to {
+                self.buffer = chars[from..to].iter().collect();
+                tokens.push(builder(&self));
+                self.clear();
+            }
+            to
+        }
+
+        fn clear(&mut self) {
+            for c in self.buffer.chars() {
+                if c == '\n' {
+                    self.line += 1;
+                    self.character = 0;
+                } else {
+                    self.character += 1;
+                }
+            }
+            self.start += self.buffer.len();
+            self.char_start += self.buffer.chars().count();
+            self.buffer.clear();
+        }
+    }
+
+    fn run_length<F>(chars: &[char], from: usize, f: F) -> usize
+    where
+        F: Fn(char) -> bool,
+    {
+        chars[from..].iter().take_while(|c| f(**c)).count()
+    }
+
+    fn first_word_index(chars: &[char]) -> usize {
+        run_length(chars, 0, |c| c == ' ' || c == '\t')
+    }
+
+    fn first_word(chars: &[char]) -> String {
+        let from = first_word_index(chars);
+        chars[from..]
+            .iter()
+            .take_while(|c| !c.is_whitespace())
+            .collect()
+    }
+
+    fn is_word_end(c: char) -> bool {
+        c.is_whitespace() || "#$:=;".contains(c)
+    }
+
+    /// `:=`, `::=`, `+=`, `?=` and `!=` assignment operators
+    fn is_assignment(chars: &[char], index: usize) -> bool {
+        let len = run_length(chars, index, |c| c == ':');
+        match chars[index] {
+            ':' => len <= 3 && chars.get(index + len) == Some(&'='),
+            '+' | '?' | '!' => chars.get(index + 1) == Some(&'='),
+            _ => false,
+        }
+    }
+
+    /// Position of colon separating targets from prerequisites, outside of variable references
+    fn find_rule_colon(chars: &[char]) -> Option<usize> {
+        let mut depth = 0;
+        for (index, c) in chars.iter().enumerate() {
+            match c {
+                '#' => return None,
+                '=' if depth == 0 => return None,
+                '(' | '{' => depth += 1,
+                ')' | '}' => depth -= 1,
+                ':' if depth == 0 && !is_assignment(chars, index) => return Some(index),
+                ':' if depth == 0 => return None,
+                _ => (),
+            }
+        }
+        None
+    }
+
+    impl TokenBuilder for Lexer {
+        fn text(&self) -> String {
+            self.buffer.to_string()
+        }
+
+        fn line(&self) -> usize {
+            self.line
+        }
+
+        fn character(&self) -> usize {
+            self.character
+        }
+
+        fn start(&self) -> usize {
+            self.start
+        }
+
+        fn end(&self, text: &String) -> usize {
+            self.start + text.len()
+        }
+
+        fn char_start(&self) -> usize {
+            self.char_start
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        struct BuilderMock {
+            line: usize,
+            character: usize,
+            start: usize,
+            char_start: usize,
+            text: String,
+        }
+
+        impl TokenBuilder for BuilderMock {
+            fn text(&self) -> String {
+                self.text.clone()
+            }
+
+            fn line(&self) -> usize {
+                self.line
+            }
+
+            fn character(&self) -> usize {
+                self.character
+            }
+
+            fn start(&self) -> usize {
+                self.start
+            }
+
+            fn end(&self, current_text: &String) -> usize {
+                self.start + current_text.len()
+            }
+
+            fn char_start(&self) -> usize {
+                self.char_start
+            }
+        }
+
+        macro_rules! builder {
+            ($text: expr, $line: expr, $character: expr, $start: expr) => {
+                BuilderMock {
+                    line: $line,
+                    character: $character,
+                    start: $start,
+                    char_start: $start,
+                    text: $text.to_owned(),
+                }
+            };
+        }
+
+        #[test]
+        fn parse_empty() {
+            let code = "".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_variable() {
+            let code = "CC := gcc".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_identifier!(builder!("CC", 0, 0, 0)),
+                lexer_whitespace!(builder!(" ", 0, 2, 2)),
+                lexer_operator!(builder!(":=", 0, 3, 3)),
+                lexer_whitespace!(builder!(" ", 0, 5, 5)),
+                lexer_string!(builder!("gcc", 0, 6, 6)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_rule_with_recipe() {
+            let code = "all: a.o\n\t@$(CC) -o $@".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_keyword!(builder!("all", 0, 0, 0)),
+                lexer_separator!(builder!(":", 0, 3, 3)),
+                lexer_whitespace!(builder!(" ", 0, 4, 4)),
+                lexer_identifier!(builder!("a.o", 0, 5, 5)),
+                lexer_whitespace!(builder!("\n", 0, 8, 8)),
+                lexer_whitespace!(builder!("\t", 1, 0, 9)),
+                lexer_operator!(builder!("@", 1, 1, 10)),
+                lexer_literal!(builder!("$(CC)", 1, 2, 11)),
+                lexer_whitespace!(builder!(" ", 1, 7, 16)),
+                lexer_identifier!(builder!("-o", 1, 8, 17)),
+                lexer_whitespace!(builder!(" ", 1, 10, 19)),
+                lexer_literal!(builder!("$@", 1, 11, 20)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_append() {
+            let code = "A+=b".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_identifier!(builder!("A", 0, 0, 0)),
+                lexer_operator!(builder!("+=", 0, 1, 1)),
+                lexer_string!(builder!("b", 0, 3, 3)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_directive_and_comment() {
+            let code = "include a.mk # b".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_keyword!(builder!("include", 0, 0, 0)),
+                lexer_whitespace!(builder!(" ", 0, 7, 7)),
+                lexer_identifier!(builder!("a.mk", 0, 8, 8)),
+                lexer_whitespace!(builder!(" ", 0, 12, 12)),
+                lexer_comment!(builder!("# b", 0, 13, 13)),
+            ];
+            assert_eq!(result, expected)
+        }
+    }
+}
@@ -0,0 +1,699 @@
+use crate::Span;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SectionKind {
+    Markdown,
+    FencedCode { info: String },
+}
+
+/// Part of markdown document which should be highlighted with single language.
+/// Fence lines always belong to markdown sections, fenced code keeps info string
+/// so caller can pick language for it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section {
+    pub kind: SectionKind,
+    pub text: String,
+    pub span: Span,
+    pub char_span: Span,
+    pub line: usize,
+}
+
+impl Section {
+    fn new(kind: SectionKind, content: &str, lo: usize, hi: usize, line: usize) -> Self {
+        let char_lo = content[..lo].chars().count();
+        let text = content[lo..hi].to_string();
+        let char_hi = char_lo + text.chars().count();
+        Self {
+            kind,
+            text,
+            span: Span::new(lo, hi),
+            char_span: Span::new(char_lo, char_hi),
+            line,
+        }
+    }
+}
+
+pub fn sections(content: &str) -> Vec<Section> {
+    let mut sections = vec![];
+    let mut fence: Option<String> = None;
+    let mut section_start = 0;
+    let mut section_line = 0;
+    let mut offset = 0;
+
+    for (line, text) in content.split_inclusive('\n').enumerate() {
+        let body = text.trim_end_matches('\n');
+        let next = offset + text.len();
+        match fence.clone() {
+            Some(ref marker) if lexer::is_closing_fence(body, marker) => {
+                if section_start < offset {
+                    sections.push(Section::new(
+                        SectionKind::FencedCode {
+                            info: lexer::fence_info(marker),
+                        },
+                        content,
+                        section_start,
+                        offset,
+                        section_line,
+                    ));
+                }
+                sections.push(Section::new(
+                    SectionKind::Markdown,
+                    content,
+                    offset,
+                    next,
+                    line,
+                ));
+                section_start = next;
+                section_line = line + 1;
+                fence = None;
+            }
+            Some(_) => (),
+            None => {
+                if let Some(marker) = lexer::opening_fence(body) {
+                    sections.push(Section::new(
+                        SectionKind::Markdown,
+                        content,
+                        section_start,
+                        next,
+                        section_line,
+                    ));
+                    section_start = next;
+                    section_line = line + 1;
+                    fence = Some(marker);
+                }
+            }
+        }
+        offset = next;
+    }
+
+    if section_start < content.len() {
+        let kind = match fence {
+            Some(ref marker) => SectionKind::FencedCode {
+                info: lexer::fence_info(marker),
+            },
+            None => SectionKind::Markdown,
+        };
+        sections.push(Section::new(
+            kind,
+            content,
+            section_start,
+            content.len(),
+            section_line,
+        ));
+    }
+    sections
+}
+
+pub mod lexer {
+    use crate::*;
+
+    /// Returns fence marker together with info string, e.g. "```rust"
+    pub fn opening_fence(line: &str) -> Option<String> {
+        let indent = line.len() - line.trim_start_matches(' ').len();
+        if indent > 3 {
+            return None;
+        }
+        let trimmed = &line[indent..];
+        let fence_char = match trimmed.chars().next() {
+            Some(c) if c == '`' || c == '~' => c,
+            _ => return None,
+        };
+        let marker_len = trimmed.len() - trimmed.trim_start_matches(fence_char).len();
+        if marker_len < 3 {
+            return None;
+        }
+        let info = trimmed[marker_len..].trim();
+        if fence_char == '`' && info.contains('`') {
+            return None;
+        }
+        Some(trimmed.trim_end().to_string())
+    }
+
+    pub fn is_closing_fence(line: &str, opening: &str) -> bool {
+        let fence_char = match opening.chars().next() {
+            Some(c) => c,
+            _ => return false,
+        };
+        let opening_len = opening.len() - opening.trim_start_matches(fence_char).len();
+        let trimmed = line.trim();
+        let indent = line.len() - line.trim_start_matches(' ').len();
+        indent <= 3 && trimmed.len() >= opening_len && trimmed.chars().all(|c| c == fence_char)
+    }
+
+    pub fn fence_info(opening: &str) -> String {
+        opening
+            .trim_start_matches(|c| c == '`' || c == '~')
+            .split_whitespace()
+            .next()
+            .unwrap_or("")
+            .to_string()
+    }
+
+    pub struct Lexer {
+        content: String,
+        buffer: String,
+        line: usize,
+        character: usize,
+        start: usize,
+        char_start: usize,
+    }
+
+    impl Lexer {
+        pub fn new(content: String) -> Self {
+            Self {
+                content,
+                buffer: String::new(),
+                line: 0,
+                character: 0,
+                start: 0,
+                char_start: 0,
+            }
+        }
+
+        pub fn tokenize(&mut self) -> Vec<TokenType> {
+            let mut tokens = vec![];
+            let content = self.content.clone();
+            let mut fence: Option<String> = None;
+            for text in content.split_inclusive('\n') {
+                let body = text.trim_end_matches('\n');
+                match fence.clone() {
+                    Some(ref marker) if is_closing_fence(body, marker) => {
+                        self.tokenize_fence(body, &mut tokens);
+                        fence = None;
+                    }
+                    Some(_) => {
+                        if !body.is_empty() {
+                            self.push(body, &mut tokens, |b| lexer_string!(b));
+                        }
+                    }
+                    None => match opening_fence(body) {
+                        Some(marker) => {
+                            self.tokenize_fence(body, &mut tokens);
+                            fence = Some(marker);
+                        }
+                        None => self.tokenize_line(body, &mut tokens),
+                    },
+                }
+                if text.ends_with('\n') {
+                    self.push("\n", &mut tokens, |b| lexer_whitespace!(b));
+                }
+            }
+            tokens
+        }
+
+        fn tokenize_fence(&mut self, body: &str, tokens: &mut Vec<TokenType>) {
+            let chars: Vec<char> = body.chars().collect();
+            let mut index = 0;
+            while index < chars.len() {
+                let c = chars[index];
+                let len = run_length(&chars, index, |x| x == c);
+                let text: String = chars[index..index + len].iter().collect();
+                match c {
+                    ' ' | '\t' | '\r' => {
+                        self.push_each(&text, tokens, |b| lexer_whitespace!(b));
+                    }
+                    '`' | '~' if index == 0 || chars[..index].iter().all(|c| *c == ' ') => {
+                        self.push(&text, tokens, |b| lexer_separator!(b))
+                    }
+                    _ => {
+                        let len = run_length(&chars, index, |x| !x.is_whitespace());
+                        let text: String = chars[index..index + len].iter().collect();
+                        self.push(&text, tokens, |b| lexer_keyword!(b));
+                        index += len;
+                        continue;
+                    }
+                }
+                index += len;
+            }
+        }
+
+        fn tokenize_line(&mut self, body: &str, tokens: &mut Vec<TokenType>) {
+            let chars: Vec<char> = body.chars().collect();
+            let mut index = self.tokenize_line_start(&chars, tokens);
+
+            while index < chars.len() {
+                let c = chars[index];
+                match c {
+                    ' ' | '\t' | '\r' => {
+                        self.push_char(c, tokens, |b| lexer_whitespace!(b));
+                        index += 1;
+                    }
+                    '`' => {
+                        let len = run_length(&chars, index, |x| x == '`');
+                        index = match find_run(&chars, index + len, '`', len) {
+                            Some(end) => self
+                                .push_range(&chars, index, end + len, tokens, |b| lexer_string!(b)),
+                            None => self.push_range(&chars, index, index + len, tokens, |b| {
+                                lexer_identifier!(b)
+                            }),
+                        };
+                    }
+                    '*' | '_' if !(c == '_' && index > 0 && chars[index - 1].is_alphanumeric()) => {
+                        let len = run_length(&chars, index, |x| x == c).min(3);
+                        let opens = chars
+                            .get(index + len)
+                            .map_or(false, |next| !next.is_whitespace());
+                        index = match find_run(&chars, index + len, c, len) {
+                            Some(end) if opens && !chars[end - 1].is_whitespace() => self
+                                .push_range(&chars, index, end + len, tokens, |b| {
+                                    lexer_literal!(b)
+                                }),
+                            _ => self.push_range(&chars, index, index + len, tokens, |b| {
+                                lexer_identifier!(b)
+                            }),
+                        };
+                    }
+                    '!' if chars.get(index + 1) == Some(&'[') => {
+                        self.push_char(c, tokens, |b| lexer_operator!(b));
+                        index += 1;
+                    }
+                    '[' => {
+                        index = self.tokenize_link(&chars, index, tokens);
+                    }
+                    _ => {
+                        let len = run_length(&chars, index, |x| !is_word_break(x));
+                        let len = if len == 0 { 1 } else { len };
+                        index = self.push_range(&chars, index, index + len, tokens, |b| {
+                            lexer_identifier!(b)
+                        });
+                    }
+                }
+            }
+        }
+
+        fn tokenize_line_start(&mut self, chars: &[char], tokens: &mut Vec<TokenType>) -> usize {
+            let mut index = 0;
+            loop {
+                let indent = run_length(chars, index, |c| c == ' ' || c == '\t');
+                for c in chars[index..index + indent].iter() {
+                    self.push_char(*c, tokens, |b| lexer_whitespace!(b));
+                }
+                index += indent;
+
+                let rest = &chars[index..];
+                if is_thematic_break(rest) {
+                    return self
+                        .push_range(chars, index, chars.len(), tokens, |b| lexer_operator!(b));
+                }
+                if heading_level(rest).is_some() {
+                    return self
+                        .push_range(chars, index, chars.len(), tokens, |b| lexer_keyword!(b));
+                }
+                match list_marker(rest) {
+                    Some(len) => {
+                        index = self
+                            .push_range(chars, index, index + len, tokens, |b| lexer_operator!(b));
+                        return index;
+                    }
+                    None => (),
+                }
+                if rest.first() == Some(&'>') {
+                    index =
+                        self.push_range(chars, index, index + 1, tokens, |b| lexer_operator!(b));
+                    continue;
+                }
+                return index;
+            }
+        }
+
+        fn tokenize_link(
+            &mut self,
+            chars: &[char],
+            index: usize,
+            tokens: &mut Vec<TokenType>,
+        ) -> usize {
+            let close = chars[index + 1..]
+                .iter()
+                .position(|c| *c == ']')
+                .map(|p| p + index + 1);
+            let url_end = close.and_then(|close| match chars.get(close + 1) {
+                Some('(') => chars[close + 2..]
+                    .iter()
+                    .position(|c| *c == ')')
+                    .map(|p| p + close + 2),
+                _ => None,
+            });
+            match (close, url_end) {
+                (Some(close), Some(url_end)) => {
+                    self.push_char('[', tokens, |b| lexer_separator!(b));
+                    if close > index + 1 {
+                        self.push_range(chars, index + 1, close, tokens, |b| lexer_identifier!(b));
+                    }
+                    self.push_char(']', tokens, |b| lexer_separator!(b));
+                    self.push_char('(', tokens, |b| lexer_separator!(b));
+                    if url_end > close + 2 {
+                        self.push_range(chars, close + 2, url_end, tokens, |b| lexer_string!(b));
+                    }
+                    self.push_char(')', tokens, |b| lexer_separator!(b));
+                    url_end + 1
+                }
+                _ => self.push_range(chars, index, index + 1, tokens, |b| lexer_identifier!(b)),
+            }
+        }
+
+        fn push_range<F>(
+            &mut self,
+            chars: &[char],
+            from: usize,
+            to: usize,
+            tokens: &mut Vec<TokenType>,
+            builder: F,
+        ) -> usize
+        where
+            F: Fn(&Lexer) -> TokenType,
+        {
+            let text: String = chars[from..to].iter().collect();
+            self.push(&text, tokens, builder);
+            to
+        }
+
+        fn push_char<F>(&mut self, c: char, tokens: &mut Vec<TokenType>, builder: F)
+        where
+            F: Fn(&Lexer) -> TokenType,
+        {
+            self.push(c.to_string().as_str(), tokens, builder)
+        }
+
+        fn push_each<F>(&mut self, text: &str, tokens: &mut Vec<TokenType>, builder: F)
+        where
+            F: Fn(&Lexer) -> TokenType,
+        {
+            for c in text.chars() {
+                self.push_char(c, tokens, &builder);
+            }
+        }
+
+        fn push<F>(&mut self, text: &str, tokens: &mut Vec<TokenType>, builder: F)
+        where
+            F: Fn(&Lexer) -> TokenType,
+        {
+            self.buffer.push_str(text);
+            tokens.push(builder(&self));
+            self.clear();
+        }
+
+        fn clear(&mut self) {
+            for c in self.buffer.chars() {
+                if c == '\n' {
+                    self.line += 1;
+                    self.character = 0;
+                } else {
+                    self.character += 1;
+                }
+            }
+            self.start += self.buffer.len();
+            self.char_start += self.buffer.chars().count();
+            self.buffer.clear();
+        }
+    }
+
+    fn run_length<F>(chars: &[char], from: usize, f: F) -> usize
+    where
+        F: Fn(char) -> bool,
+    {
+        chars[from..].iter().take_while(|c| f(**c)).count()
+    }
+
+    fn find_run(chars: &[char], from: usize, c: char, len: usize) -> Option<usize> {
+        let mut index = from;
+        while index < chars.len() {
+            let current = run_length(chars, index, |x| x == c);
+            if current == len {
+                return Some(index);
+            }
+            index += current.max(1);
+        }
+        None
+    }
+
+    fn is_word_break(c: char) -> bool {
+        c.is_whitespace() || c == '`' || c == '*' || c == '[' || c == '!'
+    }
+
+    fn is_thematic_break(chars: &[char]) -> bool {
+        let marker = match chars.first() {
+            Some(c) if *c == '-' || *c == '*' || *c == '_' => *c,
+            _ => return false,
+        };
+        chars.iter().all(|c| *c == marker || *c == ' ')
+            && chars.iter().filter(|c| **c == marker).count() >= 3
+    }
+
+    fn heading_level(chars: &[char]) -> Option<usize> {
+        let level = run_length(chars, 0, |c| c == '#');
+        match chars.get(level) {
+            _ if level == 0 || level > 6 => None,
+            None | Some(' ') | Some('\t') => Some(level),
+            _ => None,
+        }
+    }
+
+    fn list_marker(chars: &[char]) -> Option<usize> {
+        let len = match chars.first() {
+            Some('-') | Some('*') | Some('+') => 1,
+            Some(c) if c.is_ascii_digit() => {
+                let digits = run_length(chars, 0, |c| c.is_ascii_digit());
+                match chars.get(digits) {
+                    Some('.') | Some(')') if digits <= 9 => digits + 1,
+                    _ => return None,
+                }
+            }
+            _ => return None,
+        };
+        match chars.get(len) {
+            Some(' ') | Some('\t') | None => Some(len),
+            _ => None,
+        }
+    }
+
+    impl TokenBuilder for Lexer {
+        fn text(&self) -> String {
+            self.buffer.to_string()
+        }
+
+        fn line(&self) -> usize {
+            self.line
+        }
+
+        fn character(&self) -> usize {
+            self.character
+        }
+
+        fn start(&self) -> usize {
+            self.start
+        }
+
+        fn end(&self, text: &String) -> usize {
+            self.start + text.len()
+        }
+
+        fn char_start(&self) -> usize {
+            self.char_start
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        struct BuilderMock {
+            line: usize,
+            character: usize,
+            start: usize,
+            char_start: usize,
+            text: String,
+        }
+
+        impl TokenBuilder for BuilderMock {
+            fn text(&self) -> String {
+                self.text.clone()
+            }
+
+            fn line(&self) -> usize {
+                self.line
+            }
+
+            fn character(&self) -> usize {
+                self.character
+            }
+
+            fn start(&self) -> usize {
+                self.start
+            }
+
+            fn end(&self, current_text: &String) -> usize {
+                self.start + current_text.len()
+            }
+
+            fn char_start(&self) -> usize {
+                self.char_start
+            }
+        }
+
+        macro_rules! builder {
+            ($text: expr, $line: expr, $character: expr, $start: expr) => {
+                BuilderMock {
+                    line: $line,
+                    character: $character,
+                    start: $start,
+                    char_start: $start,
+                    text: $text.to_owned(),
+                }
+            };
+        }
+
+        #[test]
+        fn parse_empty() {
+            let code = "".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_heading() {
+            let code = "## Hello world\nfoo".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_keyword!(builder!("## Hello world", 0, 0, 0)),
+                lexer_whitespace!(builder!("\n", 0, 14, 14)),
+                lexer_identifier!(builder!("foo", 1, 0, 15)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_emphasis() {
+            let code = "a *b c* __d__".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_identifier!(builder!("a", 0, 0, 0)),
+                lexer_whitespace!(builder!(" ", 0, 1, 1)),
+                lexer_literal!(builder!("*b c*", 0, 2, 2)),
+                lexer_whitespace!(builder!(" ", 0, 7, 7)),
+                lexer_literal!(builder!("__d__", 0, 8, 8)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_snake_case_word() {
+            let code = "foo_bar_baz".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![lexer_identifier!(builder!("foo_bar_baz", 0, 0, 0))];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_link() {
+            let code = "![img](a.png)".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_operator!(builder!("!", 0, 0, 0)),
+                lexer_separator!(builder!("[", 0, 1, 1)),
+                lexer_identifier!(builder!("img", 0, 2, 2)),
+                lexer_separator!(builder!("]", 0, 5, 5)),
+                lexer_separator!(builder!("(", 0, 6, 6)),
+                lexer_string!(builder!("a.png", 0, 7, 7)),
+                lexer_separator!(builder!(")", 0, 12, 12)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_list_and_code_span() {
+            let code = "- `a b`\n1. c".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_operator!(builder!("-", 0, 0, 0)),
+                lexer_whitespace!(builder!(" ", 0, 1, 1)),
+                lexer_string!(builder!("`a b`", 0, 2, 2)),
+                lexer_whitespace!(builder!("\n", 0, 7, 7)),
+                lexer_operator!(builder!("1.", 1, 0, 8)),
+                lexer_whitespace!(builder!(" ", 1, 2, 10)),
+                lexer_identifier!(builder!("c", 1, 3, 11)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_fenced_code() {
+            let code = "```rust\nfn a\n```".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_separator!(builder!("```", 0, 0, 0)),
+                lexer_keyword!(builder!("rust", 0, 3, 3)),
+                lexer_whitespace!(builder!("\n", 0, 7, 7)),
+                lexer_string!(builder!("fn a", 1, 0, 8)),
+                lexer_whitespace!(builder!("\n", 1, 4, 12)),
+                lexer_separator!(builder!("```", 2, 0, 13)),
+            ];
+            assert_eq!(result, expected)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn must_return_single_section_without_fences() {
+        let result = sections("# Foo\nbar\n");
+        let expected = vec![Section::new(
+            SectionKind::Markdown,
+            "# Foo\nbar\n",
+            0,
+            10,
+            0,
+        )];
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn must_split_fenced_code() {
+        let content = "a\n```toml\nb = 1\n```\nc\n";
+        let result = sections(content);
+        let expected = vec![
+            Section::new(SectionKind::Markdown, content, 0, 10, 0),
+            Section::new(
+                SectionKind::FencedCode {
+                    info: "toml".to_string(),
+                },
+                content,
+                10,
+                16,
+                2,
+            ),
+            Section::new(SectionKind::Markdown, content, 16, 20, 3),
+            Section::new(SectionKind::Markdown, content, 20, 22, 4),
+        ];
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn must_keep_unterminated_fence_as_code() {
+        let content = "~~~\nfoo";
+        let result = sections(content);
+        let expected = vec![
+            Section::new(SectionKind::Markdown, content, 0, 4, 0),
+            Section::new(
+                SectionKind::FencedCode {
+                    info: "".to_string(),
+                },
+                content,
+                4,
+                7,
+                1,
+            ),
+        ];
+        assert_eq!(result, expected);
+    }
+}
@@ -0,0 +1,330 @@
+use crate::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Function,
+    Struct,
+    Enum,
+    Trait,
+    Impl,
+    Module,
+    Const,
+    Table,
+    Heading,
+}
+
+impl SymbolKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SymbolKind::Function => "fn",
+            SymbolKind::Struct => "struct",
+            SymbolKind::Enum => "enum",
+            SymbolKind::Trait => "trait",
+            SymbolKind::Impl => "impl",
+            SymbolKind::Module => "mod",
+            SymbolKind::Const => "const",
+            SymbolKind::Table => "table",
+            SymbolKind::Heading => "#",
+        }
+    }
+}
+
+/// Named item of document, `position` is char index where its name or heading starts
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub line: usize,
+    pub position: usize,
+    pub depth: usize,
+}
+
+/// Symbols of document in order of appearance, empty for languages without outline
+pub fn symbols(tokens: &[TokenType], language: Language) -> Vec<Symbol> {
+    match language {
+        Language::Rust => rust_symbols(tokens),
+        Language::Toml => toml_symbols(tokens),
+        Language::Markdown => markdown_symbols(tokens),
+        _ => vec![],
+    }
+}
+
+fn item_kind(text: &str) -> Option<SymbolKind> {
+    match text {
+        "fn" => Some(SymbolKind::Function),
+        "struct" => Some(SymbolKind::Struct),
+        "enum" => Some(SymbolKind::Enum),
+        "trait" => Some(SymbolKind::Trait),
+        "impl" => Some(SymbolKind::Impl),
+        "mod" => Some(SymbolKind::Module),
+        "const" => Some(SymbolKind::Const),
+        _ => None,
+    }
+}
+
+fn is_significant(token: &TokenType) -> bool {
+    match token {
+        TokenType::Whitespace { .. } | TokenType::Comment { .. } => false,
+        _ => true,
+    }
+}
+
+/// Item keyword can follow only another modifier or end of previous item,
+/// this rejects `-> impl Trait` and `*const T`
+fn can_start_item(previous: Option<&TokenType>) -> bool {
+    match previous {
+        None => true,
+        Some(TokenType::Separator { token }) => match token.text.as_str() {
+            ";" | "{" | "}" | "]" | ")" => true,
+            _ => false,
+        },
+        Some(TokenType::Keyword { .. })
+        | Some(TokenType::Identifier { .. })
+        | Some(TokenType::String { .. }) => true,
+        _ => false,
+    }
+}
+
+fn rust_symbols(tokens: &[TokenType]) -> Vec<Symbol> {
+    let tokens: Vec<&TokenType> = tokens.iter().filter(|t| is_significant(t)).collect();
+    let mut symbols = vec![];
+    let mut depth: usize = 0;
+    for (index, token) in tokens.iter().enumerate() {
+        match token {
+            TokenType::Separator { token } if token.text == "{" => depth += 1,
+            TokenType::Separator { token } if token.text == "}" => depth = depth.saturating_sub(1),
+            TokenType::Keyword { token } | TokenType::Identifier { token } => {
+                let kind = match item_kind(token.text.as_str()) {
+                    Some(kind) => kind,
+                    _ => continue,
+                };
+                let previous = index.checked_sub(1).map(|i| tokens[i]);
+                if !can_start_item(previous) {
+                    continue;
+                }
+                let symbol = match kind {
+                    SymbolKind::Impl => impl_symbol(&tokens[index + 1..], depth),
+                    _ => named_symbol(tokens.get(index + 1), kind, depth),
+                };
+                symbols.extend(symbol);
+            }
+            _ => (),
+        }
+    }
+    symbols
+}
+
+fn named_symbol(next: Option<&&TokenType>, kind: SymbolKind, depth: usize) -> Option<Symbol> {
+    match next {
+        Some(TokenType::Identifier { token }) => Some(Symbol {
+            name: token.text.clone(),
+            kind,
+            line: token.line,
+            position: token.char_span().lo,
+            depth,
+        }),
+        _ => None,
+    }
+}
+
+/// `impl<T> Foo for Bar<T>` is named `Foo for Bar<T>`
+fn impl_symbol(tokens: &[&TokenType], depth: usize) -> Option<Symbol> {
+    let mut tokens = tokens.iter().peekable();
+    if tokens.peek().map_or(false, |t| t.text() == "<") {
+        let mut generics = 0;
+        while let Some(token) = tokens.next() {
+            match token.text().as_str() {
+                "<" => generics += 1,
+                ">" => generics -= 1,
+                _ => (),
+            }
+            if generics == 0 {
+                break;
+            }
+        }
+    }
+    let first = tokens.peek()?;
+    let (line, position) = (first.line(), first.char_span().lo);
+    let parts: Vec<String> = tokens
+        .take_while(|t| match t.text().as_str() {
+            "{" | ";" | "where" => false,
+            _ => true,
+        })
+        .map(|t| t.text().clone())
+        .collect();
+    if parts.is_empty() {
+        return None;
+    }
+    Some(Symbol {
+        name: join_parts(&parts),
+        kind: SymbolKind::Impl,
+        line,
+        position,
+        depth,
+    })
+}
+
+/// Words are separated with single space while punctuation sticks to its neighbours
+fn join_parts(parts: &[String]) -> String {
+    let is_word = |s: &str| s.chars().all(|c| c.is_alphanumeric() || c == '_');
+    let mut name = String::new();
+    let mut previous_is_word = false;
+    for part in parts {
+        let word = is_word(part.as_str());
+        if word && previous_is_word {
+            name.push(' ');
+        }
+        name.push_str(part.as_str());
+        previous_is_word = word;
+    }
+    name
+}
+
+/// `[table]` and `[[array]]` headers which start a line
+fn toml_symbols(tokens: &[TokenType]) -> Vec<Symbol> {
+    let mut symbols = vec![];
+    let mut line_start = true;
+    let mut index = 0;
+    while index < tokens.len() {
+        let token = &tokens[index];
+        index += 1;
+        match token {
+            TokenType::Whitespace { token } => {
+                line_start = line_start || token.text.contains('\n');
+                continue;
+            }
+            TokenType::Separator { token } if line_start && token.text == "[" => {
+                while tokens.get(index).map_or(false, |t| t.text() == "[") {
+                    index += 1;
+                }
+                let name_tokens: Vec<&TokenType> = tokens[index..]
+                    .iter()
+                    .take_while(|t| t.text() != "]" && !t.text().contains('\n'))
+                    .collect();
+                index += name_tokens.len();
+                if let Some(first) = name_tokens.first() {
+                    let name: String = name_tokens.iter().map(|t| t.text().as_str()).collect();
+                    symbols.push(Symbol {
+                        name: name.trim().to_owned(),
+                        kind: SymbolKind::Table,
+                        line: first.line(),
+                        position: first.char_span().lo,
+                        depth: 0,
+                    });
+                }
+            }
+            _ => (),
+        }
+        line_start = false;
+    }
+    symbols
+}
+
+/// ATX headings, depth is heading level minus one
+fn markdown_symbols(tokens: &[TokenType]) -> Vec<Symbol> {
+    tokens
+        .iter()
+        .filter_map(|token| match token {
+            TokenType::Keyword { token } if token.text.starts_with('#') => {
+                let level = token.text.chars().take_while(|c| *c == '#').count();
+                let name = token.text[level..].trim();
+                if name.is_empty() {
+                    return None;
+                }
+                Some(Symbol {
+                    name: name.to_owned(),
+                    kind: SymbolKind::Heading,
+                    line: token.line,
+                    position: token.char_span().lo,
+                    depth: level - 1,
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outline_of(text: &str, language: Language) -> Vec<(String, SymbolKind, usize, usize)> {
+        symbols(&parse(text.to_owned(), language), language)
+            .into_iter()
+            .map(|s| (s.name, s.kind, s.line, s.depth))
+            .collect()
+    }
+
+    #[test]
+    fn must_find_rust_items() {
+        let code = "mod a;\npub struct B<T> {}\nenum C {}\ntrait D {}\npub(crate) const E: u8 = 1;\nfn f() {}\n";
+        assert_eq!(
+            outline_of(code, Language::Rust),
+            vec![
+                ("a".to_owned(), SymbolKind::Module, 0, 0),
+                ("B".to_owned(), SymbolKind::Struct, 1, 0),
+                ("C".to_owned(), SymbolKind::Enum, 2, 0),
+                ("D".to_owned(), SymbolKind::Trait, 3, 0),
+                ("E".to_owned(), SymbolKind::Const, 4, 0),
+                ("f".to_owned(), SymbolKind::Function, 5, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn must_name_impl_blocks_and_nest_methods() {
+        let code =
+            "impl<T> Foo for Bar<T> where T: X {\n    pub const fn new() -> *const T {}\n}\n";
+        assert_eq!(
+            outline_of(code, Language::Rust),
+            vec![
+                ("Foo for Bar<T>".to_owned(), SymbolKind::Impl, 0, 0),
+                ("new".to_owned(), SymbolKind::Function, 1, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn must_skip_impl_trait_and_fn_types() {
+        let code = "fn a(f: fn(u8)) -> impl Iterator {}";
+        assert_eq!(
+            outline_of(code, Language::Rust),
+            vec![("a".to_owned(), SymbolKind::Function, 0, 0)]
+        );
+    }
+
+    #[test]
+    fn must_find_toml_tables() {
+        let code = "[package]\nname = \"a\"\nlist = [1]\n[[bin]]\n[a.b]\nx = { y = 1 }\n";
+        assert_eq!(
+            outline_of(code, Language::Toml),
+            vec![
+                ("package".to_owned(), SymbolKind::Table, 0, 0),
+                ("bin".to_owned(), SymbolKind::Table, 3, 0),
+                ("a.b".to_owned(), SymbolKind::Table, 4, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn must_find_markdown_headings() {
+        let code = "# Title\ntext\n## Sub\n```rust\nfn a() {}\n```\n";
+        assert_eq!(
+            outline_of(code, Language::Markdown),
+            vec![
+                ("Title".to_owned(), SymbolKind::Heading, 0, 0),
+                ("Sub".to_owned(), SymbolKind::Heading, 2, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn must_point_at_symbol_name() {
+        let tokens = parse("fn ąb() {}\nfn c() {}".to_owned(), Language::Rust);
+        let positions: Vec<usize> = symbols(&tokens, Language::Rust)
+            .into_iter()
+            .map(|s| s.position)
+            .collect();
+        assert_eq!(positions, vec![3, 14]);
+    }
+}
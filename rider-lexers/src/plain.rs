@@ -1,24 +1,22 @@
 pub mod lexer {
-    use crate::{Span, Token, TokenType};
+    use crate::*;
     use plex::lexer;
 
     lexer! {
-        fn next_token(text: 'a) -> (TokenType, &'a str);
+        fn next_token(text: 'a) -> (TokenKind, &'a str);
 
-        r"[ \t\r\n]" => (TokenType::Whitespace {
-            token: Token::new(text.to_string(), 0, 0, 0, 0)
-        }, text),
+        r"[ \t\r\n]" => (TokenKind::Whitespace, text),
 
-        r"[^ \t\r\n]+" => (TokenType::Identifier {
-            token: Token::new(text.to_string(), 0, 0, 0, 0)
-        }, text),
+        r"[^ \t\r\n]+" => (TokenKind::Identifier, text),
     }
 
     pub struct Lexer<'a> {
         original: &'a str,
         remaining: &'a str,
+        current: &'a str,
         line: usize,
         character: usize,
+        char_start: usize,
     }
 
     impl<'a> Lexer<'a> {
@@ -26,8 +24,10 @@ pub mod lexer {
             Self {
                 original: s,
                 remaining: s,
+                current: "",
                 line: 0,
                 character: 0,
+                char_start: 0,
             }
         }
     }
@@ -36,27 +36,21 @@ pub mod lexer {
         type Item = (TokenType, Span);
 
         fn next(&mut self) -> Option<(TokenType, Span)> {
-            let tok: (TokenType, &str) =
-                if let Some(((token_type, text), new_remaining)) = next_token(self.remaining) {
-                    self.remaining = new_remaining;
-                    if token_type.is_new_line() {
-                        self.line += 1;
-                        self.character = text.len();
-                    } else {
-                        self.character += text.len();
-                    }
-                    (token_type, text)
+            let ((kind, text), remaining) = next_token(self.remaining)?;
+            self.remaining = remaining;
+            self.current = text;
+            let token = kind.build(self);
+            let span = token.span();
+            for c in text.chars() {
+                if c == '\n' {
+                    self.line += 1;
+                    self.character = 0;
                 } else {
-                    return None;
-                };
-            match tok {
-                (tok, text) => {
-                    let span = self.span_in(text);
-                    let token =
-                        tok.move_to(self.line, self.character - text.len(), span.lo, span.hi);
-                    Some((token, span))
+                    self.character += 1;
                 }
+                self.char_start += 1;
             }
+            Some((token, span))
         }
     }
 
@@ -69,12 +63,38 @@ pub mod lexer {
             }
         }
     }
+
+    impl<'a> TokenBuilder for Lexer<'a> {
+        fn text(&self) -> String {
+            self.current.to_string()
+        }
+
+        fn line(&self) -> usize {
+            self.line
+        }
+
+        fn character(&self) -> usize {
+            self.character
+        }
+
+        fn start(&self) -> usize {
+            self.span_in(self.current).lo
+        }
+
+        fn end(&self, text: &String) -> usize {
+            self.start() + text.len()
+        }
+
+        fn char_start(&self) -> usize {
+            self.char_start
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{Token, TokenType};
+    use crate::{Span, Token, TokenType};
 
     #[test]
     fn must_parse_simple_text() {
@@ -82,7 +102,7 @@ mod tests {
         let lexer = lexer::Lexer::new(code);
         let result: Vec<TokenType> = lexer.map(|pair| pair.0).collect();
         let expected: Vec<TokenType> = vec![TokenType::Identifier {
-            token: Token::new("foo".to_string(), 0, 0, 0, 3),
+            token: Token::new("foo".to_string(), 0, 0, Span::new(0, 3), Span::new(0, 3)),
         }];
         assert_eq!(result, expected);
     }
@@ -97,8 +117,8 @@ mod tests {
                 "foobarhelloworldexamplecomtesttest".to_string(),
                 0,
                 0,
-                0,
-                34,
+                Span::new(0, 34),
+                Span::new(0, 34),
             ),
         }];
         assert_eq!(result, expected);
@@ -111,13 +131,13 @@ mod tests {
         let result: Vec<TokenType> = lexer.map(|pair| pair.0).collect();
         let expected: Vec<TokenType> = vec![
             TokenType::Identifier {
-                token: Token::new("foo".to_string(), 0, 0, 0, 3),
+                token: Token::new("foo".to_string(), 0, 0, Span::new(0, 3), Span::new(0, 3)),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 3, 3, 4),
+                token: Token::new(" ".to_string(), 0, 3, Span::new(3, 4), Span::new(3, 4)),
             },
             TokenType::Identifier {
-                token: Token::new("bar".to_string(), 0, 4, 4, 7),
+                token: Token::new("bar".to_string(), 0, 4, Span::new(4, 7), Span::new(4, 7)),
             },
         ];
         assert_eq!(result, expected);
@@ -130,28 +150,28 @@ mod tests {
         let result: Vec<TokenType> = lexer.map(|pair| pair.0).collect();
         let expected: Vec<TokenType> = vec![
             TokenType::Identifier {
-                token: Token::new("foo".to_string(), 0, 0, 0, 3),
+                token: Token::new("foo".to_string(), 0, 0, Span::new(0, 3), Span::new(0, 3)),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 3, 3, 4),
+                token: Token::new(" ".to_string(), 0, 3, Span::new(3, 4), Span::new(3, 4)),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 4, 4, 5),
+                token: Token::new(" ".to_string(), 0, 4, Span::new(4, 5), Span::new(4, 5)),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 5, 5, 6),
+                token: Token::new(" ".to_string(), 0, 5, Span::new(5, 6), Span::new(5, 6)),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 6, 6, 7),
+                token: Token::new(" ".to_string(), 0, 6, Span::new(6, 7), Span::new(6, 7)),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 7, 7, 8),
+                token: Token::new(" ".to_string(), 0, 7, Span::new(7, 8), Span::new(7, 8)),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 8, 8, 9),
+                token: Token::new(" ".to_string(), 0, 8, Span::new(8, 9), Span::new(8, 9)),
             },
             TokenType::Identifier {
-                token: Token::new("bar".to_string(), 0, 9, 9, 12),
+                token: Token::new("bar".to_string(), 0, 9, Span::new(9, 12), Span::new(9, 12)),
             },
         ];
         assert_eq!(result, expected);
@@ -164,25 +184,37 @@ mod tests {
         let result: Vec<TokenType> = lexer.map(|pair| pair.0).collect();
         let expected: Vec<TokenType> = vec![
             TokenType::Identifier {
-                token: Token::new("foo".to_string(), 0, 0, 0, 3),
+                token: Token::new("foo".to_string(), 0, 0, Span::new(0, 3), Span::new(0, 3)),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 3, 3, 4),
+                token: Token::new(" ".to_string(), 0, 3, Span::new(3, 4), Span::new(3, 4)),
             },
             TokenType::Identifier {
-                token: Token::new("bar".to_string(), 0, 4, 4, 7),
+                token: Token::new("bar".to_string(), 0, 4, Span::new(4, 7), Span::new(4, 7)),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 7, 7, 8),
+                token: Token::new(" ".to_string(), 0, 7, Span::new(7, 8), Span::new(7, 8)),
             },
             TokenType::Identifier {
-                token: Token::new("hello".to_string(), 0, 8, 8, 13),
+                token: Token::new(
+                    "hello".to_string(),
+                    0,
+                    8,
+                    Span::new(8, 13),
+                    Span::new(8, 13),
+                ),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 13, 13, 14),
+                token: Token::new(" ".to_string(), 0, 13, Span::new(13, 14), Span::new(13, 14)),
             },
             TokenType::Identifier {
-                token: Token::new("world".to_string(), 0, 14, 14, 19),
+                token: Token::new(
+                    "world".to_string(),
+                    0,
+                    14,
+                    Span::new(14, 19),
+                    Span::new(14, 19),
+                ),
             },
         ];
         assert_eq!(result, expected);
@@ -195,13 +227,32 @@ mod tests {
         let result: Vec<TokenType> = lexer.map(|pair| pair.0).collect();
         let expected: Vec<TokenType> = vec![
             TokenType::Identifier {
-                token: Token::new("foo".to_string(), 0, 0, 0, 3),
+                token: Token::new("foo".to_string(), 0, 0, Span::new(0, 3), Span::new(0, 3)),
+            },
+            TokenType::Whitespace {
+                token: Token::new("\n".to_string(), 0, 3, Span::new(3, 4), Span::new(3, 4)),
+            },
+            TokenType::Identifier {
+                token: Token::new("bar".to_string(), 1, 0, Span::new(4, 7), Span::new(4, 7)),
+            },
+        ];
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn must_parse_multi_byte_text() {
+        let code = "żółw\nłoś";
+        let lexer = lexer::Lexer::new(code);
+        let result: Vec<TokenType> = lexer.map(|pair| pair.0).collect();
+        let expected: Vec<TokenType> = vec![
+            TokenType::Identifier {
+                token: Token::new("żółw".to_string(), 0, 0, Span::new(0, 7), Span::new(0, 4)),
             },
             TokenType::Whitespace {
-                token: Token::new("\n".to_string(), 1, 0, 3, 4),
+                token: Token::new("\n".to_string(), 0, 4, Span::new(7, 8), Span::new(4, 5)),
             },
             TokenType::Identifier {
-                token: Token::new("bar".to_string(), 1, 1, 4, 7),
+                token: Token::new("łoś".to_string(), 1, 0, Span::new(8, 13), Span::new(5, 8)),
             },
         ];
         assert_eq!(result, expected);
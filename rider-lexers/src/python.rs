@@ -0,0 +1,430 @@
+pub mod lexer {
+    use crate::*;
+
+    pub const KEYWORDS: &[&str] = &[
+        "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class",
+        "continue", "def", "del", "elif", "else", "except", "finally", "for", "from", "global",
+        "if", "import", "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return",
+        "try", "while", "with", "yield", "self", "match", "case",
+    ];
+
+    pub struct Lexer {
+        content: String,
+        buffer: String,
+        line: usize,
+        character: usize,
+        start: usize,
+        char_start: usize,
+    }
+
+    impl Lexer {
+        pub fn new(content: String) -> Self {
+            Self {
+                content,
+                buffer: String::new(),
+                line: 0,
+                character: 0,
+                start: 0,
+                char_start: 0,
+            }
+        }
+
+        pub fn tokenize(&mut self) -> Vec<TokenType> {
+            let mut tokens = vec![];
+            let chars: Vec<char> = self.content.chars().collect();
+            self.tokenize_range(&chars, 0, chars.len(), &mut tokens);
+            tokens
+        }
+
+        fn tokenize_range(
+            &mut self,
+            chars: &[char],
+            from: usize,
+            to: usize,
+            tokens: &mut Vec<TokenType>,
+        ) {
+            let chars = &chars[..to];
+            let mut index = from;
+            while index < chars.len() {
+                let c = chars[index];
+                index = match c {
+                    ' ' | '\t' | '\r' | '\n' => {
+                        self.push_range(chars, index, index + 1, tokens, |b| lexer_whitespace!(b))
+                    }
+                    '#' => {
+                        let len = run_length(chars, index, |c| c != '\n');
+                        self.push_range(chars, index, index + len, tokens, |b| lexer_comment!(b))
+                    }
+                    '"' | '\'' => self.tokenize_string(chars, index, 0, tokens),
+                    '0'..='9' => {
+                        let len = number_length(chars, index);
+                        self.push_range(chars, index, index + len, tokens, |b| lexer_literal!(b))
+                    }
+                    '.' if chars.get(index + 1).map_or(false, |c| c.is_ascii_digit()) => {
+                        let len = number_length(chars, index);
+                        self.push_range(chars, index, index + len, tokens, |b| lexer_literal!(b))
+                    }
+                    '(' | ')' | '[' | ']' | '{' | '}' | ',' | ':' | ';' | '.' => {
+                        self.push_range(chars, index, index + 1, tokens, |b| lexer_separator!(b))
+                    }
+                    _ if is_operator(c) => {
+                        let len = run_length(chars, index, is_operator);
+                        self.push_range(chars, index, index + len, tokens, |b| lexer_operator!(b))
+                    }
+                    _ if is_identifier_start(c) => {
+                        let len = run_length(chars, index, is_identifier_part);
+                        let prefix: String = chars[index..index + len].iter().collect();
+                        match chars.get(index + len) {
+                            Some('"') | Some('\'') if is_string_prefix(&prefix) => {
+                                self.tokenize_string(chars, index, len, tokens)
+                            }
+                            _ if KEYWORDS.contains(&prefix.as_str()) => {
+                                self.push_range(chars, index, index + len, tokens, |b| {
+                                    lexer_keyword!(b)
+                                })
+                            }
+                            _ => self.push_range(chars, index, index + len, tokens, |b| {
+                                lexer_identifier!(b)
+                            }),
+                        }
+                    }
+                    _ => self.push_range(chars, index, index + 1, tokens, |b| lexer_identifier!(b)),
+                };
+            }
+        }
+
+        /// Handles every string form, `prefix` is number of prefix characters
+        /// (`r`, `b`, `f`, ...) before opening quote.
+        fn tokenize_string(
+            &mut self,
+            chars: &[char],
+            from: usize,
+            prefix: usize,
+            tokens: &mut Vec<TokenType>,
+        ) -> usize {
+            let flags: String = chars[from..from + prefix]
+                .iter()
+                .collect::<String>()
+                .to_lowercase();
+            let quote_at = from + prefix;
+            let quote = chars[quote_at];
+            let triple = chars[quote_at..].starts_with(&[quote, quote, quote]);
+            let quote_len = if triple { 3 } else { 1 };
+            let format = flags.contains('f');
+
+            let mut segment = from;
+            let mut index = quote_at + quote_len;
+            while index < chars.len() {
+                match chars[index] {
+                    '\\' => index += 2,
+                    '\n' if !triple => {
+                        return self.push_range(chars, segment, index, tokens, |b| lexer_error!(b));
+                    }
+                    '{' if format && chars.get(index + 1) == Some(&'{') => index += 2,
+                    '}' if format && chars.get(index + 1) == Some(&'}') => index += 2,
+                    '{' if format => match matching_brace(chars, index) {
+                        Some(end) => {
+                            self.push_range(chars, segment, index, tokens, |b| lexer_string!(b));
+                            self.push_range(chars, index, index + 1, tokens, |b| {
+                                lexer_separator!(b)
+                            });
+                            self.tokenize_range(chars, index + 1, end, tokens);
+                            self.push_range(chars, end, end + 1, tokens, |b| lexer_separator!(b));
+                            segment = end + 1;
+                            index = end + 1;
+                        }
+                        None => index += 1,
+                    },
+                    c if c == quote && (!triple || chars[index..].starts_with(&[c, c, c])) => {
+                        let end = index + quote_len;
+                        return self.push_range(chars, segment, end, tokens, |b| lexer_string!(b));
+                    }
+                    _ => index += 1,
+                }
+            }
+            let end = chars.len().min(index);
+            self.push_range(chars, segment.min(end), end, tokens, |b| lexer_error!(b))
+        }
+
+        fn push_range<F>(
+            &mut self,
+            chars: &[char],
+            from: usize,
+            to: usize,
+            tokens: &mut Vec<TokenType>,
+            builder: F,
+        ) -> usize
+        where
+            F: Fn(&Lexer) -> TokenType,
+        {
+            if from < to {
+                self.buffer = chars[from..to].iter().collect();
+                tokens.push(builder(&self));
+                self.clear();
+            }
+            to
+        }
+
+        fn clear(&mut self) {
+            for c in self.buffer.chars() {
+                if c == '\n' {
+                    self.line += 1;
+                    self.character = 0;
+                } else {
+                    self.character += 1;
+                }
+            }
+            self.start += self.buffer.len();
+            self.char_start += self.buffer.chars().count();
+            self.buffer.clear();
+        }
+    }
+
+    fn run_length<F>(chars: &[char], from: usize, f: F) -> usize
+    where
+        F: Fn(char) -> bool,
+    {
+        chars[from..].iter().take_while(|c| f(**c)).count()
+    }
+
+    fn matching_brace(chars: &[char], from: usize) -> Option<usize> {
+        let mut depth = 0;
+        for (index, c) in chars.iter().enumerate().skip(from) {
+            match c {
+                '{' => depth += 1,
+                '}' if depth == 1 => return Some(index),
+                '}' => depth -= 1,
+                '\n' => return None,
+                _ => (),
+            }
+        }
+        None
+    }
+
+    fn is_identifier_start(c: char) -> bool {
+        c.is_alphabetic() || c == '_'
+    }
+
+    fn is_identifier_part(c: char) -> bool {
+        c.is_alphanumeric() || c == '_'
+    }
+
+    fn is_operator(c: char) -> bool {
+        "+-*/%@=<>!&|^~".contains(c)
+    }
+
+    fn is_string_prefix(prefix: &str) -> bool {
+        match prefix.to_lowercase().as_str() {
+            "r" | "u" | "b" | "f" | "br" | "rb" | "fr" | "rf" => true,
+            _ => false,
+        }
+    }
+
+    fn number_length(chars: &[char], from: usize) -> usize {
+        let radix = match (chars.get(from), chars.get(from + 1)) {
+            (Some('0'), Some('x')) | (Some('0'), Some('X')) => 16,
+            (Some('0'), Some('o')) | (Some('0'), Some('O')) => 8,
+            (Some('0'), Some('b')) | (Some('0'), Some('B')) => 2,
+            _ => 10,
+        };
+        if radix != 10 {
+            return 2 + run_length(chars, from + 2, |c| c.is_digit(radix) || c == '_');
+        }
+        let mut index = from + run_length(chars, from, |c| c.is_ascii_digit() || c == '_');
+        if chars.get(index) == Some(&'.') {
+            index += 1 + run_length(chars, index + 1, |c| c.is_ascii_digit() || c == '_');
+        }
+        if let Some('e') | Some('E') = chars.get(index) {
+            let mut exponent = index + 1;
+            if let Some('+') | Some('-') = chars.get(exponent) {
+                exponent += 1;
+            }
+            let digits = run_length(chars, exponent, |c| c.is_ascii_digit());
+            if digits > 0 {
+                index = exponent + digits;
+            }
+        }
+        if let Some('j') | Some('J') = chars.get(index) {
+            index += 1;
+        }
+        index - from
+    }
+
+    impl TokenBuilder for Lexer {
+        fn text(&self) -> String {
+            self.buffer.to_string()
+        }
+
+        fn line(&self) -> usize {
+            self.line
+        }
+
+        fn character(&self) -> usize {
+            self.character
+        }
+
+        fn start(&self) -> usize {
+            self.start
+        }
+
+        fn end(&self, text: &String) -> usize {
+            self.start + text.len()
+        }
+
+        fn char_start(&self) -> usize {
+            self.char_start
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        struct BuilderMock {
+            line: usize,
+            character: usize,
+            start: usize,
+            char_start: usize,
+            text: String,
+        }
+
+        impl TokenBuilder for BuilderMock {
+            fn text(&self) -> String {
+                self.text.clone()
+            }
+
+            fn line(&self) -> usize {
+                self.line
+            }
+
+            fn character(&self) -> usize {
+                self.character
+            }
+
+            fn start(&self) -> usize {
+                self.start
+            }
+
+            fn end(&self, current_text: &String) -> usize {
+                self.start + current_text.len()
+            }
+
+            fn char_start(&self) -> usize {
+                self.char_start
+            }
+        }
+
+        macro_rules! builder {
+            ($text: expr, $line: expr, $character: expr, $start: expr) => {
+                BuilderMock {
+                    line: $line,
+                    character: $character,
+                    start: $start,
+                    char_start: $start,
+                    text: $text.to_owned(),
+                }
+            };
+        }
+
+        #[test]
+        fn parse_empty() {
+            let code = "".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_function() {
+            let code = "def a(b): # c".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_keyword!(builder!("def", 0, 0, 0)),
+                lexer_whitespace!(builder!(" ", 0, 3, 3)),
+                lexer_identifier!(builder!("a", 0, 4, 4)),
+                lexer_separator!(builder!("(", 0, 5, 5)),
+                lexer_identifier!(builder!("b", 0, 6, 6)),
+                lexer_separator!(builder!(")", 0, 7, 7)),
+                lexer_separator!(builder!(":", 0, 8, 8)),
+                lexer_whitespace!(builder!(" ", 0, 9, 9)),
+                lexer_comment!(builder!("# c", 0, 10, 10)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_numbers_and_operators() {
+            let code = "x **= 0x1f + 1.5e3j".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_identifier!(builder!("x", 0, 0, 0)),
+                lexer_whitespace!(builder!(" ", 0, 1, 1)),
+                lexer_operator!(builder!("**=", 0, 2, 2)),
+                lexer_whitespace!(builder!(" ", 0, 5, 5)),
+                lexer_literal!(builder!("0x1f", 0, 6, 6)),
+                lexer_whitespace!(builder!(" ", 0, 10, 10)),
+                lexer_operator!(builder!("+", 0, 11, 11)),
+                lexer_whitespace!(builder!(" ", 0, 12, 12)),
+                lexer_literal!(builder!("1.5e3j", 0, 13, 13)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_raw_string() {
+            let code = "r'\\d' b\"x\"".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_string!(builder!("r'\\d'", 0, 0, 0)),
+                lexer_whitespace!(builder!(" ", 0, 5, 5)),
+                lexer_string!(builder!("b\"x\"", 0, 6, 6)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_triple_quoted_string() {
+            let code = "\"\"\"a\n'b'\"\"\"".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![lexer_string!(builder!("\"\"\"a\n'b'\"\"\"", 0, 0, 0))];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_f_string() {
+            let code = "f'a{b + 1}c{{'".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_string!(builder!("f'a", 0, 0, 0)),
+                lexer_separator!(builder!("{", 0, 3, 3)),
+                lexer_identifier!(builder!("b", 0, 4, 4)),
+                lexer_whitespace!(builder!(" ", 0, 5, 5)),
+                lexer_operator!(builder!("+", 0, 6, 6)),
+                lexer_whitespace!(builder!(" ", 0, 7, 7)),
+                lexer_literal!(builder!("1", 0, 8, 8)),
+                lexer_separator!(builder!("}", 0, 9, 9)),
+                lexer_string!(builder!("c{{'", 0, 10, 10)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_unterminated_string() {
+            let code = "'a\nb".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_error!(builder!("'a", 0, 0, 0)),
+                lexer_whitespace!(builder!("\n", 0, 2, 2)),
+                lexer_identifier!(builder!("b", 1, 0, 3)),
+            ];
+            assert_eq!(result, expected)
+        }
+    }
+}
@@ -1,48 +1,44 @@
 pub mod lexer {
-    use crate::{Span, Token, TokenType};
+    use crate::*;
     use plex::lexer;
 
+    /// Full keyword list, highlighting uses its own subset
+    pub const KEYWORDS: &[&str] = &[
+        "as", "async", "await", "bool", "break", "const", "continue", "crate", "dyn", "else",
+        "enum", "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod",
+        "move", "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super",
+        "trait", "true", "type", "unsafe", "use", "usize", "where", "while",
+    ];
+
     lexer! {
-        fn next_token(text: 'a) -> (TokenType, &'a str);
+        fn next_token(text: 'a) -> (TokenKind, &'a str);
 
-        r"( +|\t+|\n)" => (TokenType::Whitespace {
-            token: Token::new(text.to_string(), 0, 0, 0, 0)
-        }, text),
+        r"( +|\t+|\r|\n)" => (TokenKind::Whitespace, text),
 
-        "(r\"|\")" => (TokenType::String {
-            token: Token::new(text.to_string(), 0, 0, 0, 0)
-        }, text),
+        "(r\"|\")" => (TokenKind::String, text),
 
-        r"([0-9]+|[0-9]+\.[0-9]+|'[^']')" => (TokenType::Literal {
-            token: Token::new(text.to_string(), 0, 0, 0, 0)
-        }, text),
+        r"([0-9]+|[0-9]+\.[0-9]+|'[^']')" => (TokenKind::Literal, text),
 
-        r"(->|[+-/*%=<>#])" => (TokenType::Operator {
-            token: Token::new(text.to_string(), 0, 0, 0, 0)
-        }, text),
+        r"(->|[+-/*%=<>#])" => (TokenKind::Operator, text),
 
-        r"(:|::|\{|\}|\[|\]|;|,|\)|\()" => (TokenType::Separator {
-            token: Token::new(text.to_string(), 0, 0, 0, 0)
-        }, text),
+        r"(:|::|\{|\}|\[|\]|;|,|\)|\()" => (TokenKind::Separator, text),
 
-        r"(let|fn|type|struct|trait|pub|impl|for|self|Self|mod|use|enum|(iu)(8|16|32)|usize|bool)" => (TokenType::Keyword {
-            token: Token::new(text.to_string(), 0, 0, 0, 0)
-        }, text),
+        r"(let|fn|type|struct|trait|pub|impl|for|self|Self|mod|use|enum|(iu)(8|16|32)|usize|bool)" => (TokenKind::Keyword, text),
 
-        r"[^0-9 \t\r\n:+-/*,';<>=%()\[\]{}][^ \t\r\n:+-/*,';<>=%()\[\]{}]*" => (TokenType::Identifier {
-            token: Token::new(text.to_string(), 0, 0, 0, 0)
-        }, text),
+        r"[^0-9 \t\r\n:+-/*,';<>=%()\[\]{}][^ \t\r\n:+-/*,';<>=%()\[\]{}]*" => (TokenKind::Identifier, text),
 
-        r"'[^0-9 \t\r\n:+-/*,';<>=%()\[\]{}][^ \t\r\n:+-/*,';<>=%()\[\]{}]*" => (TokenType::Identifier {
-            token: Token::new(text.to_string(), 0, 0, 0, 0)
-        }, text),
+        r"'[^0-9 \t\r\n:+-/*,';<>=%()\[\]{}][^ \t\r\n:+-/*,';<>=%()\[\]{}]*" => (TokenKind::Identifier, text),
+
+        r"." => (TokenKind::Error, text),
     }
 
     pub struct Lexer<'a> {
         original: &'a str,
         remaining: &'a str,
+        current: &'a str,
         line: usize,
         character: usize,
+        char_start: usize,
     }
 
     impl<'a> Lexer<'a> {
@@ -50,8 +46,10 @@ pub mod lexer {
             Self {
                 original: s,
                 remaining: s,
+                current: "",
                 line: 0,
                 character: 0,
+                char_start: 0,
             }
         }
     }
@@ -60,27 +58,21 @@ pub mod lexer {
         type Item = (TokenType, Span);
 
         fn next(&mut self) -> Option<(TokenType, Span)> {
-            let tok: (TokenType, &str) =
-                if let Some(((token_type, text), new_remaining)) = next_token(self.remaining) {
-                    self.remaining = new_remaining;
-                    (token_type, text)
+            let ((kind, text), remaining) = next_token(self.remaining)?;
+            self.remaining = remaining;
+            self.current = text;
+            let token = kind.build(self);
+            let span = token.span();
+            for c in text.chars() {
+                if c == '\n' {
+                    self.line += 1;
+                    self.character = 0;
                 } else {
-                    return None;
-                };
-            match tok {
-                (tok, text) => {
-                    let line = self.line;
-                    if tok.is_new_line() {
-                        self.line += 1;
-                        self.character = text.len();
-                    } else {
-                        self.character += text.len();
-                    }
-                    let span = self.span_in(text);
-                    let token = tok.move_to(line, self.character - text.len(), span.lo, span.hi);
-                    Some((token, span))
+                    self.character += 1;
                 }
+                self.char_start += 1;
             }
+            Some((token, span))
         }
     }
 
@@ -93,11 +85,37 @@ pub mod lexer {
             }
         }
     }
+
+    impl<'a> TokenBuilder for Lexer<'a> {
+        fn text(&self) -> String {
+            self.current.to_string()
+        }
+
+        fn line(&self) -> usize {
+            self.line
+        }
+
+        fn character(&self) -> usize {
+            self.character
+        }
+
+        fn start(&self) -> usize {
+            self.span_in(self.current).lo
+        }
+
+        fn end(&self, text: &String) -> usize {
+            self.start() + text.len()
+        }
+
+        fn char_start(&self) -> usize {
+            self.char_start
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{Token, TokenType};
+    use crate::{Span, Token, TokenType};
 
     use super::*;
 
@@ -107,7 +125,7 @@ mod tests {
         let lexer = lexer::Lexer::new(code);
         let result: Vec<TokenType> = lexer.map(|pair| pair.0).collect();
         let expected: Vec<TokenType> = vec![TokenType::Identifier {
-            token: Token::new("foo".to_string(), 0, 0, 0, 3),
+            token: Token::new("foo".to_string(), 0, 0, Span::new(0, 3), Span::new(0, 3)),
         }];
         assert_eq!(result, expected);
     }
@@ -122,8 +140,8 @@ mod tests {
                 "foobarhelloworldexamplecomtesttest".to_string(),
                 0,
                 0,
-                0,
-                34,
+                Span::new(0, 34),
+                Span::new(0, 34),
             ),
         }];
         assert_eq!(result, expected);
@@ -136,13 +154,13 @@ mod tests {
         let result: Vec<TokenType> = lexer.map(|pair| pair.0).collect();
         let expected: Vec<TokenType> = vec![
             TokenType::Identifier {
-                token: Token::new("foo".to_string(), 0, 0, 0, 3),
+                token: Token::new("foo".to_string(), 0, 0, Span::new(0, 3), Span::new(0, 3)),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 3, 3, 4),
+                token: Token::new(" ".to_string(), 0, 3, Span::new(3, 4), Span::new(3, 4)),
             },
             TokenType::Identifier {
-                token: Token::new("bar".to_string(), 0, 4, 4, 7),
+                token: Token::new("bar".to_string(), 0, 4, Span::new(4, 7), Span::new(4, 7)),
             },
         ];
         assert_eq!(result, expected);
@@ -155,13 +173,13 @@ mod tests {
         let result: Vec<TokenType> = lexer.map(|pair| pair.0).collect();
         let expected: Vec<TokenType> = vec![
             TokenType::Identifier {
-                token: Token::new("foo".to_string(), 0, 0, 0, 3),
+                token: Token::new("foo".to_string(), 0, 0, Span::new(0, 3), Span::new(0, 3)),
             },
             TokenType::Whitespace {
-                token: Token::new("      ".to_string(), 0, 3, 3, 9),
+                token: Token::new("      ".to_string(), 0, 3, Span::new(3, 9), Span::new(3, 9)),
             },
             TokenType::Identifier {
-                token: Token::new("bar".to_string(), 0, 9, 9, 12),
+                token: Token::new("bar".to_string(), 0, 9, Span::new(9, 12), Span::new(9, 12)),
             },
         ];
         assert_eq!(result, expected);
@@ -174,25 +192,37 @@ mod tests {
         let result: Vec<TokenType> = lexer.map(|pair| pair.0).collect();
         let expected: Vec<TokenType> = vec![
             TokenType::Identifier {
-                token: Token::new("foo".to_string(), 0, 0, 0, 3),
+                token: Token::new("foo".to_string(), 0, 0, Span::new(0, 3), Span::new(0, 3)),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 3, 3, 4),
+                token: Token::new(" ".to_string(), 0, 3, Span::new(3, 4), Span::new(3, 4)),
             },
             TokenType::Identifier {
-                token: Token::new("bar".to_string(), 0, 4, 4, 7),
+                token: Token::new("bar".to_string(), 0, 4, Span::new(4, 7), Span::new(4, 7)),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 7, 7, 8),
+                token: Token::new(" ".to_string(), 0, 7, Span::new(7, 8), Span::new(7, 8)),
             },
             TokenType::Identifier {
-                token: Token::new("hello".to_string(), 0, 8, 8, 13),
+                token: Token::new(
+                    "hello".to_string(),
+                    0,
+                    8,
+                    Span::new(8, 13),
+                    Span::new(8, 13),
+                ),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 13, 13, 14),
+                token: Token::new(" ".to_string(), 0, 13, Span::new(13, 14), Span::new(13, 14)),
             },
             TokenType::Identifier {
-                token: Token::new("world".to_string(), 0, 14, 14, 19),
+                token: Token::new(
+                    "world".to_string(),
+                    0,
+                    14,
+                    Span::new(14, 19),
+                    Span::new(14, 19),
+                ),
             },
         ];
         assert_eq!(result, expected);
@@ -205,13 +235,13 @@ mod tests {
         let result: Vec<TokenType> = lexer.map(|pair| pair.0).collect();
         let expected: Vec<TokenType> = vec![
             TokenType::Identifier {
-                token: Token::new("foo".to_string(), 0, 0, 0, 3),
+                token: Token::new("foo".to_string(), 0, 0, Span::new(0, 3), Span::new(0, 3)),
             },
             TokenType::Whitespace {
-                token: Token::new("\n".to_string(), 0, 0, 3, 4),
+                token: Token::new("\n".to_string(), 0, 3, Span::new(3, 4), Span::new(3, 4)),
             },
             TokenType::Identifier {
-                token: Token::new("bar".to_string(), 1, 1, 4, 7),
+                token: Token::new("bar".to_string(), 1, 0, Span::new(4, 7), Span::new(4, 7)),
             },
         ];
         assert_eq!(result, expected);
@@ -224,28 +254,28 @@ mod tests {
         let result: Vec<TokenType> = lexer.map(|pair| pair.0).collect();
         let expected: Vec<TokenType> = vec![
             TokenType::Keyword {
-                token: Token::new("let".to_string(), 0, 0, 0, 3),
+                token: Token::new("let".to_string(), 0, 0, Span::new(0, 3), Span::new(0, 3)),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 3, 3, 4),
+                token: Token::new(" ".to_string(), 0, 3, Span::new(3, 4), Span::new(3, 4)),
             },
             TokenType::Identifier {
-                token: Token::new("a".to_string(), 0, 4, 4, 5),
+                token: Token::new("a".to_string(), 0, 4, Span::new(4, 5), Span::new(4, 5)),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 5, 5, 6),
+                token: Token::new(" ".to_string(), 0, 5, Span::new(5, 6), Span::new(5, 6)),
             },
             TokenType::Operator {
-                token: Token::new("=".to_string(), 0, 6, 6, 7),
+                token: Token::new("=".to_string(), 0, 6, Span::new(6, 7), Span::new(6, 7)),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 7, 7, 8),
+                token: Token::new(" ".to_string(), 0, 7, Span::new(7, 8), Span::new(7, 8)),
             },
             TokenType::Literal {
-                token: Token::new("'b'".to_string(), 0, 8, 8, 11),
+                token: Token::new("'b'".to_string(), 0, 8, Span::new(8, 11), Span::new(8, 11)),
             },
             TokenType::Separator {
-                token: Token::new(";".to_string(), 0, 11, 11, 12),
+                token: Token::new(";".to_string(), 0, 11, Span::new(11, 12), Span::new(11, 12)),
             },
         ];
         assert_eq!(result, expected);
@@ -258,28 +288,28 @@ mod tests {
         let result: Vec<TokenType> = lexer.map(|pair| pair.0).collect();
         let expected: Vec<TokenType> = vec![
             TokenType::Keyword {
-                token: Token::new("let".to_string(), 0, 0, 0, 3),
+                token: Token::new("let".to_string(), 0, 0, Span::new(0, 3), Span::new(0, 3)),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 3, 3, 4),
+                token: Token::new(" ".to_string(), 0, 3, Span::new(3, 4), Span::new(3, 4)),
             },
             TokenType::Identifier {
-                token: Token::new("a".to_string(), 0, 4, 4, 5),
+                token: Token::new("a".to_string(), 0, 4, Span::new(4, 5), Span::new(4, 5)),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 5, 5, 6),
+                token: Token::new(" ".to_string(), 0, 5, Span::new(5, 6), Span::new(5, 6)),
             },
             TokenType::Operator {
-                token: Token::new("=".to_string(), 0, 6, 6, 7),
+                token: Token::new("=".to_string(), 0, 6, Span::new(6, 7), Span::new(6, 7)),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 7, 7, 8),
+                token: Token::new(" ".to_string(), 0, 7, Span::new(7, 8), Span::new(7, 8)),
             },
             TokenType::Literal {
-                token: Token::new("684".to_string(), 0, 8, 8, 11),
+                token: Token::new("684".to_string(), 0, 8, Span::new(8, 11), Span::new(8, 11)),
             },
             TokenType::Separator {
-                token: Token::new(";".to_string(), 0, 11, 11, 12),
+                token: Token::new(";".to_string(), 0, 11, Span::new(11, 12), Span::new(11, 12)),
             },
         ];
         assert_eq!(result, expected);
@@ -292,40 +322,46 @@ mod tests {
         let result: Vec<TokenType> = lexer.map(|pair| pair.0).collect();
         let expected: Vec<TokenType> = vec![
             TokenType::Keyword {
-                token: Token::new("let".to_string(), 0, 0, 0, 3),
+                token: Token::new("let".to_string(), 0, 0, Span::new(0, 3), Span::new(0, 3)),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 3, 3, 4),
+                token: Token::new(" ".to_string(), 0, 3, Span::new(3, 4), Span::new(3, 4)),
             },
             TokenType::Identifier {
-                token: Token::new("a".to_string(), 0, 4, 4, 5),
+                token: Token::new("a".to_string(), 0, 4, Span::new(4, 5), Span::new(4, 5)),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 5, 5, 6),
+                token: Token::new(" ".to_string(), 0, 5, Span::new(5, 6), Span::new(5, 6)),
             },
             TokenType::Operator {
-                token: Token::new("=".to_string(), 0, 6, 6, 7),
+                token: Token::new("=".to_string(), 0, 6, Span::new(6, 7), Span::new(6, 7)),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 7, 7, 8),
+                token: Token::new(" ".to_string(), 0, 7, Span::new(7, 8), Span::new(7, 8)),
             },
             TokenType::Identifier {
-                token: Token::new("b".to_string(), 0, 8, 8, 9),
+                token: Token::new("b".to_string(), 0, 8, Span::new(8, 9), Span::new(8, 9)),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 9, 9, 10),
+                token: Token::new(" ".to_string(), 0, 9, Span::new(9, 10), Span::new(9, 10)),
             },
             TokenType::Operator {
-                token: Token::new("+".to_string(), 0, 10, 10, 11),
+                token: Token::new("+".to_string(), 0, 10, Span::new(10, 11), Span::new(10, 11)),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 11, 11, 12),
+                token: Token::new(" ".to_string(), 0, 11, Span::new(11, 12), Span::new(11, 12)),
             },
             TokenType::Literal {
-                token: Token::new("684".to_string(), 0, 12, 12, 15),
+                token: Token::new(
+                    "684".to_string(),
+                    0,
+                    12,
+                    Span::new(12, 15),
+                    Span::new(12, 15),
+                ),
             },
             TokenType::Separator {
-                token: Token::new(";".to_string(), 0, 15, 15, 16),
+                token: Token::new(";".to_string(), 0, 15, Span::new(15, 16), Span::new(15, 16)),
             },
         ];
         assert_eq!(result, expected);
@@ -340,97 +376,145 @@ mod tests {
         let result: Vec<TokenType> = lexer.map(|pair| pair.0).collect();
         let expected: Vec<TokenType> = vec![
             TokenType::Keyword {
-                token: Token::new("fn".to_string(), 0, 0, 0, 2),
+                token: Token::new("fn".to_string(), 0, 0, Span::new(0, 2), Span::new(0, 2)),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 2, 2, 3),
+                token: Token::new(" ".to_string(), 0, 2, Span::new(2, 3), Span::new(2, 3)),
             },
             TokenType::Identifier {
-                token: Token::new("foo".to_string(), 0, 3, 3, 6),
+                token: Token::new("foo".to_string(), 0, 3, Span::new(3, 6), Span::new(3, 6)),
             },
             TokenType::Separator {
-                token: Token::new("(".to_string(), 0, 6, 6, 7),
+                token: Token::new("(".to_string(), 0, 6, Span::new(6, 7), Span::new(6, 7)),
             },
             TokenType::Identifier {
-                token: Token::new("a".to_string(), 0, 7, 7, 8),
+                token: Token::new("a".to_string(), 0, 7, Span::new(7, 8), Span::new(7, 8)),
             },
             TokenType::Separator {
-                token: Token::new(":".to_string(), 0, 8, 8, 9),
+                token: Token::new(":".to_string(), 0, 8, Span::new(8, 9), Span::new(8, 9)),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 9, 9, 10),
+                token: Token::new(" ".to_string(), 0, 9, Span::new(9, 10), Span::new(9, 10)),
             },
             TokenType::Identifier {
-                token: Token::new("i32".to_string(), 0, 10, 10, 13),
+                token: Token::new(
+                    "i32".to_string(),
+                    0,
+                    10,
+                    Span::new(10, 13),
+                    Span::new(10, 13),
+                ),
             },
             TokenType::Operator {
-                token: Token::new(",".to_string(), 0, 13, 13, 14),
+                token: Token::new(",".to_string(), 0, 13, Span::new(13, 14), Span::new(13, 14)),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 14, 14, 15),
+                token: Token::new(" ".to_string(), 0, 14, Span::new(14, 15), Span::new(14, 15)),
             },
             TokenType::Identifier {
-                token: Token::new("b".to_string(), 0, 15, 15, 16),
+                token: Token::new("b".to_string(), 0, 15, Span::new(15, 16), Span::new(15, 16)),
             },
             TokenType::Separator {
-                token: Token::new(":".to_string(), 0, 16, 16, 17),
+                token: Token::new(":".to_string(), 0, 16, Span::new(16, 17), Span::new(16, 17)),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 17, 17, 18),
+                token: Token::new(" ".to_string(), 0, 17, Span::new(17, 18), Span::new(17, 18)),
             },
             TokenType::Identifier {
-                token: Token::new("i32".to_string(), 0, 18, 18, 21),
+                token: Token::new(
+                    "i32".to_string(),
+                    0,
+                    18,
+                    Span::new(18, 21),
+                    Span::new(18, 21),
+                ),
             },
             TokenType::Separator {
-                token: Token::new(")".to_string(), 0, 21, 21, 22),
+                token: Token::new(")".to_string(), 0, 21, Span::new(21, 22), Span::new(21, 22)),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 22, 22, 23),
+                token: Token::new(" ".to_string(), 0, 22, Span::new(22, 23), Span::new(22, 23)),
             },
             TokenType::Operator {
-                token: Token::new("->".to_string(), 0, 23, 23, 25),
+                token: Token::new(
+                    "->".to_string(),
+                    0,
+                    23,
+                    Span::new(23, 25),
+                    Span::new(23, 25),
+                ),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 25, 25, 26),
+                token: Token::new(" ".to_string(), 0, 25, Span::new(25, 26), Span::new(25, 26)),
             },
             TokenType::Identifier {
-                token: Token::new("int".to_string(), 0, 26, 26, 29),
+                token: Token::new(
+                    "int".to_string(),
+                    0,
+                    26,
+                    Span::new(26, 29),
+                    Span::new(26, 29),
+                ),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 29, 29, 30),
+                token: Token::new(" ".to_string(), 0, 29, Span::new(29, 30), Span::new(29, 30)),
             },
             TokenType::Separator {
-                token: Token::new("{".to_string(), 0, 30, 30, 31),
+                token: Token::new("{".to_string(), 0, 30, Span::new(30, 31), Span::new(30, 31)),
             },
             TokenType::Whitespace {
-                token: Token::new("\n".to_string(), 0, 0, 31, 32),
+                token: Token::new(
+                    "\n".to_string(),
+                    0,
+                    31,
+                    Span::new(31, 32),
+                    Span::new(31, 32),
+                ),
             },
             TokenType::Whitespace {
-                token: Token::new("            ".to_string(), 1, 1, 32, 44),
+                token: Token::new(
+                    "            ".to_string(),
+                    1,
+                    0,
+                    Span::new(32, 44),
+                    Span::new(32, 44),
+                ),
             },
             TokenType::Identifier {
-                token: Token::new("a".to_string(), 1, 13, 44, 45),
+                token: Token::new("a".to_string(), 1, 12, Span::new(44, 45), Span::new(44, 45)),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 1, 14, 45, 46),
+                token: Token::new(" ".to_string(), 1, 13, Span::new(45, 46), Span::new(45, 46)),
             },
             TokenType::Operator {
-                token: Token::new("%".to_string(), 1, 15, 46, 47),
+                token: Token::new("%".to_string(), 1, 14, Span::new(46, 47), Span::new(46, 47)),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 1, 16, 47, 48),
+                token: Token::new(" ".to_string(), 1, 15, Span::new(47, 48), Span::new(47, 48)),
             },
             TokenType::Identifier {
-                token: Token::new("b".to_string(), 1, 17, 48, 49),
+                token: Token::new("b".to_string(), 1, 16, Span::new(48, 49), Span::new(48, 49)),
             },
             TokenType::Whitespace {
-                token: Token::new("\n".to_string(), 1, 0, 49, 50),
+                token: Token::new(
+                    "\n".to_string(),
+                    1,
+                    17,
+                    Span::new(49, 50),
+                    Span::new(49, 50),
+                ),
             },
             TokenType::Whitespace {
-                token: Token::new("        ".to_string(), 2, 1, 50, 58),
+                token: Token::new(
+                    "        ".to_string(),
+                    2,
+                    0,
+                    Span::new(50, 58),
+                    Span::new(50, 58),
+                ),
             },
             TokenType::Separator {
-                token: Token::new("}".to_string(), 2, 9, 58, 59),
+                token: Token::new("}".to_string(), 2, 8, Span::new(58, 59), Span::new(58, 59)),
             },
         ];
         assert_eq!(result, expected);
@@ -442,36 +526,59 @@ mod tests {
         let result: Vec<TokenType> = lexer::Lexer::new(buffer).map(|p| p.0).collect();
         let expected: Vec<TokenType> = vec![
             TokenType::Operator {
-                token: Token::new("#".to_string(), 0, 0, 0, 1),
+                token: Token::new("#".to_string(), 0, 0, Span::new(0, 1), Span::new(0, 1)),
             },
             TokenType::Separator {
-                token: Token::new("[".to_string(), 0, 1, 1, 2),
+                token: Token::new("[".to_string(), 0, 1, Span::new(1, 2), Span::new(1, 2)),
             },
             TokenType::Identifier {
-                token: Token::new("derive".to_string(), 0, 2, 2, 8),
+                token: Token::new("derive".to_string(), 0, 2, Span::new(2, 8), Span::new(2, 8)),
             },
             TokenType::Separator {
-                token: Token::new("(".to_string(), 0, 8, 8, 9),
+                token: Token::new("(".to_string(), 0, 8, Span::new(8, 9), Span::new(8, 9)),
             },
             TokenType::Identifier {
-                token: Token::new("Debug".to_string(), 0, 9, 9, 14),
+                token: Token::new(
+                    "Debug".to_string(),
+                    0,
+                    9,
+                    Span::new(9, 14),
+                    Span::new(9, 14),
+                ),
             },
             TokenType::Operator {
-                token: Token::new(",".to_string(), 0, 14, 14, 15),
+                token: Token::new(",".to_string(), 0, 14, Span::new(14, 15), Span::new(14, 15)),
             },
             TokenType::Whitespace {
-                token: Token::new(" ".to_string(), 0, 15, 15, 16),
+                token: Token::new(" ".to_string(), 0, 15, Span::new(15, 16), Span::new(15, 16)),
             },
             TokenType::Identifier {
-                token: Token::new("Clone".to_string(), 0, 16, 16, 21),
+                token: Token::new(
+                    "Clone".to_string(),
+                    0,
+                    16,
+                    Span::new(16, 21),
+                    Span::new(16, 21),
+                ),
             },
             TokenType::Separator {
-                token: Token::new(")".to_string(), 0, 21, 21, 22),
+                token: Token::new(")".to_string(), 0, 21, Span::new(21, 22), Span::new(21, 22)),
             },
             TokenType::Separator {
-                token: Token::new("]".to_string(), 0, 22, 22, 23),
+                token: Token::new("]".to_string(), 0, 22, Span::new(22, 23), Span::new(22, 23)),
             },
         ];
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn must_parse_unknown_character_as_error() {
+        let code = "'";
+        let lexer = lexer::Lexer::new(code);
+        let result: Vec<TokenType> = lexer.map(|pair| pair.0).collect();
+        let expected: Vec<TokenType> = vec![TokenType::Error {
+            token: Token::new("'".to_string(), 0, 0, Span::new(0, 1), Span::new(0, 1)),
+        }];
+        assert_eq!(result, expected);
+    }
 }
@@ -0,0 +1,539 @@
+pub mod lexer {
+    use crate::*;
+
+    pub const KEYWORDS: &[&str] = &[
+        "alias", "break", "case", "continue", "declare", "do", "done", "elif", "else", "esac",
+        "eval", "exec", "exit", "export", "fi", "for", "function", "if", "in", "local", "readonly",
+        "return", "select", "set", "shift", "source", "then", "time", "trap", "unset", "until",
+        "while",
+    ];
+
+    struct Heredoc {
+        delimiter: String,
+        strip_tabs: bool,
+    }
+
+    pub struct Lexer {
+        content: String,
+        buffer: String,
+        line: usize,
+        character: usize,
+        start: usize,
+        char_start: usize,
+        heredocs: Vec<Heredoc>,
+    }
+
+    impl Lexer {
+        pub fn new(content: String) -> Self {
+            Self {
+                content,
+                buffer: String::new(),
+                line: 0,
+                character: 0,
+                start: 0,
+                char_start: 0,
+                heredocs: vec![],
+            }
+        }
+
+        pub fn tokenize(&mut self) -> Vec<TokenType> {
+            let mut tokens = vec![];
+            let chars: Vec<char> = self.content.chars().collect();
+            self.tokenize_range(&chars, 0, chars.len(), &mut tokens);
+            tokens
+        }
+
+        fn tokenize_range(
+            &mut self,
+            chars: &[char],
+            from: usize,
+            to: usize,
+            tokens: &mut Vec<TokenType>,
+        ) {
+            let chars = &chars[..to];
+            let mut index = from;
+            while index < chars.len() {
+                let c = chars[index];
+                let next = chars.get(index + 1).cloned();
+                let word_start = index == from || is_word_break(chars[index - 1]);
+                index = match c {
+                    ' ' | '\t' | '\r' => {
+                        self.push_range(chars, index, index + 1, tokens, |b| lexer_whitespace!(b))
+                    }
+                    '\n' => {
+                        let index = self
+                            .push_range(chars, index, index + 1, tokens, |b| lexer_whitespace!(b));
+                        self.tokenize_heredocs(chars, index, tokens)
+                    }
+                    '#' if word_start => {
+                        let len = run_length(chars, index, |c| c != '\n');
+                        self.push_range(chars, index, index + len, tokens, |b| lexer_comment!(b))
+                    }
+                    '\'' => match chars[index + 1..].iter().position(|c| *c == '\'') {
+                        Some(len) => self.push_range(chars, index, index + len + 2, tokens, |b| {
+                            lexer_string!(b)
+                        }),
+                        None => {
+                            self.push_range(chars, index, chars.len(), tokens, |b| lexer_error!(b))
+                        }
+                    },
+                    '"' => self.tokenize_double_quoted(chars, index, tokens),
+                    '$' => self.tokenize_variable(chars, index, tokens),
+                    '`' => match chars[index + 1..].iter().position(|c| *c == '`') {
+                        Some(len) => {
+                            let end = index + 1 + len;
+                            self.push_range(chars, index, index + 1, tokens, |b| {
+                                lexer_separator!(b)
+                            });
+                            self.tokenize_range(chars, index + 1, end, tokens);
+                            self.push_range(chars, end, end + 1, tokens, |b| lexer_separator!(b))
+                        }
+                        None => {
+                            self.push_range(chars, index, chars.len(), tokens, |b| lexer_error!(b))
+                        }
+                    },
+                    '<' if next == Some('<') && chars.get(index + 2) != Some(&'<') => {
+                        self.tokenize_heredoc_start(chars, index, tokens)
+                    }
+                    ';' => {
+                        let len = if next == Some(';') { 2 } else { 1 };
+                        self.push_range(chars, index, index + len, tokens, |b| lexer_separator!(b))
+                    }
+                    '(' | ')' => {
+                        self.push_range(chars, index, index + 1, tokens, |b| lexer_separator!(b))
+                    }
+                    '|' | '&' | '<' | '>' => {
+                        let len = run_length(chars, index, |c| "|&<>".contains(c));
+                        self.push_range(chars, index, index + len, tokens, |b| lexer_operator!(b))
+                    }
+                    _ => self.tokenize_word(chars, index, tokens),
+                };
+            }
+        }
+
+        fn tokenize_word(
+            &mut self,
+            chars: &[char],
+            from: usize,
+            tokens: &mut Vec<TokenType>,
+        ) -> usize {
+            let mut end = from;
+            while end < chars.len() && !is_word_end(chars[end]) {
+                if chars[end] == '\\' {
+                    end += 1;
+                } else if chars[end] == '=' && end > from && is_name(&chars[from..end]) {
+                    self.push_range(chars, from, end, tokens, |b| lexer_identifier!(b));
+                    return self.push_range(chars, end, end + 1, tokens, |b| lexer_operator!(b));
+                }
+                end += 1;
+            }
+            let end = end.min(chars.len()).max(from + 1);
+            let word: String = chars[from..end].iter().collect();
+            match word.as_str() {
+                "[" | "]" | "[[" | "]]" | "{" | "}" => {
+                    self.push_range(chars, from, end, tokens, |b| lexer_separator!(b))
+                }
+                "!" | "=" | "==" | "!=" => {
+                    self.push_range(chars, from, end, tokens, |b| lexer_operator!(b))
+                }
+                _ if KEYWORDS.contains(&word.as_str()) => {
+                    self.push_range(chars, from, end, tokens, |b| lexer_keyword!(b))
+                }
+                _ if word.chars().all(|c| c.is_ascii_digit()) => {
+                    self.push_range(chars, from, end, tokens, |b| lexer_literal!(b))
+                }
+                _ => self.push_range(chars, from, end, tokens, |b| lexer_identifier!(b)),
+            }
+        }
+
+        fn tokenize_double_quoted(
+            &mut self,
+            chars: &[char],
+            from: usize,
+            tokens: &mut Vec<TokenType>,
+        ) -> usize {
+            let mut segment = from;
+            let mut index = from + 1;
+            while index < chars.len() {
+                match chars[index] {
+                    '\\' => index += 2,
+                    '"' => {
+                        return self
+                            .push_range(chars, segment, index + 1, tokens, |b| lexer_string!(b));
+                    }
+                    '$' if chars
+                        .get(index + 1)
+                        .map_or(false, |c| is_variable_start(*c)) =>
+                    {
+                        self.push_range(chars, segment, index, tokens, |b| lexer_string!(b));
+                        index = self.tokenize_variable(chars, index, tokens);
+                        segment = index;
+                    }
+                    _ => index += 1,
+                }
+            }
+            self.push_range(chars, segment, chars.len(), tokens, |b| lexer_error!(b))
+        }
+
+        fn tokenize_variable(
+            &mut self,
+            chars: &[char],
+            from: usize,
+            tokens: &mut Vec<TokenType>,
+        ) -> usize {
+            match chars.get(from + 1) {
+                Some('(') => match matching(chars, from + 1, '(', ')') {
+                    Some(end) => {
+                        self.push_range(chars, from, from + 2, tokens, |b| lexer_separator!(b));
+                        self.tokenize_range(chars, from + 2, end, tokens);
+                        self.push_range(chars, end, end + 1, tokens, |b| lexer_separator!(b))
+                    }
+                    None => self.push_range(chars, from, chars.len(), tokens, |b| lexer_error!(b)),
+                },
+                Some('{') => match matching(chars, from + 1, '{', '}') {
+                    Some(end) => {
+                        self.push_range(chars, from, end + 1, tokens, |b| lexer_literal!(b))
+                    }
+                    None => self.push_range(chars, from, chars.len(), tokens, |b| lexer_error!(b)),
+                },
+                Some(c) if c.is_alphabetic() || *c == '_' => {
+                    let len = run_length(chars, from + 1, |c| c.is_alphanumeric() || c == '_');
+                    self.push_range(chars, from, from + 1 + len, tokens, |b| lexer_literal!(b))
+                }
+                Some(c) if c.is_ascii_digit() || "@*#?$!-".contains(*c) => {
+                    self.push_range(chars, from, from + 2, tokens, |b| lexer_literal!(b))
+                }
+                _ => self.push_range(chars, from, from + 1, tokens, |b| lexer_identifier!(b)),
+            }
+        }
+
+        fn tokenize_heredoc_start(
+            &mut self,
+            chars: &[char],
+            from: usize,
+            tokens: &mut Vec<TokenType>,
+        ) -> usize {
+            let strip_tabs = chars.get(from + 2) == Some(&'-');
+            let mut index = self.push_range(
+                chars,
+                from,
+                from + if strip_tabs { 3 } else { 2 },
+                tokens,
+                |b| lexer_operator!(b),
+            );
+            while index < chars.len() && (chars[index] == ' ' || chars[index] == '\t') {
+                index = self.push_range(chars, index, index + 1, tokens, |b| lexer_whitespace!(b));
+            }
+            let len = run_length(chars, index, |c| !is_word_end(c) || c == '\'' || c == '"');
+            if len == 0 {
+                return index;
+            }
+            let delimiter: String = chars[index..index + len]
+                .iter()
+                .filter(|c| **c != '\'' && **c != '"' && **c != '\\')
+                .collect();
+            self.heredocs.push(Heredoc {
+                delimiter,
+                strip_tabs,
+            });
+            self.push_range(chars, index, index + len, tokens, |b| lexer_keyword!(b))
+        }
+
+        fn tokenize_heredocs(
+            &mut self,
+            chars: &[char],
+            from: usize,
+            tokens: &mut Vec<TokenType>,
+        ) -> usize {
+            let mut index = from;
+            let heredocs: Vec<Heredoc> = self.heredocs.drain(..).collect();
+            for heredoc in heredocs {
+                while index < chars.len() {
+                    let len = run_length(chars, index, |c| c != '\n');
+                    let tabs = if heredoc.strip_tabs {
+                        run_length(chars, index, |c| c == '\t').min(len)
+                    } else {
+                        0
+                    };
+                    let line: String = chars[index + tabs..index + len].iter().collect();
+                    let finished = line == heredoc.delimiter;
+                    index = self
+                        .push_range(chars, index, index + tabs, tokens, |b| lexer_whitespace!(b));
+                    index = if finished {
+                        self.push_range(chars, index, index + len - tabs, tokens, |b| {
+                            lexer_keyword!(b)
+                        })
+                    } else {
+                        self.push_range(chars, index, index + len - tabs, tokens, |b| {
+                            lexer_string!(b)
+                        })
+                    };
+                    index =
+                        self.push_range(chars, index, (index + 1).min(chars.len()), tokens, |b| {
+                            lexer_whitespace!(b)
+                        });
+                    if finished {
+                        break;
+                    }
+                }
+            }
+            index
+        }
+
+        fn push_range<F>(
+            &mut self,
+            chars: &[char],
+            from: usize,
+            to: usize,
+            tokens: &mut Vec<TokenType>,
+            builder: F,
+        ) -> usize
+        where
+            F: Fn(&Lexer) -> TokenType,
+        {
+            if from < to {
+                self.buffer = chars[from..to].iter().collect();
+                tokens.push(builder(&self));
+                self.clear();
+            }
+            to
+        }
+
+        fn clear(&mut self) {
+            for c in self.buffer.chars() {
+                if c == '\n' {
+                    self.line += 1;
+                    self.character = 0;
+                } else {
+                    self.character += 1;
+                }
+            }
+            self.start += self.buffer.len();
+            self.char_start += self.buffer.chars().count();
+            self.buffer.clear();
+        }
+    }
+
+    fn run_length<F>(chars: &[char], from: usize, f: F) -> usize
+    where
+        F: Fn(char) -> bool,
+    {
+        chars[from..].iter().take_while(|c| f(**c)).count()
+    }
+
+    /// Finds closing character, skips quoted parts
+    fn matching(chars: &[char], from: usize, open: char, close: char) -> Option<usize> {
+        let mut depth = 0;
+        let mut quote: Option<char> = None;
+        let mut index = from;
+        while index < chars.len() {
+            let c = chars[index];
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) if c == '\\' => index += 1,
+                Some(_) => (),
+                None if c == '\\' => index += 1,
+                None if c == '\'' || c == '"' => quote = Some(c),
+                None if c == open => depth += 1,
+                None if c == close && depth == 1 => return Some(index),
+                None if c == close => depth -= 1,
+                None => (),
+            }
+            index += 1;
+        }
+        None
+    }
+
+    fn is_word_break(c: char) -> bool {
+        c.is_whitespace() || ";|&()".contains(c)
+    }
+
+    fn is_word_end(c: char) -> bool {
+        c.is_whitespace() || ";|&<>()'\"`$".contains(c)
+    }
+
+    fn is_variable_start(c: char) -> bool {
+        c.is_alphanumeric() || "_{(@*#?$!-".contains(c)
+    }
+
+    fn is_name(chars: &[char]) -> bool {
+        chars
+            .first()
+            .map_or(false, |c| c.is_alphabetic() || *c == '_')
+            && chars.iter().all(|c| c.is_alphanumeric() || *c == '_')
+    }
+
+    impl TokenBuilder for Lexer {
+        fn text(&self) -> String {
+            self.buffer.to_string()
+        }
+
+        fn line(&self) -> usize {
+            self.line
+        }
+
+        fn character(&self) -> usize {
+            self.character
+        }
+
+        fn start(&self) -> usize {
+            self.start
+        }
+
+        fn end(&self, text: &String) -> usize {
+            self.start + text.len()
+        }
+
+        fn char_start(&self) -> usize {
+            self.char_start
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        struct BuilderMock {
+            line: usize,
+            character: usize,
+            start: usize,
+            char_start: usize,
+            text: String,
+        }
+
+        impl TokenBuilder for BuilderMock {
+            fn text(&self) -> String {
+                self.text.clone()
+            }
+
+            fn line(&self) -> usize {
+                self.line
+            }
+
+            fn character(&self) -> usize {
+                self.character
+            }
+
+            fn start(&self) -> usize {
+                self.start
+            }
+
+            fn end(&self, current_text: &String) -> usize {
+                self.start + current_text.len()
+            }
+
+            fn char_start(&self) -> usize {
+                self.char_start
+            }
+        }
+
+        macro_rules! builder {
+            ($text: expr, $line: expr, $character: expr, $start: expr) => {
+                BuilderMock {
+                    line: $line,
+                    character: $character,
+                    start: $start,
+                    char_start: $start,
+                    text: $text.to_owned(),
+                }
+            };
+        }
+
+        #[test]
+        fn parse_empty() {
+            let code = "".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_assignment() {
+            let code = "A=1 # c".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_identifier!(builder!("A", 0, 0, 0)),
+                lexer_operator!(builder!("=", 0, 1, 1)),
+                lexer_literal!(builder!("1", 0, 2, 2)),
+                lexer_whitespace!(builder!(" ", 0, 3, 3)),
+                lexer_comment!(builder!("# c", 0, 4, 4)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_quoting() {
+            let code = "echo \"a $B\" 'c $d'".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_identifier!(builder!("echo", 0, 0, 0)),
+                lexer_whitespace!(builder!(" ", 0, 4, 4)),
+                lexer_string!(builder!("\"a ", 0, 5, 5)),
+                lexer_literal!(builder!("$B", 0, 8, 8)),
+                lexer_string!(builder!("\"", 0, 10, 10)),
+                lexer_whitespace!(builder!(" ", 0, 11, 11)),
+                lexer_string!(builder!("'c $d'", 0, 12, 12)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_command_substitution() {
+            let code = "if [ $(id -u) ]; then".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_keyword!(builder!("if", 0, 0, 0)),
+                lexer_whitespace!(builder!(" ", 0, 2, 2)),
+                lexer_separator!(builder!("[", 0, 3, 3)),
+                lexer_whitespace!(builder!(" ", 0, 4, 4)),
+                lexer_separator!(builder!("$(", 0, 5, 5)),
+                lexer_identifier!(builder!("id", 0, 7, 7)),
+                lexer_whitespace!(builder!(" ", 0, 9, 9)),
+                lexer_identifier!(builder!("-u", 0, 10, 10)),
+                lexer_separator!(builder!(")", 0, 12, 12)),
+                lexer_whitespace!(builder!(" ", 0, 13, 13)),
+                lexer_separator!(builder!("]", 0, 14, 14)),
+                lexer_separator!(builder!(";", 0, 15, 15)),
+                lexer_whitespace!(builder!(" ", 0, 16, 16)),
+                lexer_keyword!(builder!("then", 0, 17, 17)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_heredoc() {
+            let code = "cat <<'EOF'\n$a\nEOF\n".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_identifier!(builder!("cat", 0, 0, 0)),
+                lexer_whitespace!(builder!(" ", 0, 3, 3)),
+                lexer_operator!(builder!("<<", 0, 4, 4)),
+                lexer_keyword!(builder!("'EOF'", 0, 6, 6)),
+                lexer_whitespace!(builder!("\n", 0, 11, 11)),
+                lexer_string!(builder!("$a", 1, 0, 12)),
+                lexer_whitespace!(builder!("\n", 1, 2, 14)),
+                lexer_keyword!(builder!("EOF", 2, 0, 15)),
+                lexer_whitespace!(builder!("\n", 2, 3, 18)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_unterminated_string() {
+            let code = "echo 'a".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_identifier!(builder!("echo", 0, 0, 0)),
+                lexer_whitespace!(builder!(" ", 0, 4, 4)),
+                lexer_error!(builder!("'a", 0, 5, 5)),
+            ];
+            assert_eq!(result, expected)
+        }
+    }
+}
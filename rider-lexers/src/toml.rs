@@ -52,6 +52,7 @@ pub mod lexer {
         line: usize,
         character: usize,
         start: usize,
+        char_start: usize,
     }
 
     impl Lexer {
@@ -61,6 +62,7 @@ pub mod lexer {
                 line: 0,
                 character: 0,
                 start: 0,
+                char_start: 0,
                 buffer: Buffer::new(),
             }
         }
@@ -130,15 +132,16 @@ pub mod lexer {
         }
 
         fn clear(&mut self) {
-            if self.buffer.contains('\n') {
-                self.line += self.buffer.lines().count();
-                self.character +=
-                    self.buffer.len() - self.buffer.rfind('\n').unwrap_or(self.buffer.len());
-                self.start += self.buffer.len();
-            } else {
-                self.character += self.buffer.len();
-                self.start += self.buffer.len();
+            for c in self.buffer.chars() {
+                if c == '\n' {
+                    self.line += 1;
+                    self.character = 0;
+                } else {
+                    self.character += 1;
+                }
             }
+            self.start += self.buffer.len();
+            self.char_start += self.buffer.chars().count();
             self.buffer.clear();
         }
     }
@@ -171,6 +174,10 @@ pub mod lexer {
         fn end(&self, text: &String) -> usize {
             self.start + text.len()
         }
+
+        fn char_start(&self) -> usize {
+            self.char_start
+        }
     }
 
     #[cfg(test)]
@@ -181,6 +188,7 @@ pub mod lexer {
             line: usize,
             character: usize,
             start: usize,
+            char_start: usize,
             text: String,
         }
 
@@ -204,6 +212,10 @@ pub mod lexer {
             fn end(&self, current_text: &String) -> usize {
                 self.start + current_text.len()
             }
+
+            fn char_start(&self) -> usize {
+                self.char_start
+            }
         }
 
         macro_rules! builder {
@@ -212,6 +224,7 @@ pub mod lexer {
                     line: $line,
                     character: $character,
                     start: $start,
+                    char_start: $start,
                     text: $text.to_owned(),
                 }
             };
@@ -0,0 +1,630 @@
+pub mod lexer {
+    use crate::*;
+
+    pub struct Lexer {
+        content: String,
+        buffer: String,
+        line: usize,
+        character: usize,
+        start: usize,
+        char_start: usize,
+        indents: Vec<usize>,
+        expects_block: bool,
+        block_scalar: Option<usize>,
+        open_quote: Option<char>,
+        flow_depth: usize,
+    }
+
+    impl Lexer {
+        pub fn new(content: String) -> Self {
+            Self {
+                content,
+                buffer: String::new(),
+                line: 0,
+                character: 0,
+                start: 0,
+                char_start: 0,
+                indents: vec![0],
+                expects_block: false,
+                block_scalar: None,
+                open_quote: None,
+                flow_depth: 0,
+            }
+        }
+
+        pub fn tokenize(&mut self) -> Vec<TokenType> {
+            let mut tokens = vec![];
+            let content = self.content.clone();
+            for text in content.split_inclusive('\n') {
+                let chars: Vec<char> = text.trim_end_matches('\n').chars().collect();
+                self.tokenize_line(&chars, &mut tokens);
+                if text.ends_with('\n') {
+                    self.push_range(&['\n'], 0, 1, &mut tokens, |b| lexer_whitespace!(b));
+                }
+            }
+            tokens
+        }
+
+        fn tokenize_line(&mut self, chars: &[char], tokens: &mut Vec<TokenType>) {
+            let indent = run_length(chars, 0, |c| c == ' ' || c == '\t');
+            for index in 0..indent {
+                self.push_range(chars, index, index + 1, tokens, |b| lexer_whitespace!(b));
+            }
+            let rest = &chars[indent..];
+            if rest.iter().all(|c| c.is_whitespace()) {
+                self.push_range(chars, indent, chars.len(), tokens, |b| lexer_whitespace!(b));
+                return;
+            }
+
+            if let Some(quote) = self.open_quote {
+                let index = self.continue_quoted(chars, indent, quote, tokens);
+                self.tokenize_content(chars, index, tokens);
+                return;
+            }
+
+            if let Some(parent) = self.block_scalar {
+                if indent > parent {
+                    self.push_range(chars, indent, chars.len(), tokens, |b| lexer_string!(b));
+                    return;
+                }
+                self.block_scalar = None;
+            }
+
+            if rest[0] == '#' {
+                self.push_range(chars, indent, chars.len(), tokens, |b| lexer_comment!(b));
+                return;
+            }
+
+            if self.flow_depth == 0 && is_document_marker(rest) {
+                self.indents = vec![0];
+                self.expects_block = false;
+                let index =
+                    self.push_range(chars, indent, indent + 3, tokens, |b| lexer_separator!(b));
+                self.tokenize_content(chars, index, tokens);
+                return;
+            }
+
+            if self.flow_depth == 0 && !self.is_valid_indent(chars, indent) {
+                self.push_range(chars, indent, chars.len(), tokens, |b| lexer_error!(b));
+                return;
+            }
+
+            self.expects_block = false;
+            let mut index = indent;
+            while self.flow_depth == 0 && is_list_marker(chars, index) {
+                index = self.push_range(chars, index, index + 1, tokens, |b| lexer_operator!(b));
+                let spaces = run_length(chars, index, |c| c == ' ');
+                for _ in 0..spaces {
+                    index =
+                        self.push_range(chars, index, index + 1, tokens, |b| lexer_whitespace!(b));
+                }
+                if index < chars.len() && self.indents.last() != Some(&index) {
+                    self.indents.push(index);
+                }
+                self.expects_block = index >= chars.len();
+            }
+            self.tokenize_content(chars, index, tokens);
+        }
+
+        fn is_valid_indent(&mut self, chars: &[char], indent: usize) -> bool {
+            if chars[..indent].contains(&'\t') {
+                return false;
+            }
+            let top = *self.indents.last().unwrap_or(&0);
+            if indent > top {
+                if self.expects_block {
+                    self.indents.push(indent);
+                    return true;
+                }
+                return !has_key(&chars[indent..]);
+            }
+            while self.indents.last().map_or(false, |last| *last > indent) {
+                self.indents.pop();
+            }
+            match self.indents.last() {
+                Some(last) if *last == indent => true,
+                None if indent == 0 => {
+                    self.indents.push(0);
+                    true
+                }
+                _ => false,
+            }
+        }
+
+        fn tokenize_content(&mut self, chars: &[char], from: usize, tokens: &mut Vec<TokenType>) {
+            let mut index = from;
+            let mut last_separator = false;
+            while index < chars.len() {
+                let c = chars[index];
+                let previous_white = index == 0 || chars[index - 1].is_whitespace();
+                let next_white = chars.get(index + 1).map_or(true, |c| c.is_whitespace());
+                index = match c {
+                    ' ' | '\t' | '\r' => {
+                        self.push_range(chars, index, index + 1, tokens, |b| lexer_whitespace!(b))
+                    }
+                    '#' if previous_white => {
+                        self.push_range(chars, index, chars.len(), tokens, |b| lexer_comment!(b))
+                    }
+                    '"' | '\'' => {
+                        last_separator = false;
+                        self.tokenize_quoted(chars, index, tokens)
+                    }
+                    '{' | '[' => {
+                        self.flow_depth += 1;
+                        self.push_range(chars, index, index + 1, tokens, |b| lexer_separator!(b))
+                    }
+                    '}' | ']' if self.flow_depth > 0 => {
+                        self.flow_depth -= 1;
+                        self.push_range(chars, index, index + 1, tokens, |b| lexer_separator!(b))
+                    }
+                    ',' if self.flow_depth > 0 => {
+                        self.push_range(chars, index, index + 1, tokens, |b| lexer_separator!(b))
+                    }
+                    ':' if next_white || self.flow_depth > 0 => {
+                        last_separator = true;
+                        self.push_range(chars, index, index + 1, tokens, |b| lexer_separator!(b))
+                    }
+                    '&' | '*' => {
+                        let len = run_length(chars, index, |c| !is_flow_break(c));
+                        self.push_range(chars, index, index + len, tokens, |b| lexer_operator!(b))
+                    }
+                    '!' => {
+                        let len = run_length(chars, index, |c| !is_flow_break(c));
+                        self.push_range(chars, index, index + len, tokens, |b| lexer_keyword!(b))
+                    }
+                    '|' | '>' if self.flow_depth == 0 => {
+                        self.block_scalar = Some(*self.indents.last().unwrap_or(&0));
+                        let len = run_length(chars, index, |c| {
+                            c == '|' || c == '>' || c == '+' || c == '-' || c.is_ascii_digit()
+                        });
+                        self.push_range(chars, index, index + len, tokens, |b| lexer_operator!(b))
+                    }
+                    _ => {
+                        last_separator = false;
+                        self.tokenize_plain(chars, index, tokens)
+                    }
+                };
+            }
+            if last_separator && self.flow_depth == 0 {
+                self.expects_block = true;
+            }
+        }
+
+        /// Quoted scalar not closed in its line continues in lines below when they close it
+        fn tokenize_quoted(
+            &mut self,
+            chars: &[char],
+            from: usize,
+            tokens: &mut Vec<TokenType>,
+        ) -> usize {
+            let quote = chars[from];
+            if let Some(end) = closing_quote(chars, from + 1, quote) {
+                return if is_key_end(chars, end, self.flow_depth) {
+                    self.push_range(chars, from, end, tokens, |b| lexer_identifier!(b))
+                } else {
+                    self.push_range(chars, from, end, tokens, |b| lexer_string!(b))
+                };
+            }
+            let line_rest: usize = chars[from..].iter().map(|c| c.len_utf8()).sum();
+            let rest: Vec<char> = self.content[self.start + line_rest..].chars().collect();
+            if closing_quote(&rest, 0, quote).is_none() {
+                return self.push_range(chars, from, chars.len(), tokens, |b| lexer_error!(b));
+            }
+            self.open_quote = Some(quote);
+            self.push_range(chars, from, chars.len(), tokens, |b| lexer_string!(b))
+        }
+
+        /// Line of quoted scalar opened in one of lines above
+        fn continue_quoted(
+            &mut self,
+            chars: &[char],
+            from: usize,
+            quote: char,
+            tokens: &mut Vec<TokenType>,
+        ) -> usize {
+            let end = match closing_quote(chars, from, quote) {
+                Some(end) => {
+                    self.open_quote = None;
+                    end
+                }
+                None => chars.len(),
+            };
+            self.push_range(chars, from, end, tokens, |b| lexer_string!(b))
+        }
+
+        fn tokenize_plain(
+            &mut self,
+            chars: &[char],
+            from: usize,
+            tokens: &mut Vec<TokenType>,
+        ) -> usize {
+            let mut end = from + 1;
+            while end < chars.len() {
+                let c = chars[end];
+                let next_white = chars.get(end + 1).map_or(true, |c| c.is_whitespace());
+                if (c == ':' && (next_white || self.flow_depth > 0))
+                    || (c == '#' && chars[end - 1].is_whitespace())
+                    || (self.flow_depth > 0 && is_flow_indicator(c))
+                {
+                    break;
+                }
+                end += 1;
+            }
+            while end > from + 1 && chars[end - 1].is_whitespace() {
+                end -= 1;
+            }
+            let word: String = chars[from..end].iter().collect();
+            if is_key_end(chars, end, self.flow_depth) {
+                self.push_range(chars, from, end, tokens, |b| lexer_identifier!(b))
+            } else if is_number(&word) {
+                self.push_range(chars, from, end, tokens, |b| lexer_literal!(b))
+            } else if is_constant(&word) {
+                self.push_range(chars, from, end, tokens, |b| lexer_keyword!(b))
+            } else {
+                self.push_range(chars, from, end, tokens, |b| lexer_string!(b))
+            }
+        }
+
+        fn push_range<F>(
+            &mut self,
+            chars: &[char],
+            from: usize,
+            to: usize,
+            tokens: &mut Vec<TokenType>,
+            builder: F,
+        ) -> usize
+        where
+            F: Fn(&Lexer) -> TokenType,
+        {
+            if from < to {
+                self.buffer = chars[from..to].iter().collect();
+                tokens.push(builder(&self));
+                self.clear();
+            }
+            to
+        }
+
+        fn clear(&mut self) {
+            for c in self.buffer.chars() {
+                if c == '\n' {
+                    self.line += 1;
+                    self.character = 0;
+                } else {
+                    self.character += 1;
+                }
+            }
+            self.start += self.buffer.len();
+            self.char_start += self.buffer.chars().count();
+            self.buffer.clear();
+        }
+    }
+
+    fn run_length<F>(chars: &[char], from: usize, f: F) -> usize
+    where
+        F: Fn(char) -> bool,
+    {
+        chars[from..].iter().take_while(|c| f(**c)).count()
+    }
+
+    /// Index after quote closing scalar which continues at given index
+    fn closing_quote(chars: &[char], from: usize, quote: char) -> Option<usize> {
+        let mut index = from;
+        while index < chars.len() {
+            match chars[index] {
+                '\\' if quote == '"' => index += 2,
+                '\'' if quote == '\'' && chars.get(index + 1) == Some(&'\'') => index += 2,
+                c if c == quote => return Some(index + 1),
+                _ => index += 1,
+            }
+        }
+        None
+    }
+
+    fn is_flow_indicator(c: char) -> bool {
+        c == ',' || c == '[' || c == ']' || c == '{' || c == '}'
+    }
+
+    fn is_flow_break(c: char) -> bool {
+        c.is_whitespace() || is_flow_indicator(c)
+    }
+
+    fn is_document_marker(chars: &[char]) -> bool {
+        (chars.starts_with(&['-', '-', '-']) || chars.starts_with(&['.', '.', '.']))
+            && chars.get(3).map_or(true, |c| c.is_whitespace())
+    }
+
+    fn is_list_marker(chars: &[char], index: usize) -> bool {
+        chars.get(index) == Some(&'-') && chars.get(index + 1).map_or(true, |c| c.is_whitespace())
+    }
+
+    fn is_key_end(chars: &[char], end: usize, flow_depth: usize) -> bool {
+        let next = end + run_length(chars, end, |c| c == ' ' || c == '\t');
+        chars.get(next) == Some(&':')
+            && (flow_depth > 0 || chars.get(next + 1).map_or(true, |c| c.is_whitespace()))
+    }
+
+    fn has_key(chars: &[char]) -> bool {
+        let mut index = 0;
+        while is_list_marker(chars, index) {
+            index += 1 + run_length(chars, index + 1, |c| c == ' ');
+        }
+        (index..chars.len()).any(|i| {
+            chars[i] == ':'
+                && chars.get(i + 1).map_or(true, |c| c.is_whitespace())
+                && !chars[index..i].contains(&'#')
+        })
+    }
+
+    fn is_number(word: &str) -> bool {
+        let word = word.trim_start_matches(|c| c == '-' || c == '+');
+        !word.is_empty()
+            && (word.parse::<f64>().is_ok()
+                || word.starts_with("0x") && i64::from_str_radix(&word[2..], 16).is_ok()
+                || word.starts_with("0o") && i64::from_str_radix(&word[2..], 8).is_ok())
+            && !word.eq_ignore_ascii_case("inf")
+            && !word.eq_ignore_ascii_case("nan")
+            && !word.eq_ignore_ascii_case("infinity")
+    }
+
+    fn is_constant(word: &str) -> bool {
+        match word {
+            "true" | "True" | "TRUE" | "false" | "False" | "FALSE" | "null" | "Null" | "NULL"
+            | "~" | ".inf" | "-.inf" | ".nan" => true,
+            _ => false,
+        }
+    }
+
+    impl TokenBuilder for Lexer {
+        fn text(&self) -> String {
+            self.buffer.to_string()
+        }
+
+        fn line(&self) -> usize {
+            self.line
+        }
+
+        fn character(&self) -> usize {
+            self.character
+        }
+
+        fn start(&self) -> usize {
+            self.start
+        }
+
+        fn end(&self, text: &String) -> usize {
+            self.start + text.len()
+        }
+
+        fn char_start(&self) -> usize {
+            self.char_start
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        struct BuilderMock {
+            line: usize,
+            character: usize,
+            start: usize,
+            char_start: usize,
+            text: String,
+        }
+
+        impl TokenBuilder for BuilderMock {
+            fn text(&self) -> String {
+                self.text.clone()
+            }
+
+            fn line(&self) -> usize {
+                self.line
+            }
+
+            fn character(&self) -> usize {
+                self.character
+            }
+
+            fn start(&self) -> usize {
+                self.start
+            }
+
+            fn end(&self, current_text: &String) -> usize {
+                self.start + current_text.len()
+            }
+
+            fn char_start(&self) -> usize {
+                self.char_start
+            }
+        }
+
+        macro_rules! builder {
+            ($text: expr, $line: expr, $character: expr, $start: expr) => {
+                BuilderMock {
+                    line: $line,
+                    character: $character,
+                    start: $start,
+                    char_start: $start,
+                    text: $text.to_owned(),
+                }
+            };
+        }
+
+        #[test]
+        fn parse_empty() {
+            let code = "".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_key_value() {
+            let code = "name: build # ci".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_identifier!(builder!("name", 0, 0, 0)),
+                lexer_separator!(builder!(":", 0, 4, 4)),
+                lexer_whitespace!(builder!(" ", 0, 5, 5)),
+                lexer_string!(builder!("build", 0, 6, 6)),
+                lexer_whitespace!(builder!(" ", 0, 11, 11)),
+                lexer_comment!(builder!("# ci", 0, 12, 12)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_literals() {
+            let code = "- 12\n- true\n- 'a'".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_operator!(builder!("-", 0, 0, 0)),
+                lexer_whitespace!(builder!(" ", 0, 1, 1)),
+                lexer_literal!(builder!("12", 0, 2, 2)),
+                lexer_whitespace!(builder!("\n", 0, 4, 4)),
+                lexer_operator!(builder!("-", 1, 0, 5)),
+                lexer_whitespace!(builder!(" ", 1, 1, 6)),
+                lexer_keyword!(builder!("true", 1, 2, 7)),
+                lexer_whitespace!(builder!("\n", 1, 6, 11)),
+                lexer_operator!(builder!("-", 2, 0, 12)),
+                lexer_whitespace!(builder!(" ", 2, 1, 13)),
+                lexer_string!(builder!("'a'", 2, 2, 14)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_nested_mapping() {
+            let code = "a:\n  b: 1".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_identifier!(builder!("a", 0, 0, 0)),
+                lexer_separator!(builder!(":", 0, 1, 1)),
+                lexer_whitespace!(builder!("\n", 0, 2, 2)),
+                lexer_whitespace!(builder!(" ", 1, 0, 3)),
+                lexer_whitespace!(builder!(" ", 1, 1, 4)),
+                lexer_identifier!(builder!("b", 1, 2, 5)),
+                lexer_separator!(builder!(":", 1, 3, 6)),
+                lexer_whitespace!(builder!(" ", 1, 4, 7)),
+                lexer_literal!(builder!("1", 1, 5, 8)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_bad_indentation() {
+            let code = "a: 1\n  b: 2".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_identifier!(builder!("a", 0, 0, 0)),
+                lexer_separator!(builder!(":", 0, 1, 1)),
+                lexer_whitespace!(builder!(" ", 0, 2, 2)),
+                lexer_literal!(builder!("1", 0, 3, 3)),
+                lexer_whitespace!(builder!("\n", 0, 4, 4)),
+                lexer_whitespace!(builder!(" ", 1, 0, 5)),
+                lexer_whitespace!(builder!(" ", 1, 1, 6)),
+                lexer_error!(builder!("b: 2", 1, 2, 7)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_bad_dedent() {
+            let code = "a:\n    b: 1\n  c: 2".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            assert_eq!(
+                result.last(),
+                Some(&lexer_error!(builder!("c: 2", 2, 2, 14)))
+            );
+        }
+
+        #[test]
+        fn parse_unterminated_string() {
+            let code = "a: \"b".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_identifier!(builder!("a", 0, 0, 0)),
+                lexer_separator!(builder!(":", 0, 1, 1)),
+                lexer_whitespace!(builder!(" ", 0, 2, 2)),
+                lexer_error!(builder!("\"b", 0, 3, 3)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_multi_line_quoted_scalar() {
+            let code = "d: \"this is\n  continued\" # x\ne: 1".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_identifier!(builder!("d", 0, 0, 0)),
+                lexer_separator!(builder!(":", 0, 1, 1)),
+                lexer_whitespace!(builder!(" ", 0, 2, 2)),
+                lexer_string!(builder!("\"this is", 0, 3, 3)),
+                lexer_whitespace!(builder!("\n", 0, 11, 11)),
+                lexer_whitespace!(builder!(" ", 1, 0, 12)),
+                lexer_whitespace!(builder!(" ", 1, 1, 13)),
+                lexer_string!(builder!("continued\"", 1, 2, 14)),
+                lexer_whitespace!(builder!(" ", 1, 12, 24)),
+                lexer_comment!(builder!("# x", 1, 13, 25)),
+                lexer_whitespace!(builder!("\n", 1, 16, 28)),
+                lexer_identifier!(builder!("e", 2, 0, 29)),
+                lexer_separator!(builder!(":", 2, 1, 30)),
+                lexer_whitespace!(builder!(" ", 2, 2, 31)),
+                lexer_literal!(builder!("1", 2, 3, 32)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_block_scalar() {
+            let code = "run: |\n  cargo test\nb: 1".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_identifier!(builder!("run", 0, 0, 0)),
+                lexer_separator!(builder!(":", 0, 3, 3)),
+                lexer_whitespace!(builder!(" ", 0, 4, 4)),
+                lexer_operator!(builder!("|", 0, 5, 5)),
+                lexer_whitespace!(builder!("\n", 0, 6, 6)),
+                lexer_whitespace!(builder!(" ", 1, 0, 7)),
+                lexer_whitespace!(builder!(" ", 1, 1, 8)),
+                lexer_string!(builder!("cargo test", 1, 2, 9)),
+                lexer_whitespace!(builder!("\n", 1, 12, 19)),
+                lexer_identifier!(builder!("b", 2, 0, 20)),
+                lexer_separator!(builder!(":", 2, 1, 21)),
+                lexer_whitespace!(builder!(" ", 2, 2, 22)),
+                lexer_literal!(builder!("1", 2, 3, 23)),
+            ];
+            assert_eq!(result, expected)
+        }
+
+        #[test]
+        fn parse_flow_mapping() {
+            let code = "{a: [1, x]}".to_owned();
+            let mut lexer = Lexer::new(code);
+            let result = lexer.tokenize();
+            let expected = vec![
+                lexer_separator!(builder!("{", 0, 0, 0)),
+                lexer_identifier!(builder!("a", 0, 1, 1)),
+                lexer_separator!(builder!(":", 0, 2, 2)),
+                lexer_whitespace!(builder!(" ", 0, 3, 3)),
+                lexer_separator!(builder!("[", 0, 4, 4)),
+                lexer_literal!(builder!("1", 0, 5, 5)),
+                lexer_separator!(builder!(",", 0, 6, 6)),
+                lexer_whitespace!(builder!(" ", 0, 7, 7)),
+                lexer_string!(builder!("x", 0, 8, 8)),
+                lexer_separator!(builder!("]", 0, 9, 9)),
+                lexer_separator!(builder!("}", 0, 10, 10)),
+            ];
+            assert_eq!(result, expected)
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! Scripted stand-in for a language server used by `rider-editor` tests.
+//!
+//! First argument is a path to JSON script:
+//!
+//! ```json
+//! {
+//!   "responses": { "textDocument/hover": { "contents": "fn main()" } },
+//!   "diagnostics": [{ "range": { ... }, "severity": 1, "message": "..." }],
+//!   "echoText": true
+//! }
+//! ```
+//!
+//! Requests are answered with result from `responses` or `null`. After every
+//! `didOpen` and `didChange` scripted diagnostics are published, with `echoText`
+//! also one containing whole synced document so client can verify incremental sync.
+extern crate serde_json;
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+
+#[cfg_attr(tarpaulin, skip)]
+fn main() -> io::Result<()> {
+    let script: Value = std::env::args()
+        .nth(1)
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_else(|| json!({}));
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let stdout = io::stdout();
+    let mut output = stdout.lock();
+    let mut documents: HashMap<String, String> = HashMap::new();
+
+    while let Some(message) = read_message(&mut input)? {
+        let method = message["method"].as_str().unwrap_or_default().to_owned();
+        let params = &message["params"];
+        match method.as_str() {
+            "exit" => break,
+            "textDocument/didOpen" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                documents.insert(uri.to_owned(), text.to_owned());
+                publish_diagnostics(&mut output, &script, uri, text)?;
+            }
+            "textDocument/didChange" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                let text = documents.entry(uri.to_owned()).or_insert_with(String::new);
+                for change in params["contentChanges"].as_array().unwrap_or(&vec![]) {
+                    *text = apply_change(text, change);
+                }
+                publish_diagnostics(&mut output, &script, uri, text)?;
+            }
+            _ if message.get("id").is_some() => {
+                let result = match method.as_str() {
+                    "initialize" => script["responses"]["initialize"].clone(),
+                    _ => script["responses"][method.as_str()].clone(),
+                };
+                let result = match (method.as_str(), result) {
+                    ("initialize", Value::Null) => {
+                        json!({ "capabilities": { "textDocumentSync": 2 } })
+                    }
+                    (_, result) => result,
+                };
+                write_message(
+                    &mut output,
+                    &json!({ "jsonrpc": "2.0", "id": message["id"], "result": result }),
+                )?;
+            }
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+fn read_message<R: BufRead>(input: &mut R) -> io::Result<Option<Value>> {
+    let mut length = 0;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim();
+        if line.is_empty() {
+            break;
+        }
+        if line.starts_with("Content-Length:") {
+            length = line["Content-Length:".len()..].trim().parse().unwrap_or(0);
+        }
+    }
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    Ok(serde_json::from_slice(&body).ok())
+}
+
+fn write_message<W: Write>(output: &mut W, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
+
+fn publish_diagnostics<W: Write>(
+    output: &mut W,
+    script: &Value,
+    uri: &str,
+    text: &str,
+) -> io::Result<()> {
+    let mut diagnostics = script["diagnostics"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    if script["echoText"].as_bool().unwrap_or(false) {
+        diagnostics.push(json!({
+            "range": { "start": { "line": 0, "character": 0 }, "end": { "line": 0, "character": 0 } },
+            "severity": 4,
+            "message": text,
+        }));
+    }
+    write_message(
+        output,
+        &json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": uri, "diagnostics": diagnostics },
+        }),
+    )
+}
+
+/// Applies `TextDocumentContentChangeEvent`, change without range replaces whole text
+fn apply_change(text: &str, change: &Value) -> String {
+    let new_text = change["text"].as_str().unwrap_or_default();
+    let range = &change["range"];
+    if range.is_null() {
+        return new_text.to_owned();
+    }
+    let start = offset_at(text, &range["start"]);
+    let end = offset_at(text, &range["end"]);
+    format!("{}{}{}", &text[..start], new_text, &text[end..])
+}
+
+/// Byte offset of position, `character` counts UTF-16 code units
+fn offset_at(text: &str, position: &Value) -> usize {
+    let line = position["line"].as_u64().unwrap_or(0) as usize;
+    let character = position["character"].as_u64().unwrap_or(0) as usize;
+    let line_start = match line {
+        0 => 0,
+        _ => text
+            .match_indices('\n')
+            .nth(line - 1)
+            .map_or(text.len(), |(index, _)| index + 1),
+    };
+    let mut units = 0;
+    for (index, c) in text[line_start..].char_indices() {
+        if units >= character || c == '\n' {
+            return line_start + index;
+        }
+        units += c.len_utf16();
+    }
+    text.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn must_apply_incremental_change() {
+        let change = json!({
+            "range": { "start": { "line": 1, "character": 1 }, "end": { "line": 1, "character": 3 } },
+            "text": "é"
+        });
+        assert_eq!(apply_change("fn\nabcd\n", &change), "fn\naéd\n".to_owned());
+        assert_eq!(apply_change("a", &json!({ "text": "b" })), "b".to_owned());
+    }
+}
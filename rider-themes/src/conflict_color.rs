@@ -0,0 +1,37 @@
+use crate::SerdeColor;
+use crate::ThemeConfig;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct ConflictColor {
+    pub ours: ThemeConfig,
+    pub theirs: ThemeConfig,
+    pub base: ThemeConfig,
+    pub marker: ThemeConfig,
+}
+
+impl Default for ConflictColor {
+    fn default() -> Self {
+        Self {
+            ours: ThemeConfig::new(SerdeColor::new(215, 240, 215, 0), false, false),
+            theirs: ThemeConfig::new(SerdeColor::new(215, 225, 250, 0), false, false),
+            base: ThemeConfig::new(SerdeColor::new(235, 235, 235, 0), false, false),
+            marker: ThemeConfig::new(SerdeColor::new(200, 200, 200, 0), false, false),
+        }
+    }
+}
+
+impl ConflictColor {
+    pub fn new(
+        ours: ThemeConfig,
+        theirs: ThemeConfig,
+        base: ThemeConfig,
+        marker: ThemeConfig,
+    ) -> Self {
+        Self {
+            ours,
+            theirs,
+            base,
+            marker,
+        }
+    }
+}
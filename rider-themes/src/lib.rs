@@ -5,6 +5,7 @@ extern crate serde_derive;
 
 pub mod caret_color;
 pub mod code_highlighting_color;
+pub mod conflict_color;
 pub mod diff_color;
 pub mod images;
 pub mod predef;
@@ -14,6 +15,7 @@ pub mod theme_config;
 
 pub use crate::caret_color::CaretColor;
 pub use crate::code_highlighting_color::CodeHighlightingColor;
+pub use crate::conflict_color::ConflictColor;
 pub use crate::diff_color::DiffColor;
 pub use crate::images::ThemeImages;
 pub use crate::serde_color::SerdeColor;
@@ -1,5 +1,6 @@
 use crate::caret_color::CaretColor;
 use crate::CodeHighlightingColor;
+use crate::ConflictColor;
 use crate::DiffColor;
 use crate::SerdeColor;
 use crate::Theme;
@@ -41,6 +42,12 @@ pub fn build_theme() -> Theme {
             ThemeConfig::new(SerdeColor::new(135, 0, 135, 0), false, false),
             ThemeConfig::new(SerdeColor::new(18, 18, 18, 0), false, false),
         ),
+        ConflictColor::new(
+            ThemeConfig::new(SerdeColor::new(30, 60, 30, 0), false, false),
+            ThemeConfig::new(SerdeColor::new(30, 40, 75, 0), false, false),
+            ThemeConfig::new(SerdeColor::new(45, 45, 45, 0), false, false),
+            ThemeConfig::new(SerdeColor::new(80, 80, 80, 0), false, false),
+        ),
         ThemeImages::new(
             "railscasts/images/directory-64x64.png".to_owned(),
             "railscasts/images/file-64x64.png".to_owned(),
@@ -1,5 +1,6 @@
 use crate::CaretColor;
 use crate::CodeHighlightingColor;
+use crate::ConflictColor;
 use crate::DiffColor;
 use crate::SerdeColor;
 use crate::ThemeImages;
@@ -12,6 +13,8 @@ pub struct Theme {
     caret: CaretColor,
     code_highlighting: CodeHighlightingColor,
     diff: DiffColor,
+    #[serde(default)]
+    conflict: ConflictColor,
     images: ThemeImages,
 }
 
@@ -24,6 +27,7 @@ impl Default for Theme {
             caret: CaretColor::default(),
             code_highlighting: CodeHighlightingColor::default(),
             diff: DiffColor::default(),
+            conflict: ConflictColor::default(),
             images: ThemeImages::default(),
         }
     }
@@ -37,6 +41,7 @@ impl Theme {
         caret: CaretColor,
         code_highlighting: CodeHighlightingColor,
         diff: DiffColor,
+        conflict: ConflictColor,
         images: ThemeImages,
     ) -> Self {
         Self {
@@ -46,6 +51,7 @@ impl Theme {
             caret,
             code_highlighting,
             diff,
+            conflict,
             images,
         }
     }
@@ -70,6 +76,10 @@ impl Theme {
         &self.diff
     }
 
+    pub fn conflict(&self) -> &ConflictColor {
+        &self.conflict
+    }
+
     pub fn code_highlighting(&self) -> &CodeHighlightingColor {
         &self.code_highlighting
     }
@@ -123,6 +133,14 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn assert_conflict() {
+        let target = Theme::default();
+        let result = target.conflict().clone();
+        let expected = ConflictColor::default();
+        assert_eq!(result, expected);
+    }
+
     #[test]
     fn assert_code_highlighting() {
         let target = Theme::default();
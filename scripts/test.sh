@@ -4,4 +4,5 @@ cargo test -p rider-generator
 cargo test -p rider-config
 cargo test -p rider-themes
 cargo test -p rider-lexers
+cargo test -p rider-lsp-stub
 cargo test -p rider-editor